@@ -1,6 +1,7 @@
 //! Various utilities for the Forcerelay CLI
 
 use alloc::sync::Arc;
+use ckb_sdk::NetworkType;
 use eyre::eyre;
 use tokio::runtime::Runtime as TokioRuntime;
 use tracing::debug;
@@ -10,10 +11,11 @@ use ibc_relayer::chain::requests::{
 };
 use ibc_relayer::{
     chain::{
+        ckb::{rpc_client::RpcClient, utils::fetch_network},
         counterparty::{channel_connection_client, ChannelConnectionClient},
         handle::{BaseChainHandle, ChainHandle},
     },
-    config::Config,
+    config::{ChainConfig, Config},
     spawn,
 };
 use ibc_relayer_types::core::ics02_client::client_state::ClientState;
@@ -77,6 +79,24 @@ pub fn spawn_chain_runtime_generic<Handle: ChainHandle>(
     spawn::spawn_chain_runtime(config, chain_id, rt).map_err(Error::spawn)
 }
 
+/// Queries the node of a CKB chain for its network, which the CKB addresses are encoded for.
+pub fn ckb_network(config: &ChainConfig) -> Result<NetworkType, eyre::Report> {
+    let rpc_client = match config {
+        ChainConfig::Ckb(config) => RpcClient::new(&config.ckb_rpc, &config.ckb_indexer_rpc),
+        ChainConfig::Ckb4Ibc(config) => {
+            RpcClient::new(&config.ckb_rpc, &config.ckb_indexer_rpc).with_rpc_mode(config.rpc_mode)
+        }
+        _ => return Err(eyre!("chain '{}' is not a CKB chain", config.id())),
+    };
+    let rt = TokioRuntime::new()?;
+    rt.block_on(fetch_network(&rpc_client)).map_err(|e| {
+        eyre!(
+            "failed to query the network of chain '{}': {e}",
+            config.id()
+        )
+    })
+}
+
 /// Spawns a chain runtime for specified chain identifier, queries the counterparty chain associated
 /// with specified port and channel id, and spawns a chain runtime for the counterparty chain.
 ///
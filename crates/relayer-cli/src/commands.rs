@@ -1,16 +1,24 @@
 //! Definition of all the Forcerelay subcommands
 
+mod attest;
+mod audit;
+mod bench;
+mod ckb;
 mod clear;
 mod completions;
 mod config;
 mod create;
+mod dev;
+mod export;
 mod fee;
 mod forcerelay;
 mod health;
 mod keys;
 mod listen;
 mod misbehaviour;
+mod pause;
 mod query;
+mod recover;
 mod start;
 mod tx;
 mod update;
@@ -18,9 +26,11 @@ mod upgrade;
 mod version;
 
 use self::{
-    clear::ClearCmds, completions::CompletionsCmd, config::ConfigCmd, create::CreateCmds,
-    fee::FeeCmd, forcerelay::EthCkbCmd, health::HealthCheckCmd, keys::KeysCmd, listen::ListenCmd,
-    misbehaviour::MisbehaviourCmd, query::QueryCmd, start::StartCmd, tx::TxCmd, update::UpdateCmds,
+    attest::AttestCmd, audit::AuditCmd, bench::BenchCmd, ckb::CkbCmd, clear::ClearCmds,
+    completions::CompletionsCmd, config::ConfigCmd, create::CreateCmds, dev::DevCmd,
+    export::ExportCmd, fee::FeeCmd, forcerelay::EthCkbCmd, health::HealthCheckCmd, keys::KeysCmd,
+    listen::ListenCmd, misbehaviour::MisbehaviourCmd, pause::PauseCmd, pause::ResumeCmd,
+    query::QueryCmd, recover::RecoverCmds, start::StartCmd, tx::TxCmd, update::UpdateCmds,
     upgrade::UpgradeCmds, version::VersionCmd,
 };
 
@@ -66,6 +76,10 @@ pub enum CliCmd {
     #[clap(subcommand)]
     Clear(ClearCmds),
 
+    /// Recover objects (paths) whose clients are frozen
+    #[clap(subcommand)]
+    Recover(RecoverCmds),
+
     /// Start the relayer in multi-chain mode.
     ///
     /// Relays packets and open handshake messages between all chains in the config.
@@ -89,12 +103,41 @@ pub enum CliCmd {
     /// Listen to client update IBC events and handles misbehaviour
     Misbehaviour(MisbehaviourCmd),
 
-    /// The `version` subcommand, retained for backward compatibility.
+    /// Print the version, and optionally the features Forcerelay is built with
     Version(VersionCmd),
 
     /// Performs a health check of all chains in the the config
     HealthCheck(HealthCheckCmd),
 
+    /// Produce a health attestation of a chain, signed with its relayer key
+    Attest(AttestCmd),
+
+    /// Audit the config against the states deployed on the chains
+    #[clap(subcommand)]
+    Audit(AuditCmd),
+
+    /// Pause the relaying of the running relayer for a chain or a channel
+    Pause(PauseCmd),
+
+    /// Resume the relaying of the running relayer for a paused chain or channel
+    Resume(ResumeCmd),
+
+    /// Development tooling, such as funding the relayer accounts on testnets
+    #[clap(subcommand)]
+    Dev(DevCmd),
+
+    /// Maintain the cells of CKB chains, such as pruning the obsolete light client cells
+    #[clap(subcommand)]
+    Ckb(CkbCmd),
+
+    /// Export the data of the running relayer, such as its archive of the packet and channel events
+    #[clap(subcommand)]
+    Export(ExportCmd),
+
+    /// Benchmark the relaying, such as the throughput of a channel
+    #[clap(subcommand)]
+    Bench(BenchCmd),
+
     /// Generate auto-complete scripts for different shells.
     #[clap(display_order = 1000)]
     Completions(CompletionsCmd),
@@ -0,0 +1,63 @@
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+use ibc_relayer::attestation::attest_health;
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+use crate::cli_utils::spawn_chain_runtime;
+use crate::conclude::{exit_with_unrecoverable_error, Output};
+use crate::error::Error;
+use crate::prelude::*;
+
+/// Produce a health attestation of a chain, signed with the relayer key of that chain.
+///
+/// The attestation reports the latest height of the chain and the latest height of
+/// every client it hosts, so that third parties can check it against on-chain data.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct AttestCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the chain to attest"
+    )]
+    chain_id: ChainId,
+}
+
+impl Runnable for AttestCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        let chain = spawn_chain_runtime(&config, &self.chain_id)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+
+        match attest_health(&chain).map_err(Error::relayer) {
+            Ok(attestation) => Output::success(attestation).exit(),
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AttestCmd;
+
+    use abscissa_core::clap::Parser;
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+    #[test]
+    fn test_attest() {
+        assert_eq!(
+            AttestCmd {
+                chain_id: ChainId::from_string("chain_id")
+            },
+            AttestCmd::parse_from(["test", "--chain", "chain_id"])
+        )
+    }
+
+    #[test]
+    fn test_attest_no_chain() {
+        assert!(AttestCmd::try_parse_from(["test"]).is_err())
+    }
+}
@@ -0,0 +1,12 @@
+//! `audit` subcommand
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+mod config;
+
+/// `audit` subcommand
+#[derive(Command, Debug, Parser, Runnable)]
+pub enum AuditCmd {
+    /// Compare the config fields which mirror on-chain states against the live chains
+    Config(config::AuditConfigCmd),
+}
@@ -0,0 +1,148 @@
+use std::fmt::Write;
+
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+use ibc_relayer::audit::ConfigAudit;
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+use crate::cli_utils::spawn_chain_runtime;
+use crate::conclude::{exit_with_unrecoverable_error, json, Output};
+use crate::error::Error;
+use crate::prelude::*;
+
+/// Compare the config fields which mirror on-chain states, such as the type args of the
+/// CKB contracts, the addresses of the EVM contracts, the client identifiers and the
+/// allowed channels, against the live chains.
+///
+/// Exits with an error status if any field drifted, e.g. after a redeployment.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct AuditConfigCmd {
+    #[clap(
+        long = "chain",
+        value_name = "CHAIN_ID",
+        help = "Identifier of the chain to audit, all the chains in the config if not given"
+    )]
+    chain_id: Option<ChainId>,
+}
+
+// hermes audit config --chain ckb4ibc-0
+impl Runnable for AuditConfigCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        let chain_configs = match &self.chain_id {
+            Some(chain_id) => {
+                let chain_config = config.find_chain(chain_id).cloned().unwrap_or_else(|| {
+                    Output::error(format!(
+                        "chain '{chain_id}' not found in configuration file"
+                    ))
+                    .exit()
+                });
+                vec![chain_config]
+            }
+            None => config.chains.clone(),
+        };
+
+        let mut audits = vec![];
+        for chain_config in chain_configs {
+            let chain = spawn_chain_runtime(&config, chain_config.id())
+                .unwrap_or_else(exit_with_unrecoverable_error);
+            let audit = ConfigAudit::collect(&chain, &chain_config)
+                .map_err(Error::relayer)
+                .unwrap_or_else(exit_with_unrecoverable_error);
+            audits.push(audit);
+        }
+
+        let output = if audits.iter().any(ConfigAudit::has_drifted) {
+            Output::with_error()
+        } else {
+            Output::with_success()
+        };
+        if json() {
+            output.with_result(audits).exit()
+        } else {
+            output.with_msg(drift_report(&audits)).exit()
+        }
+    }
+}
+
+/// Human-readable report of the drifted fields of every audited chain
+fn drift_report(audits: &[ConfigAudit]) -> String {
+    let mut report = String::new();
+    for audit in audits {
+        if !audit.has_drifted() {
+            let _ = write!(report, "\n{}: no drift", audit.chain_id);
+            continue;
+        }
+
+        let _ = write!(
+            report,
+            "\n{}: {} drifted field(s)",
+            audit.chain_id,
+            audit.drifts.len()
+        );
+        for drift in &audit.drifts {
+            let actual = drift.actual.as_deref().unwrap_or("not found on chain");
+            let _ = write!(
+                report,
+                "\n  - {} = {} ({actual})\n    suggestion: {}",
+                drift.field, drift.configured, drift.suggestion
+            );
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{drift_report, AuditConfigCmd};
+
+    use abscissa_core::clap::Parser;
+    use ibc_relayer::audit::{ConfigAudit, ConfigDrift};
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+    #[test]
+    fn test_audit_config() {
+        assert_eq!(
+            AuditConfigCmd {
+                chain_id: Some(ChainId::from_string("chain_id"))
+            },
+            AuditConfigCmd::parse_from(["test", "--chain", "chain_id"])
+        )
+    }
+
+    #[test]
+    fn test_audit_config_all_chains() {
+        assert_eq!(
+            AuditConfigCmd { chain_id: None },
+            AuditConfigCmd::parse_from(["test"])
+        )
+    }
+
+    #[test]
+    fn test_drift_report() {
+        let audits = [
+            ConfigAudit {
+                chain_id: ChainId::from_string("ckb4ibc-0"),
+                drifts: vec![ConfigDrift::new(
+                    "packet_type_args",
+                    "0x01",
+                    None,
+                    "redeployed",
+                )],
+            },
+            ConfigAudit {
+                chain_id: ChainId::from_string("ibc-0"),
+                drifts: vec![],
+            },
+        ];
+        assert_eq!(
+            drift_report(&audits),
+            "\nckb4ibc-0: 1 drifted field(s)\
+             \n  - packet_type_args = 0x01 (not found on chain)\
+             \n    suggestion: redeployed\
+             \nibc-0: no drift"
+        );
+    }
+}
@@ -0,0 +1,12 @@
+//! `bench` subcommand
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+mod channel;
+
+/// `bench` subcommand
+#[derive(Command, Debug, Parser, Runnable)]
+pub enum BenchCmd {
+    /// Measure the throughput and the latency of the relaying of a channel
+    Channel(channel::BenchChannelCmd),
+}
@@ -0,0 +1,419 @@
+use core::time::Duration;
+use std::collections::{BTreeMap, HashSet};
+use std::thread;
+use std::time::Instant;
+
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+use eyre::eyre;
+use serde::Serialize;
+
+use ibc_relayer::chain::handle::ChainHandle;
+use ibc_relayer::chain::requests::{
+    IncludeProof, QueryChannelRequest, QueryHeight, QueryUnreceivedPacketsRequest,
+};
+use ibc_relayer::transfer::{build_transfer_messages, send_messages, TransferOptions};
+use ibc_relayer_types::applications::transfer::Amount;
+use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, PortId};
+use ibc_relayer_types::events::IbcEvent;
+
+use crate::cli_utils::{check_can_send_on_channel, ChainHandlePair};
+use crate::conclude::{exit_with_unrecoverable_error, Output};
+use crate::prelude::*;
+
+/// Interval between two batches of packets
+const BATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Interval between the queries of the packets received by the destination chain
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Timeout of the synthetic packets, long enough for them to be relayed before
+const PACKET_TIMEOUT: Duration = Duration::from_secs(3600);
+
+/// Measure the end-to-end throughput and latency of the relaying of a channel.
+///
+/// The command sends batches of `rate` ICS-20 transfers of `amount` every second over the
+/// channel, for `duration`, and watches the destination chain until the packets are
+/// received or `drain-timeout` elapses. The packets are relayed by a relayer running
+/// separately, the command only originates and tracks them. It is meant for dev chains,
+/// on which the relayer account can afford the transfers.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct BenchChannelCmd {
+    #[clap(
+        long = "src-chain",
+        required = true,
+        value_name = "SRC_CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the chain sending the packets"
+    )]
+    src_chain_id: ChainId,
+
+    #[clap(
+        long = "dst-chain",
+        required = true,
+        value_name = "DST_CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the chain receiving the packets"
+    )]
+    dst_chain_id: ChainId,
+
+    #[clap(
+        long = "src-port",
+        value_name = "SRC_PORT_ID",
+        default_value = "transfer",
+        help = "Identifier of the source port"
+    )]
+    src_port_id: PortId,
+
+    #[clap(
+        long = "src-channel",
+        visible_alias = "src-chan",
+        required = true,
+        value_name = "SRC_CHANNEL_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the source channel"
+    )]
+    src_channel_id: ChannelId,
+
+    #[clap(
+        long = "rate",
+        required = true,
+        value_name = "RATE",
+        help_heading = "REQUIRED",
+        help = "Number of packets sent every second"
+    )]
+    rate: usize,
+
+    #[clap(
+        long = "duration",
+        value_name = "DURATION",
+        default_value = "1m",
+        help = "How long to send packets for"
+    )]
+    duration: humantime::Duration,
+
+    #[clap(
+        long = "drain-timeout",
+        value_name = "DRAIN_TIMEOUT",
+        default_value = "5m",
+        help = "How long to wait for the packets to be relayed once all of them are sent"
+    )]
+    drain_timeout: humantime::Duration,
+
+    #[clap(
+        long = "amount",
+        value_name = "AMOUNT",
+        default_value = "1",
+        help = "Amount of coins transferred by each packet"
+    )]
+    amount: Amount,
+
+    #[clap(
+        long = "denom",
+        value_name = "DENOM",
+        default_value = "samoleans",
+        help = "Denomination of the coins transferred"
+    )]
+    denom: String,
+}
+
+/// Distribution of the delays between the commitment of the packets on the source
+/// chain and their reception on the destination chain, in milliseconds
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+struct LatencySummary {
+    min: u64,
+    mean: u64,
+    p50: u64,
+    p90: u64,
+    p99: u64,
+    max: u64,
+}
+
+impl LatencySummary {
+    fn new(latencies: &[Duration]) -> Option<Self> {
+        let mut millis = latencies
+            .iter()
+            .map(|latency| latency.as_millis() as u64)
+            .collect::<Vec<_>>();
+        millis.sort_unstable();
+        let (&min, &max) = (millis.first()?, millis.last()?);
+        Some(Self {
+            min,
+            mean: millis.iter().sum::<u64>() / millis.len() as u64,
+            p50: percentile(&millis, 50),
+            p90: percentile(&millis, 90),
+            p99: percentile(&millis, 99),
+            max,
+        })
+    }
+}
+
+/// Nearest-rank percentile of the sorted, non-empty `values`
+fn percentile(values: &[u64], percent: usize) -> u64 {
+    let rank = (values.len() * percent + 99) / 100;
+    values[rank.clamp(1, values.len()) - 1]
+}
+
+#[derive(Debug, Serialize)]
+struct BenchReport {
+    src_chain_id: ChainId,
+    dst_chain_id: ChainId,
+    src_port_id: PortId,
+    src_channel_id: ChannelId,
+    /// Number of packets committed on the source chain
+    sent: usize,
+    /// Number of packets received by the destination chain
+    relayed: usize,
+    /// Number of packets still not received when the drain timeout elapsed
+    pending: usize,
+    /// Packets committed per second on the source chain
+    send_rate: f64,
+    /// Packets received per second, from the start of the benchmark to the last reception
+    throughput: f64,
+    latency_ms: Option<LatencySummary>,
+}
+
+/// Packets sent over the channel and not received yet, along with the delays of the
+/// received ones
+struct PacketTracker {
+    dst_port_id: PortId,
+    dst_channel_id: ChannelId,
+    pending: BTreeMap<Sequence, Instant>,
+    sent: usize,
+    latencies: Vec<Duration>,
+    last_received_at: Option<Instant>,
+}
+
+impl PacketTracker {
+    fn record_sent(&mut self, sequence: Sequence, committed_at: Instant) {
+        self.pending.insert(sequence, committed_at);
+        self.sent += 1;
+    }
+
+    /// Query which pending packets the destination chain received since the last poll
+    fn poll(&mut self, dst_chain: &impl ChainHandle) -> eyre::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let unreceived = dst_chain
+            .query_unreceived_packets(QueryUnreceivedPacketsRequest {
+                port_id: self.dst_port_id.clone(),
+                channel_id: self.dst_channel_id.clone(),
+                packet_commitment_sequences: self.pending.keys().copied().collect(),
+            })?
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        let now = Instant::now();
+        let latencies = &mut self.latencies;
+        let pending_count = self.pending.len();
+        self.pending.retain(|sequence, committed_at| {
+            if unreceived.contains(sequence) {
+                return true;
+            }
+            latencies.push(now.duration_since(*committed_at));
+            false
+        });
+        if self.pending.len() < pending_count {
+            self.last_received_at = Some(now);
+        }
+        Ok(())
+    }
+}
+
+impl BenchChannelCmd {
+    fn bench(
+        &self,
+        src_chain: &impl ChainHandle,
+        dst_chain: &impl ChainHandle,
+    ) -> eyre::Result<BenchReport> {
+        let (channel_end, _) = src_chain.query_channel(
+            QueryChannelRequest {
+                port_id: self.src_port_id.clone(),
+                channel_id: self.src_channel_id.clone(),
+                height: QueryHeight::Latest,
+            },
+            IncludeProof::No,
+        )?;
+        let counterparty = channel_end.counterparty();
+        let dst_channel_id = counterparty.channel_id().cloned().ok_or_else(|| {
+            eyre!(
+                "the counterparty of channel '{}' has no channel identifier",
+                self.src_channel_id
+            )
+        })?;
+        let mut tracker = PacketTracker {
+            dst_port_id: counterparty.port_id().clone(),
+            dst_channel_id,
+            pending: BTreeMap::new(),
+            sent: 0,
+            latencies: vec![],
+            last_received_at: None,
+        };
+
+        let opts = TransferOptions {
+            src_port_id: self.src_port_id.clone(),
+            src_channel_id: self.src_channel_id.clone(),
+            amount: self.amount,
+            denom: self.denom.clone(),
+            receiver: None,
+            timeout_height_offset: 0,
+            timeout_duration: PACKET_TIMEOUT,
+            number_msgs: self.rate,
+            memo: None,
+        };
+
+        let start = Instant::now();
+        let mut next_batch = start;
+        while start.elapsed() < *self.duration {
+            if Instant::now() >= next_batch {
+                let msgs = build_transfer_messages(src_chain, dst_chain, &opts)?;
+                let events = send_messages(src_chain, msgs)?;
+                let committed_at = Instant::now();
+                for event in events {
+                    if let IbcEvent::SendPacket(send_packet) = event.event {
+                        tracker.record_sent(send_packet.packet.sequence, committed_at);
+                    }
+                }
+                next_batch += BATCH_INTERVAL;
+            }
+            tracker.poll(dst_chain)?;
+            let until_next_batch = next_batch.saturating_duration_since(Instant::now());
+            thread::sleep(until_next_batch.min(POLL_INTERVAL));
+        }
+        let send_elapsed = start.elapsed();
+        info!(
+            "sent {} packets in {}, waiting for them to be relayed",
+            tracker.sent,
+            humantime::format_duration(send_elapsed)
+        );
+
+        let drain_start = Instant::now();
+        while !tracker.pending.is_empty() && drain_start.elapsed() < *self.drain_timeout {
+            thread::sleep(POLL_INTERVAL);
+            tracker.poll(dst_chain)?;
+        }
+
+        let relayed = tracker.latencies.len();
+        let throughput = match tracker.last_received_at {
+            Some(last_received_at) => {
+                relayed as f64 / last_received_at.duration_since(start).as_secs_f64()
+            }
+            None => 0.0,
+        };
+        Ok(BenchReport {
+            src_chain_id: src_chain.id(),
+            dst_chain_id: dst_chain.id(),
+            src_port_id: self.src_port_id.clone(),
+            src_channel_id: self.src_channel_id.clone(),
+            sent: tracker.sent,
+            relayed,
+            pending: tracker.pending.len(),
+            send_rate: tracker.sent as f64 / send_elapsed.as_secs_f64(),
+            throughput,
+            latency_ms: LatencySummary::new(&tracker.latencies),
+        })
+    }
+}
+
+// forcerelay bench channel --src-chain ibc-0 --dst-chain ibc-1 --src-channel channel-0 --rate 10
+impl Runnable for BenchChannelCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        if self.rate == 0 {
+            Output::error("the rate should be greater than zero").exit()
+        }
+
+        let chains = ChainHandlePair::spawn(&config, &self.src_chain_id, &self.dst_chain_id)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+
+        check_can_send_on_channel(
+            &chains.src,
+            &self.src_channel_id,
+            &self.src_port_id,
+            &chains.dst.id(),
+        )
+        .unwrap_or_else(exit_with_unrecoverable_error);
+
+        match self.bench(&chains.src, &chains.dst) {
+            Ok(report) => Output::success(report).exit(),
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+    use std::str::FromStr;
+
+    use abscissa_core::clap::Parser;
+    use ibc_relayer_types::applications::transfer::Amount;
+    use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, PortId};
+
+    use super::{percentile, BenchChannelCmd, LatencySummary};
+
+    #[test]
+    fn test_bench_channel() {
+        assert_eq!(
+            BenchChannelCmd {
+                src_chain_id: ChainId::from_string("ibc-0"),
+                dst_chain_id: ChainId::from_string("ibc-1"),
+                src_port_id: PortId::transfer(),
+                src_channel_id: ChannelId::from_str("channel-0").unwrap(),
+                rate: 10,
+                duration: Duration::from_secs(60).into(),
+                drain_timeout: Duration::from_secs(300).into(),
+                amount: Amount::from(1u64),
+                denom: "samoleans".to_owned(),
+            },
+            BenchChannelCmd::parse_from([
+                "test",
+                "--src-chain",
+                "ibc-0",
+                "--dst-chain",
+                "ibc-1",
+                "--src-channel",
+                "channel-0",
+                "--rate",
+                "10"
+            ])
+        )
+    }
+
+    #[test]
+    fn test_bench_channel_no_rate() {
+        assert!(BenchChannelCmd::try_parse_from([
+            "test",
+            "--src-chain",
+            "ibc-0",
+            "--dst-chain",
+            "ibc-1",
+            "--src-channel",
+            "channel-0"
+        ])
+        .is_err())
+    }
+
+    #[test]
+    fn test_latency_summary() {
+        assert_eq!(LatencySummary::new(&[]), None);
+
+        let latencies = (1..=100).map(Duration::from_millis).collect::<Vec<_>>();
+        assert_eq!(
+            LatencySummary::new(&latencies),
+            Some(LatencySummary {
+                min: 1,
+                mean: 50,
+                p50: 50,
+                p90: 90,
+                p99: 99,
+                max: 100,
+            })
+        );
+        assert_eq!(percentile(&[7], 99), 7);
+        assert_eq!(percentile(&[1, 2, 3], 0), 1);
+    }
+}
@@ -0,0 +1,61 @@
+//! `ckb` subcommand
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+mod cells;
+mod init_client;
+mod invalidate_cache;
+mod port;
+mod prune_clients;
+mod query;
+mod state_at;
+
+/// `ckb` subcommand
+#[derive(Command, Debug, Parser, Runnable)]
+pub enum CkbCmd {
+    /// Query the cells of the Ethereum light client or of the IBC contracts
+    #[clap(subcommand)]
+    Query(CkbQueryCmds),
+
+    /// Consume the light client cells left by the abandoned deployments of the Ethereum
+    /// light client, reclaiming their capacity
+    PruneClients(prune_clients::PruneClientsCmd),
+
+    /// Create a new set of multi-client cells of the Ethereum light client from a
+    /// checkpoint slot
+    InitClient(init_client::InitClientCmd),
+
+    /// Query the connections, channels and outstanding packets of a CKB chain of the IBC
+    /// contracts as they were at a past block
+    StateAt(state_at::StateAtCmd),
+
+    /// Drop the network type and the contract outpoints of a CKB chain of the IBC
+    /// contracts cached by the running relayer, so that they are queried again
+    InvalidateCache(invalidate_cache::InvalidateCacheCmd),
+
+    /// Bind, release or list the ports of the applications on a CKB chain of the IBC
+    /// contracts, on which the relayer opens channels
+    #[clap(subcommand)]
+    Port(CkbPortCmds),
+}
+
+#[derive(Command, Debug, Parser, Runnable)]
+pub enum CkbQueryCmds {
+    /// Query the latest and oldest client cells of the multi-client set, decoded
+    ClientCell(query::QueryClientCellCmd),
+
+    /// Query the live IBC cells of a CKB chain of the IBC contracts, decoded
+    Cells(cells::QueryCellsCmd),
+}
+
+#[derive(Command, Debug, Parser, Runnable)]
+pub enum CkbPortCmds {
+    /// Bind a port to an application, so that channels are opened on it
+    Bind(port::BindPortCmd),
+
+    /// Release a bound port, so that no more channels are opened on it
+    Release(port::ReleasePortCmd),
+
+    /// List the bound ports
+    List(port::ListPortsCmd),
+}
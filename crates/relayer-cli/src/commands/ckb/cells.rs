@@ -0,0 +1,106 @@
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+use ibc_relayer::chain::ckb4ibc::cells::IbcCellKind;
+use ibc_relayer::chain::handle::ChainHandle;
+use ibc_relayer::config::ChainConfig;
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+use crate::cli_utils::spawn_chain_runtime;
+use crate::conclude::{exit_with_unrecoverable_error, Output};
+use crate::prelude::*;
+
+/// Query the live cells of the client, connection, channel and packet type scripts
+/// configured for a CKB chain, along with their outpoints, their capacities and the
+/// states decoded from them, e.g. to debug a stuck channel.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct QueryCellsCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the CKB chain to query"
+    )]
+    chain_id: ChainId,
+
+    #[clap(
+        long = "kind",
+        value_name = "KIND",
+        help = "Kind of the cells to query, among client, connection, channel and packet. Leave unspecified for all of them."
+    )]
+    kind: Option<IbcCellKind>,
+}
+
+// forcerelay ckb query cells --chain ckb4ibc-0 --kind channel
+impl Runnable for QueryCellsCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        match config.find_chain(&self.chain_id) {
+            Some(ChainConfig::Ckb4Ibc(_)) => {}
+            Some(_) => Output::error(format!(
+                "chain '{}' is not a CKB chain of the IBC contracts",
+                self.chain_id
+            ))
+            .exit(),
+            None => Output::error(format!(
+                "chain '{}' not found in configuration",
+                self.chain_id
+            ))
+            .exit(),
+        }
+
+        let chain = spawn_chain_runtime(&config, &self.chain_id)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+
+        match chain.query_ibc_cells(self.kind) {
+            Ok(cells) => Output::success(cells).exit(),
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueryCellsCmd;
+
+    use abscissa_core::clap::Parser;
+    use ibc_relayer::chain::ckb4ibc::cells::IbcCellKind;
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+    #[test]
+    fn test_query_cells() {
+        assert_eq!(
+            QueryCellsCmd {
+                chain_id: ChainId::from_string("ckb4ibc-0"),
+                kind: None,
+            },
+            QueryCellsCmd::parse_from(["test", "--chain", "ckb4ibc-0"])
+        )
+    }
+
+    #[test]
+    fn test_query_cells_kind() {
+        assert_eq!(
+            QueryCellsCmd {
+                chain_id: ChainId::from_string("ckb4ibc-0"),
+                kind: Some(IbcCellKind::Packet),
+            },
+            QueryCellsCmd::parse_from(["test", "--chain", "ckb4ibc-0", "--kind", "packet"])
+        )
+    }
+
+    #[test]
+    fn test_query_cells_unknown_kind() {
+        assert!(
+            QueryCellsCmd::try_parse_from(["test", "--chain", "ckb4ibc-0", "--kind", "cell"])
+                .is_err()
+        )
+    }
+
+    #[test]
+    fn test_query_cells_no_chain() {
+        assert!(QueryCellsCmd::try_parse_from(["test"]).is_err())
+    }
+}
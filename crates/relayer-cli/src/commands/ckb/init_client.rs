@@ -0,0 +1,217 @@
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+use ckb_types::H256;
+
+use ibc_relayer::chain::client::ClientSettings;
+use ibc_relayer::chain::handle::ChainHandle;
+use ibc_relayer::chain::tracking::{NonCosmosTrackingId, TrackedMsgs, TrackingId};
+use ibc_relayer::config::ChainConfig;
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+use ibc_relayer_types::Height;
+
+use crate::cli_utils::spawn_chain_runtime;
+use crate::commands::tx::client::parse_type_args;
+use crate::conclude::{exit_with_unrecoverable_error, Output};
+use crate::prelude::*;
+
+/// Create a new set of multi-client cells of the Ethereum light client on a CKB chain,
+/// starting from the finalized header of the given checkpoint slot of the Ethereum chain.
+///
+/// The `client_type_args.type_id` of the CKB chain is ignored, and the type id of the
+/// new set is to be written back to the configuration once created.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct InitClientCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the CKB chain to create the light client on"
+    )]
+    chain_id: ChainId,
+
+    #[clap(
+        long = "eth-chain",
+        required = true,
+        value_name = "ETH_CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the Ethereum chain tracked by the light client"
+    )]
+    eth_chain_id: ChainId,
+
+    #[clap(
+        long = "checkpoint-slot",
+        required = true,
+        value_name = "SLOT",
+        help_heading = "REQUIRED",
+        help = "Slot of the finalized header the light client starts from"
+    )]
+    checkpoint_slot: u64,
+
+    #[clap(
+        long = "cells-count",
+        value_name = "COUNT",
+        help = "Number of cells of the set, the info cell included. \
+                Defaults to the `client_type_args.cells_count` of the chain"
+    )]
+    cells_count: Option<u8>,
+
+    #[clap(
+        long = "lock-typeargs",
+        value_name = "TYPE_ARGS",
+        parse(try_from_str = parse_type_args),
+        help = "Type args of the lock of the cells. \
+                Defaults to the `lightclient_lock_typeargs` of the chain"
+    )]
+    lock_typeargs: Option<H256>,
+}
+
+impl InitClientCmd {
+    /// Apply the overrides of the command to the configuration of the CKB chain
+    fn override_ckb_config(&self, chain_config: &mut ChainConfig) {
+        let ChainConfig::Ckb(ckb_config) = chain_config else {
+            Output::error(format!(
+                "chain '{}' is not a CKB chain of the Ethereum light client",
+                self.chain_id
+            ))
+            .exit()
+        };
+        ckb_config.client_type_args.type_id = None;
+        if let Some(cells_count) = self.cells_count {
+            ckb_config.client_type_args.cells_count = cells_count;
+        }
+        if let Some(lock_typeargs) = &self.lock_typeargs {
+            ckb_config.lightclient_lock_typeargs = lock_typeargs.clone();
+        }
+    }
+}
+
+// forcerelay ckb init-client --chain ckb-0 --eth-chain eth-0 --checkpoint-slot 6632736
+impl Runnable for InitClientCmd {
+    fn run(&self) {
+        if self.cells_count.map_or(false, |count| count < 2) {
+            Output::error(
+                "a multi-client set needs at least one client cell besides its info cell"
+                    .to_owned(),
+            )
+            .exit()
+        }
+        let checkpoint = Height::new(0, self.checkpoint_slot)
+            .unwrap_or_else(|e| Output::error(format!("invalid checkpoint slot: {e}")).exit());
+
+        let mut config = (*app_config()).clone();
+        match config.find_chain_mut(&self.chain_id) {
+            Some(chain_config) => self.override_ckb_config(chain_config),
+            None => Output::error(format!(
+                "chain '{}' not found in configuration",
+                self.chain_id
+            ))
+            .exit(),
+        }
+        match config.find_chain(&self.eth_chain_id) {
+            Some(ChainConfig::Eth(_)) => {}
+            Some(_) => Output::error(format!(
+                "chain '{}' is not an Ethereum chain",
+                self.eth_chain_id
+            ))
+            .exit(),
+            None => Output::error(format!(
+                "chain '{}' not found in configuration",
+                self.eth_chain_id
+            ))
+            .exit(),
+        }
+
+        let eth_chain = spawn_chain_runtime(&config, &self.eth_chain_id)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+        let ckb_chain = spawn_chain_runtime(&config, &self.chain_id)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+
+        let client_state = eth_chain
+            .build_client_state(checkpoint, ClientSettings::Other)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+        let tracked_msgs = TrackedMsgs {
+            msgs: vec![client_state.into()],
+            tracking_id: TrackingId::Static(NonCosmosTrackingId::ETH_CREATE_CLIENT),
+        };
+        if let Err(e) = ckb_chain.send_messages_and_wait_commit(tracked_msgs) {
+            Output::error(e).exit()
+        }
+
+        match ckb_chain.query_light_client_cells() {
+            Ok(client_cells) => {
+                info!(
+                    "created the multi-client cells of type id {:#x}, to be set as the \
+                     `client_type_args.type_id` of chain '{}'",
+                    client_cells.type_id, self.chain_id
+                );
+                Output::success(client_cells).exit()
+            }
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InitClientCmd;
+
+    use abscissa_core::clap::Parser;
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+    #[test]
+    fn test_init_client() {
+        assert_eq!(
+            InitClientCmd {
+                chain_id: ChainId::from_string("ckb-0"),
+                eth_chain_id: ChainId::from_string("eth-0"),
+                checkpoint_slot: 6632736,
+                cells_count: None,
+                lock_typeargs: None,
+            },
+            InitClientCmd::parse_from([
+                "test",
+                "--chain",
+                "ckb-0",
+                "--eth-chain",
+                "eth-0",
+                "--checkpoint-slot",
+                "6632736"
+            ])
+        )
+    }
+
+    #[test]
+    fn test_init_client_overrides() {
+        assert_eq!(
+            InitClientCmd {
+                chain_id: ChainId::from_string("ckb-0"),
+                eth_chain_id: ChainId::from_string("eth-0"),
+                checkpoint_slot: 6632736,
+                cells_count: Some(3),
+                lock_typeargs: Some([1u8; 32].into()),
+            },
+            InitClientCmd::parse_from([
+                "test",
+                "--chain",
+                "ckb-0",
+                "--eth-chain",
+                "eth-0",
+                "--checkpoint-slot",
+                "6632736",
+                "--cells-count",
+                "3",
+                "--lock-typeargs",
+                "0x0101010101010101010101010101010101010101010101010101010101010101"
+            ])
+        )
+    }
+
+    #[test]
+    fn test_init_client_no_checkpoint() {
+        assert!(
+            InitClientCmd::try_parse_from(["test", "--chain", "ckb-0", "--eth-chain", "eth-0"])
+                .is_err()
+        )
+    }
+}
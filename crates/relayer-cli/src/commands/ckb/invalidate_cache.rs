@@ -0,0 +1,80 @@
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+use ibc_relayer::config::Config;
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+use crate::conclude::Output;
+use crate::error::Error;
+use crate::prelude::*;
+
+/// Drop the network type and the contract outpoints cached for a CKB chain of the IBC
+/// contracts by a running relayer, e.g. after the contracts were upgraded.
+///
+/// The command is sent to the REST server of the running relayer.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct InvalidateCacheCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the chain"
+    )]
+    chain_id: ChainId,
+}
+
+impl Runnable for InvalidateCacheCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        match invalidate_cache(&config, &self.chain_id) {
+            Ok(()) => Output::success_msg(format!(
+                "the cache of chain '{}' was invalidated",
+                self.chain_id
+            ))
+            .exit(),
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+#[cfg(feature = "rest-server")]
+fn invalidate_cache(config: &Config, chain_id: &ChainId) -> Result<(), Error> {
+    if config.find_chain(chain_id).is_none() {
+        return Err(Error::missing_chain_config(chain_id.clone()));
+    }
+    crate::commands::pause::rest_client(config)?
+        .invalidate_cache(chain_id)
+        .map_err(|e| Error::rest(e.to_string()))
+}
+
+#[cfg(not(feature = "rest-server"))]
+fn invalidate_cache(_config: &Config, _chain_id: &ChainId) -> Result<(), Error> {
+    Err(Error::rest(
+        "Forcerelay was built without REST support".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InvalidateCacheCmd;
+
+    use abscissa_core::clap::Parser;
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+    #[test]
+    fn test_invalidate_cache() {
+        assert_eq!(
+            InvalidateCacheCmd {
+                chain_id: ChainId::from_string("chain_id")
+            },
+            InvalidateCacheCmd::parse_from(["test", "--chain", "chain_id"])
+        )
+    }
+
+    #[test]
+    fn test_invalidate_cache_no_chain() {
+        assert!(InvalidateCacheCmd::try_parse_from(["test"]).is_err())
+    }
+}
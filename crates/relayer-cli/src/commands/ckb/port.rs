@@ -0,0 +1,212 @@
+use std::path::PathBuf;
+
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+use ibc_relayer::chain::ckb4ibc::port_bindings::PortBindings;
+use ibc_relayer::config::{ChainConfig, Config};
+use ibc_relayer_types::core::ics24_host::identifier::{ChainId, PortId};
+
+use crate::conclude::{exit_with_unrecoverable_error, Output};
+use crate::prelude::*;
+
+/// The `port_bindings_file` of the CKB chain `chain_id`
+fn port_bindings_file(config: &Config, chain_id: &ChainId) -> Result<PathBuf, String> {
+    match config.find_chain(chain_id) {
+        Some(ChainConfig::Ckb4Ibc(chain_config)) => {
+            chain_config.port_bindings_file.clone().ok_or_else(|| {
+                format!("chain '{chain_id}' has no `port_bindings_file`, any port is accepted")
+            })
+        }
+        Some(_) => Err(format!(
+            "chain '{chain_id}' is not a CKB chain of the IBC contracts"
+        )),
+        None => Err(format!("chain '{chain_id}' not found in configuration")),
+    }
+}
+
+/// Bind a port to an application on a CKB chain of the IBC contracts, so that the
+/// relayer opens channels on it.
+///
+/// The IBC contracts have no message to bind a port, whose id is the lock hash of the
+/// application in hex, so the binding is recorded in the `port_bindings_file` of the
+/// chain, which the running relayer reads before opening each channel.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct BindPortCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the CKB chain"
+    )]
+    chain_id: ChainId,
+
+    #[clap(
+        long = "port",
+        required = true,
+        value_name = "PORT_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the port, the lock hash of the application in hex"
+    )]
+    port_id: PortId,
+}
+
+// forcerelay ckb port bind --chain ckb4ibc-0 --port 8b1e...
+impl Runnable for BindPortCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        let path = port_bindings_file(&config, &self.chain_id)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+        let result = PortBindings::load(&path).and_then(|mut bindings| {
+            let bound = bindings.bind(self.port_id.clone())?;
+            bindings.save(&path)?;
+            Ok(bound)
+        });
+
+        match result {
+            Ok(true) => Output::success_msg(format!(
+                "port '{}' is bound on chain '{}'",
+                self.port_id, self.chain_id
+            ))
+            .exit(),
+            Ok(false) => Output::success_msg(format!(
+                "port '{}' was already bound on chain '{}'",
+                self.port_id, self.chain_id
+            ))
+            .exit(),
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+/// Release a port bound on a CKB chain of the IBC contracts, so that the relayer
+/// opens no more channels on it. The channels already open on it are still relayed.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct ReleasePortCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the CKB chain"
+    )]
+    chain_id: ChainId,
+
+    #[clap(
+        long = "port",
+        required = true,
+        value_name = "PORT_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the port to release"
+    )]
+    port_id: PortId,
+}
+
+// forcerelay ckb port release --chain ckb4ibc-0 --port 8b1e...
+impl Runnable for ReleasePortCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        let path = port_bindings_file(&config, &self.chain_id)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+        let result = PortBindings::load(&path).and_then(|mut bindings| {
+            if !bindings.release(&self.port_id) {
+                return Ok(false);
+            }
+            bindings.save(&path)?;
+            Ok(true)
+        });
+
+        match result {
+            Ok(true) => Output::success_msg(format!(
+                "port '{}' is released on chain '{}'",
+                self.port_id, self.chain_id
+            ))
+            .exit(),
+            Ok(false) => Output::error(format!(
+                "port '{}' is not bound on chain '{}'",
+                self.port_id, self.chain_id
+            ))
+            .exit(),
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+/// List the ports bound on a CKB chain of the IBC contracts
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct ListPortsCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the CKB chain"
+    )]
+    chain_id: ChainId,
+}
+
+// forcerelay ckb port list --chain ckb4ibc-0
+impl Runnable for ListPortsCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        let path = port_bindings_file(&config, &self.chain_id)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+        match PortBindings::load(&path) {
+            Ok(bindings) => Output::success(bindings.ports().cloned().collect::<Vec<_>>()).exit(),
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::{BindPortCmd, ListPortsCmd, ReleasePortCmd};
+
+    use abscissa_core::clap::Parser;
+    use ibc_relayer_types::core::ics24_host::identifier::{ChainId, PortId};
+
+    const PORT: &str = "b6ac779881b4a4b2a1f4a6e6bfd0e5b2f4f0e8e4c1d6f1c2a4b8c2d4e6f80a1c";
+
+    #[test]
+    fn test_bind_port() {
+        assert_eq!(
+            BindPortCmd {
+                chain_id: ChainId::from_string("ckb4ibc-0"),
+                port_id: PortId::from_str(PORT).unwrap(),
+            },
+            BindPortCmd::parse_from(["test", "--chain", "ckb4ibc-0", "--port", PORT])
+        )
+    }
+
+    #[test]
+    fn test_bind_port_no_port() {
+        assert!(BindPortCmd::try_parse_from(["test", "--chain", "ckb4ibc-0"]).is_err())
+    }
+
+    #[test]
+    fn test_release_port() {
+        assert_eq!(
+            ReleasePortCmd {
+                chain_id: ChainId::from_string("ckb4ibc-0"),
+                port_id: PortId::from_str(PORT).unwrap(),
+            },
+            ReleasePortCmd::parse_from(["test", "--chain", "ckb4ibc-0", "--port", PORT])
+        )
+    }
+
+    #[test]
+    fn test_list_ports() {
+        assert_eq!(
+            ListPortsCmd {
+                chain_id: ChainId::from_string("ckb4ibc-0"),
+            },
+            ListPortsCmd::parse_from(["test", "--chain", "ckb4ibc-0"])
+        )
+    }
+}
@@ -0,0 +1,104 @@
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+use ibc_relayer::chain::handle::ChainHandle;
+use ibc_relayer::config::ChainConfig;
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+use crate::cli_utils::spawn_chain_runtime;
+use crate::conclude::{exit_with_unrecoverable_error, Output};
+use crate::prelude::*;
+
+/// Consume the obsolete light client cells locked by the light client lock of a CKB chain.
+///
+/// The cells belonging to a multi-client set other than the one of the `client_type_args`
+/// of the chain are left by the previous deployments of the light client, and are
+/// consumed into the relayer account.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct PruneClientsCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the CKB chain to prune the light client cells of"
+    )]
+    chain_id: ChainId,
+
+    #[clap(
+        long = "dry-run",
+        help = "List the obsolete cells without consuming them"
+    )]
+    dry_run: bool,
+}
+
+// forcerelay ckb prune-clients --chain ckb-0
+impl Runnable for PruneClientsCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        match config.find_chain(&self.chain_id) {
+            Some(ChainConfig::Ckb(_)) => {}
+            Some(_) => Output::error(format!(
+                "chain '{}' is not a CKB chain of the Ethereum light client",
+                self.chain_id
+            ))
+            .exit(),
+            None => Output::error(format!(
+                "chain '{}' not found in configuration",
+                self.chain_id
+            ))
+            .exit(),
+        }
+
+        let chain = spawn_chain_runtime(&config, &self.chain_id)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+
+        match chain.prune_obsolete_clients(self.dry_run) {
+            Ok(pruning) => {
+                info!(
+                    "found {} obsolete client cells holding {} shannons",
+                    pruning.cells.len(),
+                    pruning.reclaimed_capacity()
+                );
+                Output::success(pruning).exit()
+            }
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PruneClientsCmd;
+
+    use abscissa_core::clap::Parser;
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+    #[test]
+    fn test_prune_clients() {
+        assert_eq!(
+            PruneClientsCmd {
+                chain_id: ChainId::from_string("ckb-0"),
+                dry_run: false,
+            },
+            PruneClientsCmd::parse_from(["test", "--chain", "ckb-0"])
+        )
+    }
+
+    #[test]
+    fn test_prune_clients_dry_run() {
+        assert_eq!(
+            PruneClientsCmd {
+                chain_id: ChainId::from_string("ckb-0"),
+                dry_run: true,
+            },
+            PruneClientsCmd::parse_from(["test", "--chain", "ckb-0", "--dry-run"])
+        )
+    }
+
+    #[test]
+    fn test_prune_clients_no_chain() {
+        assert!(PruneClientsCmd::try_parse_from(["test", "--dry-run"]).is_err())
+    }
+}
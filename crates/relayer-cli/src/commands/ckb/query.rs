@@ -0,0 +1,77 @@
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+use ibc_relayer::chain::handle::ChainHandle;
+use ibc_relayer::config::ChainConfig;
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+use crate::cli_utils::spawn_chain_runtime;
+use crate::conclude::{exit_with_unrecoverable_error, Output};
+use crate::prelude::*;
+
+/// Query the cells of the multi-client set of the Ethereum light client of a CKB chain,
+/// decoded from their molecule data: the identifiers and the slots of the latest and
+/// oldest clients, and the identifier of the last client recorded in the info cell.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct QueryClientCellCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the CKB chain to query"
+    )]
+    chain_id: ChainId,
+}
+
+// forcerelay ckb query client-cell --chain ckb-0
+impl Runnable for QueryClientCellCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        match config.find_chain(&self.chain_id) {
+            Some(ChainConfig::Ckb(_)) => {}
+            Some(_) => Output::error(format!(
+                "chain '{}' is not a CKB chain of the Ethereum light client",
+                self.chain_id
+            ))
+            .exit(),
+            None => Output::error(format!(
+                "chain '{}' not found in configuration",
+                self.chain_id
+            ))
+            .exit(),
+        }
+
+        let chain = spawn_chain_runtime(&config, &self.chain_id)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+
+        match chain.query_light_client_cells() {
+            Ok(client_cells) => Output::success(client_cells).exit(),
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueryClientCellCmd;
+
+    use abscissa_core::clap::Parser;
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+    #[test]
+    fn test_query_client_cell() {
+        assert_eq!(
+            QueryClientCellCmd {
+                chain_id: ChainId::from_string("ckb-0")
+            },
+            QueryClientCellCmd::parse_from(["test", "--chain", "ckb-0"])
+        )
+    }
+
+    #[test]
+    fn test_query_client_cell_no_chain() {
+        assert!(QueryClientCellCmd::try_parse_from(["test"]).is_err())
+    }
+}
@@ -0,0 +1,94 @@
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+use ibc_relayer::chain::handle::ChainHandle;
+use ibc_relayer::config::ChainConfig;
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+use crate::cli_utils::spawn_chain_runtime;
+use crate::conclude::{exit_with_unrecoverable_error, Output};
+use crate::prelude::*;
+
+/// Query the connections, the channels and the outstanding packets of a CKB chain as
+/// they were at a past block.
+///
+/// The states are recovered by walking back the transactions which updated the live
+/// IBC cells, so the older the block, the more transactions are fetched.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct StateAtCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the CKB chain to query"
+    )]
+    chain_id: ChainId,
+
+    #[clap(
+        long = "block",
+        required = true,
+        value_name = "BLOCK_NUMBER",
+        help_heading = "REQUIRED",
+        help = "Number of the block to query the IBC states at"
+    )]
+    block: u64,
+}
+
+// forcerelay ckb state-at --chain ckb4ibc-0 --block 1000
+impl Runnable for StateAtCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        match config.find_chain(&self.chain_id) {
+            Some(ChainConfig::Ckb4Ibc(_)) => {}
+            Some(_) => Output::error(format!(
+                "chain '{}' is not a CKB chain of the IBC contracts",
+                self.chain_id
+            ))
+            .exit(),
+            None => Output::error(format!(
+                "chain '{}' not found in configuration",
+                self.chain_id
+            ))
+            .exit(),
+        }
+
+        let chain = spawn_chain_runtime(&config, &self.chain_id)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+
+        match chain.query_ibc_state_at(self.block) {
+            Ok(state) => Output::success(state).exit(),
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StateAtCmd;
+
+    use abscissa_core::clap::Parser;
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+    #[test]
+    fn test_state_at() {
+        assert_eq!(
+            StateAtCmd {
+                chain_id: ChainId::from_string("ckb4ibc-0"),
+                block: 1000,
+            },
+            StateAtCmd::parse_from(["test", "--chain", "ckb4ibc-0", "--block", "1000"])
+        )
+    }
+
+    #[test]
+    fn test_state_at_no_block() {
+        assert!(StateAtCmd::try_parse_from(["test", "--chain", "ckb4ibc-0"]).is_err())
+    }
+
+    #[test]
+    fn test_state_at_no_chain() {
+        assert!(StateAtCmd::try_parse_from(["test", "--block", "1000"]).is_err())
+    }
+}
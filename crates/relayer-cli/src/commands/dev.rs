@@ -0,0 +1,12 @@
+//! `dev` subcommand
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+mod fund;
+
+/// `dev` subcommand
+#[derive(Command, Debug, Parser, Runnable)]
+pub enum DevCmd {
+    /// Request testnet funds from a faucet for the relayer account of a CKB chain
+    Fund(fund::DevFundCmd),
+}
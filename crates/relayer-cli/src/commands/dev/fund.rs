@@ -0,0 +1,228 @@
+use core::time::Duration;
+use std::str::FromStr;
+use std::thread;
+use std::time::Instant;
+
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+use ckb_sdk::{Address, NetworkType};
+use eyre::eyre;
+use serde_json::json;
+
+use ibc_relayer::chain::handle::ChainHandle;
+use ibc_relayer::config::ChainConfig;
+use ibc_relayer::keyring::{KeyRing, Store};
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+use crate::cli_utils::spawn_chain_runtime;
+use crate::conclude::{exit_with_unrecoverable_error, Output};
+use crate::prelude::*;
+
+/// Claim endpoint of the faucet of the CKB testnet
+const DEFAULT_FAUCET_URL: &str = "https://faucet-api.nervos.org/claim_events";
+
+/// Interval between the queries of the balance while waiting for the funds
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Request funds from a faucet for the relayer account of a CKB testnet chain.
+///
+/// The command claims the funds for the `account_address` of the chain, or the address
+/// of its `key_name` key, waits for the capacity of the account to increase and checks
+/// it against the `min_balance` of the chain, if any.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct DevFundCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the CKB chain to fund the relayer account on"
+    )]
+    chain_id: ChainId,
+
+    #[clap(
+        long = "faucet-url",
+        value_name = "FAUCET_URL",
+        default_value = DEFAULT_FAUCET_URL,
+        help = "URL of the claim endpoint of the faucet"
+    )]
+    faucet_url: String,
+
+    #[clap(
+        long = "amount",
+        value_name = "AMOUNT",
+        default_value = "10000",
+        help = "Amount of CKB to claim, among the amounts offered by the faucet"
+    )]
+    amount: u64,
+
+    #[clap(
+        long = "timeout",
+        value_name = "TIMEOUT",
+        default_value = "5m",
+        help = "How long to wait for the funds to arrive"
+    )]
+    timeout: humantime::Duration,
+}
+
+impl Runnable for DevFundCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        let chain_config = config
+            .find_chain(&self.chain_id)
+            .cloned()
+            .unwrap_or_else(|| {
+                Output::error(format!(
+                    "chain '{}' not found in configuration",
+                    self.chain_id
+                ))
+                .exit()
+            });
+        let address = relayer_address(&chain_config).unwrap_or_else(exit_with_unrecoverable_error);
+
+        let chain = spawn_chain_runtime(&config, &self.chain_id)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+
+        let balance = query_capacity(&chain)
+            .and_then(|initial| {
+                claim(&self.faucet_url, &address, self.amount)?;
+                wait_for_funds(&chain, initial, *self.timeout)
+            })
+            .unwrap_or_else(exit_with_unrecoverable_error);
+
+        match chain_config.min_balance() {
+            Some(min_balance) if balance < min_balance => Output::error(format!(
+                "the balance of {address} is {balance} shannons after the claim, \
+                 still below the minimum balance of {min_balance} shannons"
+            ))
+            .exit(),
+            _ => Output::success_msg(format!(
+                "funded {address} on chain {}, whose balance is now {balance} shannons",
+                self.chain_id
+            ))
+            .exit(),
+        }
+    }
+}
+
+/// Testnet address of the relayer account of the chain
+fn relayer_address(config: &ChainConfig) -> eyre::Result<String> {
+    let ChainConfig::Ckb4Ibc(config) = config else {
+        return Err(eyre!(
+            "chain '{}' is not a CKB chain, only CKB testnets can be funded",
+            config.id()
+        ));
+    };
+
+    match &config.account_address {
+        Some(address) => {
+            let address = Address::from_str(address).map_err(|e| eyre!(e))?;
+            if address.network() != NetworkType::Testnet {
+                return Err(eyre!("{address} is not an address of the CKB testnet"));
+            }
+            Ok(address.to_string())
+        }
+        None => {
+            let keyring = KeyRing::new_secp256k1(Store::Test, "ckb", &config.id)?;
+            let key = keyring.get_key(&config.key_name)?;
+            Ok(key.ckb_address(NetworkType::Testnet))
+        }
+    }
+}
+
+/// Capacity of the relayer account, in shannons
+fn query_capacity(chain: &impl ChainHandle) -> eyre::Result<u64> {
+    let balance = chain.query_balance(None, None)?;
+    Ok(balance.amount.parse()?)
+}
+
+fn claim(faucet_url: &str, address: &str, amount: u64) -> eyre::Result<()> {
+    let body = json!({
+        "claim_event": {
+            "address_hash": address,
+            "amount": amount.to_string(),
+        }
+    });
+
+    match ureq::post(faucet_url)
+        .set("Content-Type", "application/json")
+        .send_string(&body.to_string())
+    {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(status, response)) => Err(eyre!(
+            "the faucet rejected the claim with status {status}: {}",
+            response.into_string().unwrap_or_default()
+        )),
+        Err(e) => Err(eyre!("failed to reach the faucet at {faucet_url}: {e}")),
+    }
+}
+
+/// Wait for the capacity of the relayer account to exceed `initial`, returning it
+fn wait_for_funds(chain: &impl ChainHandle, initial: u64, timeout: Duration) -> eyre::Result<u64> {
+    let start = Instant::now();
+    loop {
+        let balance = query_capacity(chain)?;
+        if balance > initial {
+            return Ok(balance);
+        }
+        if start.elapsed() >= timeout {
+            return Err(eyre!(
+                "the funds did not arrive within {}",
+                humantime::format_duration(timeout)
+            ));
+        }
+        info!("waiting for the funds to arrive, balance is {balance} shannons");
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DevFundCmd, DEFAULT_FAUCET_URL};
+
+    use abscissa_core::clap::Parser;
+    use core::time::Duration;
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+    #[test]
+    fn test_dev_fund() {
+        assert_eq!(
+            DevFundCmd {
+                chain_id: ChainId::from_string("ckb-testnet"),
+                faucet_url: DEFAULT_FAUCET_URL.to_owned(),
+                amount: 10000,
+                timeout: Duration::from_secs(300).into(),
+            },
+            DevFundCmd::parse_from(["test", "--chain", "ckb-testnet"])
+        )
+    }
+
+    #[test]
+    fn test_dev_fund_all_options() {
+        assert_eq!(
+            DevFundCmd {
+                chain_id: ChainId::from_string("ckb-testnet"),
+                faucet_url: "http://127.0.0.1:3000/claim_events".to_owned(),
+                amount: 100000,
+                timeout: Duration::from_secs(60).into(),
+            },
+            DevFundCmd::parse_from([
+                "test",
+                "--chain",
+                "ckb-testnet",
+                "--faucet-url",
+                "http://127.0.0.1:3000/claim_events",
+                "--amount",
+                "100000",
+                "--timeout",
+                "1m"
+            ])
+        )
+    }
+
+    #[test]
+    fn test_dev_fund_no_chain() {
+        assert!(DevFundCmd::try_parse_from(["test"]).is_err())
+    }
+}
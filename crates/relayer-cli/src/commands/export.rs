@@ -0,0 +1,12 @@
+//! `export` subcommand
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+mod index;
+
+/// `export` subcommand
+#[derive(Command, Debug, Parser, Runnable)]
+pub enum ExportCmd {
+    /// Export the packet and channel events archived by the running relayer
+    Index(index::ExportIndexCmd),
+}
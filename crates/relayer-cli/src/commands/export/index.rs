@@ -0,0 +1,165 @@
+use core::str::FromStr;
+
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+use ibc_relayer::config::Config;
+
+use crate::conclude::Output;
+use crate::error::Error;
+use crate::prelude::*;
+
+/// Format of the exported events
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON object per line
+    Ndjson,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ndjson" => Ok(Self::Ndjson),
+            _ => Err(format!("unsupported export format '{s}', expected ndjson")),
+        }
+    }
+}
+
+/// Export the packet and channel events archived by the running relayer to the
+/// standard output, e.g. to bulk-load them into an analytics database.
+///
+/// The events are fetched page by page from the REST server of the running relayer,
+/// `global.archive_file` being set, and the next page is only requested once the
+/// previous one is written out, so a slow consumer holds back the export.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct ExportIndexCmd {
+    #[clap(
+        long = "format",
+        value_name = "FORMAT",
+        default_value = "ndjson",
+        help = "Format of the exported events, only ndjson is supported"
+    )]
+    format: ExportFormat,
+
+    #[clap(
+        long = "from",
+        value_name = "INDEX",
+        default_value = "0",
+        help = "Index of the first event to export, e.g. the number of events exported previously"
+    )]
+    from: u64,
+
+    #[clap(
+        long = "page-size",
+        value_name = "SIZE",
+        default_value = "1000",
+        help = "Number of events fetched from the relayer at once, at most 1000"
+    )]
+    page_size: usize,
+}
+
+impl Runnable for ExportIndexCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        if let Err(e) = export_index(&config, self.format, self.from, self.page_size) {
+            Output::error(e).exit()
+        }
+    }
+}
+
+#[cfg(feature = "rest-server")]
+fn export_index(
+    config: &Config,
+    format: ExportFormat,
+    from: u64,
+    page_size: usize,
+) -> Result<(), Error> {
+    use std::io::{ErrorKind, Write};
+
+    use ibc_relayer::archive::MAX_PAGE_SIZE;
+
+    let ExportFormat::Ndjson = format;
+    let client = crate::commands::pause::rest_client(config)?;
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+
+    let mut from = from;
+    loop {
+        let page = client
+            .archive(from, page_size.clamp(1, MAX_PAGE_SIZE))
+            .map_err(|e| Error::rest(e.to_string()))?;
+        let written = page.events.iter().try_for_each(|event| {
+            serde_json::to_writer(&mut out, event)?;
+            writeln!(out)
+        });
+        match written.and_then(|()| out.flush()) {
+            Ok(()) => {}
+            // the consumer stopped reading, e.g. `head`
+            Err(e) if e.kind() == ErrorKind::BrokenPipe => return Ok(()),
+            Err(e) => return Err(Error::io(e)),
+        }
+        match page.next {
+            Some(next) => from = next,
+            None => return Ok(()),
+        }
+    }
+}
+
+#[cfg(not(feature = "rest-server"))]
+fn export_index(
+    _config: &Config,
+    _format: ExportFormat,
+    _from: u64,
+    _page_size: usize,
+) -> Result<(), Error> {
+    Err(Error::rest(
+        "Forcerelay was built without REST support".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExportFormat, ExportIndexCmd};
+
+    use abscissa_core::clap::Parser;
+
+    #[test]
+    fn test_export_index_defaults() {
+        assert_eq!(
+            ExportIndexCmd {
+                format: ExportFormat::Ndjson,
+                from: 0,
+                page_size: 1000,
+            },
+            ExportIndexCmd::parse_from(["test"])
+        )
+    }
+
+    #[test]
+    fn test_export_index_from() {
+        assert_eq!(
+            ExportIndexCmd {
+                format: ExportFormat::Ndjson,
+                from: 42,
+                page_size: 100,
+            },
+            ExportIndexCmd::parse_from([
+                "test",
+                "--format",
+                "ndjson",
+                "--from",
+                "42",
+                "--page-size",
+                "100"
+            ])
+        )
+    }
+
+    #[test]
+    fn test_export_index_unknown_format() {
+        assert!(ExportIndexCmd::try_parse_from(["test", "--format", "csv"]).is_err())
+    }
+}
@@ -9,7 +9,7 @@ use abscissa_core::{Command, Runnable};
 use tracing::error_span;
 
 use ibc_relayer::chain::handle::{CachingChainHandle, ChainHandle};
-use ibc_relayer::config::GLOBAL_CONFIG_PATH;
+use ibc_relayer::config::{ChainConfig, GLOBAL_CONFIG_PATH};
 use ibc_relayer::event::monitor::{Error as EventError, ErrorDetail as EventErrorDetail};
 use ibc_relayer::registry::SharedRegistry;
 use ibc_relayer::supervisor::forcerelay::handle_eth_ckb_event_batch;
@@ -34,11 +34,26 @@ pub struct EthCkbCmd {
         help = "Identifier of the Ckb chain that hosts the client"
     )]
     ckb_chain: ChainId,
+
+    #[clap(
+        long = "force",
+        help = "Create a new set of multi-client cells if the existing ones are corrupted"
+    )]
+    force: bool,
 }
 
 impl Runnable for EthCkbCmd {
     fn run(&self) {
-        let config = (*app_config()).clone();
+        let mut config = (*app_config()).clone();
+        if self.force {
+            for chain_config in config.chains.iter_mut() {
+                if let ChainConfig::Ckb(ckb_config) = chain_config {
+                    if ckb_config.id == self.ckb_chain {
+                        ckb_config.recreate_corrupted_clients = true;
+                    }
+                }
+            }
+        }
         let config_path = app_config_path().expect("config path isn't set");
         GLOBAL_CONFIG_PATH
             .set(config_path.clone())
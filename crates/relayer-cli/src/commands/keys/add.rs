@@ -7,24 +7,30 @@ use std::{
 use abscissa_core::clap::Parser;
 use abscissa_core::{Command, Runnable};
 
+use ckb_sdk::NetworkType;
 use eyre::eyre;
 use hdpath::StandardHDPath;
 use ibc_relayer::{
     chain::ChainType,
-    config::{ChainConfig, Config},
+    config::{AddressType, ChainConfig, Config},
     keyring::{
-        AnySigningKeyPair, KeyRing, Secp256k1KeyPair, SigningKeyPair, SigningKeyPairSized, Store,
+        account_prefix, AnySigningKeyPair, KeyRing, Secp256k1KeyPair, SigningKeyPair,
+        SigningKeyPairSized, Store,
     },
 };
 use ibc_relayer_types::core::ics24_host::identifier::ChainId;
 use tracing::warn;
 
 use crate::application::app_config;
+use crate::cli_utils::ckb_network;
 use crate::conclude::Output;
 
+/// Public key type of the Ethereum-like keys, which derive the addresses of the Axon and EVM chains
+const ETH_SECP256K1_PK_TYPE: &str = "/ethermint.crypto.v1.ethsecp256k1.PubKey";
+
 /// The data structure that represents the arguments when invoking the `keys add` CLI command.
 ///
-/// The command has one argument and two exclusive flags:
+/// The command has one argument and three exclusive flags:
 ///
 /// The command to add a key from a file:
 ///
@@ -34,13 +40,21 @@ use crate::conclude::Output;
 ///
 /// `keys add [OPTIONS] --chain <CHAIN_ID> --mnemonic-file <MNEMONIC_FILE>`
 ///
-/// The key-file and mnemonic-file flags can't be given at the same time, this will cause a terminating error.
+/// The command to import a raw private key from a file containing it hex encoded:
+///
+/// `keys add [OPTIONS] --chain <CHAIN_ID> --private-key-file <PRIVATE_KEY_FILE>`
+///
+/// Only one of the key-file, mnemonic-file and private-key-file flags can be given, otherwise
+/// this will cause a terminating error.
 /// If successful the key will be created or restored, depending on which flag was given.
+/// The keys of the CKB chains are displayed with their CKB address on the network of the chain.
 #[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
 #[clap(
     override_usage = "forcerelay keys add [OPTIONS] --chain <CHAIN_ID> --key-file <KEY_FILE>
 
-    forcerelay keys add [OPTIONS] --chain <CHAIN_ID> --mnemonic-file <MNEMONIC_FILE>"
+    forcerelay keys add [OPTIONS] --chain <CHAIN_ID> --mnemonic-file <MNEMONIC_FILE>
+
+    forcerelay keys add [OPTIONS] --chain <CHAIN_ID> --private-key-file <PRIVATE_KEY_FILE>"
 )]
 pub struct KeysAddCmd {
     #[clap(
@@ -71,6 +85,16 @@ pub struct KeysAddCmd {
     )]
     mnemonic_file: Option<PathBuf>,
 
+    #[clap(
+        long = "private-key-file",
+        required = true,
+        value_name = "PRIVATE_KEY_FILE",
+        help_heading = "FLAGS",
+        help = "Path to file containing the hex encoded private key to import",
+        group = "add-restore"
+    )]
+    private_key_file: Option<PathBuf>,
+
     #[clap(
         long = "key-name",
         value_name = "KEY_NAME",
@@ -81,10 +105,10 @@ pub struct KeysAddCmd {
     #[clap(
         long = "hd-path",
         value_name = "HD_PATH",
-        help = "Derivation path for this key",
-        default_value = "m/44'/118'/0'/0/0"
+        help = "Derivation path for this key (defaults to m/44'/309'/0'/0/0 for the CKB chains, \
+                m/44'/60'/0'/0/0 for the Ethereum-like chains and m/44'/118'/0'/0/0 otherwise)"
     )]
-    hd_path: String,
+    hd_path: Option<String>,
 
     #[clap(
         long = "overwrite",
@@ -104,8 +128,12 @@ impl KeysAddCmd {
             .clone()
             .unwrap_or_else(|| chain_config.key_name().to_string());
 
-        let hd_path = StandardHDPath::from_str(&self.hd_path)
-            .map_err(|_| eyre!("invalid derivation path: {}", self.hd_path))?;
+        let hd_path = self
+            .hd_path
+            .as_deref()
+            .unwrap_or_else(|| default_hd_path(chain_config.r#type()));
+        let hd_path = StandardHDPath::from_str(hd_path)
+            .map_err(|_| eyre!("invalid derivation path: {}", hd_path))?;
 
         Ok(KeysAddOptions {
             config: chain_config.clone(),
@@ -131,9 +159,13 @@ impl Runnable for KeysAddCmd {
             Ok(result) => result,
         };
 
-        // Check if --key-file or --mnemonic-file was given as input.
-        match (self.key_file.clone(), self.mnemonic_file.clone()) {
-            (Some(key_file), _) => {
+        // Check if --key-file, --mnemonic-file or --private-key-file was given as input.
+        match (
+            self.key_file.clone(),
+            self.mnemonic_file.clone(),
+            self.private_key_file.clone(),
+        ) {
+            (Some(key_file), _, _) => {
                 let key = add_key(
                     &opts.config,
                     &opts.name,
@@ -156,7 +188,7 @@ impl Runnable for KeysAddCmd {
                     .exit(),
                 }
             }
-            (_, Some(mnemonic_file)) => {
+            (_, Some(mnemonic_file), _) => {
                 let key = restore_key(
                     &mnemonic_file,
                     &opts.name,
@@ -180,11 +212,31 @@ impl Runnable for KeysAddCmd {
                     .exit(),
                 }
             }
+            (_, _, Some(private_key_file)) => {
+                let key =
+                    import_private_key(&opts.config, &opts.name, &private_key_file, self.overwrite);
+
+                match key {
+                    Ok(key) => Output::success_msg(format!(
+                        "Imported key '{}' ({}) on chain {}",
+                        opts.name,
+                        key.account(),
+                        opts.config.id(),
+                    ))
+                    .exit(),
+                    Err(e) => Output::error(format!(
+                        "An error occurred importing the key on chain {} from file {:?}: {}",
+                        self.chain_id, private_key_file, e
+                    ))
+                    .exit(),
+                }
+            }
             // This case should never trigger.
-            // The 'required' parameter for the flags will trigger an error if both flags have not been given.
-            // And the 'group' parameter for the flags will trigger an error if both flags are given.
+            // The 'required' parameter for the flags will trigger an error if none of the flags is given.
+            // And the 'group' parameter for the flags will trigger an error if several flags are given.
             _ => Output::error(
-                "--mnemonic-file and --key-file can't both be set or both None".to_string(),
+                "exactly one of --key-file, --mnemonic-file and --private-key-file must be set"
+                    .to_string(),
             )
             .exit(),
         }
@@ -198,15 +250,8 @@ pub fn add_key(
     hd_path: &StandardHDPath,
     overwrite: bool,
 ) -> eyre::Result<AnySigningKeyPair> {
-    let account_prefix = match config.r#type() {
-        ChainType::CosmosSdk => &config.cosmos().account_prefix,
-        ChainType::Eth => "eth",
-        ChainType::Axon => "axon",
-        ChainType::Ckb => "ckb",
-        ChainType::Ckb4Ibc => "ckb4ibc",
-    };
     let key_pair = {
-        let mut keyring = KeyRing::new_secp256k1(Store::Test, account_prefix, config.id())?;
+        let mut keyring = KeyRing::new_secp256k1(Store::Test, account_prefix(config), config.id())?;
 
         check_key_exists(&keyring, key_name, overwrite);
 
@@ -230,22 +275,41 @@ pub fn restore_key(
     let mnemonic_content =
         fs::read_to_string(mnemonic).map_err(|_| eyre!("error reading the mnemonic file"))?;
 
-    let account_prefix = match config.r#type() {
-        ChainType::CosmosSdk => &config.cosmos().account_prefix,
-        ChainType::Eth => "eth",
-        ChainType::Axon => "axon",
-        ChainType::Ckb => "ckb",
-        ChainType::Ckb4Ibc => "ckb4ibc",
-    };
     let key_pair = {
-        let mut keyring = KeyRing::new_secp256k1(Store::Test, account_prefix, config.id())?;
+        let mut keyring = KeyRing::new_secp256k1(Store::Test, account_prefix(config), config.id())?;
 
         check_key_exists(&keyring, key_name, overwrite);
 
         let key_pair = Secp256k1KeyPair::from_mnemonic(
             &mnemonic_content,
             hdpath,
-            &config.cosmos().address_type,
+            &address_type(config)?,
+            keyring.account_prefix(),
+        )?;
+
+        keyring.add_key(key_name, key_pair.clone())?;
+        key_pair.into()
+    };
+    Ok(key_pair)
+}
+
+pub fn import_private_key(
+    config: &ChainConfig,
+    key_name: &str,
+    file: &Path,
+    overwrite: bool,
+) -> eyre::Result<AnySigningKeyPair> {
+    let private_key =
+        fs::read_to_string(file).map_err(|_| eyre!("error reading the private key file"))?;
+
+    let key_pair = {
+        let mut keyring = KeyRing::new_secp256k1(Store::Test, account_prefix(config), config.id())?;
+
+        check_key_exists(&keyring, key_name, overwrite);
+
+        let key_pair = Secp256k1KeyPair::from_private_key(
+            &private_key,
+            &address_type(config)?,
             keyring.account_prefix(),
         )?;
 
@@ -255,6 +319,30 @@ pub fn restore_key(
     Ok(key_pair)
 }
 
+/// Derivation path of the keys restored from a mnemonic, following the coin type of the chain.
+fn default_hd_path(chain_type: ChainType) -> &'static str {
+    match chain_type {
+        ChainType::CosmosSdk => "m/44'/118'/0'/0/0",
+        ChainType::Ckb | ChainType::Ckb4Ibc => "m/44'/309'/0'/0/0",
+        ChainType::Eth | ChainType::Axon | ChainType::Evm => "m/44'/60'/0'/0/0",
+    }
+}
+
+/// Address type of the keys of the chain. The addresses of the CKB keys depend on the
+/// network of the chain, which is queried from its node.
+fn address_type(config: &ChainConfig) -> eyre::Result<AddressType> {
+    let address_type = match config.r#type() {
+        ChainType::CosmosSdk => config.cosmos().address_type.clone(),
+        ChainType::Ckb | ChainType::Ckb4Ibc => AddressType::Ckb {
+            is_mainnet: ckb_network(config)? == NetworkType::Mainnet,
+        },
+        ChainType::Eth | ChainType::Axon | ChainType::Evm => AddressType::Axon {
+            pk_type: ETH_SECP256K1_PK_TYPE.to_owned(),
+        },
+    };
+    Ok(address_type)
+}
+
 /// Check if the key with the given key name already exists.
 /// If it already exists and overwrite is false, abort the command with an error.
 /// If overwrite is true, output a warning message informing the key will be overwritten.
@@ -284,8 +372,9 @@ mod tests {
                 chain_id: ChainId::from_string("chain_id"),
                 key_file: Some(PathBuf::from("key_file")),
                 mnemonic_file: None,
+                private_key_file: None,
                 key_name: None,
-                hd_path: "m/44'/118'/0'/0/0".to_string(),
+                hd_path: None,
                 overwrite: false,
             },
             KeysAddCmd::parse_from(["test", "--chain", "chain_id", "--key-file", "key_file"])
@@ -299,8 +388,9 @@ mod tests {
                 chain_id: ChainId::from_string("chain_id"),
                 key_file: None,
                 mnemonic_file: Some(PathBuf::from("mnemonic_file")),
+                private_key_file: None,
                 key_name: None,
-                hd_path: "m/44'/118'/0'/0/0".to_string(),
+                hd_path: None,
                 overwrite: false
             },
             KeysAddCmd::parse_from([
@@ -320,8 +410,9 @@ mod tests {
                 chain_id: ChainId::from_string("chain_id"),
                 key_file: Some(PathBuf::from("key_file")),
                 mnemonic_file: None,
+                private_key_file: None,
                 key_name: None,
-                hd_path: "m/44'/118'/0'/0/0".to_string(),
+                hd_path: None,
                 overwrite: true,
             },
             KeysAddCmd::parse_from([
@@ -342,8 +433,9 @@ mod tests {
                 chain_id: ChainId::from_string("chain_id"),
                 key_file: None,
                 mnemonic_file: Some(PathBuf::from("mnemonic_file")),
+                private_key_file: None,
                 key_name: None,
-                hd_path: "m/44'/118'/0'/0/0".to_string(),
+                hd_path: None,
                 overwrite: true,
             },
             KeysAddCmd::parse_from([
@@ -357,6 +449,52 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_keys_add_private_key_file() {
+        assert_eq!(
+            KeysAddCmd {
+                chain_id: ChainId::from_string("chain_id"),
+                key_file: None,
+                mnemonic_file: None,
+                private_key_file: Some(PathBuf::from("private_key_file")),
+                key_name: None,
+                hd_path: None,
+                overwrite: false,
+            },
+            KeysAddCmd::parse_from([
+                "test",
+                "--chain",
+                "chain_id",
+                "--private-key-file",
+                "private_key_file"
+            ])
+        )
+    }
+
+    #[test]
+    fn test_keys_add_mnemonic_file_hd_path() {
+        assert_eq!(
+            KeysAddCmd {
+                chain_id: ChainId::from_string("chain_id"),
+                key_file: None,
+                mnemonic_file: Some(PathBuf::from("mnemonic_file")),
+                private_key_file: None,
+                key_name: None,
+                hd_path: Some("m/44'/309'/0'/0/0".to_string()),
+                overwrite: false,
+            },
+            KeysAddCmd::parse_from([
+                "test",
+                "--chain",
+                "chain_id",
+                "--mnemonic-file",
+                "mnemonic_file",
+                "--hd-path",
+                "m/44'/309'/0'/0/0"
+            ])
+        )
+    }
+
     #[test]
     fn test_keys_add_no_file_nor_mnemonic() {
         assert!(KeysAddCmd::try_parse_from(["test", "--chain", "chain_id"]).is_err());
@@ -376,6 +514,20 @@ mod tests {
         .is_err());
     }
 
+    #[test]
+    fn test_keys_add_mnemonic_and_private_key() {
+        assert!(KeysAddCmd::try_parse_from([
+            "test",
+            "--chain",
+            "chain_id",
+            "--mnemonic-file",
+            "mnemonic_file",
+            "--private-key-file",
+            "private_key_file"
+        ])
+        .is_err());
+    }
+
     #[test]
     fn test_keys_add_no_chain() {
         assert!(KeysAddCmd::try_parse_from(["test", "--key-file", "key_file"]).is_err());
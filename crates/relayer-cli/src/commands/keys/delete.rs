@@ -3,9 +3,8 @@ use abscissa_core::{Command, Runnable};
 
 use eyre::eyre;
 use ibc_relayer::{
-    chain::ChainType,
     config::{ChainConfig, Config},
-    keyring::{KeyRing, Store},
+    keyring::{account_prefix, KeyRing, Store},
 };
 use ibc_relayer_types::core::ics24_host::identifier::ChainId;
 
@@ -114,34 +113,16 @@ impl Runnable for KeysDeleteCmd {
 }
 
 pub fn delete_key(config: &ChainConfig, key_name: &str) -> eyre::Result<()> {
-    match config.r#type() {
-        ChainType::CosmosSdk => {
-            let mut keyring =
-                KeyRing::new_secp256k1(Store::Test, &config.cosmos().account_prefix, config.id())?;
-            keyring.remove_key(key_name)?;
-        }
-        ChainType::Eth => todo!(),
-        ChainType::Axon => todo!(),
-        ChainType::Ckb => todo!(),
-        ChainType::Ckb4Ibc => todo!(),
-    }
+    let mut keyring = KeyRing::new_secp256k1(Store::Test, account_prefix(config), config.id())?;
+    keyring.remove_key(key_name)?;
     Ok(())
 }
 
 pub fn delete_all_keys(config: &ChainConfig) -> eyre::Result<()> {
-    match config.r#type() {
-        ChainType::CosmosSdk => {
-            let mut keyring =
-                KeyRing::new_secp256k1(Store::Test, &config.cosmos().account_prefix, config.id())?;
-            let keys = keyring.keys()?;
-            for (key_name, _) in keys {
-                keyring.remove_key(&key_name)?;
-            }
-        }
-        ChainType::Eth => todo!(),
-        ChainType::Axon => todo!(),
-        ChainType::Ckb => todo!(),
-        ChainType::Ckb4Ibc => todo!(),
+    let mut keyring = KeyRing::new_secp256k1(Store::Test, account_prefix(config), config.id())?;
+    let keys = keyring.keys()?;
+    for (key_name, _) in keys {
+        keyring.remove_key(&key_name)?;
     }
     Ok(())
 }
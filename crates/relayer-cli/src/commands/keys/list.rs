@@ -4,11 +4,13 @@ use core::fmt::Write;
 use abscissa_core::clap::Parser;
 use abscissa_core::{Command, Runnable};
 
-use crate::conclude::Output;
+use crate::cli_utils::ckb_network;
+use crate::conclude::{exit_with_unrecoverable_error, Output};
 use crate::{application::app_config, conclude::json};
 use ibc_relayer::{
+    chain::ChainType,
     config::{ChainConfig, Config},
-    keyring::list_keys,
+    keyring::{list_keys, AnySigningKeyPair},
 };
 use ibc_relayer_types::core::ics24_host::identifier::ChainId;
 
@@ -45,20 +47,45 @@ impl Runnable for KeysListCmd {
             Ok(result) => result,
         };
 
-        match list_keys(&opts.chain_config) {
-            Ok(keys) if json() => {
-                let keys = keys.into_iter().collect::<HashMap<_, _>>();
-                Output::success(keys).exit()
+        let keys = match list_keys(&opts.chain_config) {
+            Ok(keys) => keys,
+            Err(e) => Output::error(e).exit(),
+        };
+
+        // The keys of the CKB chains are listed with their address on the network of the chain
+        if matches!(
+            opts.chain_config.r#type(),
+            ChainType::Ckb | ChainType::Ckb4Ibc
+        ) {
+            let network =
+                ckb_network(&opts.chain_config).unwrap_or_else(exit_with_unrecoverable_error);
+            let addresses = keys.into_iter().map(|(name, key)| {
+                let address = match &key {
+                    AnySigningKeyPair::Secp256k1(key_pair) => key_pair.ckb_address(network),
+                    AnySigningKeyPair::Ed25519(_) => key.account(),
+                };
+                (name, address)
+            });
+
+            if json() {
+                Output::success(addresses.collect::<HashMap<_, _>>()).exit()
             }
-            Ok(keys) => {
-                let mut msg = String::new();
-                for (name, key) in keys {
-                    let _ = write!(msg, "\n- {} ({})", name, key.account());
-                }
-                Output::success_msg(msg).exit()
+            let mut msg = String::new();
+            for (name, address) in addresses {
+                let _ = write!(msg, "\n- {name} ({address})");
             }
-            Err(e) => Output::error(e).exit(),
+            Output::success_msg(msg).exit()
+        }
+
+        if json() {
+            let keys = keys.into_iter().collect::<HashMap<_, _>>();
+            Output::success(keys).exit()
+        }
+        let mut msg = String::new();
+        for (name, key) in keys {
+            let _ = write!(msg, "\n- {} ({})", name, key.account());
         }
+        Output::success_msg(msg).exit()
     }
 }
 
@@ -148,6 +148,7 @@ fn subscribe(
             ChainConfig::Ckb(_) => "".parse().unwrap(),
             ChainConfig::Axon(_) => "".parse().unwrap(),
             ChainConfig::Ckb4Ibc(_) => "".parse().unwrap(),
+            ChainConfig::Evm(_) => "".parse().unwrap(),
         },
         compat_mode,
         rt,
@@ -0,0 +1,219 @@
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+use ibc_relayer::config::Config;
+use ibc_relayer::supervisor::pause::PauseTarget;
+use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, PortId};
+
+use crate::conclude::Output;
+use crate::error::Error;
+use crate::prelude::*;
+
+/// Pause the relaying of a running relayer for a chain or for a channel end.
+///
+/// The relayer keeps monitoring the chain but stops submitting transactions for the
+/// target until it is resumed, even across restarts if `global.pause_file` is set.
+/// The command is sent to the REST server of the running relayer.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct PauseCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the chain"
+    )]
+    chain_id: ChainId,
+
+    #[clap(
+        long = "port",
+        value_name = "PORT_ID",
+        requires = "channel-id",
+        help = "Identifier of the port, to target a single channel of the chain"
+    )]
+    port_id: Option<PortId>,
+
+    #[clap(
+        long = "channel",
+        alias = "chan",
+        value_name = "CHANNEL_ID",
+        requires = "port-id",
+        help = "Identifier of the channel, to target a single channel of the chain"
+    )]
+    channel_id: Option<ChannelId>,
+}
+
+impl Runnable for PauseCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        let target = pause_target(&self.chain_id, &self.port_id, &self.channel_id);
+
+        match pause(&config, &target) {
+            Ok(paused) => Output::success(paused).exit(),
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+/// Resume the relaying of a running relayer for a chain or a channel end paused with
+/// the `pause` command.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct ResumeCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the chain"
+    )]
+    chain_id: ChainId,
+
+    #[clap(
+        long = "port",
+        value_name = "PORT_ID",
+        requires = "channel-id",
+        help = "Identifier of the port, to target a single channel of the chain"
+    )]
+    port_id: Option<PortId>,
+
+    #[clap(
+        long = "channel",
+        alias = "chan",
+        value_name = "CHANNEL_ID",
+        requires = "port-id",
+        help = "Identifier of the channel, to target a single channel of the chain"
+    )]
+    channel_id: Option<ChannelId>,
+}
+
+impl Runnable for ResumeCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        let target = pause_target(&self.chain_id, &self.port_id, &self.channel_id);
+
+        match resume(&config, &target) {
+            Ok(paused) => Output::success(paused).exit(),
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+fn pause_target(
+    chain_id: &ChainId,
+    port_id: &Option<PortId>,
+    channel_id: &Option<ChannelId>,
+) -> PauseTarget {
+    match (port_id, channel_id) {
+        (Some(port_id), Some(channel_id)) => PauseTarget::Channel {
+            chain_id: chain_id.clone(),
+            port_id: port_id.clone(),
+            channel_id: channel_id.clone(),
+        },
+        _ => PauseTarget::Chain {
+            chain_id: chain_id.clone(),
+        },
+    }
+}
+
+#[cfg(feature = "rest-server")]
+pub(crate) fn rest_client(config: &Config) -> Result<ibc_relayer_rest::client::RestClient, Error> {
+    if !config.rest.enabled {
+        return Err(Error::rest(
+            "the REST server is disabled in the config".to_string(),
+        ));
+    }
+    let base_url = format!("http://{}:{}", config.rest.host, config.rest.port);
+    Ok(ibc_relayer_rest::client::RestClient::new(base_url))
+}
+
+#[cfg(feature = "rest-server")]
+fn pause(config: &Config, target: &PauseTarget) -> Result<Vec<PauseTarget>, Error> {
+    rest_client(config)?
+        .pause(target)
+        .map_err(|e| Error::rest(e.to_string()))
+}
+
+#[cfg(feature = "rest-server")]
+fn resume(config: &Config, target: &PauseTarget) -> Result<Vec<PauseTarget>, Error> {
+    rest_client(config)?
+        .resume(target)
+        .map_err(|e| Error::rest(e.to_string()))
+}
+
+#[cfg(not(feature = "rest-server"))]
+fn pause(_config: &Config, _target: &PauseTarget) -> Result<Vec<PauseTarget>, Error> {
+    Err(Error::rest(
+        "Forcerelay was built without REST support".to_string(),
+    ))
+}
+
+#[cfg(not(feature = "rest-server"))]
+fn resume(_config: &Config, _target: &PauseTarget) -> Result<Vec<PauseTarget>, Error> {
+    Err(Error::rest(
+        "Forcerelay was built without REST support".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pause_target, PauseCmd, ResumeCmd};
+
+    use abscissa_core::clap::Parser;
+    use ibc_relayer::supervisor::pause::PauseTarget;
+    use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, PortId};
+
+    #[test]
+    fn test_pause_chain() {
+        let cmd = PauseCmd::parse_from(["test", "--chain", "chain_id"]);
+        assert_eq!(
+            cmd,
+            PauseCmd {
+                chain_id: ChainId::from_string("chain_id"),
+                port_id: None,
+                channel_id: None,
+            }
+        );
+        assert_eq!(
+            pause_target(&cmd.chain_id, &cmd.port_id, &cmd.channel_id),
+            PauseTarget::Chain {
+                chain_id: ChainId::from_string("chain_id")
+            }
+        );
+    }
+
+    #[test]
+    fn test_resume_channel() {
+        let cmd = ResumeCmd::parse_from([
+            "test",
+            "--chain",
+            "chain_id",
+            "--port",
+            "transfer",
+            "--channel",
+            "channel-0",
+        ]);
+        assert_eq!(
+            pause_target(&cmd.chain_id, &cmd.port_id, &cmd.channel_id),
+            PauseTarget::Channel {
+                chain_id: ChainId::from_string("chain_id"),
+                port_id: PortId::transfer(),
+                channel_id: ChannelId::new(0),
+            }
+        );
+    }
+
+    #[test]
+    fn test_pause_channel_without_port() {
+        assert!(
+            PauseCmd::try_parse_from(["test", "--chain", "chain_id", "--channel", "channel-0"])
+                .is_err()
+        )
+    }
+
+    #[test]
+    fn test_pause_no_chain() {
+        assert!(PauseCmd::try_parse_from(["test"]).is_err())
+    }
+}
@@ -17,6 +17,7 @@ mod clients;
 mod connection;
 mod connections;
 mod packet;
+mod storage;
 mod transfer;
 mod tx;
 
@@ -48,6 +49,9 @@ pub enum QueryCmd {
     #[clap(subcommand)]
     Packet(QueryPacketCmds),
 
+    /// Query the storage occupied by the IBC states of a chain
+    Storage(storage::QueryStorageCmd),
+
     /// Query information about transactions
     #[clap(subcommand)]
     Tx(tx::QueryTxCmd),
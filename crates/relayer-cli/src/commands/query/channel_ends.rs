@@ -3,11 +3,14 @@ use abscissa_core::{Command, Runnable};
 use serde::{Deserialize, Serialize};
 
 use eyre::eyre;
+use ibc_relayer::account::Balance;
+use ibc_relayer::chain::cosmos::types::gas::GasConfig;
 use ibc_relayer::chain::handle::{BaseChainHandle, ChainHandle};
 use ibc_relayer::chain::requests::{
     IncludeProof, QueryChannelRequest, QueryClientStateRequest, QueryConnectionRequest, QueryHeight,
 };
 use ibc_relayer::client_state::AnyClientState;
+use ibc_relayer::config::ChainConfig;
 use ibc_relayer::registry::Registry;
 use ibc_relayer_types::core::ics02_client::client_state::ClientState;
 use ibc_relayer_types::core::ics03_connection::connection::ConnectionEnd;
@@ -71,6 +74,8 @@ pub struct ChannelEnds {
     pub counterparty_channel_end: ChannelEnd,
     pub counterparty_connection_end: ConnectionEnd,
     pub counterparty_client_state: AnyClientState,
+    pub funding: Option<AccountFunding>,
+    pub counterparty_funding: Option<AccountFunding>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -86,6 +91,64 @@ pub struct ChannelEndsSummary {
     counterparty_connection_id: ConnectionId,
     counterparty_channel_id: ChannelId,
     counterparty_port_id: PortId,
+
+    funding: Option<AccountFunding>,
+    counterparty_funding: Option<AccountFunding>,
+}
+
+/// Balance of the relayer account on one end of the path, in the denom it pays fees with
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccountFunding {
+    pub account: String,
+    pub balance: Balance,
+    /// Whether the balance covers the fees of another transaction, e.g. a client update
+    pub sufficient: bool,
+}
+
+/// Minimum balance needed by the relayer account to pay for another transaction.
+///
+/// Only Cosmos chains configure an upper bound on the fees of a transaction, any
+/// non-zero balance is deemed sufficient on the other chains.
+fn required_balance(config: &ChainConfig) -> f64 {
+    match config {
+        ChainConfig::Cosmos(config) => GasConfig::from(config)
+            .max_fee
+            .amount
+            .iter()
+            .filter(|coin| coin.denom == config.gas_price.denom)
+            .filter_map(|coin| coin.amount.parse::<f64>().ok())
+            .sum(),
+        _ => f64::MIN_POSITIVE,
+    }
+}
+
+fn query_funding<Chain: ChainHandle>(chain: &Chain) -> eyre::Result<AccountFunding> {
+    let account = chain.get_key()?.account();
+    let balance = chain.query_balance(None, None)?;
+    let required = required_balance(&chain.config()?);
+    let sufficient = balance
+        .amount
+        .parse::<f64>()
+        .map_or(false, |amount| amount >= required);
+
+    Ok(AccountFunding {
+        account,
+        balance,
+        sufficient,
+    })
+}
+
+/// The funding of a chain is only informative, failing to query it does not fail the report.
+pub fn try_query_funding<Chain: ChainHandle>(chain: &Chain) -> Option<AccountFunding> {
+    query_funding(chain)
+        .map_err(|e| {
+            warn!(
+                "failed to query the funding of the relayer account on chain {}: {}",
+                chain.id(),
+                e
+            )
+        })
+        .ok()
 }
 
 fn do_run<Chain: ChainHandle>(cmd: &QueryChannelEndsCmd) -> eyre::Result<()> {
@@ -213,6 +276,9 @@ fn do_run<Chain: ChainHandle>(cmd: &QueryChannelEndsCmd) -> eyre::Result<()> {
         IncludeProof::No,
     )?;
 
+    let funding = try_query_funding(&chain);
+    let counterparty_funding = try_query_funding(&counterparty_chain);
+
     if cmd.verbose {
         let res = ChannelEnds {
             channel_end,
@@ -222,6 +288,9 @@ fn do_run<Chain: ChainHandle>(cmd: &QueryChannelEndsCmd) -> eyre::Result<()> {
             counterparty_channel_end,
             counterparty_connection_end,
             counterparty_client_state,
+
+            funding,
+            counterparty_funding,
         };
 
         Output::success(res).exit();
@@ -238,6 +307,9 @@ fn do_run<Chain: ChainHandle>(cmd: &QueryChannelEndsCmd) -> eyre::Result<()> {
             counterparty_connection_id,
             counterparty_channel_id,
             counterparty_port_id,
+
+            funding,
+            counterparty_funding,
         };
 
         Output::success(res).exit();
@@ -17,7 +17,7 @@ use ibc_relayer_types::core::ics24_host::identifier::{
     ChainId, ChannelId, ConnectionId, PortChannelId, PortId,
 };
 
-use crate::commands::query::channel_ends::ChannelEnds;
+use crate::commands::query::channel_ends::{try_query_funding, ChannelEnds};
 use crate::conclude::Output;
 use crate::prelude::*;
 
@@ -267,6 +267,8 @@ fn query_channel_ends<Chain: ChainHandle>(
         counterparty_channel_end,
         counterparty_connection_end,
         counterparty_client_state,
+        funding: try_query_funding(chain),
+        counterparty_funding: try_query_funding(&counterparty_chain),
     })
 }
 
@@ -0,0 +1,61 @@
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+use ibc_relayer::chain::handle::ChainHandle;
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+use crate::cli_utils::spawn_chain_runtime;
+use crate::conclude::{exit_with_unrecoverable_error, Output};
+use crate::prelude::*;
+
+/// Query the pending packets of a chain which are not relayed because their data or
+/// their memo exceed the size ceilings in the config of the chain.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct QueryOversizedPacketsCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the chain to query"
+    )]
+    chain_id: ChainId,
+}
+
+// hermes query packet oversized --chain ckb4ibc-0
+impl Runnable for QueryOversizedPacketsCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        let chain = spawn_chain_runtime(&config, &self.chain_id)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+
+        match chain.query_oversized_packets() {
+            Ok(packets) => Output::success(packets).exit(),
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueryOversizedPacketsCmd;
+
+    use abscissa_core::clap::Parser;
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+    #[test]
+    fn test_query_oversized_packets() {
+        assert_eq!(
+            QueryOversizedPacketsCmd {
+                chain_id: ChainId::from_string("chain_id")
+            },
+            QueryOversizedPacketsCmd::parse_from(["test", "--chain", "chain_id"])
+        )
+    }
+
+    #[test]
+    fn test_query_oversized_packets_no_chain() {
+        assert!(QueryOversizedPacketsCmd::try_parse_from(["test"]).is_err())
+    }
+}
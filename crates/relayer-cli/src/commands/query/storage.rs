@@ -0,0 +1,61 @@
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+use ibc_relayer::chain::handle::ChainHandle;
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+use crate::cli_utils::spawn_chain_runtime;
+use crate::conclude::{exit_with_unrecoverable_error, Output};
+use crate::prelude::*;
+
+/// Query the number and the capacity of the cells holding the IBC states of a chain,
+/// broken down by cell type and by channel.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct QueryStorageCmd {
+    #[clap(
+        long = "chain",
+        required = true,
+        value_name = "CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the chain to query"
+    )]
+    chain_id: ChainId,
+}
+
+// hermes query storage --chain ckb4ibc-0
+impl Runnable for QueryStorageCmd {
+    fn run(&self) {
+        let config = app_config();
+
+        let chain = spawn_chain_runtime(&config, &self.chain_id)
+            .unwrap_or_else(exit_with_unrecoverable_error);
+
+        match chain.query_storage_footprint() {
+            Ok(footprint) => Output::success(footprint).exit(),
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueryStorageCmd;
+
+    use abscissa_core::clap::Parser;
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+    #[test]
+    fn test_query_storage() {
+        assert_eq!(
+            QueryStorageCmd {
+                chain_id: ChainId::from_string("chain_id")
+            },
+            QueryStorageCmd::parse_from(["test", "--chain", "chain_id"])
+        )
+    }
+
+    #[test]
+    fn test_query_storage_no_chain() {
+        assert!(QueryStorageCmd::try_parse_from(["test"]).is_err())
+    }
+}
@@ -0,0 +1,13 @@
+//! `recover` subcommand
+
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+
+mod path;
+
+#[derive(Command, Debug, Parser, Runnable)]
+pub enum RecoverCmds {
+    /// Re-establish a path whose client is frozen over fresh clients, connection and
+    /// channel
+    Path(path::RecoverPathCmd),
+}
@@ -0,0 +1,292 @@
+use abscissa_core::clap::Parser;
+use abscissa_core::{Command, Runnable};
+use console::style;
+use dialoguer::Confirm;
+use serde::Serialize;
+
+use ibc_relayer::chain::counterparty::{
+    channel_on_destination, pending_packet_summary, PendingPackets,
+};
+use ibc_relayer::chain::handle::{BaseChainHandle, ChainHandle};
+use ibc_relayer::chain::requests::{IncludeProof, QueryClientStateRequest, QueryHeight};
+use ibc_relayer::channel::Channel;
+use ibc_relayer::connection::Connection;
+use ibc_relayer::foreign_client::ForeignClient;
+use ibc_relayer_types::core::ics02_client::client_state::ClientState;
+use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, ClientId, PortId};
+
+use crate::cli_utils::spawn_chain_counterparty;
+use crate::conclude::Output;
+use crate::error::Error;
+use crate::prelude::*;
+
+static PROMPT: &str = "Are you sure you want new clients, a new connection and a new channel to be created? The packets pending on the old channel are not moved to the new one.";
+
+/// Client of the old path found frozen
+#[derive(Debug, Serialize)]
+struct FrozenClient {
+    chain_id: ChainId,
+    client_id: ClientId,
+}
+
+/// Packets left pending on both ends of the old channel
+#[derive(Debug, Serialize)]
+struct StrandedPackets {
+    a: PendingPackets,
+    b: PendingPackets,
+}
+
+#[derive(Debug, Serialize)]
+struct RecoveredPath<Chain: ChainHandle> {
+    frozen_clients: Vec<FrozenClient>,
+    channel: Channel<Chain, Chain>,
+    stranded_packets: StrandedPackets,
+}
+
+/// Re-establish the path of a channel once a client underlying it is frozen, e.g.
+/// after misbehaviour was submitted.
+///
+/// A frozen client can't be used anymore, so the command creates fresh clients on both
+/// chains, opens a connection over them with the delay of the old connection, and then
+/// a channel between the same ports, with the same ordering and version.
+///
+/// The packets pending on the old channel can't be relayed anymore. None of the
+/// application modules lets a packet move to another channel, so they are only reported
+/// for the operators to settle them at the application level, e.g. by sending the
+/// transfers again over the new channel.
+#[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
+pub struct RecoverPathCmd {
+    #[clap(
+        long = "a-chain",
+        required = true,
+        value_name = "A_CHAIN_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the chain at one end of the channel to recover"
+    )]
+    chain_a: ChainId,
+
+    #[clap(
+        long = "a-port",
+        required = true,
+        value_name = "A_PORT_ID",
+        help_heading = "REQUIRED",
+        help = "Port identifier of the channel on chain `a`"
+    )]
+    port_a: PortId,
+
+    #[clap(
+        long = "a-channel",
+        visible_alias = "a-chan",
+        required = true,
+        value_name = "A_CHANNEL_ID",
+        help_heading = "REQUIRED",
+        help = "Identifier of the channel to recover on chain `a`"
+    )]
+    channel_a: ChannelId,
+
+    #[clap(long = "yes", help = "Skip the confirmation of the new path creation")]
+    yes: bool,
+}
+
+impl RecoverPathCmd {
+    fn execute(&self) -> Result<RecoveredPath<BaseChainHandle>, Error> {
+        let config = app_config();
+
+        let (chains, chan_conn_cli) = spawn_chain_counterparty::<BaseChainHandle>(
+            &config,
+            &self.chain_a,
+            &self.port_a,
+            &self.channel_a,
+        )?;
+
+        let mut frozen_clients = vec![];
+        if chan_conn_cli.client.client_state.is_frozen() {
+            frozen_clients.push(FrozenClient {
+                chain_id: chains.src.id(),
+                client_id: chan_conn_cli.client.client_id.clone(),
+            });
+        }
+        let counterparty_client_id = chan_conn_cli
+            .connection
+            .connection_end
+            .counterparty()
+            .client_id()
+            .clone();
+        let (counterparty_client_state, _) = chains
+            .dst
+            .query_client_state(
+                QueryClientStateRequest {
+                    client_id: counterparty_client_id.clone(),
+                    height: QueryHeight::Latest,
+                },
+                IncludeProof::No,
+            )
+            .map_err(Error::relayer)?;
+        if counterparty_client_state.is_frozen() {
+            frozen_clients.push(FrozenClient {
+                chain_id: chains.dst.id(),
+                client_id: counterparty_client_id,
+            });
+        }
+        if frozen_clients.is_empty() {
+            return Err(Error::client_not_frozen(
+                self.chain_a.clone(),
+                self.port_a.clone(),
+                self.channel_a.clone(),
+            ));
+        }
+
+        let channel_end = &chan_conn_cli.channel.channel_end;
+        let counterparty_channel = channel_on_destination(
+            &chan_conn_cli.channel,
+            &chan_conn_cli.connection,
+            &chains.dst,
+        )
+        .map_err(Error::supervisor)?
+        .ok_or_else(|| Error::missing_counterparty_channel_id(chan_conn_cli.channel.clone()))?;
+        let stranded_packets = StrandedPackets {
+            a: pending_packet_summary(&chains.src, &chains.dst, &chan_conn_cli.channel)
+                .map_err(Error::supervisor)?,
+            b: pending_packet_summary(&chains.dst, &chains.src, &counterparty_channel)
+                .map_err(Error::supervisor)?,
+        };
+
+        info!(
+            "creating new clients, a new connection and a new channel with order {} to replace channel {}/{} on chain {}",
+            channel_end.ordering(),
+            self.port_a,
+            self.channel_a,
+            self.chain_a
+        );
+
+        let client_a = ForeignClient::new(chains.src.clone(), chains.dst.clone())
+            .map_err(Error::foreign_client)?;
+        let client_b = ForeignClient::new(chains.dst.clone(), chains.src.clone())
+            .map_err(Error::foreign_client)?;
+
+        let connection = Connection::new(
+            client_a,
+            client_b,
+            chan_conn_cli.connection.connection_end.delay_period(),
+        )
+        .map_err(Error::connection)?;
+
+        let channel = Channel::new(
+            connection,
+            *channel_end.ordering(),
+            self.port_a.clone(),
+            counterparty_channel.port_id.clone(),
+            Some(channel_end.version().clone()),
+        )
+        .map_err(Error::channel)?;
+
+        Ok(RecoveredPath {
+            frozen_clients,
+            channel,
+            stranded_packets,
+        })
+    }
+}
+
+// forcerelay recover path --a-chain ckb4ibc-0 --a-port transfer --a-channel channel-0
+impl Runnable for RecoverPathCmd {
+    fn run(&self) {
+        if !self.yes {
+            match Confirm::new()
+                .with_prompt(format!("{}: {}", style("WARN").yellow(), PROMPT))
+                .interact()
+            {
+                Ok(true) => {}
+                Ok(false) => {
+                    Output::error("You elected not to recover the path".to_string()).exit()
+                }
+                Err(e) => Output::error(format!(
+                    "An error occurred while waiting for user input: {e}"
+                ))
+                .exit(),
+            }
+        }
+
+        match self.execute() {
+            Ok(recovered) => Output::success(recovered).exit(),
+            Err(e) => Output::error(e).exit(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecoverPathCmd;
+
+    use std::str::FromStr;
+
+    use abscissa_core::clap::Parser;
+    use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, PortId};
+
+    #[test]
+    fn test_recover_path() {
+        assert_eq!(
+            RecoverPathCmd {
+                chain_a: ChainId::from_string("chain_a"),
+                port_a: PortId::from_str("transfer").unwrap(),
+                channel_a: ChannelId::from_str("channel-0").unwrap(),
+                yes: false,
+            },
+            RecoverPathCmd::parse_from([
+                "test",
+                "--a-chain",
+                "chain_a",
+                "--a-port",
+                "transfer",
+                "--a-channel",
+                "channel-0"
+            ])
+        )
+    }
+
+    #[test]
+    fn test_recover_path_yes() {
+        assert_eq!(
+            RecoverPathCmd {
+                chain_a: ChainId::from_string("chain_a"),
+                port_a: PortId::from_str("transfer").unwrap(),
+                channel_a: ChannelId::from_str("channel-0").unwrap(),
+                yes: true,
+            },
+            RecoverPathCmd::parse_from([
+                "test",
+                "--a-chain",
+                "chain_a",
+                "--a-port",
+                "transfer",
+                "--a-chan",
+                "channel-0",
+                "--yes"
+            ])
+        )
+    }
+
+    #[test]
+    fn test_recover_path_no_channel() {
+        assert!(RecoverPathCmd::try_parse_from([
+            "test",
+            "--a-chain",
+            "chain_a",
+            "--a-port",
+            "transfer"
+        ])
+        .is_err())
+    }
+
+    #[test]
+    fn test_recover_path_no_chain() {
+        assert!(RecoverPathCmd::try_parse_from([
+            "test",
+            "--a-port",
+            "transfer",
+            "--a-channel",
+            "channel-0"
+        ])
+        .is_err())
+    }
+}
@@ -1,19 +1,22 @@
 use ibc_relayer::supervisor::SupervisorOptions;
 use std::error::Error;
 use std::io;
+use std::path::{Path, PathBuf};
 
 use abscissa_core::clap::Parser;
 use abscissa_core::{Command, Runnable};
 use crossbeam_channel::Sender;
 
 use ibc_relayer::chain::handle::{CachingChainHandle, ChainHandle};
-use ibc_relayer::config::Config;
+use ibc_relayer::config::ckb4ibc::MonitorStart;
+use ibc_relayer::config::{ChainConfig, Config};
 use ibc_relayer::registry::SharedRegistry;
 use ibc_relayer::rest;
 use ibc_relayer::supervisor::{cmd::SupervisorCmd, spawn_supervisor, SupervisorHandle};
 
 use crate::conclude::json;
 use crate::conclude::Output;
+use crate::config::Diagnostic;
 use crate::prelude::*;
 
 #[derive(Clone, Command, Debug, Parser, PartialEq, Eq)]
@@ -23,11 +26,45 @@ pub struct StartCmd {
         help = "Force a full scan of the chains for clients, connections and channels"
     )]
     full_scan: bool,
+
+    #[clap(
+        long = "from-tip",
+        group = "monitor_start",
+        help = "Scan the CKB4IBC chains without any progress yet from their tip, ignoring the IBC cells committed before"
+    )]
+    from_tip: bool,
+
+    #[clap(
+        long = "from-height",
+        value_name = "HEIGHT",
+        group = "monitor_start",
+        help = "Scan the CKB4IBC chains without any progress yet from the given block"
+    )]
+    from_height: Option<u64>,
+}
+
+impl StartCmd {
+    /// Start of the event monitors requested on the command line, overriding the one
+    /// of the chain configs
+    fn monitor_start(&self) -> Option<MonitorStart> {
+        if self.from_tip {
+            Some(MonitorStart::Tip)
+        } else {
+            self.from_height.map(MonitorStart::Height)
+        }
+    }
 }
 
 impl Runnable for StartCmd {
     fn run(&self) {
-        let config = (*app_config()).clone();
+        let mut config = (*app_config()).clone();
+        if let Some(monitor_start) = self.monitor_start() {
+            for chain_config in config.chains.iter_mut() {
+                if let ChainConfig::Ckb4Ibc(chain_config) = chain_config {
+                    chain_config.monitor_start = monitor_start;
+                }
+            }
+        }
 
         let supervisor_handle = make_supervisor::<CachingChainHandle>(config, self.full_scan)
             .unwrap_or_else(|e| {
@@ -35,10 +72,12 @@ impl Runnable for StartCmd {
             });
 
         match crate::config::config_path() {
-            Some(_) => {
-                register_signals(supervisor_handle.sender.clone()).unwrap_or_else(|e| {
-                    warn!("failed to install signal handler: {}", e);
-                });
+            Some(config_path) => {
+                register_signals(config_path, supervisor_handle.sender.clone()).unwrap_or_else(
+                    |e| {
+                        warn!("failed to install signal handler: {}", e);
+                    },
+                );
             }
             None => {
                 warn!("cannot figure out configuration path, skipping registration of signal handlers");
@@ -52,13 +91,13 @@ impl Runnable for StartCmd {
 }
 
 /// Register the SIGHUP and SIGUSR1 signals, and notify the supervisor.
-/// - [DEPRECATED] SIGHUP: Trigger a reload of the configuration.
+/// - SIGHUP: Reload the chain configurations from the configuration file.
 /// - SIGUSR1: Ask the supervisor to dump its state and print it to the console.
-fn register_signals(tx_cmd: Sender<SupervisorCmd>) -> Result<(), io::Error> {
+fn register_signals(config_path: PathBuf, tx_cmd: Sender<SupervisorCmd>) -> Result<(), io::Error> {
     use signal_hook::{consts::signal::*, iterator::Signals};
 
     let sigs = vec![
-        SIGHUP,  // Reload of configuration (disabled)
+        SIGHUP,  // Reload of configuration
         SIGUSR1, // Dump state
     ];
 
@@ -67,10 +106,20 @@ fn register_signals(tx_cmd: Sender<SupervisorCmd>) -> Result<(), io::Error> {
     std::thread::spawn(move || {
         for signal in &mut signals {
             match signal {
-                SIGHUP => warn!(
-                    "configuration reloading via SIGHUP has been disabled, \
-                     the signal handler will be removed in the future"
-                ),
+                SIGHUP => {
+                    info!("reloading configuration (triggered by SIGHUP)");
+
+                    match reload_config(&config_path) {
+                        Ok(config) => {
+                            if let Err(e) =
+                                tx_cmd.try_send(SupervisorCmd::UpdateConfig(Box::new(config)))
+                            {
+                                error!("failed to send the configuration to the supervisor: {}", e)
+                            }
+                        }
+                        Err(e) => error!("failed to reload configuration: {}", e),
+                    }
+                }
                 SIGUSR1 => {
                     info!("dumping state (triggered by SIGUSR1)");
 
@@ -101,6 +150,20 @@ fn register_signals(tx_cmd: Sender<SupervisorCmd>) -> Result<(), io::Error> {
     Ok(())
 }
 
+/// Load and validate the configuration file, so that an invalid configuration is
+/// rejected before it reaches the supervisor.
+fn reload_config(config_path: &Path) -> Result<Config, Box<dyn Error + Send + Sync>> {
+    let config = ibc_relayer::config::load(config_path)?;
+
+    match crate::config::validate_config(&config) {
+        Ok(()) => {}
+        Err(Diagnostic::Warning(e)) => warn!("relayer may be misconfigured: {}", e),
+        Err(Diagnostic::Error(e)) => return Err(e.into()),
+    }
+
+    Ok(config)
+}
+
 #[cfg(feature = "rest-server")]
 fn spawn_rest_server(config: &Config) -> Option<rest::Receiver> {
     let _span = tracing::error_span!("rest").entered();
@@ -194,12 +257,18 @@ fn make_supervisor<Chain: ChainHandle>(
 mod tests {
     use super::StartCmd;
 
+    use ibc_relayer::config::ckb4ibc::MonitorStart;
+
     use abscissa_core::clap::Parser;
 
     #[test]
     fn test_start_required_only() {
         assert_eq!(
-            StartCmd { full_scan: false },
+            StartCmd {
+                full_scan: false,
+                from_tip: false,
+                from_height: None,
+            },
             StartCmd::parse_from(["test"])
         )
     }
@@ -207,8 +276,29 @@ mod tests {
     #[test]
     fn test_start_full_scan() {
         assert_eq!(
-            StartCmd { full_scan: true },
+            StartCmd {
+                full_scan: true,
+                from_tip: false,
+                from_height: None,
+            },
             StartCmd::parse_from(["test", "--full-scan"])
         )
     }
+
+    #[test]
+    fn test_start_from_tip() {
+        let cmd = StartCmd::parse_from(["test", "--from-tip"]);
+        assert_eq!(cmd.monitor_start(), Some(MonitorStart::Tip));
+    }
+
+    #[test]
+    fn test_start_from_height() {
+        let cmd = StartCmd::parse_from(["test", "--from-height", "1000"]);
+        assert_eq!(cmd.monitor_start(), Some(MonitorStart::Height(1000)));
+    }
+
+    #[test]
+    fn test_start_from_tip_and_height() {
+        assert!(StartCmd::try_parse_from(["test", "--from-tip", "--from-height", "1000"]).is_err())
+    }
 }
@@ -2,16 +2,20 @@ use core::{
     fmt::{Display, Error as FmtError, Formatter},
     time::Duration,
 };
+use std::str::FromStr;
 use std::thread;
 
 use abscissa_core::clap::Parser;
 use abscissa_core::{Command, Runnable};
+use ckb_types::H256;
+use console::style;
+use dialoguer::Confirm;
 
 use ibc_relayer::chain::handle::ChainHandle;
 use ibc_relayer::chain::requests::{
     IncludeProof, PageRequest, QueryClientStateRequest, QueryClientStatesRequest, QueryHeight,
 };
-use ibc_relayer::config::Config;
+use ibc_relayer::config::{ChainConfig, Config};
 use ibc_relayer::event::IbcEventWithHeight;
 use ibc_relayer::foreign_client::{CreateOptions, ForeignClient};
 use ibc_relayer_types::core::ics02_client::client_state::ClientState;
@@ -19,7 +23,7 @@ use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ClientId};
 use ibc_relayer_types::events::IbcEvent;
 use ibc_relayer_types::Height;
 use tendermint_light_client_verifier::types::TrustThreshold;
-use tracing::debug;
+use tracing::{debug, info};
 
 use crate::application::app_config;
 use crate::cli_utils::{spawn_chain_runtime, spawn_chain_runtime_generic, ChainHandlePair};
@@ -71,6 +75,63 @@ pub struct TxCreateClientCmd {
     /// and trusted validator set is sufficient for a commit to be accepted going forward.
     #[clap(long = "trust-threshold", value_name = "TRUST_THRESHOLD", parse(try_from_str = parse_trust_threshold))]
     trust_threshold: Option<TrustThreshold>,
+
+    /// Derive the client parameters from the on-chain parameters of the reference chain.
+    ///
+    /// The staking parameters of a Cosmos chain give the trusting period and the trust
+    /// threshold, while only the fork schedule of an Ethereum chain is reported.
+    /// The explicit options take precedence over the derived parameters, which are
+    /// printed in an interactive yes/no prompt unless the `--yes` flag is appended.
+    #[clap(long = "auto")]
+    auto: bool,
+
+    #[clap(
+        long = "yes",
+        requires = "auto",
+        help = "Skip the confirmation of the derived client parameters"
+    )]
+    yes: bool,
+}
+
+impl TxCreateClientCmd {
+    /// Query the client parameters derived from the reference chain and let the user
+    /// confirm them before the client is created.
+    fn derive_options<Chain: ChainHandle>(&self, src_chain: &Chain) -> CreateOptions {
+        let params = match src_chain.query_client_params() {
+            Ok(params) => params,
+            Err(e) => Output::error(e).exit(),
+        };
+        let summary =
+            serde_json::to_string_pretty(&params).unwrap_or_else(|e| Output::error(e).exit());
+
+        if self.yes {
+            info!(
+                "derived client parameters from {}: {summary}",
+                self.src_chain_id
+            );
+        } else {
+            match Confirm::new()
+                .with_prompt(format!(
+                    "{} {}:\n{summary}\nCreate the client with these parameters?",
+                    style("Client parameters derived from").cyan(),
+                    self.src_chain_id,
+                ))
+                .interact()
+            {
+                Ok(true) => {}
+                Ok(false) => Output::error(
+                    "You elected not to create the client with the derived parameters".to_string(),
+                )
+                .exit(),
+                Err(e) => Output::error(format!(
+                    "An error occurred while waiting for user input: {e}"
+                ))
+                .exit(),
+            }
+        }
+
+        params.create_options()
+    }
 }
 
 /// Sample to run this tx:
@@ -88,12 +149,24 @@ impl Runnable for TxCreateClientCmd {
             Err(e) => Output::error(e).exit(),
         };
 
+        let derived = if self.auto {
+            self.derive_options(&chains.src)
+        } else {
+            CreateOptions::default()
+        };
+
         let client = ForeignClient::restore(ClientId::default(), chains.dst, chains.src);
 
         let options = CreateOptions {
-            max_clock_drift: self.clock_drift.map(Into::into),
-            trusting_period: self.trusting_period.map(Into::into),
-            trust_threshold: self.trust_threshold.map(Into::into),
+            max_clock_drift: self.clock_drift.map(Into::into).or(derived.max_clock_drift),
+            trusting_period: self
+                .trusting_period
+                .map(Into::into)
+                .or(derived.trusting_period),
+            trust_threshold: self
+                .trust_threshold
+                .map(Into::into)
+                .or(derived.trust_threshold),
         };
 
         // Trigger client creation via the "build" interface, so that we obtain the resulting event
@@ -141,11 +214,79 @@ pub struct TxUpdateClientCmd {
         help = "The trusted height of the client update. Leave unspecified for latest height."
     )]
     trusted_height: Option<u64>,
+
+    /// Update the client with the given type args instead of the one bound in the config.
+    ///
+    /// This allows refreshing a client created by someone else on a CKB4IBC host chain,
+    /// which brings up an interactive yes/no prompt unless the `--yes` flag is appended.
+    #[clap(
+        long = "client-type-args",
+        value_name = "CLIENT_TYPE_ARGS",
+        parse(try_from_str = parse_type_args)
+    )]
+    client_type_args: Option<H256>,
+
+    #[clap(
+        long = "yes",
+        requires = "client-type-args",
+        help = "Skip third-party client confirmation"
+    )]
+    yes: bool,
+}
+
+static THIRD_PARTY_CLIENT_PROMPT: &str =
+    "Are you sure you want to update a client which is not bound in the configuration?";
+
+impl TxUpdateClientCmd {
+    /// Rebind the host chain in `config` to the client given by `--client-type-args`.
+    fn override_client_type_args(&self, config: &mut Config, type_args: &H256) {
+        match config.find_chain_mut(&self.dst_chain_id) {
+            Some(ChainConfig::Ckb4Ibc(chain_config)) => {
+                chain_config.client_type_args = type_args.clone();
+            }
+            Some(_) => Output::error(format!(
+                "`--client-type-args` is only supported by CKB4IBC chains, but '{}' is not",
+                self.dst_chain_id
+            ))
+            .exit(),
+            None => Output::error(format!(
+                "chain '{}' not found in configuration file",
+                self.dst_chain_id
+            ))
+            .exit(),
+        }
+
+        if self.yes {
+            return;
+        }
+        match Confirm::new()
+            .with_prompt(format!(
+                "{}: {}\n{}: {:#x}",
+                style("WARN").yellow(),
+                THIRD_PARTY_CLIENT_PROMPT,
+                style("Client type args").cyan(),
+                type_args
+            ))
+            .interact()
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                Output::error("You elected not to update the third-party client".to_string()).exit()
+            }
+            Err(e) => Output::error(format!(
+                "An error occurred while waiting for user input: {e}"
+            ))
+            .exit(),
+        }
+    }
 }
 
 impl Runnable for TxUpdateClientCmd {
     fn run(&self) {
-        let config = app_config();
+        let mut config = (*app_config()).clone();
+        if let Some(type_args) = &self.client_type_args {
+            self.override_client_type_args(&mut config, type_args);
+        }
 
         let dst_chain = match spawn_chain_runtime(&config, &self.dst_chain_id) {
             Ok(handle) => handle,
@@ -482,6 +623,11 @@ fn parse_trust_threshold(input: &str) -> Result<TrustThreshold, Error> {
         .map_err(|e| Error::cli_arg(format!("invalid trust threshold fraction: {e}")))
 }
 
+pub(crate) fn parse_type_args(input: &str) -> Result<H256, Error> {
+    let hex = input.strip_prefix("0x").unwrap_or(input);
+    H256::from_str(hex).map_err(|e| Error::cli_arg(format!("invalid type args: {e}")))
+}
+
 type UpgradeClientResult = Result<Vec<IbcEvent>, Error>;
 type UpgradeClientsForChainResult = Result<Vec<UpgradeClientResult>, Error>;
 
@@ -558,8 +704,8 @@ impl OutputBuffer {
 #[cfg(test)]
 mod tests {
     use super::{
-        parse_trust_threshold, TxCreateClientCmd, TxUpdateClientCmd, TxUpgradeClientCmd,
-        TxUpgradeClientsCmd,
+        parse_trust_threshold, parse_type_args, TxCreateClientCmd, TxUpdateClientCmd,
+        TxUpgradeClientCmd, TxUpgradeClientsCmd,
     };
 
     use std::str::FromStr;
@@ -592,7 +738,9 @@ mod tests {
                 src_chain_id: ChainId::from_string("reference_chain"),
                 clock_drift: None,
                 trusting_period: None,
-                trust_threshold: None
+                trust_threshold: None,
+                auto: false,
+                yes: false
             },
             TxCreateClientCmd::parse_from([
                 "test",
@@ -612,7 +760,9 @@ mod tests {
                 src_chain_id: ChainId::from_string("reference_chain"),
                 clock_drift: Some("5s".parse::<Duration>().unwrap()),
                 trusting_period: None,
-                trust_threshold: None
+                trust_threshold: None,
+                auto: false,
+                yes: false
             },
             TxCreateClientCmd::parse_from([
                 "test",
@@ -630,7 +780,9 @@ mod tests {
                 src_chain_id: ChainId::from_string("reference_chain"),
                 clock_drift: Some("3s".parse::<Duration>().unwrap()),
                 trusting_period: None,
-                trust_threshold: None
+                trust_threshold: None,
+                auto: false,
+                yes: false
             },
             TxCreateClientCmd::parse_from([
                 "test",
@@ -652,7 +804,9 @@ mod tests {
                 src_chain_id: ChainId::from_string("reference_chain"),
                 clock_drift: None,
                 trusting_period: Some("5s".parse::<Duration>().unwrap()),
-                trust_threshold: None
+                trust_threshold: None,
+                auto: false,
+                yes: false
             },
             TxCreateClientCmd::parse_from([
                 "test",
@@ -670,7 +824,9 @@ mod tests {
                 src_chain_id: ChainId::from_string("reference_chain"),
                 clock_drift: None,
                 trusting_period: Some("3s".parse::<Duration>().unwrap()),
-                trust_threshold: None
+                trust_threshold: None,
+                auto: false,
+                yes: false
             },
             TxCreateClientCmd::parse_from([
                 "test",
@@ -692,7 +848,9 @@ mod tests {
                 src_chain_id: ChainId::from_string("reference_chain"),
                 clock_drift: None,
                 trusting_period: None,
-                trust_threshold: Some(TrustThreshold::new(1, 2).unwrap())
+                trust_threshold: Some(TrustThreshold::new(1, 2).unwrap()),
+                auto: false,
+                yes: false
             },
             TxCreateClientCmd::parse_from([
                 "test",
@@ -714,7 +872,9 @@ mod tests {
                 src_chain_id: ChainId::from_string("reference_chain"),
                 clock_drift: Some("5s".parse::<Duration>().unwrap()),
                 trusting_period: Some("3s".parse::<Duration>().unwrap()),
-                trust_threshold: Some(TrustThreshold::new(1, 2).unwrap())
+                trust_threshold: Some(TrustThreshold::new(1, 2).unwrap()),
+                auto: false,
+                yes: false
             },
             TxCreateClientCmd::parse_from([
                 "test",
@@ -732,6 +892,45 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_create_client_auto() {
+        assert_eq!(
+            TxCreateClientCmd {
+                dst_chain_id: ChainId::from_string("host_chain"),
+                src_chain_id: ChainId::from_string("reference_chain"),
+                clock_drift: None,
+                trusting_period: Some("3s".parse::<Duration>().unwrap()),
+                trust_threshold: None,
+                auto: true,
+                yes: true
+            },
+            TxCreateClientCmd::parse_from([
+                "test",
+                "--host-chain",
+                "host_chain",
+                "--reference-chain",
+                "reference_chain",
+                "--trusting-period",
+                "3s",
+                "--auto",
+                "--yes"
+            ])
+        )
+    }
+
+    #[test]
+    fn test_create_client_yes_without_auto() {
+        assert!(TxCreateClientCmd::try_parse_from([
+            "test",
+            "--host-chain",
+            "host_chain",
+            "--reference-chain",
+            "reference_chain",
+            "--yes"
+        ])
+        .is_err())
+    }
+
     #[test]
     fn test_create_client_no_host_chain() {
         assert!(TxCreateClientCmd::try_parse_from([
@@ -785,7 +984,9 @@ mod tests {
                 dst_chain_id: ChainId::from_string("host_chain"),
                 dst_client_id: ClientId::from_str("client_to_update").unwrap(),
                 target_height: None,
-                trusted_height: None
+                trusted_height: None,
+                client_type_args: None,
+                yes: false
             },
             TxUpdateClientCmd::parse_from([
                 "test",
@@ -804,7 +1005,9 @@ mod tests {
                 dst_chain_id: ChainId::from_string("host_chain"),
                 dst_client_id: ClientId::from_str("client_to_update").unwrap(),
                 target_height: Some(42),
-                trusted_height: None
+                trusted_height: None,
+                client_type_args: None,
+                yes: false
             },
             TxUpdateClientCmd::parse_from([
                 "test",
@@ -825,7 +1028,9 @@ mod tests {
                 dst_chain_id: ChainId::from_string("host_chain"),
                 dst_client_id: ClientId::from_str("client_to_update").unwrap(),
                 target_height: None,
-                trusted_height: Some(42)
+                trusted_height: Some(42),
+                client_type_args: None,
+                yes: false
             },
             TxUpdateClientCmd::parse_from([
                 "test",
@@ -846,7 +1051,9 @@ mod tests {
                 dst_chain_id: ChainId::from_string("host_chain"),
                 dst_client_id: ClientId::from_str("client_to_update").unwrap(),
                 target_height: Some(21),
-                trusted_height: Some(42)
+                trusted_height: Some(42),
+                client_type_args: None,
+                yes: false
             },
             TxUpdateClientCmd::parse_from([
                 "test",
@@ -862,6 +1069,44 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_update_client_third_party() {
+        let type_args = "0x29866e133f707f070459b905065294ab1a7b70bea200952a080f849319ae6202";
+        assert_eq!(
+            TxUpdateClientCmd {
+                dst_chain_id: ChainId::from_string("host_chain"),
+                dst_client_id: ClientId::from_str("client_to_update").unwrap(),
+                target_height: None,
+                trusted_height: None,
+                client_type_args: Some(parse_type_args(type_args).unwrap()),
+                yes: true
+            },
+            TxUpdateClientCmd::parse_from([
+                "test",
+                "--host-chain",
+                "host_chain",
+                "--client",
+                "client_to_update",
+                "--client-type-args",
+                type_args,
+                "--yes"
+            ])
+        )
+    }
+
+    #[test]
+    fn test_update_client_yes_without_type_args() {
+        assert!(TxUpdateClientCmd::try_parse_from([
+            "test",
+            "--host-chain",
+            "host_chain",
+            "--client",
+            "client_to_update",
+            "--yes"
+        ])
+        .is_err())
+    }
+
     #[test]
     fn test_update_client_no_chain() {
         assert!(TxUpdateClientCmd::try_parse_from([
@@ -6,7 +6,7 @@ use eyre::eyre;
 
 use ibc_relayer::{
     chain::handle::ChainHandle,
-    config::Config,
+    config::{ChainConfig, Config},
     event::IbcEventWithHeight,
     transfer::{build_and_send_transfer_messages, TransferOptions},
 };
@@ -131,7 +131,10 @@ impl Override<Config> for TxIcs20MsgTransferCmd {
         })?;
 
         if let Some(ref key_name) = self.key_name {
-            src_chain_config.cosmos_mut().key_name = key_name.to_string();
+            match src_chain_config {
+                ChainConfig::Ckb4Ibc(chain_config) => chain_config.key_name = key_name.to_string(),
+                _ => src_chain_config.cosmos_mut().key_name = key_name.to_string(),
+            }
         }
 
         Ok(config)
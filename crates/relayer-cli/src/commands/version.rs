@@ -3,19 +3,112 @@
 use super::CliCmd;
 use abscissa_core::clap::Parser;
 use abscissa_core::{Command, Runnable};
+use itertools::Itertools;
+use serde::Serialize;
+
+use ibc_relayer::chain::ChainType;
+use ibc_relayer::summary::Features;
+
+use crate::conclude::json;
+
+/// Features the binary is built with
+#[derive(Debug, Serialize)]
+struct BuildFeatures {
+    endpoints: Vec<ChainType>,
+    telemetry: bool,
+    rest_server: bool,
+    profiling: bool,
+    k256: bool,
+}
+
+impl BuildFeatures {
+    fn compiled() -> Self {
+        let features = Features::compiled();
+        Self {
+            endpoints: features.endpoints,
+            telemetry: features.telemetry,
+            rest_server: cfg!(feature = "rest-server"),
+            profiling: features.profiling,
+            k256: features.k256,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct VersionInfo {
+    name: String,
+    version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    features: Option<BuildFeatures>,
+}
 
 /// `version` subcommand
 ///
-/// This subcommand is implemented for backward compatibility reasons.
-/// Its behavior should be the same as that of the `--version` flag which
-/// is handled internally by clap.
-#[derive(Command, Debug, Default, Parser)]
-#[clap(hide = true)]
-pub struct VersionCmd {}
+/// Without any flag, its behavior is the same as that of the `--version` flag
+/// which is handled internally by clap. It also lists the features the binary
+/// is built with, and prints all of it as a JSON object with the global `--json`
+/// flag, so that deployments can be verified by scripts.
+#[derive(Command, Debug, Default, Parser, PartialEq, Eq)]
+pub struct VersionCmd {
+    #[clap(
+        long = "features",
+        help = "Also list the compiled-in chain endpoints and the optional features"
+    )]
+    features: bool,
+}
 
 impl Runnable for VersionCmd {
     /// Print version message
     fn run(&self) {
-        println!("{} {}", CliCmd::name(), clap::crate_version!());
+        let info = VersionInfo {
+            name: CliCmd::name().to_owned(),
+            version: clap::crate_version!().to_owned(),
+            features: self.features.then(BuildFeatures::compiled),
+        };
+
+        if json() {
+            println!("{}", serde_json::to_string(&info).unwrap());
+            return;
+        }
+
+        println!("{} {}", info.name, info.version);
+        if let Some(features) = info.features {
+            let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+            println!(
+                "endpoints: {}",
+                features
+                    .endpoints
+                    .iter()
+                    .map(|endpoint| format!("{endpoint:?}"))
+                    .join(", ")
+            );
+            println!("telemetry: {}", on_off(features.telemetry));
+            println!("rest-server: {}", on_off(features.rest_server));
+            println!("profiling: {}", on_off(features.profiling));
+            println!("k256: {}", on_off(features.k256));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VersionCmd;
+
+    use abscissa_core::clap::Parser;
+
+    #[test]
+    fn test_version() {
+        assert_eq!(
+            VersionCmd { features: false },
+            VersionCmd::parse_from(["test"])
+        )
+    }
+
+    #[test]
+    fn test_version_features() {
+        assert_eq!(
+            VersionCmd { features: true },
+            VersionCmd::parse_from(["test", "--features"])
+        )
     }
 }
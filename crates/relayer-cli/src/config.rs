@@ -8,7 +8,7 @@ use alloc::collections::BTreeSet;
 use std::path::PathBuf;
 
 use flex_error::{define_error, TraceError};
-use ibc_relayer::config::{ChainConfig, Config, ModeConfig};
+use ibc_relayer::config::{path::PathConfig, ChainConfig, Config, ModeConfig};
 use ibc_relayer_types::core::ics24_host::identifier::ChainId;
 use tendermint_light_client_verifier::types::TrustThreshold;
 use tracing_subscriber::filter::ParseError;
@@ -73,6 +73,16 @@ define_error! {
                     e.chain_id, e.gas_adjustment, e.gas_multiplier
                 )
             },
+
+        InvalidPath
+            {
+                path: PathConfig,
+                reason: String,
+            }
+            |e| {
+                format!("config file declares an invalid path '{0}', caused by: {1}",
+                    e.path, e.reason)
+            },
     }
 }
 
@@ -108,6 +118,9 @@ pub fn validate_config(config: &Config) -> Result<(), Diagnostic<Error>> {
     // Check for invalid mode config
     validate_mode(&config.mode)?;
 
+    // Check for paths on unknown chains and duplicate paths
+    validate_paths(config)?;
+
     Ok(())
 }
 
@@ -127,6 +140,35 @@ fn validate_mode(mode: &ModeConfig) -> Result<(), Diagnostic<Error>> {
     Ok(())
 }
 
+fn validate_paths(config: &Config) -> Result<(), Diagnostic<Error>> {
+    let mut unique_paths = BTreeSet::new();
+    for path in config.paths.iter() {
+        if !config.has_chain(&path.chain) {
+            return Err(Diagnostic::Error(Error::invalid_path(
+                path.clone(),
+                format!("chain '{}' is not configured", path.chain),
+            )));
+        }
+
+        if path.concurrency == 0 {
+            return Err(Diagnostic::Error(Error::invalid_path(
+                path.clone(),
+                "the concurrency of the packet workers must be at least 1".to_string(),
+            )));
+        }
+
+        let already_present = !unique_paths.insert((&path.chain, &path.port, &path.channel));
+        if already_present {
+            return Err(Diagnostic::Error(Error::invalid_path(
+                path.clone(),
+                "the channel is declared more than once".to_string(),
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 /// Check that the trust threshold is:
 ///
 /// a) non-zero
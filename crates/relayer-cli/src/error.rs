@@ -7,7 +7,7 @@ use tendermint::Error as TendermintError;
 
 use ibc_relayer_types::applications::ics29_fee::error::Error as FeeError;
 use ibc_relayer_types::core::ics04_channel::channel::IdentifiedChannelEnd;
-use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, PortId};
 use ibc_relayer_types::signer::SignerError;
 
 use ibc_relayer::channel::ChannelError;
@@ -72,6 +72,13 @@ define_error! {
                     e.channel_end)
             },
 
+        ClientNotFrozen
+            { chain_id: ChainId, port_id: PortId, channel_id: ChannelId }
+            | e | {
+                format_args!("no client of channel '{}/{}' on chain '{}' or of its counterparty is frozen, the path needs no recovery",
+                    e.port_id, e.channel_id, e.chain_id)
+            },
+
         Relayer
             [ RelayerError ]
             |_| { "relayer error" },
@@ -119,5 +126,12 @@ define_error! {
         KeyRing
             [ KeyRingError ]
             |_| { "keyring error" },
+
+        Rest
+            { reason: String }
+            | e | {
+                format_args!("request to the REST server of the running relayer failed: {0}",
+                    e.reason)
+            },
     }
 }
@@ -0,0 +1,165 @@
+//! Typed client for the REST server.
+
+use core::fmt::{Display, Error as FmtError, Formatter};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use ibc_relayer::archive::ArchivePage;
+use ibc_relayer::attestation::SignedHealthAttestation;
+use ibc_relayer::config::ChainConfig;
+use ibc_relayer::decision::Decision;
+use ibc_relayer::rest::chain_state::{ChainState, ChainStateQuery};
+use ibc_relayer::rest::request::VersionInfo;
+use ibc_relayer::summary::StartupSummary;
+use ibc_relayer::supervisor::{dump_state::SupervisorState, pause::PauseTarget};
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+use crate::server::JsonResult;
+
+/// Error reported by the REST server, as serialized by `RestApiError`
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct ApiError {
+    pub name: String,
+    pub msg: String,
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The request could not be sent or the server replied with a non-success status code
+    Transport(String),
+    /// The reply could not be decoded into the expected type
+    Decode(String),
+    /// The server failed to handle the request
+    Api(ApiError),
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            ClientError::Transport(e) => write!(f, "failed to reach the REST server: {e}"),
+            ClientError::Decode(e) => write!(f, "failed to decode the REST reply: {e}"),
+            ClientError::Api(e) => write!(f, "REST server error {}: {}", e.name, e.msg),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+/// Client for the routes served by [`crate::server`].
+#[derive(Clone, Debug)]
+pub struct RestClient {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+impl RestClient {
+    /// Create a client for the server listening at `base_url`, eg. `http://127.0.0.1:3000`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    pub fn version(&self) -> Result<Vec<VersionInfo>, ClientError> {
+        self.call("GET", "/version")
+    }
+
+    pub fn chains(&self) -> Result<Vec<ChainId>, ClientError> {
+        self.call_enveloped("GET", "/chains")
+    }
+
+    pub fn chain(&self, chain_id: &ChainId) -> Result<ChainConfig, ClientError> {
+        self.call_enveloped("GET", &format!("/chain/{chain_id}"))
+    }
+
+    pub fn invalidate_cache(&self, chain_id: &ChainId) -> Result<(), ClientError> {
+        self.call_enveloped("POST", &format!("/chain/{chain_id}/invalidate_cache"))
+    }
+
+    pub fn health_attestation(
+        &self,
+        chain_id: &ChainId,
+    ) -> Result<SignedHealthAttestation, ClientError> {
+        self.call_enveloped("GET", &format!("/chain/{chain_id}/attestation"))
+    }
+
+    /// Query a state of the chain, e.g. its channels or the progress of the relaying
+    pub fn chain_state(
+        &self,
+        chain_id: &ChainId,
+        query: ChainStateQuery,
+    ) -> Result<ChainState, ClientError> {
+        self.call_enveloped("GET", &format!("/chain/{chain_id}/{}", query.route()))
+    }
+
+    /// Pause the relaying for the target, returning all the paused targets
+    pub fn pause(&self, target: &PauseTarget) -> Result<Vec<PauseTarget>, ClientError> {
+        self.call_enveloped("POST", &format!("{}/pause", target_path(target)))
+    }
+
+    /// Resume the relaying for the target, returning the targets still paused
+    pub fn resume(&self, target: &PauseTarget) -> Result<Vec<PauseTarget>, ClientError> {
+        self.call_enveloped("POST", &format!("{}/resume", target_path(target)))
+    }
+
+    pub fn paused(&self) -> Result<Vec<PauseTarget>, ClientError> {
+        self.call_enveloped("GET", "/paused")
+    }
+
+    pub fn summary(&self) -> Result<StartupSummary, ClientError> {
+        self.call_enveloped("GET", "/summary")
+    }
+
+    pub fn state(&self) -> Result<SupervisorState, ClientError> {
+        self.call_enveloped("GET", "/state")
+    }
+
+    /// The latest relaying decisions, the oldest first
+    pub fn decisions(&self) -> Result<Vec<Decision>, ClientError> {
+        self.call("GET", "/decisions")
+    }
+
+    /// Page of at most `limit` events of the archive, from the one of index `from`
+    pub fn archive(&self, from: u64, limit: usize) -> Result<ArchivePage, ClientError> {
+        self.call_enveloped("GET", &format!("/archive?from={from}&limit={limit}"))
+    }
+
+    pub fn openapi(&self) -> Result<serde_json::Value, ClientError> {
+        self.call("GET", "/openapi.json")
+    }
+
+    fn call<R: DeserializeOwned>(&self, method: &str, path: &str) -> Result<R, ClientError> {
+        let body = self
+            .agent
+            .request(method, &format!("{}{path}", self.base_url))
+            .call()
+            .map_err(|e| ClientError::Transport(e.to_string()))?
+            .into_string()
+            .map_err(|e| ClientError::Transport(e.to_string()))?;
+        serde_json::from_str(&body).map_err(|e| ClientError::Decode(e.to_string()))
+    }
+
+    fn call_enveloped<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        path: &str,
+    ) -> Result<R, ClientError> {
+        match self.call(method, path)? {
+            JsonResult::Success(result) => Ok(result),
+            JsonResult::Error(e) => Err(ClientError::Api(e)),
+        }
+    }
+}
+
+fn target_path(target: &PauseTarget) -> String {
+    match target {
+        PauseTarget::Chain { chain_id } => format!("/chain/{chain_id}"),
+        PauseTarget::Channel {
+            chain_id,
+            port_id,
+            channel_id,
+        } => format!("/chain/{chain_id}/channel/{port_id}/{channel_id}"),
+    }
+}
@@ -1,18 +1,23 @@
 use core::fmt::Debug;
+use core::str::FromStr;
 
 use tracing::error;
 
 use crossbeam_channel as channel;
 
-use ibc_relayer::supervisor::dump_state::SupervisorState;
+use ibc_relayer::supervisor::{dump_state::SupervisorState, pause::PauseTarget};
 use ibc_relayer::{
+    archive::{self, ArchivePage},
+    attestation::SignedHealthAttestation,
     config::ChainConfig,
     rest::{
+        chain_state::{ChainState, ChainStateQuery},
         request::{reply_channel, ReplySender, Request, VersionInfo},
         RestApiError,
     },
+    summary::StartupSummary,
 };
-use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, PortId};
 
 pub const NAME: &str = env!(
     "CARGO_PKG_NAME",
@@ -58,12 +63,94 @@ pub fn chain_config(
     })
 }
 
+pub fn invalidate_cache(
+    sender: &channel::Sender<Request>,
+    chain_id: &str,
+) -> Result<(), RestApiError> {
+    submit_request(sender, |reply_to| Request::InvalidateCache {
+        chain_id: ChainId::from_string(chain_id),
+        reply_to,
+    })
+}
+
+pub fn health_attestation(
+    sender: &channel::Sender<Request>,
+    chain_id: &str,
+) -> Result<SignedHealthAttestation, RestApiError> {
+    submit_request(sender, |reply_to| Request::HealthAttestation {
+        chain_id: ChainId::from_string(chain_id),
+        reply_to,
+    })
+}
+
+pub fn chain_state(
+    sender: &channel::Sender<Request>,
+    chain_id: &str,
+    query: ChainStateQuery,
+) -> Result<ChainState, RestApiError> {
+    submit_request(sender, |reply_to| Request::ChainState {
+        chain_id: ChainId::from_string(chain_id),
+        query,
+        reply_to,
+    })
+}
+
+pub fn chain_target(chain_id: &str) -> PauseTarget {
+    PauseTarget::Chain {
+        chain_id: ChainId::from_string(chain_id),
+    }
+}
+
+pub fn channel_target(
+    chain_id: &str,
+    port_id: &str,
+    channel_id: &str,
+) -> Result<PauseTarget, RestApiError> {
+    Ok(PauseTarget::Channel {
+        chain_id: ChainId::from_string(chain_id),
+        port_id: PortId::from_str(port_id)
+            .map_err(|e| RestApiError::InvalidPortId(port_id.to_string(), e.0))?,
+        channel_id: ChannelId::from_str(channel_id)
+            .map_err(|e| RestApiError::InvalidChannelId(channel_id.to_string(), e.0))?,
+    })
+}
+
+pub fn pause(
+    sender: &channel::Sender<Request>,
+    target: PauseTarget,
+) -> Result<Vec<PauseTarget>, RestApiError> {
+    submit_request(sender, |reply_to| Request::Pause { target, reply_to })
+}
+
+pub fn resume(
+    sender: &channel::Sender<Request>,
+    target: PauseTarget,
+) -> Result<Vec<PauseTarget>, RestApiError> {
+    submit_request(sender, |reply_to| Request::Resume { target, reply_to })
+}
+
+pub fn paused(sender: &channel::Sender<Request>) -> Result<Vec<PauseTarget>, RestApiError> {
+    submit_request(sender, |reply_to| Request::Paused { reply_to })
+}
+
+pub fn startup_summary(sender: &channel::Sender<Request>) -> Result<StartupSummary, RestApiError> {
+    submit_request(sender, |reply_to| Request::Summary { reply_to })
+}
+
 pub fn supervisor_state(
     sender: &channel::Sender<Request>,
 ) -> Result<SupervisorState, RestApiError> {
     submit_request(sender, |reply_to| Request::State { reply_to })
 }
 
+/// Page of the event archive, read without going through the supervisor
+pub fn archived_events(from: u64, limit: usize) -> Result<ArchivePage, RestApiError> {
+    archive::global()
+        .ok_or_else(|| RestApiError::Archive("`global.archive_file` is not set".to_string()))?
+        .page(from, limit)
+        .map_err(|e| RestApiError::Archive(e.to_string()))
+}
+
 pub fn assemble_version_info(sender: &channel::Sender<Request>) -> Vec<VersionInfo> {
     // Fetch the relayer library version
     let lib_version = submit_request(sender, |reply_to| Request::Version { reply_to })
@@ -4,6 +4,8 @@ extern crate rouille;
 mod config;
 pub use config::Config;
 
+pub mod client;
+pub mod openapi;
 pub mod server;
 
 pub(crate) mod handle;
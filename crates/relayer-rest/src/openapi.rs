@@ -0,0 +1,525 @@
+//! OpenAPI description of the REST server.
+//!
+//! The document is derived from [`ROUTES`], which lists every route
+//! handled by the server, so the two must be kept in sync; the
+//! `openapi_routes_match_router` test fails when they are not.
+
+use serde_json::{json, Map, Value};
+
+use crate::handle::VER;
+
+/// A route exposed by the REST server
+#[derive(Clone, Copy, Debug)]
+pub struct Route {
+    pub method: &'static str,
+    /// Path in OpenAPI notation, eg. `/chain/{id}`
+    pub path: &'static str,
+    pub operation_id: &'static str,
+    pub summary: &'static str,
+    /// Schema of the result, as a reference into the components of the document
+    pub result: &'static str,
+    /// Whether the result is wrapped into a `{ "status", "result" }` envelope
+    pub enveloped: bool,
+    /// Optional query parameters, all of them integers
+    pub query: &'static [&'static str],
+}
+
+pub const ROUTES: &[Route] = &[
+    Route {
+        method: "get",
+        path: "/version",
+        operation_id: "version",
+        summary: "Versions of the relayer library and of the REST server",
+        result: "VersionInfoList",
+        enveloped: false,
+        query: &[],
+    },
+    Route {
+        method: "get",
+        path: "/chains",
+        operation_id: "chains",
+        summary: "Identifiers of all the configured chains",
+        result: "ChainIdList",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "get",
+        path: "/chain/{id}",
+        operation_id: "chain",
+        summary: "Configuration of the given chain",
+        result: "ChainConfig",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "post",
+        path: "/chain/{id}/invalidate_cache",
+        operation_id: "invalidate_cache",
+        summary: "Drop the states cached by the runtime of the given chain",
+        result: "Unit",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "get",
+        path: "/chain/{id}/attestation",
+        operation_id: "health_attestation",
+        summary: "Health attestation of the given chain, signed with its relayer key",
+        result: "SignedHealthAttestation",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "get",
+        path: "/chain/{id}/connections",
+        operation_id: "chain_connections",
+        summary: "Connections of the given chain, e.g. the connection cells of a CKB chain",
+        result: "ChainState",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "get",
+        path: "/chain/{id}/channels",
+        operation_id: "chain_channels",
+        summary: "Channels of the given chain, e.g. the channel cells of a CKB chain",
+        result: "ChainState",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "get",
+        path: "/chain/{id}/packets",
+        operation_id: "chain_packets",
+        summary: "Sequences of the packets committed on each channel of the given chain",
+        result: "ChainState",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "get",
+        path: "/chain/{id}/light_client",
+        operation_id: "chain_light_client",
+        summary: "Cells of the Ethereum light client on the given CKB chain",
+        result: "ChainState",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "get",
+        path: "/chain/{id}/relay_status",
+        operation_id: "chain_relay_status",
+        summary: "Monitor progress and pending transactions of the given CKB or Axon chain",
+        result: "ChainState",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "post",
+        path: "/chain/{id}/pause",
+        operation_id: "pause_chain",
+        summary: "Stop submitting transactions to and from the given chain",
+        result: "PauseTargetList",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "post",
+        path: "/chain/{id}/resume",
+        operation_id: "resume_chain",
+        summary: "Resume relaying to and from the given chain",
+        result: "PauseTargetList",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "post",
+        path: "/chain/{id}/channel/{port}/{channel}/pause",
+        operation_id: "pause_channel",
+        summary: "Stop relaying from the given channel end",
+        result: "PauseTargetList",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "post",
+        path: "/chain/{id}/channel/{port}/{channel}/resume",
+        operation_id: "resume_channel",
+        summary: "Resume relaying from the given channel end",
+        result: "PauseTargetList",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "get",
+        path: "/paused",
+        operation_id: "paused",
+        summary: "Chains and channels whose relaying is paused",
+        result: "PauseTargetList",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "get",
+        path: "/summary",
+        operation_id: "summary",
+        summary: "Chains, contract outpoints, accounts and features of the relayer",
+        result: "StartupSummary",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "get",
+        path: "/state",
+        operation_id: "state",
+        summary: "Internal state of the supervisor",
+        result: "SupervisorState",
+        enveloped: true,
+        query: &[],
+    },
+    Route {
+        method: "get",
+        path: "/decisions",
+        operation_id: "decisions",
+        summary: "Latest decisions taken by the workers about the packets they relay",
+        result: "DecisionList",
+        enveloped: false,
+        query: &[],
+    },
+    Route {
+        method: "get",
+        path: "/decisions/stream",
+        operation_id: "stream_decisions",
+        summary: "WebSocket streaming the decisions as JSON text messages, the buffered ones first",
+        result: "Decision",
+        enveloped: false,
+        query: &[],
+    },
+    Route {
+        method: "get",
+        path: "/archive",
+        operation_id: "archive",
+        summary: "Page of the archived packet and channel events, from the event of index `from`",
+        result: "ArchivePage",
+        enveloped: true,
+        query: &["from", "limit"],
+    },
+    Route {
+        method: "get",
+        path: "/openapi.json",
+        operation_id: "openapi",
+        summary: "This document",
+        result: "OpenApi",
+        enveloped: false,
+        query: &[],
+    },
+];
+
+/// Build the OpenAPI document describing all the [`ROUTES`].
+pub fn openapi_spec() -> Value {
+    let mut paths = Map::new();
+    for route in ROUTES {
+        let item = paths
+            .entry(route.path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        item[route.method] = operation(route);
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Forcerelay REST API",
+            "version": VER,
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas(),
+        },
+    })
+}
+
+fn operation(route: &Route) -> Value {
+    let result = json!({ "$ref": format!("#/components/schemas/{}", route.result) });
+    let schema = if route.enveloped {
+        json!({
+            "oneOf": [
+                {
+                    "type": "object",
+                    "required": ["status", "result"],
+                    "properties": {
+                        "status": { "type": "string", "enum": ["success"] },
+                        "result": result,
+                    },
+                },
+                {
+                    "type": "object",
+                    "required": ["status", "result"],
+                    "properties": {
+                        "status": { "type": "string", "enum": ["error"] },
+                        "result": { "$ref": "#/components/schemas/RestApiError" },
+                    },
+                },
+            ],
+        })
+    } else {
+        result
+    };
+
+    let parameters = path_parameters(route.path)
+        .map(|name| {
+            json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": { "type": "string" },
+            })
+        })
+        .chain(route.query.iter().map(|name| {
+            json!({
+                "name": name,
+                "in": "query",
+                "required": false,
+                "schema": { "type": "integer", "minimum": 0 },
+            })
+        }))
+        .collect::<Vec<_>>();
+
+    json!({
+        "operationId": route.operation_id,
+        "summary": route.summary,
+        "parameters": parameters,
+        "responses": {
+            "200": {
+                "description": "OK",
+                "content": {
+                    "application/json": { "schema": schema },
+                },
+            },
+        },
+    })
+}
+
+fn path_parameters(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+}
+
+fn schemas() -> Value {
+    json!({
+        "Unit": { "nullable": true },
+        "VersionInfo": {
+            "type": "object",
+            "required": ["name", "version"],
+            "properties": {
+                "name": { "type": "string" },
+                "version": { "type": "string" },
+            },
+        },
+        "VersionInfoList": {
+            "type": "array",
+            "items": { "$ref": "#/components/schemas/VersionInfo" },
+        },
+        "ChainIdList": {
+            "type": "array",
+            "items": { "type": "string" },
+        },
+        "ChainConfig": { "type": "object" },
+        "SupervisorState": {
+            "type": "object",
+            "required": ["chains", "workers"],
+            "properties": {
+                "chains": { "type": "array", "items": { "type": "string" } },
+                "workers": { "type": "object" },
+                "failed_chains": { "type": "array", "items": { "type": "string" } },
+            },
+        },
+        "Height": {
+            "type": "object",
+            "required": ["revision_number", "revision_height"],
+            "properties": {
+                "revision_number": { "type": "integer" },
+                "revision_height": { "type": "integer" },
+            },
+        },
+        "ClientAttestation": {
+            "type": "object",
+            "required": ["client_id", "counterparty_chain_id", "latest_height", "frozen"],
+            "properties": {
+                "client_id": { "type": "string" },
+                "counterparty_chain_id": { "type": "string" },
+                "latest_height": { "$ref": "#/components/schemas/Height" },
+                "frozen": { "type": "boolean" },
+            },
+        },
+        "HealthAttestation": {
+            "type": "object",
+            "required": ["chain_id", "account", "latest_height", "clients", "timestamp"],
+            "properties": {
+                "chain_id": { "type": "string" },
+                "account": { "type": "string" },
+                "latest_height": { "$ref": "#/components/schemas/Height" },
+                "clients": {
+                    "type": "array",
+                    "items": { "$ref": "#/components/schemas/ClientAttestation" },
+                },
+                "timestamp": { "type": "integer" },
+            },
+        },
+        "SignedHealthAttestation": {
+            "type": "object",
+            "required": ["attestation", "public_key", "signature"],
+            "properties": {
+                "attestation": { "$ref": "#/components/schemas/HealthAttestation" },
+                "public_key": { "type": "string" },
+                "signature": { "type": "string" },
+            },
+        },
+        "ChainState": {
+            "type": "object",
+            "required": ["type", "state"],
+            "properties": {
+                "type": {
+                    "type": "string",
+                    "enum": ["connections", "channels", "packets", "light_client", "relay_status"],
+                },
+                "state": {},
+            },
+        },
+        "ChainSummary": {
+            "type": "object",
+            "required": [
+                "id",
+                "chain_type",
+                "key_name",
+                "submit_txs",
+                "contract_outpoints",
+                "errors",
+            ],
+            "properties": {
+                "id": { "type": "string" },
+                "chain_type": { "type": "string" },
+                "key_name": { "type": "string" },
+                "account": { "type": "string", "nullable": true },
+                "submit_txs": { "type": "boolean" },
+                "contract_outpoints": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                },
+                "packet_filter": { "type": "object", "nullable": true },
+                "errors": { "type": "array", "items": { "type": "string" } },
+            },
+        },
+        "StartupSummary": {
+            "type": "object",
+            "required": ["version", "features", "rest", "chains"],
+            "properties": {
+                "version": { "type": "string" },
+                "features": {
+                    "type": "object",
+                    "required": ["endpoints", "telemetry", "profiling"],
+                    "properties": {
+                        "endpoints": { "type": "array", "items": { "type": "string" } },
+                        "telemetry": { "type": "boolean" },
+                        "profiling": { "type": "boolean" },
+                    },
+                },
+                "rest": { "type": "boolean" },
+                "chains": {
+                    "type": "array",
+                    "items": { "$ref": "#/components/schemas/ChainSummary" },
+                },
+            },
+        },
+        "PauseTarget": {
+            "type": "object",
+            "required": ["type", "chain_id"],
+            "properties": {
+                "type": { "type": "string", "enum": ["chain", "channel"] },
+                "chain_id": { "type": "string" },
+                "port_id": { "type": "string" },
+                "channel_id": { "type": "string" },
+            },
+        },
+        "PauseTargetList": {
+            "type": "array",
+            "items": { "$ref": "#/components/schemas/PauseTarget" },
+        },
+        "Decision": {
+            "type": "object",
+            "required": [
+                "kind",
+                "chain_id",
+                "counterparty_chain_id",
+                "port_id",
+                "channel_id",
+                "reason",
+                "timestamp",
+            ],
+            "properties": {
+                "kind": {
+                    "type": "string",
+                    "enum": ["relayed", "skipped", "deferred", "quarantined"],
+                },
+                "chain_id": { "type": "string" },
+                "counterparty_chain_id": { "type": "string" },
+                "port_id": { "type": "string" },
+                "channel_id": { "type": "string" },
+                "sequence": { "type": "integer", "nullable": true },
+                "reason": { "type": "string" },
+                "timestamp": { "type": "integer" },
+            },
+        },
+        "DecisionList": {
+            "type": "array",
+            "items": { "$ref": "#/components/schemas/Decision" },
+        },
+        "ArchivedEvent": {
+            "type": "object",
+            "required": [
+                "index",
+                "chain_id",
+                "height",
+                "tx_hash",
+                "kind",
+                "port_id",
+                "counterparty_port_id",
+                "timestamp",
+            ],
+            "properties": {
+                "index": { "type": "integer" },
+                "chain_id": { "type": "string" },
+                "height": { "type": "integer" },
+                "tx_hash": { "type": "string" },
+                "kind": { "type": "string" },
+                "port_id": { "type": "string" },
+                "channel_id": { "type": "string", "nullable": true },
+                "counterparty_port_id": { "type": "string" },
+                "counterparty_channel_id": { "type": "string", "nullable": true },
+                "sequence": { "type": "integer", "nullable": true },
+                "data": { "type": "string", "nullable": true },
+                "timestamp": { "type": "integer" },
+            },
+        },
+        "ArchivePage": {
+            "type": "object",
+            "required": ["events"],
+            "properties": {
+                "events": {
+                    "type": "array",
+                    "items": { "$ref": "#/components/schemas/ArchivedEvent" },
+                },
+                "next": { "type": "integer", "nullable": true },
+            },
+        },
+        "RestApiError": {
+            "type": "object",
+            "required": ["name", "msg"],
+            "properties": {
+                "name": { "type": "string" },
+                "msg": { "type": "string" },
+            },
+        },
+        "OpenApi": { "type": "object" },
+    })
+}
@@ -4,10 +4,17 @@ use crossbeam_channel as channel;
 use serde::{Deserialize, Serialize};
 use tracing::{info, trace};
 
-use ibc_relayer::rest::request::Request;
+use ibc_relayer::archive::MAX_PAGE_SIZE;
+use ibc_relayer::decision;
+use ibc_relayer::rest::{chain_state::ChainStateQuery, request::Request};
 
 use crate::{
-    handle::{all_chain_ids, assemble_version_info, chain_config, supervisor_state},
+    handle::{
+        all_chain_ids, archived_events, assemble_version_info, chain_config, chain_state,
+        chain_target, channel_target, health_attestation, invalidate_cache, pause, paused, resume,
+        startup_summary, supervisor_state,
+    },
+    openapi::openapi_spec,
     Config,
 };
 
@@ -38,7 +45,7 @@ pub fn spawn(config: Config) -> (ServerHandle, channel::Receiver<Request>) {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "status", content = "result")]
 #[serde(rename_all = "lowercase")]
-enum JsonResult<R, E> {
+pub(crate) enum JsonResult<R, E> {
     Success(R),
     Error(E),
 }
@@ -76,12 +83,121 @@ fn run(config: Config, sender: channel::Sender<Request>) -> ServerHandle {
                 rouille::Response::json(&JsonResult::from(result))
             },
 
+            (POST) (/chain/{id: String}/invalidate_cache) => {
+                trace!("[rest] POST /chain/{}/invalidate_cache", id);
+                let result = invalidate_cache(&sender, &id);
+                rouille::Response::json(&JsonResult::from(result))
+            },
+
+            (GET) (/chain/{id: String}/attestation) => {
+                trace!("[rest] GET /chain/{}/attestation", id);
+                let result = health_attestation(&sender, &id);
+                rouille::Response::json(&JsonResult::from(result))
+            },
+
+            (GET) (/chain/{id: String}/connections) => {
+                trace!("[rest] GET /chain/{}/connections", id);
+                let result = chain_state(&sender, &id, ChainStateQuery::Connections);
+                rouille::Response::json(&JsonResult::from(result))
+            },
+
+            (GET) (/chain/{id: String}/channels) => {
+                trace!("[rest] GET /chain/{}/channels", id);
+                let result = chain_state(&sender, &id, ChainStateQuery::Channels);
+                rouille::Response::json(&JsonResult::from(result))
+            },
+
+            (GET) (/chain/{id: String}/packets) => {
+                trace!("[rest] GET /chain/{}/packets", id);
+                let result = chain_state(&sender, &id, ChainStateQuery::Packets);
+                rouille::Response::json(&JsonResult::from(result))
+            },
+
+            (GET) (/chain/{id: String}/light_client) => {
+                trace!("[rest] GET /chain/{}/light_client", id);
+                let result = chain_state(&sender, &id, ChainStateQuery::LightClient);
+                rouille::Response::json(&JsonResult::from(result))
+            },
+
+            (GET) (/chain/{id: String}/relay_status) => {
+                trace!("[rest] GET /chain/{}/relay_status", id);
+                let result = chain_state(&sender, &id, ChainStateQuery::RelayStatus);
+                rouille::Response::json(&JsonResult::from(result))
+            },
+
+            (POST) (/chain/{id: String}/pause) => {
+                trace!("[rest] POST /chain/{}/pause", id);
+                let result = pause(&sender, chain_target(&id));
+                rouille::Response::json(&JsonResult::from(result))
+            },
+
+            (POST) (/chain/{id: String}/resume) => {
+                trace!("[rest] POST /chain/{}/resume", id);
+                let result = resume(&sender, chain_target(&id));
+                rouille::Response::json(&JsonResult::from(result))
+            },
+
+            (POST) (/chain/{id: String}/channel/{port: String}/{channel: String}/pause) => {
+                trace!("[rest] POST /chain/{}/channel/{}/{}/pause", id, port, channel);
+                let result = channel_target(&id, &port, &channel)
+                    .and_then(|target| pause(&sender, target));
+                rouille::Response::json(&JsonResult::from(result))
+            },
+
+            (POST) (/chain/{id: String}/channel/{port: String}/{channel: String}/resume) => {
+                trace!("[rest] POST /chain/{}/channel/{}/{}/resume", id, port, channel);
+                let result = channel_target(&id, &port, &channel)
+                    .and_then(|target| resume(&sender, target));
+                rouille::Response::json(&JsonResult::from(result))
+            },
+
+            (GET) (/paused) => {
+                trace!("[rest] GET /paused");
+                let result = paused(&sender);
+                rouille::Response::json(&JsonResult::from(result))
+            },
+
+            (GET) (/summary) => {
+                trace!("[rest] GET /summary");
+                let result = startup_summary(&sender);
+                rouille::Response::json(&JsonResult::from(result))
+            },
+
             (GET) (/state) => {
                 trace!("[rest] GET /state");
                 let result = supervisor_state(&sender);
                 rouille::Response::json(&JsonResult::from(result))
             },
 
+            (GET) (/decisions) => {
+                trace!("[rest] GET /decisions");
+                rouille::Response::json(&decision::global().recent())
+            },
+
+            (GET) (/decisions/stream) => {
+                trace!("[rest] GET /decisions/stream");
+                stream_decisions(request)
+            },
+
+            (GET) (/archive) => {
+                trace!("[rest] GET /archive");
+                let from = request
+                    .get_param("from")
+                    .and_then(|from| from.parse().ok())
+                    .unwrap_or(0);
+                let limit = request
+                    .get_param("limit")
+                    .and_then(|limit| limit.parse().ok())
+                    .unwrap_or(MAX_PAGE_SIZE);
+                let result = archived_events(from, limit);
+                rouille::Response::json(&JsonResult::from(result))
+            },
+
+            (GET) (/openapi.json) => {
+                trace!("[rest] GET /openapi.json");
+                rouille::Response::json(&openapi_spec())
+            },
+
             _ => rouille::Response::empty_404(),
         )
     })
@@ -94,3 +210,31 @@ fn run(config: Config, sender: channel::Sender<Request>) -> ServerHandle {
         tx_stop,
     }
 }
+
+/// Upgrade the connection to a WebSocket over which the relaying decisions are sent as
+/// JSON text messages, the buffered ones first. The stream ends when the client leaves.
+fn stream_decisions(request: &rouille::Request) -> rouille::Response {
+    let (response, websocket) = try_or_400!(rouille::websocket::start(request, None::<&str>));
+    let subscription = decision::global().subscribe();
+
+    thread::spawn(move || {
+        let Ok(mut websocket) = websocket.recv() else {
+            return;
+        };
+
+        let decisions = subscription
+            .backlog
+            .into_iter()
+            .chain(subscription.receiver.iter());
+
+        for decision in decisions {
+            let text = serde_json::to_string(&decision).expect("decisions serialize to JSON");
+            if websocket.send_text(&text).is_err() {
+                trace!("[rest] decision stream closed by the client");
+                break;
+            }
+        }
+    });
+
+    response
+}
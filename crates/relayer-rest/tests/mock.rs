@@ -1,15 +1,31 @@
+use std::collections::BTreeSet;
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
 use ibc_relayer::{
+    archive::{self, ArchivedEvent},
+    attestation::{HealthAttestation, SignedHealthAttestation},
+    chain::ChainType,
     config::ChainConfig,
-    rest::request::{Request, VersionInfo},
-    supervisor::dump_state::SupervisorState,
+    decision::{self, Decision, DecisionKind},
+    progress::RelayStatus,
+    rest::{
+        chain_state::{ChainState, ChainStateQuery},
+        request::{Request, VersionInfo},
+    },
+    summary::{ChainSummary, Features, StartupSummary},
+    supervisor::{dump_state::SupervisorState, pause::PauseTarget},
+};
+use ibc_relayer_types::{
+    core::{
+        ics04_channel::packet::Sequence,
+        ics24_host::identifier::{ChainId, ChannelId, PortId},
+    },
+    Height,
 };
-use ibc_relayer_types::core::ics24_host::identifier::ChainId;
 
-use ibc_relayer_rest::{server::spawn, Config};
+use ibc_relayer_rest::{client::RestClient, openapi::ROUTES, server::spawn, Config};
 
 enum TestResult {
     Success,
@@ -25,6 +41,14 @@ enum JsonResult<R, E> {
 }
 
 fn run_test<R, F>(port: u16, path: &str, expected: R, handler: F)
+where
+    R: Serialize,
+    F: FnOnce(Request) -> TestResult + Send + 'static,
+{
+    run_test_with_method(port, "GET", path, expected, handler)
+}
+
+fn run_test_with_method<R, F>(port: u16, method: &str, path: &str, expected: R, handler: F)
 where
     R: Serialize,
     F: FnOnce(Request) -> TestResult + Send + 'static,
@@ -41,7 +65,7 @@ where
         Err(e) => panic!("got an error: {e}"),
     });
 
-    let response = ureq::get(&format!("http://127.0.0.1:{port}{path}"))
+    let response = ureq::request(method, &format!("http://127.0.0.1:{port}{path}"))
         .call()
         .unwrap()
         .into_string()
@@ -137,3 +161,278 @@ fn state() {
         req => TestResult::WrongRequest(req),
     });
 }
+
+#[test]
+fn invalidate_cache() {
+    let result: JsonResult<_, ()> = JsonResult::Success(());
+
+    run_test_with_method(
+        19105,
+        "POST",
+        "/chain/mock-0/invalidate_cache",
+        result,
+        |req| match req {
+            Request::InvalidateCache { chain_id, reply_to }
+                if chain_id.to_string().as_str() == "mock-0" =>
+            {
+                reply_to.send(Ok(())).unwrap();
+                TestResult::Success
+            }
+            req => TestResult::WrongRequest(req),
+        },
+    );
+}
+
+#[test]
+fn openapi() {
+    let config = Config::new("127.0.0.1".to_string(), 19106);
+    let (handle, _rx) = spawn(config);
+
+    let spec = RestClient::new("http://127.0.0.1:19106").openapi().unwrap();
+    for route in ROUTES {
+        let operation = &spec["paths"][route.path][route.method];
+        assert_eq!(operation["operationId"], route.operation_id);
+    }
+
+    handle.stop();
+    handle.join().unwrap();
+}
+
+/// The routes matched by the `router!` of the server, in OpenAPI notation
+fn router_routes() -> BTreeSet<(String, String)> {
+    include_str!("../src/server.rs")
+        .lines()
+        .filter_map(|line| {
+            let arm = line.trim().strip_prefix('(')?;
+            let (method, rest) = arm.split_once(") (")?;
+            let (path, _) = rest.split_once(") =>")?;
+            // `{id: String}` -> `{id}`
+            let path = path
+                .split('/')
+                .map(|segment| match segment.split_once(':') {
+                    Some((name, _)) if segment.starts_with('{') => format!("{name}}}"),
+                    _ => segment.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            Some((method.to_lowercase(), path))
+        })
+        .collect()
+}
+
+#[test]
+fn openapi_routes_match_router() {
+    let documented = ROUTES
+        .iter()
+        .map(|route| (route.method.to_string(), route.path.to_string()))
+        .collect::<BTreeSet<_>>();
+    let routed = router_routes();
+
+    assert!(!routed.is_empty());
+    assert_eq!(
+        routed.difference(&documented).collect::<Vec<_>>(),
+        Vec::<&(String, String)>::new(),
+        "routes of the server missing from openapi::ROUTES"
+    );
+    assert_eq!(
+        documented.difference(&routed).collect::<Vec<_>>(),
+        Vec::<&(String, String)>::new(),
+        "routes of openapi::ROUTES not handled by the server"
+    );
+}
+
+#[test]
+fn client() {
+    let config = Config::new("127.0.0.1".to_string(), 19107);
+    let (handle, rx) = spawn(config);
+
+    let chain_id = ChainId::from_str("mock-0").unwrap();
+    let expected = vec![chain_id.clone()];
+    std::thread::spawn(move || match rx.recv().unwrap() {
+        Request::GetChains { reply_to } => reply_to.send(Ok(vec![chain_id])).unwrap(),
+        req => panic!("got the wrong request: {req:?}"),
+    });
+
+    let chains = RestClient::new("http://127.0.0.1:19107").chains().unwrap();
+    assert_eq!(chains, expected);
+
+    handle.stop();
+    handle.join().unwrap();
+}
+
+#[test]
+fn health_attestation() {
+    let attestation = SignedHealthAttestation {
+        attestation: HealthAttestation {
+            chain_id: ChainId::from_str("mock-0").unwrap(),
+            account: "cosmos1mock".to_string(),
+            latest_height: Height::new(0, 42).unwrap(),
+            clients: vec![],
+            timestamp: 1_700_000_000,
+        },
+        public_key: "00".to_string(),
+        signature: "00".to_string(),
+    };
+    let result: JsonResult<_, ()> = JsonResult::Success(attestation.clone());
+
+    run_test(
+        19108,
+        "/chain/mock-0/attestation",
+        result,
+        |req| match req {
+            Request::HealthAttestation { chain_id, reply_to }
+                if chain_id.to_string().as_str() == "mock-0" =>
+            {
+                reply_to.send(Ok(attestation)).unwrap();
+                TestResult::Success
+            }
+            req => TestResult::WrongRequest(req),
+        },
+    );
+}
+
+#[test]
+fn pause_channel() {
+    let target = PauseTarget::Channel {
+        chain_id: ChainId::from_str("mock-0").unwrap(),
+        port_id: PortId::transfer(),
+        channel_id: ChannelId::new(0),
+    };
+    let result: JsonResult<_, ()> = JsonResult::Success(vec![target.clone()]);
+
+    run_test_with_method(
+        19109,
+        "POST",
+        "/chain/mock-0/channel/transfer/channel-0/pause",
+        result,
+        |req| match req {
+            Request::Pause {
+                target: requested,
+                reply_to,
+            } if requested == target => {
+                reply_to.send(Ok(vec![requested])).unwrap();
+                TestResult::Success
+            }
+            req => TestResult::WrongRequest(req),
+        },
+    );
+}
+
+#[test]
+fn decisions() {
+    let decision = Decision::new(
+        DecisionKind::Skipped,
+        ChainId::from_str("mock-0").unwrap(),
+        ChainId::from_str("mock-1").unwrap(),
+        PortId::transfer(),
+        ChannelId::new(0),
+        Some(Sequence::from(7)),
+        "not incentivized enough by the fees",
+    );
+    decision::global().publish(decision.clone());
+
+    let config = Config::new("127.0.0.1".to_string(), 19110);
+    let (handle, _rx) = spawn(config);
+
+    let decisions = RestClient::new("http://127.0.0.1:19110")
+        .decisions()
+        .unwrap();
+    assert!(decisions.contains(&decision));
+
+    handle.stop();
+    handle.join().unwrap();
+}
+
+#[test]
+fn archive() {
+    let tmp_dir = tempfile::TempDir::new().unwrap();
+    archive::init(Some(&tmp_dir.path().join("archive.ndjson")));
+    let events = (1..=3).map(|sequence| ArchivedEvent {
+        index: 0,
+        chain_id: ChainId::from_string("mock-0"),
+        height: 10,
+        tx_hash: "00".repeat(32),
+        kind: "send_packet".to_string(),
+        port_id: PortId::transfer(),
+        channel_id: Some(ChannelId::new(0)),
+        counterparty_port_id: PortId::transfer(),
+        counterparty_channel_id: Some(ChannelId::new(1)),
+        sequence: Some(Sequence::from(sequence)),
+        data: None,
+        timestamp: 0,
+    });
+    archive::global().unwrap().append(events).unwrap();
+
+    let config = Config::new("127.0.0.1".to_string(), 19113);
+    let (handle, _rx) = spawn(config);
+
+    let client = RestClient::new("http://127.0.0.1:19113");
+    let page = client.archive(0, 2).unwrap();
+    assert_eq!(page.events.len(), 2);
+    assert_eq!(page.next, Some(2));
+    let page = client.archive(2, 2).unwrap();
+    assert_eq!(page.events[0].index, 2);
+    assert_eq!(page.events[0].sequence, Some(Sequence::from(3)));
+    assert_eq!(page.next, None);
+
+    handle.stop();
+    handle.join().unwrap();
+}
+
+#[test]
+fn relay_status() {
+    let state = ChainState::RelayStatus(RelayStatus {
+        latest_height: 100,
+        scanned_heights: [("packet".to_string(), 90)].into_iter().collect(),
+        monitor_lag: Some(10),
+        pending_txs: vec!["0x01".to_string()],
+    });
+    let result: JsonResult<_, ()> = JsonResult::Success(state.clone());
+
+    run_test(
+        19111,
+        "/chain/mock-0/relay_status",
+        result,
+        |req| match req {
+            Request::ChainState {
+                chain_id,
+                query: ChainStateQuery::RelayStatus,
+                reply_to,
+            } if chain_id.to_string().as_str() == "mock-0" => {
+                reply_to.send(Ok(state)).unwrap();
+                TestResult::Success
+            }
+            req => TestResult::WrongRequest(req),
+        },
+    );
+}
+
+#[test]
+fn summary() {
+    let summary = StartupSummary {
+        version: "0.23.0".to_string(),
+        features: Features::compiled(),
+        rest: true,
+        chains: vec![ChainSummary {
+            id: ChainId::from_string("mock-0"),
+            chain_type: ChainType::Ckb4Ibc,
+            key_name: "relayer".to_string(),
+            account: Some("ckt1qyqwyxfa75whssgkq9ukkdd30d8c7txct0gqfvmy2v".to_string()),
+            submit_txs: true,
+            contract_outpoints: [("channel".to_string(), format!("0x{}:0", "00".repeat(32)))]
+                .into_iter()
+                .collect(),
+            packet_filter: None,
+            errors: vec![],
+        }],
+    };
+    let result: JsonResult<_, ()> = JsonResult::Success(summary.clone());
+
+    run_test(19112, "/summary", result, |req| match req {
+        Request::Summary { reply_to } => {
+            reply_to.send(Ok(summary)).unwrap();
+            TestResult::Success
+        }
+        req => TestResult::WrongRequest(req),
+    });
+}
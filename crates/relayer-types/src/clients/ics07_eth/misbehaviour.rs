@@ -0,0 +1,95 @@
+use ethereum_types::H256;
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::protobuf::Error as ProtoError;
+use ibc_proto::protobuf::Protobuf;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::core::ics02_client::error::Error as Ics02Error;
+use crate::core::ics24_host::identifier::ClientId;
+use crate::prelude::*;
+use crate::Height;
+
+use super::header::Header;
+use super::types::TreeHash;
+
+pub const ETH_MISBEHAVIOUR_TYPE_URL: &str = "/eth.finality.v1.misbehaviour";
+
+/// Two distinct headers finalized at the same slot: the one whose root the client
+/// holds, and the one of the canonical chain
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Misbehaviour {
+    pub client_id: ClientId,
+    /// Root of the header finalized at the slot of `header` by the client
+    pub conflicting_root: H256,
+    /// Header finalized by the canonical chain
+    pub header: Header,
+}
+
+impl crate::core::ics02_client::misbehaviour::Misbehaviour for Misbehaviour {
+    fn client_id(&self) -> &ClientId {
+        &self.client_id
+    }
+
+    fn height(&self) -> Height {
+        crate::core::ics02_client::header::Header::height(&self.header)
+    }
+}
+
+impl Protobuf<Any> for Misbehaviour {}
+
+impl TryFrom<Any> for Misbehaviour {
+    type Error = Ics02Error;
+
+    fn try_from(any: Any) -> Result<Self, Self::Error> {
+        if any.type_url != ETH_MISBEHAVIOUR_TYPE_URL {
+            return Err(Ics02Error::unknown_misbehaviour_type(any.type_url));
+        }
+        let misbehaviour: Misbehaviour = serde_json::from_slice(&any.value).map_err(|e| {
+            Ics02Error::decode_raw_misbehaviour(ProtoError::try_from_protobuf(e.to_string()))
+        })?;
+        Ok(misbehaviour)
+    }
+}
+
+impl From<Misbehaviour> for Any {
+    fn from(misbehaviour: Misbehaviour) -> Self {
+        let json = serde_json::to_string(&misbehaviour).expect("jsonify misbehaviour");
+        Any {
+            type_url: ETH_MISBEHAVIOUR_TYPE_URL.to_owned(),
+            value: json.into_bytes(),
+        }
+    }
+}
+
+impl core::fmt::Display for Misbehaviour {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(
+            f,
+            "{} slot {}: {:#x} conflicts with {:#x}",
+            self.client_id,
+            self.header.slot,
+            self.conflicting_root,
+            self.header.tree_hash_root()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eth_misbehaviour_serde() {
+        let misbehaviour = Misbehaviour {
+            client_id: ClientId::default(),
+            conflicting_root: H256::repeat_byte(1),
+            header: Header {
+                slot: 32,
+                ..Default::default()
+            },
+        };
+        let any: Any = misbehaviour.clone().into();
+        let decoded: Misbehaviour = any.try_into().expect("serde error");
+        assert_eq!(decoded, misbehaviour);
+    }
+}
@@ -0,0 +1,336 @@
+//! Archival index of the packet and channel events observed on the chains.
+//!
+//! Every packet and channel event of the batches received by the supervisor is
+//! appended to a file, one JSON object per line, whatever the filters and the paused
+//! channels. The byte offset of each event is indexed in memory, so that the archive
+//! can be exported page by page through the REST API without loading it whole, and
+//! without the consumers having to scan the chains themselves.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use ibc_relayer_types::core::ics04_channel::events::Attributes as ChannelAttributes;
+use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, PortId};
+use ibc_relayer_types::events::IbcEvent;
+
+use crate::event::monitor::EventBatch;
+use crate::event::IbcEventWithHeight;
+
+/// Maximum number of events in a page of the archive
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedEvent {
+    /// Position of the event in the archive, from 0
+    pub index: u64,
+    pub chain_id: ChainId,
+    pub height: u64,
+    pub tx_hash: String,
+    /// Type of the event, e.g. `send_packet` or `channel_open_init`
+    pub kind: String,
+    /// Port of the channel end on the chain, the source one for the packet events
+    pub port_id: PortId,
+    pub channel_id: Option<ChannelId>,
+    pub counterparty_port_id: PortId,
+    pub counterparty_channel_id: Option<ChannelId>,
+    /// Sequence of the packet, for the packet events
+    pub sequence: Option<Sequence>,
+    /// Hex-encoded data of the packet, for the packet events
+    pub data: Option<String>,
+    /// Seconds since the Unix epoch at which the event was archived
+    pub timestamp: u64,
+}
+
+impl ArchivedEvent {
+    /// The archived form of a packet or channel event, `None` for the other events.
+    /// Its index is assigned once it is appended to the archive.
+    pub fn from_event(chain_id: &ChainId, event: &IbcEventWithHeight) -> Option<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let archived = |port_id, channel_id, counterparty_port_id, counterparty_channel_id| Self {
+            index: 0,
+            chain_id: chain_id.clone(),
+            height: event.height.revision_height(),
+            tx_hash: hex::encode(event.tx_hash),
+            kind: event.event.event_type().as_str().to_owned(),
+            port_id,
+            channel_id,
+            counterparty_port_id,
+            counterparty_channel_id,
+            sequence: None,
+            data: None,
+            timestamp,
+        };
+
+        if let Some(packet) = event.event.packet() {
+            return Some(Self {
+                sequence: Some(packet.sequence),
+                data: Some(hex::encode(&packet.data)),
+                ..archived(
+                    packet.source_port.clone(),
+                    Some(packet.source_channel.clone()),
+                    packet.destination_port.clone(),
+                    Some(packet.destination_channel.clone()),
+                )
+            });
+        }
+
+        let attributes: ChannelAttributes = match &event.event {
+            IbcEvent::OpenInitChannel(ev) => ev.clone().into(),
+            IbcEvent::OpenTryChannel(ev) => ev.clone().into(),
+            IbcEvent::OpenAckChannel(ev) => ev.clone().into(),
+            IbcEvent::OpenConfirmChannel(ev) => ev.clone().into(),
+            IbcEvent::CloseInitChannel(ev) => ev.clone().into(),
+            IbcEvent::CloseConfirmChannel(ev) => ev.clone().into(),
+            _ => return None,
+        };
+        Some(archived(
+            attributes.port_id,
+            attributes.channel_id,
+            attributes.counterparty_port_id,
+            attributes.counterparty_channel_id,
+        ))
+    }
+}
+
+/// Page of the archive
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivePage {
+    pub events: Vec<ArchivedEvent>,
+    /// Index of the first event of the next page, `None` if this page is the last one
+    pub next: Option<u64>,
+}
+
+struct ArchiveState {
+    file: File,
+    /// Byte offset of each event in the file
+    offsets: Vec<u64>,
+    /// Size of the file
+    end: u64,
+}
+
+pub struct EventArchive {
+    path: PathBuf,
+    state: Mutex<ArchiveState>,
+}
+
+impl EventArchive {
+    /// Open the archive at `path`, creating it if needed. A last event left incomplete
+    /// by an interrupted write is dropped.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut offsets = vec![];
+        let mut end = 0;
+        let mut reader = BufReader::new(&mut file);
+        let mut line = vec![];
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line)? as u64;
+            if read == 0 || line.last() != Some(&b'\n') {
+                break;
+            }
+            offsets.push(end);
+            end += read;
+        }
+        if file.metadata()?.len() > end {
+            warn!(
+                "dropping the incomplete last event of the archive {}",
+                path.display()
+            );
+            file.set_len(end)?;
+        }
+
+        Ok(Self {
+            path: path.to_owned(),
+            state: Mutex::new(ArchiveState { file, offsets, end }),
+        })
+    }
+
+    /// Number of events in the archive
+    pub fn len(&self) -> u64 {
+        self.state.lock().unwrap().offsets.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Append the events, indexing them from the end of the archive
+    pub fn append(&self, events: impl IntoIterator<Item = ArchivedEvent>) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let mut content = vec![];
+        let mut offsets = vec![];
+        for (index, mut event) in (state.offsets.len() as u64..).zip(events) {
+            event.index = index;
+            offsets.push(state.end + content.len() as u64);
+            serde_json::to_writer(&mut content, &event).map_err(io::Error::from)?;
+            content.push(b'\n');
+        }
+        if content.is_empty() {
+            return Ok(());
+        }
+
+        state.file.write_all(&content)?;
+        state.offsets.extend(offsets);
+        state.end += content.len() as u64;
+        Ok(())
+    }
+
+    /// At most `limit` events from the one of index `from`, capped to `MAX_PAGE_SIZE`
+    pub fn page(&self, from: u64, limit: usize) -> io::Result<ArchivePage> {
+        let (start, end, next) = {
+            let state = self.state.lock().unwrap();
+            let len = state.offsets.len() as u64;
+            let from = from.min(len);
+            let to = (from + limit.min(MAX_PAGE_SIZE) as u64).min(len);
+            let offset = |index: u64| {
+                state
+                    .offsets
+                    .get(index as usize)
+                    .copied()
+                    .unwrap_or(state.end)
+            };
+            (offset(from), offset(to), (to < len).then_some(to))
+        };
+
+        // the events are only appended, so the range stays valid once the lock is released
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(start))?;
+        let events = BufReader::new(file.take(end - start))
+            .lines()
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect::<io::Result<_>>()?;
+
+        Ok(ArchivePage { events, next })
+    }
+}
+
+static GLOBAL_ARCHIVE: OnceCell<EventArchive> = OnceCell::new();
+
+/// Open the archive the supervisor appends the events to, if `archive_file` is set
+pub fn init(path: Option<&Path>) {
+    let Some(path) = path else {
+        return;
+    };
+    match EventArchive::open(path) {
+        Ok(archive) => {
+            info!(
+                "archiving the packet and channel events to {}, {} events archived so far",
+                path.display(),
+                archive.len()
+            );
+            let _ = GLOBAL_ARCHIVE.set(archive);
+        }
+        Err(e) => warn!("failed to open the event archive {}: {e}", path.display()),
+    }
+}
+
+/// The archive of the events, if it is enabled
+pub fn global() -> Option<&'static EventArchive> {
+    GLOBAL_ARCHIVE.get()
+}
+
+/// Append the packet and channel events of the batch to the archive, if it is enabled
+pub fn archive_batch(batch: &EventBatch) {
+    let Some(archive) = global() else {
+        return;
+    };
+    let events = batch
+        .events
+        .iter()
+        .filter_map(|event| ArchivedEvent::from_event(&batch.chain_id, event));
+    if let Err(e) = archive.append(events) {
+        warn!("failed to archive the events of {}: {e}", batch.chain_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use tempfile::TempDir;
+
+    use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+    use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, PortId};
+
+    use super::{ArchivedEvent, EventArchive, MAX_PAGE_SIZE};
+
+    fn event(sequence: u64) -> ArchivedEvent {
+        ArchivedEvent {
+            index: 0,
+            chain_id: ChainId::from_string("chain-a"),
+            height: 10,
+            tx_hash: "00".repeat(32),
+            kind: "send_packet".to_owned(),
+            port_id: PortId::transfer(),
+            channel_id: Some(ChannelId::new(0)),
+            counterparty_port_id: PortId::transfer(),
+            counterparty_channel_id: Some(ChannelId::new(1)),
+            sequence: Some(Sequence::from(sequence)),
+            data: Some("01".to_owned()),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_archive_pages() {
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("archive.ndjson");
+
+        let archive = EventArchive::open(&path).unwrap();
+        archive.append((1..=3).map(event)).unwrap();
+        archive.append((4..=5).map(event)).unwrap();
+        assert_eq!(archive.len(), 5);
+
+        let page = archive.page(0, 2).unwrap();
+        assert_eq!(page.next, Some(2));
+        let indexes = page.events.iter().map(|e| e.index).collect::<Vec<_>>();
+        assert_eq!(indexes, vec![0, 1]);
+
+        let page = archive.page(3, MAX_PAGE_SIZE + 1).unwrap();
+        assert_eq!(page.next, None);
+        assert_eq!(page.events[1].sequence, Some(Sequence::from(5)));
+        assert_eq!(page.events[1].index, 4);
+
+        assert!(archive.page(10, 2).unwrap().events.is_empty());
+    }
+
+    #[test]
+    fn test_archive_drops_incomplete_event() {
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("archive.ndjson");
+
+        EventArchive::open(&path)
+            .unwrap()
+            .append((1..=2).map(event))
+            .unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"index\":")
+            .unwrap();
+
+        let archive = EventArchive::open(&path).unwrap();
+        assert_eq!(archive.len(), 2);
+        archive.append([event(3)]).unwrap();
+        let page = archive.page(0, 10).unwrap();
+        assert_eq!(page.events.len(), 3);
+        assert_eq!(page.events[2].index, 2);
+    }
+}
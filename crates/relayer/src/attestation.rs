@@ -0,0 +1,149 @@
+//! Signed health attestations.
+//!
+//! An attestation is a snapshot of what the relayer observes on a chain: the latest
+//! height along with the latest height of every client hosted there. It is signed with
+//! the relayer key of that chain, so that a third party can check that the relayer was
+//! live at the given time by verifying the signature and comparing the reported heights
+//! against on-chain data.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use ibc_relayer_types::core::ics02_client::client_state::ClientState;
+use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ClientId};
+use ibc_relayer_types::Height;
+
+use crate::chain::handle::ChainHandle;
+use crate::chain::requests::{PageRequest, QueryClientStatesRequest};
+use crate::error::Error;
+use crate::keyring::errors::Error as KeyringError;
+use crate::keyring::AnySigningKeyPair;
+
+/// Latest state of a client hosted on the attested chain
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientAttestation {
+    pub client_id: ClientId,
+    pub counterparty_chain_id: ChainId,
+    pub latest_height: Height,
+    pub frozen: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthAttestation {
+    pub chain_id: ChainId,
+    /// Account of the relayer key which signs the attestation
+    pub account: String,
+    pub latest_height: Height,
+    pub clients: Vec<ClientAttestation>,
+    /// Seconds since the Unix epoch at which the attestation was produced
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedHealthAttestation {
+    pub attestation: HealthAttestation,
+    /// Hex encoded public key of the relayer key
+    pub public_key: String,
+    /// Hex encoded signature over [`HealthAttestation::digest`]
+    pub signature: String,
+}
+
+impl HealthAttestation {
+    /// Query the latest height and the client states of the given chain.
+    pub fn collect<Chain: ChainHandle>(chain: &Chain, account: String) -> Result<Self, Error> {
+        let latest_height = chain.query_latest_height()?;
+        let clients = chain
+            .query_clients(QueryClientStatesRequest {
+                pagination: Some(PageRequest::all()),
+            })?
+            .into_iter()
+            .map(|client| ClientAttestation {
+                client_id: client.client_id,
+                counterparty_chain_id: client.client_state.chain_id(),
+                latest_height: client.client_state.latest_height(),
+                frozen: client.client_state.is_frozen(),
+            })
+            .collect();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+
+        Ok(Self {
+            chain_id: chain.id(),
+            account,
+            latest_height,
+            clients,
+            timestamp,
+        })
+    }
+
+    /// SHA-256 digest of the JSON encoding of the attestation, which is the message
+    /// being signed. The key then signs it with the signing scheme of its chain.
+    pub fn digest(&self) -> Result<Vec<u8>, Error> {
+        let encoded =
+            serde_json::to_vec(self).map_err(|e| Error::key_base(KeyringError::encode(e)))?;
+        Ok(Sha256::digest(encoded).to_vec())
+    }
+
+    pub fn sign(self, key: &AnySigningKeyPair) -> Result<SignedHealthAttestation, Error> {
+        let signature = key.sign(&self.digest()?).map_err(Error::key_base)?;
+        Ok(SignedHealthAttestation {
+            attestation: self,
+            public_key: hex::encode(key.public_key()),
+            signature: hex::encode(signature),
+        })
+    }
+}
+
+/// Collect a health attestation of the given chain and sign it with its relayer key.
+pub fn attest_health<Chain: ChainHandle>(chain: &Chain) -> Result<SignedHealthAttestation, Error> {
+    let key = chain.get_key()?;
+    HealthAttestation::collect(chain, key.account())?.sign(&key)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use hdpath::StandardHDPath;
+    use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1};
+    use sha2::{Digest, Sha256};
+
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+    use ibc_relayer_types::Height;
+
+    use super::HealthAttestation;
+    use crate::config::AddressType;
+    use crate::keyring::{AnySigningKeyPair, Secp256k1KeyPair, SigningKeyPair};
+
+    #[test]
+    fn test_signed_attestation_verifies() {
+        let mnemonic =
+            "feed label choose question decrease slab regular humor salmon wheel slab inform";
+        let hd_path = StandardHDPath::from_str("m/44'/118'/0'/0/0").unwrap();
+        let key: AnySigningKeyPair =
+            Secp256k1KeyPair::from_mnemonic(mnemonic, &hd_path, &AddressType::Cosmos, "cosmos")
+                .unwrap()
+                .into();
+        let attestation = HealthAttestation {
+            chain_id: ChainId::from_string("mock-0"),
+            account: key.account(),
+            latest_height: Height::new(0, 42).unwrap(),
+            clients: vec![],
+            timestamp: 1_700_000_000,
+        };
+        let digest = attestation.digest().unwrap();
+
+        let signed = attestation.sign(&key).unwrap();
+
+        let public_key = PublicKey::from_slice(&hex::decode(signed.public_key).unwrap()).unwrap();
+        let signature = Signature::from_compact(&hex::decode(signed.signature).unwrap()).unwrap();
+        let message = Message::from_slice(&Sha256::digest(digest)).unwrap();
+        Secp256k1::verification_only()
+            .verify_ecdsa(&message, &signature, &public_key)
+            .unwrap();
+    }
+}
@@ -0,0 +1,116 @@
+//! Drift between the chain configs and the states deployed on chain.
+//!
+//! Several fields of a chain config mirror what is deployed on the chain: the type args
+//! of the CKB contracts, the addresses of the EVM contracts, the client identifiers of
+//! the connections or the channels allowed by the packet filter. Nothing checks them once
+//! the relayer is running, so they go stale silently after a redeployment. The audit
+//! compares them against the live states and reports every field which drifted along
+//! with a suggested correction.
+
+use serde::{Deserialize, Serialize};
+
+use ibc_relayer_types::core::ics04_channel::channel::State;
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+use crate::chain::handle::ChainHandle;
+use crate::chain::requests::{IncludeProof, QueryChannelRequest, QueryHeight};
+use crate::config::filter::{ChannelPolicy, PacketFilter};
+use crate::config::ChainConfig;
+use crate::error::Error;
+
+/// A config field which does not match the states on chain
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigDrift {
+    /// Path of the field in the chain config, e.g. `packet_filter.list`
+    pub field: String,
+    pub configured: String,
+    /// Value found on chain, if any
+    pub actual: Option<String>,
+    pub suggestion: String,
+}
+
+impl ConfigDrift {
+    pub fn new(
+        field: impl Into<String>,
+        configured: impl ToString,
+        actual: Option<String>,
+        suggestion: impl Into<String>,
+    ) -> Self {
+        Self {
+            field: field.into(),
+            configured: configured.to_string(),
+            actual,
+            suggestion: suggestion.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigAudit {
+    pub chain_id: ChainId,
+    pub drifts: Vec<ConfigDrift>,
+}
+
+impl ConfigAudit {
+    /// Compare the config of the given chain against its live states.
+    pub fn collect<Chain: ChainHandle>(chain: &Chain, config: &ChainConfig) -> Result<Self, Error> {
+        let mut drifts = chain.audit_config()?;
+        if let ChainConfig::Cosmos(config) = config {
+            drifts.extend(audit_packet_filter(chain, &config.packet_filter));
+        }
+
+        Ok(Self {
+            chain_id: chain.id(),
+            drifts,
+        })
+    }
+
+    pub fn has_drifted(&self) -> bool {
+        !self.drifts.is_empty()
+    }
+}
+
+/// Check that the channels explicitly allowed by the packet filter exist and are open.
+/// The patterns with wildcards match whatever channels exist, so they never drift.
+pub fn audit_packet_filter<Chain: ChainHandle>(
+    chain: &Chain,
+    packet_filter: &PacketFilter,
+) -> Vec<ConfigDrift> {
+    let ChannelPolicy::Allow(filters) = &packet_filter.channel_policy else {
+        return vec![];
+    };
+
+    filters
+        .iter_exact()
+        .filter_map(|(port_id, channel_id)| {
+            let configured = format!("{port_id}/{channel_id}");
+            let request = QueryChannelRequest {
+                port_id: port_id.clone(),
+                channel_id: channel_id.clone(),
+                height: QueryHeight::Latest,
+            };
+            match chain.query_channel(request, IncludeProof::No) {
+                Ok((channel_end, _)) if channel_end.is_open() => None,
+                Ok((channel_end, _)) if channel_end.state_matches(&State::Uninitialized) => {
+                    Some(missing_channel(configured))
+                }
+                Ok((channel_end, _)) => Some(ConfigDrift::new(
+                    "packet_filter.list",
+                    configured,
+                    Some(channel_end.state.to_string()),
+                    "the channel is not open, remove it from the allowed channels",
+                )),
+                Err(_) => Some(missing_channel(configured)),
+            }
+        })
+        .collect()
+}
+
+fn missing_channel(configured: String) -> ConfigDrift {
+    ConfigDrift::new(
+        "packet_filter.list",
+        configured,
+        None,
+        "the channel does not exist, remove it from the allowed channels or fix its identifiers",
+    )
+}
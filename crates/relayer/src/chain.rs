@@ -6,7 +6,10 @@ pub mod cosmos;
 pub mod counterparty;
 pub mod endpoint;
 pub mod eth;
+pub mod evm;
 pub mod handle;
+#[cfg(test)]
+pub mod mock_ckb;
 pub mod requests;
 pub mod runtime;
 pub mod tracking;
@@ -26,6 +29,8 @@ pub enum ChainType {
     Axon,
     Ckb,
     Ckb4Ibc,
+    /// EVM chains on which the IBC handler contract is deployed
+    Evm,
 }
 
 impl<'de> Deserialize<'de> for ChainType {
@@ -41,6 +46,7 @@ impl<'de> Deserialize<'de> for ChainType {
             "eth" => Ok(Self::Eth),
             "axon" => Ok(Self::Axon),
             "ckb" => Ok(Self::Ckb),
+            "evm" => Ok(Self::Evm),
 
             // NOTE(new): Add a case here
             _ => Err(D::Error::unknown_variant(&original, &["cosmos-sdk"])), // NOTE(new): mention the new variant here
@@ -69,6 +75,9 @@ mod tests {
         assert!(matches!(parse("cosmossdk"), Ok(CosmosSdk)));
         assert!(matches!(parse("cosmos-sdk"), Ok(CosmosSdk)));
 
+        assert!(matches!(parse("evm"), Ok(Evm)));
+        assert!(matches!(parse("EVM"), Ok(Evm)));
+
         // NOTE(new): Add tests here
 
         assert!(matches!(parse("hello-world"), Err(_)));
@@ -5,7 +5,6 @@ use std::{
     collections::HashMap,
     str::FromStr,
     sync::{self, Arc},
-    thread,
 };
 
 use axon_tools::{
@@ -23,25 +22,29 @@ use crate::{
         requests::QueryHeight,
     },
     client_state::{AnyClientState, IdentifiedAnyClientState},
-    config::{axon::AxonChainConfig, filter::port, ChainConfig},
+    config::{axon::AxonChainConfig, filter::port, ChainConfig, SignerKind},
     connection::ConnectionMsgType,
     consensus_state::AnyConsensusState,
     denom::DenomTrace,
     error::Error,
     event::{monitor::TxMonitorCmd, IbcEventWithHeight},
-    keyring::{KeyRing, Secp256k1KeyPair},
+    keyring::{
+        connect_eth_ledger, encode_bech32, EthSigner, KeyRing, RemoteEthSigner, RemoteSigner,
+        Secp256k1KeyPair,
+    },
     light_client::{axon::LightClient as AxonLightClient, LightClient},
     misbehaviour::MisbehaviourEvidence,
-    util::collate::collate,
+    progress::{RelayProgress, RelayStatus, SharedRelayProgress},
+    util::{collate::collate, panic::spawn_chain_thread},
 };
 use eth_light_client_in_ckb_prover::Receipts;
 use eth_light_client_in_ckb_verification::trie;
 use ethers::{
     abi::{AbiDecode, AbiEncode},
     contract::ContractError,
-    prelude::{k256::ecdsa::SigningKey, EthLogDecode, SignerMiddleware},
+    prelude::{EthLogDecode, SignerMiddleware},
     providers::{Middleware, Provider, Ws},
-    signers::Wallet,
+    signers::Signer as _,
     types::{
         Block, BlockId, BlockNumber, Transaction, TransactionReceipt, TransactionRequest, TxHash,
         H160, U64,
@@ -98,17 +101,18 @@ use tendermint_rpc::{endpoint::broadcast::tx_sync::Response, query};
 
 use self::{
     contract::{OwnableIBCHandler, OwnableIBCHandlerEvents},
+    handler::{message_event, IbcHandler, ProofTxHashes},
     monitor::AxonEventMonitor,
 };
 
-type ContractProvider = SignerMiddleware<Provider<Ws>, Wallet<SigningKey>>;
+type ContractProvider = SignerMiddleware<Provider<Ws>, EthSigner>;
 type Contract = OwnableIBCHandler<ContractProvider>;
 type ContractEvents = OwnableIBCHandlerEvents;
 
 use super::{
     client::ClientSettings,
     cosmos::encode::key_pair_to_signer,
-    endpoint::{ChainEndpoint, ChainStatus, HealthCheck},
+    endpoint::{ChainCapabilities, ChainEndpoint, ChainStatus, HealthCheck},
     handle::{CacheTxHashStatus, Subscription},
     requests::{
         self, CrossChainQueryRequest, IncludeProof, QueryChannelClientStateRequest,
@@ -126,8 +130,9 @@ use super::{
 };
 use tokio::runtime::{self, Runtime as TokioRuntime};
 
-mod contract;
-mod monitor;
+pub(crate) mod contract;
+pub(crate) mod handler;
+pub(crate) mod monitor;
 mod msg;
 mod rpc;
 
@@ -138,13 +143,12 @@ pub struct AxonChain {
     config: AxonChainConfig,
     light_client: AxonLightClient,
     tx_monitor_cmd: Option<TxMonitorCmd>,
-    contract: Contract,
+    handler: IbcHandler<ContractProvider>,
     rpc_client: rpc::AxonRpcClient,
     client: Arc<ContractProvider>,
     keybase: KeyRing<Secp256k1KeyPair>,
-    conn_tx_hash: HashMap<ConnectionId, TxHash>,
-    chan_tx_hash: HashMap<(ChannelId, PortId), TxHash>,
-    packet_tx_hash: HashMap<(ChannelId, PortId, u64), TxHash>,
+    tx_hashes: ProofTxHashes,
+    progress: SharedRelayProgress,
 }
 
 // Allow temporarily for development. Should remove when work is done.
@@ -169,16 +173,38 @@ impl ChainEndpoint for AxonChain {
         let client = rt
             .block_on(Provider::<Ws>::connect(url.to_string()))
             .map_err(|_| Error::web_socket(url.into()))?;
-        let key_entry = keybase.get_key(&config.key_name).map_err(Error::key_base)?;
-        let wallet = key_entry.into_ether_wallet();
-        let client = Arc::new(SignerMiddleware::new(client, wallet));
+        let chain_id = || {
+            rt.block_on(client.get_chainid())
+                .map(|chain_id| chain_id.as_u64())
+                .map_err(|e| Error::rpc_response(e.to_string()))
+        };
+        let signer = match config.signer {
+            SignerKind::Keyring => {
+                let key_entry = keybase.get_key(&config.key_name).map_err(Error::key_base)?;
+                EthSigner::Wallet(key_entry.into_ether_wallet())
+            }
+            SignerKind::Ledger => rt
+                .block_on(connect_eth_ledger(chain_id()?))
+                .map_err(Error::key_base)?,
+            SignerKind::Remote => {
+                let remote = RemoteSigner::from_signing(&config.signing, rt.clone())
+                    .map_err(Error::key_base)?;
+                let remote = rt
+                    .block_on(RemoteEthSigner::connect(remote, chain_id()?))
+                    .map_err(Error::key_base)?;
+                EthSigner::Remote(remote)
+            }
+        };
+        let client = Arc::new(SignerMiddleware::new(client, signer));
 
         let contract = Contract::new(config.contract_address, Arc::clone(&client));
+        let handler = IbcHandler::new(rt.clone(), contract);
 
         let light_client = AxonLightClient::from_config(&config, rt.clone())?;
         let metadata = rt.block_on(rpc_client.get_current_metadata())?;
         let epoch_len = metadata.version.end - metadata.version.start + 1;
         light_client.bootstrap(client.clone(), rpc_client.clone(), epoch_len)?;
+        let progress = RelayProgress::shared(config.progress_file.clone());
 
         Ok(Self {
             rt,
@@ -186,12 +212,11 @@ impl ChainEndpoint for AxonChain {
             keybase,
             light_client,
             tx_monitor_cmd: None,
-            contract,
+            handler,
             rpc_client,
             client,
-            conn_tx_hash: HashMap::new(),
-            chan_tx_hash: HashMap::new(),
-            packet_tx_hash: HashMap::new(),
+            tx_hashes: ProofTxHashes::default(),
+            progress,
         })
     }
 
@@ -204,6 +229,15 @@ impl ChainEndpoint for AxonChain {
         Ok(HealthCheck::Healthy)
     }
 
+    fn capabilities(&self) -> ChainCapabilities {
+        // the IBC contracts of Axon have no fee middleware, queries or upgrades
+        ChainCapabilities {
+            ordered_channels: true,
+            proofs: true,
+            ..Default::default()
+        }
+    }
+
     fn subscribe(&mut self) -> Result<Subscription, Error> {
         let tx_monitor_cmd = match &self.tx_monitor_cmd {
             Some(tx_monitor_cmd) => tx_monitor_cmd,
@@ -218,6 +252,16 @@ impl ChainEndpoint for AxonChain {
         Ok(subscription)
     }
 
+    fn query_relay_status(&self) -> Result<RelayStatus, Error> {
+        let latest_height = self
+            .rt
+            .block_on(self.client.get_block_number())
+            .map_err(|e| Error::rpc_response(e.to_string()))?
+            .as_u64();
+        let progress = self.progress.lock().map_err(Error::other)?;
+        Ok(progress.status(latest_height))
+    }
+
     fn keybase(&self) -> &KeyRing<Self::SigningKeyPair> {
         &self.keybase
     }
@@ -227,6 +271,11 @@ impl ChainEndpoint for AxonChain {
     }
 
     fn get_signer(&self) -> Result<Signer, Error> {
+        if self.config.signer != SignerKind::Keyring {
+            let address = self.client.signer().address();
+            let account = encode_bech32("axon", address.as_bytes()).map_err(Error::key_base)?;
+            return account.parse().map_err(Error::other);
+        }
         let key_entry = self
             .keybase()
             .get_key(&self.config.key_name)
@@ -321,7 +370,7 @@ impl ChainEndpoint for AxonChain {
     ) -> Result<Vec<IdentifiedAnyClientState>, Error> {
         let client_states: Vec<_> = self
             .rt
-            .block_on(self.contract.get_client_states().call())
+            .block_on(self.handler.contract().get_client_states().call())
             .map_err(convert_err)?;
         let client_states = client_states
             .iter()
@@ -333,64 +382,24 @@ impl ChainEndpoint for AxonChain {
     fn query_client_state(
         &self,
         request: QueryClientStateRequest,
-        include_proof: IncludeProof,
+        _include_proof: IncludeProof,
     ) -> Result<(AnyClientState, Option<MerkleProof>), Error> {
-        if matches!(request.height, QueryHeight::Specific(_)) {
-            return Err(Error::other_error(
-                "not support client state query in specific height".to_string(),
-            ));
-        }
-        let (client_state, _) = self
-            .rt
-            .block_on(
-                self.contract
-                    .get_client_state(request.client_id.to_string())
-                    .call(),
-            )
-            .map_err(convert_err)?;
-
-        let client_state = to_any_client_state(&client_state)?;
-        Ok((client_state, None))
+        self.handler.query_client_state(request)
     }
 
     fn query_consensus_state(
         &self,
         request: QueryConsensusStateRequest,
-        include_proof: IncludeProof,
+        _include_proof: IncludeProof,
     ) -> Result<(AnyConsensusState, Option<MerkleProof>), Error> {
-        let client_id: String = request.client_id.to_string();
-        let height = request.consensus_height;
-        let height = HeightData {
-            revision_number: height.revision_number(),
-            revision_height: height.revision_height(),
-        };
-        let (consensus_state, _) = self
-            .rt
-            .block_on(self.contract.get_consensus_state(client_id, height).call())
-            .map_err(convert_err)?;
-        let consensus_state = to_any_consensus_state(&consensus_state)?;
-        Ok((consensus_state, None))
+        self.handler.query_consensus_state(request)
     }
 
     fn query_consensus_state_heights(
         &self,
         request: QueryConsensusStateHeightsRequest,
     ) -> Result<Vec<Height>, Error> {
-        let client_id = request.client_id;
-        let heights: Vec<_> = self
-            .rt
-            .block_on(
-                self.contract
-                    .get_consensus_heights(client_id.to_string())
-                    .call(),
-            )
-            .map_err(convert_err)?;
-        let heights = heights
-            .iter()
-            .map(|height| Height::new(height.revision_number, height.revision_height))
-            .collect::<Result<Vec<Height>, _>>()
-            .map_err(|_| Error::invalid_height_no_source())?;
-        Ok(heights)
+        self.handler.query_consensus_state_heights(request)
     }
 
     fn query_upgraded_client_state(
@@ -409,323 +418,113 @@ impl ChainEndpoint for AxonChain {
 
     fn query_connections(
         &self,
-        request: QueryConnectionsRequest,
+        _request: QueryConnectionsRequest,
     ) -> Result<Vec<IdentifiedConnectionEnd>, Error> {
-        let connections: Vec<_> = self
-            .rt
-            .block_on(self.contract.get_connections().call())
-            .map_err(convert_err)?;
-        let connections = connections
-            .into_iter()
-            .map(IdentifiedConnectionEnd::from)
-            .collect();
-        Ok(connections)
+        self.handler.query_connections()
     }
 
     fn query_client_connections(
         &self,
         request: QueryClientConnectionsRequest,
     ) -> Result<Vec<ConnectionId>, Error> {
-        let connection_ids: Vec<_> = self
-            .rt
-            .block_on(
-                self.contract
-                    .get_client_connections(request.client_id.to_string())
-                    .call(),
-            )
-            .map_err(convert_err)?;
-        let connection_ids = connection_ids
-            .iter()
-            .map(|id| ConnectionId::from_str(id.as_ref()))
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| Error::other_error(e.to_string()))?;
-        Ok(connection_ids)
+        self.handler.query_client_connections(request)
     }
 
     fn query_connection(
         &self,
         request: QueryConnectionRequest,
-        include_proof: IncludeProof,
+        _include_proof: IncludeProof,
     ) -> Result<(ConnectionEnd, Option<MerkleProof>), Error> {
-        if matches!(request.height, QueryHeight::Specific(_)) {
-            return Err(Error::other_error(
-                "not support connection query in specific height".to_string(),
-            ));
-        }
-        let (connection_end, _) = self
-            .rt
-            .block_on(
-                self.contract
-                    .get_connection(request.connection_id.to_string())
-                    .call(),
-            )
-            .map_err(convert_err)?;
-        let connection_end = connection_end.into();
-        Ok((connection_end, None))
+        self.handler.query_connection(request)
     }
 
     fn query_connection_channels(
         &self,
         request: QueryConnectionChannelsRequest,
     ) -> Result<Vec<IdentifiedChannelEnd>, Error> {
-        let channels: Vec<_> = self
-            .rt
-            .block_on(
-                self.contract
-                    .get_connection_channels(request.connection_id.to_string())
-                    .call(),
-            )
-            .map_err(convert_err)?;
-        let channels = channels
-            .into_iter()
-            .map(IdentifiedChannelEnd::from)
-            .collect();
-        Ok(channels)
+        self.handler.query_connection_channels(request)
     }
 
     fn query_channels(
         &self,
-        request: QueryChannelsRequest,
+        _request: QueryChannelsRequest,
     ) -> Result<Vec<IdentifiedChannelEnd>, Error> {
-        let channels: Vec<_> = self
-            .rt
-            .block_on(self.contract.get_channels().call())
-            .map_err(convert_err)?;
-        let channels = channels
-            .into_iter()
-            .map(IdentifiedChannelEnd::from)
-            .collect();
-        Ok(channels)
+        self.handler.query_channels()
     }
 
     fn query_channel(
         &self,
         request: QueryChannelRequest,
-        include_proof: IncludeProof,
+        _include_proof: IncludeProof,
     ) -> Result<(ChannelEnd, Option<MerkleProof>), Error> {
-        if matches!(request.height, QueryHeight::Specific(_)) {
-            return Err(Error::other_error(
-                "not support channel query in specific height".to_string(),
-            ));
-        }
-        let (channel_end, _) = self
-            .rt
-            .block_on(
-                self.contract
-                    .get_channel(request.port_id.to_string(), request.channel_id.to_string())
-                    .call(),
-            )
-            .map_err(convert_err)?;
-        let channel_end = channel_end.into();
-        Ok((channel_end, None))
+        self.handler.query_channel(request)
     }
 
     fn query_channel_client_state(
         &self,
         request: QueryChannelClientStateRequest,
     ) -> Result<Option<IdentifiedAnyClientState>, Error> {
-        let (client_state, found) = self
-            .rt
-            .block_on(
-                self.contract
-                    .get_channel_client_state(
-                        request.port_id.to_string(),
-                        request.channel_id.to_string(),
-                    )
-                    .call(),
-            )
-            .map_err(convert_err)?;
-
-        if !found {
-            Ok(None)
-        } else {
-            let client_state = to_identified_any_client_state(&client_state)?;
-            Ok(Some(client_state))
-        }
+        self.handler.query_channel_client_state(request)
     }
 
     fn query_packet_commitment(
         &self,
         request: QueryPacketCommitmentRequest,
-        include_proof: IncludeProof,
+        _include_proof: IncludeProof,
     ) -> Result<(Vec<u8>, Option<MerkleProof>), Error> {
-        let (commitment, _) = self
-            .rt
-            .block_on(
-                self.contract
-                    .get_hashed_packet_commitment(
-                        request.port_id.to_string(),
-                        request.channel_id.to_string(),
-                        request.sequence.into(),
-                    )
-                    .call(),
-            )
-            .map_err(convert_err)?;
-        Ok((commitment.to_vec(), None))
+        self.handler.query_packet_commitment(request)
     }
 
     fn query_packet_commitments(
         &self,
         request: QueryPacketCommitmentsRequest,
     ) -> Result<(Vec<Sequence>, Height), Error> {
-        let commitment_sequences = self
-            .rt
-            .block_on(
-                self.contract
-                    .get_hashed_packet_commitment_sequences(
-                        request.port_id.to_string(),
-                        request.channel_id.to_string(),
-                    )
-                    .call(),
-            )
-            .map_err(convert_err)?;
-
-        let commitment_sequences = commitment_sequences
-            .iter()
-            .map(|seq| (*seq).into())
-            .collect();
-        let height = Height::new(u64::MAX, u64::MAX).unwrap();
-        Ok((commitment_sequences, height))
+        self.handler.query_packet_commitments(request)
     }
 
     fn query_packet_receipt(
         &self,
         request: QueryPacketReceiptRequest,
-        include_proof: IncludeProof,
+        _include_proof: IncludeProof,
     ) -> Result<(Vec<u8>, Option<MerkleProof>), Error> {
-        let has_receipt = self
-            .rt
-            .block_on(
-                self.contract
-                    .has_packet_receipt(
-                        request.port_id.to_string(),
-                        request.channel_id.to_string(),
-                        request.sequence.into(),
-                    )
-                    .call(),
-            )
-            .map_err(convert_err)?;
-        Ok((vec![has_receipt as u8], None))
+        self.handler.query_packet_receipt(request)
     }
 
     fn query_unreceived_packets(
         &self,
         request: QueryUnreceivedPacketsRequest,
     ) -> Result<Vec<Sequence>, Error> {
-        let mut sequences: Vec<Sequence> = vec![];
-        for seq in request.packet_commitment_sequences {
-            let has_receipt = self
-                .rt
-                .block_on(
-                    self.contract
-                        .has_packet_receipt(
-                            request.port_id.to_string(),
-                            request.channel_id.to_string(),
-                            seq.into(),
-                        )
-                        .call(),
-                )
-                .map_err(convert_err)?;
-            if !has_receipt {
-                sequences.push(seq);
-            }
-        }
-        Ok(sequences)
+        self.handler.query_unreceived_packets(request)
     }
 
     fn query_packet_acknowledgement(
         &self,
         request: QueryPacketAcknowledgementRequest,
-        include_proof: IncludeProof,
+        _include_proof: IncludeProof,
     ) -> Result<(Vec<u8>, Option<MerkleProof>), Error> {
-        if matches!(request.height, QueryHeight::Specific(_)) {
-            return Err(Error::other_error(
-                "not support packet commitment query in specific height".to_string(),
-            ));
-        }
-        let (commitment, _) = self
-            .rt
-            .block_on(
-                self.contract
-                    .get_hashed_packet_acknowledgement_commitment(
-                        request.port_id.to_string(),
-                        request.channel_id.to_string(),
-                        request.sequence.into(),
-                    )
-                    .call(),
-            )
-            .map_err(convert_err)?;
-        Ok((commitment.to_vec(), None))
+        self.handler.query_packet_acknowledgement(request)
     }
 
     fn query_packet_acknowledgements(
         &self,
         request: QueryPacketAcknowledgementsRequest,
     ) -> Result<(Vec<Sequence>, Height), Error> {
-        let mut sequences: Vec<Sequence> = vec![];
-        for seq in request.packet_commitment_sequences {
-            let (_, found) = self
-                .rt
-                .block_on(
-                    self.contract
-                        .get_hashed_packet_acknowledgement_commitment(
-                            request.port_id.to_string(),
-                            request.channel_id.to_string(),
-                            seq.into(),
-                        )
-                        .call(),
-                )
-                .map_err(convert_err)?;
-            if found {
-                sequences.push(seq);
-            }
-        }
-        let height = Height::new(u64::MAX, u64::MAX).unwrap();
-        Ok((sequences, height))
+        self.handler.query_packet_acknowledgements(request)
     }
 
     fn query_unreceived_acknowledgements(
         &self,
         request: QueryUnreceivedAcksRequest,
     ) -> Result<Vec<Sequence>, Error> {
-        let mut sequences: Vec<Sequence> = vec![];
-        for seq in request.packet_ack_sequences {
-            let (_, found) = self
-                .rt
-                .block_on(
-                    self.contract
-                        .get_hashed_packet_acknowledgement_commitment(
-                            request.port_id.to_string(),
-                            request.channel_id.to_string(),
-                            seq.into(),
-                        )
-                        .call(),
-                )
-                .map_err(convert_err)?;
-            if !found {
-                sequences.push(seq);
-            }
-        }
-        Ok(sequences)
+        self.handler.query_unreceived_acknowledgements(request)
     }
 
     fn query_next_sequence_receive(
         &self,
         request: QueryNextSequenceReceiveRequest,
-        include_proof: IncludeProof,
+        _include_proof: IncludeProof,
     ) -> Result<(Sequence, Option<MerkleProof>), Error> {
-        let sequence = self
-            .rt
-            .block_on(
-                self.contract
-                    .get_next_sequence_recvs(
-                        request.port_id.to_string(),
-                        request.channel_id.to_string(),
-                    )
-                    .call(),
-            )
-            .map_err(convert_err)?;
-        Ok((sequence.into(), None))
+        self.handler.query_next_sequence_receive(request)
     }
 
     fn query_txs(&self, request: QueryTxRequest) -> Result<Vec<IbcEventWithHeight>, Error> {
@@ -801,27 +600,14 @@ impl ChainEndpoint for AxonChain {
         &self,
         message_type: ConnectionMsgType,
         connection_id: &ConnectionId,
-        client_id: &ClientId,
-        height: Height,
+        _client_id: &ClientId,
+        _height: Height,
     ) -> Result<(Option<AnyClientState>, Proofs), Error> {
-        let state = match message_type {
-            ConnectionMsgType::OpenTry => connection::State::Init,
-            ConnectionMsgType::OpenAck => connection::State::TryOpen,
-            ConnectionMsgType::OpenConfirm => connection::State::Open,
-        };
-        let tx_hash = self
-            .conn_tx_hash
-            .get(connection_id)
-            .ok_or(Error::conn_proof(
-                connection_id.clone(),
-                format!("missing connection tx_hash, state {state:?}"),
-            ))?;
-        let proofs = self.get_proofs(tx_hash).map_err(|e| {
-            Error::conn_proof(
-                connection_id.clone(),
-                format!("{}, state {state:?}", e.detail()),
-            )
-        })?;
+        let proofs = self
+            .tx_hashes
+            .connection_proofs(message_type, connection_id, |tx_hash| {
+                self.get_proofs(tx_hash)
+            })?;
         Ok((None, proofs))
     }
 
@@ -829,20 +615,10 @@ impl ChainEndpoint for AxonChain {
         &self,
         port_id: &PortId,
         channel_id: &ChannelId,
-        height: Height,
+        _height: Height,
     ) -> Result<Proofs, Error> {
-        let tx_hash = self
-            .chan_tx_hash
-            .get(&(channel_id.clone(), port_id.clone()))
-            .ok_or(Error::chan_proof(
-                port_id.clone(),
-                channel_id.clone(),
-                "missing channel tx_hash".to_owned(),
-            ))?;
-        let proofs = self.get_proofs(tx_hash).map_err(|e| {
-            Error::chan_proof(port_id.clone(), channel_id.clone(), e.detail().to_string())
-        })?;
-        Ok(proofs)
+        self.tx_hashes
+            .channel_proofs(port_id, channel_id, |tx_hash| self.get_proofs(tx_hash))
     }
 
     fn build_packet_proofs(
@@ -851,25 +627,12 @@ impl ChainEndpoint for AxonChain {
         port_id: PortId,
         channel_id: ChannelId,
         sequence: Sequence,
-        height: Height,
+        _height: Height,
     ) -> Result<Proofs, Error> {
-        let tx_hash = self
-            .packet_tx_hash
-            .get(&(channel_id.clone(), port_id.clone(), sequence.into()))
-            .ok_or(Error::packet_proof(
-                port_id.clone(),
-                channel_id.clone(),
-                sequence.into(),
-                format!("missing packet tx_hash, type {packet_type:?}"),
-            ))?;
-        let proofs = self.get_proofs(tx_hash).map_err(|e| {
-            Error::chan_proof(
-                port_id.clone(),
-                channel_id.clone(),
-                format!("{}, type {packet_type:?}", e.detail()),
-            )
-        })?;
-        Ok(proofs)
+        self.tx_hashes
+            .packet_proofs(packet_type, &port_id, &channel_id, sequence, |tx_hash| {
+                self.get_proofs(tx_hash)
+            })
     }
 
     fn cache_ics_tx_hash<T: Into<[u8; 32]>>(
@@ -878,18 +641,7 @@ impl ChainEndpoint for AxonChain {
         tx_hash: T,
     ) -> Result<(), Error> {
         let hash: [u8; 32] = tx_hash.into();
-        match cached_status {
-            CacheTxHashStatus::Connection(conn_id) => {
-                self.conn_tx_hash.insert(conn_id, hash.into());
-            }
-            CacheTxHashStatus::Channel(chan_id, port_id) => {
-                self.chan_tx_hash.insert((chan_id, port_id), hash.into());
-            }
-            CacheTxHashStatus::Packet(chan_id, port_id, sequence) => {
-                self.packet_tx_hash
-                    .insert((chan_id, port_id, sequence), hash.into());
-            }
-        }
+        self.tx_hashes.cache(cached_status, TxHash::from(hash));
         Ok(())
     }
 }
@@ -904,9 +656,12 @@ impl AxonChain {
             self.config.contract_address,
             header_receiver,
             self.rt.clone(),
+            self.progress.clone(),
         )
         .map_err(Error::event_monitor)?;
-        thread::spawn(move || event_monitor.run());
+        spawn_chain_thread(self.config.id.clone(), "event_monitor", move || {
+            event_monitor.run()
+        });
         Ok(monitor_tx)
     }
 
@@ -1012,261 +767,28 @@ impl AxonChain {
 impl AxonChain {
     fn send_message(&mut self, message: Any) -> Result<IbcEventWithHeight, Error> {
         let type_url = message.type_url.clone();
-        let tx_receipt = match type_url.as_str() {
-            update_client::TYPE_URL => {
-                let msg = update_client::MsgUpdateClient::from_any(message).map_err(|e| {
-                    Error::other_error(format!("fail to decode MsgUpdateClient {}", e))
-                })?;
-                let bytes = msg.header.value.as_slice();
-                let type_url = msg.header.type_url;
-                let to = match type_url.as_str() {
-                    "HEADER_TYPE_URL" => self.config.ckb_light_client_contract_address,
-                    "CELL_TYPE_URL" => self.config.image_cell_contract_address,
-                    type_url => {
-                        return Err(Error::other_error(format!("unknown type_url {}", type_url)))
-                    }
-                };
-
-                let tx = TransactionRequest::new().to(to).data(bytes.to_vec());
-                let tx_receipt: eyre::Result<Option<TransactionReceipt>> = self
-                    .rt
-                    .block_on(async { Ok(self.client.send_transaction(tx, None).await?.await?) });
-                tx_receipt.map_err(convert_err)?
-            }
-            conn_open_init::TYPE_URL => {
-                let msg: contract::MsgConnectionOpenInit = message.try_into()?;
-                let tx_receipt: eyre::Result<Option<TransactionReceipt>> =
-                    self.rt.block_on(async {
-                        Ok(self
-                            .contract
-                            .connection_open_init(msg.clone())
-                            .send()
-                            .await?
-                            .await?)
-                    });
-                tx_receipt.map_err(convert_err)?
-            }
-            conn_open_try::TYPE_URL => {
-                let msg: contract::MsgConnectionOpenTry = message.try_into()?;
-                let tx_receipt: eyre::Result<Option<TransactionReceipt>> =
-                    self.rt.block_on(async {
-                        Ok(self
-                            .contract
-                            .connection_open_try(msg.clone())
-                            .send()
-                            .await?
-                            .await?)
-                    });
-                tx_receipt.map_err(convert_err)?
-            }
-            conn_open_ack::TYPE_URL => {
-                let msg: contract::MsgConnectionOpenAck = message.try_into()?;
-                let tx_receipt: eyre::Result<Option<TransactionReceipt>> =
-                    self.rt.block_on(async {
-                        Ok(self
-                            .contract
-                            .connection_open_ack(msg.clone())
-                            .send()
-                            .await?
-                            .await?)
-                    });
-                tx_receipt.map_err(convert_err)?
-            }
-            conn_open_confirm::TYPE_URL => {
-                let msg: contract::MsgConnectionOpenConfirm = message.try_into()?;
-                let tx_receipt: eyre::Result<Option<TransactionReceipt>> =
-                    self.rt.block_on(async {
-                        Ok(self
-                            .contract
-                            .connection_open_confirm(msg.clone())
-                            .send()
-                            .await?
-                            .await?)
-                    });
-                tx_receipt.map_err(convert_err)?
-            }
-            chan_open_init::TYPE_URL => {
-                let msg: contract::MsgChannelOpenInit = message.try_into()?;
-                let tx_receipt: eyre::Result<Option<TransactionReceipt>> =
-                    self.rt.block_on(async {
-                        Ok(self
-                            .contract
-                            .channel_open_init(msg.clone())
-                            .send()
-                            .await?
-                            .await?)
-                    });
-                tx_receipt.map_err(convert_err)?
-            }
-            chan_open_try::TYPE_URL => {
-                let msg: contract::MsgChannelOpenTry = message.try_into()?;
-                let tx_receipt: eyre::Result<Option<TransactionReceipt>> =
-                    self.rt.block_on(async {
-                        Ok(self
-                            .contract
-                            .channel_open_try(msg.clone())
-                            .send()
-                            .await?
-                            .await?)
-                    });
-                tx_receipt.map_err(convert_err)?
-            }
-            chan_open_ack::TYPE_URL => {
-                let msg: contract::MsgChannelOpenAck = message.try_into()?;
-                let tx_receipt: eyre::Result<Option<TransactionReceipt>> =
-                    self.rt.block_on(async {
-                        Ok(self
-                            .contract
-                            .channel_open_ack(msg.clone())
-                            .send()
-                            .await?
-                            .await?)
-                    });
-                tx_receipt.map_err(convert_err)?
-            }
-            chan_open_confirm::TYPE_URL => {
-                let msg: contract::MsgChannelOpenConfirm = message.try_into()?;
-                let tx_receipt: eyre::Result<Option<TransactionReceipt>> =
-                    self.rt.block_on(async {
-                        Ok(self
-                            .contract
-                            .channel_open_confirm(msg.clone())
-                            .send()
-                            .await?
-                            .await?)
-                    });
-                tx_receipt.map_err(convert_err)?
-            }
-            chan_close_init::TYPE_URL => {
-                let msg: contract::MsgChannelCloseInit = message.try_into()?;
-                let tx_receipt: eyre::Result<Option<TransactionReceipt>> =
-                    self.rt.block_on(async {
-                        Ok(self
-                            .contract
-                            .channel_close_init(msg.clone())
-                            .send()
-                            .await?
-                            .await?)
-                    });
-                tx_receipt.map_err(convert_err)?
-            }
-            chan_close_confirm::TYPE_URL => {
-                let msg: contract::MsgChannelCloseConfirm = message.try_into()?;
-                let tx_receipt: eyre::Result<Option<TransactionReceipt>> =
-                    self.rt.block_on(async {
-                        Ok(self
-                            .contract
-                            .channel_close_confirm(msg.clone())
-                            .send()
-                            .await?
-                            .await?)
-                    });
-                tx_receipt.map_err(convert_err)?
-            }
-            recv_packet::TYPE_URL => {
-                let msg: contract::MsgPacketRecv = message.try_into()?;
-                let tx_receipt: eyre::Result<Option<TransactionReceipt>> =
-                    self.rt.block_on(async {
-                        Ok(self.contract.recv_packet(msg.clone()).send().await?.await?)
-                    });
-                tx_receipt.map_err(convert_err)?
-            }
-            acknowledgement::TYPE_URL => {
-                let msg: contract::MsgPacketAcknowledgement = message.try_into()?;
-                let tx_receipt: eyre::Result<Option<TransactionReceipt>> =
-                    self.rt.block_on(async {
-                        Ok(self
-                            .contract
-                            .acknowledge_packet(msg.clone())
-                            .send()
-                            .await?
-                            .await?)
-                    });
-                tx_receipt.map_err(convert_err)?
-            }
-            url => {
-                return Err(Error::other_error(format!(
-                    "not support message type url: {}",
-                    url
-                )))
-            }
-        };
-        let tx_receipt = tx_receipt.ok_or(Error::send_tx(String::from("fail to send tx")))?;
-        let event = {
-            use contract::OwnableIBCHandlerEvents::*;
-            let mut events = tx_receipt
-                .logs
-                .into_iter()
-                .map(Into::into)
-                .map(|log| OwnableIBCHandlerEvents::decode_log(&log));
-            match type_url.as_str() {
-                update_client::TYPE_URL => Some(Ok(UpdateClientFilter(Default::default()))),
-                conn_open_init::TYPE_URL => {
-                    events.find(|event| matches!(event, Ok(OpenInitConnectionFilter(_))))
-                }
-                conn_open_try::TYPE_URL => {
-                    events.find(|event| matches!(event, Ok(OpenTryConnectionFilter(_))))
-                }
-                conn_open_ack::TYPE_URL => {
-                    events.find(|event| matches!(event, Ok(OpenAckConnectionFilter(_))))
-                }
-                conn_open_confirm::TYPE_URL => {
-                    events.find(|event| matches!(event, Ok(OpenConfirmConnectionFilter(_))))
-                }
-                chan_open_init::TYPE_URL => {
-                    events.find(|event| matches!(event, Ok(OpenInitChannelFilter(_))))
-                }
-                chan_open_try::TYPE_URL => {
-                    events.find(|event| matches!(event, Ok(OpenTryChannelFilter(_))))
-                }
-                chan_open_ack::TYPE_URL => {
-                    events.find(|event| matches!(event, Ok(OpenAckChannelFilter(_))))
-                }
-                chan_open_confirm::TYPE_URL => {
-                    events.find(|event| matches!(event, Ok(OpenConfirmChannelFilter(_))))
-                }
-                chan_close_init::TYPE_URL => {
-                    events.find(|event| matches!(event, Ok(CloseInitChannelFilter(_))))
-                }
-                chan_close_confirm::TYPE_URL => {
-                    events.find(|event| matches!(event, Ok(CloseConfirmChannelFilter(_))))
-                }
-                recv_packet::TYPE_URL => {
-                    events.find(|event| matches!(event, Ok(ReceivePacketFilter(_))))
-                }
-                acknowledgement::TYPE_URL => {
-                    events.find(|event| matches!(event, Ok(AcknowledgePacketFilter(_))))
+        let tx_receipt = if type_url == update_client::TYPE_URL {
+            let msg = update_client::MsgUpdateClient::from_any(message)
+                .map_err(|e| Error::other_error(format!("fail to decode MsgUpdateClient {}", e)))?;
+            let bytes = msg.header.value.as_slice();
+            let type_url = msg.header.type_url;
+            let to = match type_url.as_str() {
+                "HEADER_TYPE_URL" => self.config.ckb_light_client_contract_address,
+                "CELL_TYPE_URL" => self.config.image_cell_contract_address,
+                type_url => {
+                    return Err(Error::other_error(format!("unknown type_url {}", type_url)))
                 }
-                url => {
-                    return Err(Error::other_error(format!(
-                        "not support message type url: {}",
-                        url
-                    )))
-                }
-            }
-        }
-        .ok_or_else(|| {
-            Error::other_error(String::from(
-                "not find right event from AXON transaction receipt",
-            ))
-        })?
-        .unwrap()
-        .into();
-        let tx_hash = tx_receipt.transaction_hash.0;
-        let height = {
-            let block_height = tx_receipt.block_number.ok_or_else(|| {
-                Error::other_error(format!(
-                    "transaction {} is still pending",
-                    hex::encode(tx_hash)
-                ))
-            })?;
-            Height::new(u64::MAX, block_height.as_u64()).unwrap()
+            };
+
+            let tx = TransactionRequest::new().to(to).data(bytes.to_vec());
+            let tx_receipt: eyre::Result<Option<TransactionReceipt>> = self
+                .rt
+                .block_on(async { Ok(self.client.send_transaction(tx, None).await?.await?) });
+            tx_receipt.map_err(convert_err)?
+        } else {
+            self.handler.send_message(message)?
         };
-        Ok(IbcEventWithHeight {
-            event,
-            height,
-            tx_hash,
-        })
+        message_event(&type_url, tx_receipt)
     }
 }
 
@@ -1279,13 +801,3 @@ fn to_identified_any_client_state(
 ) -> Result<IdentifiedAnyClientState, Error> {
     todo!("Type conversion. How to get specific consensus state from bytes?")
 }
-
-fn to_any_client_state(client_state: &ethers::core::types::Bytes) -> Result<AnyClientState, Error> {
-    todo!("Type conversion. How to get specific consensus state from bytes?");
-}
-
-fn to_any_consensus_state(
-    consensus_state: &ethers::core::types::Bytes,
-) -> Result<AnyConsensusState, Error> {
-    todo!("Type conversion.");
-}
@@ -0,0 +1,593 @@
+//! Queries and messages of the IBC handler contract, which has the same interface on
+//! Axon as on the other EVM chains, along with the transactions the proofs of the IBC
+//! objects are built from.
+
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+
+use ethers::{
+    abi::Detokenize,
+    contract::{builders::ContractCall, EthLogDecode},
+    core::types::Bytes,
+    providers::Middleware,
+    types::{TransactionReceipt, TxHash},
+};
+use ibc_proto::google::protobuf::Any;
+use ibc_relayer_types::{
+    core::{
+        ics02_client::msgs::update_client,
+        ics03_connection::{
+            connection::{ConnectionEnd, IdentifiedConnectionEnd},
+            msgs::{conn_open_ack, conn_open_confirm, conn_open_init, conn_open_try},
+        },
+        ics04_channel::{
+            channel::{ChannelEnd, IdentifiedChannelEnd},
+            msgs::{
+                acknowledgement, chan_close_confirm, chan_close_init, chan_open_ack,
+                chan_open_confirm, chan_open_init, chan_open_try, recv_packet,
+            },
+            packet::{PacketMsgType, Sequence},
+        },
+        ics23_commitment::merkle::MerkleProof,
+        ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+    },
+    proofs::Proofs,
+    Height,
+};
+use prost::Message;
+use tokio::runtime::Runtime as TokioRuntime;
+
+use crate::{
+    chain::{
+        handle::CacheTxHashStatus,
+        requests::{
+            QueryChannelClientStateRequest, QueryChannelRequest, QueryClientConnectionsRequest,
+            QueryClientStateRequest, QueryConnectionChannelsRequest, QueryConnectionRequest,
+            QueryConsensusStateHeightsRequest, QueryConsensusStateRequest, QueryHeight,
+            QueryNextSequenceReceiveRequest, QueryPacketAcknowledgementRequest,
+            QueryPacketAcknowledgementsRequest, QueryPacketCommitmentRequest,
+            QueryPacketCommitmentsRequest, QueryPacketReceiptRequest, QueryUnreceivedAcksRequest,
+            QueryUnreceivedPacketsRequest,
+        },
+    },
+    client_state::{AnyClientState, IdentifiedAnyClientState},
+    connection::ConnectionMsgType,
+    consensus_state::AnyConsensusState,
+    error::Error,
+    event::IbcEventWithHeight,
+};
+
+use super::contract::{self, HeightData, OwnableIBCHandler, OwnableIBCHandlerEvents};
+
+/// The IBC handler contract deployed at the address of `contract`, called on `rt`
+pub(crate) struct IbcHandler<M> {
+    rt: Arc<TokioRuntime>,
+    contract: OwnableIBCHandler<M>,
+}
+
+impl<M: Middleware + 'static> IbcHandler<M> {
+    pub fn new(rt: Arc<TokioRuntime>, contract: OwnableIBCHandler<M>) -> Self {
+        Self { rt, contract }
+    }
+
+    pub fn contract(&self) -> &OwnableIBCHandler<M> {
+        &self.contract
+    }
+
+    fn call<D: Detokenize>(&self, call: ContractCall<M, D>) -> Result<D, Error> {
+        self.rt.block_on(call.call()).map_err(convert_err)
+    }
+
+    fn send<D: Detokenize>(
+        &self,
+        call: ContractCall<M, D>,
+    ) -> Result<Option<TransactionReceipt>, Error> {
+        self.rt.block_on(async {
+            let pending_tx = call.send().await.map_err(convert_err)?;
+            pending_tx.await.map_err(convert_err)
+        })
+    }
+
+    /// Send a connection, channel or packet message to the contract, the clients being
+    /// updated by their own contracts, which differ from a chain to another.
+    pub fn send_message(&self, message: Any) -> Result<Option<TransactionReceipt>, Error> {
+        let handler = &self.contract;
+        let type_url = message.type_url.clone();
+        match type_url.as_str() {
+            conn_open_init::TYPE_URL => self.send(
+                handler.connection_open_init(contract::MsgConnectionOpenInit::try_from(message)?),
+            ),
+            conn_open_try::TYPE_URL => self.send(
+                handler.connection_open_try(contract::MsgConnectionOpenTry::try_from(message)?),
+            ),
+            conn_open_ack::TYPE_URL => self.send(
+                handler.connection_open_ack(contract::MsgConnectionOpenAck::try_from(message)?),
+            ),
+            conn_open_confirm::TYPE_URL => {
+                self.send(handler.connection_open_confirm(
+                    contract::MsgConnectionOpenConfirm::try_from(message)?,
+                ))
+            }
+            chan_open_init::TYPE_URL => self
+                .send(handler.channel_open_init(contract::MsgChannelOpenInit::try_from(message)?)),
+            chan_open_try::TYPE_URL => {
+                self.send(handler.channel_open_try(contract::MsgChannelOpenTry::try_from(message)?))
+            }
+            chan_open_ack::TYPE_URL => {
+                self.send(handler.channel_open_ack(contract::MsgChannelOpenAck::try_from(message)?))
+            }
+            chan_open_confirm::TYPE_URL => self.send(
+                handler.channel_open_confirm(contract::MsgChannelOpenConfirm::try_from(message)?),
+            ),
+            chan_close_init::TYPE_URL => self.send(
+                handler.channel_close_init(contract::MsgChannelCloseInit::try_from(message)?),
+            ),
+            chan_close_confirm::TYPE_URL => self.send(
+                handler.channel_close_confirm(contract::MsgChannelCloseConfirm::try_from(message)?),
+            ),
+            recv_packet::TYPE_URL => {
+                self.send(handler.recv_packet(contract::MsgPacketRecv::try_from(message)?))
+            }
+            acknowledgement::TYPE_URL => self.send(
+                handler.acknowledge_packet(contract::MsgPacketAcknowledgement::try_from(message)?),
+            ),
+            url => Err(Error::other_error(format!(
+                "not support message type url: {url}"
+            ))),
+        }
+    }
+
+    pub fn query_client_state(
+        &self,
+        request: QueryClientStateRequest,
+    ) -> Result<(AnyClientState, Option<MerkleProof>), Error> {
+        if matches!(request.height, QueryHeight::Specific(_)) {
+            return Err(Error::other_error(
+                "not support client state query in specific height".to_string(),
+            ));
+        }
+        let (client_state, _) = self.call(
+            self.contract
+                .get_client_state(request.client_id.to_string()),
+        )?;
+        Ok((to_any_client_state(&client_state)?, None))
+    }
+
+    pub fn query_consensus_state(
+        &self,
+        request: QueryConsensusStateRequest,
+    ) -> Result<(AnyConsensusState, Option<MerkleProof>), Error> {
+        let height = HeightData {
+            revision_number: request.consensus_height.revision_number(),
+            revision_height: request.consensus_height.revision_height(),
+        };
+        let (consensus_state, _) = self.call(
+            self.contract
+                .get_consensus_state(request.client_id.to_string(), height),
+        )?;
+        Ok((to_any_consensus_state(&consensus_state)?, None))
+    }
+
+    pub fn query_consensus_state_heights(
+        &self,
+        request: QueryConsensusStateHeightsRequest,
+    ) -> Result<Vec<Height>, Error> {
+        let heights = self.call(
+            self.contract
+                .get_consensus_heights(request.client_id.to_string()),
+        )?;
+        heights
+            .into_iter()
+            .map(|height| Height::new(height.revision_number, height.revision_height))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| Error::invalid_height_no_source())
+    }
+
+    pub fn query_connections(&self) -> Result<Vec<IdentifiedConnectionEnd>, Error> {
+        let connections = self.call(self.contract.get_connections())?;
+        Ok(connections
+            .into_iter()
+            .map(IdentifiedConnectionEnd::from)
+            .collect())
+    }
+
+    pub fn query_client_connections(
+        &self,
+        request: QueryClientConnectionsRequest,
+    ) -> Result<Vec<ConnectionId>, Error> {
+        let connection_ids = self.call(
+            self.contract
+                .get_client_connections(request.client_id.to_string()),
+        )?;
+        connection_ids
+            .iter()
+            .map(|id| ConnectionId::from_str(id))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Error::other_error(e.to_string()))
+    }
+
+    pub fn query_connection(
+        &self,
+        request: QueryConnectionRequest,
+    ) -> Result<(ConnectionEnd, Option<MerkleProof>), Error> {
+        if matches!(request.height, QueryHeight::Specific(_)) {
+            return Err(Error::other_error(
+                "not support connection query in specific height".to_string(),
+            ));
+        }
+        let (connection_end, _) = self.call(
+            self.contract
+                .get_connection(request.connection_id.to_string()),
+        )?;
+        Ok((connection_end.into(), None))
+    }
+
+    pub fn query_connection_channels(
+        &self,
+        request: QueryConnectionChannelsRequest,
+    ) -> Result<Vec<IdentifiedChannelEnd>, Error> {
+        let channels = self.call(
+            self.contract
+                .get_connection_channels(request.connection_id.to_string()),
+        )?;
+        Ok(channels
+            .into_iter()
+            .map(IdentifiedChannelEnd::from)
+            .collect())
+    }
+
+    pub fn query_channels(&self) -> Result<Vec<IdentifiedChannelEnd>, Error> {
+        let channels = self.call(self.contract.get_channels())?;
+        Ok(channels
+            .into_iter()
+            .map(IdentifiedChannelEnd::from)
+            .collect())
+    }
+
+    pub fn query_channel(
+        &self,
+        request: QueryChannelRequest,
+    ) -> Result<(ChannelEnd, Option<MerkleProof>), Error> {
+        if matches!(request.height, QueryHeight::Specific(_)) {
+            return Err(Error::other_error(
+                "not support channel query in specific height".to_string(),
+            ));
+        }
+        let (channel_end, _) = self.call(
+            self.contract
+                .get_channel(request.port_id.to_string(), request.channel_id.to_string()),
+        )?;
+        Ok((channel_end.into(), None))
+    }
+
+    /// The client state is looked up through the connection of the channel, since the
+    /// contract returns it without its identifier
+    pub fn query_channel_client_state(
+        &self,
+        request: QueryChannelClientStateRequest,
+    ) -> Result<Option<IdentifiedAnyClientState>, Error> {
+        let (channel_end, found) = self.call(
+            self.contract
+                .get_channel(request.port_id.to_string(), request.channel_id.to_string()),
+        )?;
+        if !found {
+            return Ok(None);
+        }
+        let channel_end = ChannelEnd::from(channel_end);
+        let Some(connection_id) = channel_end.connection_hops().first() else {
+            return Ok(None);
+        };
+        let (connection_end, found) =
+            self.call(self.contract.get_connection(connection_id.to_string()))?;
+        if !found {
+            return Ok(None);
+        }
+        let client_id = ConnectionEnd::from(connection_end).client_id().clone();
+        let (client_state, found) =
+            self.call(self.contract.get_client_state(client_id.to_string()))?;
+        if !found {
+            return Ok(None);
+        }
+        Ok(Some(IdentifiedAnyClientState::new(
+            client_id,
+            to_any_client_state(&client_state)?,
+        )))
+    }
+
+    pub fn query_packet_commitment(
+        &self,
+        request: QueryPacketCommitmentRequest,
+    ) -> Result<(Vec<u8>, Option<MerkleProof>), Error> {
+        let (commitment, found) = self.call(self.contract.get_hashed_packet_commitment(
+            request.port_id.to_string(),
+            request.channel_id.to_string(),
+            request.sequence.into(),
+        ))?;
+        // the commitment is deleted once the packet is acknowledged or timed out
+        if !found {
+            return Ok((vec![], None));
+        }
+        Ok((commitment.to_vec(), None))
+    }
+
+    pub fn query_packet_commitments(
+        &self,
+        request: QueryPacketCommitmentsRequest,
+    ) -> Result<(Vec<Sequence>, Height), Error> {
+        let sequences = self.call(self.contract.get_hashed_packet_commitment_sequences(
+            request.port_id.to_string(),
+            request.channel_id.to_string(),
+        ))?;
+        let sequences = sequences.into_iter().map(Sequence::from).collect();
+        let height = Height::new(u64::MAX, u64::MAX).unwrap();
+        Ok((sequences, height))
+    }
+
+    pub fn query_packet_receipt(
+        &self,
+        request: QueryPacketReceiptRequest,
+    ) -> Result<(Vec<u8>, Option<MerkleProof>), Error> {
+        let has_receipt = self.call(self.contract.has_packet_receipt(
+            request.port_id.to_string(),
+            request.channel_id.to_string(),
+            request.sequence.into(),
+        ))?;
+        Ok((vec![has_receipt as u8], None))
+    }
+
+    pub fn query_unreceived_packets(
+        &self,
+        request: QueryUnreceivedPacketsRequest,
+    ) -> Result<Vec<Sequence>, Error> {
+        let mut sequences = vec![];
+        for seq in request.packet_commitment_sequences {
+            let has_receipt = self.call(self.contract.has_packet_receipt(
+                request.port_id.to_string(),
+                request.channel_id.to_string(),
+                seq.into(),
+            ))?;
+            if !has_receipt {
+                sequences.push(seq);
+            }
+        }
+        Ok(sequences)
+    }
+
+    pub fn query_packet_acknowledgement(
+        &self,
+        request: QueryPacketAcknowledgementRequest,
+    ) -> Result<(Vec<u8>, Option<MerkleProof>), Error> {
+        if matches!(request.height, QueryHeight::Specific(_)) {
+            return Err(Error::other_error(
+                "not support packet acknowledgement query in specific height".to_string(),
+            ));
+        }
+        let (commitment, _) =
+            self.call(self.contract.get_hashed_packet_acknowledgement_commitment(
+                request.port_id.to_string(),
+                request.channel_id.to_string(),
+                request.sequence.into(),
+            ))?;
+        Ok((commitment.to_vec(), None))
+    }
+
+    pub fn query_packet_acknowledgements(
+        &self,
+        request: QueryPacketAcknowledgementsRequest,
+    ) -> Result<(Vec<Sequence>, Height), Error> {
+        let mut sequences = vec![];
+        for seq in request.packet_commitment_sequences {
+            let (_, found) =
+                self.call(self.contract.get_hashed_packet_acknowledgement_commitment(
+                    request.port_id.to_string(),
+                    request.channel_id.to_string(),
+                    seq.into(),
+                ))?;
+            if found {
+                sequences.push(seq);
+            }
+        }
+        let height = Height::new(u64::MAX, u64::MAX).unwrap();
+        Ok((sequences, height))
+    }
+
+    pub fn query_unreceived_acknowledgements(
+        &self,
+        request: QueryUnreceivedAcksRequest,
+    ) -> Result<Vec<Sequence>, Error> {
+        let mut sequences = vec![];
+        for seq in request.packet_ack_sequences {
+            let (_, found) =
+                self.call(self.contract.get_hashed_packet_acknowledgement_commitment(
+                    request.port_id.to_string(),
+                    request.channel_id.to_string(),
+                    seq.into(),
+                ))?;
+            if !found {
+                sequences.push(seq);
+            }
+        }
+        Ok(sequences)
+    }
+
+    pub fn query_next_sequence_receive(
+        &self,
+        request: QueryNextSequenceReceiveRequest,
+    ) -> Result<(Sequence, Option<MerkleProof>), Error> {
+        let sequence = self.call(self.contract.get_next_sequence_recvs(
+            request.port_id.to_string(),
+            request.channel_id.to_string(),
+        ))?;
+        Ok((sequence.into(), None))
+    }
+}
+
+/// Hashes of the transactions which last changed the IBC objects, whose receipts are
+/// the proofs of the objects
+#[derive(Default)]
+pub(crate) struct ProofTxHashes {
+    connections: HashMap<ConnectionId, TxHash>,
+    channels: HashMap<(ChannelId, PortId), TxHash>,
+    packets: HashMap<(ChannelId, PortId, u64), TxHash>,
+}
+
+impl ProofTxHashes {
+    pub fn cache(&mut self, cached_status: CacheTxHashStatus, tx_hash: TxHash) {
+        match cached_status {
+            CacheTxHashStatus::Connection(conn_id) => {
+                self.connections.insert(conn_id, tx_hash);
+            }
+            CacheTxHashStatus::Channel(chan_id, port_id) => {
+                self.channels.insert((chan_id, port_id), tx_hash);
+            }
+            CacheTxHashStatus::Packet(chan_id, port_id, sequence) => {
+                self.packets.insert((chan_id, port_id, sequence), tx_hash);
+            }
+        }
+    }
+
+    pub fn connection_proofs(
+        &self,
+        message_type: ConnectionMsgType,
+        connection_id: &ConnectionId,
+        get_proofs: impl FnOnce(&TxHash) -> Result<Proofs, Error>,
+    ) -> Result<Proofs, Error> {
+        let tx_hash = self.connections.get(connection_id).ok_or_else(|| {
+            Error::conn_proof(
+                connection_id.clone(),
+                format!("missing connection tx_hash, message {message_type:?}"),
+            )
+        })?;
+        get_proofs(tx_hash).map_err(|e| {
+            Error::conn_proof(
+                connection_id.clone(),
+                format!("{}, message {message_type:?}", e.detail()),
+            )
+        })
+    }
+
+    pub fn channel_proofs(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        get_proofs: impl FnOnce(&TxHash) -> Result<Proofs, Error>,
+    ) -> Result<Proofs, Error> {
+        let tx_hash = self
+            .channels
+            .get(&(channel_id.clone(), port_id.clone()))
+            .ok_or_else(|| {
+                Error::chan_proof(
+                    port_id.clone(),
+                    channel_id.clone(),
+                    "missing channel tx_hash".to_owned(),
+                )
+            })?;
+        get_proofs(tx_hash).map_err(|e| {
+            Error::chan_proof(port_id.clone(), channel_id.clone(), e.detail().to_string())
+        })
+    }
+
+    pub fn packet_proofs(
+        &self,
+        packet_type: PacketMsgType,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+        get_proofs: impl FnOnce(&TxHash) -> Result<Proofs, Error>,
+    ) -> Result<Proofs, Error> {
+        let tx_hash = self
+            .packets
+            .get(&(channel_id.clone(), port_id.clone(), sequence.into()))
+            .ok_or_else(|| {
+                Error::packet_proof(
+                    port_id.clone(),
+                    channel_id.clone(),
+                    sequence.into(),
+                    format!("missing packet tx_hash, type {packet_type:?}"),
+                )
+            })?;
+        get_proofs(tx_hash).map_err(|e| {
+            Error::packet_proof(
+                port_id.clone(),
+                channel_id.clone(),
+                sequence.into(),
+                format!("{}, type {packet_type:?}", e.detail()),
+            )
+        })
+    }
+}
+
+/// The event emitted by the contract for the message of `type_url` in `tx_receipt`,
+/// at the height of the block of the transaction. The clients are updated by other
+/// contracts, so their updates have an empty event.
+pub(crate) fn message_event(
+    type_url: &str,
+    tx_receipt: Option<TransactionReceipt>,
+) -> Result<IbcEventWithHeight, Error> {
+    let tx_receipt = tx_receipt.ok_or_else(|| Error::send_tx(String::from("fail to send tx")))?;
+    let event = if type_url == update_client::TYPE_URL {
+        OwnableIBCHandlerEvents::UpdateClientFilter(Default::default())
+    } else {
+        tx_receipt
+            .logs
+            .iter()
+            .cloned()
+            .filter_map(|log| OwnableIBCHandlerEvents::decode_log(&log.into()).ok())
+            .find(|event| is_message_event(type_url, event))
+            .ok_or_else(|| {
+                Error::other_error(format!(
+                    "not find the event of {type_url} from the transaction receipt"
+                ))
+            })?
+    };
+    let tx_hash = tx_receipt.transaction_hash.0;
+    let block_number = tx_receipt.block_number.ok_or_else(|| {
+        Error::other_error(format!(
+            "transaction {} is still pending",
+            hex::encode(tx_hash)
+        ))
+    })?;
+    Ok(IbcEventWithHeight {
+        event: event.into(),
+        height: Height::new(u64::MAX, block_number.as_u64()).unwrap(),
+        tx_hash,
+    })
+}
+
+/// Whether the event is the one emitted by the contract for the message of `type_url`.
+fn is_message_event(type_url: &str, event: &OwnableIBCHandlerEvents) -> bool {
+    use OwnableIBCHandlerEvents::*;
+    match type_url {
+        conn_open_init::TYPE_URL => matches!(event, OpenInitConnectionFilter(_)),
+        conn_open_try::TYPE_URL => matches!(event, OpenTryConnectionFilter(_)),
+        conn_open_ack::TYPE_URL => matches!(event, OpenAckConnectionFilter(_)),
+        conn_open_confirm::TYPE_URL => matches!(event, OpenConfirmConnectionFilter(_)),
+        chan_open_init::TYPE_URL => matches!(event, OpenInitChannelFilter(_)),
+        chan_open_try::TYPE_URL => matches!(event, OpenTryChannelFilter(_)),
+        chan_open_ack::TYPE_URL => matches!(event, OpenAckChannelFilter(_)),
+        chan_open_confirm::TYPE_URL => matches!(event, OpenConfirmChannelFilter(_)),
+        chan_close_init::TYPE_URL => matches!(event, CloseInitChannelFilter(_)),
+        chan_close_confirm::TYPE_URL => matches!(event, CloseConfirmChannelFilter(_)),
+        recv_packet::TYPE_URL => matches!(event, ReceivePacketFilter(_)),
+        acknowledgement::TYPE_URL => matches!(event, AcknowledgePacketFilter(_)),
+        _ => false,
+    }
+}
+
+fn convert_err<T: ToString>(err: T) -> Error {
+    Error::other_error(err.to_string())
+}
+
+/// The states are stored by the contract as encoded protobuf `Any` messages.
+fn decode_any(bytes: &Bytes, payload_type: &str) -> Result<Any, Error> {
+    Any::decode(bytes.as_ref()).map_err(|e| Error::protobuf_decode(payload_type.to_owned(), e))
+}
+
+fn to_any_client_state(client_state: &Bytes) -> Result<AnyClientState, Error> {
+    let any = decode_any(client_state, "ClientState")?;
+    AnyClientState::try_from(any).map_err(Error::ics02)
+}
+
+fn to_any_consensus_state(consensus_state: &Bytes) -> Result<AnyConsensusState, Error> {
+    let any = decode_any(consensus_state, "ConsensusState")?;
+    AnyConsensusState::try_from(any).map_err(Error::ics02)
+}
@@ -1,4 +1,6 @@
+use std::cmp::min;
 use std::sync::Arc;
+use std::time::Duration;
 
 use super::contract::*;
 // use super::ibc::*;
@@ -6,12 +8,14 @@ use crate::event::bus::EventBus;
 use crate::event::IbcEventWithHeight;
 use crate::light_client::AnyHeader;
 use crossbeam_channel as channel;
-use ethers::contract::stream::EventStreamMeta;
+use ethers::abi::RawLog;
 use ethers::contract::EthEvent;
+use ethers::contract::EthLogDecode;
 use ethers::contract::LogMeta;
 use ethers::prelude::*;
 use ethers::providers::Middleware;
 use ethers::types::Address;
+use ethers::types::{Filter, Log};
 use ibc_relayer_types::clients::ics07_axon::header::Header as AxonHeader;
 use ibc_relayer_types::core::ics02_client::client_type::ClientType;
 use ibc_relayer_types::core::ics02_client::events::{self, Attributes};
@@ -24,12 +28,20 @@ use OwnableIBCHandlerEvents as ContractEvents;
 
 use crate::chain::tracking::TrackingId;
 use crate::event::monitor::{Error, EventBatch, MonitorCmd, Next, Result, TxMonitorCmd};
+use crate::progress::SharedRelayProgress;
 use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ClientId};
 use tendermint_rpc::{Url, WebSocketClientUrl};
 use tokio::runtime::Runtime as TokioRuntime;
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn};
 
 type Client = Provider<Ws>;
+
+/// Scan of the events of the contract in the relay progress
+const EVENTS_SCAN: &str = "events";
+/// Number of blocks whose logs are fetched at once while catching up with the chain
+const BACKFILL_BLOCK_RANGE: u64 = 1000;
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
 // abigen!(IBC, "./crates/relayer/src/chain/axon/IBC.json");
 // use IBCEvents as ContractIBCEvents;
 
@@ -38,11 +50,18 @@ pub struct AxonEventMonitor {
     client: Arc<Client>,
     rt: Arc<TokioRuntime>,
     chain_id: ChainId,
+    websocket_addr: WebSocketClientUrl,
     contract_address: Address,
     start_block_number: u64,
+    /// Block number and index of the last log processed, older logs being skipped
+    /// when the logs fetched to fill a gap overlap with the subscription
+    last_log: Option<(u64, U256)>,
+    /// Consecutive failures to subscribe to the logs of the contract
+    failures: u32,
     rx_cmd: channel::Receiver<MonitorCmd>,
     header_receiver: Receiver<AxonHeader>,
     event_bus: EventBus<Arc<Result<EventBatch>>>,
+    progress: SharedRelayProgress,
 }
 
 impl AxonEventMonitor {
@@ -59,30 +78,43 @@ impl AxonEventMonitor {
         contract_address: Address,
         header_receiver: Receiver<AxonHeader>,
         rt: Arc<TokioRuntime>,
+        progress: SharedRelayProgress,
     ) -> Result<(Self, TxMonitorCmd)> {
         let (tx_cmd, rx_cmd) = channel::unbounded();
 
         // let ws_addr = websocket_addr.clone();
         let client = rt
             .block_on(Provider::<Ws>::connect(websocket_addr.to_string()))
-            .map_err(|_| Error::client_creation_failed(chain_id.clone(), websocket_addr))?;
+            .map_err(|_| Error::client_creation_failed(chain_id.clone(), websocket_addr.clone()))?;
 
-        // here should consider recovering from long-time-crash
-        let start_block_number = rt
-            .block_on(client.get_block_number())
-            .map_err(|e| Error::others(e.to_string()))?
-            .as_u64();
+        // resume from the block of the last event emitted before the relayer stopped,
+        // whose later events may not have been emitted yet
+        let scanned_height = progress.lock().unwrap().scanned_height(EVENTS_SCAN);
+        let start_block_number = match scanned_height {
+            Some(height) => {
+                info!("resuming the events of the contract from block {height}");
+                height
+            }
+            None => rt
+                .block_on(client.get_block_number())
+                .map_err(|e| Error::others(e.to_string()))?
+                .as_u64(),
+        };
 
         let event_bus = EventBus::new();
         let monitor = Self {
             client: Arc::new(client),
             rt,
             chain_id,
+            websocket_addr,
             contract_address,
             start_block_number,
+            last_log: None,
+            failures: 0,
             rx_cmd,
             header_receiver,
             event_bus,
+            progress,
         };
         Ok((monitor, TxMonitorCmd::new(tx_cmd)))
     }
@@ -119,55 +151,147 @@ impl AxonEventMonitor {
         Next::Continue
     }
 
+    /// Subscribe to the logs of the contract with `eth_subscribe`, fill the gap since
+    /// the last log processed with `eth_getLogs`, then process the logs pushed by the
+    /// node until the subscription is lost, after which it subscribes again
     async fn run_loop(&mut self) -> Next {
-        let contract = Arc::new(Contract::new(
-            self.contract_address,
-            Arc::clone(&self.client),
-        ));
-        let events = contract.events().from_block(self.start_block_number);
-        if let Ok(stream) = events.stream().await {
-            let mut meta_stream = stream.with_meta();
-            loop {
-                tokio::select! {
-                    Some(header) = self.header_receiver.recv() => {
-                        if let Next::Abort = self.update_subscribe() {
-                            return Next::Abort;
-                        }
-                        let height = header.height();
-                        let event = IbcEventWithHeight::new(
-                            events::NewBlock::new(height).into(),
-                            height,
-                        );
-                        let batch = EventBatch {
-                            chain_id: self.chain_id.clone(),
-                            tracking_id: TrackingId::new_uuid(),
-                            height,
-                            events: vec![event],
-                        };
-                        self.process_batch(batch);
-                    },
-
-                    Some(ret) = meta_stream.next() => {
-                        if let Next::Abort = self.update_subscribe() {
-                            return Next::Abort;
-                        }
-                        match ret {
-                            Ok((event, meta)) => {
-                                self.process_event(event, meta).unwrap_or_else(|e| {
-                                    error!("error while process event: {:?}", e);
-                                });
-                            }
-                            Err(err) => {
-                                error!("error when monitoring axon events, reason: {:?}", err);
-                                return Next::Continue;
-                                // TODO: reconnect
-                            }
+        if self.failures > 0 {
+            let delay = INITIAL_RECONNECT_DELAY
+                .saturating_mul(1 << (self.failures - 1).min(16))
+                .min(MAX_RECONNECT_DELAY);
+            tokio::time::sleep(delay).await;
+            if let Next::Abort = self.update_subscribe() {
+                return Next::Abort;
+            }
+            match Provider::<Ws>::connect(self.websocket_addr.to_string()).await {
+                Ok(client) => self.client = Arc::new(client),
+                Err(e) => {
+                    self.failures += 1;
+                    warn!(
+                        "failed to reconnect to axon node {}: {e}",
+                        self.websocket_addr
+                    );
+                    return Next::Continue;
+                }
+            }
+        }
+
+        let client = Arc::clone(&self.client);
+        let filter = Filter::new().address(self.contract_address);
+        let mut logs = match client.subscribe_logs(&filter).await {
+            Ok(logs) => logs,
+            Err(e) => {
+                self.failures += 1;
+                warn!("failed to subscribe to the logs of the contract: {e}");
+                return Next::Continue;
+            }
+        };
+        if let Err(e) = self.backfill(&client, &filter).await {
+            self.failures += 1;
+            warn!("failed to fetch the logs of the contract missed: {e}");
+            return Next::Continue;
+        }
+        if self.failures > 0 {
+            info!("subscribed to the logs of the contract again");
+        }
+        self.failures = 0;
+
+        loop {
+            tokio::select! {
+                Some(header) = self.header_receiver.recv() => {
+                    if let Next::Abort = self.update_subscribe() {
+                        return Next::Abort;
+                    }
+                    let height = header.height();
+                    let event = IbcEventWithHeight::new(
+                        events::NewBlock::new(height).into(),
+                        height,
+                    );
+                    let batch = EventBatch {
+                        chain_id: self.chain_id.clone(),
+                        tracking_id: TrackingId::new_uuid(),
+                        height,
+                        events: vec![event],
+                    };
+                    self.process_batch(batch);
+                },
+
+                log = logs.next() => {
+                    if let Next::Abort = self.update_subscribe() {
+                        return Next::Abort;
+                    }
+                    match log {
+                        Some(log) => self.process_log(log),
+                        None => {
+                            self.failures += 1;
+                            error!("lost the subscription to the logs of the contract");
+                            return Next::Continue;
                         }
                     }
                 }
             }
         }
-        Next::Abort
+    }
+
+    /// Process the logs of the blocks from the last one processed up to the tip,
+    /// which were emitted while the monitor was not subscribed
+    async fn backfill(&mut self, client: &Client, filter: &Filter) -> Result<()> {
+        let tip = client
+            .get_block_number()
+            .await
+            .map_err(|e| Error::others(e.to_string()))?
+            .as_u64();
+        let mut from = self.start_block_number;
+        while from <= tip {
+            let to = min(from + BACKFILL_BLOCK_RANGE - 1, tip);
+            let range = filter.clone().from_block(from).to_block(to);
+            let logs = client
+                .get_logs(&range)
+                .await
+                .map_err(|e| Error::others(e.to_string()))?;
+            debug!(
+                "fetched {} logs of the contract from block {from} to {to}",
+                logs.len()
+            );
+            for log in logs {
+                self.process_log(log);
+            }
+            // all the events up to this block are emitted now
+            self.start_block_number = self.start_block_number.max(to);
+            self.progress
+                .lock()
+                .unwrap()
+                .set_scanned_height(EVENTS_SCAN, self.start_block_number);
+            from = to + 1;
+        }
+        Ok(())
+    }
+
+    fn process_log(&mut self, log: Log) {
+        // logs of pending or reorganized blocks
+        if log.removed == Some(true) {
+            return;
+        }
+        let (Some(block_number), Some(log_index)) = (log.block_number, log.log_index) else {
+            return;
+        };
+        let position = (block_number.as_u64(), log_index);
+        if self.last_log.map_or(false, |last_log| position <= last_log) {
+            return;
+        }
+        self.last_log = Some(position);
+
+        let meta = LogMeta::from(&log);
+        let raw_log = RawLog {
+            topics: log.topics,
+            data: log.data.to_vec(),
+        };
+        match ContractEvents::decode_log(&raw_log) {
+            Ok(event) => self.process_event(event, meta).unwrap_or_else(|e| {
+                error!("error while process event: {:?}", e);
+            }),
+            Err(e) => debug!("skipping the log of an unknown event: {e}"),
+        }
     }
 
     fn process_event(&mut self, event: ContractEvents, meta: LogMeta) -> Result<()> {
@@ -181,6 +305,10 @@ impl AxonEventMonitor {
             events: vec![self.to_ibc_event(event, meta)],
         };
         self.process_batch(batch);
+        self.progress
+            .lock()
+            .unwrap()
+            .set_scanned_height(EVENTS_SCAN, self.start_block_number);
         Ok(())
     }
 
@@ -1,8 +1,10 @@
 use ckb_jsonrpc_types::{OutputsValidator, TransactionView as JsonTx};
+use ckb_sdk::rpc::ckb_indexer::{ScriptType, SearchKey};
 use ckb_sdk::{Address, AddressPayload, NetworkType};
 use ckb_types::core::TransactionView;
-use ckb_types::packed::CellOutput;
+use ckb_types::packed::{CellOutput, Script};
 use ckb_types::prelude::*;
+use ckb_types::H256;
 use eth2_types::MainnetEthSpec;
 use eth_light_client_in_ckb_verification::types::{
     packed::Client as PackedClient, packed::ClientInfo as PackedClientInfo,
@@ -12,6 +14,7 @@ use eth_light_client_in_ckb_verification::types::{
 use ibc_proto::ibc::apps::fee::v1::{
     QueryIncentivizedPacketRequest, QueryIncentivizedPacketResponse,
 };
+use ibc_proto::protobuf::Protobuf;
 use ibc_relayer_storage::prelude::{StorageAsMMRStore as _, StorageReader as _};
 use ibc_relayer_storage::{Slot, Storage};
 use ibc_relayer_types::applications::ics31_icq::response::CrossChainQueryResponse;
@@ -21,11 +24,15 @@ use ibc_relayer_types::clients::ics07_ckb::{
     light_block::LightBlock as CkbLightBlock,
 };
 use ibc_relayer_types::clients::ics07_eth::{
-    client_state::ClientState as EthClientState, types::Update as EthUpdate,
+    client_state::ClientState as EthClientState, misbehaviour::Misbehaviour as EthMisbehaviour,
+    types::Update as EthUpdate,
 };
 use ibc_relayer_types::{
     core::{
-        ics02_client::events::UpdateClient,
+        ics02_client::{
+            events::UpdateClient,
+            msgs::misbehaviour::{MsgSubmitMisbehaviour, TYPE_URL as SUBMIT_MISBEHAVIOUR_TYPE_URL},
+        },
         ics03_connection::connection::{ConnectionEnd, IdentifiedConnectionEnd},
         ics04_channel::{
             channel::{ChannelEnd, IdentifiedChannelEnd},
@@ -38,8 +45,8 @@ use ibc_relayer_types::{
     Height as ICSHeight,
 };
 use semver::Version;
+use std::collections::BTreeMap;
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
 use tendermint_light_client::errors::Error as LightClientError;
 use tendermint_rpc::endpoint::broadcast::tx_sync::Response;
 use tokio::runtime::Runtime as TokioRuntime;
@@ -49,8 +56,9 @@ use crate::keyring::Store;
 
 use crate::{
     account::Balance,
+    chain::ckb4ibc::denom::{udt_amount, CKB_DENOM},
     chain::cosmos::encode::key_pair_to_signer,
-    chain::endpoint::{ChainEndpoint, ChainStatus, HealthCheck},
+    chain::endpoint::{ChainCapabilities, ChainEndpoint, ChainStatus, HealthCheck},
     client_state::{AnyClientState, IdentifiedAnyClientState},
     config::ckb::ChainConfig as CkbChainConfig,
     config::ChainConfig,
@@ -60,6 +68,7 @@ use crate::{
     error::Error,
     event::IbcEventWithHeight,
     keyring::{KeyRing, Secp256k1KeyPair},
+    light_client::eth::bootstrap::multi_client_set,
     misbehaviour::MisbehaviourEvidence,
 };
 
@@ -79,10 +88,16 @@ use super::{
 };
 
 mod assembler;
+pub mod broadcast;
+pub mod client_cells;
 mod communication;
 mod helper;
+pub mod header_updater;
+pub mod pending_tx;
+pub mod prune;
 pub mod sighash;
-mod signer;
+pub(crate) mod signer;
+mod throttle;
 pub mod utils;
 
 #[cfg(test)]
@@ -105,8 +120,13 @@ pub mod prelude {
 
 use assembler::TxAssembler;
 
+use broadcast::TxBroadcaster;
+
 use prelude::{CkbReader as _, CkbWriter as _, UpdateCells};
 
+use client_cells::ClientCells;
+use prune::{ClientsPruning, ObsoleteClientCell};
+
 use rpc_client::RpcClient;
 
 // Ref: https://github.com/satoshilabs/slips/pull/621
@@ -115,6 +135,7 @@ pub const HD_PATH: &str = "m/44'/309'/0'/0/0";
 pub struct CkbChain {
     pub rt: Arc<TokioRuntime>,
     pub rpc_client: Arc<RpcClient>,
+    pub broadcaster: TxBroadcaster,
     pub config: CkbChainConfig,
     pub keybase: KeyRing<Secp256k1KeyPair>,
     // TODO the spec of Ethereum should be selectable.
@@ -146,44 +167,48 @@ impl CkbChain {
             let update_cells = self.rt.block_on(self.rpc_client.fetch_update_cells(
                 &self.config.lightclient_contract_typeargs,
                 &client_type_args,
-            ))?;
-            if let Some(UpdateCells {
-                oldest: _,
-                latest,
-                info: _,
-            }) = update_cells
-            {
-                let latest_client = PackedClient::new_unchecked(latest.output_data);
-                self.cached_onchain_packed_client = Some(latest_client.clone());
-
-                let onchain_base_slot = latest_client.minimal_slot().unpack();
-                // This is for reporting that clients have been created at that slot.
-                // TODO: better error type to match the semantic.
-                return Err(Error::light_client_verification(
-                    chain_id,
-                    LightClientError::missing_last_block_id(utils::into_height(onchain_base_slot)),
-                ));
-            } else {
-                return Err(Error::other_error(
-                    "no multi-client cells found for config".to_owned(),
-                ));
+            ));
+            match update_cells {
+                Ok(Some(UpdateCells {
+                    oldest: _,
+                    latest,
+                    info: _,
+                })) => {
+                    let latest_client = PackedClient::new_unchecked(latest.output_data);
+                    self.cached_onchain_packed_client = Some(latest_client.clone());
+
+                    let onchain_base_slot = latest_client.minimal_slot().unpack();
+                    // This is for reporting that clients have been created at that slot.
+                    // TODO: better error type to match the semantic.
+                    return Err(Error::light_client_verification(
+                        chain_id,
+                        LightClientError::missing_last_block_id(utils::into_height(
+                            onchain_base_slot,
+                        )),
+                    ));
+                }
+                Ok(None) => {
+                    return Err(Error::other_error(
+                        "no multi-client cells found for config".to_owned(),
+                    ));
+                }
+                Err(err)
+                    if err.is_ckb_multi_client_corrupted()
+                        && self.config.recreate_corrupted_clients =>
+                {
+                    tracing::warn!("{err}, creating a new set of multi-client cells");
+                }
+                Err(err) => return Err(err),
             }
         }
 
-        let client_count = {
-            let cells_count = client_type_args.cells_count;
-            cells_count.checked_sub(1).expect("invalid cells_count")
-        };
-
         let (packed_client, packed_proof_update, prev_slot_opt) =
             self.get_new_client_and_proof(&chain_id, &mut header_updates, minimal_updates_count)?;
-        let clients = (0..client_count)
-            .map(|i| packed_client.clone().as_builder().id(i.into()).build())
-            .collect::<Vec<_>>();
-        let client_info = PackedClientInfo::new_builder()
-            .last_id(0.into())
-            .minimal_updates_count(minimal_updates_count.into())
-            .build();
+        let (clients, client_info) = multi_client_set(
+            packed_client,
+            client_type_args.cells_count,
+            minimal_updates_count,
+        );
 
         let tx_assembler_address = self.tx_assembler_address()?;
         let (tx, inputs, type_id) =
@@ -243,6 +268,12 @@ impl CkbChain {
             return Err(Error::other_error("no multi-client cells found".to_owned()));
         };
 
+        if client_cells::is_frozen(&update_cells.info) {
+            return Err(Error::other_error(
+                "multi-client cells are frozen by a misbehaviour evidence".to_owned(),
+            ));
+        }
+
         let latest_client = PackedClient::new_unchecked(update_cells.latest.output_data.clone());
         self.cached_onchain_packed_client = Some(latest_client);
 
@@ -283,6 +314,58 @@ impl CkbChain {
         Ok(vec![])
     }
 
+    /// Freeze the multi-client cells once the misbehaviour of their clients is found,
+    /// moving them under a lock whose args are the conflicting header root.
+    fn freeze_eth_multi_client(
+        &mut self,
+        misbehaviour: EthMisbehaviour,
+    ) -> Result<Vec<IbcEventWithHeight>, Error> {
+        let client_type_args: PackedClientTypeArgs = {
+            let Some(type_id) = self.config.client_type_args.type_id.as_ref() else {
+                return Err(Error::other_error(
+                    "no type id in client type args".to_owned(),
+                ));
+            };
+            let type_id = PackedHash::from_slice(type_id.0.as_slice()).expect("build type id");
+            PackedClientTypeArgs::new_builder()
+                .cells_count(self.config.client_type_args.cells_count.into())
+                .type_id(type_id)
+                .build()
+        };
+
+        let Some((client_cells, info_cell)) =
+            self.rt.block_on(self.rpc_client.fetch_multi_client_cells(
+                &self.config.lightclient_contract_typeargs,
+                &client_type_args,
+            ))?
+        else {
+            return Err(Error::other_error("no multi-client cells found".to_owned()));
+        };
+        if client_cells::is_frozen(&info_cell) {
+            tracing::info!("multi-client cells are frozen already");
+            return Ok(vec![]);
+        }
+
+        let tx_assembler_address = self.tx_assembler_address()?;
+        let (tx, inputs) =
+            self.rt
+                .block_on(self.rpc_client.assemble_freeze_multi_client_transaction(
+                    &tx_assembler_address,
+                    client_cells,
+                    info_cell,
+                    &self.config.lightclient_lock_typeargs,
+                    &self.config.lightclient_contract_typeargs,
+                    misbehaviour.conflicting_root.as_bytes().to_vec(),
+                ))?;
+        let tx_hash: H256 = tx.hash().unpack();
+        self.sign_and_send_transaction(tx, inputs)?;
+
+        tracing::warn!(
+            "froze the multi-client cells in transaction {tx_hash:#x}, misbehaviour: {misbehaviour}"
+        );
+        Ok(vec![])
+    }
+
     fn get_new_client_and_proof(
         &self,
         chain_id: &str,
@@ -329,9 +412,12 @@ impl CkbChain {
         let tx = signer::sign(tx, &inputs, vec![], key).map_err(Error::key_base)?;
 
         let task = async {
+            let json_tx = tx.data().into();
+            self.broadcaster
+                .broadcast(&json_tx, Some(OutputsValidator::Passthrough));
             let send_res = self
                 .rpc_client
-                .send_transaction(&tx.data().into(), Some(OutputsValidator::Passthrough))
+                .send_transaction(&json_tx, Some(OutputsValidator::Passthrough))
                 .await;
             let hash = match send_res {
                 Ok(hash) => Ok(hash),
@@ -342,11 +428,21 @@ impl CkbChain {
                     )
                     .await
                     .unwrap_or_default();
+                    let tx_hash = format!("{:#x}", tx.hash());
+                    let e = utils::diagnose_ckb_script_failure(
+                        self.rpc_client.as_ref(),
+                        &json_tx,
+                        Error::ckb_send_tx_failure(tx_hash.clone(), e),
+                    )
+                    .await;
                     let tx_info = format!(
                         "== transaction for debugging is below ==\n{}",
                         serde_json::to_string(&JsonTx::from(tx)).expect("jsonify ckb tx")
                     );
-                    Err(Error::send_tx(format!("{e}\n{pool_log}\n{tx_info}\n")))
+                    tracing::error!(
+                        "ckb send_transaction {tx_hash} failed: {e}\n{pool_log}\n{tx_info}\n"
+                    );
+                    Err(e)
                 }
             }?;
 
@@ -358,9 +454,9 @@ impl CkbChain {
             utils::wait_ckb_transaction_committed(
                 &self.rpc_client,
                 hash,
-                Duration::from_secs(3),
-                0,
-                Duration::from_secs(60),
+                self.config.tx_poll_interval,
+                self.config.tx_confirmations,
+                self.config.tx_commit_timeout,
             )
             .await?;
             tracing::info!("transaction committed to block");
@@ -375,25 +471,62 @@ impl CkbChain {
         let network = if let Some(network) = cached_network_opt {
             network
         } else {
-            let network = {
-                let chain_info = self
-                    .rt
-                    .block_on(self.rpc_client.get_blockchain_info())
-                    .map_err(|e| Error::rpc_response(e.to_string()))?;
-                if chain_info.chain == "ckb" {
-                    NetworkType::Mainnet
-                } else if chain_info.chain == "ckb_testnet" {
-                    NetworkType::Testnet
-                } else {
-                    NetworkType::Dev
-                }
-            };
+            let network = self
+                .rt
+                .block_on(utils::fetch_network(self.rpc_client.as_ref()))?;
             *self.cached_network.write().map_err(Error::other)? = Some(network);
             network
         };
         Ok(network)
     }
 
+    /// Balances of the relayer account: the capacity of its plain cells and the UDT amounts
+    /// held by its typed cells, by the hash of their type script.
+    ///
+    /// Any typed cell whose data starts with a 16 bytes amount is counted as a SUDT or xUDT cell.
+    fn query_account_balances(&self) -> Result<Vec<Balance>, Error> {
+        let address = self.tx_assembler_address()?;
+        let lock_script: Script = address.payload().into();
+        let search_key = SearchKey {
+            script: lock_script.into(),
+            script_type: ScriptType::Lock,
+            filter: None,
+            with_data: Some(true),
+            group_by_transaction: None,
+        };
+        let resp = self.rpc_client.fetch_live_cells(search_key, u32::MAX, None);
+        let cells = self.rt.block_on(resp)?;
+
+        let mut capacity = 0u64;
+        let mut udt_amounts: BTreeMap<H256, u128> = BTreeMap::new();
+        for cell in cells.objects {
+            let Some(type_script) = cell.output.type_ else {
+                capacity += cell.output.capacity.value();
+                continue;
+            };
+            let Some(amount) = cell
+                .output_data
+                .as_ref()
+                .and_then(|data| udt_amount(data.as_bytes()))
+            else {
+                continue;
+            };
+            let type_hash: H256 = Script::from(type_script).calc_script_hash().unpack();
+            let total = udt_amounts.entry(type_hash).or_default();
+            *total = total.saturating_add(amount);
+        }
+
+        let mut balances = vec![Balance {
+            amount: capacity.to_string(),
+            denom: CKB_DENOM.to_owned(),
+        }];
+        balances.extend(udt_amounts.into_iter().map(|(type_hash, amount)| Balance {
+            amount: amount.to_string(),
+            denom: format!("{type_hash:#x}"),
+        }));
+        Ok(balances)
+    }
+
     pub fn tx_assembler_address(&self) -> Result<Address, Error> {
         let cached_address = self
             .cached_tx_assembler_address
@@ -419,6 +552,83 @@ impl CkbChain {
         Ok(address)
     }
 
+    fn prune_obsolete_client_cells(&mut self, dry_run: bool) -> Result<ClientsPruning, Error> {
+        let Some(current_type_id) = self.config.client_type_args.type_id.clone() else {
+            return Err(Error::other_error(
+                "no type id in client type args, cannot tell the obsolete clients apart".to_owned(),
+            ));
+        };
+
+        let obsolete_cells = self
+            .rt
+            .block_on(self.rpc_client.fetch_obsolete_client_cells(
+                &self.config.lightclient_lock_typeargs,
+                &self.config.lightclient_contract_typeargs,
+                &current_type_id,
+            ))?;
+        let mut pruning = ClientsPruning {
+            cells: obsolete_cells
+                .iter()
+                .map(|(cell, type_id)| ObsoleteClientCell::new(cell, type_id.clone()))
+                .collect(),
+            tx_hash: None,
+        };
+        if dry_run || obsolete_cells.is_empty() {
+            return Ok(pruning);
+        }
+
+        let tx_assembler_address = self.tx_assembler_address()?;
+        let (tx, inputs) = self
+            .rt
+            .block_on(self.rpc_client.assemble_prune_clients_transaction(
+                &tx_assembler_address,
+                obsolete_cells.into_iter().map(|(cell, _)| cell).collect(),
+                &self.config.lightclient_lock_typeargs,
+                &self.config.lightclient_contract_typeargs,
+            ))?;
+        let tx_hash: H256 = tx.hash().unpack();
+        self.sign_and_send_transaction(tx, inputs)?;
+
+        tracing::info!(
+            "pruned {} obsolete client cells in transaction {tx_hash:#x}, reclaiming {} shannons",
+            pruning.cells.len(),
+            pruning.reclaimed_capacity()
+        );
+        pruning.tx_hash = Some(tx_hash);
+        Ok(pruning)
+    }
+
+    fn query_multi_client_cells(&self) -> Result<ClientCells, Error> {
+        let client_type_args = &self.config.client_type_args;
+        let Some(type_id) = client_type_args.type_id.clone() else {
+            return Err(Error::other_error(
+                "no type id in client type args".to_owned(),
+            ));
+        };
+        let packed_client_type_args: PackedClientTypeArgs = {
+            let type_id = PackedHash::from_slice(type_id.0.as_slice()).expect("build type id");
+            PackedClientTypeArgs::new_builder()
+                .cells_count(client_type_args.cells_count.into())
+                .type_id(type_id)
+                .build()
+        };
+
+        let update_cells = self.rt.block_on(self.rpc_client.fetch_update_cells(
+            &self.config.lightclient_contract_typeargs,
+            &packed_client_type_args,
+        ))?;
+        let Some(update_cells) = update_cells else {
+            return Err(Error::other_error(format!(
+                "no multi-client cells found for type id {type_id:#x}"
+            )));
+        };
+        Ok(ClientCells::decode(
+            type_id,
+            client_type_args.cells_count,
+            &update_cells,
+        ))
+    }
+
     fn print_status_log(&self) -> Result<(), Error> {
         let contract_typeid_args = &self.config.lightclient_contract_typeargs;
         let client_type_args = &self.config.client_type_args;
@@ -480,7 +690,10 @@ impl ChainEndpoint for CkbChain {
 
     fn bootstrap(config: ChainConfig, rt: Arc<TokioRuntime>) -> Result<Self, Error> {
         let config: CkbChainConfig = config.try_into()?;
-        let rpc_client = Arc::new(RpcClient::new(&config.ckb_rpc, &config.ckb_indexer_rpc));
+        let rpc_client = Arc::new(
+            RpcClient::new(&config.ckb_rpc, &config.ckb_indexer_rpc)
+                .with_chain_id(config.id.clone()),
+        );
         let storage = Storage::new(&config.data_dir)?;
 
         #[cfg(not(test))]
@@ -523,9 +736,11 @@ impl ChainEndpoint for CkbChain {
         #[cfg(not(test))]
         let _: Secp256k1KeyPair = keybase.get_key(&config.key_name).map_err(Error::key_base)?;
 
+        let broadcaster = TxBroadcaster::new(&config.broadcast_rpcs);
         let ckb = CkbChain {
             rt,
             rpc_client,
+            broadcaster,
             config,
             keybase,
             storage,
@@ -547,6 +762,20 @@ impl ChainEndpoint for CkbChain {
         Ok(HealthCheck::Healthy)
     }
 
+    fn capabilities(&self) -> ChainCapabilities {
+        // the chain only hosts the light client cells of its counterparty, it has
+        // neither channels nor IBC states to prove
+        ChainCapabilities::default()
+    }
+
+    fn prune_obsolete_clients(&mut self, dry_run: bool) -> Result<ClientsPruning, Error> {
+        self.prune_obsolete_client_cells(dry_run)
+    }
+
+    fn query_light_client_cells(&self) -> Result<ClientCells, Error> {
+        self.query_multi_client_cells()
+    }
+
     fn keybase(&self) -> &KeyRing<Self::SigningKeyPair> {
         &self.keybase
     }
@@ -572,6 +801,18 @@ impl ChainEndpoint for CkbChain {
         &mut self,
         tracked_msgs: TrackedMsgs,
     ) -> Result<Vec<IbcEventWithHeight>, Error> {
+        if let Some(msg) = tracked_msgs
+            .msgs
+            .iter()
+            .find(|msg| msg.type_url == SUBMIT_MISBEHAVIOUR_TYPE_URL)
+        {
+            let msg = MsgSubmitMisbehaviour::decode_vec(&msg.value)
+                .map_err(|e| Error::send_tx(e.to_string()))?;
+            let misbehaviour = EthMisbehaviour::try_from(msg.misbehaviour)
+                .map_err(|e| Error::send_tx(e.to_string()))?;
+            return self.freeze_eth_multi_client(misbehaviour);
+        }
+
         let updates = tracked_msgs
             .msgs
             .into_iter()
@@ -620,13 +861,23 @@ impl ChainEndpoint for CkbChain {
     fn query_balance(
         &self,
         _key_name: Option<&str>,
-        _denom: Option<&str>,
+        denom: Option<&str>,
     ) -> Result<Balance, Error> {
-        todo!()
+        let denom = denom.unwrap_or(CKB_DENOM);
+        let amount = self
+            .query_account_balances()?
+            .into_iter()
+            .find(|balance| balance.denom == denom)
+            .map(|balance| balance.amount)
+            .unwrap_or_else(|| String::from("0"));
+        Ok(Balance {
+            amount,
+            denom: denom.to_owned(),
+        })
     }
 
     fn query_all_balances(&self, _key_name: Option<&str>) -> Result<Vec<Balance>, Error> {
-        todo!()
+        self.query_account_balances()
     }
 
     fn query_denom_trace(&self, _hash: String) -> Result<DenomTrace, Error> {
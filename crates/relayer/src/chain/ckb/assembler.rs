@@ -1,9 +1,12 @@
 #![allow(dead_code)]
 
+use std::collections::HashSet;
+
 use async_trait::async_trait;
 use ckb_sdk::{
     constants::TYPE_ID_CODE_HASH,
-    traits::{LiveCell, PrimaryScriptType},
+    rpc::ckb_indexer::SearchKey,
+    traits::{CellQueryOptions, LiveCell, PrimaryScriptType},
     Address,
 };
 use ckb_types::{
@@ -20,11 +23,15 @@ use eth_light_client_in_ckb_verification::types::packed::{
 
 use super::{
     prelude::{CellSearcher, TxCompleter},
+    prune,
     rpc_client::RpcClient,
     utils,
 };
 use crate::error::Error;
 
+/// Number of cells fetched at once while searching the obsolete client cells
+const OBSOLETE_CELLS_PAGE_SIZE: u32 = 100;
+
 fn make_typeid_script(type_args: Vec<u8>) -> packed::Script {
     packed::Script::new_builder()
         .code_hash(TYPE_ID_CODE_HASH.0.pack())
@@ -87,11 +94,10 @@ pub trait TxAssembler: CellSearcher + TxCompleter {
         if cells.is_empty() {
             return Ok(None);
         } else if cells.len() != cells_count as usize {
-            panic!(
-                "fetched client cells count not match: expect {}, actual {}",
+            return Err(Error::ckb_multi_client_cells_count_mismatch(
                 cells_count,
-                cells.len()
-            );
+                cells.len(),
+            ));
         }
 
         let mut client_cells = vec![];
@@ -100,21 +106,21 @@ pub trait TxAssembler: CellSearcher + TxCompleter {
             if PackedClientReader::verify(&cell.output_data, false).is_ok() {
                 client_cells.push(cell);
             } else if PackedClientInfoReader::verify(&cell.output_data, false).is_ok() {
-                let prev = client_info_cell_opt.replace(cell.clone());
-                if prev.is_some() {
-                    panic!(
-                        "multi client cell has more than one client info:\nfirst:\n{:?}\nsecond:\n{:?}",
-                        PackedClientInfo::new_unchecked(prev.unwrap().output_data),
-                        PackedClientInfo::new_unchecked(cell.output_data),
-                    );
+                if let Some(prev) = client_info_cell_opt.replace(cell.clone()) {
+                    return Err(Error::ckb_multi_client_duplicate_info(
+                        format!("{:?}", PackedClientInfo::new_unchecked(prev.output_data)),
+                        format!("{:?}", PackedClientInfo::new_unchecked(cell.output_data)),
+                    ));
                 }
             } else {
-                panic!("multi client cell has invalid data: {:?}", cell.output_data);
+                return Err(Error::ckb_multi_client_invalid_data(hex::encode(
+                    cell.output_data,
+                )));
             }
         }
 
         let Some(client_info_cell) = client_info_cell_opt else {
-            panic!("on-chain data corrupted: client info cell not found");
+            return Err(Error::ckb_multi_client_info_missing());
         };
         Ok(Some((client_cells, client_info_cell)))
     }
@@ -179,7 +185,9 @@ pub trait TxAssembler: CellSearcher + TxCompleter {
             }
         }
         let (Some(oldest), Some(latest)) = (oldest, latest) else {
-            panic!("on-chain data corrupted: oldest or latest client not found");
+            return Err(Error::ckb_multi_client_update_cells_missing(
+                oldest_id, latest_id,
+            ));
         };
         let update_cells = UpdateCells {
             oldest,
@@ -212,6 +220,45 @@ pub trait TxAssembler: CellSearcher + TxCompleter {
         }
     }
 
+    /// Search the live cells locked by the light client lock and typed by the light
+    /// client contract, which belong to another multi-client set than the current one
+    async fn fetch_obsolete_client_cells(
+        &self,
+        lock_typeid_args: &H256,
+        contract_typeid_args: &H256,
+        current_type_id: &H256,
+    ) -> Result<Vec<(LiveCell, H256)>, Error> {
+        let lock_script = {
+            let lock_contract = make_typeid_script(lock_typeid_args.as_bytes().to_vec());
+            make_lightclient_script(lock_contract.calc_script_hash(), vec![])
+        };
+        let contract_hash =
+            make_typeid_script(contract_typeid_args.as_bytes().to_vec()).calc_script_hash();
+
+        let search: SearchKey = CellQueryOptions::new(lock_script, PrimaryScriptType::Lock).into();
+        let mut obsolete_cells = vec![];
+        let mut cursor = None;
+        loop {
+            let page = self
+                .fetch_live_cells(search.clone(), OBSOLETE_CELLS_PAGE_SIZE, cursor)
+                .await
+                .map_err(|e| Error::rpc_response(e.to_string()))?;
+            let is_last_page = page.objects.len() < OBSOLETE_CELLS_PAGE_SIZE as usize;
+            for cell in page.objects {
+                let cell: LiveCell = cell.into();
+                let type_id = prune::client_type_id(&cell, &contract_hash)
+                    .filter(|type_id| type_id != current_type_id);
+                if let Some(type_id) = type_id {
+                    obsolete_cells.push((cell, type_id));
+                }
+            }
+            if is_last_page {
+                return Ok(obsolete_cells);
+            }
+            cursor = Some(page.last_cursor);
+        }
+    }
+
     async fn build_lock_script(
         &self,
         lock_typeid_args: &H256,
@@ -259,7 +306,12 @@ pub trait TxAssembler: CellSearcher + TxCompleter {
         // We have to get one input cell to calculate the type id for those new cells.
         let mut _excessive_capacity = 0;
         let input_cells = self
-            .search_cells_by_address_and_capacity(address, 1, &mut _excessive_capacity)
+            .search_cells_by_address_and_capacity(
+                address,
+                1,
+                &mut _excessive_capacity,
+                &HashSet::new(),
+            )
             .await?;
         let inputs_capacity: u64 = input_cells
             .iter()
@@ -457,6 +509,130 @@ pub trait TxAssembler: CellSearcher + TxCompleter {
         inputs_as_cell_outputs.append(&mut new_inputs_as_cell_outputs);
         Ok((tx, inputs_as_cell_outputs))
     }
+
+    async fn assemble_prune_clients_transaction(
+        &self,
+        address: &Address,
+        obsolete_cells: Vec<LiveCell>,
+        lock_typeid_args: &H256,
+        contract_typeid_args: &H256,
+    ) -> Result<(TransactionView, Vec<packed::CellOutput>), Error> {
+        let (_, lock_contract_celldep) = self.build_lock_script(lock_typeid_args).await?;
+        let lc_contract_celldep = {
+            let lc_contract = make_typeid_script(contract_typeid_args.as_bytes().to_vec());
+            let cell = search_contract_cell(self, &lc_contract, contract_typeid_args).await?;
+            packed::CellDep::new_builder()
+                .out_point(cell.out_point)
+                .dep_type(DepType::Code.into())
+                .build()
+        };
+
+        let inputs_capacity: u64 = obsolete_cells
+            .iter()
+            .map(|c| Unpack::<u64>::unpack(&c.output.capacity()))
+            .sum();
+        let (inputs, mut inputs_as_cell_outputs): (
+            Vec<packed::CellInput>,
+            Vec<packed::CellOutput>,
+        ) = obsolete_cells
+            .into_iter()
+            .map(|cell| {
+                let input = packed::CellInput::new(cell.out_point, 0);
+                let input_as_cell_output = cell.output;
+                (input, input_as_cell_output)
+            })
+            .unzip();
+        let witnesses = vec![packed::Bytes::default(); inputs.len()];
+
+        // The capacity of the consumed cells all goes to the change cell
+        let tx = TransactionView::new_advanced_builder()
+            .inputs(inputs)
+            .witnesses(witnesses)
+            .cell_dep(lc_contract_celldep)
+            .cell_dep(lock_contract_celldep)
+            .build();
+
+        let fee_rate = 3000;
+        let (tx, mut new_inputs_as_cell_outputs) = self
+            .complete_tx_with_secp256k1_change(tx, address, inputs_capacity, fee_rate)
+            .await?;
+        inputs_as_cell_outputs.append(&mut new_inputs_as_cell_outputs);
+        Ok((tx, inputs_as_cell_outputs))
+    }
+
+    /// Move all the cells of a multi-client set under the light client lock with the
+    /// `frozen_args`, so that the set is no longer updated once its misbehaviour is found.
+    /// The data of the cells are left untouched for the evidence to be inspected.
+    async fn assemble_freeze_multi_client_transaction(
+        &self,
+        address: &Address,
+        client_cells: Vec<LiveCell>,
+        info_cell: LiveCell,
+        lock_typeid_args: &H256,
+        contract_typeid_args: &H256,
+        frozen_args: Vec<u8>,
+    ) -> Result<(TransactionView, Vec<packed::CellOutput>), Error> {
+        let (lock_script, lock_contract_celldep) = self.build_lock_script(lock_typeid_args).await?;
+        let frozen_lock_script = lock_script.as_builder().args(frozen_args.pack()).build();
+        let lc_contract_celldep = {
+            let lc_contract = make_typeid_script(contract_typeid_args.as_bytes().to_vec());
+            let cell = search_contract_cell(self, &lc_contract, contract_typeid_args).await?;
+            packed::CellDep::new_builder()
+                .out_point(cell.out_point)
+                .dep_type(DepType::Code.into())
+                .build()
+        };
+
+        let input_cells = client_cells
+            .into_iter()
+            .chain([info_cell])
+            .collect::<Vec<_>>();
+        let inputs_capacity: u64 = input_cells
+            .iter()
+            .map(|c| Unpack::<u64>::unpack(&c.output.capacity()))
+            .sum();
+        let (outputs, outputs_data): (Vec<packed::CellOutput>, Vec<packed::Bytes>) = input_cells
+            .iter()
+            .map(|cell| {
+                let output = cell
+                    .output
+                    .clone()
+                    .as_builder()
+                    .lock(frozen_lock_script.clone())
+                    .build_exact_capacity(Capacity::bytes(cell.output_data.len()).unwrap())
+                    .expect("build frozen client output");
+                (output, cell.output_data.pack())
+            })
+            .unzip();
+        let (inputs, mut inputs_as_cell_outputs): (
+            Vec<packed::CellInput>,
+            Vec<packed::CellOutput>,
+        ) = input_cells
+            .into_iter()
+            .map(|cell| {
+                let input = packed::CellInput::new(cell.out_point, 0);
+                let input_as_cell_output = cell.output;
+                (input, input_as_cell_output)
+            })
+            .unzip();
+        let witnesses = vec![packed::Bytes::default(); inputs.len()];
+
+        let tx = TransactionView::new_advanced_builder()
+            .inputs(inputs)
+            .outputs(outputs)
+            .outputs_data(outputs_data)
+            .witnesses(witnesses)
+            .cell_dep(lc_contract_celldep)
+            .cell_dep(lock_contract_celldep)
+            .build();
+
+        let fee_rate = 3000;
+        let (tx, mut new_inputs_as_cell_outputs) = self
+            .complete_tx_with_secp256k1_change(tx, address, inputs_capacity, fee_rate)
+            .await?;
+        inputs_as_cell_outputs.append(&mut new_inputs_as_cell_outputs);
+        Ok((tx, inputs_as_cell_outputs))
+    }
 }
 
 impl TxAssembler for RpcClient {}
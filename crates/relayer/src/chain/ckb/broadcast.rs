@@ -0,0 +1,45 @@
+use ckb_jsonrpc_types::{OutputsValidator, Transaction};
+use tracing::{debug, warn};
+
+use super::prelude::CkbWriter;
+use super::rpc_client::RpcClient;
+use crate::config::rpc_url::RpcUrl;
+
+/// Sends the signed transactions to a list of additional CKB nodes, so that a transaction
+/// is not lost because of the pool issues of a single node.
+///
+/// The submission is fire-and-forget: the responses of these nodes are only logged, and
+/// the confirmation of the transactions is tracked through the primary node.
+#[derive(Default)]
+pub struct TxBroadcaster {
+    nodes: Vec<(RpcUrl, RpcClient)>,
+}
+
+impl TxBroadcaster {
+    pub fn new(urls: &[RpcUrl]) -> Self {
+        let nodes = urls
+            .iter()
+            .map(|url| (url.clone(), RpcClient::new(url, url)))
+            .collect();
+        Self { nodes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Spawn the submission of the transaction to every additional node on the current
+    /// tokio runtime, without waiting for any of them.
+    pub fn broadcast(&self, tx: &Transaction, outputs_validator: Option<OutputsValidator>) {
+        for (url, node) in &self.nodes {
+            let url = url.clone();
+            let send = node.send_transaction(tx, outputs_validator.clone());
+            tokio::spawn(async move {
+                match send.await {
+                    Ok(hash) => debug!("ckb transaction {hash:#x} broadcasted to {url}"),
+                    Err(e) => warn!("failed to broadcast ckb transaction to {url}: {e}"),
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Decoded view of the multi-client cells of the Ethereum light client, so that the
+//! operators can inspect them without decoding their molecule data by hand.
+
+use ckb_sdk::traits::LiveCell;
+use ckb_types::{prelude::*, H256};
+use eth_light_client_in_ckb_verification::types::{
+    packed::{Client as PackedClient, ClientInfo as PackedClientInfo},
+    prelude::Unpack as _,
+};
+use serde::{Deserialize, Serialize};
+
+use super::prelude::UpdateCells;
+
+/// Position of a live cell on chain
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellOutPoint {
+    pub tx_hash: H256,
+    pub index: u32,
+}
+
+impl From<&LiveCell> for CellOutPoint {
+    fn from(cell: &LiveCell) -> Self {
+        Self {
+            tx_hash: cell.out_point.tx_hash().unpack(),
+            index: cell.out_point.index().unpack(),
+        }
+    }
+}
+
+/// Whether the cell of a multi-client set has been moved under a lock with args by
+/// `assemble_freeze_multi_client_transaction`, its clients having misbehaved
+pub(super) fn is_frozen(cell: &LiveCell) -> bool {
+    !cell.output.lock().args().raw_data().is_empty()
+}
+
+/// Client cell of a multi-client set
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientCell {
+    pub out_point: CellOutPoint,
+    pub id: u8,
+    /// Slot of the first header of the client
+    pub minimal_slot: u64,
+    /// Slot of the last header of the client
+    pub maximal_slot: u64,
+    pub tip_valid_header_root: H256,
+    /// Root of the MMR of the headers, hex encoded
+    pub headers_mmr_root: String,
+}
+
+impl ClientCell {
+    fn decode(cell: &LiveCell) -> Self {
+        let client = PackedClient::new_unchecked(cell.output_data.clone());
+        Self {
+            out_point: cell.into(),
+            id: u8::from(client.id().as_reader()),
+            minimal_slot: client.minimal_slot().unpack(),
+            maximal_slot: client.maximal_slot().unpack(),
+            tip_valid_header_root: H256::from_slice(client.tip_valid_header_root().as_slice())
+                .expect("hash of 32 bytes"),
+            headers_mmr_root: hex::encode(client.headers_mmr_root().as_slice()),
+        }
+    }
+}
+
+/// Latest and oldest client cells of the multi-client set of the chain, along with
+/// its client info cell
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientCells {
+    pub type_id: H256,
+    pub cells_count: u8,
+    pub info: CellOutPoint,
+    /// Identifier of the latest client
+    pub last_id: u8,
+    pub minimal_updates_count: u8,
+    /// Whether the cells are frozen by a misbehaviour evidence
+    pub frozen: bool,
+    /// Client whose cell is updated last
+    pub latest: ClientCell,
+    /// Client whose cell is updated next
+    pub oldest: ClientCell,
+}
+
+impl ClientCells {
+    /// The cells are expected to be checked by `fetch_update_cells` already
+    pub(super) fn decode(type_id: H256, cells_count: u8, update_cells: &UpdateCells) -> Self {
+        let info = PackedClientInfo::new_unchecked(update_cells.info.output_data.clone());
+        Self {
+            type_id,
+            cells_count,
+            info: (&update_cells.info).into(),
+            last_id: u8::from(info.last_id().as_reader()),
+            minimal_updates_count: u8::from(info.minimal_updates_count().as_reader()),
+            frozen: is_frozen(&update_cells.info),
+            latest: ClientCell::decode(&update_cells.latest),
+            oldest: ClientCell::decode(&update_cells.oldest),
+        }
+    }
+}
@@ -1,8 +1,8 @@
 use ckb_jsonrpc_types::{
-    BlockNumber, BlockView, CellWithStatus, ChainInfo, HeaderView, JsonBytes, OutPoint,
-    OutputsValidator, RawTxPool, Transaction, TransactionWithStatusResponse, TxPoolInfo,
+    BlockNumber, BlockView, CellWithStatus, ChainInfo, DryRunResult, HeaderView, JsonBytes,
+    OutPoint, OutputsValidator, RawTxPool, Transaction, TransactionWithStatusResponse, TxPoolInfo,
 };
-use ckb_sdk::rpc::ckb_indexer::{Cell, Pagination, SearchKey};
+use ckb_sdk::rpc::ckb_indexer::{Cell, Pagination, SearchKey, Tip};
 use ckb_types::H256;
 use std::{future::Future, pin::Pin};
 
@@ -35,10 +35,16 @@ pub trait CkbReader {
         cursor: Option<JsonBytes>,
     ) -> Response<Pagination<Cell>>;
 
+    fn get_indexer_tip(&self) -> Response<Option<Tip>>;
+
     // For debugging purposes.
     fn get_raw_tx_pool(&self, verbose: bool) -> Response<RawTxPool>;
 
     fn tx_pool_info(&self) -> Response<TxPoolInfo>;
+
+    // Runs the scripts of the transaction without sending it, failing with the same
+    // error as `send_transaction` if they fail.
+    fn estimate_cycles(&self, tx: &Transaction) -> Response<DryRunResult>;
 }
 
 pub trait CkbWriter {
@@ -0,0 +1,118 @@
+//! Background task keeping the Ethereum light client on CKB up to date.
+//!
+//! Instead of waiting for the events of the Ethereum chain, the updater checks the
+//! finalized slot of Ethereum on a fixed interval, and relays the missing headers
+//! once the light client lags behind by at least `min_lag` slots.
+
+use core::convert::Infallible;
+use std::sync::Arc;
+
+use tracing::{debug, error_span, info, warn};
+
+use crate::chain::handle::ChainHandle;
+use crate::chain::requests::{PageRequest, QueryClientStatesRequest};
+use crate::config::ckb::HeaderUpdaterConfig;
+use crate::error::Error;
+use crate::supervisor::forcerelay::{
+    extract_missing_slot_from_error, send_messages, MAX_HEADERS_IN_BATCH,
+};
+use crate::util::task::{spawn_background_task, Next, TaskError, TaskHandle};
+
+pub fn spawn_header_updater<Eth: ChainHandle, Ckb: ChainHandle>(
+    eth: Eth,
+    ckb: Ckb,
+    config: HeaderUpdaterConfig,
+) -> TaskHandle {
+    let span = error_span!("worker.header_updater", eth = %eth.id(), ckb = %ckb.id());
+    let interval = config.interval;
+    let mut updater = HeaderUpdater {
+        eth,
+        ckb: Arc::new(ckb),
+        config,
+        next_slot: None,
+    };
+
+    spawn_background_task(
+        span,
+        Some(interval),
+        move || -> Result<Next, TaskError<Infallible>> {
+            if let Err(e) = updater.update() {
+                warn!("failed to update the light client: {e}");
+            }
+            Ok(Next::Continue)
+        },
+    )
+}
+
+struct HeaderUpdater<Eth, Ckb> {
+    eth: Eth,
+    ckb: Arc<Ckb>,
+    config: HeaderUpdaterConfig,
+    /// First slot which isn't relayed yet, unknown until the light client is probed
+    next_slot: Option<u64>,
+}
+
+impl<Eth: ChainHandle, Ckb: ChainHandle> HeaderUpdater<Eth, Ckb> {
+    fn update(&mut self) -> Result<(), Error> {
+        let finalized_slot = self.eth.query_latest_height()?.revision_height();
+        let mut next_slot = match self.next_slot {
+            Some(slot) => slot,
+            None => self.probe(finalized_slot)?,
+        };
+
+        let lag = (finalized_slot + 1).saturating_sub(next_slot);
+        if lag < self.config.min_lag {
+            debug!("light client lags {lag} slots behind, skip updating");
+            return Ok(());
+        }
+        if lag > self.config.max_lag {
+            warn!(
+                "light client lags {lag} slots behind, over the threshold of {} slots",
+                self.config.max_lag
+            );
+        }
+
+        while next_slot <= finalized_slot {
+            let limit = std::cmp::min(MAX_HEADERS_IN_BATCH, finalized_slot - next_slot + 1);
+            let client_states = self.eth.query_clients(QueryClientStatesRequest {
+                pagination: Some(PageRequest {
+                    offset: next_slot,
+                    limit,
+                    ..Default::default()
+                }),
+            })?;
+            if client_states.is_empty() {
+                break;
+            }
+            let end_slot = next_slot + client_states.len() as u64 - 1;
+            if let Err(e) = send_messages(&self.ckb, client_states) {
+                // a missing slot means that the light client was updated by someone else
+                self.next_slot = extract_missing_slot_from_error(&e);
+                return Err(e);
+            }
+            info!("headers [{next_slot}, {end_slot}] are relayed to ckb");
+            next_slot = end_slot + 1;
+            self.next_slot = Some(next_slot);
+        }
+
+        Ok(())
+    }
+
+    // Submit the latest finalized header, which the light client rejects with its
+    // first missing slot unless it is up to date.
+    fn probe(&mut self, finalized_slot: u64) -> Result<u64, Error> {
+        let client_states = self.eth.query_clients(QueryClientStatesRequest {
+            pagination: Some(PageRequest {
+                offset: finalized_slot,
+                limit: 1,
+                ..Default::default()
+            }),
+        })?;
+        let next_slot = match send_messages(&self.ckb, client_states) {
+            Ok(_) => finalized_slot + 1,
+            Err(e) => extract_missing_slot_from_error(&e).ok_or(e)?,
+        };
+        self.next_slot = Some(next_slot);
+        Ok(next_slot)
+    }
+}
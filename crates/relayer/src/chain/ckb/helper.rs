@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use async_trait::async_trait;
 use ckb_sdk::{
     rpc::ckb_indexer::SearchKey,
@@ -71,11 +73,14 @@ pub trait CellSearcher: CkbReader {
             .await
     }
 
+    /// Live cells of `address` holding at least `need_capacity`, other than the
+    /// `excluded` ones which are already spent by pending transactions
     async fn search_cells_by_address_and_capacity(
         &self,
         address: &Address,
         need_capacity: u64,
         excessive_capacity: &mut u64,
+        excluded: &HashSet<packed::OutPoint>,
     ) -> Result<Vec<LiveCell>, Error> {
         let lockscript: packed::Script = address.payload().into();
         let mut searched_capacity = 0;
@@ -99,10 +104,12 @@ pub trait CellSearcher: CkbReader {
             let mut live_cells = result
                 .objects
                 .into_iter()
+                .map(LiveCell::from)
+                .filter(|cell| !excluded.contains(&cell.out_point))
                 .filter_map(|cell| {
                     if searched_capacity < need_capacity {
-                        searched_capacity += Into::<u64>::into(cell.output.capacity);
-                        Some(cell.into())
+                        searched_capacity += Unpack::<u64>::unpack(&cell.output.capacity());
+                        Some(cell)
                     } else {
                         None
                     }
@@ -119,11 +126,30 @@ pub trait CellSearcher: CkbReader {
 #[async_trait]
 pub trait TxCompleter: CellSearcher {
     async fn complete_tx_with_secp256k1_change(
+        &self,
+        tx: TransactionView,
+        address: &Address,
+        inputs_capacity: u64,
+        fee_rate: u64,
+    ) -> Result<(TransactionView, Vec<packed::CellOutput>), Error> {
+        self.complete_tx_with_secp256k1_change_excluding(
+            tx,
+            address,
+            inputs_capacity,
+            fee_rate,
+            &HashSet::new(),
+        )
+        .await
+    }
+
+    /// Same as `complete_tx_with_secp256k1_change`, without spending the `excluded` cells
+    async fn complete_tx_with_secp256k1_change_excluding(
         &self,
         mut tx: TransactionView,
         address: &Address,
         inputs_capacity: u64,
         fee_rate: u64,
+        excluded: &HashSet<packed::OutPoint>,
     ) -> Result<(TransactionView, Vec<packed::CellOutput>), Error> {
         let lock_script: packed::Script = address.payload().into();
         let mut change_cell = packed::CellOutput::new_builder()
@@ -147,6 +173,7 @@ pub trait TxCompleter: CellSearcher {
                     address,
                     need_capacity,
                     &mut excessive_capacity,
+                    excluded,
                 )
                 .await?;
             let inputs_cell = live_cells
@@ -2,19 +2,24 @@
 #![allow(unused_variables)]
 
 use ckb_jsonrpc_types::{
-    BlockNumber, BlockView, CellWithStatus, ChainInfo, Header, HeaderView, JsonBytes, OutPoint,
-    OutputsValidator, RawTxPool, ResponseFormat, Transaction, TransactionView,
+    BlockNumber, BlockView, CellWithStatus, ChainInfo, DryRunResult, Header, HeaderView, JsonBytes,
+    OutPoint, OutputsValidator, RawTxPool, ResponseFormat, Transaction, TransactionView,
     TransactionWithStatusResponse, TxPoolInfo, TxStatus,
 };
-use ckb_sdk::rpc::ckb_indexer::{Cell, Pagination, SearchKey};
+use ckb_sdk::rpc::ckb_indexer::{Cell, Pagination, SearchKey, Tip};
+use ckb_sdk::rpc::ckb_light_client::ScriptStatus;
 use ckb_types::{packed, prelude::*, H256};
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
 use std::{
     collections::HashMap,
+    num::{NonZeroU32, NonZeroUsize},
     sync::{Arc, RwLock},
+    time::Duration,
 };
-use tendermint_rpc::Url;
 
 use super::prelude::{CkbReader, CkbWriter, Response as Rpc};
+use crate::config::ckb4ibc::RpcMode;
+use crate::config::rpc_url::RpcUrl;
 use crate::error::Error;
 
 #[derive(Clone)]
@@ -32,12 +37,45 @@ struct RpcData {
 }
 
 impl RpcClient {
-    pub fn new(_ckb_uri: &Url, _indexer_uri: &Url) -> Self {
+    pub fn new(_ckb_uri: &RpcUrl, _indexer_uri: &RpcUrl) -> Self {
         Self {
             data: Arc::new(RwLock::new(RpcData::default())),
         }
     }
 
+    pub fn with_chain_id(self, _chain_id: ChainId) -> Self {
+        self
+    }
+
+    pub fn with_confirmations(self, _confirmations: u64) -> Self {
+        self
+    }
+
+    pub fn with_indexer_guard(
+        self,
+        _chain_id: ChainId,
+        _max_lag: u64,
+        _max_wait: Duration,
+    ) -> Self {
+        self
+    }
+
+    pub fn with_request_limits(
+        self,
+        _max_requests_per_second: Option<NonZeroU32>,
+        _max_concurrent_requests: Option<NonZeroUsize>,
+    ) -> Self {
+        self
+    }
+
+    pub fn with_rpc_mode(self, _mode: RpcMode) -> Self {
+        self
+    }
+
+    pub fn register_light_client_scripts(&self, _scripts: Vec<ScriptStatus>) -> Rpc<()> {
+        Box::pin(async { Ok(()) })
+    }
+
     pub fn set_blockchain_info(&self, chain_info: Option<&str>) {
         self.data.write().unwrap().chain_info = chain_info.map(ToOwned::to_owned);
     }
@@ -172,6 +210,14 @@ impl CkbReader for RpcClient {
         Box::pin(async { Ok(resp) })
     }
 
+    fn get_indexer_tip(&self) -> Rpc<Option<Tip>> {
+        let resp = Tip {
+            block_hash: Default::default(),
+            block_number: u64::MAX.into(),
+        };
+        Box::pin(async { Ok(Some(resp)) })
+    }
+
     fn get_raw_tx_pool(&self, verbose: bool) -> Rpc<RawTxPool> {
         todo!()
     }
@@ -179,6 +225,11 @@ impl CkbReader for RpcClient {
     fn tx_pool_info(&self) -> Rpc<TxPoolInfo> {
         todo!()
     }
+
+    fn estimate_cycles(&self, tx: &Transaction) -> Rpc<DryRunResult> {
+        let resp = DryRunResult { cycles: 0.into() };
+        Box::pin(async { Ok(resp) })
+    }
 }
 
 impl CkbWriter for RpcClient {
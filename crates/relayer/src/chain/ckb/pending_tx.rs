@@ -0,0 +1,411 @@
+use std::time::{Duration, Instant};
+
+use ckb_jsonrpc_types::{Status, Transaction};
+use ckb_types::{packed, prelude::Unpack, H256};
+use futures::future::join_all;
+use tracing::{debug, warn};
+
+use super::broadcast::TxBroadcaster;
+use super::prelude::{CkbReader, CkbWriter};
+use super::utils::diagnose_ckb_script_failure;
+use crate::error::Error;
+
+/// Parameters used to poll the status of submitted transactions.
+#[derive(Clone, Copy, Debug)]
+pub struct PendingTxConfig {
+    pub poll_interval: Duration,
+    pub confirmations: u64,
+    pub timeout: Duration,
+    /// Resolve the transactions as soon as the node accepts them into its pool, for the
+    /// transactions whose unconfirmed outputs are spent by the next ones
+    pub accept_in_pool: bool,
+}
+
+impl Default for PendingTxConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(3),
+            confirmations: 4,
+            timeout: Duration::from_secs(600),
+            accept_in_pool: false,
+        }
+    }
+}
+
+enum TxProgress {
+    Pending,
+    /// Committed in the block of the hash and number
+    Committed(H256, u64),
+    Rejected(String),
+}
+
+struct PendingTx<T> {
+    index: usize,
+    hash: H256,
+    /// Hash and number of the block the transaction was last seen committed in
+    block: Option<(H256, u64)>,
+    payload: T,
+}
+
+type TrackResult<T> = Result<(H256, T), Error>;
+
+/// Submits a batch of transactions at once and tracks all of them concurrently,
+/// so that a slow transaction doesn't stall the others in the same batch.
+pub struct PendingTxTracker<'a, R> {
+    rpc: &'a R,
+    config: PendingTxConfig,
+    broadcaster: Option<&'a TxBroadcaster>,
+}
+
+impl<'a, R> PendingTxTracker<'a, R>
+where
+    R: CkbReader + CkbWriter,
+{
+    pub fn new(rpc: &'a R, config: PendingTxConfig) -> Self {
+        Self {
+            rpc,
+            config,
+            broadcaster: None,
+        }
+    }
+
+    /// Also send the transactions to the additional nodes of the broadcaster.
+    pub fn with_broadcaster(mut self, broadcaster: &'a TxBroadcaster) -> Self {
+        self.broadcaster = Some(broadcaster);
+        self
+    }
+
+    /// Send all transactions immediately and wait until each of them is committed with
+    /// enough confirmations, rejected or timed out.
+    ///
+    /// Results are returned in the same order as `txs`, each successful one carrying the
+    /// transaction hash along with the payload attached to it.
+    pub async fn submit_and_track<T>(&self, txs: Vec<(Transaction, T)>) -> Vec<TrackResult<T>> {
+        let parents = vec![vec![]; txs.len()];
+        self.submit_chained_and_track(txs, &parents).await
+    }
+
+    /// Same as `submit_and_track`, for transactions spending the outputs of their
+    /// `parents`, the indexes of the previous transactions of `txs` they are chained on.
+    ///
+    /// The transactions are sent in waves, each wave being made of the transactions whose
+    /// parents are all sent already. A transaction whose parent failed to be sent is not
+    /// sent, and is reported as spending a dead cell to be assembled again.
+    pub async fn submit_chained_and_track<T>(
+        &self,
+        txs: Vec<(Transaction, T)>,
+        parents: &[Vec<usize>],
+    ) -> Vec<TrackResult<T>> {
+        let mut results = txs.iter().map(|_| None).collect::<Vec<_>>();
+        let (transactions, payloads): (Vec<_>, Vec<_>) = txs.into_iter().unzip();
+        let mut payloads = payloads.into_iter().map(Some).collect::<Vec<_>>();
+        let depths = depths(parents);
+        let mut sent = vec![false; transactions.len()];
+
+        let mut pending = vec![];
+        for depth in 0..=depths.iter().copied().max().unwrap_or_default() {
+            let mut wave = vec![];
+            for index in (0..transactions.len()).filter(|index| depths[*index] == depth) {
+                match parents[index].iter().find(|parent| !sent[**parent]) {
+                    Some(parent) => {
+                        results[index] = Some(Err(Error::ckb_tx_dead_cell(
+                            format!("{:#x}", tx_hash(&transactions[index])),
+                            format!(
+                                "parent transaction {:#x} is not sent",
+                                tx_hash(&transactions[*parent])
+                            ),
+                        )));
+                    }
+                    None => wave.push(index),
+                }
+            }
+
+            if let Some(broadcaster) = self.broadcaster {
+                for index in &wave {
+                    broadcaster.broadcast(&transactions[*index], None);
+                }
+            }
+            let resps = join_all(
+                wave.iter()
+                    .map(|index| self.rpc.send_transaction(&transactions[*index], None)),
+            )
+            .await;
+            for (index, resp) in wave.into_iter().zip(resps) {
+                match resp {
+                    Ok(hash) => {
+                        debug!("ckb transaction {hash:#x} submitted");
+                        sent[index] = true;
+                        pending.push(PendingTx {
+                            index,
+                            hash,
+                            block: None,
+                            payload: payloads[index].take().expect("payload of a new tx"),
+                        });
+                    }
+                    Err(e) => {
+                        let hash = tx_hash(&transactions[index]);
+                        results[index] =
+                            Some(Err(Error::ckb_send_tx_failure(format!("{hash:#x}"), e)));
+                    }
+                }
+            }
+        }
+
+        if self.config.accept_in_pool {
+            for tx in pending.drain(..) {
+                results[tx.index] = Some(Ok((tx.hash, tx.payload)));
+            }
+        }
+        self.wait_for(pending, &mut results).await;
+
+        let mut diagnosed = Vec::with_capacity(results.len());
+        for (tx, result) in transactions.iter().zip(results) {
+            let result = match result.expect("every transaction is resolved") {
+                Err(e) => {
+                    let e = diagnose_ckb_script_failure(self.rpc, tx, e).await;
+                    if e.is_ckb_script_failure() {
+                        warn!("{e}");
+                    }
+                    Err(e)
+                }
+                result => result,
+            };
+            diagnosed.push(result);
+        }
+        diagnosed
+    }
+
+    /// Track the transactions of `hashes` which were sent before, e.g. by the relayer
+    /// before it restarted, until each of them is committed with enough confirmations,
+    /// rejected or timed out. The transactions unknown to the node are rejected at once,
+    /// since they have been dropped from its pool.
+    pub async fn track_sent(&self, hashes: Vec<H256>) -> Vec<Result<H256, Error>> {
+        let mut results = hashes.iter().map(|_| None).collect::<Vec<_>>();
+        let resps = join_all(hashes.iter().map(|hash| self.rpc.get_transaction(hash))).await;
+        let mut pending = vec![];
+        for (index, (hash, resp)) in hashes.into_iter().zip(resps).enumerate() {
+            match resp {
+                Ok(Some(resp)) if resp.tx_status.status != Status::Unknown => {
+                    pending.push(PendingTx {
+                        index,
+                        hash,
+                        block: None,
+                        payload: (),
+                    });
+                }
+                Ok(_) => {
+                    results[index] = Some(Err(Error::ckb_tx_rejection(
+                        format!("{hash:#x}"),
+                        "unknown to the node".to_owned(),
+                    )));
+                }
+                Err(e) => results[index] = Some(Err(e)),
+            }
+        }
+        self.wait_for(pending, &mut results).await;
+
+        results
+            .into_iter()
+            .map(|result| {
+                result
+                    .expect("every transaction is resolved")
+                    .map(|(hash, ())| hash)
+            })
+            .collect()
+    }
+
+    /// Poll the pending transactions until each of them is resolved or timed out
+    async fn wait_for<T>(
+        &self,
+        mut pending: Vec<PendingTx<T>>,
+        results: &mut [Option<TrackResult<T>>],
+    ) {
+        let start = Instant::now();
+        while !pending.is_empty() {
+            if start.elapsed() > self.config.timeout {
+                for tx in pending.drain(..) {
+                    results[tx.index] = Some(Err(Error::ckb_tx_commit_timeout(
+                        format!("{:#x}", tx.hash),
+                        self.config.timeout,
+                    )));
+                }
+                break;
+            }
+            tokio::time::sleep(self.config.poll_interval).await;
+            pending = self.poll_once(pending, results).await;
+        }
+    }
+
+    /// Query the status of all pending transactions concurrently, resolve the ones which
+    /// are either rejected or confirmed, and return the rest.
+    ///
+    /// The status of the committed transactions is queried again until they are
+    /// confirmed, since a reorg may drop them from their block or move them to another.
+    async fn poll_once<T>(
+        &self,
+        pending: Vec<PendingTx<T>>,
+        results: &mut [Option<TrackResult<T>>],
+    ) -> Vec<PendingTx<T>> {
+        let statuses = join_all(pending.iter().map(|tx| async move {
+            let Some(resp) = self.rpc.get_transaction(&tx.hash).await? else {
+                return Ok(TxProgress::Pending);
+            };
+            let progress = match resp.tx_status.status {
+                Status::Rejected => TxProgress::Rejected(
+                    resp.tx_status
+                        .reason
+                        .unwrap_or_else(|| "unknown".to_string()),
+                ),
+                Status::Committed => match resp.tx_status.block_hash {
+                    Some(block_hash) => {
+                        let block_number = match &tx.block {
+                            Some((hash, number)) if *hash == block_hash => *number,
+                            _ => {
+                                let block = self.rpc.get_block(&block_hash).await?;
+                                block.header.inner.number.value()
+                            }
+                        };
+                        TxProgress::Committed(block_hash, block_number)
+                    }
+                    None => TxProgress::Pending,
+                },
+                _ => TxProgress::Pending,
+            };
+            Ok::<_, Error>(progress)
+        }))
+        .await;
+
+        let tip_number = match self.rpc.get_tip_header().await {
+            Ok(tip) => Some(tip.inner.number.value()),
+            Err(e) => {
+                warn!("failed to fetch ckb tip header: {e}");
+                None
+            }
+        };
+
+        let mut still_pending = vec![];
+        for (mut tx, status) in pending.into_iter().zip(statuses) {
+            match status {
+                Ok(TxProgress::Committed(block_hash, block_number)) => {
+                    tx.block = Some((block_hash, block_number))
+                }
+                Ok(TxProgress::Rejected(reason)) => {
+                    results[tx.index] = Some(Err(Error::ckb_tx_rejection(
+                        format!("{:#x}", tx.hash),
+                        reason,
+                    )));
+                    continue;
+                }
+                Ok(TxProgress::Pending) => {
+                    if let Some((block_hash, _)) = tx.block.take() {
+                        warn!(
+                            "ckb transaction {:#x} is no longer committed in block {block_hash:#x}, \
+                             waiting for it to be committed again",
+                            tx.hash
+                        );
+                    }
+                }
+                Err(e) => warn!("failed to query ckb tx {:#x}: {e}", tx.hash),
+            }
+            let block_number = tx.block.as_ref().map(|(_, number)| *number);
+            match (block_number, tip_number) {
+                (Some(block_number), Some(tip_number))
+                    if tip_number >= block_number + self.config.confirmations =>
+                {
+                    debug!("ckb transaction {:#x} committed", tx.hash);
+                    results[tx.index] = Some(Ok((tx.hash, tx.payload)));
+                }
+                _ => still_pending.push(tx),
+            }
+        }
+        still_pending
+    }
+}
+
+fn tx_hash(tx: &Transaction) -> H256 {
+    packed::Transaction::from(tx.clone())
+        .calc_tx_hash()
+        .unpack()
+}
+
+/// Depth of each transaction in the graph of `parents`, the ones without parents being
+/// at depth 0. Parents always precede their children.
+fn depths(parents: &[Vec<usize>]) -> Vec<usize> {
+    let mut depths: Vec<usize> = Vec::with_capacity(parents.len());
+    for tx_parents in parents {
+        let depth = tx_parents
+            .iter()
+            .map(|parent| depths[*parent] + 1)
+            .max()
+            .unwrap_or(0);
+        depths.push(depth);
+    }
+    depths
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use ckb_jsonrpc_types::Transaction;
+
+    use super::{depths, PendingTxConfig, PendingTxTracker};
+    use crate::chain::ckb::rpc_client::RpcClient;
+    use crate::config::rpc_url::RpcUrl;
+
+    #[test]
+    fn test_submit_and_track_keeps_order() {
+        let url = RpcUrl::from_str("http://ckb_rpc").unwrap();
+        let rpc_client = RpcClient::new(&url, &url);
+        let config = PendingTxConfig {
+            poll_interval: Duration::ZERO,
+            ..Default::default()
+        };
+        let tracker = PendingTxTracker::new(&rpc_client, config);
+        let txs = vec![(Transaction::default(), 1), (Transaction::default(), 2)];
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let results = rt.block_on(tracker.submit_and_track(txs));
+
+        let payloads = results
+            .into_iter()
+            .map(|result| result.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(payloads, vec![1, 2]);
+        assert_eq!(rpc_client.get_transactions_len(), 2);
+    }
+
+    #[test]
+    fn test_submit_chained_and_track_sends_all() {
+        let url = RpcUrl::from_str("http://ckb_rpc").unwrap();
+        let rpc_client = RpcClient::new(&url, &url);
+        let config = PendingTxConfig {
+            poll_interval: Duration::ZERO,
+            ..Default::default()
+        };
+        let tracker = PendingTxTracker::new(&rpc_client, config);
+        let txs = vec![
+            (Transaction::default(), 1),
+            (Transaction::default(), 2),
+            (Transaction::default(), 3),
+        ];
+        let parents = vec![vec![], vec![0], vec![]];
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let results = rt.block_on(tracker.submit_chained_and_track(txs, &parents));
+
+        let payloads = results
+            .into_iter()
+            .map(|result| result.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(payloads, vec![1, 2, 3]);
+        assert_eq!(rpc_client.get_transactions_len(), 3);
+    }
+
+    #[test]
+    fn test_depths() {
+        let parents = vec![vec![], vec![0], vec![], vec![1, 2], vec![]];
+        assert_eq!(depths(&parents), vec![0, 1, 0, 2, 0]);
+    }
+}
@@ -0,0 +1,60 @@
+//! Pruning of the light client cells of abandoned deployments.
+//!
+//! Every creation of the Ethereum light client on CKB deploys a new set of
+//! multi-client cells, locked by the light client lock. The sets of the previous
+//! deployments are never consumed again, and keep showing up when searching the
+//! cells of the light client contract, so they are consumed by the relayer on
+//! demand to reclaim their capacity.
+
+use ckb_sdk::traits::LiveCell;
+use ckb_types::{packed, prelude::*, H256};
+use eth_light_client_in_ckb_verification::types::packed::ClientTypeArgs as PackedClientTypeArgs;
+use serde::{Deserialize, Serialize};
+
+/// A live cell of a multi-client set other than the configured one
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObsoleteClientCell {
+    pub tx_hash: H256,
+    pub index: u32,
+    /// Type id of the multi-client set the cell belongs to
+    pub type_id: H256,
+    /// Capacity of the cell, in shannons
+    pub capacity: u64,
+}
+
+impl ObsoleteClientCell {
+    pub(super) fn new(cell: &LiveCell, type_id: H256) -> Self {
+        Self {
+            tx_hash: cell.out_point.tx_hash().unpack(),
+            index: cell.out_point.index().unpack(),
+            type_id,
+            capacity: cell.output.capacity().unpack(),
+        }
+    }
+}
+
+/// The obsolete client cells found on chain, and the transaction consuming them
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ClientsPruning {
+    pub cells: Vec<ObsoleteClientCell>,
+    /// Hash of the pruning transaction, unless nothing was sent
+    pub tx_hash: Option<H256>,
+}
+
+impl ClientsPruning {
+    /// Capacity released by the obsolete cells, in shannons
+    pub fn reclaimed_capacity(&self) -> u64 {
+        self.cells.iter().map(|cell| cell.capacity).sum()
+    }
+}
+
+/// Type id of the multi-client set of the cell, if the cell is typed by the light
+/// client contract of hash `contract_hash`
+pub(super) fn client_type_id(cell: &LiveCell, contract_hash: &packed::Byte32) -> Option<H256> {
+    let type_script = cell.output.type_().to_opt()?;
+    if &type_script.code_hash() != contract_hash {
+        return None;
+    }
+    let client_type_args = PackedClientTypeArgs::from_slice(&type_script.args().raw_data()).ok()?;
+    Some(H256::from_slice(client_type_args.type_id().as_slice()).expect("hash of 32 bytes"))
+}
@@ -1,19 +1,32 @@
 #![allow(dead_code)]
 
 use ckb_jsonrpc_types::{
-    BlockNumber, BlockView, CellWithStatus, ChainInfo, HeaderView, JsonBytes, OutPoint,
-    OutputsValidator, RawTxPool, Transaction, TransactionWithStatusResponse, TxPoolInfo, Uint32,
+    BlockNumber, BlockView, CellWithStatus, ChainInfo, DryRunResult, HeaderView, JsonBytes,
+    OutPoint, OutputsValidator, RawTxPool, Status, Transaction, TransactionWithStatusResponse,
+    TxPoolInfo, Uint32,
 };
-use ckb_sdk::rpc::ckb_indexer::{Cell, Order, Pagination, SearchKey};
-use ckb_types::H256;
-use futures::FutureExt;
+use ckb_sdk::rpc::ckb_indexer::{Cell, Order, Pagination, SearchKey, Tip};
+use ckb_sdk::rpc::ckb_light_client::ScriptStatus;
+use ckb_types::{h256, H256};
+use futures::{future, FutureExt};
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+use jsonrpc_core::response::Output;
+use moka::sync::Cache;
 use reqwest::Client;
+use serde_json::Value;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
-use tendermint_rpc::{Error as TmError, Url};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tendermint_rpc::Error as TmError;
 
 use super::prelude::{CkbReader, CkbWriter, Response as Rpc};
+use super::throttle::RequestThrottle;
+use crate::config::ckb4ibc::RpcMode;
+use crate::config::rpc_url::RpcUrl;
 use crate::error::Error;
+use crate::telemetry;
 
 #[allow(clippy::upper_case_acronyms)]
 enum Target {
@@ -37,23 +50,21 @@ macro_rules! jsonrpc {
             Target::CKB => $self.ckb_uri.clone(),
             Target::Indexer => $self.indexer_uri.clone(),
         };
-        let reqwest_url = reqwest::Url::parse(&url.to_string()).unwrap();
-        let c = $self.raw.post(reqwest_url).json(&req_json);
-        async {
-            let resp = c
-                .send()
-                .await
-                .map_err(|_| Error::rpc(url.clone(), TmError::invalid_url(url)))?;
-            let output = resp
-                .json::<jsonrpc_core::response::Output>()
-                .await
-                .map_err(|e| Error::rpc_response(e.to_string()))?;
+        let client = $self.raw.clone();
+        let throttle = $self.throttle.clone();
+        let chain_id = $self.chain_id.clone();
+        async move {
+            let _permit = match &throttle {
+                Some(throttle) => throttle.acquire(chain_id.as_ref(), $method).await,
+                None => None,
+            };
+            let output = post_json(&client, &url, &req_json).await?;
 
             match output {
-                jsonrpc_core::response::Output::Success(success) => {
+                Output::Success(success) => {
                     Ok(serde_json::from_value::<$return>(success.result).unwrap())
                 }
-                jsonrpc_core::response::Output::Failure(e) => {
+                Output::Failure(e) => {
                     Err(Error::rpc_response(format!("{:?}", e)))
                 }
             }
@@ -61,54 +72,455 @@ macro_rules! jsonrpc {
     }}
 }
 
+async fn post_json(client: &Client, url: &RpcUrl, request: &Value) -> Result<Output, Error> {
+    match url {
+        RpcUrl::Http(url) => {
+            let reqwest_url = reqwest::Url::parse(&url.to_string()).unwrap();
+            let resp = client
+                .post(reqwest_url)
+                .json(request)
+                .send()
+                .await
+                .map_err(|_| Error::rpc(url.clone(), TmError::invalid_url(url.clone())))?;
+            resp.json::<Output>()
+                .await
+                .map_err(|e| Error::rpc_response(e.to_string()))
+        }
+        RpcUrl::Unix(path) => post_json_over_unix_socket(path, request).await,
+    }
+}
+
+#[cfg(unix)]
+async fn post_json_over_unix_socket(path: &Path, request: &Value) -> Result<Output, Error> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::UnixStream;
+
+    let io_error =
+        |e: std::io::Error| Error::rpc_response(format!("unix socket {}: {e}", path.display()));
+    let body = serde_json::to_vec(request).map_err(|e| Error::rpc_response(e.to_string()))?;
+    let head = format!(
+        "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+
+    let mut stream = UnixStream::connect(path).await.map_err(io_error)?;
+    stream.write_all(head.as_bytes()).await.map_err(io_error)?;
+    stream.write_all(&body).await.map_err(io_error)?;
+    let mut response = vec![];
+    stream.read_to_end(&mut response).await.map_err(io_error)?;
+
+    let body = http_response_body(&response).map_err(Error::rpc_response)?;
+    serde_json::from_slice(&body).map_err(|e| Error::rpc_response(e.to_string()))
+}
+
+#[cfg(not(unix))]
+async fn post_json_over_unix_socket(path: &Path, _request: &Value) -> Result<Output, Error> {
+    Err(Error::rpc_response(format!(
+        "unix socket {} is not supported on this platform",
+        path.display()
+    )))
+}
+
+/// Extract the body of a raw HTTP/1.1 response, which is either delimited by the end
+/// of the connection or sent in chunks.
+fn http_response_body(response: &[u8]) -> Result<Vec<u8>, String> {
+    let head_end = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| "incomplete http response".to_string())?;
+    let head = String::from_utf8_lossy(&response[..head_end]);
+    let mut lines = head.lines();
+    let status_line = lines.next().unwrap_or_default();
+    if status_line.split_whitespace().nth(1) != Some("200") {
+        return Err(format!("unexpected http status `{status_line}`"));
+    }
+    let chunked = lines.any(|line| {
+        let line = line.to_ascii_lowercase();
+        line.starts_with("transfer-encoding:") && line.contains("chunked")
+    });
+
+    let mut rest = &response[head_end + 4..];
+    if !chunked {
+        return Ok(rest.to_vec());
+    }
+    let mut body = vec![];
+    loop {
+        let size_end = rest
+            .windows(2)
+            .position(|window| window == b"\r\n")
+            .ok_or_else(|| "incomplete http chunk".to_string())?;
+        let size = String::from_utf8_lossy(&rest[..size_end]);
+        let size = usize::from_str_radix(size.split(';').next().unwrap_or_default().trim(), 16)
+            .map_err(|e| format!("invalid http chunk size: {e}"))?;
+        if size == 0 {
+            return Ok(body);
+        }
+        let chunk = rest
+            .get(size_end + 2..size_end + 2 + size)
+            .ok_or_else(|| "incomplete http chunk".to_string())?;
+        body.extend_from_slice(chunk);
+        rest = rest.get(size_end + 4 + size..).unwrap_or_default();
+    }
+}
+
+/// Interval between two checks of the indexer tip while waiting for it to catch up
+const INDEXER_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long the indexer is trusted to keep up once it is seen in sync with the node,
+/// i.e. about a block, before its tip is checked again
+const INDEXER_SYNC_TTL: Duration = Duration::from_secs(8);
+
+/// Guard holding the searches for cells back while the indexer lags behind the node,
+/// as it returns no cell for the blocks it has not indexed yet
+#[derive(Clone)]
+struct IndexerGuard {
+    chain_id: ChainId,
+    max_lag: u64,
+    max_wait: Duration,
+    synced_at: Arc<Mutex<Option<Instant>>>,
+}
+
+impl IndexerGuard {
+    fn synced_lately(&self) -> bool {
+        let synced_at = self.synced_at.lock().unwrap();
+        synced_at.map_or(false, |synced_at| synced_at.elapsed() < INDEXER_SYNC_TTL)
+    }
+
+    fn set_synced(&self) {
+        *self.synced_at.lock().unwrap() = Some(Instant::now());
+    }
+}
+
+/// Genesis hashes of the public CKB chains, by the names their full nodes give them
+const GENESIS_HASHES: [(&str, H256); 2] = [
+    (
+        "ckb",
+        h256!("0x92b197aa1fba0f63633922c61c92375c9c074a93e85963554f5499fe1450d0e5"),
+    ),
+    (
+        "ckb_testnet",
+        h256!("0x10639e0895502b5688a6be8cf69460d76541bfa4821629d86d62ba0aae3f9606"),
+    ),
+];
+
+/// Chain info of a ckb-light-client node, which has no `get_blockchain_info` method:
+/// the name of the chain is told by its genesis block and the time and epoch by the tip
+fn light_client_chain_info(genesis_hash: &H256, tip: &HeaderView) -> Result<ChainInfo, Error> {
+    let chain = GENESIS_HASHES
+        .iter()
+        .find(|(_, hash)| hash == genesis_hash)
+        .map_or("ckb_dev", |(chain, _)| *chain);
+    let chain_info = serde_json::json!({
+        "chain": chain,
+        "median_time": tip.inner.timestamp,
+        "epoch": tip.inner.epoch,
+        "difficulty": "0x0",
+        "is_initial_block_download": false,
+        "alerts": [],
+    });
+    serde_json::from_value(chain_info).map_err(|e| Error::rpc_response(e.to_string()))
+}
+
+fn light_client_unsupported<T: Send + 'static>(method: &str) -> Rpc<T> {
+    future::ready(Err(Error::ckb_light_client_unsupported(method.to_owned()))).boxed()
+}
+
+/// Maximum number of confirmed transactions kept by the response cache
+const TRANSACTION_CACHE_CAPACITY: u64 = 10_000;
+
+/// Maximum number of blocks kept by the response cache
+const BLOCK_CACHE_CAPACITY: u64 = 1_000;
+
+/// Responses which don't change anymore once they are returned, by hash, as the same
+/// ones are fetched again and again while scanning the IBC cells: the blocks, and the
+/// transactions committed `confirmations` blocks below the last tip seen. The status of
+/// the transactions less deep is queried every time, as a reorg may still drop them
+#[derive(Clone)]
+struct ResponseCache {
+    transactions: Cache<H256, TransactionWithStatusResponse>,
+    blocks: Cache<H256, BlockView>,
+    /// Confirmations of the transactions which are cached, none being cached if not given
+    confirmations: Option<u64>,
+    /// Number of the last tip returned by the node
+    tip: Arc<AtomicU64>,
+}
+
+impl ResponseCache {
+    fn new() -> Self {
+        Self {
+            transactions: Cache::new(TRANSACTION_CACHE_CAPACITY),
+            blocks: Cache::new(BLOCK_CACHE_CAPACITY),
+            confirmations: None,
+            tip: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    fn is_confirmed(&self, block_number: u64) -> bool {
+        self.confirmations.map_or(false, |confirmations| {
+            self.tip.load(Ordering::Relaxed) >= block_number.saturating_add(confirmations)
+        })
+    }
+}
+
 #[derive(Clone)]
 pub struct RpcClient {
     raw: Client,
-    ckb_uri: Url,
-    indexer_uri: Url,
+    ckb_uri: RpcUrl,
+    indexer_uri: RpcUrl,
     id: Arc<AtomicU64>,
+    chain_id: Option<ChainId>,
+    indexer_guard: Option<IndexerGuard>,
+    throttle: Option<RequestThrottle>,
+    cache: ResponseCache,
+    mode: RpcMode,
 }
 
 impl RpcClient {
-    pub fn new(ckb_uri: &Url, indexer_uri: &Url) -> Self {
+    pub fn new(ckb_uri: &RpcUrl, indexer_uri: &RpcUrl) -> Self {
         RpcClient {
             raw: Client::new(),
             ckb_uri: ckb_uri.clone(),
             indexer_uri: indexer_uri.clone(),
             id: Arc::new(AtomicU64::new(0)),
+            chain_id: None,
+            indexer_guard: None,
+            throttle: None,
+            cache: ResponseCache::new(),
+            mode: RpcMode::Full,
         }
     }
+
+    /// Talk to a ckb-light-client node at the url of the node if `mode` is
+    /// [`RpcMode::Light`], which serves the searches for cells in place of the indexer.
+    /// The methods it lacks fail with [`Error::ckb_light_client_unsupported`]
+    pub fn with_rpc_mode(mut self, mode: RpcMode) -> Self {
+        if mode == RpcMode::Light {
+            self.indexer_uri = self.ckb_uri.clone();
+        }
+        self.mode = mode;
+        self
+    }
+
+    fn is_light_client(&self) -> bool {
+        self.mode == RpcMode::Light
+    }
+
+    /// Register `scripts` with the light client, so that it keeps their cells and
+    /// transactions. The scripts registered already are left as they are, so that the
+    /// light client doesn't search their cells from scratch again. Nothing is done if
+    /// the node is a full node, which keeps every cell
+    pub fn register_light_client_scripts(&self, scripts: Vec<ScriptStatus>) -> Rpc<()> {
+        if !self.is_light_client() {
+            return future::ready(Ok(())).boxed();
+        }
+        let client = self.clone();
+        async move {
+            let mut registered =
+                jsonrpc!("get_scripts", Target::CKB, client, Vec<ScriptStatus>).await?;
+            let missing = scripts
+                .into_iter()
+                .filter(|script| {
+                    !registered.iter().any(|registered| {
+                        registered.script == script.script
+                            && registered.script_type == script.script_type
+                    })
+                })
+                .collect::<Vec<_>>();
+            if missing.is_empty() {
+                return Ok(());
+            }
+            registered.extend(missing);
+            jsonrpc!("set_scripts", Target::CKB, client, (), registered).await
+        }
+        .boxed()
+    }
+
+    /// Cache the transactions once they are committed `confirmations` blocks below the
+    /// tip, after which they are taken for final. No transaction is cached otherwise
+    pub fn with_confirmations(mut self, confirmations: u64) -> Self {
+        self.cache.confirmations = Some(confirmations);
+        self
+    }
+
+    /// Record the metrics of the queries sent by this client under `chain_id`
+    pub fn with_chain_id(mut self, chain_id: ChainId) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Make the searches for cells wait up to `max_wait` for the indexer to catch up with
+    /// the node once it lags more than `max_lag` blocks behind, failing with
+    /// [`Error::ckb_indexer_lagging`] if it doesn't, so that the callers retry later
+    /// instead of taking the missing cells for consumed ones
+    pub fn with_indexer_guard(
+        mut self,
+        chain_id: ChainId,
+        max_lag: u64,
+        max_wait: Duration,
+    ) -> Self {
+        self.indexer_guard = Some(IndexerGuard {
+            chain_id,
+            max_lag,
+            max_wait,
+            synced_at: Arc::new(Mutex::new(None)),
+        });
+        self
+    }
+
+    /// Send up to `max_requests_per_second` requests per second and keep up to
+    /// `max_concurrent_requests` of them in flight, queueing the others, so that the rate
+    /// limits of the RPC providers are not hit. No limit is applied if none is given
+    pub fn with_request_limits(
+        mut self,
+        max_requests_per_second: Option<NonZeroU32>,
+        max_concurrent_requests: Option<NonZeroUsize>,
+    ) -> Self {
+        self.throttle = RequestThrottle::new(max_requests_per_second, max_concurrent_requests);
+        self
+    }
+
+    async fn wait_for_indexer(&self, guard: &IndexerGuard) -> Result<(), Error> {
+        if guard.synced_lately() {
+            return Ok(());
+        }
+        let started_at = Instant::now();
+        loop {
+            let node_tip = self.get_tip_header().await?.inner.number.value();
+            let indexer_tip = self
+                .get_indexer_tip()
+                .await?
+                .map_or(0, |tip| tip.block_number.value());
+            if node_tip.saturating_sub(indexer_tip) <= guard.max_lag {
+                guard.set_synced();
+                return Ok(());
+            }
+            if started_at.elapsed() >= guard.max_wait {
+                return Err(Error::ckb_indexer_lagging(
+                    guard.chain_id.clone(),
+                    indexer_tip,
+                    node_tip,
+                ));
+            }
+            tokio::time::sleep(INDEXER_POLL_INTERVAL).await;
+        }
+    }
+
+    fn record_cell_query(&self, _method: &'static str) {
+        telemetry!({
+            if let Some(chain_id) = &self.chain_id {
+                ibc_telemetry::global().ckb_cell_queries(chain_id, _method);
+            }
+        });
+    }
 }
 
 impl CkbReader for RpcClient {
     fn get_blockchain_info(&self) -> Rpc<ChainInfo> {
+        if self.is_light_client() {
+            let genesis = jsonrpc!("get_genesis_block", Target::CKB, self, BlockView);
+            let tip = jsonrpc!("get_tip_header", Target::CKB, self, HeaderView);
+            return async move {
+                let (genesis, tip) = future::try_join(genesis, tip).await?;
+                light_client_chain_info(&genesis.header.hash, &tip)
+            }
+            .boxed();
+        }
         jsonrpc!("get_blockchain_info", Target::CKB, self, ChainInfo).boxed()
     }
 
     fn get_block_by_number(&self, number: BlockNumber) -> Rpc<BlockView> {
+        if self.is_light_client() {
+            // the light client keeps the genesis block only, and looks up no block by number
+            if number.value() != 0 {
+                return light_client_unsupported("get_block_by_number");
+            }
+            return jsonrpc!("get_genesis_block", Target::CKB, self, BlockView).boxed();
+        }
         jsonrpc!("get_block_by_number", Target::CKB, self, BlockView, number).boxed()
     }
 
     fn get_block(&self, hash: &H256) -> Rpc<BlockView> {
-        jsonrpc!("get_block", Target::CKB, self, BlockView, hash).boxed()
+        if let Some(block) = self.cache.blocks.get(hash) {
+            return future::ready(Ok(block)).boxed();
+        }
+        if self.is_light_client() {
+            // the light client keeps the headers of the blocks only, so the block has no
+            // transaction
+            let block_hash = hash.clone();
+            let query = jsonrpc!("get_header", Target::CKB, self, Option<HeaderView>, hash);
+            return async move {
+                let header = query.await?.ok_or_else(|| {
+                    Error::rpc_response(format!("block {block_hash:#x} is not found"))
+                })?;
+                Ok(BlockView {
+                    header,
+                    ..Default::default()
+                })
+            }
+            .boxed();
+        }
+        let blocks = self.cache.blocks.clone();
+        let block_hash = hash.clone();
+        let query = jsonrpc!("get_block", Target::CKB, self, BlockView, hash);
+        async move {
+            let block = query.await?;
+            blocks.insert(block_hash, block.clone());
+            Ok(block)
+        }
+        .boxed()
     }
 
     fn get_tip_header(&self) -> Rpc<HeaderView> {
-        jsonrpc!("get_tip_header", Target::CKB, self, HeaderView).boxed()
+        let tip = self.cache.tip.clone();
+        let query = jsonrpc!("get_tip_header", Target::CKB, self, HeaderView);
+        async move {
+            let header = query.await?;
+            tip.fetch_max(header.inner.number.value(), Ordering::Relaxed);
+            Ok(header)
+        }
+        .boxed()
     }
 
     fn get_transaction(&self, hash: &H256) -> Rpc<Option<TransactionWithStatusResponse>> {
-        jsonrpc!(
+        if let Some(tx) = self.cache.transactions.get(hash) {
+            return future::ready(Ok(Some(tx))).boxed();
+        }
+        let client = self.clone();
+        let tx_hash = hash.clone();
+        let query = jsonrpc!(
             "get_transaction",
             Target::CKB,
             self,
             Option<TransactionWithStatusResponse>,
             hash
-        )
+        );
+        async move {
+            let tx = query.await?;
+            if client.cache.confirmations.is_none() {
+                return Ok(tx);
+            }
+            // the transactions which are not committed yet may still be committed or rejected
+            let block_hash = tx
+                .as_ref()
+                .filter(|tx| tx.tx_status.status == Status::Committed)
+                .and_then(|tx| tx.tx_status.block_hash.clone());
+            if let (Some(tx), Some(block_hash)) = (&tx, block_hash) {
+                let block_number = client.get_block(&block_hash).await?.header.inner.number;
+                if client.cache.is_confirmed(block_number.value()) {
+                    client.cache.transactions.insert(tx_hash, tx.clone());
+                }
+            }
+            Ok(tx)
+        }
         .boxed()
     }
 
     fn get_live_cell(&self, out_point: &OutPoint, with_data: bool) -> Rpc<CellWithStatus> {
+        if self.is_light_client() {
+            return light_client_unsupported("get_live_cell");
+        }
+        self.record_cell_query("get_live_cell");
         jsonrpc!(
             "get_live_cell",
             Target::CKB,
@@ -149,8 +561,9 @@ impl CkbReader for RpcClient {
     ) -> Rpc<Pagination<Cell>> {
         let order = Order::Asc;
         let limit = Uint32::from(limit);
+        self.record_cell_query("get_cells");
 
-        jsonrpc!(
+        let query = jsonrpc!(
             "get_cells",
             Target::Indexer,
             self,
@@ -159,17 +572,59 @@ impl CkbReader for RpcClient {
             order,
             limit,
             cursor,
-        )
-        .boxed()
+        );
+        match self.indexer_guard.clone() {
+            Some(guard) => {
+                let client = self.clone();
+                async move {
+                    client.wait_for_indexer(&guard).await?;
+                    query.await
+                }
+                .boxed()
+            }
+            None => query.boxed(),
+        }
+    }
+
+    fn get_indexer_tip(&self) -> Rpc<Option<Tip>> {
+        if self.is_light_client() {
+            // the light client has searched the cells up to the block of the script which
+            // lags the most, whose hash it doesn't tell
+            let query = jsonrpc!("get_scripts", Target::CKB, self, Vec<ScriptStatus>);
+            return async move {
+                let scripts = query.await?;
+                let tip = scripts
+                    .iter()
+                    .map(|script| script.block_number.value())
+                    .min()
+                    .map(|block_number| Tip {
+                        block_hash: H256::default(),
+                        block_number: block_number.into(),
+                    });
+                Ok(tip)
+            }
+            .boxed();
+        }
+        jsonrpc!("get_indexer_tip", Target::Indexer, self, Option<Tip>).boxed()
     }
 
     fn get_raw_tx_pool(&self, verbose: bool) -> Rpc<RawTxPool> {
+        if self.is_light_client() {
+            return light_client_unsupported("get_raw_tx_pool");
+        }
         jsonrpc!("get_raw_tx_pool", Target::CKB, self, RawTxPool, verbose).boxed()
     }
 
     fn tx_pool_info(&self) -> Rpc<TxPoolInfo> {
+        if self.is_light_client() {
+            return light_client_unsupported("tx_pool_info");
+        }
         jsonrpc!("tx_pool_info", Target::CKB, self, TxPoolInfo).boxed()
     }
+
+    fn estimate_cycles(&self, tx: &Transaction) -> Rpc<DryRunResult> {
+        jsonrpc!("estimate_cycles", Target::CKB, self, DryRunResult, tx).boxed()
+    }
 }
 
 impl CkbWriter for RpcClient {
@@ -178,6 +633,10 @@ impl CkbWriter for RpcClient {
         tx: &Transaction,
         outputs_validator: Option<OutputsValidator>,
     ) -> Rpc<H256> {
+        // the light client takes no outputs validator
+        if self.is_light_client() {
+            return jsonrpc!("send_transaction", Target::CKB, self, H256, tx).boxed();
+        }
         jsonrpc!(
             "send_transaction",
             Target::CKB,
@@ -1,4 +1,4 @@
-use std::{fs, str::FromStr, sync::Arc};
+use std::{fs, str::FromStr, sync::Arc, time::Duration};
 
 use ckb_sdk::{
     constants::TYPE_ID_CODE_HASH,
@@ -10,21 +10,34 @@ use ckb_types::{
     core::{BlockNumber, Capacity, ScriptHashType},
     h256, packed,
     prelude::*,
+    H256,
+};
+use eth_light_client_in_ckb_verification::types::packed::{
+    Client as PackedClient, ClientInfo as PackedClientInfo, ClientTypeArgs as PackedClientTypeArgs,
+    Hash as PackedHash,
 };
 use hdpath::StandardHDPath;
 use ibc_relayer_types::{
-    clients::ics07_eth::types::{Header as EthHeader, Update as EthUpdate},
-    core::ics24_host::identifier::ChainId,
+    clients::ics07_eth::{
+        misbehaviour::Misbehaviour as EthMisbehaviour,
+        types::{Header as EthHeader, Update as EthUpdate, H256 as EthH256},
+    },
+    core::{
+        ics02_client::client_type::ClientType,
+        ics24_host::identifier::{ChainId, ClientId},
+    },
 };
 use rand::{thread_rng, Rng as _};
 use tempfile::TempDir;
-use tendermint_rpc::Url;
 use tokio::runtime::Runtime as TokioRuntime;
 
-use super::{CkbChain, HD_PATH};
+use super::{assembler::TxAssembler, rpc_client::RpcClient, CkbChain, HD_PATH};
 use crate::{
     chain::endpoint::ChainEndpoint,
-    config::{ckb::ChainConfig as CkbChainConfig, ckb::ClientTypeArgs, AddressType, ChainConfig},
+    config::{
+        ckb::ChainConfig as CkbChainConfig, ckb::ClientTypeArgs, rpc_url::RpcUrl, AddressType,
+        ChainConfig,
+    },
     keyring::{Secp256k1KeyPair, SigningKeyPair},
 };
 
@@ -100,24 +113,28 @@ fn test_create_eth_multi_client_case_2() {
     test_create_eth_multi_client(2);
 }
 
-fn test_create_eth_multi_client(case_id: usize) {
-    let tmp_dir = TempDir::new().unwrap();
-    let testdata_dir = format!("{}/case-{}", TESTDATA_DIR, case_id);
-
+fn new_chain(
+    tmp_dir: &TempDir,
+    client_type_args: ClientTypeArgs,
+    recreate_corrupted_clients: bool,
+) -> CkbChain {
     let mut chain = {
         let ckb_config = CkbChainConfig {
             id: ChainId::new("chainA".to_string(), 10),
-            ckb_rpc: Url::from_str("http://ckb_rpc").unwrap(),
-            ckb_indexer_rpc: Url::from_str("http://ckb_indexer_rpc").unwrap(),
+            ckb_rpc: RpcUrl::from_str("http://ckb_rpc").unwrap(),
+            ckb_indexer_rpc: RpcUrl::from_str("http://ckb_indexer_rpc").unwrap(),
             lightclient_contract_typeargs: h256!("0x123"),
             lightclient_lock_typeargs: h256!("0x123"),
-            client_type_args: ClientTypeArgs {
-                type_id: None,
-                cells_count: 3,
-            },
+            client_type_args,
             minimal_updates_count: 1,
             key_name: "ckb-chain-test".to_string(),
             data_dir: tmp_dir.path().to_path_buf(),
+            broadcast_rpcs: vec![],
+            header_updater: None,
+            tx_poll_interval: Duration::from_secs(3),
+            tx_confirmations: 0,
+            tx_commit_timeout: Duration::from_secs(60),
+            recreate_corrupted_clients,
         };
         let config = ChainConfig::Ckb(ckb_config);
         let rt = Arc::new(TokioRuntime::new().unwrap());
@@ -186,6 +203,20 @@ fn test_create_eth_multi_client(case_id: usize) {
         rpc_client.add_cell(&key, cell);
     }
 
+    chain
+}
+
+fn test_create_eth_multi_client(case_id: usize) {
+    let tmp_dir = TempDir::new().unwrap();
+    let testdata_dir = format!("{}/case-{}", TESTDATA_DIR, case_id);
+
+    let client_type_args = ClientTypeArgs {
+        type_id: None,
+        cells_count: 3,
+    };
+    let mut chain = new_chain(&tmp_dir, client_type_args, false);
+    let rpc_client = Arc::clone(&chain.rpc_client);
+
     let updates_part_1 = load_updates_from_file(&testdata_dir, "headers_part_1.json");
 
     let result = chain.create_eth_multi_client(updates_part_1);
@@ -195,6 +226,269 @@ fn test_create_eth_multi_client(case_id: usize) {
     assert_eq!(txs_len, 1);
 }
 
+fn multi_client_type_args(cells_count: u8) -> PackedClientTypeArgs {
+    let type_id = PackedHash::from_slice(h256!("0x456").as_bytes()).unwrap();
+    PackedClientTypeArgs::new_builder()
+        .cells_count(cells_count.into())
+        .type_id(type_id)
+        .build()
+}
+
+fn packed_client(id: u8) -> Vec<u8> {
+    PackedClient::new_builder()
+        .id(id.into())
+        .build()
+        .as_slice()
+        .to_vec()
+}
+
+fn packed_client_info(last_id: u8) -> Vec<u8> {
+    PackedClientInfo::new_builder()
+        .last_id(last_id.into())
+        .minimal_updates_count(1.into())
+        .build()
+        .as_slice()
+        .to_vec()
+}
+
+fn add_multi_client_cells(
+    rpc_client: &RpcClient,
+    contract_typeid_args: &H256,
+    client_type_args: &PackedClientTypeArgs,
+    cells_data: Vec<Vec<u8>>,
+) {
+    let contract = packed::Script::new_builder()
+        .code_hash(TYPE_ID_CODE_HASH.0.pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(contract_typeid_args.as_bytes().to_vec().pack())
+        .build();
+    let client_type = packed::Script::new_builder()
+        .code_hash(contract.calc_script_hash())
+        .hash_type(ScriptHashType::Type.into())
+        .args(client_type_args.as_slice().to_vec().pack())
+        .build();
+    let key: SearchKey = CellQueryOptions::new(client_type.clone(), PrimaryScriptType::Type).into();
+    for data in cells_data {
+        let output = packed::CellOutput::new_builder()
+            .type_(Some(client_type.clone()).pack())
+            .build_exact_capacity(Capacity::bytes(data.len()).unwrap())
+            .unwrap();
+        rpc_client.add_cell(&key, random_cell(1003, output, data));
+    }
+}
+
+#[test]
+fn test_fetch_corrupted_multi_client_cells() {
+    let contract_typeid_args = h256!("0x123");
+    let client_type_args = multi_client_type_args(3);
+    let rt = TokioRuntime::new().unwrap();
+    let fetch_update_cells = |cells_data: Vec<Vec<u8>>| {
+        let rpc_client = RpcClient::new(
+            &RpcUrl::from_str("http://ckb_rpc").unwrap(),
+            &RpcUrl::from_str("http://ckb_indexer_rpc").unwrap(),
+        );
+        add_multi_client_cells(
+            &rpc_client,
+            &contract_typeid_args,
+            &client_type_args,
+            cells_data,
+        );
+        rt.block_on(rpc_client.fetch_update_cells(&contract_typeid_args, &client_type_args))
+    };
+
+    let update_cells = fetch_update_cells(vec![]).unwrap();
+    assert!(update_cells.is_none());
+
+    let update_cells = fetch_update_cells(vec![
+        packed_client(0),
+        packed_client(1),
+        packed_client_info(0),
+    ])
+    .unwrap()
+    .unwrap();
+    assert_eq!(update_cells.latest.output_data, packed_client(0));
+    assert_eq!(update_cells.oldest.output_data, packed_client(1));
+
+    let corrupted_layouts = vec![
+        // Cells count mismatch
+        vec![packed_client(0), packed_client_info(0)],
+        // Duplicate client info
+        vec![
+            packed_client(0),
+            packed_client_info(0),
+            packed_client_info(1),
+        ],
+        // Invalid cell data
+        vec![packed_client(0), packed_client_info(0), vec![1, 2, 3]],
+        // Missing client info
+        vec![packed_client(0), packed_client(1), packed_client(2)],
+        // Missing latest client
+        vec![packed_client(1), packed_client(1), packed_client_info(0)],
+    ];
+    for cells_data in corrupted_layouts {
+        let err = fetch_update_cells(cells_data).err().unwrap();
+        assert!(err.is_ckb_multi_client_corrupted(), "{err}");
+    }
+}
+
+#[test]
+fn test_recreate_corrupted_multi_client() {
+    let tmp_dir = TempDir::new().unwrap();
+    let testdata_dir = format!("{}/case-1", TESTDATA_DIR);
+
+    let client_type_args = ClientTypeArgs {
+        type_id: Some(h256!("0x456")),
+        cells_count: 3,
+    };
+    let mut chain = new_chain(&tmp_dir, client_type_args, false);
+    let rpc_client = Arc::clone(&chain.rpc_client);
+    add_multi_client_cells(
+        &rpc_client,
+        &chain.config.lightclient_contract_typeargs,
+        &multi_client_type_args(3),
+        vec![packed_client_info(0)],
+    );
+
+    let updates = load_updates_from_file(&testdata_dir, "headers_part_1.json");
+    let err = chain
+        .create_eth_multi_client(updates.clone())
+        .err()
+        .unwrap();
+    assert!(err.is_ckb_multi_client_corrupted());
+    assert_eq!(rpc_client.get_transactions_len(), 0);
+
+    chain.config.recreate_corrupted_clients = true;
+    let result = chain.create_eth_multi_client(updates);
+    assert!(result.is_ok());
+    assert_eq!(rpc_client.get_transactions_len(), 1);
+    assert_ne!(chain.config.client_type_args.type_id, Some(h256!("0x456")));
+}
+
+#[test]
+fn test_query_light_client_cells() {
+    let tmp_dir = TempDir::new().unwrap();
+    let client_type_args = ClientTypeArgs {
+        type_id: Some(h256!("0x456")),
+        cells_count: 3,
+    };
+    let chain = new_chain(&tmp_dir, client_type_args, false);
+    assert!(chain.query_light_client_cells().is_err());
+
+    add_multi_client_cells(
+        &chain.rpc_client,
+        &chain.config.lightclient_contract_typeargs,
+        &multi_client_type_args(3),
+        vec![packed_client(0), packed_client(1), packed_client_info(0)],
+    );
+    let client_cells = chain.query_light_client_cells().unwrap();
+    assert_eq!(client_cells.type_id, h256!("0x456"));
+    assert_eq!(client_cells.cells_count, 3);
+    assert_eq!(client_cells.last_id, 0);
+    assert_eq!(client_cells.minimal_updates_count, 1);
+    assert!(!client_cells.frozen);
+    assert_eq!(client_cells.latest.id, 0);
+    assert_eq!(client_cells.oldest.id, 1);
+}
+
+#[test]
+fn test_fetch_obsolete_client_cells() {
+    let lock_typeid_args = h256!("0x111");
+    let contract_typeid_args = h256!("0x123");
+    let typeid_script = |args: &H256| {
+        packed::Script::new_builder()
+            .code_hash(TYPE_ID_CODE_HASH.0.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(args.as_bytes().to_vec().pack())
+            .build()
+    };
+    let lock_script = packed::Script::new_builder()
+        .code_hash(typeid_script(&lock_typeid_args).calc_script_hash())
+        .hash_type(ScriptHashType::Type.into())
+        .build();
+    let client_type = |code_hash: packed::Byte32, type_id: H256| {
+        let args = PackedClientTypeArgs::new_builder()
+            .cells_count(3u8.into())
+            .type_id(PackedHash::from_slice(type_id.as_bytes()).unwrap())
+            .build();
+        packed::Script::new_builder()
+            .code_hash(code_hash)
+            .hash_type(ScriptHashType::Type.into())
+            .args(args.as_slice().to_vec().pack())
+            .build()
+    };
+    let contract_hash = typeid_script(&contract_typeid_args).calc_script_hash();
+
+    let rpc_client = RpcClient::new(
+        &RpcUrl::from_str("http://ckb_rpc").unwrap(),
+        &RpcUrl::from_str("http://ckb_indexer_rpc").unwrap(),
+    );
+    let key: SearchKey = CellQueryOptions::new(lock_script.clone(), PrimaryScriptType::Lock).into();
+    let cell_types = vec![
+        // Cells of the current multi-client set
+        client_type(contract_hash.clone(), h256!("0x456")),
+        client_type(contract_hash.clone(), h256!("0x456")),
+        // Cells of an abandoned multi-client set
+        client_type(contract_hash.clone(), h256!("0x789")),
+        client_type(contract_hash.clone(), h256!("0x789")),
+        // Cell of another contract
+        client_type(random_hash(), h256!("0x789")),
+    ];
+    for client_type in cell_types {
+        let output = packed::CellOutput::new_builder()
+            .lock(lock_script.clone())
+            .type_(Some(client_type).pack())
+            .build_exact_capacity(Capacity::zero())
+            .unwrap();
+        rpc_client.add_cell(&key, random_cell(1003, output, vec![]));
+    }
+
+    let rt = TokioRuntime::new().unwrap();
+    let obsolete_cells = rt
+        .block_on(rpc_client.fetch_obsolete_client_cells(
+            &lock_typeid_args,
+            &contract_typeid_args,
+            &h256!("0x456"),
+        ))
+        .unwrap();
+    assert_eq!(obsolete_cells.len(), 2);
+    assert!(obsolete_cells
+        .iter()
+        .all(|(_, type_id)| type_id == &h256!("0x789")));
+}
+
+#[test]
+fn test_freeze_eth_multi_client() {
+    let tmp_dir = TempDir::new().unwrap();
+    let client_type_args = ClientTypeArgs {
+        type_id: Some(h256!("0x456")),
+        cells_count: 3,
+    };
+    let mut chain = new_chain(&tmp_dir, client_type_args, false);
+    let rpc_client = Arc::clone(&chain.rpc_client);
+    add_multi_client_cells(
+        &rpc_client,
+        &chain.config.lightclient_contract_typeargs,
+        &multi_client_type_args(3),
+        vec![packed_client(0), packed_client(1), packed_client_info(0)],
+    );
+
+    let misbehaviour = EthMisbehaviour {
+        client_id: ClientId::new(ClientType::Eth, 0).unwrap(),
+        conflicting_root: EthH256::repeat_byte(1),
+        header: Default::default(),
+    };
+    chain.freeze_eth_multi_client(misbehaviour).unwrap();
+    assert_eq!(rpc_client.get_transactions_len(), 1);
+
+    // All the cells of the set are moved under the frozen lock, along with their data
+    let tx = rpc_client.get_transaction_by_index(0).unwrap();
+    for (output, data) in tx.outputs.iter().zip(&tx.outputs_data).take(3) {
+        assert_eq!(output.lock.args.as_bytes(), &[1u8; 32]);
+        assert!(output.type_.is_some());
+        assert!(!data.is_empty());
+    }
+}
+
 // TODO: add update_eth_multi_client test
 
 // fn test_update_eth_client(case_id: usize) {
@@ -0,0 +1,111 @@
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::telemetry;
+
+/// Limits of the requests sent to the node and the indexer, the requests beyond them
+/// being queued, as the public RPC providers reject the clients sending too many
+#[derive(Clone)]
+pub struct RequestThrottle {
+    /// Time between the starts of two requests
+    interval: Option<Duration>,
+    /// Time the next request may start at
+    next_start: Arc<Mutex<Instant>>,
+    /// Permits of the requests in flight
+    in_flight: Option<Arc<Semaphore>>,
+}
+
+impl RequestThrottle {
+    /// Throttle starting up to `max_requests_per_second` requests per second and keeping
+    /// up to `max_concurrent_requests` of them in flight, `None` if no limit is given
+    pub fn new(
+        max_requests_per_second: Option<NonZeroU32>,
+        max_concurrent_requests: Option<NonZeroUsize>,
+    ) -> Option<Self> {
+        if max_requests_per_second.is_none() && max_concurrent_requests.is_none() {
+            return None;
+        }
+        Some(Self {
+            interval: max_requests_per_second.map(|max| Duration::from_secs(1) / max.get()),
+            next_start: Arc::new(Mutex::new(Instant::now())),
+            in_flight: max_concurrent_requests.map(|max| Arc::new(Semaphore::new(max.get()))),
+        })
+    }
+
+    /// Wait for the limits to let a request of `method` start, returning the permit to
+    /// hold while it is in flight
+    pub async fn acquire(
+        &self,
+        _chain_id: Option<&ChainId>,
+        _method: &'static str,
+    ) -> Option<OwnedSemaphorePermit> {
+        let mut throttled = false;
+        let permit = match &self.in_flight {
+            Some(semaphore) => match semaphore.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    throttled = true;
+                    semaphore.clone().acquire_owned().await.ok()
+                }
+            },
+            None => None,
+        };
+        if let Some(interval) = self.interval {
+            let start = {
+                let mut next_start = self.next_start.lock().unwrap();
+                let start = (*next_start).max(Instant::now());
+                *next_start = start + interval;
+                start
+            };
+            let wait = start.saturating_duration_since(Instant::now());
+            if !wait.is_zero() {
+                throttled = true;
+                tokio::time::sleep(wait).await;
+            }
+        }
+        if throttled {
+            telemetry!({
+                if let Some(chain_id) = _chain_id {
+                    ibc_telemetry::global().ckb_rpc_throttled(chain_id, _method);
+                }
+            });
+        }
+        permit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::{NonZeroU32, NonZeroUsize};
+    use std::time::{Duration, Instant};
+
+    use super::RequestThrottle;
+
+    #[test]
+    fn test_request_limits_queue_requests() {
+        let throttle = RequestThrottle::new(NonZeroU32::new(20), NonZeroUsize::new(1)).unwrap();
+        let in_flight = throttle.in_flight.clone().unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        // the requests start 50ms apart
+        let started_at = Instant::now();
+        for _ in 0..3 {
+            rt.block_on(throttle.acquire(None, "get_cells"));
+        }
+        assert!(started_at.elapsed() >= Duration::from_millis(100));
+
+        let permit = rt.block_on(throttle.acquire(None, "get_cells"));
+        assert_eq!(in_flight.available_permits(), 0);
+        drop(permit);
+        assert_eq!(in_flight.available_permits(), 1);
+    }
+
+    #[test]
+    fn test_no_limit_no_throttle() {
+        assert!(RequestThrottle::new(None, None).is_none());
+    }
+}
@@ -1,5 +1,6 @@
 use ckb_hash::BLAKE2B_LEN;
-use ckb_jsonrpc_types::Status;
+use ckb_jsonrpc_types::{Script, Status, Transaction};
+use ckb_sdk::NetworkType;
 use ckb_types::{packed::CellInput, H256};
 use eth2_types::EthSpec;
 use eth_light_client_in_ckb_verification::mmr::{self, HeaderWithCache};
@@ -20,7 +21,7 @@ use tendermint_light_client::errors::Error as LightClientError;
 use tracing::debug;
 
 use crate::chain::ckb::communication::CkbReader;
-use crate::error::Error;
+use crate::error::{Error, ErrorDetail};
 
 use super::rpc_client::RpcClient;
 
@@ -305,15 +306,16 @@ pub async fn wait_ckb_transaction_committed(
     rpc: &Arc<RpcClient>,
     hash: H256,
     interval: Duration,
-    confirms: u8,
+    confirms: u64,
     time_limit: Duration,
 ) -> Result<(), Error> {
     let mut block_number = 0u64;
     let mut time_used = Duration::from_secs(0);
     loop {
         if time_used > time_limit {
-            return Err(Error::send_tx(
-                "timeout for waiting ckb tx committed".to_string(),
+            return Err(Error::ckb_tx_commit_timeout(
+                format!("{hash:#x}"),
+                time_limit,
             ));
         }
 
@@ -324,10 +326,10 @@ pub async fn wait_ckb_transaction_committed(
             .await?
             .expect("wait transaction response");
         if tx.tx_status.status == Status::Rejected {
-            return Err(Error::send_tx(format!(
-                "transaction {hash:#x} had been rejected, reason: {}",
-                tx.tx_status.reason.unwrap_or_else(|| "unknown".to_string())
-            )));
+            return Err(Error::ckb_tx_rejection(
+                format!("{hash:#x}"),
+                tx.tx_status.reason.unwrap_or_else(|| "unknown".to_string()),
+            ));
         }
         if tx.tx_status.status != Status::Committed {
             continue;
@@ -340,7 +342,7 @@ pub async fn wait_ckb_transaction_committed(
         } else {
             let tip = rpc.get_tip_header().await?;
             let tip_number: u64 = tip.inner.number.into();
-            if tip_number >= block_number + confirms as u64 {
+            if tip_number >= block_number + confirms {
                 break;
             }
         }
@@ -348,6 +350,20 @@ pub async fn wait_ckb_transaction_committed(
     Ok(())
 }
 
+/// Network of the CKB node, told by the name of its chain
+pub async fn fetch_network(rpc: &impl CkbReader) -> Result<NetworkType, Error> {
+    let chain_info = rpc
+        .get_blockchain_info()
+        .await
+        .map_err(|e| Error::rpc_response(e.to_string()))?;
+    let network = match chain_info.chain.as_str() {
+        "ckb" => NetworkType::Mainnet,
+        "ckb_testnet" => NetworkType::Testnet,
+        _ => NetworkType::Dev,
+    };
+    Ok(network)
+}
+
 // Calculate type id for multi-client creation.
 pub fn calculate_type_id(first_input: &CellInput, cell_count: usize) -> [u8; BLAKE2B_LEN] {
     let mut blake2b = ckb_hash::new_blake2b();
@@ -397,6 +413,77 @@ pub async fn collect_ckb_tx_pool_info_on_duplicate_tx(
     }
 }
 
+/// Locate the script whose failure rejected `tx` and estimate the cycles of `tx`, to be
+/// reported along with the failure.
+///
+/// The scripts of `tx` are run again by `estimate_cycles`, which also tells the script
+/// failure behind a rejection whose reason is not detailed, such as the reason attached
+/// to a rejected transaction status. Other errors are returned as is.
+pub async fn diagnose_ckb_script_failure(
+    rpc: &impl CkbReader,
+    tx: &Transaction,
+    error: Error,
+) -> Error {
+    let tx_hash = match error.detail() {
+        ErrorDetail::CkbTxScriptFailure(e) => e.tx_hash.clone(),
+        ErrorDetail::CkbTxRejected(e) => e.tx_hash.clone(),
+        _ => return error,
+    };
+    let (error, cycles) = match rpc.estimate_cycles(tx).await {
+        Ok(result) => (error, Some(result.cycles.value())),
+        Err(e) if !error.is_ckb_script_failure() => {
+            let failure = Error::ckb_send_tx_failure(tx_hash, e);
+            if failure.is_ckb_script_failure() {
+                (failure, None)
+            } else {
+                (error, None)
+            }
+        }
+        Err(_) => (error, None),
+    };
+    match error.detail() {
+        ErrorDetail::CkbTxScriptFailure(e) => {
+            let contract = script_of_group(rpc, tx, &e.script)
+                .await
+                .map(|script| format!("{:#x} ({:?})", script.code_hash, script.hash_type));
+            Error::ckb_tx_script_failure(
+                e.tx_hash.clone(),
+                e.exit_code,
+                e.script.clone(),
+                contract,
+                cycles,
+                e.reason.clone(),
+            )
+        }
+        _ => error,
+    }
+}
+
+/// Script of `tx` run by a script group reported by the node, e.g. "Inputs[0].Lock",
+/// the cells of the inputs being looked up among the live ones
+async fn script_of_group(rpc: &impl CkbReader, tx: &Transaction, group: &str) -> Option<Script> {
+    let (cells, rest) = group.split_once('[')?;
+    let (index, kind) = rest.split_once("].")?;
+    let index: usize = index.parse().ok()?;
+    let output = match cells {
+        "Inputs" => {
+            let input = tx.inputs.get(index)?;
+            let cell = rpc
+                .get_live_cell(&input.previous_output, false)
+                .await
+                .ok()?;
+            cell.cell?.output
+        }
+        "Outputs" => tx.outputs.get(index)?.clone(),
+        _ => return None,
+    };
+    match kind {
+        "Lock" => Some(output.lock),
+        "Type" => output.type_,
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::Path;
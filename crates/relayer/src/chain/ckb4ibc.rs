@@ -1,40 +1,46 @@
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::str::FromStr;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::Instant;
 
 use crate::account::Balance;
+use crate::audit::ConfigDrift;
 use crate::chain::ckb::prelude::{CellSearcher, CkbReader, CkbWriter, TxCompleter};
 use crate::chain::ckb4ibc::extractor::extract_channel_end_from_tx;
-use crate::chain::ckb4ibc::utils::{get_connection_idx, get_connection_search_key};
+use crate::chain::ckb4ibc::utils::get_connection_idx;
 use crate::chain::endpoint::ChainEndpoint;
 use crate::client_state::{AnyClientState, IdentifiedAnyClientState};
-use crate::config::ckb4ibc::ChainConfig as Ckb4IbcChainConfig;
-use crate::config::ChainConfig;
+use crate::config::ckb4ibc::{ChainConfig as Ckb4IbcChainConfig, MonitorStart};
+use crate::config::{ChainConfig, SignerKind};
 use crate::connection::ConnectionMsgType;
 use crate::consensus_state::AnyConsensusState;
 use crate::denom::DenomTrace;
 use crate::error::Error;
 use crate::event::monitor::TxMonitorCmd;
 use crate::event::IbcEventWithHeight;
-use crate::keyring::{KeyRing, Secp256k1KeyPair};
+use crate::keyring::{
+    CkbTxSigner, DigestSigner, KeyRing, LedgerSigner, RemoteSigner, Secp256k1KeyPair, CKB_HD_PATH,
+};
 use crate::misbehaviour::MisbehaviourEvidence;
+use crate::progress::{RelayProgress, RelayStatus, SharedRelayProgress};
+use crate::telemetry;
+use crate::util::panic::spawn_chain_thread;
 
-use ckb_ics_axon::handler::{IbcChannel, IbcConnections, IbcPacket, PacketStatus};
+use ckb_ics_axon::handler::{IbcConnections, IbcPacket, PacketStatus};
 use ckb_ics_axon::message::Envelope;
+use ckb_ics_axon::object::Ordering as CkbOrdering;
 use ckb_ics_axon::{ChannelArgs, PacketArgs};
-use ckb_jsonrpc_types::{JsonBytes, Status, TransactionView};
+use ckb_jsonrpc_types::{Status, TransactionView};
 use ckb_sdk::constants::TYPE_ID_CODE_HASH;
-use ckb_sdk::rpc::ckb_light_client::{ScriptType, SearchKey};
-use ckb_sdk::traits::SecpCkbRawKeySigner;
+use ckb_sdk::rpc::ckb_light_client::{ScriptStatus, ScriptType, SearchKey};
 use ckb_sdk::unlock::{ScriptSigner, SecpSighashScriptSigner};
 use ckb_sdk::{Address, AddressPayload, NetworkType, ScriptGroup, ScriptGroupType};
-use ckb_types::core::ScriptHashType;
 use ckb_types::core::TransactionView as CoreTransactionView;
 use ckb_types::molecule::prelude::Entity;
 use ckb_types::packed::{CellInput, OutPoint, Script, WitnessArgs};
 use ckb_types::prelude::{Builder, Pack, Unpack};
-use futures::TryFutureExt;
+use ckb_types::H256;
+use ibc_proto::google::protobuf::Any;
 use ibc_proto::ibc::apps::fee::v1::{
     QueryIncentivizedPacketRequest, QueryIncentivizedPacketResponse,
 };
@@ -57,34 +63,53 @@ use ibc_relayer_types::core::ics24_host::identifier::{
 };
 use ibc_relayer_types::proofs::Proofs;
 use ibc_relayer_types::signer::Signer;
-use ibc_relayer_types::timestamp::Timestamp;
 use ibc_relayer_types::Height;
 use semver::Version;
 use std::sync::RwLock;
 use tendermint::Time;
 use tendermint_rpc::endpoint::broadcast::tx_sync::Response;
 use tokio::runtime::Runtime;
-
-use self::extractor::{extract_connections_from_tx, extract_ibc_packet_from_tx};
-use self::message::{convert_msg_to_ckb_tx, CkbTxInfo, Converter, MsgToTxConverter};
+use tracing::{debug, error, error_span, field, info, instrument, warn, Span};
+
+use self::author::KnownAuthors;
+use self::cells::{IbcCell, IbcCellKind};
+use self::commitment::{is_channel_lock, is_connections_lock, is_packet_lock, IbcCellProof};
+use self::denom::{udt_amount, DenomRegistry, CKB_DENOM};
+use self::deployment::deployment_block;
+use self::extractor::{
+    channel_end_output_idx, extract_envelope_bytes, extract_ibc_connections_from_tx,
+    ibc_connections_output_idx,
+};
+use self::footprint::StorageFootprint;
+use self::history::IbcStateAt;
+use self::message::{
+    consumed_cells, convert_msg_to_ckb_tx, processed_packet_key, redundant_update_event,
+    relayed_packet, tendermint_client_update, CkbTxInfo, Converter, MsgToTxConverter,
+};
 use self::monitor::Ckb4IbcEventMonitor;
+use self::packet_size::OversizedPacket;
+use self::scheduler::{CellKey, ChangeCell, ConflictGraph, UnconfirmedTxs};
+use self::state_cache::{CachedKeys, IbcStateCache};
+use self::tendermint::{is_tendermint_client, TendermintClient, TendermintClientCell};
 use self::utils::{
-    convert_port_id_to_array, get_channel_idx, get_dummy_merkle_proof, get_encoded_object,
-    get_search_key,
+    convert_port_id_to_array, decode_transaction, get_channel_idx, get_encoded_object,
+    get_ibc_cell_merkle_proof, get_light_client_scripts, paginate,
 };
 
+use super::ckb::broadcast::TxBroadcaster;
+use super::ckb::pending_tx::{PendingTxConfig, PendingTxTracker};
 use super::ckb::rpc_client::RpcClient;
-use super::ckb::utils::wait_ckb_transaction_committed;
+use super::ckb::utils::fetch_network;
 use super::client::ClientSettings;
 use super::cosmos::encode::key_pair_to_signer;
-use super::endpoint::{ChainStatus, HealthCheck};
+use super::endpoint::{ChainCapabilities, ChainStatus, HealthCheck};
 use super::handle::Subscription;
 use super::requests::{
     CrossChainQueryRequest, IncludeProof, QueryChannelClientStateRequest, QueryChannelRequest,
     QueryChannelsRequest, QueryClientConnectionsRequest, QueryClientStateRequest,
     QueryClientStatesRequest, QueryConnectionChannelsRequest, QueryConnectionRequest,
     QueryConnectionsRequest, QueryConsensusStateHeightsRequest, QueryConsensusStateRequest,
-    QueryHostConsensusStateRequest, QueryNextSequenceReceiveRequest,
+    QueryHeight, QueryHostConsensusStateRequest, QueryNextSequenceReceiveRequest,
     QueryPacketAcknowledgementRequest, QueryPacketAcknowledgementsRequest,
     QueryPacketCommitmentRequest, QueryPacketCommitmentsRequest, QueryPacketEventDataRequest,
     QueryPacketReceiptRequest, QueryTxRequest, QueryUnreceivedAcksRequest,
@@ -94,99 +119,334 @@ use super::requests::{
 use super::tracking::TrackedMsgs;
 use tokio::runtime::Runtime as TokioRuntime;
 
+mod author;
 mod cache_set;
+pub mod cells;
+mod chain_async;
+mod commitment;
+mod committed_txs;
+pub mod compatibility;
+pub mod denom;
+mod deployment;
 pub mod extractor;
+pub mod footprint;
+pub mod history;
 pub mod message;
 mod monitor;
+pub mod packet_size;
+pub mod port_bindings;
+mod scan_offset;
+mod scanned_blocks;
+mod scheduler;
+pub mod state_cache;
+pub mod tendermint;
+mod tip_subscription;
 pub mod utils;
 
+pub use self::chain_async::Ckb4IbcChainAsync;
 pub use utils::keccak256;
 
+/// Fee rate of the transactions before applying the multiplier of their messages,
+/// in shannons per byte
+const BASE_FEE_RATE: u64 = 3000;
+
+/// Number of times the messages are assembled again after their cell inputs
+/// are consumed by another relayer
+const MAX_DEAD_CELL_RETRIES: usize = 3;
+
+/// Message whose transaction consumed dead cells, along with its RLP encoded envelope
+/// and the rejection error
+type DeadMsg = (Any, Vec<u8>, Error);
+
 pub struct Ckb4IbcChain {
     rt: Arc<TokioRuntime>,
     rpc_client: Arc<RpcClient>,
+    chain_async: Ckb4IbcChainAsync<RpcClient>,
+    broadcaster: TxBroadcaster,
     config: Ckb4IbcChainConfig,
     keybase: KeyRing<Secp256k1KeyPair>,
-    cached_network: RwLock<Option<NetworkType>>,
+    cached_network: RwLock<Option<(NetworkType, Instant)>>,
+    genesis_hash: H256,
+    /// Version of the IBC contracts of the chain
+    contracts_version: Version,
 
     tx_monitor_cmd: Option<TxMonitorCmd>,
 
-    client_outpoint: OutPoint,
+    client_outpoints: BTreeMap<H256, OutPoint>,
     connection_outpoint: OutPoint,
     channel_outpoint: OutPoint,
     packet_outpoint: OutPoint,
+    contract_outpoints_fetched_at: Instant,
 
-    channel_input_data: RefCell<HashMap<(ChannelId, PortId), CellInput>>,
-    channel_cache: RefCell<HashMap<ChannelId, IbcChannel>>,
-    connection_cache: RefCell<Option<(IbcConnections, CellInput)>>,
-    packet_input_data: RefCell<HashMap<(ChannelId, PortId, Sequence), CellInput>>,
+    ibc_state_cache: Arc<IbcStateCache>,
 
     cached_tx_assembler_address: RwLock<Option<Address>>,
+
+    unconfirmed_txs: RwLock<UnconfirmedTxs>,
+
+    /// Progress of the relaying on the chain, shared with the event monitor
+    progress: SharedRelayProgress,
 }
 
 impl Ckb4IbcChain {
+    /// Async queries of the chain, which the queries of the endpoint block on
+    pub fn chain_async(&self) -> &Ckb4IbcChainAsync<RpcClient> {
+        &self.chain_async
+    }
+
     pub fn network(&self) -> Result<NetworkType, Error> {
-        let cached_network_opt: Option<NetworkType> =
-            *self.cached_network.read().map_err(Error::other)?;
-        let network = if let Some(network) = cached_network_opt {
+        let cached_network_opt = *self.cached_network.read().map_err(Error::other)?;
+        let network = if let Some((network, _)) = cached_network_opt
+            .filter(|(_, fetched_at)| fetched_at.elapsed() < self.config.cache_ttl)
+        {
             network
         } else {
-            let network = {
-                let chain_info = self
-                    .rt
-                    .block_on(self.rpc_client.get_blockchain_info())
-                    .map_err(|e| Error::rpc_response(e.to_string()))?;
-                if chain_info.chain == "ckb" {
-                    NetworkType::Mainnet
-                } else if chain_info.chain == "ckb_testnet" {
-                    NetworkType::Testnet
-                } else {
-                    NetworkType::Dev
-                }
-            };
-            *self.cached_network.write().map_err(Error::other)? = Some(network);
+            let network = self.rt.block_on(fetch_network(self.rpc_client.as_ref()))?;
+            *self.cached_network.write().map_err(Error::other)? = Some((network, Instant::now()));
             network
         };
         Ok(network)
     }
 
     pub fn tx_assembler_address(&self) -> Result<Address, Error> {
+        let network = self.network()?;
         let cached_address = self
             .cached_tx_assembler_address
             .read()
             .map_err(Error::other)?
             .clone();
-        let address = if let Some(address) = cached_address {
-            address
-        } else {
-            let network = self.network()?;
-            let key: Secp256k1KeyPair = self
-                .keybase
-                .get_key(&self.config.key_name)
-                .map_err(Error::key_base)?;
-            let address_payload = AddressPayload::from_pubkey(&key.public_key);
-            let address = Address::new(network, address_payload, true);
-            *self
-                .cached_tx_assembler_address
-                .write()
-                .map_err(Error::other)? = Some(address.clone());
-            address
+        let address =
+            if let Some(address) = cached_address.filter(|address| address.network() == network) {
+                address
+            } else {
+                let public_key = self.tx_signer()?.public_key().map_err(Error::key_base)?;
+                let address_payload = AddressPayload::from_pubkey(&public_key);
+                let address = Address::new(network, address_payload, true);
+                self.register_light_client_account(&address)?;
+                *self
+                    .cached_tx_assembler_address
+                    .write()
+                    .map_err(Error::other)? = Some(address.clone());
+                address
+            };
+        Ok(address)
+    }
+
+    /// Register the lock of `address` with the ckb-light-client node, if the chain is
+    /// reached through one, so that it keeps the cells of the account
+    fn register_light_client_account(&self, address: &Address) -> Result<(), Error> {
+        let lock_script: Script = address.payload().into();
+        let script = ScriptStatus {
+            script: lock_script.into(),
+            script_type: ScriptType::Lock,
+            block_number: self.config.light_client_start_block.into(),
         };
+        self.rt
+            .block_on(self.rpc_client.register_light_client_scripts(vec![script]))
+    }
+
+    /// Address of the account paying the fees of the transactions and receiving their
+    /// change, which is the relayer account unless `fee_payer_key_name` is configured
+    pub fn fee_payer_address(&self) -> Result<Address, Error> {
+        if self.config.fee_payer_key_name.is_none() {
+            return self.tx_assembler_address();
+        }
+        let public_key = self
+            .fee_payer_signer()?
+            .public_key()
+            .map_err(Error::key_base)?;
+        let address_payload = AddressPayload::from_pubkey(&public_key);
+        let address = Address::new(self.network()?, address_payload, true);
+        self.register_light_client_account(&address)?;
         Ok(address)
     }
 
+    /// Balances of the account paying the fees: the capacity of its plain cells and the
+    /// amounts held by its cells of the UDTs registered in `udt_denoms`
+    fn query_account_balances(&self) -> Result<Vec<Balance>, Error> {
+        // a chain which is only monitored pays no fees, so its fee payer is never loaded
+        let address = match &self.config.fee_payer_key_name {
+            Some(_) if self.config.submit_txs => self.fee_payer_address()?,
+            _ => self.account_address()?,
+        };
+        let lock_script: Script = address.payload().into();
+        let search_key = SearchKey {
+            script: lock_script.into(),
+            script_type: ScriptType::Lock,
+            filter: None,
+            with_data: Some(true),
+            group_by_transaction: None,
+        };
+        let resp = self.rpc_client.fetch_live_cells(search_key, u32::MAX, None);
+        let cells = self.rt.block_on(resp)?;
+
+        let registry = DenomRegistry::new(&self.config.udt_denoms);
+        let mut capacity = 0u64;
+        let mut udt_amounts: BTreeMap<String, u128> = BTreeMap::new();
+        for cell in cells.objects {
+            let Some(type_script) = cell.output.type_ else {
+                capacity += cell.output.capacity.value();
+                continue;
+            };
+            let type_hash: H256 = Script::from(type_script).calc_script_hash().unpack();
+            if let Some(denom) = registry.denom_of(&type_hash) {
+                let amount = cell
+                    .output_data
+                    .as_ref()
+                    .and_then(|data| udt_amount(data.as_bytes()))
+                    .unwrap_or_default();
+                let total = udt_amounts.entry(denom).or_default();
+                *total = total.saturating_add(amount);
+            }
+        }
+
+        let mut balances = vec![Balance {
+            amount: capacity.to_string(),
+            denom: CKB_DENOM.to_owned(),
+        }];
+        balances.extend(udt_amounts.into_iter().map(|(denom, amount)| Balance {
+            amount: amount.to_string(),
+            denom,
+        }));
+        Ok(balances)
+    }
+
+    /// Address of the relayer account for queries, which doesn't load the signing key
+    /// if it is configured.
+    pub fn account_address(&self) -> Result<Address, Error> {
+        match &self.config.account_address {
+            Some(address) => Address::from_str(address).map_err(Error::other),
+            None => self.tx_assembler_address(),
+        }
+    }
+
+    /// Load the signing key, which is only allowed if the relayer submits transactions
+    /// to the chain. Callers are expected to drop it as soon as they are done signing.
+    fn signing_key(&self) -> Result<Secp256k1KeyPair, Error> {
+        if !self.config.submit_txs {
+            return Err(Error::submission_disabled(self.config.id.clone()));
+        }
+        self.keybase
+            .get_key(&self.config.key_name)
+            .map_err(Error::key_base)
+    }
+
+    /// Signer of the transactions of the relayer account, either its key in the keyring,
+    /// the Ledger device or the remote service holding it, depending on the `signer` option
+    fn tx_signer(&self) -> Result<Box<dyn DigestSigner>, Error> {
+        if !self.config.submit_txs {
+            return Err(Error::submission_disabled(self.config.id.clone()));
+        }
+        let signer: Box<dyn DigestSigner> = match self.config.signer {
+            SignerKind::Keyring => Box::new(self.signing_key()?),
+            SignerKind::Ledger => {
+                Box::new(LedgerSigner::connect(&CKB_HD_PATH).map_err(Error::key_base)?)
+            }
+            SignerKind::Remote => Box::new(
+                RemoteSigner::from_signing(&self.config.signing, self.rt.clone())
+                    .map_err(Error::key_base)?,
+            ),
+        };
+        Ok(signer)
+    }
+
+    /// Signer of the inputs funding the fees of the transactions, the key of
+    /// `fee_payer_key_name` in the keyring if it is configured, or the signer of the
+    /// relayer account otherwise
+    fn fee_payer_signer(&self) -> Result<Box<dyn DigestSigner>, Error> {
+        let Some(key_name) = &self.config.fee_payer_key_name else {
+            return self.tx_signer();
+        };
+        if !self.config.submit_txs {
+            return Err(Error::submission_disabled(self.config.id.clone()));
+        }
+        let key = self.keybase.get_key(key_name).map_err(Error::key_base)?;
+        Ok(Box::new(key))
+    }
+
+    /// Check that the relayer supports the IBC contracts, that the node and the indexer
+    /// respond, that the indexer keeps up with the node, that the contract cells are still
+    /// live and that the relayer account and its fee payer are usable
+    fn check_health(&self) -> Result<(), Error> {
+        compatibility::check_contracts_version(&self.config.id, &self.contracts_version)?;
+
+        let rpc_client = self.rpc_client.as_ref();
+        let node_tip = self
+            .rt
+            .block_on(rpc_client.get_tip_header())?
+            .inner
+            .number
+            .value();
+        let indexer_tip = self
+            .rt
+            .block_on(rpc_client.get_indexer_tip())?
+            .ok_or_else(|| Error::rpc_response("the indexer has not indexed any block".to_owned()))?
+            .block_number
+            .value();
+        if node_tip.saturating_sub(indexer_tip) > self.config.max_indexer_lag {
+            return Err(Error::ckb_indexer_lagging(
+                self.config.id.clone(),
+                indexer_tip,
+                node_tip,
+            ));
+        }
+
+        search_client_outpoints(&self.rt, rpc_client, &self.config)?;
+        search_contract_outpoint(
+            &self.rt,
+            rpc_client,
+            &self.config.connection_type_args,
+            "connection",
+        )?;
+        search_contract_outpoint(
+            &self.rt,
+            rpc_client,
+            &self.config.channel_type_args,
+            "channel",
+        )?;
+        search_contract_outpoint(
+            &self.rt,
+            rpc_client,
+            &self.config.packet_type_args,
+            "packet",
+        )?;
+
+        if self.config.submit_txs {
+            self.tx_signer()?.public_key().map_err(Error::key_base)?;
+            if self.config.fee_payer_key_name.is_some() {
+                self.fee_payer_signer()?
+                    .public_key()
+                    .map_err(Error::key_base)?;
+            }
+        }
+
+        if let Some(min_balance) = self.config.min_balance {
+            let balance = self.query_balance(None, None)?;
+            let capacity = balance
+                .amount
+                .parse::<u64>()
+                .map_err(|e| Error::query(format!("invalid capacity {}: {e}", balance.amount)))?;
+            if capacity < min_balance {
+                return Err(Error::low_balance(
+                    self.config.id.clone(),
+                    format!("{} {}", balance.amount, balance.denom),
+                    min_balance,
+                ));
+            }
+        }
+        Ok(())
+    }
+
     pub fn get_converter(&self) -> Converter {
-        if self.connection_cache.borrow().is_none() {
+        let cached = self.ibc_state_cache.has_connection();
+        telemetry!(ckb4ibc_cache_lookup, &self.config.id, "connection", cached);
+        if !cached {
             let _ = self.query_connection_and_cache().unwrap();
         }
         Converter {
-            channel_input_data: self.channel_input_data.borrow(),
-            channel_cache: self.channel_cache.borrow(),
+            cache: &self.ibc_state_cache,
             config: &self.config,
-            connection_cache: self.connection_cache.borrow(),
-            client_outpoint: &self.client_outpoint,
-            packet_input_data: self.packet_input_data.borrow(),
-            packet_owner: Default::default(),
+            client_outpoints: &self.client_outpoints,
             chan_contract_outpoint: &self.channel_outpoint,
             packet_contract_outpoint: &self.packet_outpoint,
             conn_contract_outpoint: &self.connection_outpoint,
@@ -194,70 +454,123 @@ impl Ckb4IbcChain {
     }
 
     fn init_event_monitor(&mut self) -> Result<TxMonitorCmd, Error> {
+        let known_authors = if self.config.verify_tx_authors {
+            let mut own_addresses = vec![self.account_address()?];
+            if self.config.fee_payer_key_name.is_some() && self.config.submit_txs {
+                own_addresses.push(self.fee_payer_address()?);
+            }
+            Some(KnownAuthors::new(
+                &own_addresses,
+                &self.config.known_relayers,
+            )?)
+        } else {
+            None
+        };
+        let start_height = self.monitor_start_height()?;
         let (monitor, monitor_tx) = Ckb4IbcEventMonitor::new(
             self.rt.clone(),
             self.rpc_client.clone(),
             self.config.clone(),
+            self.ibc_state_cache.clone(),
+            known_authors,
+            self.progress.clone(),
+            start_height,
         );
-        std::thread::spawn(move || monitor.run());
+        spawn_chain_thread(self.config.id.clone(), "event_monitor", move || {
+            monitor.run()
+        });
         Ok(monitor_tx)
     }
 
+    /// Height up to which the scans without any progress skip the cells, following
+    /// `monitor_start`
+    fn monitor_start_height(&self) -> Result<u64, Error> {
+        let start = match self.config.monitor_start {
+            MonitorStart::Tip => self
+                .rt
+                .block_on(self.rpc_client.get_tip_header())?
+                .inner
+                .number
+                .value(),
+            MonitorStart::Height(height) => height,
+            MonitorStart::Earliest => {
+                let outpoints = [
+                    &self.connection_outpoint,
+                    &self.channel_outpoint,
+                    &self.packet_outpoint,
+                ]
+                .into_iter()
+                .chain(self.client_outpoints.values());
+                let blocks = outpoints
+                    .map(|outpoint| {
+                        self.rt.block_on(deployment_block(
+                            self.rpc_client.as_ref(),
+                            outpoint.tx_hash().unpack(),
+                            outpoint.index().unpack(),
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, _>>();
+                match blocks {
+                    Ok(blocks) => blocks.into_iter().min().unwrap_or_default(),
+                    Err(e) => {
+                        warn!(
+                            "failed to find the block deploying the IBC contracts of {}, scanning from genesis: {e}",
+                            self.config.id
+                        );
+                        0
+                    }
+                }
+            }
+        };
+        info!(
+            "event monitor of {} starts from block {start} ({:?})",
+            self.config.id, self.config.monitor_start
+        );
+        // the cells of the start block are scanned
+        Ok(start.saturating_sub(1))
+    }
+
+    /// Fetch the packet cell of `sequence` on the channel end.
     fn fetch_packet_cell_and_extract(
         &self,
         channel_id: &ChannelId,
         port_id: &PortId,
         sequence: Sequence,
     ) -> Result<(IbcPacket, CellInput), Error> {
-        let script = Script::new_builder()
-            .code_hash(self.get_converter().get_packet_code_hash())
-            .hash_type(ScriptHashType::Type.into())
-            .args(
-                PacketArgs {
-                    channel_id: get_channel_idx(channel_id)?,
-                    port_id: port_id.as_str().as_bytes().try_into().unwrap(),
-                    sequence: u64::from(sequence) as u16,
-                    owner: Default::default(),
-                }
-                .get_search_args()
-                .pack(),
-            )
-            .build();
-        let search_key = get_search_key(script);
-        let resp = self
-            .rpc_client
-            .fetch_live_cells(search_key, 1, None)
-            .and_then(|resp| async move {
-                let cell = resp
-                    .objects
-                    .into_iter()
-                    .next()
-                    .ok_or(Error::query(String::from("query packet")))?;
-                let tx_hash = &cell.out_point.tx_hash;
-                let tx_resp = self
-                    .rpc_client
-                    .get_transaction(tx_hash)
-                    .await
-                    .map_err(|_| Error::query("".to_string()))?
-                    .ok_or(Error::query("".to_string()))?
-                    .transaction
-                    .unwrap();
-                let tx = match tx_resp.inner {
-                    ckb_jsonrpc_types::Either::Left(r) => r,
-                    ckb_jsonrpc_types::Either::Right(json_bytes) => {
-                        let bytes = json_bytes.as_bytes();
-                        let tx: TransactionView = serde_json::from_slice(bytes).unwrap();
-                        tx
-                    }
-                };
-                let ibc_packet = extract_ibc_packet_from_tx(tx)?;
-                let cell_input = CellInput::new_builder()
-                    .previous_output(cell.out_point.into())
-                    .build();
-                Ok((ibc_packet, cell_input))
+        self.rt
+            .block_on(self.chain_async.fetch_packet(channel_id, port_id, sequence))
+    }
+
+    /// Look up whether the packet of an unordered channel is received or acknowledged
+    /// already, i.e. whether its live cell is past the `Send` status, so that its message
+    /// is skipped instead of being assembled again, e.g. after another relayer's.
+    fn fetch_packet_processed(&self, channel_id: ChannelId, port_id: PortId, sequence: Sequence) {
+        let unordered = self
+            .ibc_state_cache
+            .channel(&channel_id)
+            .map_or(false, |channel| {
+                matches!(channel.order, CkbOrdering::Unordered)
             });
-        let result = self.rt.block_on(resp)?;
-        Ok(result)
+        if !unordered {
+            return;
+        }
+        let packets = self.rt.block_on(self.chain_async.fetch_packets(
+            &channel_id,
+            &port_id,
+            vec![sequence],
+        ));
+        match packets {
+            Ok(packets) => {
+                let processed = packets
+                    .iter()
+                    .any(|(packet, _)| packet.status != PacketStatus::Send);
+                self.ibc_state_cache
+                    .set_packet_processed(channel_id, port_id, sequence, processed);
+            }
+            Err(e) => warn!(
+                "failed to fetch the packet cell of {sequence} on {port_id}/{channel_id}: {e}"
+            ),
+        }
     }
 
     fn fetch_channel_cell_and_extract(
@@ -266,150 +579,651 @@ impl Ckb4IbcChain {
         port_id: PortId,
         is_open: bool,
     ) -> Result<ChannelEnd, Error> {
-        let channel_code_hash = self.get_converter().get_channel_code_hash();
-        let script = Script::new_builder()
-            .code_hash(channel_code_hash)
-            .args(
-                ChannelArgs {
-                    client_id: self.config.client_id(),
-                    open: is_open,
-                    channel_id: get_channel_idx(&channel_id)?,
-                    port_id: convert_port_id_to_array(&port_id)?,
-                }
-                .to_args()
-                .pack(),
-            )
-            .hash_type(ScriptHashType::Type.into())
-            .build();
-        let search_key = get_search_key(script);
-        let channel_end_future = self
-            .rpc_client
-            .fetch_live_cells(search_key, 1, None)
-            .and_then(|resp| async move {
-                let cell = resp
-                    .objects
-                    .first()
-                    .ok_or(Error::query("no channel cell is fetched".to_string()))?;
-                let tx_hash = &cell.out_point.tx_hash;
-                let tx_resp = self
-                    .rpc_client
-                    .get_transaction(tx_hash)
-                    .await
-                    .map_err(|_| Error::query("fetch back tx failed1".to_string()))?
-                    .ok_or(Error::query("fetch back tx failed2".to_string()))?
-                    .transaction
-                    .unwrap();
-                let tx = match tx_resp.inner {
-                    ckb_jsonrpc_types::Either::Left(r) => r,
-                    ckb_jsonrpc_types::Either::Right(json_bytes) => {
-                        let bytes = json_bytes.as_bytes();
-                        let tx: TransactionView = serde_json::from_slice(bytes).unwrap();
-                        tx
-                    }
-                };
-                let channel_end = extract_channel_end_from_tx(tx)?;
-                let input = CellInput::new_builder()
-                    .previous_output(
-                        OutPoint::new_builder()
-                            .tx_hash(tx_hash.pack())
-                            .index(cell.tx_index.pack())
-                            .build(),
-                    )
-                    .build();
-                Ok((channel_end, input))
-            });
-        let ((channel_end, ibc_channel_end), cell_input) = self.rt.block_on(channel_end_future)?;
+        let ((channel_end, ibc_channel_end), cell_input) = self.rt.block_on(
+            self.chain_async
+                .fetch_channel(&channel_id, &port_id, is_open),
+        )?;
 
-        let mut data = self.channel_input_data.borrow_mut();
-        data.insert(
-            (channel_end.channel_id.clone(), channel_end.port_id),
+        self.ibc_state_cache.insert_channel(
+            channel_end.channel_id,
+            channel_end.port_id,
+            ibc_channel_end,
             cell_input,
         );
-        let mut cache = self.channel_cache.borrow_mut();
-        cache.insert(channel_end.channel_id, ibc_channel_end);
         Ok(channel_end.channel_end)
     }
 
-    fn clear_cache(&mut self) {
-        let channel_data = self.channel_input_data.get_mut();
-        channel_data.clear();
+    /// Fetch the live cell of the Tendermint client `client_id`
+    fn fetch_tendermint_client(&self, client_id: &ClientId) -> Result<TendermintClientCell, Error> {
+        self.rt
+            .block_on(self.chain_async.fetch_tendermint_client(client_id))
+    }
 
-        let channel_cache = self.channel_cache.get_mut();
-        channel_cache.clear();
+    /// Proof of the first live IBC cell whose lock is matched by `matches`, against the
+    /// commitment root of the host consensus state.
+    ///
+    /// The indexer only serves the live cells, so the proof is the one of the latest
+    /// state of the object, whatever height it is requested at.
+    fn fetch_ibc_cell_proof(
+        &self,
+        matches: impl Fn(&Script) -> bool,
+        height: Height,
+    ) -> Result<Option<IbcCellProof>, Error> {
+        self.rt
+            .block_on(self.chain_async.fetch_ibc_cell_proof(matches, height))
+    }
 
-        let packet_data = self.packet_input_data.get_mut();
-        packet_data.clear();
+    fn refresh_contract_outpoints(&mut self) -> Result<(), Error> {
+        let rpc_client = self.rpc_client.as_ref();
+        self.client_outpoints = search_client_outpoints(&self.rt, rpc_client, &self.config)?;
+        self.connection_outpoint = search_contract_outpoint(
+            &self.rt,
+            rpc_client,
+            &self.config.connection_type_args,
+            "connection",
+        )?;
+        self.channel_outpoint = search_contract_outpoint(
+            &self.rt,
+            rpc_client,
+            &self.config.channel_type_args,
+            "channel",
+        )?;
+        self.packet_outpoint = search_contract_outpoint(
+            &self.rt,
+            rpc_client,
+            &self.config.packet_type_args,
+            "packet",
+        )?;
+        self.contract_outpoints_fetched_at = Instant::now();
+        Ok(())
+    }
 
-        self.connection_cache.swap(&RefCell::new(None));
+    fn reset_cache(&mut self, genesis_hash: H256) -> Result<(), Error> {
+        self.genesis_hash = genesis_hash;
+        *self.cached_network.get_mut().map_err(Error::other)? = None;
+        *self
+            .cached_tx_assembler_address
+            .get_mut()
+            .map_err(Error::other)? = None;
+        self.unconfirmed_txs
+            .get_mut()
+            .map_err(Error::other)?
+            .clear();
+        self.ibc_state_cache.clear();
+        // the heights and the sequences of the previous chain don't apply to the new one
+        self.progress.lock().map_err(Error::other)?.clear();
+        self.refresh_contract_outpoints()
+    }
+
+    /// Drop all the cached states if the CKB node has been reset to a different chain,
+    /// otherwise fetch the contract outpoints again once they expire.
+    fn refresh_expired_cache(&mut self) -> Result<(), Error> {
+        let genesis_hash = self
+            .rt
+            .block_on(fetch_genesis_hash(self.rpc_client.as_ref()))?;
+        if genesis_hash != self.genesis_hash {
+            warn!(
+                "genesis hash of {} changed from {:#x} to {genesis_hash:#x}, invalidating cache",
+                self.config.id, self.genesis_hash
+            );
+            return self.reset_cache(genesis_hash);
+        }
+        if self.contract_outpoints_fetched_at.elapsed() >= self.config.cache_ttl {
+            self.refresh_contract_outpoints()?;
+        }
+        Ok(())
+    }
+
+    /// Forget the unconfirmed transactions once they are all committed, and roll back the
+    /// cells chained on them if one of them is rejected or dropped from the pool, so that
+    /// the cells are fetched from the indexer again.
+    fn check_unconfirmed_txs(&self) -> Result<(), Error> {
+        let mut unconfirmed = self.unconfirmed_txs.write().map_err(Error::other)?;
+        let mut pending = vec![];
+        for tx_hash in &unconfirmed.hashes {
+            let tx_status = self
+                .rt
+                .block_on(self.rpc_client.get_transaction(tx_hash))?
+                .map(|resp| resp.tx_status);
+            match tx_status.as_ref().map(|tx_status| &tx_status.status) {
+                Some(Status::Committed) => {}
+                Some(Status::Pending | Status::Proposed) => pending.push(tx_hash.clone()),
+                _ => {
+                    let reason = tx_status
+                        .and_then(|tx_status| tx_status.reason)
+                        .unwrap_or_else(|| "unknown".to_owned());
+                    warn!(
+                        "unconfirmed ckb transaction {tx_hash:#x} on {} is dropped ({reason}), rolling back the cells chained on it",
+                        self.config.id
+                    );
+                    unconfirmed.clear();
+                    self.ibc_state_cache.clear();
+                    return Ok(());
+                }
+            }
+        }
+        if pending.is_empty() {
+            unconfirmed.clear();
+            self.ibc_state_cache.unpin();
+        } else {
+            unconfirmed.hashes = pending;
+        }
+        Ok(())
     }
 
     fn query_connection_and_cache(
         &self,
     ) -> Result<(Vec<IdentifiedConnectionEnd>, IbcConnections, CellInput), Error> {
-        let search_key = get_connection_search_key(&self.config);
-
-        let cells_rpc_result = self
-            .rpc_client
-            .fetch_live_cells(search_key, 1, None)
-            .and_then(|cells| async {
-                let cell = cells
-                    .objects
-                    .into_iter()
-                    .next()
-                    .ok_or(Error::query("get ibc connection cell failed 1".to_string()))?;
-                let tx_resp = self
-                    .rpc_client
-                    .get_transaction(&cell.out_point.tx_hash)
-                    .await?;
-                Ok((
-                    tx_resp,
-                    CellInput::new_builder()
-                        .previous_output(cell.out_point.into())
-                        .build(),
-                ))
-            });
-        let r = self.rt.block_on(cells_rpc_result);
-        // let (transaction, cell_input) = self.rt.block_on(cells_rpc_result)?;
-        let (transaction, cell_input) = match r {
-            Ok(r) => r,
-            Err(e) => {
-                print!("{e}");
-                return Err(e);
+        let (connections, ibc_connection, cell_input) =
+            self.rt.block_on(self.chain_async.fetch_connections())?;
+        self.ibc_state_cache
+            .set_connection(ibc_connection.clone(), cell_input.clone());
+        Ok((connections, ibc_connection, cell_input))
+    }
+
+    /// Assemble a transaction for each message and submit them at once.
+    ///
+    /// A transaction consuming the cells updated by a previous transaction of the batch is
+    /// chained on the outputs of that parent, funded by its change output, and sent once
+    /// the parent is accepted. The independent ones are funded by distinct capacity cells
+    /// and sent concurrently, so that no transaction of the batch spends a cell spent by
+    /// another one.
+    ///
+    /// With `chain_unconfirmed_txs`, the transactions are also chained on the unconfirmed
+    /// ones of the previous batches, and resolved once accepted into the pool.
+    ///
+    /// Returns the messages whose transactions are rejected because their cell inputs
+    /// have been consumed by someone else, along with their envelopes and the rejection errors.
+    fn assemble_and_submit(
+        &self,
+        msgs: Vec<Any>,
+        result_events: &mut Vec<IbcEventWithHeight>,
+    ) -> Result<Vec<DeadMsg>, Error> {
+        let mut txs = Vec::new();
+        let mut submitted_msgs = Vec::new();
+        let mut graph = ConflictGraph::default();
+        let mut change_cells: Vec<Option<ChangeCell>> = Vec::new();
+        let mut tx_inputs = Vec::new();
+        let mut tx_hashes = Vec::new();
+        let mut msg_spans = Vec::new();
+        let chained = self.config.chain_unconfirmed_txs;
+        let (mut pool_change_cells, mut spent_cells) = if chained {
+            let unconfirmed = self.unconfirmed_txs.read().map_err(Error::other)?;
+            // the change cells committed meanwhile are only spent from the unconfirmed ones
+            let spent_cells = unconfirmed
+                .spent_cells
+                .iter()
+                .chain(unconfirmed.change_cells.iter().map(|cell| &cell.out_point))
+                .cloned()
+                .collect();
+            (unconfirmed.change_cells.clone(), spent_cells)
+        } else {
+            (vec![], HashSet::new())
+        };
+        let converter = self.get_converter();
+        // the fee payer is loaded once per batch, as it may be a Ledger device or a remote
+        // signer which each load goes to. The IBC inputs are unlocked by the contracts, and
+        // the inputs funding the fees which follow them are all locked by the fee payer
+        let tx_signer = CkbTxSigner::new(self.fee_payer_signer()?).map_err(Error::key_base)?;
+        let signer = SecpSighashScriptSigner::new(Box::new(tx_signer));
+        let fee_payer_lock = Script::from(&self.fee_payer_address()?);
+        for msg in msgs {
+            let assembly_start = Instant::now();
+            let cell_keys = consumed_cells(&msg)?;
+            let span = message_span(&msg, &cell_keys);
+            let _entered = span.enter();
+            for key in &cell_keys {
+                if let CellKey::Client(client_id) = key {
+                    if self.ibc_state_cache.client(client_id).is_none() {
+                        let cell = self.fetch_tendermint_client(client_id)?;
+                        self.ibc_state_cache.insert_client(client_id.clone(), cell);
+                    }
+                }
+            }
+            if let Some((channel_id, port_id, sequence)) = processed_packet_key(&msg)? {
+                self.fetch_packet_processed(channel_id, port_id, sequence);
             }
+            let CkbTxInfo {
+                unsigned_tx,
+                envelope,
+                input_capacity,
+                event,
+            } = convert_msg_to_ckb_tx(msg.clone(), &converter).map_err(|e| {
+                error!("failed to convert the message: {e}");
+                e
+            })?;
+            if unsigned_tx.is_none() {
+                if let Some(e) = event {
+                    let ibc_event = IbcEventWithHeight {
+                        event: e,
+                        height: Height::new(1, 1).unwrap(),
+                        tx_hash: [0; 32],
+                    };
+                    result_events.push(ibc_event);
+                }
+                continue;
+            }
+            let unsigned_tx = unsigned_tx.unwrap();
+            let ibc_inputs_count = unsigned_tx.inputs().len();
+            let envelope_bytes = rlp::encode(&envelope).to_vec();
+            let fee_rate = self
+                .config
+                .fee_multipliers
+                .fee_rate(BASE_FEE_RATE, &msg.type_url);
+            let change_cell = graph
+                .dependencies(&cell_keys)
+                .into_iter()
+                .find_map(|parent| change_cells[parent].take())
+                .or_else(|| pool_change_cells.pop());
+            let (tx, fee) = match self.complete_tx_with_secp256k1_change_and_envelope(
+                unsigned_tx,
+                input_capacity,
+                envelope,
+                fee_rate,
+                change_cell,
+                &spent_cells,
+            ) {
+                Ok(completed) => completed,
+                Err(e) => {
+                    error!("failed to assemble the transaction of the message: {e}");
+                    // none of the batch is sent, so the cells chained on the transactions
+                    // assembled so far are rolled back
+                    self.ibc_state_cache.clear();
+                    return Err(e);
+                }
+            };
+            let tx = signer
+                .sign_tx(
+                    &tx,
+                    &ScriptGroup {
+                        script: fee_payer_lock.clone(),
+                        group_type: ScriptGroupType::Lock,
+                        input_indices: (ibc_inputs_count..tx.inputs().len()).collect(),
+                        output_indices: vec![],
+                    },
+                )
+                .map_err(|e| {
+                    error!("failed to sign the transaction: {e}");
+                    Error::other(e)
+                })?;
+            let inputs = tx
+                .inputs()
+                .into_iter()
+                .map(|input| input.previous_output())
+                .collect::<Vec<_>>();
+            spent_cells.extend(inputs.iter().cloned());
+            tx_inputs.push(inputs);
+            let tx: TransactionView = tx.into();
+            span.record("tx_hash", format!("{:#x}", tx.hash).as_str());
+            self.chain_cached_cells(&tx, &cell_keys);
+            graph.add(&cell_keys);
+            change_cells.push(ChangeCell::of(&tx));
+            let _assembly_time = assembly_start.elapsed().as_millis() as u64;
+            telemetry!(ckb4ibc_tx_assembly_time, &self.config.id, _assembly_time);
+            tx_hashes.push(tx.hash.clone());
+            txs.push((tx.inner, (event, fee)));
+            submitted_msgs.push((msg, envelope_bytes));
+            msg_spans.push(span.clone());
+        }
+
+        let tracker_config = PendingTxConfig {
+            accept_in_pool: chained,
+            ..self.pending_tx_config()
         };
-        let tx = transaction
-            .ok_or(Error::query("get ibc connection cell failed 2".to_string()))?
-            .transaction
-            .ok_or(Error::query("get ibc connection cell failed 3".to_string()))?;
-        let tx = match tx.inner {
-            ckb_jsonrpc_types::Either::Left(r) => r,
-            ckb_jsonrpc_types::Either::Right(json_bytes) => {
-                let bytes = json_bytes.as_bytes();
-                let tx: TransactionView = serde_json::from_slice(bytes).unwrap();
-                tx
+        let tracker = PendingTxTracker::new(self.rpc_client.as_ref(), tracker_config)
+            .with_broadcaster(&self.broadcaster);
+        let in_flight = tx_hashes
+            .iter()
+            .zip(&submitted_msgs)
+            .map(|(tx_hash, (msg, _))| {
+                let packets: Vec<_> = relayed_packet(msg)?.into_iter().collect();
+                Ok((format!("{tx_hash:#x}"), packets))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.progress
+            .lock()
+            .map_err(Error::other)?
+            .add_in_flight_txs(in_flight);
+        let submitted_at = Instant::now();
+        let resps = self
+            .rt
+            .block_on(tracker.submit_chained_and_track(txs, graph.parents()));
+        let _latency = submitted_at.elapsed().as_millis() as u64;
+        let resolved = tx_hashes
+            .iter()
+            .zip(&resps)
+            .map(|(tx_hash, resp)| (format!("{tx_hash:#x}"), resp.is_ok()))
+            .collect();
+        self.progress
+            .lock()
+            .map_err(Error::other)?
+            .resolve_in_flight_txs(resolved);
+        if chained {
+            let mut unconfirmed = self.unconfirmed_txs.write().map_err(Error::other)?;
+            unconfirmed.change_cells = pool_change_cells;
+            for ((resp, inputs), change_cell) in resps.iter().zip(tx_inputs).zip(change_cells) {
+                if let Ok((tx_hash, _)) = resp {
+                    unconfirmed.hashes.push(tx_hash.clone());
+                    unconfirmed.spent_cells.extend(inputs);
+                    unconfirmed.change_cells.extend(change_cell);
+                }
+            }
+            if resps.iter().any(Result::is_err) {
+                // the cells chained on the transactions not sent are rolled back
+                self.ibc_state_cache.clear();
+            }
+        } else {
+            self.ibc_state_cache.clear();
+        }
+
+        let mut dead = vec![];
+        for ((resp, (msg, envelope)), span) in resps.into_iter().zip(submitted_msgs).zip(msg_spans)
+        {
+            let _entered = span.enter();
+            match resp {
+                Ok((tx_hash, (event, _fee))) => {
+                    debug!("transaction committed");
+                    telemetry!(ckb4ibc_tx_confirmed, &self.config.id, _latency, _fee);
+                    if let Some(event) = event {
+                        let ibc_event_with_height = IbcEventWithHeight {
+                            event,
+                            height: Height::new(1, 1).unwrap(),
+                            tx_hash: tx_hash.into(),
+                        };
+                        result_events.push(ibc_event_with_height);
+                    }
+                }
+                Err(e) if e.is_ckb_dead_cell_error() => {
+                    warn!("transaction consumed dead cells: {e}");
+                    dead.push((msg, envelope, e))
+                }
+                Err(e) => {
+                    error!("failed to send the transaction: {e}");
+                    return Err(e);
+                }
+            }
+        }
+        Ok(dead)
+    }
+
+    fn pending_tx_config(&self) -> PendingTxConfig {
+        PendingTxConfig {
+            poll_interval: self.config.tx_poll_interval,
+            confirmations: self.config.confirmations,
+            timeout: self.config.tx_commit_timeout,
+            accept_in_pool: false,
+        }
+    }
+
+    /// Wait for the transactions the relayer left in flight before it restarted, so that
+    /// the packets they relay are known to be relayed once they are committed
+    fn resume_in_flight_txs(&self) -> Result<(), Error> {
+        let in_flight = self.progress.lock().map_err(Error::other)?.in_flight_txs();
+        if in_flight.is_empty() {
+            return Ok(());
+        }
+        info!(
+            "waiting for {} ckb transactions sent to {} before the relayer restarted",
+            in_flight.len(),
+            self.config.id
+        );
+        let mut resolved = vec![];
+        let mut hashes = vec![];
+        for tx_hash in in_flight {
+            match H256::from_str(tx_hash.trim_start_matches("0x")) {
+                Ok(hash) => hashes.push((tx_hash, hash)),
+                Err(e) => {
+                    warn!("dropping invalid ckb transaction hash {tx_hash} in flight: {e}");
+                    resolved.push((tx_hash, false));
+                }
+            }
+        }
+        let tracker = PendingTxTracker::new(self.rpc_client.as_ref(), self.pending_tx_config());
+        let (tx_hashes, hashes): (Vec<_>, Vec<_>) = hashes.into_iter().unzip();
+        let results = self.rt.block_on(tracker.track_sent(hashes));
+        for (tx_hash, result) in tx_hashes.into_iter().zip(results) {
+            if let Err(e) = &result {
+                warn!(
+                    "ckb transaction {tx_hash} sent to {} before the relayer restarted is not committed: {e}",
+                    self.config.id
+                );
+            }
+            resolved.push((tx_hash, result.is_ok()));
+        }
+        self.progress
+            .lock()
+            .map_err(Error::other)?
+            .resolve_in_flight_txs(resolved);
+        Ok(())
+    }
+
+    /// Drop the messages relaying the packets which are already relayed, as recorded in
+    /// the progress of the relaying, e.g. by the transactions in flight before a restart
+    fn drop_relayed_packets(&self, msgs: Vec<Any>) -> Result<Vec<Any>, Error> {
+        let progress = self.progress.lock().map_err(Error::other)?;
+        let mut kept = vec![];
+        for msg in msgs {
+            match relayed_packet(&msg)? {
+                Some(packet) if progress.is_relayed(&packet) => info!(
+                    "{} of packet {} on {}/{} is already relayed to {}, dropping it",
+                    packet.step, packet.sequence, packet.port_id, packet.channel_id, self.config.id
+                ),
+                _ => kept.push(msg),
+            }
+        }
+        Ok(kept)
+    }
+
+    /// Drop the updates of the Tendermint clients whose consensus states are on the chain
+    /// already, e.g. because another relayer updated the clients meanwhile, reporting
+    /// them as done without any transaction
+    fn drop_redundant_client_updates(
+        &self,
+        msgs: Vec<Any>,
+        result_events: &mut Vec<IbcEventWithHeight>,
+    ) -> Result<Vec<Any>, Error> {
+        let mut kept = vec![];
+        for msg in msgs {
+            let Some(update) = tendermint_client_update(&msg)? else {
+                kept.push(msg);
+                continue;
+            };
+            // the cached cell may be updated by a pending transaction of the relayer, and
+            // the live one by another relayer
+            let mut event = match self.ibc_state_cache.client(&update.client_id) {
+                Some(cell) => redundant_update_event(&update, &cell.client)?,
+                None => None,
+            };
+            if event.is_none() {
+                let cell = self.fetch_tendermint_client(&update.client_id)?;
+                event = redundant_update_event(&update, &cell.client)?;
+            }
+            match event {
+                Some(event) => {
+                    info!(
+                        "consensus state updating client {} is on {} already, skipping the update",
+                        update.client_id, self.config.id
+                    );
+                    telemetry!(
+                        ckb4ibc_free_ride_client_update,
+                        &self.config.id,
+                        update.client_id.as_str()
+                    );
+                    result_events.push(IbcEventWithHeight {
+                        event,
+                        height: Height::new(1, 1).unwrap(),
+                        tx_hash: [0; 32],
+                    });
+                }
+                None => kept.push(msg),
             }
+        }
+        Ok(kept)
+    }
+
+    /// Point the cached cells consumed by a pending transaction to its outputs, so that
+    /// the next transactions of the batch consuming them are chained on it
+    fn chain_cached_cells(&self, tx: &TransactionView, cell_keys: &[CellKey]) {
+        let output = |index: usize| {
+            CellInput::new_builder()
+                .previous_output(
+                    OutPoint::new_builder()
+                        .tx_hash(tx.hash.pack())
+                        .index((index as u32).pack())
+                        .build(),
+                )
+                .build()
         };
-        let (connections, ibc_connection) = extract_connections_from_tx(tx)?;
-        let result = std::cell::RefCell::new(Some((ibc_connection.clone(), cell_input.clone())));
-        self.connection_cache.swap(&result);
-        Ok((connections, ibc_connection, cell_input))
+        for key in cell_keys {
+            let chained = match key {
+                CellKey::Connections => ibc_connections_output_idx(tx).and_then(|index| {
+                    let connections = extract_ibc_connections_from_tx(tx.clone())?;
+                    self.ibc_state_cache
+                        .advance_connection(connections, output(index));
+                    Ok(())
+                }),
+                CellKey::Channel(channel_id) => channel_end_output_idx(tx).and_then(|index| {
+                    let (_, channel) = extract_channel_end_from_tx(tx.clone())?;
+                    self.ibc_state_cache
+                        .advance_channel(channel_id, channel, output(index));
+                    Ok(())
+                }),
+                CellKey::Packet(..) => Ok(()),
+                CellKey::Client(client_id) => {
+                    let data = tx
+                        .inner
+                        .outputs_data
+                        .first()
+                        .map(|data| data.as_bytes())
+                        .unwrap_or_default();
+                    TendermintClient::from_cell_data(client_id, data).map(|client| {
+                        let cell = TendermintClientCell {
+                            client,
+                            input: output(0),
+                            capacity: tx.inner.outputs[0].capacity.value(),
+                        };
+                        self.ibc_state_cache.advance_client(client_id.clone(), cell);
+                    })
+                }
+            };
+            if let Err(e) = chained {
+                warn!(
+                    "failed to chain the cells of ckb transaction {:#x} on {}: {e}",
+                    tx.hash, self.config.id
+                );
+            }
+        }
+    }
+
+    /// Drop the messages which another relayer has already submitted, i.e. those whose
+    /// envelope matches the one of a transaction which created the refetched cells.
+    ///
+    /// Redundant relayers sharing the same configuration race for the same cells, so the
+    /// messages they relayed first are not failures of this relayer.
+    fn drop_externally_relayed(&self, dead: Vec<DeadMsg>) -> Vec<DeadMsg> {
+        let tx_hashes = self
+            .ibc_state_cache
+            .inputs()
+            .iter()
+            .map(|input| input.previous_output().tx_hash().unpack())
+            .collect::<HashSet<H256>>();
+        let envelopes = tx_hashes
+            .into_iter()
+            .filter_map(|tx_hash| match self.fetch_envelope(&tx_hash) {
+                Ok(envelope) => Some(envelope),
+                Err(e) => {
+                    warn!("failed to fetch the envelope of ckb transaction {tx_hash:#x}: {e}");
+                    None
+                }
+            })
+            .collect::<HashSet<_>>();
+
+        dead.into_iter()
+            .filter(|(msg, envelope, _)| {
+                let relayed = envelopes.contains(envelope);
+                if relayed {
+                    info!(
+                        "{} was already relayed to {} by another relayer, skipping it",
+                        msg.type_url, self.config.id
+                    );
+                    telemetry!(ckb4ibc_externally_relayed, &self.config.id, &msg.type_url);
+                }
+                !relayed
+            })
+            .collect()
+    }
+
+    fn fetch_envelope(&self, tx_hash: &H256) -> Result<Vec<u8>, Error> {
+        let tx = self
+            .rt
+            .block_on(self.rpc_client.get_transaction(tx_hash))?
+            .and_then(|resp| resp.transaction)
+            .ok_or_else(|| Error::query(format!("no ckb transaction {tx_hash:#x}")))?;
+        extract_envelope_bytes(&decode_transaction(tx)?)
+    }
+
+    /// Fetch the live cells of the connections and of the given channels and packets,
+    /// replacing the cached ones which have been consumed.
+    fn refetch_cells(&self, (channels, packets): CachedKeys) -> Result<(), Error> {
+        self.ibc_state_cache.clear();
+        self.query_connection_and_cache()?;
+        for (channel_id, port_id) in channels {
+            self.fetch_channel_cell_and_extract(channel_id.clone(), port_id.clone(), false)
+                .or_else(|_| self.fetch_channel_cell_and_extract(channel_id, port_id, true))?;
+        }
+        for (channel_id, port_id, sequence) in packets {
+            let (_, cell_input) =
+                self.fetch_packet_cell_and_extract(&channel_id, &port_id, sequence)?;
+            self.ibc_state_cache
+                .insert_packet_input(channel_id, port_id, sequence, cell_input);
+        }
+        Ok(())
     }
 
+    /// Returns the completed transaction along with the fee it pays, in shannons.
+    ///
+    /// The transaction is funded by the `change_cell` of its parent first, if any, and by
+    /// live capacity cells other than the `spent_cells` of the pending transactions, both
+    /// of the fee payer, which receives the change as well.
     pub fn complete_tx_with_secp256k1_change_and_envelope(
         &self,
-        tx: CoreTransactionView,
-        input_capacity: u64,
+        mut tx: CoreTransactionView,
+        mut input_capacity: u64,
         envelope: Envelope,
-    ) -> Result<CoreTransactionView, Error> {
-        let fee_rate = 3000;
-        let address = self.tx_assembler_address()?;
-        let tx = self.rpc_client.complete_tx_with_secp256k1_change(
+        fee_rate: u64,
+        change_cell: Option<ChangeCell>,
+        spent_cells: &HashSet<OutPoint>,
+    ) -> Result<(CoreTransactionView, u64), Error> {
+        let address = self.fee_payer_address()?;
+        if let Some(change_cell) = change_cell {
+            tx = tx
+                .as_advanced_builder()
+                .input(
+                    CellInput::new_builder()
+                        .previous_output(change_cell.out_point)
+                        .build(),
+                )
+                .build();
+            input_capacity += change_cell.capacity;
+        }
+        let tx = self.rpc_client.complete_tx_with_secp256k1_change_excluding(
             tx,
             &address,
             input_capacity,
             fee_rate,
+            spent_cells,
         );
-        let (result, _) = self.rt.block_on(tx)?;
+        let (result, change_inputs) = self.rt.block_on(tx)?;
+        let inputs_capacity = change_inputs
+            .iter()
+            .map(|output| Unpack::<u64>::unpack(&output.capacity()))
+            .sum::<u64>()
+            + input_capacity;
+        let outputs_capacity = result
+            .outputs_capacity()
+            .map_err(|e| Error::send_tx(e.to_string()))?
+            .as_u64();
+        let fee = inputs_capacity.saturating_sub(outputs_capacity);
         let witness = WitnessArgs::new_builder()
             .output_type(get_encoded_object(envelope).witness)
             .build()
@@ -421,10 +1235,64 @@ impl Ckb4IbcChain {
             .witness(WitnessArgs::new_builder().build().as_bytes().pack())
             .witness(witness)
             .build();
-        Ok(result)
+        Ok((result, fee))
     }
 }
 
+/// Span of the conversion, the assembly, the signing and the broadcast of the transaction
+/// of `msg`, which consumes the cells of `cell_keys`
+fn message_span(msg: &Any, cell_keys: &[CellKey]) -> Span {
+    let channel = cell_keys.iter().find_map(|key| match key {
+        CellKey::Channel(channel_id) | CellKey::Packet(channel_id, _) => Some(channel_id),
+        _ => None,
+    });
+    let sequence = cell_keys.iter().find_map(|key| match key {
+        CellKey::Packet(_, sequence) => Some(u64::from(*sequence)),
+        _ => None,
+    });
+    error_span!(
+        "ckb4ibc.msg",
+        msg = %msg.type_url,
+        channel = channel.map(field::display),
+        sequence,
+        tx_hash = field::Empty,
+    )
+}
+
+async fn fetch_genesis_hash(rpc_client: &RpcClient) -> Result<H256, Error> {
+    let genesis = rpc_client.get_block_by_number(0.into()).await?;
+    Ok(genesis.header.hash)
+}
+
+fn search_contract_outpoint(
+    rt: &Runtime,
+    rpc_client: &RpcClient,
+    type_args: &H256,
+    name: &str,
+) -> Result<OutPoint, Error> {
+    let cell = rt.block_on(
+        rpc_client
+            .search_cell_by_typescript(&TYPE_ID_CODE_HASH.pack(), &type_args.as_bytes().to_owned()),
+    )?;
+    cell.map(|cell| cell.out_point)
+        .ok_or_else(|| Error::other_error(format!("invalid `{name} type args not found` option")))
+}
+
+fn search_client_outpoints(
+    rt: &Runtime,
+    rpc_client: &RpcClient,
+    config: &Ckb4IbcChainConfig,
+) -> Result<BTreeMap<H256, OutPoint>, Error> {
+    config
+        .all_client_type_args()
+        .into_iter()
+        .map(|type_args| {
+            let outpoint = search_contract_outpoint(rt, rpc_client, type_args, "client")?;
+            Ok((type_args.clone(), outpoint))
+        })
+        .collect()
+}
+
 impl ChainEndpoint for Ckb4IbcChain {
     type LightBlock = CkbLightBlock;
 
@@ -442,7 +1310,22 @@ impl ChainEndpoint for Ckb4IbcChain {
 
     fn bootstrap(config: ChainConfig, rt: Arc<Runtime>) -> Result<Self, Error> {
         let config: Ckb4IbcChainConfig = config.try_into()?;
-        let rpc_client = Arc::new(RpcClient::new(&config.ckb_rpc, &config.ckb_indexer_rpc));
+        let rpc_client = Arc::new(
+            RpcClient::new(&config.ckb_rpc, &config.ckb_indexer_rpc)
+                .with_rpc_mode(config.rpc_mode)
+                .with_chain_id(config.id.clone())
+                .with_confirmations(config.confirmations)
+                .with_indexer_guard(
+                    config.id.clone(),
+                    config.max_indexer_lag,
+                    config.indexer_wait,
+                )
+                .with_request_limits(
+                    config.max_rpc_requests_per_second,
+                    config.max_concurrent_rpc_requests,
+                ),
+        );
+        rt.block_on(rpc_client.register_light_client_scripts(get_light_client_scripts(&config)))?;
 
         #[cfg(not(test))]
         {
@@ -450,63 +1333,41 @@ impl ChainEndpoint for Ckb4IbcChain {
             rt.block_on(init_sighash_celldep(rpc_client.as_ref()))?;
         }
 
-        let client_cell = rt.block_on(rpc_client.search_cell_by_typescript(
-            &TYPE_ID_CODE_HASH.pack(),
-            &config.client_type_args.as_bytes().to_owned(),
-        ))?;
-        if client_cell.is_none() {
-            return Err(Error::other_error(
-                "invalid `client type args not found` option".to_owned(),
-            ));
-        }
-
-        let conn_contract_cell = rt.block_on(rpc_client.search_cell_by_typescript(
-            &TYPE_ID_CODE_HASH.pack(),
-            &config.connection_type_args.as_bytes().to_owned(),
-        ))?;
-        if conn_contract_cell.is_none() {
-            return Err(Error::other_error(
-                "invalid `connection type args not found` option".to_owned(),
-            ));
-        }
-
-        let chan_contract_cell = rt.block_on(rpc_client.search_cell_by_typescript(
-            &TYPE_ID_CODE_HASH.pack(),
-            &config.channel_type_args.as_bytes().to_owned(),
-        ))?;
-        if chan_contract_cell.is_none() {
-            return Err(Error::other_error(
-                "invalid `channel type args not found` option".to_owned(),
-            ));
-        }
+        let contracts_version = compatibility::contracts_version(&config)?;
+        let genesis_hash = rt.block_on(fetch_genesis_hash(rpc_client.as_ref()))?;
+        let client_outpoints = search_client_outpoints(&rt, &rpc_client, &config)?;
+        let connection_outpoint =
+            search_contract_outpoint(&rt, &rpc_client, &config.connection_type_args, "connection")?;
+        let channel_outpoint =
+            search_contract_outpoint(&rt, &rpc_client, &config.channel_type_args, "channel")?;
+        let packet_outpoint =
+            search_contract_outpoint(&rt, &rpc_client, &config.packet_type_args, "packet")?;
 
-        let packet_contract_cell = rt.block_on(rpc_client.search_cell_by_typescript(
-            &TYPE_ID_CODE_HASH.pack(),
-            &config.packet_type_args.as_bytes().to_owned(),
-        ))?;
-        if packet_contract_cell.is_none() {
-            return Err(Error::other_error(
-                "invalid `packet type args not found` option".to_owned(),
-            ));
-        }
         let keybase =
             KeyRing::new(Default::default(), "ckb", &config.id).map_err(Error::key_base)?;
+        let broadcaster = TxBroadcaster::new(&config.broadcast_rpcs);
+        let chain_async = Ckb4IbcChainAsync::new(rpc_client.clone(), config.clone());
+        let progress = RelayProgress::shared(config.progress_file.clone());
         let chain = Ckb4IbcChain {
             rt,
             rpc_client,
+            chain_async,
+            broadcaster,
             config,
             keybase,
             cached_network: RwLock::new(None),
+            genesis_hash,
+            contracts_version,
             tx_monitor_cmd: None,
-            client_outpoint: client_cell.unwrap().out_point,
-            connection_outpoint: conn_contract_cell.unwrap().out_point,
-            channel_outpoint: chan_contract_cell.unwrap().out_point,
-            packet_outpoint: packet_contract_cell.unwrap().out_point,
-            channel_input_data: RefCell::new(HashMap::new()),
-            channel_cache: RefCell::new(HashMap::new()),
-            connection_cache: RefCell::new(None),
-            packet_input_data: RefCell::new(HashMap::new()),
+            client_outpoints,
+            connection_outpoint,
+            channel_outpoint,
+            packet_outpoint,
+            contract_outpoints_fetched_at: Instant::now(),
+            ibc_state_cache: Arc::new(IbcStateCache::default()),
             cached_tx_assembler_address: RwLock::new(None),
+            unconfirmed_txs: RwLock::new(UnconfirmedTxs::default()),
+            progress,
         };
         Ok(chain)
     }
@@ -520,7 +1381,19 @@ impl ChainEndpoint for Ckb4IbcChain {
     }
 
     fn health_check(&self) -> Result<HealthCheck, Error> {
-        Ok(HealthCheck::Healthy)
+        match self.check_health() {
+            Ok(()) => Ok(HealthCheck::Healthy),
+            Err(e) => Ok(HealthCheck::Unhealthy(Box::new(e))),
+        }
+    }
+
+    fn capabilities(&self) -> ChainCapabilities {
+        // ICS-29 fees, ICS-31 queries and client upgrades have no CKB contract
+        ChainCapabilities {
+            ordered_channels: true,
+            proofs: true,
+            ..Default::default()
+        }
     }
 
     fn subscribe(&mut self) -> Result<Subscription, Error> {
@@ -536,6 +1409,119 @@ impl ChainEndpoint for Ckb4IbcChain {
         Ok(subscription)
     }
 
+    fn invalidate_cache(&mut self) -> Result<(), Error> {
+        let genesis_hash = self
+            .rt
+            .block_on(fetch_genesis_hash(self.rpc_client.as_ref()))?;
+        self.reset_cache(genesis_hash)
+    }
+
+    fn query_storage_footprint(&self) -> Result<StorageFootprint, Error> {
+        telemetry!(query, &self.config.id, "query_storage_footprint");
+        self.rt.block_on(self.chain_async.query_storage_footprint())
+    }
+
+    fn query_oversized_packets(&self) -> Result<Vec<OversizedPacket>, Error> {
+        telemetry!(query, &self.config.id, "query_oversized_packets");
+        self.rt.block_on(self.chain_async.query_oversized_packets())
+    }
+
+    fn query_ibc_state_at(&self, block_number: u64) -> Result<IbcStateAt, Error> {
+        telemetry!(query, &self.config.id, "query_ibc_state_at");
+        self.rt
+            .block_on(self.chain_async.query_ibc_state_at(block_number))
+    }
+
+    fn query_ibc_cells(&self, kind: Option<IbcCellKind>) -> Result<Vec<IbcCell>, Error> {
+        telemetry!(query, &self.config.id, "query_ibc_cells");
+        self.rt.block_on(self.chain_async.query_ibc_cells(kind))
+    }
+
+    fn query_relay_status(&self) -> Result<RelayStatus, Error> {
+        telemetry!(query, &self.config.id, "query_relay_status");
+        let status = self
+            .rt
+            .block_on(self.chain_async.query_application_status())?;
+        let progress = self.progress.lock().map_err(Error::other)?;
+        Ok(progress.status(status.height.revision_height()))
+    }
+
+    fn query_contract_outpoints(&self) -> Result<BTreeMap<String, String>, Error> {
+        let format_outpoint = |outpoint: &OutPoint| {
+            let tx_hash: H256 = outpoint.tx_hash().unpack();
+            let index: u32 = outpoint.index().unpack();
+            format!("{tx_hash:#x}:{index}")
+        };
+        let contracts = [
+            ("connection", &self.connection_outpoint),
+            ("channel", &self.channel_outpoint),
+            ("packet", &self.packet_outpoint),
+        ];
+        let mut outpoints: BTreeMap<_, _> = contracts
+            .into_iter()
+            .map(|(name, outpoint)| (name.to_owned(), format_outpoint(outpoint)))
+            .collect();
+        for (type_args, outpoint) in &self.client_outpoints {
+            outpoints.insert(format!("client/{type_args:#x}"), format_outpoint(outpoint));
+        }
+        Ok(outpoints)
+    }
+
+    fn audit_config(&self) -> Result<Vec<ConfigDrift>, Error> {
+        let mut contracts = vec![
+            ("client_type_args".to_owned(), &self.config.client_type_args),
+            (
+                "connection_type_args".to_owned(),
+                &self.config.connection_type_args,
+            ),
+            (
+                "channel_type_args".to_owned(),
+                &self.config.channel_type_args,
+            ),
+            ("packet_type_args".to_owned(), &self.config.packet_type_args),
+        ];
+        contracts.extend(
+            self.config
+                .clients
+                .iter()
+                .map(|(client_id, type_args)| (format!("clients.{client_id}"), type_args)),
+        );
+
+        let mut drifts = vec![];
+        for (field, type_args) in contracts {
+            let cell = self.rt.block_on(self.rpc_client.search_cell_by_typescript(
+                &TYPE_ID_CODE_HASH.pack(),
+                &type_args.as_bytes().to_owned(),
+            ))?;
+            if cell.is_none() {
+                drifts.push(ConfigDrift::new(
+                    field,
+                    format!("{type_args:#x}"),
+                    None,
+                    "no live cell has these type id args, set them to the type id args of the redeployed contract",
+                ));
+            }
+        }
+
+        // the connections on a client missing from `clients` are bound to the default one
+        let (connections, _, _) = self.query_connection_and_cache()?;
+        for client_id in self.config.clients.keys() {
+            let in_use = connections
+                .iter()
+                .any(|connection| connection.connection_end.client_id() == client_id);
+            if !in_use {
+                drifts.push(ConfigDrift::new(
+                    "clients",
+                    client_id,
+                    None,
+                    "no connection is on this client, remove it or rename it after the client of its connections",
+                ));
+            }
+        }
+
+        Ok(drifts)
+    }
+
     fn keybase(&self) -> &KeyRing<Self::SigningKeyPair> {
         &self.keybase
     }
@@ -545,112 +1531,76 @@ impl ChainEndpoint for Ckb4IbcChain {
     }
 
     fn get_signer(&self) -> Result<Signer, Error> {
-        let key_entry = self
-            .keybase()
-            .get_key(&self.config.key_name)
-            .map_err(Error::key_base)?;
+        if self.config.signer != SignerKind::Keyring {
+            return self
+                .tx_assembler_address()?
+                .to_string()
+                .parse()
+                .map_err(Error::other);
+        }
+        let key_entry = self.signing_key()?;
         let signer = key_pair_to_signer(&key_entry)?;
         Ok(signer)
     }
 
+    fn get_key(&mut self) -> Result<Self::SigningKeyPair, Error> {
+        self.signing_key()
+    }
+
     fn ibc_version(&self) -> Result<Option<Version>, Error> {
-        Ok(None)
+        Ok(Some(self.contracts_version.clone()))
     }
 
+    #[instrument(
+        name = "send_messages_and_wait_commit",
+        level = "error",
+        skip_all,
+        fields(
+            chain = %self.config.id,
+            tracking_id = %tracked_msgs.tracking_id()
+        ),
+    )]
     fn send_messages_and_wait_commit(
         &mut self,
         tracked_msgs: TrackedMsgs,
     ) -> Result<Vec<IbcEventWithHeight>, Error> {
-        let mut txs = Vec::new();
-        let mut tx_hashes = Vec::new();
-        let mut events = Vec::new();
-        let converter = self.get_converter();
+        if !self.config.submit_txs {
+            return Err(Error::submission_disabled(self.config.id.clone()));
+        }
+        self.refresh_expired_cache()?;
+        if self.config.chain_unconfirmed_txs {
+            self.check_unconfirmed_txs()?;
+        }
+        self.resume_in_flight_txs()?;
+        let _message_count = tracked_msgs.msgs.len() as u64;
         let mut result_events = Vec::new();
-        for msg in tracked_msgs.msgs {
-            let CkbTxInfo {
-                unsigned_tx,
-                envelope,
-                input_capacity,
-                event,
-            } = convert_msg_to_ckb_tx(msg, &converter)?;
-            if unsigned_tx.is_none() {
-                if let Some(e) = event {
-                    let ibc_event = IbcEventWithHeight {
-                        event: e,
-                        height: Height::new(1, 1).unwrap(),
-                        tx_hash: [0; 32],
-                    };
-                    result_events.push(ibc_event);
-                }
-                continue;
+        let msgs = self.drop_relayed_packets(tracked_msgs.msgs)?;
+        let mut msgs = self.drop_redundant_client_updates(msgs, &mut result_events)?;
+        let mut retries = 0;
+        loop {
+            let cached_cells = self.ibc_state_cache.cached_keys();
+            let dead = self.assemble_and_submit(msgs, &mut result_events)?;
+            if dead.is_empty() {
+                break;
             }
-            let unsigned_tx = unsigned_tx.unwrap();
-            if let Ok(tx) = self.complete_tx_with_secp256k1_change_and_envelope(
-                unsigned_tx,
-                input_capacity,
-                envelope,
-            ) {
-                let secret_key = self
-                    .keybase
-                    .get_key(&self.config.key_name)
-                    .map_err(Error::key_base)?
-                    .into_ckb_keypair(self.network()?)
-                    .private_key;
-                let signer = SecpSighashScriptSigner::new(Box::new(
-                    SecpCkbRawKeySigner::new_with_secret_keys(vec![secret_key]),
-                ));
-                let tx = signer
-                    .sign_tx(
-                        &tx,
-                        &ScriptGroup {
-                            script: Script::from(&self.tx_assembler_address()?),
-                            group_type: ScriptGroupType::Lock,
-                            input_indices: vec![1],
-                            output_indices: vec![],
-                        },
-                    )
-                    .unwrap();
-                tx_hashes.push(tx.hash().unpack());
-                txs.push(tx);
-                events.push(event);
+            self.refetch_cells(cached_cells)?;
+            let mut dead = self.drop_externally_relayed(dead);
+            if dead.is_empty() {
+                break;
             }
-        }
-        let resps = txs.into_iter().map(|tx| {
-            let tx: TransactionView = tx.into();
-            self.rpc_client
-                .send_transaction(&tx.inner, None)
-                .and_then(|tx_hash| {
-                    wait_ckb_transaction_committed(
-                        &self.rpc_client,
-                        tx_hash,
-                        Duration::from_secs(10),
-                        4,
-                        Duration::from_secs(600),
-                    )
-                })
-        });
-        let resps = self.rt.block_on(futures::future::join_all(resps));
-        for (i, res) in resps.iter().enumerate() {
-            match res {
-                Ok(_) => {
-                    if let Some(event) = events.get(i).unwrap().clone() {
-                        let tx_hash: [u8; 32] = tx_hashes.get(i).unwrap().clone().into();
-                        let ibc_event_with_height = IbcEventWithHeight {
-                            event,
-                            height: Height::new(1, 1).unwrap(),
-                            tx_hash,
-                        };
-                        result_events.push(ibc_event_with_height);
-                    }
-                }
-                Err(_) => {
-                    return Err(Error::send_tx("todo".into()));
-                }
+            if retries == MAX_DEAD_CELL_RETRIES {
+                let (_, _, e) = dead.remove(0);
+                return Err(e);
             }
+            retries += 1;
+            warn!(
+                "{} transactions consumed dead cells on {}, assembling them again ({retries}/{MAX_DEAD_CELL_RETRIES})",
+                dead.len(),
+                self.config.id
+            );
+            msgs = dead.into_iter().map(|(msg, _, _)| msg).collect();
         }
-        drop(converter);
-        self.clear_cache();
-
+        telemetry!(total_messages_submitted, &self.config.id, _message_count);
         Ok(result_events)
     }
 
@@ -681,63 +1631,89 @@ impl ChainEndpoint for Ckb4IbcChain {
     fn query_balance(
         &self,
         _key_name: Option<&str>,
-        _denom: Option<&str>,
+        denom: Option<&str>,
     ) -> Result<Balance, Error> {
-        let address = self.tx_assembler_address()?;
-        let lock_script: Script = address.payload().into();
-        let search_key = SearchKey {
-            script: lock_script.into(),
-            script_type: ScriptType::Lock,
-            filter: None,
-            with_data: None,
-            group_by_transaction: None,
+        telemetry!(query, &self.config.id, "query_balance");
+        let denom = match denom {
+            None | Some(CKB_DENOM) => CKB_DENOM.to_owned(),
+            Some(denom) => {
+                let registry = DenomRegistry::new(&self.config.udt_denoms);
+                registry
+                    .type_hash_of(denom)
+                    .and_then(|type_hash| registry.denom_of(type_hash))
+                    .ok_or_else(|| {
+                        Error::query(format!("no UDT is registered for the denom {denom}"))
+                    })?
+            }
         };
-        let resp = self.rpc_client.fetch_live_cells(search_key, u32::MAX, None);
-        let cells = self.rt.block_on(resp)?;
-        let capacity = cells
-            .objects
+        let amount = self
+            .query_account_balances()?
             .into_iter()
-            .filter(|c| c.output.type_.is_none())
-            .map(|c| c.output.capacity)
-            .fold(0, |prev, curr| curr.value() + prev);
-        Ok(Balance {
-            amount: capacity.to_string(),
-            denom: String::from("ckb"),
-        })
+            .find(|balance| balance.denom == denom)
+            .map(|balance| balance.amount)
+            .unwrap_or_else(|| String::from("0"));
+        Ok(Balance { amount, denom })
     }
 
     fn query_all_balances(&self, _key_name: Option<&str>) -> Result<Vec<Balance>, Error> {
-        todo!()
+        telemetry!(query, &self.config.id, "query_all_balances");
+        self.query_account_balances()
     }
 
-    fn query_denom_trace(&self, _hash: String) -> Result<DenomTrace, Error> {
-        todo!()
+    fn query_denom_trace(&self, hash: String) -> Result<DenomTrace, Error> {
+        telemetry!(query, &self.config.id, "query_denom_trace");
+        DenomRegistry::new(&self.config.udt_denoms).denom_trace(&hash)
     }
 
     fn query_commitment_prefix(&self) -> Result<CommitmentPrefix, Error> {
+        telemetry!(query, &self.config.id, "query_commitment_prefix");
         Ok(vec![0u8].try_into().unwrap())
     }
 
     fn query_application_status(&self) -> Result<ChainStatus, Error> {
-        let header = self.rt.block_on(self.rpc_client.get_tip_header())?;
-        let height = Height::new(1, header.inner.number.value()).unwrap();
-        let ts_milisec = header.inner.timestamp.value();
-        let timestamp = Timestamp::from_nanoseconds(ts_milisec * 1_000_000).unwrap();
-        Ok(ChainStatus { height, timestamp })
+        telemetry!(query, &self.config.id, "query_application_status");
+        self.rt
+            .block_on(self.chain_async.query_application_status())
     }
 
     fn query_clients(
         &self,
         _request: QueryClientStatesRequest,
     ) -> Result<Vec<IdentifiedAnyClientState>, Error> {
-        Ok(vec![])
+        telemetry!(query, &self.config.id, "query_clients");
+        // the clients are the ones of the connections, along with the configured ones
+        // which may have no connection yet
+        let (connections, _, _) = self.query_connection_and_cache()?;
+        let client_ids = connections
+            .iter()
+            .map(|connection| connection.connection_end.client_id().clone())
+            .chain(self.config.clients.keys().cloned())
+            .collect::<BTreeSet<_>>();
+        client_ids
+            .into_iter()
+            .map(|client_id| {
+                let (client_state, _) = self.query_client_state(
+                    QueryClientStateRequest {
+                        client_id: client_id.clone(),
+                        height: QueryHeight::Latest,
+                    },
+                    IncludeProof::No,
+                )?;
+                Ok(IdentifiedAnyClientState::new(client_id, client_state))
+            })
+            .collect()
     }
 
     fn query_client_state(
         &self,
-        _request: QueryClientStateRequest,
+        request: QueryClientStateRequest,
         _include_proof: IncludeProof,
     ) -> Result<(AnyClientState, Option<MerkleProof>), Error> {
+        telemetry!(query, &self.config.id, "query_client_state");
+        if is_tendermint_client(&request.client_id) {
+            let cell = self.fetch_tendermint_client(&request.client_id)?;
+            return Ok((AnyClientState::Tendermint(cell.client.client_state), None));
+        }
         Ok((
             AnyClientState::Ckb(CkbClientState {
                 chain_id: self.config.counter_chain.clone(),
@@ -748,23 +1724,26 @@ impl ChainEndpoint for Ckb4IbcChain {
 
     fn query_consensus_state(
         &self,
-        _request: QueryConsensusStateRequest,
+        request: QueryConsensusStateRequest,
         _include_proof: IncludeProof,
     ) -> Result<(AnyConsensusState, Option<MerkleProof>), Error> {
-        Ok((
-            AnyConsensusState::Ckb(CkbConsensusState {
-                timestamp: Time::now(),
-                commitment_root: CommitmentRoot::from_bytes(&[]),
-            }),
-            None,
-        ))
+        telemetry!(query, &self.config.id, "query_consensus_state");
+        let consensus_state = self.rt.block_on(
+            self.chain_async
+                .query_consensus_state(&request.client_id, request.consensus_height),
+        )?;
+        Ok((consensus_state, None))
     }
 
     fn query_consensus_state_heights(
         &self,
-        _request: QueryConsensusStateHeightsRequest,
+        request: QueryConsensusStateHeightsRequest,
     ) -> Result<Vec<Height>, Error> {
-        Ok(vec![])
+        telemetry!(query, &self.config.id, "query_consensus_state_heights");
+        self.rt.block_on(
+            self.chain_async
+                .query_consensus_state_heights(&request.client_id),
+        )
     }
 
     fn query_upgraded_client_state(
@@ -785,6 +1764,7 @@ impl ChainEndpoint for Ckb4IbcChain {
         &self,
         _request: QueryConnectionsRequest,
     ) -> Result<Vec<IdentifiedConnectionEnd>, Error> {
+        telemetry!(query, &self.config.id, "query_connections");
         let (result, _, _) = self.query_connection_and_cache()?;
         Ok(result)
     }
@@ -793,6 +1773,7 @@ impl ChainEndpoint for Ckb4IbcChain {
         &self,
         _request: QueryClientConnectionsRequest,
     ) -> Result<Vec<ConnectionId>, Error> {
+        telemetry!(query, &self.config.id, "query_client_connections");
         let (result, _, _) = self.query_connection_and_cache()?;
         Ok(result.into_iter().map(|c| c.id().clone()).collect())
     }
@@ -802,14 +1783,29 @@ impl ChainEndpoint for Ckb4IbcChain {
         request: QueryConnectionRequest,
         _include_proof: IncludeProof,
     ) -> Result<(ConnectionEnd, Option<MerkleProof>), Error> {
-        let (connections, _, _) = self.query_connection_and_cache()?;
-        let idx = get_connection_idx(&request.connection_id)? as usize;
-        let connection_end = connections
-            .into_iter()
-            .nth(idx)
-            .ok_or(Error::ckb_conn_id_invalid(
-                request.connection_id.as_str().to_string(),
-            ))?;
+        telemetry!(query, &self.config.id, "query_connection");
+        let (connections, ibc_connections, _) = self.query_connection_and_cache()?;
+        let idx = get_connection_idx(&request.connection_id)?;
+        let next_connection_number = u64::from(ibc_connections.next_connection_number);
+        // the id may come from a connections cell that has been migrated since
+        if u64::from(idx) >= next_connection_number || idx as usize >= connections.len() {
+            warn!(
+                connection_id = %request.connection_id,
+                next_connection_number,
+                "connection id exceeds the connections recorded on {}, states need resync",
+                self.config.id
+            );
+            telemetry!(
+                ckb4ibc_stale_connection_id,
+                &self.config.id,
+                request.connection_id.as_str()
+            );
+            return Err(Error::ckb_conn_id_stale(
+                request.connection_id.to_string(),
+                next_connection_number,
+            ));
+        }
+        let connection_end = connections.into_iter().nth(idx as usize).unwrap();
         Ok((connection_end.connection_end, None))
     }
 
@@ -817,6 +1813,7 @@ impl ChainEndpoint for Ckb4IbcChain {
         &self,
         _request: QueryConnectionChannelsRequest,
     ) -> Result<Vec<IdentifiedChannelEnd>, Error> {
+        telemetry!(query, &self.config.id, "query_connection_channels");
         self.query_channels(QueryChannelsRequest { pagination: None })
     }
 
@@ -824,51 +1821,9 @@ impl ChainEndpoint for Ckb4IbcChain {
         &self,
         request: QueryChannelsRequest,
     ) -> Result<Vec<IdentifiedChannelEnd>, Error> {
-        let channel_code_hash = self.get_converter().get_channel_code_hash();
-        let script = Script::new_builder()
-            .code_hash(channel_code_hash)
-            .args("".pack())
-            .hash_type(ScriptHashType::Type.into())
-            .build();
-        let search_key = get_search_key(script);
-        let (limit, index) = {
-            if let Some(pagination) = request.pagination {
-                (pagination.limit as u32, pagination.offset as u32)
-            } else {
-                (100, 0)
-            }
-        };
-        let json_bytes = JsonBytes::from_vec(index.to_be_bytes().to_vec());
-        let cursor = Some(json_bytes);
-        let cells_rpc_result = self.rpc_client.fetch_live_cells(search_key, limit, cursor);
-        let txs_rpc_result = self
-            .rt
-            .block_on(cells_rpc_result)?
-            .objects
-            .into_iter()
-            .map(|cell| self.rpc_client.get_transaction(&cell.out_point.tx_hash));
-        let channel_ends = self
-            .rt
-            .block_on(futures::future::join_all(txs_rpc_result))
-            .into_iter()
-            .flatten()
-            .flatten()
-            .filter(|resp| resp.tx_status.status == Status::Committed && resp.transaction.is_some())
-            .flat_map(|tx| {
-                let tx_resp = tx.transaction.unwrap();
-                let tx = match tx_resp.inner {
-                    ckb_jsonrpc_types::Either::Left(r) => r,
-                    ckb_jsonrpc_types::Either::Right(json_bytes) => {
-                        let bytes = json_bytes.as_bytes();
-                        let tx: TransactionView = serde_json::from_slice(bytes).unwrap();
-                        tx
-                    }
-                };
-                extract_channel_end_from_tx(tx)
-            })
-            .map(|e| e.0)
-            .collect();
-        Ok(channel_ends)
+        telemetry!(query, &self.config.id, "query_channels");
+        let channel_ends = self.rt.block_on(self.chain_async.query_channels())?;
+        Ok(paginate(channel_ends, request.pagination))
     }
 
     fn query_channel(
@@ -876,6 +1831,7 @@ impl ChainEndpoint for Ckb4IbcChain {
         request: QueryChannelRequest,
         _include_proof: IncludeProof,
     ) -> Result<(ChannelEnd, Option<MerkleProof>), Error> {
+        telemetry!(query, &self.config.id, "query_channel");
         if let Ok(r) = self.fetch_channel_cell_and_extract(
             request.channel_id.clone(),
             request.port_id.clone(),
@@ -891,9 +1847,36 @@ impl ChainEndpoint for Ckb4IbcChain {
 
     fn query_channel_client_state(
         &self,
-        _request: QueryChannelClientStateRequest,
+        request: QueryChannelClientStateRequest,
     ) -> Result<Option<IdentifiedAnyClientState>, Error> {
-        Ok(None)
+        telemetry!(query, &self.config.id, "query_channel_client_state");
+        let (channel_end, _) = self.query_channel(
+            QueryChannelRequest {
+                port_id: request.port_id,
+                channel_id: request.channel_id,
+                height: QueryHeight::Latest,
+            },
+            IncludeProof::No,
+        )?;
+        let Some(connection_id) = channel_end.connection_hops().first() else {
+            return Ok(None);
+        };
+        let (connection_end, _) = self.query_connection(
+            QueryConnectionRequest {
+                connection_id: connection_id.clone(),
+                height: QueryHeight::Latest,
+            },
+            IncludeProof::No,
+        )?;
+        let client_id = connection_end.client_id().clone();
+        let (client_state, _) = self.query_client_state(
+            QueryClientStateRequest {
+                client_id: client_id.clone(),
+                height: QueryHeight::Latest,
+            },
+            IncludeProof::No,
+        )?;
+        Ok(Some(IdentifiedAnyClientState::new(client_id, client_state)))
     }
 
     fn query_packet_commitment(
@@ -901,11 +1884,21 @@ impl ChainEndpoint for Ckb4IbcChain {
         request: QueryPacketCommitmentRequest,
         _include_proof: IncludeProof,
     ) -> Result<(Vec<u8>, Option<MerkleProof>), Error> {
-        let (ibc_packet, _) = self.fetch_packet_cell_and_extract(
-            &request.channel_id,
-            &request.port_id,
-            request.sequence,
-        )?;
+        telemetry!(query, &self.config.id, "query_packet_commitment");
+        // the packet cell is consumed once the packet is acknowledged and its
+        // acknowledgement is processed, which clears the commitment as well
+        let packet = self
+            .rt
+            .block_on(self.chain_async.fetch_packets(
+                &request.channel_id,
+                &request.port_id,
+                vec![request.sequence],
+            ))?
+            .into_iter()
+            .next();
+        let Some((ibc_packet, _)) = packet else {
+            return Ok((vec![], None));
+        };
         if ibc_packet.status != PacketStatus::Send {
             Ok((vec![], None))
         } else {
@@ -919,7 +1912,7 @@ impl ChainEndpoint for Ckb4IbcChain {
                         .try_into()
                         .unwrap(),
                     sequence: ibc_packet.packet.sequence,
-                    owner: Default::default(),
+                    owner: self.config.packet_owner(&request.port_id),
                 }
                 .get_search_args(),
                 None,
@@ -929,9 +1922,39 @@ impl ChainEndpoint for Ckb4IbcChain {
 
     fn query_packet_commitments(
         &self,
-        _request: QueryPacketCommitmentsRequest,
+        request: QueryPacketCommitmentsRequest,
     ) -> Result<(Vec<Sequence>, Height), Error> {
-        todo!()
+        telemetry!(query, &self.config.id, "query_packet_commitments");
+        let height = self.query_application_status()?.height;
+        let ((_, ibc_channel), _) = match self.rt.block_on(self.chain_async.fetch_channel(
+            &request.channel_id,
+            &request.port_id,
+            false,
+        )) {
+            Ok(channel) => channel,
+            Err(_) => self.rt.block_on(self.chain_async.fetch_channel(
+                &request.channel_id,
+                &request.port_id,
+                true,
+            ))?,
+        };
+        // the packets of unordered channels may be acknowledged in any order, so all the
+        // packets sent on the channel are checked
+        let sequences = (1..u64::from(ibc_channel.sequence.next_send_packet))
+            .map(Sequence::from)
+            .collect();
+        let packets = self.rt.block_on(self.chain_async.fetch_packets(
+            &request.channel_id,
+            &request.port_id,
+            sequences,
+        ))?;
+        let mut sequences = packets
+            .into_iter()
+            .filter(|(packet, _)| packet.status == PacketStatus::Send)
+            .map(|(packet, _)| Sequence::from(packet.packet.sequence as u64))
+            .collect::<Vec<_>>();
+        sequences.sort();
+        Ok((paginate(sequences, request.pagination), height))
     }
 
     fn query_packet_receipt(
@@ -939,6 +1962,7 @@ impl ChainEndpoint for Ckb4IbcChain {
         request: QueryPacketReceiptRequest,
         _include_proof: IncludeProof,
     ) -> Result<(Vec<u8>, Option<MerkleProof>), Error> {
+        telemetry!(query, &self.config.id, "query_packet_receipt");
         let (ibc_packet, _) = self.fetch_packet_cell_and_extract(
             &request.channel_id,
             &request.port_id,
@@ -957,7 +1981,7 @@ impl ChainEndpoint for Ckb4IbcChain {
                         .try_into()
                         .unwrap(),
                     sequence: ibc_packet.packet.sequence,
-                    owner: Default::default(),
+                    owner: self.config.packet_owner(&request.port_id),
                 }
                 .get_search_args(),
                 None,
@@ -977,6 +2001,7 @@ impl ChainEndpoint for Ckb4IbcChain {
         request: QueryPacketAcknowledgementRequest,
         _include_proof: IncludeProof,
     ) -> Result<(Vec<u8>, Option<MerkleProof>), Error> {
+        telemetry!(query, &self.config.id, "query_packet_acknowledgement");
         let (ibc_packet, _) = self.fetch_packet_cell_and_extract(
             &request.channel_id,
             &request.port_id,
@@ -993,33 +2018,46 @@ impl ChainEndpoint for Ckb4IbcChain {
         &self,
         request: QueryPacketAcknowledgementsRequest,
     ) -> Result<(Vec<Sequence>, Height), Error> {
+        telemetry!(query, &self.config.id, "query_packet_acknowledgements");
         let port_id = request.port_id;
         let channel_id = request.channel_id;
-        let result = request
-            .packet_commitment_sequences
+        let packets = self.rt.block_on(self.chain_async.fetch_packets(
+            &channel_id,
+            &port_id,
+            request.packet_commitment_sequences,
+        ))?;
+        let result = packets
             .into_iter()
-            .flat_map(|seq| self.fetch_packet_cell_and_extract(&channel_id, &port_id, seq))
             .filter(|(packet, _)| packet.status == PacketStatus::InboxAck)
             .map(|(p, _)| Sequence::from(p.packet.sequence as u64))
             .collect::<Vec<_>>();
-        Ok((result, Height::new(u64::MAX, u64::MAX).unwrap()))
+        let height = self.query_application_status()?.height;
+        Ok((result, height))
     }
 
     fn query_unreceived_acknowledgements(
         &self,
         request: QueryUnreceivedAcksRequest,
     ) -> Result<Vec<Sequence>, Error> {
+        telemetry!(query, &self.config.id, "query_unreceived_acknowledgements");
         let port_id = request.port_id;
         let channel_id = request.channel_id;
-        let mut data = self.packet_input_data.borrow_mut();
-        let result = request
-            .packet_ack_sequences
+        let packets = self.rt.block_on(self.chain_async.fetch_packets(
+            &channel_id,
+            &port_id,
+            request.packet_ack_sequences,
+        ))?;
+        let result = packets
             .into_iter()
-            .flat_map(|seq| self.fetch_packet_cell_and_extract(&channel_id, &port_id, seq))
             .filter(|(packet, _)| packet.status == PacketStatus::Send)
             .map(|(p, cell_input)| {
                 let seq = Sequence::from(p.packet.sequence as u64);
-                data.insert((channel_id.clone(), port_id.clone(), seq), cell_input);
+                self.ibc_state_cache.insert_packet_input(
+                    channel_id.clone(),
+                    port_id.clone(),
+                    seq,
+                    cell_input,
+                );
                 seq
             })
             .collect::<Vec<_>>();
@@ -1028,10 +2066,34 @@ impl ChainEndpoint for Ckb4IbcChain {
 
     fn query_next_sequence_receive(
         &self,
-        _request: QueryNextSequenceReceiveRequest,
+        request: QueryNextSequenceReceiveRequest,
         _include_proof: IncludeProof,
     ) -> Result<(Sequence, Option<MerkleProof>), Error> {
-        todo!()
+        telemetry!(query, &self.config.id, "query_next_sequence_receive");
+        let cached = self.ibc_state_cache.channel(&request.channel_id);
+        telemetry!(
+            ckb4ibc_cache_lookup,
+            &self.config.id,
+            "channel",
+            cached.is_some()
+        );
+        let channel = match cached {
+            Some(channel) => channel,
+            None => {
+                self.fetch_channel_cell_and_extract(
+                    request.channel_id.clone(),
+                    request.port_id,
+                    true,
+                )?;
+                self.ibc_state_cache
+                    .channel(&request.channel_id)
+                    .ok_or_else(|| {
+                        Error::query(format!("no channel cell of {}", request.channel_id))
+                    })?
+            }
+        };
+        let sequence = Sequence::from(u64::from(channel.sequence.next_recv_packet));
+        Ok((sequence, None))
     }
 
     fn query_txs(&self, _request: QueryTxRequest) -> Result<Vec<IbcEventWithHeight>, Error> {
@@ -1049,7 +2111,9 @@ impl ChainEndpoint for Ckb4IbcChain {
         &self,
         _request: QueryHostConsensusStateRequest,
     ) -> Result<Self::ConsensusState, Error> {
-        todo!()
+        telemetry!(query, &self.config.id, "query_host_consensus_state");
+        self.rt
+            .block_on(self.chain_async.query_host_consensus_state())
     }
 
     fn build_client_state(
@@ -1111,35 +2175,105 @@ impl ChainEndpoint for Ckb4IbcChain {
     fn build_connection_proofs_and_client_state(
         &self,
         _message_type: ConnectionMsgType,
-        _connection_id: &ConnectionId,
+        connection_id: &ConnectionId,
         _client_id: &ClientId,
         height: Height,
     ) -> Result<(Option<AnyClientState>, Proofs), Error> {
+        // all the connection ends are held by the connections cell
+        let proof = self
+            .fetch_ibc_cell_proof(|lock| is_connections_lock(lock, &self.config), height)?
+            .ok_or_else(|| {
+                Error::conn_proof(connection_id.clone(), "no connections cell".to_owned())
+            })?;
         Ok((
             Some(AnyClientState::Ckb(CkbClientState {
                 chain_id: self.id(),
             })),
-            get_dummy_merkle_proof(height),
+            get_ibc_cell_merkle_proof(rlp::encode(&proof).to_vec(), height),
         ))
     }
 
     fn build_channel_proofs(
         &self,
-        _port_id: &PortId,
-        _channel_id: &ChannelId,
+        port_id: &PortId,
+        channel_id: &ChannelId,
         height: Height,
     ) -> Result<Proofs, Error> {
-        Ok(get_dummy_merkle_proof(height))
+        let channel_args = ChannelArgs {
+            client_id: Default::default(),
+            open: false,
+            channel_id: get_channel_idx(channel_id)?,
+            port_id: convert_port_id_to_array(port_id)?,
+        };
+        let proof = self
+            .fetch_ibc_cell_proof(
+                |lock| is_channel_lock(lock, &self.config, &channel_args),
+                height,
+            )?
+            .ok_or_else(|| {
+                Error::chan_proof(
+                    port_id.clone(),
+                    channel_id.clone(),
+                    "no channel cell".to_owned(),
+                )
+            })?;
+        Ok(get_ibc_cell_merkle_proof(
+            rlp::encode(&proof).to_vec(),
+            height,
+        ))
     }
 
+    /// The packets received and acknowledged are proven by their packet cells, while
+    /// the timeouts are proven by the channel cell, which holds the sequences and the
+    /// state of the channel
     fn build_packet_proofs(
         &self,
-        _packet_type: PacketMsgType,
-        _port_id: PortId,
-        _channel_id: ChannelId,
-        _sequence: Sequence,
+        packet_type: PacketMsgType,
+        port_id: PortId,
+        channel_id: ChannelId,
+        sequence: Sequence,
         height: Height,
     ) -> Result<Proofs, Error> {
-        Ok(get_dummy_merkle_proof(height))
+        let config = &self.config;
+        let channel_idx = get_channel_idx(&channel_id)?;
+        let port_id_in_args = convert_port_id_to_array(&port_id)?;
+        let proof = match packet_type {
+            PacketMsgType::Recv | PacketMsgType::Ack => {
+                let packet_args = PacketArgs {
+                    channel_id: channel_idx,
+                    port_id: port_id_in_args,
+                    sequence: u64::from(sequence) as u16,
+                    owner: config.packet_owner(&port_id),
+                };
+                self.fetch_ibc_cell_proof(
+                    |lock| is_packet_lock(lock, config, &packet_args),
+                    height,
+                )?
+            }
+            _ => {
+                let channel_args = ChannelArgs {
+                    client_id: Default::default(),
+                    open: false,
+                    channel_id: channel_idx,
+                    port_id: port_id_in_args,
+                };
+                self.fetch_ibc_cell_proof(
+                    |lock| is_channel_lock(lock, config, &channel_args),
+                    height,
+                )?
+            }
+        };
+        let proof = proof.ok_or_else(|| {
+            Error::packet_proof(
+                port_id,
+                channel_id,
+                u64::from(sequence),
+                format!("no cell to prove {packet_type:?}"),
+            )
+        })?;
+        Ok(get_ibc_cell_merkle_proof(
+            rlp::encode(&proof).to_vec(),
+            height,
+        ))
     }
 }
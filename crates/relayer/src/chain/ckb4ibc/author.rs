@@ -0,0 +1,314 @@
+//! Authors of the IBC transactions observed on CKB.
+//!
+//! With the `verify_tx_authors` option, the event monitor recovers the signers of
+//! the transactions mutating the IBC cells from the secp256k1 signatures of their
+//! witnesses, and tags each transaction as sent by the relayer itself, by one of the
+//! `known_relayers`, or by an unknown party which may be tampering with the cells.
+
+use std::collections::HashSet;
+use std::fmt::{Display, Error as FmtError, Formatter};
+use std::str::FromStr;
+
+use ckb_hash::{blake2b_256, new_blake2b};
+use ckb_jsonrpc_types::TransactionView;
+use ckb_sdk::constants::SIGHASH_TYPE_HASH;
+use ckb_sdk::Address;
+use ckb_types::bytes::Bytes;
+use ckb_types::core::TransactionView as CoreTransactionView;
+use ckb_types::packed::{self, WitnessArgs};
+use ckb_types::prelude::*;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, Secp256k1};
+use serde::{Deserialize, Serialize};
+
+use crate::chain::ckb::prelude::CkbReader;
+use crate::error::Error;
+
+use super::utils::decode_transaction;
+
+/// Lock args of a secp256k1 sighash lock, the Blake160 hash of the public key
+pub type LockArgs = [u8; 20];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxAuthor {
+    /// Signed by the relayer account
+    #[serde(rename = "self")]
+    Own,
+    /// Signed by one of the `known_relayers`
+    KnownPeer,
+    /// Signed by none of the known accounts, or by no secp256k1 key at all
+    Unknown,
+}
+
+impl TxAuthor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TxAuthor::Own => "self",
+            TxAuthor::KnownPeer => "known_peer",
+            TxAuthor::Unknown => "unknown",
+        }
+    }
+}
+
+impl Display for TxAuthor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Accounts expected to sign the IBC transactions of a chain
+#[derive(Clone, Debug, Default)]
+pub struct KnownAuthors {
+    own: HashSet<LockArgs>,
+    peers: HashSet<LockArgs>,
+}
+
+impl KnownAuthors {
+    /// Both the `own` accounts, i.e. the relayer account and its fee payer, and the `peers`
+    /// are addresses of secp256k1 sighash locks
+    pub fn new(own: &[Address], peers: &[String]) -> Result<Self, Error> {
+        let own = own
+            .iter()
+            .map(sighash_lock_args)
+            .collect::<Result<_, _>>()?;
+        let peers = peers
+            .iter()
+            .map(|peer| {
+                let address = Address::from_str(peer).map_err(Error::other)?;
+                sighash_lock_args(&address)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { own, peers })
+    }
+
+    /// Author of a transaction signed by `signers`, the relayer itself taking
+    /// precedence over its peers
+    pub fn classify(&self, signers: &[LockArgs]) -> TxAuthor {
+        if signers.iter().any(|signer| self.own.contains(signer)) {
+            TxAuthor::Own
+        } else if signers.iter().any(|signer| self.peers.contains(signer)) {
+            TxAuthor::KnownPeer
+        } else {
+            TxAuthor::Unknown
+        }
+    }
+}
+
+fn sighash_lock_args(address: &Address) -> Result<LockArgs, Error> {
+    let script: packed::Script = address.payload().into();
+    let args = script.args().raw_data();
+    if script.code_hash() != SIGHASH_TYPE_HASH.pack() || args.len() != 20 {
+        return Err(Error::other_error(format!(
+            "{address} is not the address of a secp256k1 sighash lock"
+        )));
+    }
+    let mut lock_args = [0u8; 20];
+    lock_args.copy_from_slice(&args);
+    Ok(lock_args)
+}
+
+/// Lock scripts of the cells consumed by the transaction, in the order of its inputs
+pub async fn fetch_input_locks<R: CkbReader>(
+    rpc_client: &R,
+    tx: &TransactionView,
+) -> Result<Vec<packed::Script>, Error> {
+    let mut locks = Vec::with_capacity(tx.inner.inputs.len());
+    for input in &tx.inner.inputs {
+        let out_point = &input.previous_output;
+        let tx_hash = &out_point.tx_hash;
+        let previous_tx = rpc_client
+            .get_transaction(tx_hash)
+            .await?
+            .and_then(|resp| resp.transaction)
+            .ok_or_else(|| Error::query(format!("transaction {tx_hash:#x} is not found")))?;
+        let previous_tx = decode_transaction(previous_tx)?;
+        let output = previous_tx
+            .inner
+            .outputs
+            .get(out_point.index.value() as usize)
+            .ok_or_else(|| {
+                Error::query(format!(
+                    "output {} of transaction {tx_hash:#x} is not found",
+                    out_point.index.value()
+                ))
+            })?;
+        locks.push(output.lock.clone().into());
+    }
+    Ok(locks)
+}
+
+/// Lock args of the secp256k1 sighash locks of the inputs whose signature in the
+/// witnesses of the transaction recovers their public key. The inputs are grouped by
+/// lock script, the signature of each group being in the witness of its first input
+pub fn recover_signers(tx: &CoreTransactionView, input_locks: &[packed::Script]) -> Vec<LockArgs> {
+    let witnesses = tx.witnesses();
+    let sighash_code_hash = SIGHASH_TYPE_HASH.pack();
+    let mut groups: Vec<(packed::Byte32, packed::Script, Vec<usize>)> = vec![];
+    for (index, lock) in input_locks.iter().enumerate() {
+        if lock.code_hash() != sighash_code_hash || lock.args().raw_data().len() != 20 {
+            continue;
+        }
+        let lock_hash = lock.calc_script_hash();
+        match groups.iter_mut().find(|(hash, _, _)| hash == &lock_hash) {
+            Some((_, _, indexes)) => indexes.push(index),
+            None => groups.push((lock_hash, lock.clone(), vec![index])),
+        }
+    }
+
+    let extra_witnesses = (input_locks.len()..witnesses.len())
+        .filter_map(|index| witnesses.get(index))
+        .collect::<Vec<_>>();
+    groups
+        .into_iter()
+        .filter_map(|(_, lock, indexes)| {
+            let group_witnesses = indexes
+                .iter()
+                .filter_map(|index| witnesses.get(*index))
+                .collect::<Vec<_>>();
+            let public_key = recover_group_key(&tx.hash(), &group_witnesses, &extra_witnesses)?;
+            let mut lock_args = [0u8; 20];
+            lock_args.copy_from_slice(&blake2b_256(public_key.serialize())[..20]);
+            (lock.args().raw_data().as_ref() == lock_args).then_some(lock_args)
+        })
+        .collect()
+}
+
+/// Public key signing the sighash-all message of a group of inputs
+fn recover_group_key(
+    tx_hash: &packed::Byte32,
+    group_witnesses: &[packed::Bytes],
+    extra_witnesses: &[packed::Bytes],
+) -> Option<secp256k1::PublicKey> {
+    let (first_witness, other_witnesses) = group_witnesses.split_first()?;
+    let witness_args = WitnessArgs::from_slice(&first_witness.raw_data()).ok()?;
+    let signature = witness_args.lock().to_opt()?.raw_data();
+    if signature.len() != 65 {
+        return None;
+    }
+    let zeroed_witness = witness_args
+        .as_builder()
+        .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+        .build();
+
+    let mut blake2b = new_blake2b();
+    blake2b.update(&tx_hash.raw_data());
+    let zeroed_witness = zeroed_witness.as_bytes();
+    blake2b.update(&(zeroed_witness.len() as u64).to_le_bytes());
+    blake2b.update(&zeroed_witness);
+    for witness in other_witnesses.iter().chain(extra_witnesses) {
+        let witness = witness.raw_data();
+        blake2b.update(&(witness.len() as u64).to_le_bytes());
+        blake2b.update(&witness);
+    }
+    let mut digest = [0u8; 32];
+    blake2b.finalize(&mut digest);
+
+    let recovery_id = RecoveryId::from_i32(signature[64] as i32).ok()?;
+    let signature = RecoverableSignature::from_compact(&signature[..64], recovery_id).ok()?;
+    let message = Message::from_slice(&digest).ok()?;
+    Secp256k1::verification_only()
+        .recover_ecdsa(&message, &signature)
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_hash::blake2b_256;
+    use ckb_sdk::constants::SIGHASH_TYPE_HASH;
+    use ckb_sdk::{Address, AddressPayload, NetworkType};
+    use ckb_types::core::{Capacity, ScriptHashType, TransactionBuilder};
+    use ckb_types::packed::{self, CellInput, CellOutput, OutPoint};
+    use ckb_types::prelude::*;
+
+    use super::{recover_signers, KnownAuthors, LockArgs, TxAuthor};
+    use crate::chain::ckb::signer::sign;
+    use crate::config::AddressType;
+    use crate::keyring::Secp256k1KeyPair;
+
+    fn key_pair(private_key: &str) -> Secp256k1KeyPair {
+        Secp256k1KeyPair::from_private_key(private_key, &AddressType::Ckb { is_mainnet: false }, "")
+            .unwrap()
+    }
+
+    fn lock_args(key: &Secp256k1KeyPair) -> LockArgs {
+        let mut lock_args = [0u8; 20];
+        lock_args.copy_from_slice(&blake2b_256(key.public_key.serialize())[..20]);
+        lock_args
+    }
+
+    fn sighash_lock(key: &Secp256k1KeyPair) -> packed::Script {
+        packed::Script::new_builder()
+            .code_hash(SIGHASH_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(lock_args(key).to_vec().pack())
+            .build()
+    }
+
+    #[test]
+    fn test_recover_signers() {
+        let key = key_pair("0x63d86723e08f0f813a36ce6aa123bb2289d90680ae1e99d4de8cdb334553f24d");
+        let other_key =
+            key_pair("0x1111111111111111111111111111111111111111111111111111111111111111");
+        let lock = sighash_lock(&key);
+        let input_locks = vec![lock.clone(), lock.clone()];
+        let inputs = input_locks
+            .iter()
+            .map(|lock| {
+                CellOutput::new_builder()
+                    .lock(lock.clone())
+                    .build_exact_capacity(Capacity::zero())
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let tx = TransactionBuilder::default()
+            .inputs((0u32..2).map(|index| {
+                CellInput::new(OutPoint::new_builder().index(index.pack()).build(), 0)
+            }))
+            .output(inputs[0].clone())
+            .output_data(Default::default())
+            .build();
+
+        let signed_tx = sign(tx.clone(), &inputs, vec![], key.clone()).unwrap();
+        let signers = recover_signers(&signed_tx, &input_locks);
+        assert_eq!(signers, vec![lock_args(&key)]);
+
+        // a signature of another key doesn't match the lock args of the inputs
+        let forged_tx = sign(tx, &inputs, vec![], other_key).unwrap();
+        assert!(recover_signers(&forged_tx, &input_locks).is_empty());
+    }
+
+    #[test]
+    fn test_classify_authors() {
+        let address = |key: &Secp256k1KeyPair| {
+            Address::new(
+                NetworkType::Testnet,
+                AddressPayload::from_pubkey(&key.public_key),
+                true,
+            )
+        };
+        let own = key_pair("0x63d86723e08f0f813a36ce6aa123bb2289d90680ae1e99d4de8cdb334553f24d");
+        let peer = key_pair("0x1111111111111111111111111111111111111111111111111111111111111111");
+        let stranger =
+            key_pair("0x2222222222222222222222222222222222222222222222222222222222222222");
+        let fee_payer =
+            key_pair("0x3333333333333333333333333333333333333333333333333333333333333333");
+        let authors = KnownAuthors::new(
+            &[address(&own), address(&fee_payer)],
+            &[address(&peer).to_string()],
+        )
+        .unwrap();
+
+        let own = lock_args(&own);
+        let peer = lock_args(&peer);
+        let stranger = lock_args(&stranger);
+        let fee_payer = lock_args(&fee_payer);
+        assert_eq!(authors.classify(&[stranger, own]), TxAuthor::Own);
+        assert_eq!(authors.classify(&[fee_payer]), TxAuthor::Own);
+        assert_eq!(authors.classify(&[peer]), TxAuthor::KnownPeer);
+        assert_eq!(authors.classify(&[stranger]), TxAuthor::Unknown);
+        assert_eq!(authors.classify(&[]), TxAuthor::Unknown);
+
+        assert!(KnownAuthors::new(&[], &["not an address".to_owned()]).is_err());
+    }
+}
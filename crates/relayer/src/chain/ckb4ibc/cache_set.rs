@@ -42,6 +42,14 @@ where
         self.set.contains(element)
     }
 
+    pub fn remove(&mut self, element: &T) -> bool {
+        if !self.set.remove(element) {
+            return false;
+        }
+        self.times.retain(|e| e != element);
+        true
+    }
+
     fn pop(&mut self) {
         let element = self.times.pop_front().unwrap();
         self.set.remove(&element);
@@ -78,4 +86,19 @@ mod tests {
         assert!(cache_set.has(&4));
         assert!(cache_set.has(&5));
     }
+
+    #[test]
+    fn cache_set_remove_test() {
+        let mut cache_set = CacheSet::<u32>::new(2);
+        cache_set.insert(1);
+        cache_set.insert(2);
+
+        assert!(cache_set.remove(&1));
+        assert!(!cache_set.remove(&1));
+        assert!(!cache_set.has(&1));
+
+        cache_set.insert(3);
+        assert!(cache_set.has(&2));
+        assert!(cache_set.has(&3));
+    }
 }
@@ -0,0 +1,248 @@
+//! Live IBC cells of a CKB chain, decoded for debugging.
+//!
+//! The cells of the type scripts configured for the chain are listed along with their
+//! outpoints and capacities, and the states they hold are decoded by the extractor, so
+//! that the operators debugging a stuck channel see what the contracts see.
+
+use core::fmt::{Display, Error as FmtError, Formatter};
+use std::str::FromStr;
+
+use ckb_ics_axon::handler::{IbcPacket, PacketStatus};
+use ckb_sdk::constants::TYPE_ID_CODE_HASH;
+use ckb_sdk::rpc::ckb_indexer::Cell;
+use ckb_types::packed::Script;
+use ckb_types::prelude::{Builder, Pack, Unpack};
+use ckb_types::H256;
+use ibc_relayer_types::core::ics03_connection::connection::IdentifiedConnectionEnd;
+use ibc_relayer_types::core::ics04_channel::channel::IdentifiedChannelEnd;
+use serde::{Deserialize, Serialize};
+
+use crate::chain::ckb::prelude::CellSearcher;
+use crate::config::ckb4ibc::ChainConfig;
+use crate::error::Error;
+
+use super::extractor::{
+    extract_channel_end_from_tx, extract_connections_from_tx, extract_ibc_packet_from_tx,
+};
+use super::footprint::{fetch_all_cells, fetch_cell_transaction};
+use super::utils::{get_connection_search_key, get_script_hash, get_search_key};
+
+/// Type script of the IBC cells
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IbcCellKind {
+    Client,
+    Connection,
+    Channel,
+    Packet,
+}
+
+impl IbcCellKind {
+    pub const ALL: [IbcCellKind; 4] = [
+        IbcCellKind::Client,
+        IbcCellKind::Connection,
+        IbcCellKind::Channel,
+        IbcCellKind::Packet,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Client => "client",
+            Self::Connection => "connection",
+            Self::Channel => "channel",
+            Self::Packet => "packet",
+        }
+    }
+}
+
+impl Display for IbcCellKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for IbcCellKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("unknown IBC cell kind '{s}'"))
+    }
+}
+
+/// Packet held by a packet cell
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PacketCell {
+    pub sequence: u64,
+    pub source_port_id: String,
+    pub source_channel_id: String,
+    pub destination_port_id: String,
+    pub destination_channel_id: String,
+    pub status: String,
+    /// Size of the packet data, in bytes
+    pub data_size: usize,
+}
+
+impl From<IbcPacket> for PacketCell {
+    fn from(ibc_packet: IbcPacket) -> Self {
+        let status = [
+            (PacketStatus::Send, "send"),
+            (PacketStatus::Recv, "recv"),
+            (PacketStatus::InboxAck, "inbox_ack"),
+            (PacketStatus::OutboxAck, "outbox_ack"),
+            (PacketStatus::Ack, "ack"),
+        ]
+        .into_iter()
+        .find(|(status, _)| *status == ibc_packet.status)
+        .map_or("unknown", |(_, name)| name);
+        let packet = ibc_packet.packet;
+        Self {
+            sequence: packet.sequence as u64,
+            source_port_id: packet.source_port_id,
+            source_channel_id: packet.source_channel_id,
+            destination_port_id: packet.destination_port_id,
+            destination_channel_id: packet.destination_channel_id,
+            status: status.to_owned(),
+            data_size: packet.data.len(),
+        }
+    }
+}
+
+/// State decoded from an IBC cell
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IbcCellState {
+    Connections(Vec<IdentifiedConnectionEnd>),
+    Channel {
+        channel: IdentifiedChannelEnd,
+        next_send_packet: u64,
+        next_recv_ack: u64,
+    },
+    Packet(PacketCell),
+    /// The data of the client cells is only decoded by their light client
+    Client {
+        data_size: usize,
+    },
+}
+
+/// Live IBC cell
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IbcCell {
+    pub kind: IbcCellKind,
+    /// Outpoint of the cell, as `tx_hash:index`
+    pub out_point: String,
+    /// Capacity of the cell, in shannons
+    pub capacity: u64,
+    /// Block in which the cell was created
+    pub block_number: u64,
+    pub state: IbcCellState,
+}
+
+fn format_out_point(tx_hash: &H256, index: u32) -> String {
+    format!("{tx_hash:#x}:{index}")
+}
+
+/// Live cells of `kind`, or of all the kinds if it is not given
+pub async fn collect_ibc_cells<R: CellSearcher + Sync>(
+    rpc_client: &R,
+    config: &ChainConfig,
+    kind: Option<IbcCellKind>,
+) -> Result<Vec<IbcCell>, Error> {
+    let kinds = kind.map_or(IbcCellKind::ALL.to_vec(), |kind| vec![kind]);
+
+    let mut ibc_cells = vec![];
+    for kind in kinds {
+        match kind {
+            IbcCellKind::Client => {
+                for client_type_args in config.all_client_type_args() {
+                    let client_cell = rpc_client
+                        .search_cell_by_typescript(
+                            &TYPE_ID_CODE_HASH.pack(),
+                            &client_type_args.as_bytes().to_owned(),
+                        )
+                        .await?;
+                    if let Some(cell) = client_cell {
+                        ibc_cells.push(IbcCell {
+                            kind,
+                            out_point: format_out_point(
+                                &cell.out_point.tx_hash().unpack(),
+                                cell.out_point.index().unpack(),
+                            ),
+                            capacity: cell.output.capacity().unpack(),
+                            block_number: cell.block_number,
+                            state: IbcCellState::Client {
+                                data_size: cell.output_data.len(),
+                            },
+                        });
+                    }
+                }
+            }
+            IbcCellKind::Connection => {
+                let search_key = get_connection_search_key(config);
+                for cell in fetch_all_cells(rpc_client, search_key).await? {
+                    let tx = fetch_cell_transaction(rpc_client, &cell).await?;
+                    let (connections, _) = extract_connections_from_tx(tx)?;
+                    ibc_cells.push(ibc_cell(
+                        kind,
+                        &cell,
+                        IbcCellState::Connections(connections),
+                    ));
+                }
+            }
+            IbcCellKind::Channel => {
+                let script = Script::new_builder()
+                    .code_hash(get_script_hash(&config.channel_type_args))
+                    .args("".pack())
+                    .build();
+                for cell in fetch_all_cells(rpc_client, get_search_key(script)).await? {
+                    let tx = fetch_cell_transaction(rpc_client, &cell).await?;
+                    let (channel, ibc_channel) = extract_channel_end_from_tx(tx)?;
+                    let state = IbcCellState::Channel {
+                        channel,
+                        next_send_packet: u64::from(ibc_channel.sequence.next_send_packet),
+                        next_recv_ack: u64::from(ibc_channel.sequence.next_recv_ack),
+                    };
+                    ibc_cells.push(ibc_cell(kind, &cell, state));
+                }
+            }
+            IbcCellKind::Packet => {
+                let script = Script::new_builder()
+                    .code_hash(get_script_hash(&config.packet_type_args))
+                    .args("".pack())
+                    .build();
+                for cell in fetch_all_cells(rpc_client, get_search_key(script)).await? {
+                    let tx = fetch_cell_transaction(rpc_client, &cell).await?;
+                    let ibc_packet = extract_ibc_packet_from_tx(tx)?;
+                    let state = IbcCellState::Packet(ibc_packet.into());
+                    ibc_cells.push(ibc_cell(kind, &cell, state));
+                }
+            }
+        }
+    }
+    Ok(ibc_cells)
+}
+
+fn ibc_cell(kind: IbcCellKind, cell: &Cell, state: IbcCellState) -> IbcCell {
+    IbcCell {
+        kind,
+        out_point: format_out_point(&cell.out_point.tx_hash, cell.out_point.index.value()),
+        capacity: cell.output.capacity.value(),
+        block_number: cell.block_number.value(),
+        state,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IbcCellKind;
+
+    #[test]
+    fn test_ibc_cell_kind_from_str() {
+        for kind in IbcCellKind::ALL {
+            assert_eq!(kind.as_str().parse::<IbcCellKind>(), Ok(kind));
+        }
+        assert!("cell".parse::<IbcCellKind>().is_err());
+    }
+}
@@ -0,0 +1,476 @@
+//! Async queries of a CKB chain of the IBC contracts.
+//!
+//! The blocking `ChainEndpoint` of `Ckb4IbcChain` runs each query to completion on the
+//! runtime of the chain. The queries are implemented here as async methods instead, which
+//! the endpoint merely blocks on, so that the indexer and RPC calls of a query are issued
+//! concurrently rather than one after the other, and so that async callers can await the
+//! queries without blocking a thread. The caching of the fetched cells is left to the
+//! endpoint.
+
+use std::sync::Arc;
+
+use ckb_ics_axon::handler::{IbcChannel, IbcConnections, IbcPacket};
+use ckb_ics_axon::{ChannelArgs, PacketArgs};
+use ckb_jsonrpc_types::{HeaderView, Status};
+use ckb_sdk::constants::TYPE_ID_CODE_HASH;
+use ckb_types::core::ScriptHashType;
+use ckb_types::packed::{CellInput, OutPoint, Script};
+use ckb_types::prelude::{Builder, Pack};
+use ckb_types::H256;
+use futures::{future, stream, StreamExt, TryStreamExt};
+use ibc_relayer_types::clients::ics07_ckb::client_state::ClientState as CkbClientState;
+use ibc_relayer_types::clients::ics07_ckb::consensus_state::ConsensusState as CkbConsensusState;
+use ibc_relayer_types::core::ics02_client::client_state::ClientState;
+use ibc_relayer_types::core::ics03_connection::connection::IdentifiedConnectionEnd;
+use ibc_relayer_types::core::ics04_channel::channel::IdentifiedChannelEnd;
+use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+use ibc_relayer_types::core::ics23_commitment::commitment::CommitmentRoot;
+use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, ClientId, PortId};
+use ibc_relayer_types::timestamp::Timestamp;
+use ibc_relayer_types::Height;
+use tendermint::Time;
+
+use crate::chain::ckb::prelude::CellSearcher;
+use crate::chain::endpoint::ChainStatus;
+use crate::config::ckb4ibc::ChainConfig;
+use crate::consensus_state::AnyConsensusState;
+use crate::error::Error;
+
+use super::cells::{collect_ibc_cells, IbcCell, IbcCellKind};
+use super::commitment::{
+    collect_all_ibc_cells, collect_ibc_cells_root, prove_ibc_cell, IbcCellProof,
+};
+use super::extractor::{
+    extract_channel_end_from_tx, extract_connections_from_tx, extract_ibc_packet_from_tx,
+};
+use super::footprint::{
+    collect_storage_footprint, fetch_all_cells, fetch_cell_transaction, StorageFootprint,
+};
+use super::history::{collect_ibc_state_at, IbcStateAt};
+use super::packet_size::{collect_oversized_packets, OversizedPacket};
+use super::tendermint::{
+    get_client_lock_script, is_tendermint_client, TendermintClient, TendermintClientCell,
+};
+use super::utils::{
+    convert_port_id_to_array, decode_transaction, get_channel_idx, get_connection_search_key,
+    get_script_hash, get_search_key,
+};
+
+/// Maximum number of the RPC calls of a query which are in flight at once
+const MAX_CONCURRENT_CALLS: usize = 16;
+
+/// Time of a block, as the timestamp of its header
+fn header_time(header: &HeaderView) -> Result<Time, Error> {
+    let ts_milisec = header.inner.timestamp.value();
+    Timestamp::from_nanoseconds(ts_milisec * 1_000_000)
+        .map_err(Error::other)?
+        .into_tm_time()
+        .ok_or_else(|| Error::query("ckb header has no timestamp".to_owned()))
+}
+
+/// Queries of a CKB chain, sharing the RPC client of its endpoint
+#[derive(Clone)]
+pub struct Ckb4IbcChainAsync<R> {
+    rpc_client: Arc<R>,
+    config: ChainConfig,
+}
+
+impl<R: CellSearcher + Sync> Ckb4IbcChainAsync<R> {
+    pub fn new(rpc_client: Arc<R>, config: ChainConfig) -> Self {
+        Self { rpc_client, config }
+    }
+
+    pub async fn query_application_status(&self) -> Result<ChainStatus, Error> {
+        let header = self.rpc_client.get_tip_header().await?;
+        let height = Height::new(1, header.inner.number.value()).unwrap();
+        let ts_milisec = header.inner.timestamp.value();
+        let timestamp = Timestamp::from_nanoseconds(ts_milisec * 1_000_000).unwrap();
+        Ok(ChainStatus { height, timestamp })
+    }
+
+    /// Timestamp of the tip header along with the commitment root of the live IBC cells,
+    /// which are fetched concurrently
+    pub async fn query_host_consensus_state(&self) -> Result<CkbConsensusState, Error> {
+        let (header, commitment_root) = futures::try_join!(
+            self.rpc_client.get_tip_header(),
+            collect_ibc_cells_root(self.rpc_client.as_ref(), &self.config),
+        )?;
+        Ok(CkbConsensusState {
+            timestamp: header_time(&header)?,
+            commitment_root,
+        })
+    }
+
+    /// Consensus state stored on CKB for `client_id` at `height`
+    ///
+    /// The cell of a Tendermint client keeps the consensus states of its latest heights.
+    /// The other clients verify the proofs of their counterparty against its own light
+    /// client, so they keep no root and have a single height, whose consensus state is
+    /// dated by the block of their contract cell.
+    pub async fn query_consensus_state(
+        &self,
+        client_id: &ClientId,
+        height: Height,
+    ) -> Result<AnyConsensusState, Error> {
+        if is_tendermint_client(client_id) {
+            let client = self.fetch_tendermint_client(client_id).await?.client;
+            let consensus_state = client.consensus_state(client_id, height)?;
+            return Ok(AnyConsensusState::Tendermint(consensus_state.clone()));
+        }
+        if height != self.ckb_client_state().latest_height() {
+            return Err(Error::ckb_consensus_state_not_found(
+                client_id.to_string(),
+                height,
+            ));
+        }
+        let client_type_args = H256::from(self.config.client_id_of(client_id));
+        let cell = self
+            .rpc_client
+            .search_cell_by_typescript(
+                &TYPE_ID_CODE_HASH.pack(),
+                &client_type_args.as_bytes().to_owned(),
+            )
+            .await?
+            .ok_or_else(|| Error::ckb_consensus_state_not_found(client_id.to_string(), height))?;
+        // the block is found from the transaction of the cell rather than by its number,
+        // which a ckb-light-client node can't look a block up by
+        let block_hash = self
+            .rpc_client
+            .get_transaction(&cell.out_point.tx_hash)
+            .await?
+            .and_then(|tx| tx.tx_status.block_hash)
+            .ok_or_else(|| Error::ckb_consensus_state_not_found(client_id.to_string(), height))?;
+        let block = self.rpc_client.get_block(&block_hash).await?;
+        Ok(AnyConsensusState::Ckb(CkbConsensusState {
+            timestamp: header_time(&block.header)?,
+            commitment_root: CommitmentRoot::from_bytes(&[]),
+        }))
+    }
+
+    /// Heights of the consensus states stored on CKB for `client_id`
+    pub async fn query_consensus_state_heights(
+        &self,
+        client_id: &ClientId,
+    ) -> Result<Vec<Height>, Error> {
+        if is_tendermint_client(client_id) {
+            let client = self.fetch_tendermint_client(client_id).await?.client;
+            return Ok(client.consensus_state_heights());
+        }
+        Ok(vec![self.ckb_client_state().latest_height()])
+    }
+
+    /// State of the clients other than the Tendermint ones
+    fn ckb_client_state(&self) -> CkbClientState {
+        CkbClientState {
+            chain_id: self.config.counter_chain.clone(),
+        }
+    }
+
+    /// Connections of the live connections cell, along with the cell as an input
+    pub async fn fetch_connections(
+        &self,
+    ) -> Result<(Vec<IdentifiedConnectionEnd>, IbcConnections, CellInput), Error> {
+        let search_key = get_connection_search_key(&self.config);
+        let cell = self
+            .rpc_client
+            .fetch_live_cells(search_key, 1, None)
+            .await?
+            .objects
+            .into_iter()
+            .next()
+            .ok_or(Error::query("get ibc connection cell failed 1".to_string()))?;
+        let tx = self
+            .rpc_client
+            .get_transaction(&cell.out_point.tx_hash)
+            .await?
+            .ok_or(Error::query("get ibc connection cell failed 2".to_string()))?
+            .transaction
+            .ok_or(Error::query("get ibc connection cell failed 3".to_string()))?;
+        let tx = decode_transaction(tx)?;
+        let (connections, ibc_connections) = extract_connections_from_tx(tx)?;
+        let cell_input = CellInput::new_builder()
+            .previous_output(cell.out_point.into())
+            .build();
+        Ok((connections, ibc_connections, cell_input))
+    }
+
+    /// Channel ends of the live channel cells, whose transactions are fetched
+    /// concurrently, ordered by channel identifier
+    pub async fn query_channels(&self) -> Result<Vec<IdentifiedChannelEnd>, Error> {
+        let script = Script::new_builder()
+            .code_hash(get_script_hash(&self.config.channel_type_args))
+            .args("".pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build();
+        let cells = fetch_all_cells(self.rpc_client.as_ref(), get_search_key(script)).await?;
+        let txs = stream::iter(cells)
+            .map(|cell| self.rpc_client.get_transaction(&cell.out_point.tx_hash))
+            .buffer_unordered(MAX_CONCURRENT_CALLS)
+            .collect::<Vec<_>>()
+            .await;
+        let mut channel_ends: Vec<_> = txs
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|resp| resp.tx_status.status == Status::Committed && resp.transaction.is_some())
+            .flat_map(|tx| {
+                let tx = decode_transaction(tx.transaction.unwrap())?;
+                extract_channel_end_from_tx(tx)
+            })
+            .map(|e| e.0)
+            .collect();
+        // The channel cells are recreated by every update of their channel, so they are
+        // ordered by channel identifier rather than by their position in the indexer
+        channel_ends.sort_by_key(|channel| {
+            (
+                get_channel_idx(&channel.channel_id).ok(),
+                channel.port_id.clone(),
+            )
+        });
+        Ok(channel_ends)
+    }
+
+    /// Channel end of the live channel cell, along with the cell as an input.
+    ///
+    /// The client of the channel is only known from its channel end, so the cell is
+    /// searched under every client served by the chain at once.
+    pub async fn fetch_channel(
+        &self,
+        channel_id: &ChannelId,
+        port_id: &PortId,
+        is_open: bool,
+    ) -> Result<((IdentifiedChannelEnd, IbcChannel), CellInput), Error> {
+        let channel_code_hash = get_script_hash(&self.config.channel_type_args);
+        let channel_idx = get_channel_idx(channel_id)?;
+        let port_id_in_args = convert_port_id_to_array(port_id)?;
+        let searches = self
+            .config
+            .all_client_type_args()
+            .into_iter()
+            .map(|client_type_args| {
+                let script = Script::new_builder()
+                    .code_hash(channel_code_hash.clone())
+                    .args(
+                        ChannelArgs {
+                            client_id: client_type_args.clone().into(),
+                            open: is_open,
+                            channel_id: channel_idx,
+                            port_id: port_id_in_args,
+                        }
+                        .to_args()
+                        .pack(),
+                    )
+                    .hash_type(ScriptHashType::Type.into())
+                    .build();
+                self.rpc_client
+                    .fetch_live_cells(get_search_key(script), 1, None)
+            });
+        let cell = future::try_join_all(searches)
+            .await?
+            .into_iter()
+            .find_map(|resp| resp.objects.into_iter().next())
+            .ok_or(Error::query("no channel cell is fetched".to_string()))?;
+
+        let tx_hash = &cell.out_point.tx_hash;
+        let tx_resp = self
+            .rpc_client
+            .get_transaction(tx_hash)
+            .await
+            .map_err(|_| Error::query("fetch back tx failed1".to_string()))?
+            .ok_or(Error::query("fetch back tx failed2".to_string()))?
+            .transaction
+            .ok_or(Error::query(format!(
+                "transaction {tx_hash:#x} is returned without its content"
+            )))?;
+        let tx = decode_transaction(tx_resp)?;
+        let channel_end = extract_channel_end_from_tx(tx)?;
+        let input = CellInput::new_builder()
+            .previous_output(
+                OutPoint::new_builder()
+                    .tx_hash(tx_hash.pack())
+                    .index(cell.tx_index.pack())
+                    .build(),
+            )
+            .build();
+        Ok((channel_end, input))
+    }
+
+    /// Packet of the live packet cell of `sequence` on the channel end, along with the
+    /// cell as an input.
+    ///
+    /// The owner is left out of the search args, so that the packet cell is found
+    /// whichever owner is configured for the port, but it can be consumed by the owner
+    /// at any time, after which the packet is no longer found.
+    pub async fn fetch_packet(
+        &self,
+        channel_id: &ChannelId,
+        port_id: &PortId,
+        sequence: Sequence,
+    ) -> Result<(IbcPacket, CellInput), Error> {
+        let script = Script::new_builder()
+            .code_hash(get_script_hash(&self.config.packet_type_args))
+            .hash_type(ScriptHashType::Type.into())
+            .args(
+                PacketArgs {
+                    channel_id: get_channel_idx(channel_id)?,
+                    port_id: convert_port_id_to_array(port_id)?,
+                    sequence: u64::from(sequence) as u16,
+                    owner: self.config.packet_owner(port_id),
+                }
+                .get_search_args()
+                .pack(),
+            )
+            .build();
+        let cell = self
+            .rpc_client
+            .fetch_live_cells(get_search_key(script), 1, None)
+            .await?
+            .objects
+            .into_iter()
+            .next()
+            .ok_or(Error::query(String::from("query packet")))?;
+        let tx_hash = &cell.out_point.tx_hash;
+        let tx_resp = self
+            .rpc_client
+            .get_transaction(tx_hash)
+            .await
+            .map_err(|_| Error::query("".to_string()))?
+            .ok_or(Error::query("".to_string()))?
+            .transaction
+            .ok_or(Error::query(format!(
+                "transaction {tx_hash:#x} is returned without its content"
+            )))?;
+        let tx = decode_transaction(tx_resp)?;
+        let ibc_packet = extract_ibc_packet_from_tx(tx)?;
+        let cell_input = CellInput::new_builder()
+            .previous_output(cell.out_point.into())
+            .build();
+        Ok((ibc_packet, cell_input))
+    }
+
+    /// Packets of the live packet cells of `sequences` on the channel end, in the order
+    /// of `sequences`. The sequences whose packet cell is not found are skipped.
+    ///
+    /// The packet cells are searched by a single paginated indexer query, on the prefix
+    /// the search args of all the sequences share, i.e. the one of the channel end, and
+    /// matched against the search args of each sequence. Only the transactions of the
+    /// matched cells are then fetched, concurrently.
+    pub async fn fetch_packets(
+        &self,
+        channel_id: &ChannelId,
+        port_id: &PortId,
+        sequences: Vec<Sequence>,
+    ) -> Result<Vec<(IbcPacket, CellInput)>, Error> {
+        let channel_idx = get_channel_idx(channel_id)?;
+        let port = convert_port_id_to_array(port_id)?;
+        let owner = self.config.packet_owner(port_id);
+        let search_args = sequences
+            .iter()
+            .map(|sequence| {
+                PacketArgs {
+                    channel_id: channel_idx,
+                    port_id: port,
+                    sequence: u64::from(*sequence) as u16,
+                    owner,
+                }
+                .get_search_args()
+            })
+            .collect::<Vec<_>>();
+        let Some(prefix) = search_args.iter().cloned().reduce(|prefix, args| {
+            let len = prefix.iter().zip(&args).take_while(|(a, b)| a == b).count();
+            prefix[..len].to_vec()
+        }) else {
+            return Ok(vec![]);
+        };
+
+        let script = Script::new_builder()
+            .code_hash(get_script_hash(&self.config.packet_type_args))
+            .hash_type(ScriptHashType::Type.into())
+            .args(prefix.pack())
+            .build();
+        let cells = fetch_all_cells(self.rpc_client.as_ref(), get_search_key(script)).await?;
+        let matched = search_args.iter().filter_map(|args| {
+            cells
+                .iter()
+                .find(|cell| cell.output.lock.args.as_bytes().starts_with(args))
+        });
+        stream::iter(matched)
+            .map(|cell| async move {
+                let tx = fetch_cell_transaction(self.rpc_client.as_ref(), cell).await?;
+                let ibc_packet = extract_ibc_packet_from_tx(tx)?;
+                let cell_input = CellInput::new_builder()
+                    .previous_output(cell.out_point.clone().into())
+                    .build();
+                Ok::<_, Error>((ibc_packet, cell_input))
+            })
+            .buffered(MAX_CONCURRENT_CALLS)
+            .try_collect()
+            .await
+    }
+
+    /// Live cell of the Tendermint client `client_id`
+    pub async fn fetch_tendermint_client(
+        &self,
+        client_id: &ClientId,
+    ) -> Result<TendermintClientCell, Error> {
+        let client_type_args = H256::from(self.config.client_id_of(client_id));
+        let search_key = get_search_key(get_client_lock_script(&client_type_args));
+        let cell = self
+            .rpc_client
+            .fetch_live_cells(search_key, 1, None)
+            .await?
+            .objects
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::ckb_tendermint_client_not_found(client_id.to_string()))?;
+        let data = cell.output_data.unwrap_or_default();
+        let client = TendermintClient::from_cell_data(client_id, data.as_bytes())?;
+        Ok(TendermintClientCell {
+            client,
+            input: CellInput::new_builder()
+                .previous_output(cell.out_point.into())
+                .build(),
+            capacity: cell.output.capacity.value(),
+        })
+    }
+
+    /// Proof at `height` of the first IBC cell whose lock is matched by `matches`
+    ///
+    /// Only the live cells are indexed, so the proof is refused unless `height` is the
+    /// tip both before and after the cells are collected, which makes them the state at
+    /// `height` rather than a later one.
+    pub async fn fetch_ibc_cell_proof(
+        &self,
+        matches: impl Fn(&Script) -> bool,
+        height: Height,
+    ) -> Result<Option<IbcCellProof>, Error> {
+        self.ensure_latest_height(height).await?;
+        let cells = collect_all_ibc_cells(self.rpc_client.as_ref(), &self.config).await?;
+        self.ensure_latest_height(height).await?;
+        Ok(prove_ibc_cell(&cells, matches))
+    }
+
+    async fn ensure_latest_height(&self, height: Height) -> Result<(), Error> {
+        let latest_height = self.query_application_status().await?.height;
+        if height != latest_height {
+            return Err(Error::ckb_proof_height_not_latest(height, latest_height));
+        }
+        Ok(())
+    }
+
+    pub async fn query_storage_footprint(&self) -> Result<StorageFootprint, Error> {
+        collect_storage_footprint(self.rpc_client.as_ref(), &self.config).await
+    }
+
+    pub async fn query_oversized_packets(&self) -> Result<Vec<OversizedPacket>, Error> {
+        collect_oversized_packets(self.rpc_client.as_ref(), &self.config).await
+    }
+
+    pub async fn query_ibc_state_at(&self, block_number: u64) -> Result<IbcStateAt, Error> {
+        collect_ibc_state_at(self.rpc_client.as_ref(), &self.config, block_number).await
+    }
+
+    pub async fn query_ibc_cells(&self, kind: Option<IbcCellKind>) -> Result<Vec<IbcCell>, Error> {
+        collect_ibc_cells(self.rpc_client.as_ref(), &self.config, kind).await
+    }
+}
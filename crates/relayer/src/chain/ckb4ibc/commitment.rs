@@ -0,0 +1,333 @@
+//! Commitment root of the IBC states of a CKB chain.
+//!
+//! CKB has no state tree to take a root from, the IBC states are the live connection,
+//! channel and packet cells instead. The data of these cells is already the keccak
+//! commitment of the encoded objects, as laid out by `ckb_ics_axon`, and their locks
+//! identify the objects, so every cell is committed to by the keccak hash of its lock
+//! and data. The root is the one of the keccak merkle tree of these sorted leaves, and
+//! the proof of an object is the merkle path of its cell.
+
+use ckb_ics_axon::{ChannelArgs, PacketArgs};
+use ckb_types::core::ScriptHashType;
+use ckb_types::packed::{OutPoint, Script};
+use ckb_types::prelude::{Builder, Entity, Pack};
+use ibc_relayer_types::core::ics23_commitment::commitment::CommitmentRoot;
+use rlp::{DecoderError, Rlp, RlpStream};
+
+use crate::chain::ckb::prelude::CkbReader;
+use crate::config::ckb4ibc::ChainConfig;
+use crate::error::Error;
+
+use super::footprint::fetch_all_cells;
+use super::utils::{
+    get_connection_lock_script, get_connection_search_key, get_script_hash, get_search_key,
+    keccak256,
+};
+
+/// Live IBC cell, committed to by its lock and data
+#[derive(Clone, Debug)]
+pub struct IbcCell {
+    pub out_point: OutPoint,
+    pub lock: Script,
+    pub data: Vec<u8>,
+}
+
+impl IbcCell {
+    fn leaf(&self) -> [u8; 32] {
+        cell_leaf(self.lock.as_slice(), &self.data)
+    }
+}
+
+fn cell_leaf(lock: &[u8], data: &[u8]) -> [u8; 32] {
+    keccak256(&[lock, data].concat())
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    keccak256(&[left.as_slice(), right.as_slice()].concat())
+}
+
+/// Levels of the merkle tree of `leaves`, from the leaves up to the root. The last node
+/// of a level of an odd number of nodes is carried up to the next level as is.
+fn merkle_levels(leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![leaves];
+    while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+        let next = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_pair(left, right),
+                [node] => *node,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+fn sorted_leaves(cells: &[IbcCell]) -> Vec<[u8; 32]> {
+    let mut leaves = cells.iter().map(IbcCell::leaf).collect::<Vec<_>>();
+    leaves.sort_unstable();
+    leaves
+}
+
+/// Root of the merkle tree of the cells, whatever order they are given in
+pub fn ibc_cells_root(cells: &[IbcCell]) -> CommitmentRoot {
+    let root = merkle_levels(sorted_leaves(cells))
+        .last()
+        .and_then(|level| level.first().copied())
+        .unwrap_or_default();
+    CommitmentRoot::from_bytes(&root)
+}
+
+/// Merkle proof of an IBC cell, i.e. of the object identified by the lock of the cell
+/// and committed to by its data
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IbcCellProof {
+    /// Molecule encoding of the lock of the cell
+    pub lock: Vec<u8>,
+    pub data: Vec<u8>,
+    /// Index of the leaf of the cell among the sorted leaves
+    pub index: u64,
+    pub leaves_count: u64,
+    /// Siblings of the path from the leaf up to the root, the carried nodes having none
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl IbcCellProof {
+    pub fn verify(&self, root: &CommitmentRoot) -> bool {
+        if self.index >= self.leaves_count {
+            return false;
+        }
+        let mut node = cell_leaf(&self.lock, &self.data);
+        let (mut index, mut count) = (self.index, self.leaves_count);
+        let mut siblings = self.siblings.iter();
+        while count > 1 {
+            if index ^ 1 < count {
+                let Some(sibling) = siblings.next() else {
+                    return false;
+                };
+                node = if index % 2 == 0 {
+                    hash_pair(&node, sibling)
+                } else {
+                    hash_pair(sibling, &node)
+                };
+            }
+            index /= 2;
+            count = (count + 1) / 2;
+        }
+        siblings.next().is_none() && root.as_bytes() == node
+    }
+}
+
+impl rlp::Encodable for IbcCellProof {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(5);
+        s.append(&self.lock);
+        s.append(&self.data);
+        s.append(&self.index);
+        s.append(&self.leaves_count);
+        s.begin_list(self.siblings.len());
+        for sibling in &self.siblings {
+            s.append(&sibling.to_vec());
+        }
+    }
+}
+
+impl rlp::Decodable for IbcCellProof {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 5 {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+        let siblings = rlp
+            .list_at::<Vec<u8>>(4)?
+            .into_iter()
+            .map(|sibling| {
+                sibling
+                    .try_into()
+                    .map_err(|_| DecoderError::RlpInvalidLength)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            lock: rlp.val_at(0)?,
+            data: rlp.val_at(1)?,
+            index: rlp.val_at(2)?,
+            leaves_count: rlp.val_at(3)?,
+            siblings,
+        })
+    }
+}
+
+/// Proof of the first cell among `cells` whose lock is matched by `matches`
+pub fn prove_ibc_cell(
+    cells: &[IbcCell],
+    matches: impl Fn(&Script) -> bool,
+) -> Option<IbcCellProof> {
+    let cell = cells.iter().find(|cell| matches(&cell.lock))?;
+    let leaves = sorted_leaves(cells);
+    let leaves_count = leaves.len() as u64;
+    let mut index = leaves.binary_search(&cell.leaf()).ok()?;
+    let proof_index = index as u64;
+
+    let mut siblings = vec![];
+    for level in merkle_levels(leaves) {
+        if let Some(sibling) = level.get(index ^ 1) {
+            siblings.push(*sibling);
+        }
+        index /= 2;
+    }
+    Some(IbcCellProof {
+        lock: cell.lock.as_slice().to_vec(),
+        data: cell.data.clone(),
+        index: proof_index,
+        leaves_count,
+        siblings,
+    })
+}
+
+/// Whether `lock` is the one of the connections cell of the chain
+pub fn is_connections_lock(lock: &Script, config: &ChainConfig) -> bool {
+    lock.as_slice() == get_connection_lock_script(config).as_slice()
+}
+
+/// Whether `lock` is the one of the cell of the channel end of `channel_args`, whatever
+/// the client and the state of the channel, which are left out of `channel_args`
+pub fn is_channel_lock(lock: &Script, config: &ChainConfig, channel_args: &ChannelArgs) -> bool {
+    if lock.code_hash().as_slice() != get_script_hash(&config.channel_type_args).as_slice() {
+        return false;
+    }
+    let args = lock.args().raw_data();
+    config
+        .all_client_type_args()
+        .into_iter()
+        .any(|client_type_args| {
+            [true, false].into_iter().any(|open| {
+                let expected = ChannelArgs {
+                    client_id: client_type_args.clone().into(),
+                    open,
+                    ..*channel_args
+                };
+                args.starts_with(&expected.to_args())
+            })
+        })
+}
+
+/// Whether `lock` is the one of the packet cell of `packet_args`
+pub fn is_packet_lock(lock: &Script, config: &ChainConfig, packet_args: &PacketArgs) -> bool {
+    lock.code_hash().as_slice() == get_script_hash(&config.packet_type_args).as_slice()
+        && lock
+            .args()
+            .raw_data()
+            .starts_with(&packet_args.get_search_args())
+}
+
+pub async fn collect_all_ibc_cells<R: CkbReader>(
+    rpc_client: &R,
+    config: &ChainConfig,
+) -> Result<Vec<IbcCell>, Error> {
+    let mut search_keys = vec![get_connection_search_key(config)];
+    for type_args in [&config.channel_type_args, &config.packet_type_args] {
+        let script = Script::new_builder()
+            .code_hash(get_script_hash(type_args))
+            .hash_type(ScriptHashType::Type.into())
+            .args("".pack())
+            .build();
+        search_keys.push(get_search_key(script));
+    }
+
+    let mut cells = vec![];
+    for search_key in search_keys {
+        let live_cells = fetch_all_cells(rpc_client, search_key).await?;
+        cells.extend(live_cells.into_iter().map(|cell| {
+            IbcCell {
+                out_point: cell.out_point.into(),
+                lock: cell.output.lock.into(),
+                data: cell
+                    .output_data
+                    .map(|data| data.into_bytes().to_vec())
+                    .unwrap_or_default(),
+            }
+        }));
+    }
+    Ok(cells)
+}
+
+pub async fn collect_ibc_cells_root<R: CkbReader>(
+    rpc_client: &R,
+    config: &ChainConfig,
+) -> Result<CommitmentRoot, Error> {
+    let cells = collect_all_ibc_cells(rpc_client, config).await?;
+    Ok(ibc_cells_root(&cells))
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_types::packed::{OutPoint, Script};
+    use ckb_types::prelude::{Builder, Entity, Pack};
+
+    use super::{ibc_cells_root, prove_ibc_cell, IbcCell, IbcCellProof};
+
+    fn cell(index: u32, data: u8) -> IbcCell {
+        IbcCell {
+            out_point: OutPoint::new_builder().index(index.pack()).build(),
+            lock: Script::new_builder()
+                .args(index.to_le_bytes().as_slice().pack())
+                .build(),
+            data: vec![data; 32],
+        }
+    }
+
+    fn is_cell(index: u32) -> impl Fn(&Script) -> bool {
+        move |lock| lock.args().raw_data().as_ref() == index.to_le_bytes()
+    }
+
+    #[test]
+    fn test_ibc_cells_root() {
+        let (a, b) = (cell(0, 0), cell(1, 0));
+        let root = ibc_cells_root(&[a.clone(), b.clone()]);
+        assert_eq!(root, ibc_cells_root(&[b.clone(), a.clone()]));
+        assert_ne!(root, ibc_cells_root(&[a.clone()]));
+        // the root changes with the state of any object
+        assert_ne!(root, ibc_cells_root(&[a, cell(1, 1)]));
+    }
+
+    #[test]
+    fn test_prove_ibc_cell() {
+        for count in 1..=7 {
+            let cells = (0..count).map(|i| cell(i, i as u8)).collect::<Vec<_>>();
+            let root = ibc_cells_root(&cells);
+            for i in 0..count {
+                let proof = prove_ibc_cell(&cells, is_cell(i)).unwrap();
+                assert!(proof.verify(&root), "cell {i} of {count}");
+                let decoded: IbcCellProof = rlp::decode(&rlp::encode(&proof)).unwrap();
+                assert_eq!(decoded, proof);
+
+                let mut forged = proof.clone();
+                forged.data = vec![u8::MAX; 32];
+                assert!(!forged.verify(&root));
+            }
+            assert!(prove_ibc_cell(&cells, is_cell(count)).is_none());
+        }
+    }
+
+    /// The root and the encoded proof of three cells, computed apart from this module,
+    /// are the vector the verifier of the IBC cells in the contracts is checked against
+    #[test]
+    fn test_ibc_cell_proof_vector() {
+        let cells = (0..3).map(|i| cell(i, i as u8)).collect::<Vec<_>>();
+        let root = ibc_cells_root(&cells);
+        assert_eq!(
+            hex::encode(root.as_bytes()),
+            "a5b7b45f1e2cbd9c22901c935ad0052b502a525d5ead9449c8e07efaac92edd5"
+        );
+        let proof = prove_ibc_cell(&cells, is_cell(2)).unwrap();
+        assert_eq!(
+            hex::encode(rlp::encode(&proof)),
+            "f8a2b839390000001000000030000000310000000000000000000000000000000000000000000000\
+             000000000000000000000000000400000002000000a0020202020202020202020202020202020202\
+             02020202020202020202020202020103f842a086af4567e6ad9d894917d0290dc23d67abc539f78c\
+             68003b52182b9a3f759898a0e8f80dae1ea537d395f8c83bc0c85ef1686ebb495954149f8dae1fff\
+             f83f7562"
+        );
+        assert!(proof.verify(&root));
+    }
+}
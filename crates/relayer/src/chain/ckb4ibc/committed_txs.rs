@@ -0,0 +1,98 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ckb_types::H256;
+
+/// Number of blocks during which the block of an emitted transaction is checked
+/// against the canonical chain
+pub const REORG_TRACKING_BLOCKS: u64 = 64;
+
+/// Transactions whose IBC events have been emitted by the event monitor, along with
+/// the blocks they were committed in, so that the events of the transactions whose
+/// blocks get orphaned by a reorg can be retracted.
+#[derive(Default)]
+pub struct CommittedTxs {
+    /// Committed transactions, grouped by block number then by block hash
+    blocks: BTreeMap<u64, BTreeMap<H256, BTreeSet<H256>>>,
+}
+
+impl CommittedTxs {
+    pub fn record(&mut self, tx_hash: H256, block_number: u64, block_hash: H256) {
+        self.blocks
+            .entry(block_number)
+            .or_default()
+            .entry(block_hash)
+            .or_default()
+            .insert(tx_hash);
+    }
+
+    /// Stop tracking the blocks which are deep enough below `tip` to be considered
+    /// safe from reorgs.
+    pub fn prune(&mut self, tip: u64) {
+        let oldest = tip.saturating_sub(REORG_TRACKING_BLOCKS);
+        self.blocks = self.blocks.split_off(&oldest);
+    }
+
+    pub fn block_numbers(&self) -> Vec<u64> {
+        self.blocks.keys().copied().collect()
+    }
+
+    /// Remove and return the transactions recorded at `block_number` in any other
+    /// block than the canonical one, or in any block if there is no longer a block
+    /// at this number.
+    pub fn retract_orphaned(
+        &mut self,
+        block_number: u64,
+        canonical_hash: Option<&H256>,
+    ) -> Vec<H256> {
+        let Some(blocks) = self.blocks.get_mut(&block_number) else {
+            return vec![];
+        };
+        let orphaned = blocks
+            .keys()
+            .filter(|hash| Some(*hash) != canonical_hash)
+            .cloned()
+            .collect::<Vec<_>>();
+        let tx_hashes = orphaned
+            .iter()
+            .filter_map(|hash| blocks.remove(hash))
+            .flatten()
+            .collect();
+        if blocks.is_empty() {
+            self.blocks.remove(&block_number);
+        }
+        tx_hashes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_types::{h256, H256};
+
+    use super::{CommittedTxs, REORG_TRACKING_BLOCKS};
+
+    #[test]
+    fn test_retract_orphaned_txs() {
+        let mut txs = CommittedTxs::default();
+        txs.record(h256!("0x1"), 10, h256!("0xa"));
+        txs.record(h256!("0x2"), 10, h256!("0xb"));
+        txs.record(h256!("0x3"), 11, h256!("0xc"));
+
+        assert_eq!(
+            txs.retract_orphaned(10, Some(&h256!("0xa"))),
+            vec![h256!("0x2")]
+        );
+        assert!(txs.retract_orphaned(10, Some(&h256!("0xa"))).is_empty());
+        assert_eq!(txs.retract_orphaned(11, None), vec![h256!("0x3")]);
+        assert_eq!(txs.block_numbers(), vec![10]);
+    }
+
+    #[test]
+    fn test_prune_deep_blocks() {
+        let mut txs = CommittedTxs::default();
+        txs.record(H256::default(), 10, H256::default());
+        txs.record(H256::default(), 20, H256::default());
+
+        txs.prune(15 + REORG_TRACKING_BLOCKS);
+        assert_eq!(txs.block_numbers(), vec![20]);
+    }
+}
@@ -0,0 +1,63 @@
+//! Versions of the suite of IBC contracts the relayer is compatible with.
+//!
+//! The contract cells of a CKB chain only hold the code of the contracts, which carries
+//! no version. The version of the deployed suite is thus taken from the configuration
+//! of the chain, and otherwise assumed to be the one the relayer is built for, i.e. the
+//! one encoding the IBC objects as the `ckb-ics-axon` dependency does.
+
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+use semver::{Version, VersionReq};
+
+use crate::config::ckb4ibc::ChainConfig;
+use crate::error::Error;
+
+/// Version of the suite of IBC contracts the relayer is built for
+pub const CONTRACTS_VERSION: &str = "0.1.0";
+
+/// Versions of the suite of IBC contracts the relayer supports
+pub const CONTRACTS_VERSION_REQ: &str = ">=0.1, <0.2";
+
+/// Version of the IBC contracts of the chain of `config`
+pub fn contracts_version(config: &ChainConfig) -> Result<Version, Error> {
+    let version = config
+        .contracts_version
+        .as_deref()
+        .unwrap_or(CONTRACTS_VERSION);
+    Version::parse(version)
+        .map_err(|e| Error::other_error(format!("invalid `contracts_version` {version}: {e}")))
+}
+
+/// Check that the relayer supports the IBC contracts of `version`
+pub fn check_contracts_version(chain_id: &ChainId, version: &Version) -> Result<(), Error> {
+    let requirements =
+        VersionReq::parse(CONTRACTS_VERSION_REQ).expect("parsing the contracts requirements");
+    if requirements.matches(version) {
+        Ok(())
+    } else {
+        Err(Error::ckb_incompatible_contracts(
+            chain_id.clone(),
+            version.to_string(),
+            CONTRACTS_VERSION_REQ.to_owned(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+    use semver::Version;
+
+    use super::{check_contracts_version, CONTRACTS_VERSION};
+
+    #[test]
+    fn test_check_contracts_version() {
+        let chain_id = ChainId::from_string("ckb4ibc-0");
+        let built_for = Version::parse(CONTRACTS_VERSION).unwrap();
+        assert!(check_contracts_version(&chain_id, &built_for).is_ok());
+        assert!(check_contracts_version(&chain_id, &Version::new(0, 1, 7)).is_ok());
+
+        let err = check_contracts_version(&chain_id, &Version::new(0, 2, 0)).unwrap_err();
+        assert!(err.to_string().contains("0.2.0"));
+        assert!(check_contracts_version(&chain_id, &Version::new(1, 0, 0)).is_err());
+    }
+}
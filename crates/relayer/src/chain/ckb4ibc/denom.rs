@@ -0,0 +1,141 @@
+//! Registry of the SUDT/xUDT tokens which hold ICS-20 denominations on CKB.
+//!
+//! The registry is configured in `udt_denoms` of the chain config, mapping the IBC
+//! denom trace of each token, e.g. `transfer/channel-0/uatom`, to the hash of the type
+//! script of its UDT cells.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use ckb_types::H256;
+use ibc_proto::ibc::applications::transfer::v1::DenomTrace as RawDenomTrace;
+use ibc_relayer_types::applications::transfer::denom::PrefixedDenom;
+use sha2::{Digest, Sha256};
+
+use crate::denom::DenomTrace;
+use crate::error::Error;
+
+/// Denomination of the capacity of the cells
+pub const CKB_DENOM: &str = "ckb";
+
+/// Prefix of the denominations of the tokens which went through IBC
+const IBC_DENOM_PREFIX: &str = "ibc/";
+
+pub struct DenomRegistry<'a> {
+    udt_denoms: &'a BTreeMap<String, H256>,
+}
+
+impl<'a> DenomRegistry<'a> {
+    pub fn new(udt_denoms: &'a BTreeMap<String, H256>) -> Self {
+        Self { udt_denoms }
+    }
+
+    /// Denom trace of the token whose hash is `hash`, with or without the `ibc/` prefix
+    pub fn denom_trace(&self, hash: &str) -> Result<DenomTrace, Error> {
+        let hash = hash.strip_prefix(IBC_DENOM_PREFIX).unwrap_or(hash);
+        let trace = self
+            .udt_denoms
+            .keys()
+            .find(|trace| denom_hash(trace).eq_ignore_ascii_case(hash))
+            .ok_or_else(|| Error::query(format!("no UDT is registered for the denom {hash}")))?;
+        parse_denom_trace(trace)
+    }
+
+    /// Denomination of the balances of the UDT of `type_hash`, which is `ibc/{hash}`
+    /// for the tokens which went through IBC and the base denom for the others
+    pub fn denom_of(&self, type_hash: &H256) -> Option<String> {
+        self.udt_denoms
+            .iter()
+            .find(|(_, udt_type_hash)| *udt_type_hash == type_hash)
+            .map(|(trace, _)| {
+                if trace.contains('/') {
+                    format!("{IBC_DENOM_PREFIX}{}", denom_hash(trace))
+                } else {
+                    trace.clone()
+                }
+            })
+    }
+
+    /// Type script hash of the UDT of `denom`, given either as its denom trace or as
+    /// returned by [`DenomRegistry::denom_of`]
+    pub fn type_hash_of(&self, denom: &str) -> Option<&H256> {
+        self.udt_denoms.get(denom).or_else(|| {
+            self.udt_denoms
+                .values()
+                .find(|type_hash| self.denom_of(type_hash).as_deref() == Some(denom))
+        })
+    }
+}
+
+/// Upper-case hex encoded SHA-256 hash of the denom trace, as in `ibc/{hash}`
+pub fn denom_hash(trace: &str) -> String {
+    hex::encode_upper(Sha256::digest(trace.as_bytes()))
+}
+
+pub fn parse_denom_trace(trace: &str) -> Result<DenomTrace, Error> {
+    let denom = PrefixedDenom::from_str(trace)
+        .map_err(|e| Error::other_error(format!("invalid denom trace {trace}: {e}")))?;
+    let RawDenomTrace { path, base_denom } = denom.into();
+    Ok(DenomTrace { path, base_denom })
+}
+
+/// Amount of tokens held by a SUDT or xUDT cell, encoded in the first 16 bytes of its data
+pub fn udt_amount(data: &[u8]) -> Option<u128> {
+    let amount: [u8; 16] = data.get(..16)?.try_into().ok()?;
+    Some(u128::from_le_bytes(amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use ckb_types::h256;
+
+    use super::{denom_hash, udt_amount, DenomRegistry};
+
+    #[test]
+    fn test_denom_registry() {
+        let udt_denoms = BTreeMap::from([
+            ("transfer/channel-0/uatom".to_owned(), h256!("0x1")),
+            ("uckb".to_owned(), h256!("0x2")),
+        ]);
+        let registry = DenomRegistry::new(&udt_denoms);
+        let hash = denom_hash("transfer/channel-0/uatom");
+
+        let trace = registry.denom_trace(&format!("ibc/{hash}")).unwrap();
+        assert_eq!(trace.path, "transfer/channel-0");
+        assert_eq!(trace.base_denom, "uatom");
+        assert!(registry.denom_trace(&hash.to_lowercase()).is_ok());
+        assert!(registry
+            .denom_trace(&denom_hash("transfer/channel-1/uatom"))
+            .is_err());
+
+        assert_eq!(
+            registry.denom_of(&h256!("0x1")),
+            Some(format!("ibc/{hash}"))
+        );
+        assert_eq!(registry.denom_of(&h256!("0x2")), Some("uckb".to_owned()));
+        assert_eq!(registry.denom_of(&h256!("0x3")), None);
+
+        assert_eq!(
+            registry.type_hash_of(&format!("ibc/{hash}")),
+            Some(&h256!("0x1"))
+        );
+        assert_eq!(
+            registry.type_hash_of("transfer/channel-0/uatom"),
+            Some(&h256!("0x1"))
+        );
+        assert_eq!(registry.type_hash_of("uckb"), Some(&h256!("0x2")));
+        assert_eq!(registry.type_hash_of("ckb"), None);
+    }
+
+    #[test]
+    fn test_udt_amount() {
+        let mut data = 1000u128.to_le_bytes().to_vec();
+        assert_eq!(udt_amount(&data), Some(1000));
+        // xUDT cells may append extension data after the amount
+        data.extend([1, 2, 3]);
+        assert_eq!(udt_amount(&data), Some(1000));
+        assert_eq!(udt_amount(&[0; 8]), None);
+    }
+}
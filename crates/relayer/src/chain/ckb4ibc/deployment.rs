@@ -0,0 +1,72 @@
+//! Blocks deploying the IBC contracts of a CKB chain.
+//!
+//! The contracts are deployed in type-id cells, so upgrading one consumes its previous
+//! cell and creates a new one of the same type script. Walking back the inputs of the
+//! same type script from the live contract cell leads to the transaction which created
+//! the first of them, and no IBC cell of the contract can be committed before its block.
+
+use ckb_types::H256;
+
+use crate::chain::ckb::prelude::CkbReader;
+use crate::error::Error;
+
+use super::history::fetch_transaction;
+
+/// Number of the block deploying the contract whose live cell is output `index` of
+/// transaction `tx_hash`
+pub async fn deployment_block<R: CkbReader>(
+    rpc_client: &R,
+    mut tx_hash: H256,
+    index: u32,
+) -> Result<u64, Error> {
+    let (mut tx, mut block_hash) = fetch_transaction(rpc_client, &tx_hash).await?;
+    let type_script = tx
+        .inner
+        .outputs
+        .get(index as usize)
+        .ok_or_else(|| {
+            Error::query(format!(
+                "contract cell {tx_hash:#x}:{index} is not found in its transaction"
+            ))
+        })?
+        .type_
+        .clone();
+
+    // a cell without type script can't be upgraded, so its transaction deployed it
+    while type_script.is_some() {
+        let mut previous = None;
+        for input in &tx.inner.inputs {
+            let out_point = &input.previous_output;
+            // the input of a cellbase transaction has no previous output
+            if out_point.tx_hash == H256::default() {
+                continue;
+            }
+            let (input_tx, _) = fetch_transaction(rpc_client, &out_point.tx_hash).await?;
+            let is_contract = input_tx
+                .inner
+                .outputs
+                .get(out_point.index.value() as usize)
+                .map_or(false, |output| output.type_ == type_script);
+            if is_contract {
+                previous = Some(out_point.tx_hash.clone());
+                break;
+            }
+        }
+        let Some(previous) = previous else {
+            break;
+        };
+        (tx, block_hash) = fetch_transaction(rpc_client, &previous).await?;
+        tx_hash = previous;
+    }
+
+    let block_hash = block_hash
+        .ok_or_else(|| Error::query(format!("transaction {tx_hash:#x} is not committed")))?;
+    let block_number = rpc_client
+        .get_block(&block_hash)
+        .await?
+        .header
+        .inner
+        .number
+        .value();
+    Ok(block_number)
+}
@@ -19,6 +19,7 @@ use ibc_relayer_types::core::ics03_connection::connection::{
 use ibc_relayer_types::core::ics03_connection::connection::{
     Counterparty as ConnectionCounterparty, State as ConnectionState,
 };
+use ibc_relayer_types::core::ics03_connection::events::Attributes as ConnectionAttributes;
 use ibc_relayer_types::core::ics03_connection::version::Version as ConnVersion;
 use ibc_relayer_types::core::ics04_channel::channel::{
     ChannelEnd, Counterparty as ChannelCounterparty, IdentifiedChannelEnd, Order,
@@ -69,6 +70,16 @@ pub fn extract_connections_from_tx(
     Ok((result, ibc_connection_cell))
 }
 
+/// Index of the output of the transaction holding the channel end it updates
+pub fn channel_end_output_idx(tx: &TransactionView) -> Result<usize, Error> {
+    get_object_idx(tx, ObjectType::ChannelEnd)
+}
+
+/// Index of the output of the transaction holding the connections it updates
+pub fn ibc_connections_output_idx(tx: &TransactionView) -> Result<usize, Error> {
+    get_object_idx(tx, ObjectType::IbcConnections)
+}
+
 pub fn extract_ibc_packet_from_tx(tx: TransactionView) -> Result<IbcPacket, Error> {
     let idx = get_object_idx(&tx, ObjectType::IbcPacket)?;
     let witness = tx.inner.witnesses.get(idx).unwrap();
@@ -80,6 +91,22 @@ pub fn extract_ibc_packet_from_tx(tx: TransactionView) -> Result<IbcPacket, Erro
     Ok(ibc_packet)
 }
 
+/// Attributes of the handshake events of the connection at `idx` of the connections cell,
+/// including its counterparty as far as it is known at its current state
+pub fn connection_attributes(
+    connection: &CkbConnectionEnd,
+    idx: usize,
+) -> Result<ConnectionAttributes, Error> {
+    let connection = convert_connection_end(connection.clone(), idx)?;
+    let counterparty = connection.connection_end.counterparty();
+    Ok(ConnectionAttributes {
+        connection_id: Some(connection.connection_id.clone()),
+        client_id: connection.connection_end.client_id().clone(),
+        counterparty_connection_id: counterparty.connection_id().cloned(),
+        counterparty_client_id: counterparty.client_id().clone(),
+    })
+}
+
 fn navigate(t: MsgType, object_type: ObjectType) -> usize {
     match (&t, &object_type) {
         (MsgType::MsgClientCreate, ObjectType::IbcConnections) => 0,
@@ -101,7 +128,7 @@ fn navigate(t: MsgType, object_type: ObjectType) -> usize {
         (MsgType::MsgAckOutboxPacket, ObjectType::ChannelEnd) => 0, // only input
         (MsgType::MsgAckInboxPacket, ObjectType::ChannelEnd) => 0,  // only input
         (MsgType::MsgFinishPacket, ObjectType::ChannelEnd) => todo!(),
-        (MsgType::MsgTimeoutPacket, ObjectType::ChannelEnd) => todo!(),
+        (MsgType::MsgTimeoutPacket, ObjectType::ChannelEnd) => 0,
         (MsgType::MsgSendPacket, ObjectType::IbcPacket) => 1,
         (MsgType::MsgRecvPacket, ObjectType::IbcPacket) => 1,
         (MsgType::MsgAckPacket, ObjectType::IbcPacket) => 1,
@@ -216,7 +243,9 @@ enum ObjectType {
     IbcPacket,
 }
 
-fn get_object_idx(tx: &TransactionView, object_type: ObjectType) -> Result<usize, Error> {
+/// RLP encoded envelope of the message handled by the transaction, which is carried by
+/// the output type of its last witness.
+pub fn extract_envelope_bytes(tx: &TransactionView) -> Result<Vec<u8>, Error> {
     let msg = tx.inner.witnesses.last().ok_or(Error::ckb_none_witness())?;
 
     let bytes = msg.as_bytes();
@@ -224,11 +253,59 @@ fn get_object_idx(tx: &TransactionView, object_type: ObjectType) -> Result<usize
         .map_err(|_| Error::ckb_decode_witness_args())?
         .output_type()
         .to_opt()
-        .unwrap();
-    let envelope_slice = envelope_bytes.raw_data();
+        .ok_or(Error::ckb_decode_envelope())?;
+
+    Ok(envelope_bytes.raw_data().to_vec())
+}
+
+fn get_object_idx(tx: &TransactionView, object_type: ObjectType) -> Result<usize, Error> {
+    let envelope_slice = extract_envelope_bytes(tx)?;
 
     let envelope =
         rlp::decode::<Envelope>(&envelope_slice).map_err(|_| Error::ckb_decode_envelope())?;
 
     Ok(navigate(envelope.msg_type, object_type))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ckb_ics_axon::object::{ConnectionCounterparty, ConnectionEnd, State};
+    use ibc_relayer_types::core::ics24_host::identifier::{ClientId, ConnectionId};
+
+    use super::connection_attributes;
+
+    #[test]
+    fn test_connection_attributes() {
+        let mut connection = ConnectionEnd {
+            state: State::Init,
+            client_id: "07-axon-0".to_owned(),
+            counterparty: ConnectionCounterparty {
+                client_id: "07-tendermint-1".to_owned(),
+                connection_id: None,
+            },
+            delay_period: 0,
+        };
+
+        let attrs = connection_attributes(&connection, 2).unwrap();
+        assert_eq!(
+            attrs.connection_id,
+            Some(ConnectionId::from_str("connection-2").unwrap())
+        );
+        assert_eq!(attrs.client_id, ClientId::from_str("07-axon-0").unwrap());
+        assert_eq!(
+            attrs.counterparty_client_id,
+            ClientId::from_str("07-tendermint-1").unwrap()
+        );
+        assert_eq!(attrs.counterparty_connection_id, None);
+
+        connection.state = State::Open;
+        connection.counterparty.connection_id = Some("connection-5".to_owned());
+        let attrs = connection_attributes(&connection, 2).unwrap();
+        assert_eq!(
+            attrs.counterparty_connection_id,
+            Some(ConnectionId::from_str("connection-5").unwrap())
+        );
+    }
+}
@@ -0,0 +1,323 @@
+//! On-chain storage footprint of the IBC states.
+//!
+//! Every IBC object on CKB lives in a cell, and the capacity of a cell has to cover
+//! its own size, so the capacity locked by the IBC cells is what the relayer pays
+//! for storing the states. The footprint counts the live connection, channel, packet
+//! and client cells, and derives the growth of the storage from the blocks in which
+//! the live cells were created.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use ckb_ics_axon::handler::PacketStatus;
+use ckb_jsonrpc_types::TransactionView;
+use ckb_sdk::constants::TYPE_ID_CODE_HASH;
+use ckb_sdk::rpc::ckb_indexer::{Cell, SearchKey};
+use ckb_types::packed::Script;
+use ckb_types::prelude::{Builder, Pack, Unpack};
+use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, PortId};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::chain::ckb::prelude::{CellSearcher, CkbReader};
+use crate::config::ckb4ibc::ChainConfig;
+use crate::error::Error;
+
+use super::extractor::{extract_channel_end_from_tx, extract_ibc_packet_from_tx};
+use super::utils::{
+    decode_transaction, get_connection_search_key, get_script_hash, get_search_key,
+};
+
+const CELLS_PAGE_SIZE: u32 = 100;
+
+/// Number of times a scan of the live cells starts over once its cursor is invalidated
+const MAX_SCAN_RESTARTS: usize = 3;
+
+/// Number of blocks covered by each sample of the storage growth
+pub const GROWTH_INTERVAL_BLOCKS: u64 = 10_000;
+
+/// Number of cells of one kind and the capacity they occupy, in shannons
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellsUsage {
+    pub count: u64,
+    pub capacity: u64,
+}
+
+impl CellsUsage {
+    pub fn add(&mut self, capacity: u64) {
+        self.count += 1;
+        self.capacity += capacity;
+    }
+
+    pub fn merge(&mut self, other: &CellsUsage) {
+        self.count += other.count;
+        self.capacity += other.capacity;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelFootprint {
+    pub channel_id: ChannelId,
+    pub port_id: PortId,
+    pub channel: CellsUsage,
+    pub packets: CellsUsage,
+}
+
+/// Live IBC cells created up to `block_number`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrowthSample {
+    pub block_number: u64,
+    pub cells: CellsUsage,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageFootprint {
+    /// Tip block number at which the footprint is collected
+    pub block_number: u64,
+    pub connections: CellsUsage,
+    pub channels: CellsUsage,
+    pub packets: CellsUsage,
+    pub clients: CellsUsage,
+    pub per_channel: Vec<ChannelFootprint>,
+    /// Cumulative usage every `GROWTH_INTERVAL_BLOCKS` blocks, oldest first
+    pub growth: Vec<GrowthSample>,
+}
+
+impl StorageFootprint {
+    pub fn total(&self) -> CellsUsage {
+        let mut total = CellsUsage::default();
+        for usage in [
+            &self.connections,
+            &self.channels,
+            &self.packets,
+            &self.clients,
+        ] {
+            total.merge(usage);
+        }
+        total
+    }
+}
+
+/// Accumulate the `(block_number, capacity)` of cells into samples, each one covering
+/// the cells created before the end of its interval.
+pub fn growth_samples(
+    cells: impl IntoIterator<Item = (u64, u64)>,
+    interval_blocks: u64,
+) -> Vec<GrowthSample> {
+    let mut buckets = BTreeMap::<u64, CellsUsage>::new();
+    for (block_number, capacity) in cells {
+        let end = (block_number / interval_blocks + 1) * interval_blocks;
+        buckets.entry(end).or_default().add(capacity);
+    }
+
+    let mut cumulative = CellsUsage::default();
+    buckets
+        .into_iter()
+        .map(|(block_number, usage)| {
+            cumulative.merge(&usage);
+            GrowthSample {
+                block_number,
+                cells: cumulative,
+            }
+        })
+        .collect()
+}
+
+pub async fn collect_storage_footprint<R: CellSearcher + Sync>(
+    rpc_client: &R,
+    config: &ChainConfig,
+) -> Result<StorageFootprint, Error> {
+    let block_number = rpc_client.get_tip_header().await?.inner.number.value();
+    let mut created = vec![];
+
+    let mut connections = CellsUsage::default();
+    for cell in fetch_all_cells(rpc_client, get_connection_search_key(config)).await? {
+        connections.add(cell.output.capacity.value());
+        created.push((cell.block_number.value(), cell.output.capacity.value()));
+    }
+
+    let mut per_channel = BTreeMap::<(ChannelId, PortId), (CellsUsage, CellsUsage)>::new();
+
+    let mut channels = CellsUsage::default();
+    let channel_script = Script::new_builder()
+        .code_hash(get_script_hash(&config.channel_type_args))
+        .args("".pack())
+        .build();
+    for cell in fetch_all_cells(rpc_client, get_search_key(channel_script)).await? {
+        let capacity = cell.output.capacity.value();
+        let tx = fetch_cell_transaction(rpc_client, &cell).await?;
+        let (channel_end, _) = extract_channel_end_from_tx(tx)?;
+        channels.add(capacity);
+        per_channel
+            .entry((channel_end.channel_id, channel_end.port_id))
+            .or_default()
+            .0
+            .add(capacity);
+        created.push((cell.block_number.value(), capacity));
+    }
+
+    let mut packets = CellsUsage::default();
+    let packet_script = Script::new_builder()
+        .code_hash(get_script_hash(&config.packet_type_args))
+        .args("".pack())
+        .build();
+    for cell in fetch_all_cells(rpc_client, get_search_key(packet_script)).await? {
+        let capacity = cell.output.capacity.value();
+        let tx = fetch_cell_transaction(rpc_client, &cell).await?;
+        let ibc_packet = extract_ibc_packet_from_tx(tx)?;
+        // the packet belongs to the source channel if it was sent from this chain
+        let (channel_id, port_id) = match ibc_packet.status {
+            PacketStatus::Recv | PacketStatus::OutboxAck => (
+                &ibc_packet.packet.destination_channel_id,
+                &ibc_packet.packet.destination_port_id,
+            ),
+            _ => (
+                &ibc_packet.packet.source_channel_id,
+                &ibc_packet.packet.source_port_id,
+            ),
+        };
+        packets.add(capacity);
+        if let (Ok(channel_id), Ok(port_id)) =
+            (ChannelId::from_str(channel_id), PortId::from_str(port_id))
+        {
+            per_channel
+                .entry((channel_id, port_id))
+                .or_default()
+                .1
+                .add(capacity);
+        }
+        created.push((cell.block_number.value(), capacity));
+    }
+
+    let mut clients = CellsUsage::default();
+    for client_type_args in config.all_client_type_args() {
+        let client_cell = rpc_client
+            .search_cell_by_typescript(
+                &TYPE_ID_CODE_HASH.pack(),
+                &client_type_args.as_bytes().to_owned(),
+            )
+            .await?;
+        if let Some(cell) = client_cell {
+            let capacity: u64 = cell.output.capacity().unpack();
+            clients.add(capacity);
+            created.push((cell.block_number, capacity));
+        }
+    }
+
+    let per_channel = per_channel
+        .into_iter()
+        .map(
+            |((channel_id, port_id), (channel, packets))| ChannelFootprint {
+                channel_id,
+                port_id,
+                channel,
+                packets,
+            },
+        )
+        .collect();
+
+    Ok(StorageFootprint {
+        block_number,
+        connections,
+        channels,
+        packets,
+        clients,
+        per_channel,
+        growth: growth_samples(created, GROWTH_INTERVAL_BLOCKS),
+    })
+}
+
+/// Live cells of `search_key`, paged through up to the last page.
+///
+/// The cursors of the indexer don't survive its restarts, so a scan whose cursor is
+/// rejected starts over from the first cell. The cells of the pages already fetched are
+/// dropped, since they may have been consumed meanwhile, and only the cells of a scan
+/// which reached its last page are returned.
+pub async fn fetch_all_cells<R: CkbReader>(
+    rpc_client: &R,
+    search_key: SearchKey,
+) -> Result<Vec<Cell>, Error> {
+    let mut cells = vec![];
+    let mut cursor = None;
+    let mut restarts = 0;
+    loop {
+        let resp = rpc_client
+            .fetch_live_cells(search_key.clone(), CELLS_PAGE_SIZE, cursor.clone())
+            .await;
+        let page = match resp {
+            Ok(page) => page,
+            Err(e) if e.is_ckb_indexer_cursor_invalid() && restarts < MAX_SCAN_RESTARTS => {
+                restarts += 1;
+                warn!(
+                    "cursor of the live cells scan is invalidated, starting over \
+                     ({restarts}/{MAX_SCAN_RESTARTS}): {e}"
+                );
+                cells.clear();
+                cursor = None;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        let is_last_page = page.objects.len() < CELLS_PAGE_SIZE as usize;
+        cells.extend(page.objects);
+        if is_last_page {
+            return Ok(cells);
+        }
+        cursor = Some(page.last_cursor);
+    }
+}
+
+pub(super) async fn fetch_cell_transaction<R: CkbReader>(
+    rpc_client: &R,
+    cell: &Cell,
+) -> Result<TransactionView, Error> {
+    let tx_hash = &cell.out_point.tx_hash;
+    let tx = rpc_client
+        .get_transaction(tx_hash)
+        .await?
+        .and_then(|resp| resp.transaction)
+        .ok_or_else(|| Error::query(format!("transaction {tx_hash:#x} is not found")))?;
+    decode_transaction(tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{growth_samples, CellsUsage, GrowthSample};
+
+    #[test]
+    fn test_growth_samples_are_cumulative() {
+        let cells = [(15, 3), (2, 1), (9, 2), (31, 4)];
+        let samples = growth_samples(cells, 10);
+        assert_eq!(
+            samples,
+            vec![
+                GrowthSample {
+                    block_number: 10,
+                    cells: CellsUsage {
+                        count: 2,
+                        capacity: 3
+                    },
+                },
+                GrowthSample {
+                    block_number: 20,
+                    cells: CellsUsage {
+                        count: 3,
+                        capacity: 6
+                    },
+                },
+                GrowthSample {
+                    block_number: 40,
+                    cells: CellsUsage {
+                        count: 4,
+                        capacity: 10
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_growth_samples_of_no_cells() {
+        assert!(growth_samples([], 10).is_empty());
+    }
+}
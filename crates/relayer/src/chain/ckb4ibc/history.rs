@@ -0,0 +1,201 @@
+//! IBC states of a CKB chain as they were at a past block.
+//!
+//! The indexer only serves the live cells, but every transaction updating an IBC object
+//! consumes the previous cell of the object. The history of the connections, and the
+//! one of every channel, is thus the chain of transactions leading to its live cell,
+//! which is walked back up to the last transaction committed at or before the block:
+//! its output held the object at that block. The object didn't exist yet if the chain
+//! runs out before. The channels are never destroyed, closing them only updates their
+//! state, so the live channels cover all the channels of any past block.
+
+use ckb_ics_axon::handler::IbcChannel;
+use ckb_ics_axon::object::Ordering as CkbOrdering;
+use ckb_jsonrpc_types::TransactionView;
+use ckb_types::core::ScriptHashType;
+use ckb_types::packed::Script;
+use ckb_types::prelude::{Builder, Pack, Unpack};
+use ckb_types::H256;
+use ibc_relayer_types::core::ics03_connection::connection::IdentifiedConnectionEnd;
+use ibc_relayer_types::core::ics04_channel::channel::IdentifiedChannelEnd;
+use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, PortId};
+use serde::{Deserialize, Serialize};
+
+use crate::chain::ckb::prelude::CkbReader;
+use crate::config::ckb4ibc::ChainConfig;
+use crate::error::Error;
+
+use super::extractor::{extract_channel_end_from_tx, extract_connections_from_tx};
+use super::footprint::fetch_all_cells;
+use super::utils::{
+    decode_transaction, get_channel_idx, get_connection_search_key, get_script_hash, get_search_key,
+};
+
+/// Packets sent on a channel and not acknowledged yet
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutstandingPackets {
+    pub port_id: PortId,
+    pub channel_id: ChannelId,
+    pub count: u64,
+    /// Sequences of the packets, which are only known for the ordered channels, whose
+    /// packets are acknowledged in the order they are sent
+    pub sequences: Option<Vec<Sequence>>,
+}
+
+impl OutstandingPackets {
+    pub fn of_channel(channel_end: &IdentifiedChannelEnd, channel: &IbcChannel) -> Self {
+        let next_recv_ack = u64::from(channel.sequence.next_recv_ack);
+        let next_send_packet = u64::from(channel.sequence.next_send_packet);
+        let sequences = matches!(channel.order, CkbOrdering::Ordered).then(|| {
+            (next_recv_ack..next_send_packet)
+                .map(Sequence::from)
+                .collect()
+        });
+        Self {
+            port_id: channel_end.port_id.clone(),
+            channel_id: channel_end.channel_id.clone(),
+            count: next_send_packet.saturating_sub(next_recv_ack),
+            sequences,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IbcStateAt {
+    pub block_number: u64,
+    pub connections: Vec<IdentifiedConnectionEnd>,
+    pub channels: Vec<IdentifiedChannelEnd>,
+    /// Outstanding packets of the channels having any
+    pub outstanding_packets: Vec<OutstandingPackets>,
+}
+
+pub(super) async fn fetch_transaction<R: CkbReader>(
+    rpc_client: &R,
+    tx_hash: &H256,
+) -> Result<(TransactionView, Option<H256>), Error> {
+    let resp = rpc_client
+        .get_transaction(tx_hash)
+        .await?
+        .ok_or_else(|| Error::query(format!("transaction {tx_hash:#x} is not found")))?;
+    let tx = resp
+        .transaction
+        .ok_or_else(|| Error::query(format!("transaction {tx_hash:#x} is not found")))?;
+    Ok((decode_transaction(tx)?, resp.tx_status.block_hash))
+}
+
+/// Transaction whose output held the object at `block_number`, walking back from the
+/// transaction of its live cell through the inputs of the object, i.e. the ones locked
+/// by `code_hash`
+async fn transaction_at<R: CkbReader>(
+    rpc_client: &R,
+    mut tx_hash: H256,
+    code_hash: &H256,
+    block_number: u64,
+) -> Result<Option<TransactionView>, Error> {
+    loop {
+        let (tx, block_hash) = fetch_transaction(rpc_client, &tx_hash).await?;
+        let block_hash = block_hash
+            .ok_or_else(|| Error::query(format!("transaction {tx_hash:#x} is not committed")))?;
+        let committed_at = rpc_client
+            .get_block(&block_hash)
+            .await?
+            .header
+            .inner
+            .number
+            .value();
+        if committed_at <= block_number {
+            return Ok(Some(tx));
+        }
+
+        let mut previous = None;
+        for input in &tx.inner.inputs {
+            let out_point = &input.previous_output;
+            let (input_tx, _) = fetch_transaction(rpc_client, &out_point.tx_hash).await?;
+            let index = out_point.index.value() as usize;
+            let is_object = input_tx
+                .inner
+                .outputs
+                .get(index)
+                .map_or(false, |output| &output.lock.code_hash == code_hash);
+            if is_object {
+                previous = Some(out_point.tx_hash.clone());
+                break;
+            }
+        }
+        match previous {
+            Some(previous) => tx_hash = previous,
+            None => return Ok(None),
+        }
+    }
+}
+
+/// Connections, channels and outstanding packets of the chain at `block_number`
+pub async fn collect_ibc_state_at<R: CkbReader>(
+    rpc_client: &R,
+    config: &ChainConfig,
+    block_number: u64,
+) -> Result<IbcStateAt, Error> {
+    let mut connections = vec![];
+    let connection_code_hash: H256 = get_script_hash(&config.connection_type_args).unpack();
+    let connection_cells = fetch_all_cells(rpc_client, get_connection_search_key(config)).await?;
+    if let Some(cell) = connection_cells.into_iter().next() {
+        let tx = transaction_at(
+            rpc_client,
+            cell.out_point.tx_hash,
+            &connection_code_hash,
+            block_number,
+        )
+        .await?;
+        if let Some(tx) = tx {
+            connections = extract_connections_from_tx(tx)?.0;
+        }
+    }
+
+    let mut channels = vec![];
+    let mut outstanding_packets = vec![];
+    let channel_code_hash = get_script_hash(&config.channel_type_args);
+    let channel_script = Script::new_builder()
+        .code_hash(channel_code_hash.clone())
+        .hash_type(ScriptHashType::Type.into())
+        .args("".pack())
+        .build();
+    let channel_code_hash: H256 = channel_code_hash.unpack();
+    for cell in fetch_all_cells(rpc_client, get_search_key(channel_script)).await? {
+        let tx = transaction_at(
+            rpc_client,
+            cell.out_point.tx_hash,
+            &channel_code_hash,
+            block_number,
+        )
+        .await?;
+        let Some(tx) = tx else {
+            continue;
+        };
+        let (channel_end, channel) = extract_channel_end_from_tx(tx)?;
+        let outstanding = OutstandingPackets::of_channel(&channel_end, &channel);
+        if outstanding.count > 0 {
+            outstanding_packets.push(outstanding);
+        }
+        channels.push(channel_end);
+    }
+    // ordered by channel identifier as `query_channels` does
+    channels.sort_by_key(|channel| {
+        (
+            get_channel_idx(&channel.channel_id).ok(),
+            channel.port_id.clone(),
+        )
+    });
+    outstanding_packets.sort_by_key(|packets| {
+        (
+            get_channel_idx(&packets.channel_id).ok(),
+            packets.port_id.clone(),
+        )
+    });
+
+    Ok(IbcStateAt {
+        block_number,
+        connections,
+        channels,
+        outstanding_packets,
+    })
+}
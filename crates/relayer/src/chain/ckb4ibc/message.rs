@@ -1,21 +1,30 @@
 mod chan;
 mod client;
 mod conn;
+mod transfer;
 
-use std::{borrow::Borrow, cell::Ref, collections::HashMap};
+use std::collections::BTreeMap;
+use std::str::FromStr;
 
 use chan::*;
 use conn::*;
 
-use crate::{config::ckb4ibc::ChainConfig, error::Error, keyring::Secp256k1KeyPair};
+use crate::{
+    config::ckb4ibc::ChainConfig,
+    error::Error,
+    keyring::Secp256k1KeyPair,
+    progress::{PacketStep, RelayedPacket},
+};
 use ckb_ics_axon::{
     handler::{IbcChannel, IbcConnections},
-    message::Envelope,
+    message::{Envelope, MsgType},
 };
 use ckb_types::core::TransactionView;
 use ckb_types::packed::{Byte32, CellInput, OutPoint};
+use ckb_types::H256;
 use ibc_proto::google::protobuf::Any;
 use ibc_relayer_types::{
+    applications::transfer::msgs::transfer::{MsgTransfer, TYPE_URL as TRANSFER_TYPE_URL},
     core::ics02_client::msgs::update_client::{
         MsgUpdateClient, TYPE_URL as UPDATE_CLIENT_TYPE_URL,
     },
@@ -42,18 +51,24 @@ use ibc_relayer_types::{
                 chan_open_try::MsgChannelOpenTry,
                 chan_open_try::TYPE_URL as CHAN_OPEN_TRY_TYPE_URL,
                 recv_packet::{MsgRecvPacket, TYPE_URL as RECV_PACKET_TYPE_URL},
+                timeout::{MsgTimeout, TYPE_URL as TIMEOUT_TYPE_URL},
             },
             packet::Sequence,
         },
-        ics24_host::identifier::{ChannelId, PortId},
+        ics24_host::identifier::{ChannelId, ClientId, PortId},
     },
     events::IbcEvent,
     tx_msg::Msg,
 };
 
 use self::client::convert_update_client;
+pub use self::client::redundant_update_event;
+use self::transfer::convert_transfer_to_tx;
 
-use super::utils::get_script_hash;
+use super::scheduler::CellKey;
+use super::state_cache::IbcStateCache;
+use super::tendermint::{is_tendermint_client, TendermintClientCell};
+use super::utils::{get_connection_id, get_script_hash};
 
 pub trait MsgToTxConverter {
     fn get_key(&self) -> &Secp256k1KeyPair;
@@ -66,7 +81,10 @@ pub trait MsgToTxConverter {
 
     fn get_ibc_channel_input(&self, channel_id: &ChannelId, port_id: &PortId) -> CellInput;
 
-    fn get_client_outpoint(&self) -> OutPoint;
+    /// The cell of a Tendermint client, consumed by its updates
+    fn get_tendermint_client(&self, client_id: &ClientId) -> TendermintClientCell;
+
+    fn get_client_outpoint(&self, client_id: &[u8; 32]) -> OutPoint;
     fn get_conn_contract_outpoint(&self) -> OutPoint;
     fn get_chan_contract_outpoint(&self) -> OutPoint;
     fn get_packet_contract_outpoint(&self) -> OutPoint;
@@ -77,26 +95,29 @@ pub trait MsgToTxConverter {
 
     fn get_connection_code_hash(&self) -> Byte32;
 
-    fn get_client_id(&self) -> [u8; 32];
+    /// The client of the connection end at `connection_idx`, to which the channels
+    /// on the connection are bound
+    fn get_connection_client_id(&self, connection_idx: u16) -> Result<[u8; 32], Error>;
 
     fn get_packet_cell_input(&self, chan: ChannelId, port: PortId, seq: Sequence) -> CellInput;
 
-    fn get_packet_owner(&self) -> [u8; 32];
+    /// Whether the live cell of the packet of an unordered channel shows it is received
+    /// or acknowledged already, the channel cell keeping no track of these packets
+    fn is_packet_processed(&self, chan: &ChannelId, port: &PortId, seq: Sequence) -> bool;
+
+    /// Owner of the packet cells of `port_id`, which can unlock them besides the relayer
+    fn get_packet_owner(&self, port_id: &PortId) -> [u8; 32];
 
     fn get_config(&self) -> &ChainConfig;
 }
 
 pub struct Converter<'a> {
-    pub channel_input_data: Ref<'a, HashMap<(ChannelId, PortId), CellInput>>,
-    pub channel_cache: Ref<'a, HashMap<ChannelId, IbcChannel>>,
-    pub connection_cache: Ref<'a, Option<(IbcConnections, CellInput)>>,
-    pub packet_input_data: Ref<'a, HashMap<(ChannelId, PortId, Sequence), CellInput>>,
+    pub cache: &'a IbcStateCache,
     pub config: &'a ChainConfig,
-    pub client_outpoint: &'a OutPoint,
+    pub client_outpoints: &'a BTreeMap<H256, OutPoint>,
     pub chan_contract_outpoint: &'a OutPoint,
     pub packet_contract_outpoint: &'a OutPoint,
     pub conn_contract_outpoint: &'a OutPoint,
-    pub packet_owner: [u8; 32],
 }
 
 impl<'a> MsgToTxConverter for Converter<'a> {
@@ -105,26 +126,27 @@ impl<'a> MsgToTxConverter for Converter<'a> {
     }
 
     fn get_ibc_connections(&self) -> IbcConnections {
-        self.connection_cache.borrow().as_ref().unwrap().0.clone()
+        self.cache.connection().unwrap().0
     }
 
     fn get_ibc_connections_input(&self) -> CellInput {
-        self.connection_cache.borrow().as_ref().unwrap().1.clone()
+        self.cache.connection().unwrap().1
     }
 
     fn get_ibc_channel(&self, channel_id: &ChannelId) -> IbcChannel {
-        self.channel_cache.get(channel_id).unwrap().clone()
+        self.cache.channel(channel_id).unwrap()
     }
 
     fn get_ibc_channel_input(&self, channel_id: &ChannelId, port_id: &PortId) -> CellInput {
-        self.channel_input_data
-            .get(&(channel_id.clone(), port_id.clone()))
-            .unwrap()
-            .clone()
+        self.cache.channel_input(channel_id, port_id).unwrap()
     }
 
-    fn get_client_outpoint(&self) -> OutPoint {
-        self.client_outpoint.clone()
+    fn get_tendermint_client(&self, client_id: &ClientId) -> TendermintClientCell {
+        self.cache.client(client_id).unwrap()
+    }
+
+    fn get_client_outpoint(&self, client_id: &[u8; 32]) -> OutPoint {
+        self.client_outpoints[&H256::from(*client_id)].clone()
     }
 
     fn get_conn_contract_outpoint(&self) -> OutPoint {
@@ -151,8 +173,17 @@ impl<'a> MsgToTxConverter for Converter<'a> {
         get_script_hash(&self.config.connection_type_args)
     }
 
-    fn get_client_id(&self) -> [u8; 32] {
-        self.config.client_id()
+    fn get_connection_client_id(&self, connection_idx: u16) -> Result<[u8; 32], Error> {
+        let connections = self.get_ibc_connections();
+        let connection_end = connections
+            .connections
+            .get(connection_idx as usize)
+            .ok_or_else(|| {
+                Error::ckb_conn_id_invalid(get_connection_id(connection_idx).to_string())
+            })?;
+        let client_id = ClientId::from_str(&connection_end.client_id)
+            .map_err(|_| Error::ckb_client_id_invalid(connection_end.client_id.clone()))?;
+        Ok(self.config.client_id_of(&client_id))
     }
 
     fn get_packet_cell_input(
@@ -161,14 +192,23 @@ impl<'a> MsgToTxConverter for Converter<'a> {
         port_id: PortId,
         sequence: Sequence,
     ) -> CellInput {
-        self.packet_input_data
-            .get(&(channel_id, port_id, sequence))
+        self.cache
+            .packet_input(&channel_id, &port_id, sequence)
             .unwrap()
-            .clone()
     }
 
-    fn get_packet_owner(&self) -> [u8; 32] {
-        self.packet_owner
+    fn is_packet_processed(
+        &self,
+        channel_id: &ChannelId,
+        port_id: &PortId,
+        sequence: Sequence,
+    ) -> bool {
+        self.cache
+            .is_packet_processed(channel_id, port_id, sequence)
+    }
+
+    fn get_packet_owner(&self, port_id: &PortId) -> [u8; 32] {
+        self.config.packet_owner(port_id)
     }
 
     fn get_config(&self) -> &ChainConfig {
@@ -183,6 +223,22 @@ pub struct CkbTxInfo {
     pub event: Option<IbcEvent>,
 }
 
+impl CkbTxInfo {
+    /// Message whose step is executed on chain already according to the cached cells,
+    /// e.g. by another relayer, which needs no transaction and only reports its `event`
+    fn executed(msg_type: MsgType, event: Option<IbcEvent>) -> Self {
+        Self {
+            unsigned_tx: None,
+            envelope: Envelope {
+                msg_type,
+                content: vec![],
+            },
+            input_capacity: 0,
+            event,
+        }
+    }
+}
+
 // Return a transaction which needs to be added relayer's input in it and to be signed.
 pub fn convert_msg_to_ckb_tx<C: MsgToTxConverter>(
     msg: Any,
@@ -247,6 +303,16 @@ pub fn convert_msg_to_ckb_tx<C: MsgToTxConverter>(
                 .map_err(|e| Error::protobuf_decode(ACK_TYPE_URL.to_string(), e))?;
             convert_ack_packet_to_tx(msg, converter)
         }
+        TIMEOUT_TYPE_URL => {
+            let msg = MsgTimeout::from_any(msg)
+                .map_err(|e| Error::protobuf_decode(TIMEOUT_TYPE_URL.to_string(), e))?;
+            convert_timeout_packet_to_tx(msg, converter)
+        }
+        TRANSFER_TYPE_URL => {
+            let msg = MsgTransfer::from_any(msg)
+                .map_err(|e| Error::protobuf_decode(TRANSFER_TYPE_URL.to_string(), e))?;
+            convert_transfer_to_tx(msg, converter)
+        }
         UPDATE_CLIENT_TYPE_URL => {
             let msg = MsgUpdateClient::from_any(msg)
                 .map_err(|e| Error::protobuf_decode(UPDATE_CLIENT_TYPE_URL.to_string(), e))?;
@@ -255,3 +321,141 @@ pub fn convert_msg_to_ckb_tx<C: MsgToTxConverter>(
         _ => todo!(),
     }
 }
+
+/// Cells of the IBC objects consumed by the transaction of a message
+pub fn consumed_cells(msg: &Any) -> Result<Vec<CellKey>, Error> {
+    let decode_error = |e| Error::protobuf_decode(msg.type_url.clone(), e);
+    let keys = match msg.type_url.as_str() {
+        CONN_OPEN_INIT_TYPE_URL
+        | CONN_OPEN_TRY_TYPE_URL
+        | CONN_OPEN_ACK_TYPE_URL
+        | CONN_OPEN_CONFIRM_TYPE_URL
+        | CHAN_OPEN_INIT_TYPE_URL
+        | CHAN_OPEN_TRY_TYPE_URL => vec![CellKey::Connections],
+        CHAN_OPEN_ACK_TYPE_URL => {
+            let msg = MsgChannelOpenAck::from_any(msg.clone()).map_err(decode_error)?;
+            vec![CellKey::Channel(msg.channel_id)]
+        }
+        CHAN_OPEN_CONFIRM_TYPE_URL => {
+            let msg = MsgChannelOpenConfirm::from_any(msg.clone()).map_err(decode_error)?;
+            vec![CellKey::Channel(msg.channel_id)]
+        }
+        CHAN_CLOSE_INIT_TYPE_URL => {
+            let msg = MsgChannelCloseInit::from_any(msg.clone()).map_err(decode_error)?;
+            vec![CellKey::Channel(msg.channel_id)]
+        }
+        RECV_PACKET_TYPE_URL => {
+            let msg = MsgRecvPacket::from_any(msg.clone()).map_err(decode_error)?;
+            vec![CellKey::Channel(msg.packet.destination_channel)]
+        }
+        ACK_TYPE_URL => {
+            let msg = MsgAcknowledgement::from_any(msg.clone()).map_err(decode_error)?;
+            let channel_id = msg.packet.source_channel;
+            vec![
+                CellKey::Channel(channel_id.clone()),
+                CellKey::Packet(channel_id, msg.packet.sequence),
+            ]
+        }
+        TIMEOUT_TYPE_URL => {
+            let msg = MsgTimeout::from_any(msg.clone()).map_err(decode_error)?;
+            let channel_id = msg.packet.source_channel;
+            vec![
+                CellKey::Channel(channel_id.clone()),
+                CellKey::Packet(channel_id, msg.packet.sequence),
+            ]
+        }
+        TRANSFER_TYPE_URL => {
+            let msg = MsgTransfer::from_any(msg.clone()).map_err(decode_error)?;
+            vec![CellKey::Channel(msg.source_channel)]
+        }
+        UPDATE_CLIENT_TYPE_URL => {
+            let msg = MsgUpdateClient::from_any(msg.clone()).map_err(decode_error)?;
+            if is_tendermint_client(&msg.client_id) {
+                vec![CellKey::Client(msg.client_id)]
+            } else {
+                vec![]
+            }
+        }
+        _ => vec![],
+    };
+    Ok(keys)
+}
+
+/// Packet cell telling whether a packet message of an unordered channel is executed
+/// already: the receipt written on the destination channel end for a `MsgRecvPacket`,
+/// and the commitment turned into an ack on the source one for a `MsgAcknowledgement`
+pub fn processed_packet_key(msg: &Any) -> Result<Option<(ChannelId, PortId, Sequence)>, Error> {
+    let decode_error = |e| Error::protobuf_decode(msg.type_url.clone(), e);
+    let key = match msg.type_url.as_str() {
+        RECV_PACKET_TYPE_URL => {
+            let packet = MsgRecvPacket::from_any(msg.clone())
+                .map_err(decode_error)?
+                .packet;
+            Some((
+                packet.destination_channel,
+                packet.destination_port,
+                packet.sequence,
+            ))
+        }
+        ACK_TYPE_URL => {
+            let packet = MsgAcknowledgement::from_any(msg.clone())
+                .map_err(decode_error)?
+                .packet;
+            Some((packet.source_channel, packet.source_port, packet.sequence))
+        }
+        _ => None,
+    };
+    Ok(key)
+}
+
+/// Update of a Tendermint client carried by a message
+pub fn tendermint_client_update(msg: &Any) -> Result<Option<MsgUpdateClient>, Error> {
+    if msg.type_url != UPDATE_CLIENT_TYPE_URL {
+        return Ok(None);
+    }
+    let msg = MsgUpdateClient::from_any(msg.clone())
+        .map_err(|e| Error::protobuf_decode(UPDATE_CLIENT_TYPE_URL.to_string(), e))?;
+    Ok(is_tendermint_client(&msg.client_id).then_some(msg))
+}
+
+/// Packet relayed to the chain by a message, identified by the channel end of the chain
+pub fn relayed_packet(msg: &Any) -> Result<Option<RelayedPacket>, Error> {
+    let decode_error = |e| Error::protobuf_decode(msg.type_url.clone(), e);
+    let packet = match msg.type_url.as_str() {
+        RECV_PACKET_TYPE_URL => {
+            let packet = MsgRecvPacket::from_any(msg.clone())
+                .map_err(decode_error)?
+                .packet;
+            RelayedPacket {
+                step: PacketStep::Recv,
+                port_id: packet.destination_port,
+                channel_id: packet.destination_channel,
+                sequence: packet.sequence,
+            }
+        }
+        ACK_TYPE_URL => {
+            let packet = MsgAcknowledgement::from_any(msg.clone())
+                .map_err(decode_error)?
+                .packet;
+            RelayedPacket {
+                step: PacketStep::Ack,
+                port_id: packet.source_port,
+                channel_id: packet.source_channel,
+                sequence: packet.sequence,
+            }
+        }
+        TIMEOUT_TYPE_URL => {
+            let packet = MsgTimeout::from_any(msg.clone())
+                .map_err(decode_error)?
+                .packet;
+            RelayedPacket {
+                step: PacketStep::Timeout,
+                port_id: packet.source_port,
+                channel_id: packet.source_channel,
+                sequence: packet.sequence,
+            }
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(packet))
+}
@@ -1,6 +1,7 @@
 use std::str::FromStr;
 
 use super::{CkbTxInfo, MsgToTxConverter};
+use crate::chain::ckb4ibc::port_bindings::ensure_port_bound;
 use crate::chain::ckb4ibc::utils::{
     convert_port_id_to_array, convert_proof, get_channel_capacity, get_channel_idx,
     get_connection_capacity, get_connection_id, get_connection_idx, get_connection_lock_script,
@@ -19,6 +20,7 @@ use ckb_ics_axon::message::MsgChannelOpenConfirm as CkbMsgChannelOpenConfirm;
 use ckb_ics_axon::message::MsgChannelOpenInit as CkbMsgChannelOpenInit;
 use ckb_ics_axon::message::MsgChannelOpenTry as CkbMsgChannelOpenTry;
 use ckb_ics_axon::message::MsgRecvPacket as CkbMsgRecvPacket;
+use ckb_ics_axon::message::MsgTimeoutPacket as CkbMsgTimeoutPacket;
 use ckb_ics_axon::message::MsgType;
 use ckb_ics_axon::object::Packet as CkbPacket;
 use ckb_ics_axon::object::{ChannelCounterparty, Ordering as CkbOrdering, State as CkbState};
@@ -28,9 +30,12 @@ use ckb_types::packed::CellDep;
 use ckb_types::packed::{CellOutput, Script, WitnessArgs};
 use ckb_types::prelude::{Builder, Entity, Pack};
 use ibc_relayer_types::core::ics04_channel::channel::{ChannelEnd, Order, State};
-use ibc_relayer_types::core::ics04_channel::events::{OpenAck, OpenConfirm, OpenInit, OpenTry};
+use ibc_relayer_types::core::ics04_channel::events::{
+    OpenAck, OpenConfirm, OpenInit, OpenTry, TimeoutPacket,
+};
 use ibc_relayer_types::core::ics04_channel::msgs::acknowledgement::MsgAcknowledgement;
 use ibc_relayer_types::core::ics04_channel::msgs::recv_packet::MsgRecvPacket;
+use ibc_relayer_types::core::ics04_channel::msgs::timeout::MsgTimeout;
 use ibc_relayer_types::core::ics04_channel::msgs::{
     chan_close_init::MsgChannelCloseInit, chan_open_ack::MsgChannelOpenAck,
     chan_open_confirm::MsgChannelOpenConfirm, chan_open_init::MsgChannelOpenInit,
@@ -39,15 +44,19 @@ use ibc_relayer_types::core::ics04_channel::msgs::{
 use ibc_relayer_types::core::ics04_channel::packet::Packet;
 use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, PortId};
 use ibc_relayer_types::events::IbcEvent;
+use tracing::info;
 
 pub fn convert_chan_open_init_to_tx<C: MsgToTxConverter>(
     msg: MsgChannelOpenInit,
     converter: &C,
 ) -> Result<CkbTxInfo, Error> {
+    ensure_port_bound(converter.get_config(), &msg.port_id)?;
     let old_connection_cell = converter.get_ibc_connections();
     let next_channel_num = old_connection_cell.next_channel_number;
     let mut new_connection_cell = old_connection_cell.clone();
     new_connection_cell.next_channel_number += 1;
+    let client_id =
+        converter.get_connection_client_id(get_connection_idx(&msg.channel.connection_hops[0])?)?;
 
     let ibc_channel_end =
         convert_channel_end(msg.channel.clone(), msg.port_id.clone(), next_channel_num)?;
@@ -61,7 +70,7 @@ pub fn convert_chan_open_init_to_tx<C: MsgToTxConverter>(
         content: rlp::encode(&CkbMsgChannelOpenInit {}).to_vec(),
     };
     let channel_args = ChannelArgs {
-        client_id: converter.get_client_id(),
+        client_id,
         open: false,
         channel_id: next_channel_num,
         port_id: convert_port_id_to_array(&msg.port_id)?,
@@ -71,7 +80,7 @@ pub fn convert_chan_open_init_to_tx<C: MsgToTxConverter>(
         .cell_dep(
             CellDep::new_builder()
                 .dep_type(DepType::Code.into())
-                .out_point(converter.get_client_outpoint())
+                .out_point(converter.get_client_outpoint(&client_id))
                 .build(),
         )
         .cell_dep(
@@ -142,10 +151,13 @@ pub fn convert_chan_open_try_to_tx<C: MsgToTxConverter>(
     msg: MsgChannelOpenTry,
     converter: &C,
 ) -> Result<CkbTxInfo, Error> {
+    ensure_port_bound(converter.get_config(), &msg.port_id)?;
     let old_connection_cell = converter.get_ibc_connections();
     let next_channel_num = old_connection_cell.next_channel_number;
     let mut new_connection_cell = old_connection_cell.clone();
     new_connection_cell.next_channel_number += 1;
+    let client_id =
+        converter.get_connection_client_id(get_connection_idx(&msg.channel.connection_hops[0])?)?;
 
     let ibc_channel_end =
         convert_channel_end(msg.channel.clone(), msg.port_id.clone(), next_channel_num)?;
@@ -165,7 +177,7 @@ pub fn convert_chan_open_try_to_tx<C: MsgToTxConverter>(
         .cell_dep(
             CellDep::new_builder()
                 .dep_type(DepType::Code.into())
-                .out_point(converter.get_client_outpoint())
+                .out_point(converter.get_client_outpoint(&client_id))
                 .build(),
         )
         .cell_dep(
@@ -188,7 +200,7 @@ pub fn convert_chan_open_try_to_tx<C: MsgToTxConverter>(
                         .code_hash(converter.get_channel_code_hash())
                         .args(
                             ChannelArgs {
-                                client_id: converter.get_client_id(),
+                                client_id,
                                 open: false,
                                 channel_id: next_channel_num,
                                 port_id: convert_port_id_to_array(&msg.port_id)?,
@@ -241,8 +253,23 @@ pub fn convert_chan_open_ack_to_tx<C: MsgToTxConverter>(
 ) -> Result<CkbTxInfo, Error> {
     let channel_idx = get_channel_idx(&msg.channel_id)?;
     let old_channel = converter.get_ibc_channel(&msg.channel_id);
+    let client_id = converter.get_connection_client_id(old_channel.connection_hops[0] as u16)?;
     let connection_id = get_connection_id(old_channel.connection_hops[0] as u16);
     let counterparty_port_id = PortId::from_str(&old_channel.counterparty.port_id).unwrap();
+    let event = IbcEvent::OpenAckChannel(OpenAck {
+        port_id: msg.port_id.clone(),
+        channel_id: Some(msg.channel_id.clone()),
+        connection_id,
+        counterparty_port_id,
+        counterparty_channel_id: Some(msg.counterparty_channel_id.clone()),
+    });
+    if matches!(old_channel.state, CkbState::Open) {
+        info!(
+            "channel {} is open already, skipping its ack",
+            msg.channel_id
+        );
+        return Ok(CkbTxInfo::executed(MsgType::MsgChannelOpenAck, Some(event)));
+    }
     let mut new_channel = old_channel.clone();
     new_channel.state = CkbState::Open;
     new_channel.counterparty.channel_id = msg.counterparty_channel_id.as_str().to_string();
@@ -256,7 +283,7 @@ pub fn convert_chan_open_ack_to_tx<C: MsgToTxConverter>(
     };
 
     let lock_args = ChannelArgs {
-        client_id: converter.get_client_id(),
+        client_id,
         open: true,
         channel_id: channel_idx,
         port_id: convert_port_id_to_array(&msg.port_id)?,
@@ -268,7 +295,7 @@ pub fn convert_chan_open_ack_to_tx<C: MsgToTxConverter>(
     let packed_tx = TransactionView::new_advanced_builder()
         .cell_dep(
             CellDep::new_builder()
-                .out_point(converter.get_client_outpoint())
+                .out_point(converter.get_client_outpoint(&client_id))
                 .build(),
         )
         .cell_dep(
@@ -307,13 +334,6 @@ pub fn convert_chan_open_ack_to_tx<C: MsgToTxConverter>(
         )
         .build();
 
-    let event = IbcEvent::OpenAckChannel(OpenAck {
-        port_id: msg.port_id,
-        channel_id: Some(msg.channel_id),
-        connection_id,
-        counterparty_port_id,
-        counterparty_channel_id: Some(msg.counterparty_channel_id),
-    });
     Ok(CkbTxInfo {
         unsigned_tx: Some(packed_tx),
         envelope,
@@ -327,6 +347,7 @@ pub fn convert_chan_open_confirm_to_tx<C: MsgToTxConverter>(
     converter: &C,
 ) -> Result<CkbTxInfo, Error> {
     let old_channel = converter.get_ibc_channel(&msg.channel_id);
+    let client_id = converter.get_connection_client_id(old_channel.connection_hops[0] as u16)?;
     let mut new_channel = old_channel.clone();
     new_channel.state = CkbState::Open;
 
@@ -335,6 +356,23 @@ pub fn convert_chan_open_confirm_to_tx<C: MsgToTxConverter>(
         .map_err(|_| Error::ckb_port_id_invalid(old_channel.counterparty.port_id.clone()))?;
     let counterparty_channel_id =
         ChannelId::from_str(&old_channel.counterparty.channel_id).unwrap();
+    let event = IbcEvent::OpenConfirmChannel(OpenConfirm {
+        port_id: msg.port_id.clone(),
+        channel_id: Some(msg.channel_id.clone()),
+        connection_id,
+        counterparty_port_id,
+        counterparty_channel_id: Some(counterparty_channel_id),
+    });
+    if matches!(old_channel.state, CkbState::Open) {
+        info!(
+            "channel {} is open already, skipping its confirm",
+            msg.channel_id
+        );
+        return Ok(CkbTxInfo::executed(
+            MsgType::MsgChannelOpenConfirm,
+            Some(event),
+        ));
+    }
 
     let envelope = Envelope {
         msg_type: MsgType::MsgChannelOpenConfirm,
@@ -345,7 +383,7 @@ pub fn convert_chan_open_confirm_to_tx<C: MsgToTxConverter>(
     };
 
     let lock_args = ChannelArgs {
-        client_id: converter.get_client_id(),
+        client_id,
         open: true,
         channel_id: get_channel_idx(&msg.channel_id)?,
         port_id: convert_port_id_to_array(&msg.port_id)?,
@@ -357,7 +395,7 @@ pub fn convert_chan_open_confirm_to_tx<C: MsgToTxConverter>(
     let packed_tx = TransactionView::new_advanced_builder()
         .cell_dep(
             CellDep::new_builder()
-                .out_point(converter.get_client_outpoint())
+                .out_point(converter.get_client_outpoint(&client_id))
                 .build(),
         )
         .cell_dep(
@@ -389,13 +427,6 @@ pub fn convert_chan_open_confirm_to_tx<C: MsgToTxConverter>(
                 .pack(),
         )
         .build();
-    let event = IbcEvent::OpenConfirmChannel(OpenConfirm {
-        port_id: msg.port_id,
-        channel_id: Some(msg.channel_id),
-        connection_id,
-        counterparty_port_id,
-        counterparty_channel_id: Some(counterparty_channel_id),
-    });
     Ok(CkbTxInfo {
         unsigned_tx: Some(packed_tx),
         envelope,
@@ -417,6 +448,24 @@ pub fn convert_ack_packet_to_tx<C: MsgToTxConverter>(
 ) -> Result<CkbTxInfo, Error> {
     let channel_id = msg.packet.source_channel.clone();
     let old_channel_end = converter.get_ibc_channel(&channel_id);
+    let client_id =
+        converter.get_connection_client_id(old_channel_end.connection_hops[0] as u16)?;
+    let next_recv_ack: u64 = old_channel_end.sequence.next_recv_ack.into();
+    let processed =
+        converter.is_packet_processed(&channel_id, &msg.packet.source_port, msg.packet.sequence);
+    if packet_executed(
+        &old_channel_end,
+        next_recv_ack,
+        msg.packet.sequence.into(),
+        processed,
+    ) {
+        info!(
+            "packet {} on channel {channel_id} is acknowledged already, skipping it",
+            msg.packet.sequence
+        );
+        return Ok(CkbTxInfo::executed(MsgType::MsgAckPacket, None));
+    }
+    check_packet_order(&old_channel_end, next_recv_ack, msg.packet.sequence.into())?;
     let mut new_channel_end = old_channel_end.clone();
     new_channel_end.sequence.next_recv_ack += 1;
     let old_channel_end_encoded = get_encoded_object(old_channel_end);
@@ -449,7 +498,7 @@ pub fn convert_ack_packet_to_tx<C: MsgToTxConverter>(
     let packed_tx = TransactionView::new_advanced_builder()
         .cell_dep(
             CellDep::new_builder()
-                .out_point(converter.get_client_outpoint())
+                .out_point(converter.get_client_outpoint(&client_id))
                 .build(),
         )
         .cell_dep(
@@ -468,7 +517,7 @@ pub fn convert_ack_packet_to_tx<C: MsgToTxConverter>(
                         .hash_type(ScriptHashType::Type.into())
                         .args(
                             ChannelArgs {
-                                client_id: converter.get_client_id(),
+                                client_id,
                                 open: true,
                                 channel_id: channel_idx,
                                 port_id: port_id_in_args,
@@ -493,7 +542,7 @@ pub fn convert_ack_packet_to_tx<C: MsgToTxConverter>(
                                 channel_id: channel_idx,
                                 port_id: port_id_in_args,
                                 sequence: seq,
-                                owner: converter.get_packet_owner(),
+                                owner: converter.get_packet_owner(&port_id),
                             }
                             .to_args()
                             .pack(),
@@ -534,6 +583,31 @@ pub fn convert_recv_packet_to_tx<C: MsgToTxConverter>(
 ) -> Result<CkbTxInfo, Error> {
     let channel_id = msg.packet.destination_channel.clone();
     let old_channel_end = converter.get_ibc_channel(&channel_id);
+    let client_id =
+        converter.get_connection_client_id(old_channel_end.connection_hops[0] as u16)?;
+    let next_recv_packet: u64 = old_channel_end.sequence.next_recv_packet.into();
+    let processed = converter.is_packet_processed(
+        &channel_id,
+        &msg.packet.destination_port,
+        msg.packet.sequence,
+    );
+    if packet_executed(
+        &old_channel_end,
+        next_recv_packet,
+        msg.packet.sequence.into(),
+        processed,
+    ) {
+        info!(
+            "packet {} on channel {channel_id} is received already, skipping it",
+            msg.packet.sequence
+        );
+        return Ok(CkbTxInfo::executed(MsgType::MsgRecvPacket, None));
+    }
+    check_packet_order(
+        &old_channel_end,
+        next_recv_packet,
+        msg.packet.sequence.into(),
+    )?;
     let mut new_channel_end = old_channel_end.clone();
     new_channel_end.sequence.next_recv_packet += 1;
 
@@ -563,7 +637,7 @@ pub fn convert_recv_packet_to_tx<C: MsgToTxConverter>(
     let packed_tx = TransactionView::new_advanced_builder()
         .cell_dep(
             CellDep::new_builder()
-                .out_point(converter.get_client_outpoint())
+                .out_point(converter.get_client_outpoint(&client_id))
                 .build(),
         )
         .input(channel_input)
@@ -575,7 +649,7 @@ pub fn convert_recv_packet_to_tx<C: MsgToTxConverter>(
                         .hash_type(ScriptHashType::Type.into())
                         .args(
                             ChannelArgs {
-                                client_id: converter.get_client_id(),
+                                client_id,
                                 open: true,
                                 channel_id: channel_idx,
                                 port_id: port_id_in_args,
@@ -599,7 +673,7 @@ pub fn convert_recv_packet_to_tx<C: MsgToTxConverter>(
                                 channel_id: channel_idx,
                                 port_id: port_id_in_args,
                                 sequence: seq,
-                                owner: converter.get_packet_owner(),
+                                owner: converter.get_packet_owner(&port_id),
                             }
                             .to_args()
                             .pack(),
@@ -634,6 +708,108 @@ pub fn convert_recv_packet_to_tx<C: MsgToTxConverter>(
     })
 }
 
+// Only ordered channels are handled here, since a timeout on an ordered channel closes it,
+// while a timed out packet on an unordered channel is left to the packet owner.
+pub fn convert_timeout_packet_to_tx<C: MsgToTxConverter>(
+    msg: MsgTimeout,
+    converter: &C,
+) -> Result<CkbTxInfo, Error> {
+    let channel_id = msg.packet.source_channel.clone();
+    let port_id = msg.packet.source_port.clone();
+    let old_channel_end = converter.get_ibc_channel(&channel_id);
+    let client_id =
+        converter.get_connection_client_id(old_channel_end.connection_hops[0] as u16)?;
+    if !matches!(old_channel_end.order, CkbOrdering::Ordered) {
+        return Err(Error::ckb_unordered_packet_timeout(channel_id.to_string()));
+    }
+    let mut new_channel_end = old_channel_end.clone();
+    new_channel_end.state = CkbState::Closed;
+
+    let old_channel_end_encoded = get_encoded_object(old_channel_end);
+    let new_channel_end_encoded = get_encoded_object(new_channel_end);
+
+    let envelope = Envelope {
+        msg_type: MsgType::MsgTimeoutPacket,
+        content: rlp::encode(&CkbMsgTimeoutPacket {
+            proofs: convert_proof(msg.proofs)?,
+        })
+        .to_vec(),
+    };
+
+    let lock_args = ChannelArgs {
+        client_id,
+        open: false,
+        channel_id: get_channel_idx(&channel_id)?,
+        port_id: convert_port_id_to_array(&port_id)?,
+    };
+
+    let channel_input = converter.get_ibc_channel_input(&channel_id, &port_id);
+    let packet_input = converter.get_packet_cell_input(channel_id, port_id, msg.packet.sequence);
+    let packed_tx = TransactionView::new_advanced_builder()
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(converter.get_client_outpoint(&client_id))
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .dep_type(DepType::Code.into())
+                .out_point(converter.get_chan_contract_outpoint())
+                .build(),
+        )
+        .input(channel_input)
+        .input(packet_input)
+        .output(
+            CellOutput::new_builder()
+                .lock(
+                    Script::new_builder()
+                        .code_hash(converter.get_channel_code_hash())
+                        .hash_type(ScriptHashType::Type.into())
+                        .args(lock_args.to_args().pack())
+                        .build(),
+                )
+                .capacity(get_channel_capacity().pack())
+                .build(),
+        )
+        .output_data(new_channel_end_encoded.data)
+        .witness(
+            WitnessArgs::new_builder()
+                .input_type(old_channel_end_encoded.witness)
+                .output_type(new_channel_end_encoded.witness)
+                .build()
+                .as_bytes()
+                .pack(),
+        )
+        .build();
+    let event = IbcEvent::TimeoutPacket(TimeoutPacket { packet: msg.packet });
+    Ok(CkbTxInfo {
+        unsigned_tx: Some(packed_tx),
+        envelope,
+        input_capacity: CHANNEL_CELL_CAPACITY + PACKET_CELL_CAPACITY,
+        event: Some(event),
+    })
+}
+
+// Packets of an ordered channel must be processed in the exact order of their sequences,
+// which the channel cell keeps track of.
+fn check_packet_order(channel: &IbcChannel, expected: u64, sequence: u64) -> Result<(), Error> {
+    if matches!(channel.order, CkbOrdering::Ordered) && sequence != expected {
+        return Err(Error::ckb_packet_sequence_mismatch(expected, sequence));
+    }
+    Ok(())
+}
+
+// The ordered channels processed the packets whose sequences are below the next one
+// expected, whereas the unordered ones keep no such track, so that the live cells of
+// their packets tell whether they are `processed`.
+fn packet_executed(channel: &IbcChannel, expected: u64, sequence: u64, processed: bool) -> bool {
+    if matches!(channel.order, CkbOrdering::Ordered) {
+        sequence < expected
+    } else {
+        processed
+    }
+}
+
 pub fn convert_channel_end(
     channel_end: ChannelEnd,
     port_id: PortId,
@@ -706,3 +882,56 @@ pub fn convert_ibc_packet(packet: Packet) -> CkbPacket {
         data: packet.data,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ckb_ics_axon::handler::IbcChannel;
+    use ckb_ics_axon::object::{ChannelCounterparty, Ordering as CkbOrdering, State as CkbState};
+
+    use super::{check_packet_order, packet_executed};
+
+    fn channel(order: CkbOrdering) -> IbcChannel {
+        IbcChannel {
+            num: 0,
+            port_id: String::new(),
+            state: CkbState::Open,
+            order,
+            sequence: Default::default(),
+            counterparty: ChannelCounterparty {
+                port_id: String::new(),
+                channel_id: String::new(),
+            },
+            connection_hops: vec![0],
+        }
+    }
+
+    #[test]
+    fn test_ordered_channel_requires_next_sequence() {
+        let channel = channel(CkbOrdering::Ordered);
+        assert!(check_packet_order(&channel, 2, 2).is_ok());
+        assert!(check_packet_order(&channel, 2, 1).is_err());
+        assert!(check_packet_order(&channel, 2, 3).is_err());
+    }
+
+    #[test]
+    fn test_unordered_channel_accepts_any_sequence() {
+        let channel = channel(CkbOrdering::Unordered);
+        assert!(check_packet_order(&channel, 2, 1).is_ok());
+        assert!(check_packet_order(&channel, 2, 3).is_ok());
+    }
+
+    #[test]
+    fn test_packets_below_next_sequence_of_ordered_channel_are_executed() {
+        let channel = channel(CkbOrdering::Ordered);
+        assert!(packet_executed(&channel, 2, 1, false));
+        assert!(!packet_executed(&channel, 2, 2, false));
+        assert!(!packet_executed(&channel, 2, 3, true));
+    }
+
+    #[test]
+    fn test_processed_packets_of_unordered_channel_are_executed() {
+        let channel = channel(CkbOrdering::Unordered);
+        assert!(!packet_executed(&channel, 2, 1, false));
+        assert!(packet_executed(&channel, 2, 3, true));
+    }
+}
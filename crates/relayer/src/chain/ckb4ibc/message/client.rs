@@ -1,8 +1,16 @@
 use ckb_ics_axon::message::{Envelope, MsgType};
+use ckb_types::{
+    core::{Capacity, DepType, TransactionView},
+    packed::{CellDep, CellOutput, WitnessArgs},
+    prelude::{Builder, Entity, Pack},
+    H256,
+};
 use ibc_relayer_types::{
+    clients::ics07_tendermint::header::Header as TmHeader,
     core::ics02_client::{
         client_type::ClientType,
         events::{Attributes, UpdateClient},
+        header::Header,
         msgs::update_client::MsgUpdateClient,
     },
     events::IbcEvent,
@@ -11,12 +19,18 @@ use ibc_relayer_types::{
 
 use super::{CkbTxInfo, MsgToTxConverter};
 
+use crate::chain::ckb4ibc::tendermint::{
+    get_client_lock_script, is_tendermint_client, TendermintClient,
+};
 use crate::error::Error;
 
 pub fn convert_update_client<C: MsgToTxConverter>(
     msg: MsgUpdateClient,
-    _converter: &C,
+    converter: &C,
 ) -> Result<CkbTxInfo, Error> {
+    if is_tendermint_client(&msg.client_id) {
+        return convert_update_tendermint_client(msg, converter);
+    }
     Ok(CkbTxInfo {
         unsigned_tx: None,
         envelope: Envelope {
@@ -34,3 +48,79 @@ pub fn convert_update_client<C: MsgToTxConverter>(
         })),
     })
 }
+
+/// Event of an update of the Tendermint `client` whose consensus state is kept already,
+/// which needs no transaction
+pub fn redundant_update_event(
+    msg: &MsgUpdateClient,
+    client: &TendermintClient,
+) -> Result<Option<IbcEvent>, Error> {
+    let header = TmHeader::try_from(msg.header.clone()).map_err(|e| {
+        Error::ckb_tendermint_client_invalid(msg.client_id.to_string(), format!("header: {e}"))
+    })?;
+    if !client.has_consensus_state_of(&header) {
+        return Ok(None);
+    }
+    Ok(Some(IbcEvent::UpdateClient(UpdateClient {
+        common: Attributes {
+            client_id: msg.client_id.clone(),
+            client_type: ClientType::Tendermint,
+            consensus_height: header.height(),
+        },
+        header: Some(Box::new(header)),
+    })))
+}
+
+/// Consume the cell of the Tendermint client and output the updated one, the header
+/// being verified by the contract of the client from the envelope
+fn convert_update_tendermint_client<C: MsgToTxConverter>(
+    msg: MsgUpdateClient,
+    converter: &C,
+) -> Result<CkbTxInfo, Error> {
+    let header = TmHeader::try_from(msg.header.clone()).map_err(|e| {
+        Error::ckb_tendermint_client_invalid(msg.client_id.to_string(), format!("header: {e}"))
+    })?;
+    let client = converter.get_config().client_id_of(&msg.client_id);
+    let cell = converter.get_tendermint_client(&msg.client_id);
+    let updated = cell.client.update(&msg.client_id, header.clone())?;
+
+    let data = rlp::encode(&updated);
+    let output = CellOutput::new_builder()
+        .lock(get_client_lock_script(&H256::from(client)))
+        .build();
+    let occupied_capacity = output
+        .occupied_capacity(Capacity::bytes(data.len()).unwrap())
+        .unwrap()
+        .as_u64();
+    let capacity = cell.capacity.max(occupied_capacity);
+
+    let packed_tx = TransactionView::new_advanced_builder()
+        .cell_dep(
+            CellDep::new_builder()
+                .dep_type(DepType::Code.into())
+                .out_point(converter.get_client_outpoint(&client))
+                .build(),
+        )
+        .input(cell.input)
+        .output(output.as_builder().capacity(capacity.pack()).build())
+        .output_data(data.as_ref().pack())
+        .witness(WitnessArgs::default().as_bytes().pack())
+        .build();
+    let event = IbcEvent::UpdateClient(UpdateClient {
+        common: Attributes {
+            client_id: msg.client_id,
+            client_type: ClientType::Tendermint,
+            consensus_height: header.height(),
+        },
+        header: Some(Box::new(header)),
+    });
+    Ok(CkbTxInfo {
+        unsigned_tx: Some(packed_tx),
+        envelope: Envelope {
+            msg_type: MsgType::MsgClientUpdate,
+            content: msg.header.value,
+        },
+        input_capacity: cell.capacity,
+        event: Some(event),
+    })
+}
@@ -1,13 +1,14 @@
-use std::str::FromStr;
-
 use crate::{
-    chain::ckb4ibc::utils::{
-        convert_proof, get_connection_capacity, get_connection_id, get_connection_idx,
-        get_connection_lock_script, get_encoded_object,
+    chain::ckb4ibc::{
+        extractor::connection_attributes,
+        utils::{
+            convert_proof, get_connection_capacity, get_connection_idx, get_connection_lock_script,
+            get_encoded_object,
+        },
     },
     error::Error,
 };
-use ckb_ics_axon::{consts::CONNECTION_CELL_CAPACITY, convert_client_id_to_string};
+use ckb_ics_axon::consts::CONNECTION_CELL_CAPACITY;
 use ckb_ics_axon::{
     message::{
         Envelope, MsgConnectionOpenAck as CkbMsgConnectionOpenAck,
@@ -23,18 +24,16 @@ use ckb_types::{
     prelude::{Builder, Entity, Pack},
 };
 use ibc_relayer_types::{
-    core::{
-        ics03_connection::{
-            events::{Attributes, OpenAck, OpenConfirm, OpenInit, OpenTry},
-            msgs::{
-                conn_open_ack::MsgConnectionOpenAck, conn_open_confirm::MsgConnectionOpenConfirm,
-                conn_open_init::MsgConnectionOpenInit, conn_open_try::MsgConnectionOpenTry,
-            },
+    core::ics03_connection::{
+        events::{OpenAck, OpenConfirm, OpenInit, OpenTry},
+        msgs::{
+            conn_open_ack::MsgConnectionOpenAck, conn_open_confirm::MsgConnectionOpenConfirm,
+            conn_open_init::MsgConnectionOpenInit, conn_open_try::MsgConnectionOpenTry,
         },
-        ics24_host::identifier::ClientId,
     },
     events::IbcEvent,
 };
+use tracing::info;
 
 use super::{CkbTxInfo, MsgToTxConverter};
 
@@ -42,6 +41,7 @@ pub fn convert_conn_open_init_to_tx<C: MsgToTxConverter>(
     msg: MsgConnectionOpenInit,
     converter: &C,
 ) -> Result<CkbTxInfo, Error> {
+    let client = converter.get_config().client_id_of(&msg.client_id);
     let client_id = msg.client_id.to_string();
 
     let remote_client_id = msg.counterparty.client_id().to_string();
@@ -57,7 +57,8 @@ pub fn convert_conn_open_init_to_tx<C: MsgToTxConverter>(
         delay_period: msg.delay_period.as_secs(),
     };
     let old_ibc_connection_cell = converter.get_ibc_connections();
-    let this_conn_idx = old_ibc_connection_cell.next_channel_number;
+    let this_conn_idx = old_ibc_connection_cell.connections.len();
+    let attrs = connection_attributes(&connection_end, this_conn_idx)?;
     let mut new_ibc_connection_cell = old_ibc_connection_cell.clone();
     new_ibc_connection_cell.connections.push(connection_end);
     new_ibc_connection_cell.next_connection_number += 1;
@@ -74,7 +75,7 @@ pub fn convert_conn_open_init_to_tx<C: MsgToTxConverter>(
         .cell_dep(
             CellDep::new_builder()
                 .dep_type(DepType::Code.into())
-                .out_point(converter.get_client_outpoint())
+                .out_point(converter.get_client_outpoint(&client))
                 .build(),
         )
         .cell_dep(
@@ -100,12 +101,7 @@ pub fn convert_conn_open_init_to_tx<C: MsgToTxConverter>(
                 .pack(),
         )
         .build();
-    let event = IbcEvent::OpenInitConnection(OpenInit(Attributes {
-        connection_id: Some(get_connection_id(this_conn_idx)),
-        client_id: msg.client_id.clone(),
-        counterparty_connection_id: None,
-        counterparty_client_id: msg.counterparty.client_id().clone(),
-    }));
+    let event = IbcEvent::OpenInitConnection(OpenInit(attrs));
     Ok(CkbTxInfo {
         unsigned_tx: Some(packed_tx),
         envelope,
@@ -118,6 +114,7 @@ pub fn convert_conn_open_try_to_tx<C: MsgToTxConverter>(
     msg: MsgConnectionOpenTry,
     converter: &C,
 ) -> Result<CkbTxInfo, Error> {
+    let client = converter.get_config().client_id_of(&msg.client_id);
     let client_id = msg.client_id.to_string();
 
     let remote_client_id = msg.counterparty.client_id().to_string();
@@ -137,7 +134,8 @@ pub fn convert_conn_open_try_to_tx<C: MsgToTxConverter>(
         delay_period: msg.delay_period.as_secs(),
     };
     let old_ibc_connection_cell = converter.get_ibc_connections();
-    let this_conn_idx = old_ibc_connection_cell.next_channel_number;
+    let this_conn_idx = old_ibc_connection_cell.connections.len();
+    let attrs = connection_attributes(&connection_end, this_conn_idx)?;
     let mut new_ibc_connection_cell = old_ibc_connection_cell.clone();
     new_ibc_connection_cell.connections.push(connection_end);
     new_ibc_connection_cell.next_connection_number += 1;
@@ -157,7 +155,7 @@ pub fn convert_conn_open_try_to_tx<C: MsgToTxConverter>(
         .cell_dep(
             CellDep::new_builder()
                 .dep_type(DepType::Code.into())
-                .out_point(converter.get_client_outpoint())
+                .out_point(converter.get_client_outpoint(&client))
                 .build(),
         )
         .cell_dep(
@@ -183,12 +181,7 @@ pub fn convert_conn_open_try_to_tx<C: MsgToTxConverter>(
                 .pack(),
         )
         .build();
-    let event = IbcEvent::OpenTryConnection(OpenTry(Attributes {
-        connection_id: Some(get_connection_id(this_conn_idx)),
-        client_id: msg.client_id,
-        counterparty_connection_id: msg.counterparty.connection_id.clone(),
-        counterparty_client_id: msg.counterparty.client_id().clone(),
-    }));
+    let event = IbcEvent::OpenTryConnection(OpenTry(attrs));
     Ok(CkbTxInfo {
         unsigned_tx: Some(packed_tx),
         envelope,
@@ -204,11 +197,25 @@ pub fn convert_conn_open_ack_to_tx<C: MsgToTxConverter>(
     let old_ibc_connection_cell = converter.get_ibc_connections();
     let mut new_ibc_connection_cell = old_ibc_connection_cell.clone();
 
+    let client = converter.get_connection_client_id(get_connection_idx(&msg.connection_id)?)?;
     let idx = get_connection_idx(&msg.connection_id)? as usize;
     let mut connection_end = new_ibc_connection_cell.connections.get_mut(idx).unwrap();
+    let executed = matches!(connection_end.state, State::Open);
     connection_end.state = State::Open;
     connection_end.counterparty.connection_id =
         Some(msg.counterparty_connection_id.as_str().to_string());
+    let attrs = connection_attributes(connection_end, idx)?;
+    if executed {
+        info!(
+            "connection {} is open already, skipping its ack",
+            msg.connection_id
+        );
+        let event = IbcEvent::OpenAckConnection(OpenAck(attrs));
+        return Ok(CkbTxInfo::executed(
+            MsgType::MsgConnectionOpenAck,
+            Some(event),
+        ));
+    }
 
     let envelope = Envelope {
         msg_type: MsgType::MsgConnectionOpenAck,
@@ -225,7 +232,7 @@ pub fn convert_conn_open_ack_to_tx<C: MsgToTxConverter>(
         .cell_dep(
             CellDep::new_builder()
                 .dep_type(DepType::Code.into())
-                .out_point(converter.get_client_outpoint())
+                .out_point(converter.get_client_outpoint(&client))
                 .build(),
         )
         .cell_dep(
@@ -251,13 +258,7 @@ pub fn convert_conn_open_ack_to_tx<C: MsgToTxConverter>(
                 .pack(),
         )
         .build();
-    let client_id = convert_client_id_to_string(converter.get_client_id());
-    let event = IbcEvent::OpenAckConnection(OpenAck(Attributes {
-        connection_id: Some(msg.connection_id),
-        client_id: ClientId::from_str(&client_id).unwrap(),
-        counterparty_connection_id: Some(msg.counterparty_connection_id),
-        counterparty_client_id: ClientId::from_str("counterpartyclient-dummy").unwrap(),
-    }));
+    let event = IbcEvent::OpenAckConnection(OpenAck(attrs));
     Ok(CkbTxInfo {
         unsigned_tx: Some(packed_tx),
         envelope,
@@ -273,9 +274,23 @@ pub fn convert_conn_open_confirm_to_tx<C: MsgToTxConverter>(
     let old_ibc_connection_cell = converter.get_ibc_connections();
     let mut new_ibc_connection_cell = old_ibc_connection_cell.clone();
 
+    let client = converter.get_connection_client_id(get_connection_idx(&msg.connection_id)?)?;
     let idx = get_connection_idx(&msg.connection_id)? as usize;
     let mut connection_end = new_ibc_connection_cell.connections.get_mut(idx).unwrap();
+    let executed = matches!(connection_end.state, State::Open);
     connection_end.state = State::Open;
+    let attrs = connection_attributes(connection_end, idx)?;
+    if executed {
+        info!(
+            "connection {} is open already, skipping its confirm",
+            msg.connection_id
+        );
+        let event = IbcEvent::OpenConfirmConnection(OpenConfirm(attrs));
+        return Ok(CkbTxInfo::executed(
+            MsgType::MsgConnectionOpenConfirm,
+            Some(event),
+        ));
+    }
 
     let envelope = Envelope {
         msg_type: MsgType::MsgConnectionOpenConfirm,
@@ -292,7 +307,7 @@ pub fn convert_conn_open_confirm_to_tx<C: MsgToTxConverter>(
         .cell_dep(
             CellDep::new_builder()
                 .dep_type(DepType::Code.into())
-                .out_point(converter.get_client_outpoint())
+                .out_point(converter.get_client_outpoint(&client))
                 .build(),
         )
         .cell_dep(
@@ -318,13 +333,7 @@ pub fn convert_conn_open_confirm_to_tx<C: MsgToTxConverter>(
                 .pack(),
         )
         .build();
-    let client_id = convert_client_id_to_string(converter.get_client_id());
-    let event = IbcEvent::OpenConfirmConnection(OpenConfirm(Attributes {
-        connection_id: Some(msg.connection_id),
-        client_id: ClientId::from_str(&client_id).unwrap(),
-        counterparty_connection_id: None,
-        counterparty_client_id: ClientId::from_str("counterpartyclient-dummy").unwrap(),
-    }));
+    let event = IbcEvent::OpenConfirmConnection(OpenConfirm(attrs));
     Ok(CkbTxInfo {
         unsigned_tx: Some(packed_tx),
         envelope,
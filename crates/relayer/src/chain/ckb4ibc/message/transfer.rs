@@ -0,0 +1,170 @@
+use ckb_ics_axon::consts::CHANNEL_CELL_CAPACITY;
+use ckb_ics_axon::handler::{IbcPacket, PacketStatus};
+use ckb_ics_axon::message::{Envelope, MsgType};
+use ckb_ics_axon::object::{Packet as CkbPacket, State as CkbState};
+use ckb_ics_axon::{ChannelArgs, PacketArgs};
+use ckb_types::core::{DepType, ScriptHashType, TransactionView};
+use ckb_types::packed::{CellDep, CellOutput, Script, WitnessArgs};
+use ckb_types::prelude::{Builder, Entity, Pack};
+use ibc_proto::ibc::applications::transfer::v2::FungibleTokenPacketData;
+use ibc_relayer_types::applications::transfer::msgs::transfer::MsgTransfer;
+use ibc_relayer_types::core::ics04_channel::events::SendPacket;
+use ibc_relayer_types::core::ics04_channel::packet::{Packet, Sequence};
+use ibc_relayer_types::events::IbcEvent;
+
+use super::{CkbTxInfo, MsgToTxConverter};
+use crate::chain::ckb4ibc::utils::{
+    convert_port_id_to_array, get_channel_capacity, get_channel_idx, get_encoded_object,
+    get_packet_capacity,
+};
+use crate::error::Error;
+
+/// Send an ICS-20 packet carrying the transfer over an open channel of the chain.
+///
+/// Only the channel and packet cells are assembled, the tokens are not escrowed by the
+/// transaction, so this is meant to exercise the packet relaying end to end.
+pub fn convert_transfer_to_tx<C: MsgToTxConverter>(
+    msg: MsgTransfer,
+    converter: &C,
+) -> Result<CkbTxInfo, Error> {
+    let channel_id = msg.source_channel.clone();
+    let port_id = msg.source_port.clone();
+    let old_channel_end = converter.get_ibc_channel(&channel_id);
+    if !matches!(old_channel_end.state, CkbState::Open) {
+        return Err(Error::other_error(format!(
+            "cannot send packets over channel {channel_id} which is not open"
+        )));
+    }
+    let client_id =
+        converter.get_connection_client_id(old_channel_end.connection_hops[0] as u16)?;
+
+    let data = FungibleTokenPacketData {
+        denom: msg.token.denom.clone(),
+        amount: msg.token.amount.clone(),
+        sender: msg.sender.to_string(),
+        receiver: msg.receiver.to_string(),
+        memo: msg.memo.clone().unwrap_or_default(),
+    };
+    let data = serde_json::to_vec(&data).map_err(|e| Error::other_error(e.to_string()))?;
+
+    let sequence = old_channel_end.sequence.next_send_packet;
+    let packet = Packet {
+        sequence: Sequence::from(u64::from(sequence)),
+        source_port: port_id.clone(),
+        source_channel: channel_id.clone(),
+        destination_port: old_channel_end.counterparty.port_id.parse().map_err(|_| {
+            Error::ckb_port_id_invalid(old_channel_end.counterparty.port_id.clone())
+        })?,
+        destination_channel: old_channel_end
+            .counterparty
+            .channel_id
+            .parse()
+            .map_err(|_| {
+                Error::ckb_chan_id_invalid(old_channel_end.counterparty.channel_id.clone())
+            })?,
+        data,
+        timeout_height: msg.timeout_height,
+        timeout_timestamp: msg.timeout_timestamp,
+    };
+
+    let mut new_channel_end = old_channel_end.clone();
+    new_channel_end.sequence.next_send_packet += 1;
+    let old_channel_end_encoded = get_encoded_object(old_channel_end);
+    let new_channel_end_encoded = get_encoded_object(new_channel_end);
+
+    let ibc_packet = IbcPacket {
+        packet: CkbPacket {
+            sequence,
+            source_port_id: port_id.to_string(),
+            source_channel_id: channel_id.to_string(),
+            destination_port_id: packet.destination_port.to_string(),
+            destination_channel_id: packet.destination_channel.to_string(),
+            data: packet.data.clone(),
+        },
+        tx_hash: None,
+        status: PacketStatus::Send,
+    };
+    let ibc_packet_encoded = get_encoded_object(ibc_packet);
+
+    let envelope = Envelope {
+        msg_type: MsgType::MsgSendPacket,
+        content: vec![],
+    };
+
+    let channel_idx = get_channel_idx(&channel_id)?;
+    let port_id_in_args = convert_port_id_to_array(&port_id)?;
+    let channel_input = converter.get_ibc_channel_input(&channel_id, &port_id);
+    let packed_tx = TransactionView::new_advanced_builder()
+        .cell_dep(
+            CellDep::new_builder()
+                .dep_type(DepType::Code.into())
+                .out_point(converter.get_chan_contract_outpoint())
+                .build(),
+        )
+        .input(channel_input)
+        .output(
+            CellOutput::new_builder()
+                .lock(
+                    Script::new_builder()
+                        .code_hash(converter.get_channel_code_hash())
+                        .hash_type(ScriptHashType::Type.into())
+                        .args(
+                            ChannelArgs {
+                                client_id,
+                                open: true,
+                                channel_id: channel_idx,
+                                port_id: port_id_in_args,
+                            }
+                            .to_args()
+                            .pack(),
+                        )
+                        .build(),
+                )
+                .capacity(get_channel_capacity().pack())
+                .build(),
+        )
+        .output_data(new_channel_end_encoded.data)
+        .output(
+            CellOutput::new_builder()
+                .lock(
+                    Script::new_builder()
+                        .code_hash(converter.get_packet_code_hash())
+                        .args(
+                            PacketArgs {
+                                channel_id: channel_idx,
+                                port_id: port_id_in_args,
+                                sequence,
+                                owner: converter.get_packet_owner(&port_id),
+                            }
+                            .to_args()
+                            .pack(),
+                        )
+                        .build(),
+                )
+                .capacity(get_packet_capacity().pack())
+                .build(),
+        )
+        .output_data(ibc_packet_encoded.data)
+        .witness(
+            WitnessArgs::new_builder()
+                .input_type(old_channel_end_encoded.witness)
+                .output_type(new_channel_end_encoded.witness)
+                .build()
+                .as_bytes()
+                .pack(),
+        )
+        .witness(
+            WitnessArgs::new_builder()
+                .output_type(ibc_packet_encoded.witness)
+                .build()
+                .as_bytes()
+                .pack(),
+        )
+        .build();
+    Ok(CkbTxInfo {
+        unsigned_tx: Some(packed_tx),
+        envelope,
+        input_capacity: CHANNEL_CELL_CAPACITY,
+        event: Some(IbcEvent::SendPacket(SendPacket { packet })),
+    })
+}
@@ -1,20 +1,21 @@
+use std::collections::{BTreeSet, HashMap};
 use std::str::FromStr;
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use ckb_ics_axon::handler::{IbcPacket, PacketStatus};
 use ckb_ics_axon::object::State as CkbState;
-use ckb_ics_axon::{ChannelArgs, ConnectionArgs};
-use ckb_jsonrpc_types::{Status, TransactionView};
-use ckb_sdk::rpc::ckb_indexer::SearchKey;
+use ckb_ics_axon::{ChannelArgs, ConnectionArgs, PacketArgs};
+use ckb_jsonrpc_types::{HeaderView, Status, TransactionView};
+use ckb_sdk::rpc::ckb_indexer::{Cell, SearchKey};
 use ckb_types::core::ScriptHashType;
-use ckb_types::packed::Script;
+use ckb_types::packed::{Script, Transaction};
 use ckb_types::prelude::{Builder, Entity, Pack};
 use ckb_types::H256;
 use crossbeam_channel::Receiver;
 use ibc_relayer_types::core::ics02_client::height::Height;
 use ibc_relayer_types::core::ics03_connection::events::{
-    Attributes, OpenInit as ConnectionOpenInit, OpenTry as ConnectionOpenTry,
+    OpenInit as ConnectionOpenInit, OpenTry as ConnectionOpenTry,
 };
 use ibc_relayer_types::core::ics04_channel::channel::State;
 use ibc_relayer_types::core::ics04_channel::events::{
@@ -23,24 +24,56 @@ use ibc_relayer_types::core::ics04_channel::events::{
 };
 use ibc_relayer_types::core::ics04_channel::packet::{Packet, Sequence};
 use ibc_relayer_types::core::ics04_channel::timeout::TimeoutHeight;
-use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
+use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, PortId};
 use ibc_relayer_types::events::IbcEvent;
 use ibc_relayer_types::timestamp::Timestamp;
 use tokio::runtime::Runtime as TokioRuntime;
+use tracing::{debug, error, info, warn};
 
 use crate::chain::ckb::prelude::CkbReader;
 use crate::chain::ckb::rpc_client::RpcClient;
 use crate::chain::ckb4ibc::extractor::{
-    extract_channel_end_from_tx, extract_ibc_connections_from_tx, extract_ibc_packet_from_tx,
+    connection_attributes, extract_channel_end_from_tx, extract_ibc_connections_from_tx,
+    extract_ibc_packet_from_tx,
 };
 use crate::chain::tracking::TrackingId;
 use crate::config::ckb4ibc::ChainConfig;
+use crate::decision::{self, Decision, DecisionKind};
+use crate::error::Error as RelayerError;
 use crate::event::bus::EventBus;
 use crate::event::monitor::{Error, EventBatch, MonitorCmd, Next, Result, TxMonitorCmd};
 use crate::event::IbcEventWithHeight;
+use crate::progress::SharedRelayProgress;
+use crate::telemetry;
 
+use super::author::{fetch_input_locks, recover_signers, KnownAuthors, TxAuthor};
 use super::cache_set::CacheSet;
-use super::utils::{get_script_hash, get_search_key};
+use super::committed_txs::CommittedTxs;
+use super::footprint::fetch_all_cells;
+use super::packet_size::oversized_reason;
+use super::scan_offset::{ScanOffset, ScanOffsets};
+use super::scanned_blocks::ScannedBlocks;
+use super::state_cache::IbcStateCache;
+use super::tip_subscription::TipSubscription;
+use super::utils::{
+    convert_port_id_to_array, decode_transaction, get_channel_idx, get_script_hash, get_search_key,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Interval between the polls of the tip while the node pushes its tips, in case the
+/// subscription stalls without being closed
+const SUBSCRIBED_POLL_INTERVAL: Duration = Duration::from_secs(60);
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Delay before the next attempt to reach the node after `failures` consecutive
+/// failures, doubled on every failure up to `MAX_RETRY_DELAY`
+fn retry_delay(failures: u32) -> Duration {
+    let exponent = failures.saturating_sub(1).min(16);
+    INITIAL_RETRY_DELAY
+        .saturating_mul(1 << exponent)
+        .min(MAX_RETRY_DELAY)
+}
 
 // todo add cell emitter here
 pub struct Ckb4IbcEventMonitor {
@@ -50,6 +83,36 @@ pub struct Ckb4IbcEventMonitor {
     event_bus: EventBus<Arc<Result<EventBatch>>>,
     config: ChainConfig,
     cache_set: RwLock<CacheSet<H256>>,
+    scan_offsets: RwLock<ScanOffsets>,
+    committed_txs: RwLock<CommittedTxs>,
+    scanned_blocks: ScannedBlocks,
+    /// Cells cached by the chain endpoint, dropped when they are invalidated by a reorg
+    ibc_state_cache: Arc<IbcStateCache>,
+    /// Tip block number seen the last time the node was reached
+    last_tip: Option<u64>,
+    /// Tips pushed by the node, if `ws_url` is configured
+    tip_subscription: Option<TipSubscription>,
+    /// When the tip was last fetched from the node or pushed by it
+    last_tip_at: Instant,
+    /// Number of consecutive failures to reach the node
+    failures: u32,
+    /// Whether the indexer lagged behind the node beyond `max_indexer_lag` when last checked
+    indexer_lagging: bool,
+    /// Accounts the signers of the observed transactions are checked against, if
+    /// `verify_tx_authors` is enabled
+    known_authors: Option<KnownAuthors>,
+    /// Transactions whose author was already reported
+    authored_txs: RwLock<CacheSet<H256>>,
+    progress: SharedRelayProgress,
+    /// Heights up to which the events of the scans were emitted before the relayer
+    /// restarted, or the ones preceding `monitor_start` for the scans which never ran,
+    /// the cells committed up to them being skipped. The packet scans of the channel
+    /// ends are added as the channel ends are found.
+    resumed_heights: RwLock<HashMap<String, u64>>,
+    /// Height the packet scans which never ran resume from
+    packets_resumed_height: u64,
+    /// Channel end of each live channel cell, by the hash of its transaction
+    channel_cells: RwLock<HashMap<H256, (PortId, ChannelId)>>,
 }
 
 impl Ckb4IbcEventMonitor {
@@ -57,56 +120,190 @@ impl Ckb4IbcEventMonitor {
         rt: Arc<TokioRuntime>,
         rpc_client: Arc<RpcClient>,
         config: ChainConfig,
+        ibc_state_cache: Arc<IbcStateCache>,
+        known_authors: Option<KnownAuthors>,
+        progress: SharedRelayProgress,
+        start_height: u64,
     ) -> (Self, TxMonitorCmd) {
         let (tx_cmd, rx_cmd) = crossbeam_channel::unbounded();
-        let monitor = Ckb4IbcEventMonitor {
+        let scan_offsets = ScanOffsets::load(config.scan_offsets_path.clone());
+        let tip_subscription = config
+            .ws_url
+            .clone()
+            .map(|url| TipSubscription::spawn(&rt, url, config.id.clone()));
+        let mut monitor = Ckb4IbcEventMonitor {
             rt,
             rpc_client,
             rx_cmd,
             event_bus: EventBus::default(),
             config,
             cache_set: RwLock::new(CacheSet::new(512)),
+            scan_offsets: RwLock::new(scan_offsets),
+            committed_txs: RwLock::new(CommittedTxs::default()),
+            scanned_blocks: ScannedBlocks::default(),
+            ibc_state_cache,
+            last_tip: None,
+            tip_subscription,
+            last_tip_at: Instant::now(),
+            failures: 0,
+            indexer_lagging: false,
+            known_authors,
+            authored_txs: RwLock::new(CacheSet::new(512)),
+            progress,
+            resumed_heights: RwLock::default(),
+            packets_resumed_height: start_height,
+            channel_cells: RwLock::default(),
+        };
+        let scans = monitor
+            .config
+            .all_client_type_args()
+            .into_iter()
+            .map(|client_type_args| monitor.channel_scan(client_type_args))
+            .collect::<Vec<_>>();
+        let resumed_heights = {
+            let mut progress = monitor.progress.lock().unwrap();
+            // the packets used to be scanned at once rather than per channel end
+            if let Some(height) = progress.forget_scan("packet") {
+                monitor.packets_resumed_height = height;
+            }
+            scans
+                .into_iter()
+                .map(|scan| {
+                    let height = progress.scanned_height(&scan).unwrap_or(start_height);
+                    (scan, height)
+                })
+                .collect()
         };
+        monitor.resumed_heights = RwLock::new(resumed_heights);
         (monitor, TxMonitorCmd::new(tx_cmd))
     }
 
     pub fn run(mut self) {
         let rt = self.rt.clone();
         loop {
-            std::thread::sleep(Duration::from_secs(5));
-            let result = rt.block_on(self.run_once());
+            let pushed_tip = match self.tip_subscription.as_mut() {
+                Some(subscription) if self.failures == 0 => {
+                    rt.block_on(subscription.next_tip(POLL_INTERVAL))
+                }
+                _ => {
+                    let delay = if self.failures == 0 {
+                        POLL_INTERVAL
+                    } else {
+                        retry_delay(self.failures)
+                    };
+                    std::thread::sleep(delay);
+                    None
+                }
+            };
+            let result = rt.block_on(self.run_once(pushed_tip));
             match result {
                 Next::Continue => continue,
                 Next::Abort => break,
             }
         }
     }
-    async fn run_once(&mut self) -> Next {
+
+    /// Scan the blocks up to `pushed_tip` if the node pushed one, or else up to the tip
+    /// polled from the node, unless the node pushes its tips and there is no new one
+    async fn run_once(&mut self, pushed_tip: Option<HeaderView>) -> Next {
         if let Ok(cmd) = self.rx_cmd.try_recv() {
             match cmd {
                 MonitorCmd::Shutdown => return Next::Abort,
-                MonitorCmd::Subscribe(tx) => tx.send(self.event_bus.subscribe()).unwrap(),
+                MonitorCmd::Subscribe(tx) => {
+                    if let Err(e) = tx.send(self.event_bus.subscribe()) {
+                        error!("failed to send back subscription: {e}");
+                    }
+                }
+            }
+        }
+
+        let subscribed = self
+            .tip_subscription
+            .as_ref()
+            .map_or(false, TipSubscription::is_connected);
+        let tip_header = match pushed_tip {
+            Some(tip_header) => tip_header,
+            None if subscribed
+                && self.failures == 0
+                && self.last_tip_at.elapsed() < SUBSCRIBED_POLL_INTERVAL =>
+            {
+                return Next::Continue;
             }
+            None => match self.rpc_client.get_tip_header().await {
+                Ok(tip_header) => tip_header,
+                Err(e) => {
+                    self.on_disconnected(e.to_string());
+                    return Next::Continue;
+                }
+            },
+        };
+        self.last_tip_at = Instant::now();
+        let tip = tip_header.inner.number.value();
+        if self.failures > 0 {
+            self.on_reconnected(tip);
         }
+        self.last_tip = Some(tip);
+        self.check_indexer_lag(tip).await;
+        self.roll_back_reorganized_blocks(&tip_header).await;
+        self.retract_orphaned_events(tip).await;
+
         let result = async {
             tokio::select! {
                 Ok(batch) = self.fetch_channel_events() => {
-                    batch
+                    Ok(batch)
                 },
                 Ok(batch) = self.fetch_connection_events() => {
-                    batch
+                    Ok(batch)
                 },
                 Ok(batch) = self.fetch_packet_events() => {
-                    batch
-                }
+                    Ok(batch)
+                },
+                else => Err("failed to fetch the events of every script".to_owned()),
             }
         }
         .await;
 
-        self.process_batch(result);
+        match result {
+            Ok(batch) => self.process_batch(batch),
+            Err(reason) => self.on_disconnected(reason),
+        }
         Next::Continue
     }
 
+    /// Notify the subscribers on the first failure to reach the node, and back off
+    /// the following attempts.
+    fn on_disconnected(&mut self, reason: String) {
+        self.failures += 1;
+        if self.failures == 1 {
+            error!(chain = %self.config.id, "failed to reach ckb node: {reason}");
+            self.propagate_error(Error::node_unreachable(self.config.id.clone(), reason));
+        } else {
+            warn!(
+                chain = %self.config.id,
+                "failed to reach ckb node {} times in a row, retrying in {:?}: {reason}",
+                self.failures,
+                retry_delay(self.failures)
+            );
+        }
+    }
+
+    /// Resume the scans from the last height known before the node was lost, or
+    /// from the new tip if the chain of the node went backwards in the meantime,
+    /// and notify the subscribers that the events since then may have been missed.
+    fn on_reconnected(&mut self, tip: u64) {
+        let height = self.last_tip.map_or(tip, |last_tip| last_tip.min(tip));
+        info!(
+            chain = %self.config.id,
+            "reconnected to ckb node after {} failures, resuming from block {height}",
+            self.failures
+        );
+        telemetry!(ws_reconnect, &self.config.id);
+
+        self.failures = 0;
+        self.rewind_scans(height);
+        self.propagate_error(Error::restarted(self.config.id.clone(), height));
+    }
+
     async fn fetch_connection_events(&self) -> Result<EventBatch> {
         let connection_code_hash = get_script_hash(&self.config.connection_type_args);
         let script = Script::new_builder()
@@ -136,7 +333,7 @@ impl Ckb4IbcEventMonitor {
             .await?
             .into_iter()
             .next()
-            .unwrap();
+            .ok_or_else(|| Error::collect_events_failed("no ibc connections cell".to_string()))?;
         if self.cache_set.read().unwrap().has(&tx_hash) {
             return Ok(EventBatch {
                 chain_id: self.config.id.clone(),
@@ -146,54 +343,23 @@ impl Ckb4IbcEventMonitor {
             });
         }
         self.cache_set.write().unwrap().insert(tx_hash.clone());
-        let events = ibc_connection_cell
-            .connections
-            .into_iter()
-            .enumerate()
-            .flat_map(|(idx, connection_end)| match connection_end.state {
-                CkbState::Init => {
-                    let attrs = Attributes {
-                        connection_id: Some(ConnectionId::from_str(&idx.to_string()).unwrap()), // todo connection id here is invalid
-                        client_id: ClientId::from_str(
-                            &String::from_utf8(self.config.client_id().to_vec()).unwrap(),
-                        )
-                        .unwrap(),
-                        counterparty_connection_id: None,
-                        counterparty_client_id: ClientId::from_str(
-                            &connection_end.counterparty.client_id,
-                        )
-                        .unwrap(),
-                    };
-                    let event = IbcEvent::OpenInitConnection(ConnectionOpenInit(attrs));
-                    Some(IbcEventWithHeight {
-                        event,
-                        height: Height::new(1, 1).unwrap(),
-                        tx_hash: tx_hash.clone().into(),
-                    })
-                }
-                CkbState::OpenTry => {
-                    let attrs = Attributes {
-                        connection_id: Some(ConnectionId::from_str(&idx.to_string()).unwrap()), // todo connection id here is invalid
-                        client_id: ClientId::from_str(
-                            &String::from_utf8(self.config.client_id().to_vec()).unwrap(),
-                        )
-                        .unwrap(),
-                        counterparty_connection_id: None,
-                        counterparty_client_id: ClientId::from_str(
-                            &connection_end.counterparty.client_id,
-                        )
-                        .unwrap(),
-                    };
-                    let event = IbcEvent::OpenTryConnection(ConnectionOpenTry(attrs));
-                    Some(IbcEventWithHeight {
-                        event,
-                        height: Height::new(1, 1).unwrap(),
-                        tx_hash: tx_hash.clone().into(),
-                    })
-                }
-                _ => None,
-            })
-            .collect::<Vec<_>>();
+        let mut events = vec![];
+        for (idx, connection_end) in ibc_connection_cell.connections.iter().enumerate() {
+            let attrs = || {
+                connection_attributes(connection_end, idx)
+                    .map_err(|e| Error::collect_events_failed(e.to_string()))
+            };
+            let event = match connection_end.state {
+                CkbState::Init => IbcEvent::OpenInitConnection(ConnectionOpenInit(attrs()?)),
+                CkbState::OpenTry => IbcEvent::OpenTryConnection(ConnectionOpenTry(attrs()?)),
+                _ => continue,
+            };
+            events.push(IbcEventWithHeight {
+                event,
+                height: Height::new(1, 1).unwrap(),
+                tx_hash: tx_hash.clone().into(),
+            });
+        }
         Ok(EventBatch {
             chain_id: self.config.id.clone(),
             tracking_id: TrackingId::Static("ckb connection events collection"),
@@ -203,34 +369,40 @@ impl Ckb4IbcEventMonitor {
     }
 
     async fn fetch_channel_events(&self) -> Result<EventBatch> {
-        let script = Script::new_builder()
-            .code_hash(get_script_hash(&self.config.channel_type_args))
-            .args(
-                ChannelArgs {
-                    client_id: self.config.client_id(),
-                    open: false,
-                    channel_id: Default::default(),
-                    port_id: Default::default(),
-                }
-                .get_prefix_for_searching_unopen()
-                .pack(),
-            )
-            .build();
+        let mut identified_channel_ends = vec![];
+        // the channel cells are locked by their client, so each client is scanned on its own
+        for client_type_args in self.config.all_client_type_args() {
+            let script = Script::new_builder()
+                .code_hash(get_script_hash(&self.config.channel_type_args))
+                .args(
+                    ChannelArgs {
+                        client_id: client_type_args.clone().into(),
+                        open: false,
+                        channel_id: Default::default(),
+                        port_id: Default::default(),
+                    }
+                    .get_prefix_for_searching_unopen()
+                    .pack(),
+                )
+                .build();
 
-        let key = get_search_key(script);
-        let identified_channel_ends = self
-            .search_and_extract(
-                key,
-                &|tx| {
-                    let hash = tx.hash.clone();
-                    let obj = extract_channel_end_from_tx(tx)
-                        .map_err(|_| Error::collect_events_failed("channel".to_string()))?
-                        .0;
-                    Ok((obj, hash))
-                },
-                20,
-            )
-            .await?;
+            let key = get_search_key(script);
+            let channel_ends = self
+                .scan_and_extract(
+                    &self.channel_scan(client_type_args),
+                    key,
+                    &|tx| {
+                        let hash = tx.hash.clone();
+                        let obj = extract_channel_end_from_tx(tx)
+                            .map_err(|_| Error::collect_events_failed("channel".to_string()))?
+                            .0;
+                        Ok((obj, hash))
+                    },
+                    20,
+                )
+                .await?;
+            identified_channel_ends.extend(channel_ends);
+        }
 
         let events = identified_channel_ends
             .into_iter()
@@ -274,23 +446,36 @@ impl Ckb4IbcEventMonitor {
     }
 
     async fn fetch_packet_events(&self) -> Result<EventBatch> {
-        let script = Script::new_builder()
-            .code_hash(get_script_hash(&self.config.packet_type_args))
-            .args("".pack())
-            .build();
-        let key = get_search_key(script);
-        let ibc_packets = self
-            .search_and_extract(
-                key,
-                &|tx| {
-                    let hash = tx.hash.clone();
-                    let obj = extract_ibc_packet_from_tx(tx)
-                        .map_err(|_| Error::collect_events_failed("packet".to_string()))?;
-                    Ok((obj, hash))
-                },
-                20,
-            )
-            .await?;
+        let mut ibc_packets = vec![];
+        // the packet cells are scanned per channel end, so that the cells of a busy
+        // channel don't hold back the ones of the others
+        for (port_id, channel_id) in self.channel_ends().await? {
+            let Some(prefix) = packet_search_prefix(&self.config, &port_id, &channel_id) else {
+                continue;
+            };
+            let script = Script::new_builder()
+                .code_hash(get_script_hash(&self.config.packet_type_args))
+                .args(prefix.pack())
+                .build();
+            let key = get_search_key(script);
+            let packets = self
+                .scan_and_extract(
+                    &packet_scan(&port_id, &channel_id),
+                    key,
+                    &|tx| {
+                        let hash = tx.hash.clone();
+                        let obj = extract_ibc_packet_from_tx(tx)
+                            .map_err(|_| Error::collect_events_failed("packet".to_string()))?;
+                        Ok((obj, hash))
+                    },
+                    20,
+                )
+                .await;
+            match packets {
+                Ok(packets) => ibc_packets.extend(packets),
+                Err(e) => warn!("failed to scan the packets of {port_id}/{channel_id}: {e}"),
+            }
+        }
         let events = ibc_packets
             .into_iter()
             .filter(|(packet, tx)| {
@@ -300,6 +485,7 @@ impl Ckb4IbcEventMonitor {
                 self.cache_set.write().unwrap().insert(tx.clone());
                 (packet, tx)
             })
+            .filter(|(packet, _)| self.within_size_limits(packet))
             .map(|item| match item.0.status {
                 PacketStatus::Send => IbcEventWithHeight {
                     event: IbcEvent::SendPacket(SendPacket {
@@ -334,6 +520,40 @@ impl Ckb4IbcEventMonitor {
         })
     }
 
+    /// Whether the packet fits in the size ceilings of the chain, the oversized ones
+    /// being reported to the decision feed and never relayed
+    fn within_size_limits(&self, packet: &IbcPacket) -> bool {
+        let Some(reason) = oversized_reason(packet, &self.config) else {
+            return true;
+        };
+        let sequence = packet.packet.sequence;
+        warn!(
+            "skipping packet {sequence} of {}/{}: {reason}",
+            packet.packet.source_port_id, packet.packet.source_channel_id
+        );
+
+        let (chain_id, counterparty_chain_id) = if packet.status == PacketStatus::Send {
+            (self.config.id.clone(), self.config.counter_chain.clone())
+        } else {
+            (self.config.counter_chain.clone(), self.config.id.clone())
+        };
+        if let (Ok(port_id), Ok(channel_id)) = (
+            PortId::from_str(&packet.packet.source_port_id),
+            ChannelId::from_str(&packet.packet.source_channel_id),
+        ) {
+            decision::global().publish(Decision::new(
+                DecisionKind::Skipped,
+                chain_id,
+                counterparty_chain_id,
+                port_id,
+                channel_id,
+                Some(Sequence::from(sequence as u64)),
+                reason,
+            ));
+        }
+        false
+    }
+
     async fn search_and_extract<T, F>(
         &self,
         search_key: SearchKey,
@@ -348,38 +568,489 @@ impl Ckb4IbcEventMonitor {
             .fetch_live_cells(search_key, limit, None)
             .await
             .map_err(|_| Error::collect_events_failed("fetch channel event failed".to_string()))?;
+        Ok(self.extract_from_cells(cells.objects, extractor).await)
+    }
 
-        let tx_response = cells
+    /// Name of the scan of the channel cells bound to the client, the one of the
+    /// default client being simply `channel`
+    fn channel_scan(&self, client_type_args: &H256) -> String {
+        if client_type_args == &self.config.client_type_args {
+            "channel".to_string()
+        } else {
+            format!("channel-{client_type_args:x}")
+        }
+    }
+
+    /// Port and channel identifiers of the live channel cells, the channel end of each
+    /// cell being extracted from its transaction only the first time it is found
+    async fn channel_ends(&self) -> Result<BTreeSet<(PortId, ChannelId)>> {
+        let script = Script::new_builder()
+            .code_hash(get_script_hash(&self.config.channel_type_args))
+            .args("".pack())
+            .build();
+        let cells = fetch_all_cells(self.rpc_client.as_ref(), get_search_key(script))
+            .await
+            .map_err(|_| Error::collect_events_failed("fetch channel cells failed".to_string()))?;
+
+        let mut channel_cells = HashMap::new();
+        for cell in cells {
+            let tx_hash = cell.out_point.tx_hash;
+            let cached = self.channel_cells.read().unwrap().get(&tx_hash).cloned();
+            let channel_end = match cached {
+                Some(channel_end) => channel_end,
+                None => {
+                    let Ok(Some(resp)) = self.rpc_client.get_transaction(&tx_hash).await else {
+                        continue;
+                    };
+                    let Some(Ok(tx)) = resp.transaction.map(decode_transaction) else {
+                        continue;
+                    };
+                    match extract_channel_end_from_tx(tx) {
+                        Ok((channel, _)) => (channel.port_id, channel.channel_id),
+                        Err(e) => {
+                            warn!("skipping channel cell of transaction {tx_hash:#x}: {e}");
+                            continue;
+                        }
+                    }
+                }
+            };
+            channel_cells.insert(tx_hash, channel_end);
+        }
+
+        let channel_ends = channel_cells.values().cloned().collect();
+        // only the live cells are kept, each channel cell being replaced on every update
+        *self.channel_cells.write().unwrap() = channel_cells;
+        Ok(channel_ends)
+    }
+
+    /// Same as `search_and_extract`, but pages through the cells from the offset
+    /// recorded for `script` instead of always starting from the first one,
+    /// so every cell is eventually scanned however many there are.
+    async fn scan_and_extract<T, F>(
+        &self,
+        script: &str,
+        search_key: SearchKey,
+        extractor: &F,
+        limit: u32,
+    ) -> Result<Vec<(T, H256)>>
+    where
+        F: Fn(TransactionView) -> Result<(T, H256)>,
+    {
+        let offset = self.scan_offsets.read().unwrap().get(script);
+        let resp = self
+            .rpc_client
+            .fetch_live_cells(search_key, limit, offset.cursor.clone())
+            .await;
+        let cells = match resp {
+            Ok(cells) => cells,
+            Err(e) => {
+                // the cursor doesn't survive the restarts of the indexer, so the scan starts
+                // over from the first cell rather than taking its pages as the last ones
+                if offset.cursor.is_some() && e.is_ckb_indexer_cursor_invalid() {
+                    warn!("cursor of the {script} scan is invalidated, starting over: {e}");
+                    let restarted = ScanOffset {
+                        cursor: None,
+                        block_number: offset.block_number,
+                    };
+                    self.scan_offsets.write().unwrap().update(script, restarted);
+                }
+                return Err(Error::collect_events_failed(format!(
+                    "fetch {script} event failed"
+                )));
+            }
+        };
+
+        let has_unconfirmed = cells
+            .objects
+            .iter()
+            .any(|cell| !self.is_confirmed(cell.block_number.value()));
+        let (next_offset, scanned_height) = if cells.objects.is_empty() {
+            // all cells have been scanned, start over from the first one next time
+            let next_offset = ScanOffset {
+                cursor: None,
+                block_number: offset.block_number,
+            };
+            (next_offset, Some(offset.block_number))
+        } else if has_unconfirmed {
+            // scan the page again once its cells are confirmed
+            (offset, None)
+        } else {
+            let next_offset = ScanOffset {
+                cursor: Some(cells.last_cursor),
+                block_number: cells
+                    .objects
+                    .iter()
+                    .map(|cell| cell.block_number.value())
+                    .max()
+                    .unwrap_or_default(),
+            };
+            // the next page may hold other cells of the last block of this one
+            let scanned_height = next_offset.block_number.saturating_sub(1);
+            (next_offset, Some(scanned_height))
+        };
+
+        if let Ok(_tip) = self.rpc_client.get_tip_header().await {
+            let _lag = if next_offset.cursor.is_some() {
+                _tip.inner
+                    .number
+                    .value()
+                    .saturating_sub(next_offset.block_number)
+            } else {
+                0
+            };
+            telemetry!(ckb4ibc_scan_lag, &self.config.id, script, _lag);
+        }
+
+        // the events of the cells committed up to the resumed height were emitted before
+        // the relayer restarted
+        let resumed_height = self.resumed_height(script);
+        let cells = cells
             .objects
             .into_iter()
-            .map(|cell| self.rpc_client.get_transaction(&cell.out_point.tx_hash));
+            .filter(|cell| resumed_height.map_or(true, |height| cell.block_number.value() > height))
+            .collect();
+        let result = self.extract_from_cells(cells, extractor).await;
+        self.scan_offsets
+            .write()
+            .unwrap()
+            .update(script, next_offset);
+        if let Some(scanned_height) = scanned_height {
+            self.progress
+                .lock()
+                .unwrap()
+                .set_scanned_height(script, scanned_height);
+        }
+        Ok(result)
+    }
+
+    /// Height up to which the events of `scan` were emitted before the relayer restarted,
+    /// looked up the first time the packet scan of a channel end runs
+    fn resumed_height(&self, scan: &str) -> Option<u64> {
+        if let Some(height) = self.resumed_heights.read().unwrap().get(scan) {
+            return Some(*height);
+        }
+        if !scan.starts_with("packet/") {
+            return None;
+        }
+        let height = self
+            .progress
+            .lock()
+            .unwrap()
+            .scanned_height(scan)
+            .unwrap_or(self.packets_resumed_height);
+        self.resumed_heights
+            .write()
+            .unwrap()
+            .insert(scan.to_owned(), height);
+        Some(height)
+    }
 
-        let result = futures::future::join_all(tx_response)
+    async fn extract_from_cells<T, F>(&self, cells: Vec<Cell>, extractor: &F) -> Vec<(T, H256)>
+    where
+        F: Fn(TransactionView) -> Result<(T, H256)>,
+    {
+        let tx_response = cells
+            .into_iter()
+            .filter(|cell| self.is_confirmed(cell.block_number.value()))
+            .map(|cell| {
+                let block_number = cell.block_number.value();
+                let resp = self.rpc_client.get_transaction(&cell.out_point.tx_hash);
+                async move { (block_number, resp.await) }
+            });
+
+        let txs = futures::future::join_all(tx_response)
             .await
             .into_iter()
-            .flatten()
-            .flatten()
-            .filter(|resp| resp.tx_status.status == Status::Committed && resp.transaction.is_some())
-            .flat_map(|tx| {
-                let tx_resp = tx.transaction.unwrap();
-                let tx = match tx_resp.inner {
-                    ckb_jsonrpc_types::Either::Left(r) => r,
-                    ckb_jsonrpc_types::Either::Right(json_bytes) => {
-                        let bytes = json_bytes.as_bytes();
-                        let tx: TransactionView = serde_json::from_slice(bytes).unwrap();
-                        tx
+            .filter_map(|(block_number, resp)| Some((block_number, resp.ok()??)))
+            .filter(|(_, resp)| {
+                resp.tx_status.status == Status::Committed && resp.transaction.is_some()
+            })
+            .filter_map(|(block_number, tx)| {
+                let block_hash = tx.tx_status.block_hash;
+                let tx = match decode_transaction(tx.transaction.unwrap()) {
+                    Ok(tx) => tx,
+                    Err(e) => {
+                        warn!("skipping undecodable transaction: {e}");
+                        return None;
                     }
                 };
-                extractor(tx)
+                if let Some(block_hash) = block_hash {
+                    self.committed_txs.write().unwrap().record(
+                        tx.hash.clone(),
+                        block_number,
+                        block_hash,
+                    );
+                }
+                Some(tx)
             })
             .collect::<Vec<_>>();
 
-        Ok(result)
+        if let Some(known_authors) = &self.known_authors {
+            for tx in &txs {
+                self.report_author(known_authors, tx).await;
+            }
+        }
+        txs.into_iter()
+            .filter_map(|tx| extractor(tx).ok())
+            .collect::<Vec<_>>()
+    }
+
+    /// Recover the signers of the transaction and tag it with its author, once per
+    /// transaction, warning about the transactions of unknown authors
+    async fn report_author(&self, known_authors: &KnownAuthors, tx: &TransactionView) {
+        if self.authored_txs.read().unwrap().has(&tx.hash) {
+            return;
+        }
+        let input_locks = match fetch_input_locks(self.rpc_client.as_ref(), tx).await {
+            Ok(input_locks) => input_locks,
+            Err(e) => {
+                warn!(
+                    "failed to fetch the inputs of transaction {:#x}: {e}",
+                    tx.hash
+                );
+                return;
+            }
+        };
+        self.authored_txs.write().unwrap().insert(tx.hash.clone());
+
+        let core_tx = Transaction::from(tx.inner.clone()).into_view();
+        let signers = recover_signers(&core_tx, &input_locks);
+        let author = known_authors.classify(&signers);
+        let signers = signers.iter().map(hex::encode).collect::<Vec<_>>();
+        telemetry!(ckb4ibc_tx_author, &self.config.id, author.as_str());
+        if author == TxAuthor::Unknown {
+            warn!(
+                chain = %self.config.id,
+                author = %author,
+                ?signers,
+                "ibc transaction {:#x} is not signed by any known relayer",
+                tx.hash
+            );
+        } else {
+            debug!(
+                chain = %self.config.id,
+                author = %author,
+                ?signers,
+                "observed ibc transaction {:#x}",
+                tx.hash
+            );
+        }
     }
 
     fn process_batch(&mut self, batch: EventBatch) {
         self.event_bus.broadcast(Arc::new(Ok(batch)));
     }
+
+    /// Record how far the indexer lags behind the node, warning once it lags beyond
+    /// `max_indexer_lag`: the cells of the blocks not indexed yet are missing from the
+    /// searches, which then fail as if the IBC cells were gone
+    async fn check_indexer_lag(&mut self, tip: u64) {
+        let indexer_tip = match self.rpc_client.get_indexer_tip().await {
+            Ok(Some(indexer_tip)) => indexer_tip.block_number.value(),
+            Ok(None) => 0,
+            Err(e) => {
+                warn!(
+                    "failed to query the tip of the ckb indexer of {}: {e}",
+                    self.config.id
+                );
+                return;
+            }
+        };
+        let lag = tip.saturating_sub(indexer_tip);
+        telemetry!(ckb4ibc_indexer_lag, &self.config.id, lag);
+
+        let lagging = lag > self.config.max_indexer_lag;
+        if lagging && !self.indexer_lagging {
+            warn!(
+                "ckb indexer of {} lags {lag} blocks behind the node, at block {indexer_tip} of {tip}, \
+                the latest IBC cells are missing until it catches up",
+                self.config.id
+            );
+        } else if !lagging && self.indexer_lagging {
+            info!(
+                "ckb indexer of {} caught up with the node, at block {indexer_tip} of {tip}",
+                self.config.id
+            );
+        }
+        self.indexer_lagging = lagging;
+    }
+
+    /// Compare the tip block with the tip blocks seen before, and roll the scans back to
+    /// the fork point if the chain of the node has been reorganized since then.
+    async fn roll_back_reorganized_blocks(&mut self, tip_header: &HeaderView) {
+        let tip = tip_header.inner.number.value();
+        let extends =
+            self.scanned_blocks
+                .extends(tip, &tip_header.hash, &tip_header.inner.parent_hash);
+        let reorganized = match (extends, self.scanned_blocks.last()) {
+            (Some(extends), _) => !extends,
+            (None, None) => false,
+            (None, Some((number, hash))) => match self.canonical_hash(number, tip).await {
+                Ok(canonical_hash) => canonical_hash.as_ref() != Some(&hash),
+                Err(e) => {
+                    warn!("failed to fetch ckb block {number} to detect reorgs: {e}");
+                    return;
+                }
+            },
+        };
+        if !reorganized {
+            self.scanned_blocks.record(tip, tip_header.hash.clone());
+            return;
+        }
+
+        let mut fork = None;
+        for (number, hash) in self.scanned_blocks.recorded() {
+            match self.canonical_hash(number, tip).await {
+                Ok(Some(canonical_hash)) if canonical_hash == hash => {
+                    fork = Some(number);
+                    break;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("failed to fetch ckb block {number} to find the reorg fork: {e}");
+                    return;
+                }
+            }
+        }
+        // none of the recorded blocks is left, restart from below the oldest one
+        let fork = fork.unwrap_or_else(|| {
+            let oldest = self
+                .scanned_blocks
+                .recorded()
+                .last()
+                .map(|(number, _)| *number);
+            oldest.unwrap_or(tip).saturating_sub(1)
+        });
+
+        warn!(
+            chain = %self.config.id,
+            "chain reorganized after block {fork}, rolling back the scans and dropping the cached cells"
+        );
+        self.scanned_blocks.rollback(fork);
+        self.scanned_blocks.record(tip, tip_header.hash.clone());
+        self.rewind_scans(fork);
+        self.ibc_state_cache.clear();
+        self.propagate_error(Error::chain_reorganized(self.config.id.clone(), fork));
+    }
+
+    /// Hash of the block at `number` on the chain of the node, `None` if it is above the tip.
+    async fn canonical_hash(
+        &self,
+        number: u64,
+        tip: u64,
+    ) -> core::result::Result<Option<H256>, RelayerError> {
+        if number > tip {
+            return Ok(None);
+        }
+        let block = self.rpc_client.get_block_by_number(number.into()).await?;
+        Ok(Some(block.header.hash))
+    }
+
+    /// Check the blocks of the recently emitted transactions against the chain of the
+    /// node, and retract the events of the transactions whose blocks were orphaned.
+    async fn retract_orphaned_events(&mut self, tip: u64) {
+        let block_numbers = {
+            let mut committed_txs = self.committed_txs.write().unwrap();
+            committed_txs.prune(tip);
+            committed_txs.block_numbers()
+        };
+
+        let mut retracted = vec![];
+        let mut lowest_orphaned = None;
+        for block_number in block_numbers {
+            let canonical_hash = if block_number > tip {
+                None
+            } else {
+                match self
+                    .rpc_client
+                    .get_block_by_number(block_number.into())
+                    .await
+                {
+                    Ok(block) => Some(block.header.hash),
+                    Err(e) => {
+                        warn!("failed to fetch ckb block {block_number} to detect reorgs: {e}");
+                        return;
+                    }
+                }
+            };
+            let orphaned = self
+                .committed_txs
+                .write()
+                .unwrap()
+                .retract_orphaned(block_number, canonical_hash.as_ref());
+            if !orphaned.is_empty() {
+                lowest_orphaned.get_or_insert(block_number);
+                retracted.extend(orphaned);
+            }
+        }
+
+        let Some(lowest_orphaned) = lowest_orphaned else {
+            return;
+        };
+        warn!(
+            chain = %self.config.id,
+            "blocks from {lowest_orphaned} are orphaned, retracting the events of {} transactions",
+            retracted.len()
+        );
+        {
+            let mut cache_set = self.cache_set.write().unwrap();
+            for tx_hash in &retracted {
+                cache_set.remove(tx_hash);
+            }
+        }
+        self.rewind_scans(lowest_orphaned.saturating_sub(1));
+        let tx_hashes = retracted.iter().map(|hash| format!("{hash:#x}")).collect();
+        self.propagate_error(Error::events_retracted(self.config.id.clone(), tx_hashes));
+    }
+
+    /// Restart the scans which went beyond `height`, including the ones resumed beyond it
+    fn rewind_scans(&mut self, height: u64) {
+        self.scan_offsets.write().unwrap().rewind(height);
+        for resumed_height in self.resumed_heights.get_mut().unwrap().values_mut() {
+            *resumed_height = (*resumed_height).min(height);
+        }
+        self.packets_resumed_height = self.packets_resumed_height.min(height);
+    }
+
+    /// Whether the block has enough confirmations for the events of its transactions
+    /// to be emitted.
+    fn is_confirmed(&self, block_number: u64) -> bool {
+        self.last_tip
+            .map_or(false, |tip| block_number + self.config.confirmations <= tip)
+    }
+
+    fn propagate_error(&mut self, error: Error) {
+        self.event_bus.broadcast(Arc::new(Err(error)));
+    }
+}
+
+/// Name of the scan of the packet cells of a channel end
+fn packet_scan(port_id: &PortId, channel_id: &ChannelId) -> String {
+    format!("packet/{port_id}/{channel_id}")
+}
+
+/// Prefix of the lock args of the packet cells of a channel end, i.e. the one the search
+/// args of all its sequences share
+fn packet_search_prefix(
+    config: &ChainConfig,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+) -> Option<Vec<u8>> {
+    let channel_idx = get_channel_idx(channel_id).ok()?;
+    let port = convert_port_id_to_array(port_id).ok()?;
+    let owner = config.packet_owner(port_id);
+    let search_args = |sequence| {
+        PacketArgs {
+            channel_id: channel_idx,
+            port_id: port,
+            sequence,
+            owner,
+        }
+        .get_search_args()
+    };
+    let (first, last) = (search_args(0), search_args(u16::MAX));
+    let len = first.iter().zip(&last).take_while(|(a, b)| a == b).count();
+    Some(first[..len].to_vec())
 }
 
 fn convert_packet(packet: IbcPacket) -> Packet {
@@ -416,3 +1087,19 @@ fn convert_packet(packet: IbcPacket) -> Packet {
         timeout_timestamp: Timestamp::none(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::retry_delay;
+
+    #[test]
+    fn test_retry_delay_backs_off_exponentially() {
+        assert_eq!(retry_delay(1), Duration::from_secs(1));
+        assert_eq!(retry_delay(2), Duration::from_secs(2));
+        assert_eq!(retry_delay(5), Duration::from_secs(16));
+        assert_eq!(retry_delay(7), Duration::from_secs(60));
+        assert_eq!(retry_delay(u32::MAX), Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,169 @@
+//! Size ceilings of the packets extracted from the CKB cells.
+//!
+//! A packet cell may carry arbitrarily large data, which could never fit in the
+//! transactions of the counterparty. Such packets are skipped by the event monitor
+//! instead of being relayed, and listed by `query_oversized_packets`.
+
+use std::str::FromStr;
+
+use ckb_ics_axon::handler::{IbcPacket, PacketStatus};
+use ckb_types::packed::Script;
+use ckb_types::prelude::{Builder, Pack};
+use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, PortId};
+use serde::{Deserialize, Serialize};
+
+use crate::chain::ckb::prelude::CkbReader;
+use crate::config::ckb4ibc::ChainConfig;
+use crate::error::Error;
+
+use super::extractor::extract_ibc_packet_from_tx;
+use super::footprint::{fetch_all_cells, fetch_cell_transaction};
+use super::utils::{get_script_hash, get_search_key};
+
+/// Packet of a live packet cell exceeding the size ceilings of the chain
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OversizedPacket {
+    pub port_id: PortId,
+    pub channel_id: ChannelId,
+    pub sequence: Sequence,
+    /// Size of the packet data, in bytes
+    pub data_size: usize,
+    /// Size of the memo of the ICS-20 packet data, in bytes
+    pub memo_size: Option<usize>,
+    pub reason: String,
+}
+
+/// Memo of the packet data, if the data is encoded in JSON as the ICS-20 packets
+#[derive(Deserialize)]
+struct PacketMemo {
+    #[serde(default)]
+    memo: String,
+}
+
+fn memo_size(data: &[u8]) -> Option<usize> {
+    serde_json::from_slice::<PacketMemo>(data)
+        .ok()
+        .map(|packet| packet.memo.len())
+}
+
+/// Why the packet is not relayed, if its data or its memo exceed the ceilings of `config`
+pub fn oversized_reason(packet: &IbcPacket, config: &ChainConfig) -> Option<String> {
+    let data_size = packet.packet.data.len();
+    if data_size > config.max_packet_data_size {
+        return Some(format!(
+            "packet data of {data_size} bytes exceeds the maximum of {} bytes",
+            config.max_packet_data_size
+        ));
+    }
+    match memo_size(&packet.packet.data) {
+        Some(memo_size) if memo_size > config.max_memo_size => Some(format!(
+            "memo of {memo_size} bytes exceeds the maximum of {} bytes",
+            config.max_memo_size
+        )),
+        _ => None,
+    }
+}
+
+/// Pending packets of the live packet cells which are skipped because of their size
+pub async fn collect_oversized_packets<R: CkbReader>(
+    rpc_client: &R,
+    config: &ChainConfig,
+) -> Result<Vec<OversizedPacket>, Error> {
+    let packet_script = Script::new_builder()
+        .code_hash(get_script_hash(&config.packet_type_args))
+        .args("".pack())
+        .build();
+
+    let mut oversized = vec![];
+    for cell in fetch_all_cells(rpc_client, get_search_key(packet_script)).await? {
+        let tx = fetch_cell_transaction(rpc_client, &cell).await?;
+        let ibc_packet = extract_ibc_packet_from_tx(tx)?;
+        if ibc_packet.status == PacketStatus::Ack {
+            continue;
+        }
+        let Some(reason) = oversized_reason(&ibc_packet, config) else {
+            continue;
+        };
+        let packet = &ibc_packet.packet;
+        let (Ok(port_id), Ok(channel_id)) = (
+            PortId::from_str(&packet.source_port_id),
+            ChannelId::from_str(&packet.source_channel_id),
+        ) else {
+            continue;
+        };
+        oversized.push(OversizedPacket {
+            port_id,
+            channel_id,
+            sequence: Sequence::from(packet.sequence as u64),
+            data_size: packet.data.len(),
+            memo_size: memo_size(&packet.data),
+            reason,
+        });
+    }
+    Ok(oversized)
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_ics_axon::handler::{IbcPacket, PacketStatus};
+    use ckb_ics_axon::object::Packet;
+
+    use super::{memo_size, oversized_reason};
+    use crate::config::ckb4ibc::ChainConfig;
+
+    fn config() -> ChainConfig {
+        toml::from_str(
+            r#"
+            id = "ckb4ibc-0"
+            counter_chain = "axon-0"
+            ckb_rpc = "http://127.0.0.1:8114"
+            ckb_indexer_rpc = "http://127.0.0.1:8116"
+            key_name = "relayer"
+            client_type_args = "0x0000000000000000000000000000000000000000000000000000000000000001"
+            connection_type_args = "0x0000000000000000000000000000000000000000000000000000000000000002"
+            channel_type_args = "0x0000000000000000000000000000000000000000000000000000000000000003"
+            packet_type_args = "0x0000000000000000000000000000000000000000000000000000000000000004"
+            max_packet_data_size = 128
+            max_memo_size = 16
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn packet(data: &[u8]) -> IbcPacket {
+        IbcPacket {
+            packet: Packet {
+                sequence: 1,
+                source_port_id: "transfer".to_owned(),
+                source_channel_id: "channel-0".to_owned(),
+                destination_port_id: "transfer".to_owned(),
+                destination_channel_id: "channel-0".to_owned(),
+                data: data.to_vec(),
+            },
+            tx_hash: None,
+            status: PacketStatus::Send,
+        }
+    }
+
+    #[test]
+    fn test_packet_within_limits() {
+        let data = br#"{"denom":"ckb","amount":"1","memo":"hello"}"#;
+        assert_eq!(memo_size(data), Some(5));
+        assert_eq!(oversized_reason(&packet(data), &config()), None);
+        assert_eq!(oversized_reason(&packet(b"not json"), &config()), None);
+    }
+
+    #[test]
+    fn test_oversized_data() {
+        let reason = oversized_reason(&packet(&[0; 129]), &config()).unwrap();
+        assert!(reason.contains("packet data of 129 bytes"));
+    }
+
+    #[test]
+    fn test_oversized_memo() {
+        let data = br#"{"denom":"ckb","amount":"1","memo":"a memo longer than sixteen bytes"}"#;
+        let reason = oversized_reason(&packet(data), &config()).unwrap();
+        assert!(reason.contains("memo of 32 bytes"));
+    }
+}
@@ -0,0 +1,159 @@
+//! Ports bound to the applications on a CKB chain of the IBC contracts.
+//!
+//! The IBC contracts define no message to bind or release a port: the messages they
+//! take, listed by `ckb_ics_axon::message::MsgType`, only create and update the client,
+//! connection, channel and packet cells. The id of a port is the lock hash of the
+//! application owning it, in hex, and the channel contract opens a channel on any, so
+//! there is no binding on chain for the converter to build a transaction for. The
+//! bindings are therefore kept by the relayer, in the `port_bindings_file` of the
+//! chain, which the `ckb port` commands edit. Once the file is configured, the
+//! channel handshakes are only opened on the bound ports, so that no channel is opened
+//! for an application which is not deployed yet or was retired. The channels already
+//! open on a released port are still relayed.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use ibc_relayer_types::core::ics24_host::identifier::PortId;
+use serde_derive::{Deserialize, Serialize};
+
+use super::utils::convert_port_id_to_array;
+use crate::config::ckb4ibc::ChainConfig;
+use crate::error::Error;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortBindings {
+    ports: BTreeSet<PortId>,
+}
+
+impl PortBindings {
+    /// The bindings recorded in `path`, none if the file doesn't exist yet
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| Error::ckb_port_bindings(path.display().to_string(), e.to_string()))?;
+        serde_json::from_str(&content)
+            .map_err(|e| Error::ckb_port_bindings(path.display().to_string(), e.to_string()))
+    }
+
+    /// Record the bindings in `path`, replacing the file at once so that a relayer
+    /// reading it never sees it half written
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let error = |e: String| Error::ckb_port_bindings(path.display().to_string(), e);
+        let content = serde_json::to_string_pretty(self).map_err(|e| error(e.to_string()))?;
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, content).map_err(|e| error(e.to_string()))?;
+        std::fs::rename(&tmp_path, path).map_err(|e| error(e.to_string()))
+    }
+
+    /// Bind `port_id`, which has to be the lock hash of the application in hex.
+    /// Returns whether it wasn't bound yet
+    pub fn bind(&mut self, port_id: PortId) -> Result<bool, Error> {
+        convert_port_id_to_array(&port_id)?;
+        Ok(self.ports.insert(port_id))
+    }
+
+    /// Release `port_id`, returns whether it was bound
+    pub fn release(&mut self, port_id: &PortId) -> bool {
+        self.ports.remove(port_id)
+    }
+
+    pub fn is_bound(&self, port_id: &PortId) -> bool {
+        self.ports.contains(port_id)
+    }
+
+    pub fn ports(&self) -> impl Iterator<Item = &PortId> {
+        self.ports.iter()
+    }
+}
+
+/// Fails unless `port_id` is bound on the chain of `config`, any port being accepted
+/// if the chain has no `port_bindings_file`
+pub fn ensure_port_bound(config: &ChainConfig, port_id: &PortId) -> Result<(), Error> {
+    let Some(path) = &config.port_bindings_file else {
+        return Ok(());
+    };
+    if PortBindings::load(path)?.is_bound(port_id) {
+        Ok(())
+    } else {
+        Err(Error::ckb_port_not_bound(
+            config.id.clone(),
+            port_id.to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ibc_relayer_types::core::ics24_host::identifier::PortId;
+    use tempfile::TempDir;
+
+    use super::{ensure_port_bound, PortBindings};
+    use crate::config::ckb4ibc::ChainConfig;
+
+    fn port(byte: u8) -> PortId {
+        PortId::from_str(&hex::encode([byte; 32])).unwrap()
+    }
+
+    fn config(port_bindings_file: Option<&str>) -> ChainConfig {
+        let mut config: ChainConfig = toml::from_str(
+            r#"
+            id = "ckb4ibc-0"
+            counter_chain = "axon-0"
+            ckb_rpc = "http://127.0.0.1:8114"
+            ckb_indexer_rpc = "http://127.0.0.1:8116"
+            key_name = "relayer"
+            client_type_args = "0x0000000000000000000000000000000000000000000000000000000000000001"
+            connection_type_args = "0x0000000000000000000000000000000000000000000000000000000000000002"
+            channel_type_args = "0x0000000000000000000000000000000000000000000000000000000000000003"
+            packet_type_args = "0x0000000000000000000000000000000000000000000000000000000000000004"
+            "#,
+        )
+        .unwrap();
+        config.port_bindings_file = port_bindings_file.map(Into::into);
+        config
+    }
+
+    #[test]
+    fn test_port_bindings_are_persisted() {
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("ports.json");
+
+        let mut bindings = PortBindings::load(&path).unwrap();
+        assert_eq!(bindings.ports().count(), 0);
+        assert!(bindings.bind(port(1)).unwrap());
+        assert!(bindings.bind(port(2)).unwrap());
+        assert!(!bindings.bind(port(1)).unwrap());
+        assert!(bindings.bind(PortId::transfer()).is_err());
+        bindings.save(&path).unwrap();
+
+        let mut bindings = PortBindings::load(&path).unwrap();
+        assert!(bindings.is_bound(&port(1)));
+        assert!(bindings.release(&port(1)));
+        assert!(!bindings.release(&port(1)));
+        bindings.save(&path).unwrap();
+
+        let bindings = PortBindings::load(&path).unwrap();
+        assert_eq!(bindings.ports().collect::<Vec<_>>(), vec![&port(2)]);
+    }
+
+    #[test]
+    fn test_channels_only_open_on_bound_ports() {
+        assert!(ensure_port_bound(&config(None), &port(1)).is_ok());
+
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("ports.json");
+        let config = config(path.to_str());
+        assert!(ensure_port_bound(&config, &port(1)).is_err());
+
+        let mut bindings = PortBindings::default();
+        bindings.bind(port(1)).unwrap();
+        bindings.save(&path).unwrap();
+        assert!(ensure_port_bound(&config, &port(1)).is_ok());
+        assert!(ensure_port_bound(&config, &port(2)).is_err());
+    }
+}
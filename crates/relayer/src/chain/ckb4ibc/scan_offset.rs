@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use ckb_jsonrpc_types::JsonBytes;
+use serde_derive::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Position reached by the event monitor while paging through the live cells of a scan
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanOffset {
+    /// Indexer cursor to resume the scan from, `None` to restart from the first cell
+    pub cursor: Option<JsonBytes>,
+    /// Block number of the last scanned cell
+    pub block_number: u64,
+}
+
+/// Scan offsets of the event monitor, one per scan, i.e. per client for the channel
+/// handshakes and per channel end for the packets, so that a slow or broken scan
+/// doesn't hold back the others.
+///
+/// Offsets are written to `path` on every update if it is given, which lets a
+/// restarted relayer resume its scans where they stopped.
+pub struct ScanOffsets {
+    path: Option<PathBuf>,
+    offsets: HashMap<String, ScanOffset>,
+}
+
+impl ScanOffsets {
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let offsets = path
+            .as_ref()
+            .filter(|path| path.exists())
+            .and_then(|path| {
+                let content = std::fs::read_to_string(path)
+                    .map_err(|e| warn!("failed to read scan offsets {}: {e}", path.display()))
+                    .ok()?;
+                serde_json::from_str(&content)
+                    .map_err(|e| warn!("failed to parse scan offsets {}: {e}", path.display()))
+                    .ok()
+            })
+            .unwrap_or_default();
+        Self { path, offsets }
+    }
+
+    pub fn get(&self, key: &str) -> ScanOffset {
+        self.offsets.get(key).cloned().unwrap_or_default()
+    }
+
+    pub fn update(&mut self, key: &str, offset: ScanOffset) {
+        if self.offsets.get(key) == Some(&offset) {
+            return;
+        }
+        self.offsets.insert(key.to_owned(), offset);
+        self.save();
+    }
+
+    /// Restart the scans which went beyond `height` from the first cell, since their
+    /// cursors may point to cells which are no longer on the chain.
+    pub fn rewind(&mut self, height: u64) {
+        let mut rewound = false;
+        for offset in self.offsets.values_mut() {
+            if offset.block_number > height {
+                *offset = ScanOffset {
+                    cursor: None,
+                    block_number: height,
+                };
+                rewound = true;
+            }
+        }
+        if rewound {
+            self.save();
+        }
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_string_pretty(&self.offsets)
+            .map_err(|e| e.to_string())
+            .and_then(|content| std::fs::write(path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("failed to save scan offsets {}: {e}", path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_jsonrpc_types::JsonBytes;
+    use tempfile::TempDir;
+
+    use super::{ScanOffset, ScanOffsets};
+
+    #[test]
+    fn test_scan_offsets_are_persisted() {
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("scan_offsets.json");
+
+        let offset = ScanOffset {
+            cursor: Some(JsonBytes::from_vec(vec![1, 2, 3])),
+            block_number: 42,
+        };
+        let mut offsets = ScanOffsets::load(Some(path.clone()));
+        assert_eq!(
+            offsets.get("packet/transfer/channel-0"),
+            ScanOffset::default()
+        );
+        offsets.update("packet/transfer/channel-0", offset.clone());
+
+        let offsets = ScanOffsets::load(Some(path));
+        assert_eq!(offsets.get("packet/transfer/channel-0"), offset);
+        assert_eq!(
+            offsets.get("packet/transfer/channel-1"),
+            ScanOffset::default()
+        );
+        assert_eq!(offsets.get("channel"), ScanOffset::default());
+    }
+
+    #[test]
+    fn test_scan_offsets_rewind() {
+        let mut offsets = ScanOffsets::load(None);
+        let behind = ScanOffset {
+            cursor: Some(JsonBytes::from_vec(vec![1])),
+            block_number: 10,
+        };
+        offsets.update("channel", behind.clone());
+        offsets.update(
+            "packet/transfer/channel-0",
+            ScanOffset {
+                cursor: Some(JsonBytes::from_vec(vec![2])),
+                block_number: 30,
+            },
+        );
+
+        offsets.rewind(20);
+        assert_eq!(offsets.get("channel"), behind);
+        assert_eq!(
+            offsets.get("packet/transfer/channel-0"),
+            ScanOffset {
+                cursor: None,
+                block_number: 20,
+            }
+        );
+    }
+}
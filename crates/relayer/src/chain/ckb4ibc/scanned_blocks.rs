@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use ckb_types::H256;
+
+use super::committed_txs::REORG_TRACKING_BLOCKS;
+
+/// Hashes of the tip blocks seen by the event monitor, so that a reorg of the scanned
+/// blocks can be detected and the scans rolled back to the fork point.
+#[derive(Default)]
+pub struct ScannedBlocks {
+    hashes: BTreeMap<u64, H256>,
+}
+
+impl ScannedBlocks {
+    /// Record the tip block, forgetting the blocks above it and the blocks deep enough
+    /// below it to be considered safe from reorgs.
+    pub fn record(&mut self, number: u64, hash: H256) {
+        let oldest = number.saturating_sub(REORG_TRACKING_BLOCKS);
+        self.hashes
+            .retain(|recorded, _| (oldest..number).contains(recorded));
+        self.hashes.insert(number, hash);
+    }
+
+    pub fn last(&self) -> Option<(u64, H256)> {
+        self.hashes
+            .iter()
+            .next_back()
+            .map(|(number, hash)| (*number, hash.clone()))
+    }
+
+    /// Whether the block is the last recorded one or its child, `None` if it can't be
+    /// told from the recorded hashes alone.
+    pub fn extends(&self, number: u64, hash: &H256, parent_hash: &H256) -> Option<bool> {
+        let (last_number, last_hash) = self.last()?;
+        if number == last_number {
+            Some(hash == &last_hash)
+        } else if number == last_number + 1 {
+            Some(parent_hash == &last_hash)
+        } else {
+            None
+        }
+    }
+
+    /// Recorded blocks, from the highest to the lowest.
+    pub fn recorded(&self) -> Vec<(u64, H256)> {
+        self.hashes
+            .iter()
+            .rev()
+            .map(|(number, hash)| (*number, hash.clone()))
+            .collect()
+    }
+
+    /// Forget the blocks above the fork point.
+    pub fn rollback(&mut self, fork: u64) {
+        self.hashes.retain(|recorded, _| *recorded <= fork);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_types::h256;
+
+    use super::{ScannedBlocks, REORG_TRACKING_BLOCKS};
+
+    #[test]
+    fn test_detect_parent_hash_mismatch() {
+        let mut blocks = ScannedBlocks::default();
+        assert_eq!(blocks.extends(10, &h256!("0xa"), &h256!("0x9")), None);
+
+        blocks.record(10, h256!("0xa"));
+        assert_eq!(blocks.extends(10, &h256!("0xa"), &h256!("0x9")), Some(true));
+        assert_eq!(blocks.extends(11, &h256!("0xb"), &h256!("0xa")), Some(true));
+        assert_eq!(
+            blocks.extends(11, &h256!("0xb"), &h256!("0xf")),
+            Some(false)
+        );
+        assert_eq!(
+            blocks.extends(10, &h256!("0xf"), &h256!("0x9")),
+            Some(false)
+        );
+        assert_eq!(blocks.extends(12, &h256!("0xc"), &h256!("0xb")), None);
+    }
+
+    #[test]
+    fn test_rollback_to_fork() {
+        let mut blocks = ScannedBlocks::default();
+        blocks.record(10, h256!("0xa"));
+        blocks.record(11, h256!("0xb"));
+        blocks.record(12, h256!("0xc"));
+
+        blocks.rollback(10);
+        assert_eq!(blocks.recorded(), vec![(10, h256!("0xa"))]);
+
+        blocks.record(12, h256!("0xc"));
+        blocks.record(11, h256!("0xd"));
+        assert_eq!(blocks.last(), Some((11, h256!("0xd"))));
+
+        blocks.record(11 + REORG_TRACKING_BLOCKS, h256!("0xe"));
+        assert_eq!(
+            blocks.recorded(),
+            vec![
+                (11 + REORG_TRACKING_BLOCKS, h256!("0xe")),
+                (11, h256!("0xd"))
+            ]
+        );
+    }
+}
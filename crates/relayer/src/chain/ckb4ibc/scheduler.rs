@@ -0,0 +1,134 @@
+//! Conflicts between the transactions of a batch of messages.
+//!
+//! Every message consumes the live cells of the IBC objects it updates. Transactions
+//! consuming disjoint cells are independent and submitted together, while a transaction
+//! consuming a cell updated by a previous one of the batch is chained on the outputs of
+//! that parent, and is only sent once the parent is accepted by the node.
+
+use std::collections::{HashMap, HashSet};
+
+use ckb_jsonrpc_types::TransactionView;
+use ckb_types::packed::OutPoint;
+use ckb_types::prelude::{Builder, Pack};
+use ckb_types::H256;
+use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, ClientId};
+
+/// Cell of an IBC object consumed by a message
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CellKey {
+    Connections,
+    Channel(ChannelId),
+    Packet(ChannelId, Sequence),
+    /// Cell of a Tendermint client
+    Client(ClientId),
+}
+
+/// Dependencies between the transactions of a batch, in the order of their assembly
+#[derive(Debug, Default)]
+pub struct ConflictGraph {
+    last_consumers: HashMap<CellKey, usize>,
+    parents: Vec<Vec<usize>>,
+}
+
+impl ConflictGraph {
+    /// Previous transactions a transaction consuming the cells of `keys` depends on,
+    /// i.e. the last ones consuming these cells
+    pub fn dependencies(&self, keys: &[CellKey]) -> Vec<usize> {
+        let mut parents = keys
+            .iter()
+            .filter_map(|key| self.last_consumers.get(key).copied())
+            .collect::<Vec<_>>();
+        parents.sort_unstable();
+        parents.dedup();
+        parents
+    }
+
+    /// Add the next transaction of the batch, consuming the cells of `keys`, and return
+    /// its dependencies
+    pub fn add(&mut self, keys: &[CellKey]) -> Vec<usize> {
+        let parents = self.dependencies(keys);
+        let index = self.parents.len();
+        for key in keys {
+            self.last_consumers.insert(key.clone(), index);
+        }
+        self.parents.push(parents.clone());
+        parents
+    }
+
+    pub fn parents(&self) -> &[Vec<usize>] {
+        &self.parents
+    }
+}
+
+/// Change output of a pending transaction, which funds the next transaction chained on it
+#[derive(Clone, Debug)]
+pub struct ChangeCell {
+    pub out_point: OutPoint,
+    pub capacity: u64,
+}
+
+impl ChangeCell {
+    /// The change output is the last output of the transactions of the relayer
+    pub fn of(tx: &TransactionView) -> Option<Self> {
+        let index = tx.inner.outputs.len().checked_sub(1)?;
+        let out_point = OutPoint::new_builder()
+            .tx_hash(tx.hash.pack())
+            .index((index as u32).pack())
+            .build();
+        Some(Self {
+            out_point,
+            capacity: tx.inner.outputs[index].capacity.value(),
+        })
+    }
+}
+
+/// Transactions accepted into the pool of the node but not committed yet, whose outputs
+/// the next batches are chained on when `chain_unconfirmed_txs` is enabled
+#[derive(Debug, Default)]
+pub struct UnconfirmedTxs {
+    pub hashes: Vec<H256>,
+    /// Change outputs not spent by any transaction yet
+    pub change_cells: Vec<ChangeCell>,
+    /// Capacity cells spent by the transactions, still live for the indexer
+    pub spent_cells: HashSet<OutPoint>,
+}
+
+impl UnconfirmedTxs {
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.hashes.clear();
+        self.change_cells.clear();
+        self.spent_cells.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+    use ibc_relayer_types::core::ics24_host::identifier::ChannelId;
+
+    use super::{CellKey, ConflictGraph};
+
+    #[test]
+    fn test_conflict_graph() {
+        let channel = |index| CellKey::Channel(ChannelId::new(index));
+        let packet =
+            |index, sequence: u64| CellKey::Packet(ChannelId::new(index), Sequence::from(sequence));
+
+        let mut graph = ConflictGraph::default();
+        // packets of different channels are independent
+        assert!(graph.add(&[channel(0)]).is_empty());
+        assert!(graph.add(&[channel(1), packet(1, 1)]).is_empty());
+        // while the ones of the same channel are chained
+        assert_eq!(graph.add(&[channel(0)]), vec![0]);
+        assert_eq!(graph.add(&[channel(0), packet(0, 1)]), vec![2]);
+        assert!(graph.add(&[CellKey::Connections]).is_empty());
+        assert_eq!(graph.add(&[channel(1), CellKey::Connections]), vec![1, 4]);
+        assert!(graph.add(&[]).is_empty());
+        assert_eq!(graph.parents()[3], vec![2]);
+    }
+}
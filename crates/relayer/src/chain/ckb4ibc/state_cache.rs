@@ -0,0 +1,342 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use ckb_ics_axon::handler::{IbcChannel, IbcConnections};
+use ckb_types::packed::CellInput;
+use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, ClientId, PortId};
+
+use super::tendermint::TendermintClientCell;
+
+type PacketKey = (ChannelId, PortId, Sequence);
+
+/// Channels and packets of the cached cells
+pub type CachedKeys = (Vec<(ChannelId, PortId)>, Vec<PacketKey>);
+
+/// Cells of IBC objects and of Tendermint clients fetched from CKB, which are consumed
+/// as inputs when the corresponding objects get updated.
+///
+/// Every accessor takes the lock only for the duration of the call and returns
+/// an owned value, so no borrow outlives it and the cache can be shared across threads.
+///
+/// The cells chained on the outputs of pending transactions are pinned, i.e. they are
+/// neither replaced by the cells fetched from the indexer, which are outdated until the
+/// pending transactions are committed, nor invalidated, until they are unpinned or
+/// the whole cache is cleared.
+#[derive(Default)]
+pub struct IbcStateCache {
+    connection: RwLock<Option<(IbcConnections, CellInput)>>,
+    channels: RwLock<HashMap<ChannelId, IbcChannel>>,
+    channel_inputs: RwLock<HashMap<(ChannelId, PortId), CellInput>>,
+    packet_inputs: RwLock<HashMap<PacketKey, CellInput>>,
+    /// Packets of unordered channels whose live cells show they are received or
+    /// acknowledged already, as looked up before the conversion of their messages
+    processed_packets: RwLock<HashSet<PacketKey>>,
+    clients: RwLock<HashMap<ClientId, TendermintClientCell>>,
+    pinned_connection: RwLock<bool>,
+    pinned_channels: RwLock<HashSet<ChannelId>>,
+    pinned_clients: RwLock<HashSet<ClientId>>,
+}
+
+// The cached values are always replaced as a whole, so a panic while
+// holding a lock cannot leave them in an inconsistent state.
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl IbcStateCache {
+    pub fn connection(&self) -> Option<(IbcConnections, CellInput)> {
+        read(&self.connection).clone()
+    }
+
+    pub fn has_connection(&self) -> bool {
+        read(&self.connection).is_some()
+    }
+
+    pub fn set_connection(&self, connections: IbcConnections, input: CellInput) {
+        if !*read(&self.pinned_connection) {
+            *write(&self.connection) = Some((connections, input));
+        }
+    }
+
+    /// Set the connections updated by a pending transaction, pinning them
+    pub fn advance_connection(&self, connections: IbcConnections, input: CellInput) {
+        *write(&self.connection) = Some((connections, input));
+        *write(&self.pinned_connection) = true;
+    }
+
+    pub fn channel(&self, channel_id: &ChannelId) -> Option<IbcChannel> {
+        read(&self.channels).get(channel_id).cloned()
+    }
+
+    pub fn channel_input(&self, channel_id: &ChannelId, port_id: &PortId) -> Option<CellInput> {
+        read(&self.channel_inputs)
+            .get(&(channel_id.clone(), port_id.clone()))
+            .cloned()
+    }
+
+    pub fn insert_channel(
+        &self,
+        channel_id: ChannelId,
+        port_id: PortId,
+        channel: IbcChannel,
+        input: CellInput,
+    ) {
+        if read(&self.pinned_channels).contains(&channel_id) {
+            return;
+        }
+        write(&self.channel_inputs).insert((channel_id.clone(), port_id), input);
+        write(&self.channels).insert(channel_id, channel);
+    }
+
+    /// Replace the channel and the inputs of its cell on all its ports, so that the
+    /// next transactions of the channel consume the output of a pending one, pinning it
+    pub fn advance_channel(&self, channel_id: &ChannelId, channel: IbcChannel, input: CellInput) {
+        write(&self.pinned_channels).insert(channel_id.clone());
+        for (_, cell_input) in write(&self.channel_inputs)
+            .iter_mut()
+            .filter(|((id, _), _)| id == channel_id)
+        {
+            *cell_input = input.clone();
+        }
+        write(&self.channels).insert(channel_id.clone(), channel);
+    }
+
+    pub fn packet_input(
+        &self,
+        channel_id: &ChannelId,
+        port_id: &PortId,
+        sequence: Sequence,
+    ) -> Option<CellInput> {
+        read(&self.packet_inputs)
+            .get(&(channel_id.clone(), port_id.clone(), sequence))
+            .cloned()
+    }
+
+    pub fn insert_packet_input(
+        &self,
+        channel_id: ChannelId,
+        port_id: PortId,
+        sequence: Sequence,
+        input: CellInput,
+    ) {
+        write(&self.packet_inputs).insert((channel_id, port_id, sequence), input);
+    }
+
+    pub fn is_packet_processed(
+        &self,
+        channel_id: &ChannelId,
+        port_id: &PortId,
+        sequence: Sequence,
+    ) -> bool {
+        read(&self.processed_packets).contains(&(channel_id.clone(), port_id.clone(), sequence))
+    }
+
+    pub fn set_packet_processed(
+        &self,
+        channel_id: ChannelId,
+        port_id: PortId,
+        sequence: Sequence,
+        processed: bool,
+    ) {
+        let key = (channel_id, port_id, sequence);
+        if processed {
+            write(&self.processed_packets).insert(key);
+        } else {
+            write(&self.processed_packets).remove(&key);
+        }
+    }
+
+    pub fn client(&self, client_id: &ClientId) -> Option<TendermintClientCell> {
+        read(&self.clients).get(client_id).cloned()
+    }
+
+    pub fn insert_client(&self, client_id: ClientId, cell: TendermintClientCell) {
+        if !read(&self.pinned_clients).contains(&client_id) {
+            write(&self.clients).insert(client_id, cell);
+        }
+    }
+
+    /// Set the client updated by a pending transaction, pinning it
+    pub fn advance_client(&self, client_id: ClientId, cell: TendermintClientCell) {
+        write(&self.pinned_clients).insert(client_id.clone());
+        write(&self.clients).insert(client_id, cell);
+    }
+
+    pub fn invalidate_connection(&self) {
+        if !*read(&self.pinned_connection) {
+            *write(&self.connection) = None;
+        }
+    }
+
+    pub fn invalidate_channel(&self, channel_id: &ChannelId) {
+        if read(&self.pinned_channels).contains(channel_id) {
+            return;
+        }
+        write(&self.channels).remove(channel_id);
+        write(&self.channel_inputs).retain(|(id, _), _| id != channel_id);
+        write(&self.packet_inputs).retain(|(id, _, _), _| id != channel_id);
+        write(&self.processed_packets).retain(|(id, _, _)| id != channel_id);
+    }
+
+    pub fn invalidate_packet(&self, channel_id: &ChannelId, port_id: &PortId, sequence: Sequence) {
+        write(&self.packet_inputs).remove(&(channel_id.clone(), port_id.clone(), sequence));
+    }
+
+    /// Inputs of all the cached cells
+    pub fn inputs(&self) -> Vec<CellInput> {
+        let mut inputs = vec![];
+        if let Some((_, input)) = &*read(&self.connection) {
+            inputs.push(input.clone());
+        }
+        inputs.extend(read(&self.channel_inputs).values().cloned());
+        inputs.extend(read(&self.packet_inputs).values().cloned());
+        inputs.extend(read(&self.clients).values().map(|cell| cell.input.clone()));
+        inputs
+    }
+
+    pub fn cached_keys(&self) -> CachedKeys {
+        let channels = read(&self.channel_inputs).keys().cloned().collect();
+        let packets = read(&self.packet_inputs).keys().cloned().collect();
+        (channels, packets)
+    }
+
+    /// Whether some cells are chained on the outputs of pending transactions
+    pub fn has_pinned(&self) -> bool {
+        *read(&self.pinned_connection)
+            || !read(&self.pinned_channels).is_empty()
+            || !read(&self.pinned_clients).is_empty()
+    }
+
+    /// Let the cells fetched from the indexer replace the pinned ones again, once the
+    /// pending transactions are committed
+    pub fn unpin(&self) {
+        *write(&self.pinned_connection) = false;
+        write(&self.pinned_channels).clear();
+        write(&self.pinned_clients).clear();
+    }
+
+    /// Drop all the cached cells, they are consumed once a transaction is committed.
+    pub fn clear(&self) {
+        self.unpin();
+        self.invalidate_connection();
+        write(&self.channels).clear();
+        write(&self.channel_inputs).clear();
+        write(&self.packet_inputs).clear();
+        write(&self.processed_packets).clear();
+        write(&self.clients).clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_ics_axon::handler::IbcChannel;
+    use ckb_ics_axon::object::{ChannelCounterparty, Ordering as CkbOrdering, State as CkbState};
+    use ckb_types::packed::{CellInput, OutPoint};
+    use ckb_types::prelude::{Builder, Entity, Pack};
+    use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+    use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, PortId};
+
+    use super::IbcStateCache;
+
+    #[test]
+    fn test_invalidate_channel_drops_its_packets() {
+        let cache = IbcStateCache::default();
+        let port_id = PortId::transfer();
+        let sequence = Sequence::from(1);
+        cache.insert_packet_input(
+            ChannelId::new(0),
+            port_id.clone(),
+            sequence,
+            CellInput::default(),
+        );
+        cache.insert_packet_input(
+            ChannelId::new(1),
+            port_id.clone(),
+            sequence,
+            CellInput::default(),
+        );
+
+        cache.invalidate_channel(&ChannelId::new(0));
+        assert!(cache
+            .packet_input(&ChannelId::new(0), &port_id, sequence)
+            .is_none());
+        assert!(cache
+            .packet_input(&ChannelId::new(1), &port_id, sequence)
+            .is_some());
+
+        cache.clear();
+        assert!(cache
+            .packet_input(&ChannelId::new(1), &port_id, sequence)
+            .is_none());
+    }
+
+    #[test]
+    fn test_inputs_of_all_cached_cells() {
+        let cache = IbcStateCache::default();
+        assert!(cache.inputs().is_empty());
+
+        for sequence in 1..=2 {
+            cache.insert_packet_input(
+                ChannelId::new(0),
+                PortId::transfer(),
+                Sequence::from(sequence),
+                CellInput::default(),
+            );
+        }
+        assert_eq!(cache.inputs().len(), 2);
+
+        cache.clear();
+        assert!(cache.inputs().is_empty());
+    }
+
+    fn channel(num: u16) -> IbcChannel {
+        IbcChannel {
+            num,
+            port_id: String::new(),
+            state: CkbState::Open,
+            order: CkbOrdering::Unordered,
+            sequence: Default::default(),
+            counterparty: ChannelCounterparty {
+                port_id: String::new(),
+                channel_id: String::new(),
+            },
+            connection_hops: vec![0],
+        }
+    }
+
+    fn input(index: u32) -> CellInput {
+        CellInput::new_builder()
+            .previous_output(OutPoint::new_builder().index(index.pack()).build())
+            .build()
+    }
+
+    #[test]
+    fn test_pinned_channel_is_kept() {
+        let cache = IbcStateCache::default();
+        let channel_id = ChannelId::new(0);
+        let port_id = PortId::transfer();
+        cache.insert_channel(channel_id.clone(), port_id.clone(), channel(0), input(0));
+        cache.advance_channel(&channel_id, channel(1), input(1));
+        assert!(cache.has_pinned());
+
+        // the outdated cell fetched from the indexer doesn't replace the pending one
+        cache.insert_channel(channel_id.clone(), port_id.clone(), channel(0), input(0));
+        cache.invalidate_channel(&channel_id);
+        assert_eq!(cache.channel(&channel_id).unwrap().num, 1);
+        assert_eq!(cache.channel_input(&channel_id, &port_id), Some(input(1)));
+
+        cache.unpin();
+        cache.insert_channel(channel_id.clone(), port_id.clone(), channel(2), input(2));
+        assert_eq!(cache.channel(&channel_id).unwrap().num, 2);
+
+        cache.advance_channel(&channel_id, channel(3), input(3));
+        cache.clear();
+        assert!(!cache.has_pinned());
+        assert!(cache.channel(&channel_id).is_none());
+    }
+}
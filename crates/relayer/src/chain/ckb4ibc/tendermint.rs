@@ -0,0 +1,324 @@
+//! Clients of Tendermint counterparties on CKB.
+//!
+//! The state of a Tendermint client is held by a single live cell locked by the contract
+//! of the client, i.e. the one deployed under the type args of the client, so that the
+//! cell can only be consumed by a transaction updating the client with a header the
+//! contract verified. The cell data is the RLP encoding of the protobuf client state and
+//! of the protobuf consensus states of the latest heights of the client, at most
+//! `MAX_CONSENSUS_STATES` of them, so that the occupied capacity of the cell is bounded:
+//! the consensus state of the earliest height is pruned whenever an update exceeds it.
+//!
+//! The headers are carried by the envelopes of the update transactions, and the proofs
+//! of the Tendermint chain are ICS-23 merkle proofs, verified by the contract as well.
+
+use ckb_types::core::ScriptHashType;
+use ckb_types::packed::{CellInput, Script};
+use ckb_types::prelude::Builder;
+use ckb_types::H256;
+use ibc_proto::ibc::lightclients::tendermint::v1::{
+    ClientState as RawTmClientState, ConsensusState as RawTmConsensusState,
+};
+use ibc_proto::protobuf::Protobuf;
+use ibc_relayer_types::clients::ics07_tendermint::client_state::ClientState as TmClientState;
+use ibc_relayer_types::clients::ics07_tendermint::consensus_state::ConsensusState as TmConsensusState;
+use ibc_relayer_types::clients::ics07_tendermint::header::Header as TmHeader;
+use ibc_relayer_types::core::ics02_client::client_state::ClientState;
+use ibc_relayer_types::core::ics02_client::client_type::ClientType;
+use ibc_relayer_types::core::ics24_host::identifier::ClientId;
+use ibc_relayer_types::Height;
+use rlp::{DecoderError, Rlp, RlpStream};
+
+use crate::error::Error;
+
+use super::utils::get_script_hash;
+
+/// Maximum number of the consensus states kept by the cell of a Tendermint client
+pub const MAX_CONSENSUS_STATES: usize = 8;
+
+/// Whether `client_id` identifies the client of a Tendermint counterparty
+pub fn is_tendermint_client(client_id: &ClientId) -> bool {
+    client_id
+        .as_str()
+        .starts_with(ClientType::Tendermint.as_str())
+}
+
+/// Lock of the cell of a Tendermint client, i.e. the contract of the client
+pub fn get_client_lock_script(client_type_args: &H256) -> Script {
+    Script::new_builder()
+        .code_hash(get_script_hash(client_type_args))
+        .hash_type(ScriptHashType::Type.into())
+        .build()
+}
+
+/// State of a Tendermint client, as held by its cell
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TendermintClient {
+    pub client_state: TmClientState,
+    /// Consensus states of the client ordered by height, the one of its latest height last
+    pub consensus_states: Vec<(Height, TmConsensusState)>,
+}
+
+impl TendermintClient {
+    pub fn from_cell_data(client_id: &ClientId, data: &[u8]) -> Result<Self, Error> {
+        rlp::decode(data)
+            .map_err(|e| Error::ckb_tendermint_client_invalid(client_id.to_string(), e.to_string()))
+    }
+
+    /// Consensus state stored for `height`, which fails if the height is earlier than the
+    /// ones kept by the cell, i.e. its consensus state has been pruned
+    pub fn consensus_state(
+        &self,
+        client_id: &ClientId,
+        height: Height,
+    ) -> Result<&TmConsensusState, Error> {
+        if let Some((_, consensus_state)) = self.consensus_states.iter().find(|(h, _)| *h == height)
+        {
+            return Ok(consensus_state);
+        }
+        match self.consensus_states.first() {
+            Some((earliest_height, _)) if height < *earliest_height => Err(
+                Error::ckb_consensus_state_pruned(client_id.to_string(), height, *earliest_height),
+            ),
+            _ => Err(Error::ckb_consensus_state_not_found(
+                client_id.to_string(),
+                height,
+            )),
+        }
+    }
+
+    /// Heights of the consensus states kept by the cell
+    pub fn consensus_state_heights(&self) -> Vec<Height> {
+        self.consensus_states
+            .iter()
+            .map(|(height, _)| *height)
+            .collect()
+    }
+
+    /// Whether the consensus state of `header` is kept already, e.g. because another
+    /// relayer updated the client with it, so that updating the client with it is
+    /// redundant
+    pub fn has_consensus_state_of(&self, header: &TmHeader) -> bool {
+        let revision_number = self.client_state.latest_height().revision_number();
+        let revision_height = header.signed_header.header.height.value();
+        self.consensus_states.iter().any(|(height, _)| {
+            height.revision_number() == revision_number
+                && height.revision_height() == revision_height
+        })
+    }
+
+    /// Client updated with `header`, which has to be trusted from one of the heights
+    /// whose consensus state is kept, pruning the earliest ones beyond the maximum
+    pub fn update(&self, client_id: &ClientId, header: TmHeader) -> Result<Self, Error> {
+        let latest_height = self.client_state.latest_height();
+        if self
+            .consensus_state(client_id, header.trusted_height)
+            .is_err()
+        {
+            return Err(Error::ckb_tendermint_header_untrusted(
+                client_id.to_string(),
+                header.trusted_height,
+                latest_height,
+            ));
+        }
+        // the height of the header in the revision of the client, as `with_header` takes it
+        let height = Height::new(
+            latest_height.revision_number(),
+            header.signed_header.header.height.value(),
+        )
+        .map_err(|e| Error::ckb_tendermint_client_invalid(client_id.to_string(), e.to_string()))?;
+        let client_state = if height > latest_height {
+            self.client_state
+                .clone()
+                .with_header(header.clone())
+                .map_err(|e| {
+                    Error::ckb_tendermint_client_invalid(client_id.to_string(), e.to_string())
+                })?
+        } else {
+            self.client_state.clone()
+        };
+
+        let mut consensus_states = self.consensus_states.clone();
+        consensus_states.retain(|(h, _)| *h != height);
+        consensus_states.push((height, header.into()));
+        consensus_states.sort_by_key(|(h, _)| *h);
+        let pruned = consensus_states.len().saturating_sub(MAX_CONSENSUS_STATES);
+        consensus_states.drain(..pruned);
+        Ok(Self {
+            client_state,
+            consensus_states,
+        })
+    }
+}
+
+impl rlp::Encodable for TendermintClient {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        let client_state = Protobuf::<RawTmClientState>::encode_vec(&self.client_state)
+            .expect("encode Tendermint client state");
+        s.begin_list(2);
+        s.append(&client_state);
+        s.begin_list(self.consensus_states.len());
+        for (height, consensus_state) in &self.consensus_states {
+            let consensus_state = Protobuf::<RawTmConsensusState>::encode_vec(consensus_state)
+                .expect("encode Tendermint consensus state");
+            s.begin_list(3);
+            s.append(&height.revision_number());
+            s.append(&height.revision_height());
+            s.append(&consensus_state);
+        }
+    }
+}
+
+impl rlp::Decodable for TendermintClient {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 2 {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+        let client_state: Vec<u8> = rlp.val_at(0)?;
+        let consensus_states = rlp
+            .at(1)?
+            .iter()
+            .map(|item| {
+                if item.item_count()? != 3 {
+                    return Err(DecoderError::RlpIncorrectListLen);
+                }
+                let height = Height::new(item.val_at(0)?, item.val_at(1)?)
+                    .map_err(|_| DecoderError::Custom("invalid Tendermint consensus height"))?;
+                let consensus_state: Vec<u8> = item.val_at(2)?;
+                let consensus_state = Protobuf::<RawTmConsensusState>::decode_vec(&consensus_state)
+                    .map_err(|_| DecoderError::Custom("invalid Tendermint consensus state"))?;
+                Ok((height, consensus_state))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if consensus_states.is_empty() {
+            return Err(DecoderError::Custom("no Tendermint consensus state"));
+        }
+        Ok(Self {
+            client_state: Protobuf::<RawTmClientState>::decode_vec(&client_state)
+                .map_err(|_| DecoderError::Custom("invalid Tendermint client state"))?,
+            consensus_states,
+        })
+    }
+}
+
+/// Live cell of a Tendermint client, consumed by the next update of the client
+#[derive(Clone, Debug)]
+pub struct TendermintClientCell {
+    pub client: TendermintClient,
+    pub input: CellInput,
+    pub capacity: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use ibc_relayer_types::clients::ics07_tendermint::client_state::test_util::get_dummy_tendermint_client_state;
+    use ibc_relayer_types::clients::ics07_tendermint::consensus_state::ConsensusState as TmConsensusState;
+    use ibc_relayer_types::clients::ics07_tendermint::header::test_util::get_dummy_ics07_header;
+    use ibc_relayer_types::clients::ics07_tendermint::header::Header as TmHeader;
+    use ibc_relayer_types::core::ics02_client::client_state::ClientState;
+    use ibc_relayer_types::core::ics02_client::client_type::ClientType;
+    use ibc_relayer_types::core::ics24_host::identifier::ClientId;
+    use ibc_relayer_types::Height;
+
+    use super::{is_tendermint_client, TendermintClient, MAX_CONSENSUS_STATES};
+
+    fn client() -> TendermintClient {
+        let header = get_dummy_ics07_header();
+        let mut client_state =
+            get_dummy_tendermint_client_state(header.signed_header.header.clone());
+        client_state.latest_height = header.trusted_height;
+        TendermintClient {
+            client_state,
+            consensus_states: vec![(header.trusted_height, header.into())],
+        }
+    }
+
+    /// Header of `height`, trusted from `trusted_height`
+    fn header(height: u64, trusted_height: u64) -> TmHeader {
+        let mut header = get_dummy_ics07_header();
+        header.signed_header.header.height = height.try_into().unwrap();
+        header.trusted_height = Height::new(0, trusted_height).unwrap();
+        header
+    }
+
+    #[test]
+    fn test_tendermint_client_cell_data() {
+        let client_id = ClientId::new(ClientType::Tendermint, 0).unwrap();
+        assert!(is_tendermint_client(&client_id));
+        assert!(!is_tendermint_client(
+            &ClientId::new(ClientType::Ckb4Ibc, 0).unwrap()
+        ));
+
+        let client = client()
+            .update(&client_id, header(2, 1))
+            .unwrap()
+            .update(&client_id, header(3, 2))
+            .unwrap();
+        let data = rlp::encode(&client);
+        assert_eq!(
+            TendermintClient::from_cell_data(&client_id, &data).unwrap(),
+            client
+        );
+        assert!(TendermintClient::from_cell_data(&client_id, &data[1..]).is_err());
+    }
+
+    #[test]
+    fn test_update_tendermint_client() {
+        let client_id = ClientId::new(ClientType::Tendermint, 0).unwrap();
+        let client = client();
+        let header = get_dummy_ics07_header();
+
+        assert!(!client.has_consensus_state_of(&header));
+        let updated = client.update(&client_id, header.clone()).unwrap();
+        assert!(updated.has_consensus_state_of(&header));
+        let height = Height::new(0, header.signed_header.header.height.value()).unwrap();
+        assert_eq!(updated.client_state.latest_height(), height);
+        assert_eq!(
+            updated.consensus_state(&client_id, height).unwrap(),
+            &TmConsensusState::from(header.clone())
+        );
+        // the consensus state of the trusted height is kept by the update
+        assert_eq!(
+            updated.consensus_state_heights(),
+            vec![header.trusted_height, height]
+        );
+        assert!(updated.update(&client_id, header).is_ok());
+
+        let err = updated
+            .update(&client_id, self::header(height.revision_height() + 1, 2))
+            .unwrap_err();
+        assert!(err.to_string().contains("trusted"));
+    }
+
+    #[test]
+    fn test_prune_tendermint_consensus_states() {
+        let client_id = ClientId::new(ClientType::Tendermint, 0).unwrap();
+        let mut client = client();
+        let last = MAX_CONSENSUS_STATES as u64 + 2;
+        for height in 2..=last {
+            client = client
+                .update(&client_id, header(height, height - 1))
+                .unwrap();
+        }
+        let earliest = last - MAX_CONSENSUS_STATES as u64 + 1;
+        let heights = (earliest..=last)
+            .map(|height| Height::new(0, height).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(client.consensus_state_heights(), heights);
+        assert_eq!(
+            client.client_state.latest_height(),
+            heights[heights.len() - 1]
+        );
+
+        let pruned = Height::new(0, earliest - 1).unwrap();
+        let err = client.consensus_state(&client_id, pruned).unwrap_err();
+        assert!(err.to_string().contains("pruned"));
+        let unknown = Height::new(0, last + 1).unwrap();
+        let err = client.consensus_state(&client_id, unknown).unwrap_err();
+        assert!(err.to_string().contains("no consensus state"));
+
+        // the consensus state of a pruned height can't be trusted anymore
+        assert!(client
+            .update(&client_id, header(last + 1, earliest - 1))
+            .is_err());
+    }
+}
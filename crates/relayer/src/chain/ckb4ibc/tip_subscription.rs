@@ -0,0 +1,201 @@
+//! Tips of a CKB chain pushed by its node over WebSocket.
+//!
+//! The node notifies the subscribers of its `new_tip_header` topic of every block
+//! appended to its chain, so that the event monitor scans the new blocks as soon as
+//! they come instead of polling the node for its tip. The subscription lives in a
+//! background task which connects again once it is lost, the monitor falling back to
+//! polling until it is back.
+
+use core::time::Duration;
+
+use ckb_jsonrpc_types::HeaderView;
+use futures::{SinkExt, StreamExt};
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+use serde_json::{json, Value};
+use tendermint_rpc::Url;
+use tokio::runtime::Runtime as TokioRuntime;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{info, warn};
+
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+enum TipUpdate {
+    Connected,
+    Tip(HeaderView),
+    Disconnected,
+}
+
+/// Subscription to the tips of a CKB node
+pub struct TipSubscription {
+    updates: UnboundedReceiver<TipUpdate>,
+    connected: bool,
+}
+
+impl TipSubscription {
+    /// Subscribe to the tips of the node serving WebSocket clients at `url`
+    pub fn spawn(rt: &TokioRuntime, url: Url, chain_id: ChainId) -> Self {
+        let (sender, updates) = unbounded_channel();
+        rt.spawn(run_subscription(url, chain_id, sender));
+        Self {
+            updates,
+            connected: false,
+        }
+    }
+
+    /// Whether the node was pushing its tips when the updates were last received
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Wait up to `timeout` for the node to push a new tip, returning the latest tip
+    /// pushed, if any. It only sleeps if the subscription is lost
+    pub async fn next_tip(&mut self, timeout: Duration) -> Option<HeaderView> {
+        let mut tip = self.drain();
+        if tip.is_none() {
+            if self.connected {
+                if let Ok(Some(update)) = tokio::time::timeout(timeout, self.updates.recv()).await {
+                    tip = self.apply(update);
+                }
+            } else {
+                tokio::time::sleep(timeout).await;
+            }
+        }
+        self.drain().or(tip)
+    }
+
+    /// Apply the updates received so far, returning the latest tip among them
+    fn drain(&mut self) -> Option<HeaderView> {
+        let mut tip = None;
+        while let Ok(update) = self.updates.try_recv() {
+            tip = self.apply(update).or(tip);
+        }
+        tip
+    }
+
+    fn apply(&mut self, update: TipUpdate) -> Option<HeaderView> {
+        match update {
+            TipUpdate::Connected => self.connected = true,
+            TipUpdate::Tip(header) => return Some(header),
+            TipUpdate::Disconnected => self.connected = false,
+        }
+        None
+    }
+}
+
+/// Keep the subscription alive until the monitor drops it
+async fn run_subscription(url: Url, chain_id: ChainId, sender: UnboundedSender<TipUpdate>) {
+    let mut failures = 0u32;
+    loop {
+        let reason = match subscribe(&url, &sender, &mut failures).await {
+            Ok(()) => return,
+            Err(reason) => reason,
+        };
+        if sender.send(TipUpdate::Disconnected).is_err() {
+            return;
+        }
+        failures += 1;
+        let delay = INITIAL_RECONNECT_DELAY
+            .saturating_mul(1 << failures.saturating_sub(1).min(16))
+            .min(MAX_RECONNECT_DELAY);
+        warn!(
+            chain = %chain_id,
+            "lost the subscription to the tips of ckb node {url}, polling it until \
+             connecting again in {delay:?}: {reason}"
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Forward the tips pushed by the node until the subscription fails, or until the
+/// monitor drops its end of the subscription, which ends it without error
+async fn subscribe(
+    url: &Url,
+    sender: &UnboundedSender<TipUpdate>,
+    failures: &mut u32,
+) -> Result<(), String> {
+    let (mut stream, _) = tokio_tungstenite::connect_async(url.to_string())
+        .await
+        .map_err(|e| e.to_string())?;
+    let request = json!({
+        "id": 0,
+        "jsonrpc": "2.0",
+        "method": "subscribe",
+        "params": ["new_tip_header"],
+    });
+    stream
+        .send(Message::Text(request.to_string()))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    while let Some(message) = stream.next().await {
+        let text = match message.map_err(|e| e.to_string())? {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let message: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        if let Some(error) = message.get("error") {
+            return Err(format!("the node rejected the subscription: {error}"));
+        }
+        // the notifications carry the header serialized as a string, while the
+        // response to the request only carries the identifier of the subscription
+        let update = match message.pointer("/params/result").and_then(Value::as_str) {
+            Some(result) => {
+                let header = serde_json::from_str(result).map_err(|e| e.to_string())?;
+                TipUpdate::Tip(header)
+            }
+            None => {
+                info!("subscribed to the tips of ckb node {url}");
+                *failures = 0;
+                TipUpdate::Connected
+            }
+        };
+        if sender.send(update).is_err() {
+            return Ok(());
+        }
+    }
+    Err("the node closed the connection".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use ckb_jsonrpc_types::HeaderView;
+    use ckb_types::core::HeaderBuilder;
+    use ckb_types::prelude::Pack;
+    use tokio::runtime::Runtime as TokioRuntime;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use super::{TipSubscription, TipUpdate};
+
+    #[test]
+    fn test_next_tip_returns_the_latest_pushed_tip() {
+        let rt = TokioRuntime::new().unwrap();
+        let (sender, updates) = unbounded_channel();
+        let mut subscription = TipSubscription {
+            updates,
+            connected: false,
+        };
+        let header =
+            |number: u64| HeaderView::from(HeaderBuilder::default().number(number.pack()).build());
+
+        sender.send(TipUpdate::Connected).unwrap();
+        sender.send(TipUpdate::Tip(header(1))).unwrap();
+        sender.send(TipUpdate::Tip(header(2))).unwrap();
+        let tip = rt.block_on(subscription.next_tip(Duration::from_secs(5)));
+        assert!(subscription.is_connected());
+        assert_eq!(tip.map(|tip| tip.inner.number.value()), Some(2));
+
+        // no new block within the timeout
+        let tip = rt.block_on(subscription.next_tip(Duration::from_millis(10)));
+        assert!(tip.is_none());
+
+        sender.send(TipUpdate::Disconnected).unwrap();
+        let tip = rt.block_on(subscription.next_tip(Duration::from_millis(10)));
+        assert!(tip.is_none());
+        assert!(!subscription.is_connected());
+    }
+}
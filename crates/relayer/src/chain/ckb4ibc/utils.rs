@@ -1,5 +1,7 @@
 use std::str::FromStr;
 
+use crate::chain::ckb4ibc::commitment::IbcCellProof;
+use crate::chain::requests::PageRequest;
 use crate::config::ckb4ibc::ChainConfig;
 use crate::error::Error;
 use ckb_ics_axon::consts::{
@@ -9,15 +11,18 @@ use ckb_ics_axon::consts::{
 use ckb_ics_axon::object::Proofs as CkbProofs;
 use ckb_ics_axon::proof::ObjectProof;
 use ckb_ics_axon::ConnectionArgs;
+use ckb_jsonrpc_types::{Either, ResponseFormat, TransactionView};
 use ckb_sdk::constants::TYPE_ID_CODE_HASH;
-use ckb_sdk::rpc::ckb_light_client::{ScriptType, SearchKey};
+use ckb_sdk::rpc::ckb_light_client::{ScriptStatus, ScriptType, SearchKey};
 use ckb_types::core::{Capacity, ScriptHashType};
-use ckb_types::packed::{Byte32, Bytes, BytesOpt, Script};
+use ckb_types::packed::{self, Byte32, Bytes, BytesOpt, Script};
 use ckb_types::prelude::{Builder, Entity, Pack};
 use ckb_types::H256;
+use ibc_proto::ibc::core::commitment::v1::MerkleProof as RawMerkleProof;
 use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
-use ibc_relayer_types::proofs::{ConsensusProof, Proofs};
+use ibc_relayer_types::proofs::Proofs;
 use ibc_relayer_types::Height;
+use prost::Message;
 use tiny_keccak::{Hasher, Keccak};
 
 pub fn keccak256(slice: &[u8]) -> [u8; 32] {
@@ -43,14 +48,30 @@ pub fn get_encoded_object<T: rlp::Encodable>(obj: T) -> EncodedObject {
     }
 }
 
+/// Convert the proofs of the counterparty, which are either the object proofs of Axon,
+/// or the proofs of the IBC cells of CKB and the ICS-23 merkle proofs of a Tendermint
+/// chain, left to the client to verify.
 pub fn convert_proof(ckb_proofs: Proofs) -> Result<CkbProofs, Error> {
     let object_proof_data: Vec<u8> = ckb_proofs.object_proof().clone().into();
-    let object_proof = rlp::decode::<ObjectProof>(&object_proof_data)
-        .map_err(|_| Error::other_error(String::from("convert object proof error")))?;
+    let height = ckb_proofs.height().revision_number().to_le_bytes().to_vec();
+    if let Ok(object_proof) = rlp::decode::<ObjectProof>(&object_proof_data) {
+        return Ok(CkbProofs {
+            height,
+            object_proof,
+            client_proof: vec![],
+        });
+    }
+    let is_client_proof = rlp::decode::<IbcCellProof>(&object_proof_data).is_ok()
+        || RawMerkleProof::decode(object_proof_data.as_slice()).is_ok();
+    if !is_client_proof {
+        return Err(Error::other_error(String::from(
+            "convert object proof error",
+        )));
+    }
     Ok(CkbProofs {
-        height: ckb_proofs.height().revision_number().to_le_bytes().to_vec(),
-        object_proof,
-        client_proof: vec![],
+        height,
+        object_proof: ObjectProof::default(),
+        client_proof: object_proof_data,
     })
 }
 
@@ -123,6 +144,39 @@ pub fn get_connection_lock_script(config: &ChainConfig) -> Script {
         .build()
 }
 
+/// Scripts registered with a ckb-light-client node, i.e. the type ids of the client,
+/// connection, channel and packet contracts and the lock of the connection cell
+pub fn get_light_client_scripts(config: &ChainConfig) -> Vec<ScriptStatus> {
+    let block_number = config.light_client_start_block.into();
+    let mut all_type_args = config.all_client_type_args();
+    all_type_args.extend([
+        &config.connection_type_args,
+        &config.channel_type_args,
+        &config.packet_type_args,
+    ]);
+    let mut scripts = all_type_args
+        .into_iter()
+        .map(|type_args| {
+            let script = Script::new_builder()
+                .code_hash(TYPE_ID_CODE_HASH.pack())
+                .hash_type(ScriptHashType::Type.into())
+                .args(type_args.as_bytes().pack())
+                .build();
+            ScriptStatus {
+                script: script.into(),
+                script_type: ScriptType::Type,
+                block_number,
+            }
+        })
+        .collect::<Vec<_>>();
+    scripts.push(ScriptStatus {
+        script: get_connection_lock_script(config).into(),
+        script_type: ScriptType::Lock,
+        block_number,
+    });
+    scripts
+}
+
 pub fn get_search_key(script: Script) -> SearchKey {
     SearchKey {
         script: script.into(),
@@ -148,19 +202,154 @@ pub fn get_packet_capacity() -> Capacity {
     Capacity::bytes(PACKET_CELL_CAPACITY as usize).unwrap()
 }
 
-pub fn get_dummy_merkle_proof(height: Height) -> Proofs {
-    let encoded = rlp::encode(&ObjectProof::default()).to_vec();
-    let consensus_proof = ConsensusProof::new(
-        vec![0u8].try_into().unwrap(),
-        Height::new(1, u64::MAX).unwrap(),
-    )
-    .unwrap();
-    Proofs::new(
-        encoded.try_into().unwrap(),
-        Some(vec![0u8].try_into().unwrap()),
-        Some(consensus_proof),
-        None,
-        height,
-    )
-    .unwrap()
+/// Proofs of an object whose proof is `object_proof`. The clients of CKB are not held
+/// by IBC cells, so there are no client and consensus proofs to go along with it.
+pub fn get_ibc_cell_merkle_proof(object_proof: Vec<u8>, height: Height) -> Proofs {
+    Proofs::new(object_proof.try_into().unwrap(), None, None, None, height).unwrap()
+}
+
+/// Decode a transaction returned by `get_transaction`.
+///
+/// Depending on the verbosity and on the node, the transaction is either returned as
+/// its JSON view or as its molecule-packed bytes. Some nodes wrap the JSON view in the
+/// bytes instead, which is only tried if the bytes are not a packed transaction.
+pub fn decode_transaction(tx: ResponseFormat<TransactionView>) -> Result<TransactionView, Error> {
+    let bytes = match tx.inner {
+        Either::Left(tx) => return Ok(tx),
+        Either::Right(bytes) => bytes,
+    };
+    match packed::Transaction::from_slice(bytes.as_bytes()) {
+        Ok(tx) => Ok(tx.into_view().into()),
+        Err(e) => serde_json::from_slice(bytes.as_bytes())
+            .map_err(|_| Error::query(format!("invalid packed transaction: {e}"))),
+    }
+}
+
+/// Select the page requested by `pagination` among `items`, or all of them if no page
+/// is requested. The indexer cursors are not stable across the updates of the cells,
+/// so only the offset of the page is supported and its key is ignored.
+pub fn paginate<T>(mut items: Vec<T>, pagination: Option<PageRequest>) -> Vec<T> {
+    let Some(pagination) = pagination else {
+        return items;
+    };
+    if pagination.reverse {
+        items.reverse();
+    }
+    let limit = match pagination.limit {
+        0 => usize::MAX,
+        limit => limit as usize,
+    };
+    items
+        .into_iter()
+        .skip(pagination.offset as usize)
+        .take(limit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_ics_axon::proof::ObjectProof;
+    use ckb_jsonrpc_types::{Either, JsonBytes, ResponseFormat, TransactionView};
+    use ckb_types::core::TransactionBuilder;
+    use ckb_types::packed::CellOutput;
+    use ckb_types::prelude::{Builder, Entity, Pack};
+    use ibc_proto::ibc::core::commitment::v1::MerkleProof as RawMerkleProof;
+    use ibc_proto::ics23::CommitmentProof;
+    use ibc_relayer_types::proofs::Proofs;
+    use ibc_relayer_types::Height;
+    use prost::Message;
+
+    use super::{convert_proof, decode_transaction, get_ibc_cell_merkle_proof, paginate};
+    use crate::chain::requests::PageRequest;
+
+    fn transaction() -> TransactionView {
+        TransactionBuilder::default()
+            .output(CellOutput::new_builder().capacity(100u64.pack()).build())
+            .output_data(vec![1u8, 2, 3].pack())
+            .build()
+            .into()
+    }
+
+    #[test]
+    fn test_decode_packed_transaction() {
+        let tx = transaction();
+        let packed = ckb_types::packed::Transaction::from(tx.inner.clone());
+        let resp = ResponseFormat {
+            inner: Either::Right(JsonBytes::from_vec(packed.as_slice().to_vec())),
+        };
+        assert_eq!(decode_transaction(resp).unwrap().hash, tx.hash);
+    }
+
+    #[test]
+    fn test_decode_json_transaction() {
+        let tx = transaction();
+        let resp = ResponseFormat {
+            inner: Either::Right(JsonBytes::from_vec(serde_json::to_vec(&tx).unwrap())),
+        };
+        assert_eq!(decode_transaction(resp).unwrap().hash, tx.hash);
+
+        let resp = ResponseFormat {
+            inner: Either::Left(tx.clone()),
+        };
+        assert_eq!(decode_transaction(resp).unwrap().hash, tx.hash);
+    }
+
+    #[test]
+    fn test_decode_invalid_transaction() {
+        let resp = ResponseFormat::<TransactionView> {
+            inner: Either::Right(JsonBytes::from_vec(vec![1, 2, 3])),
+        };
+        assert!(decode_transaction(resp).is_err());
+    }
+
+    #[test]
+    fn test_convert_tendermint_proof() {
+        let height = Height::new(1, 10).unwrap();
+        let object_proof = rlp::encode(&ObjectProof::default()).to_vec();
+        let proofs = convert_proof(get_ibc_cell_merkle_proof(object_proof, height)).unwrap();
+        assert!(proofs.client_proof.is_empty());
+
+        let merkle_proof = RawMerkleProof {
+            proofs: vec![CommitmentProof::default()],
+        }
+        .encode_to_vec();
+        let tendermint_proofs = Proofs::new(
+            merkle_proof.clone().try_into().unwrap(),
+            None,
+            None,
+            None,
+            height,
+        )
+        .unwrap();
+        let proofs = convert_proof(tendermint_proofs).unwrap();
+        assert_eq!(proofs.client_proof, merkle_proof);
+
+        let invalid_proofs =
+            Proofs::new(vec![1, 2, 3].try_into().unwrap(), None, None, None, height).unwrap();
+        assert!(convert_proof(invalid_proofs).is_err());
+    }
+
+    #[test]
+    fn test_paginate() {
+        let items = (0..10).collect::<Vec<_>>();
+        assert_eq!(paginate(items.clone(), None), items);
+        assert_eq!(paginate(items.clone(), Some(PageRequest::all())), items);
+
+        let page = PageRequest {
+            offset: 8,
+            limit: 3,
+            ..Default::default()
+        };
+        assert_eq!(paginate(items.clone(), Some(page.clone())), vec![8, 9]);
+        assert_eq!(
+            paginate(
+                items,
+                Some(PageRequest {
+                    reverse: true,
+                    ..page
+                })
+            ),
+            vec![1, 0]
+        );
+    }
 }
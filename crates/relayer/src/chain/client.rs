@@ -1,5 +1,11 @@
 //! Data structures and logic to set up IBC client's parameters.
 
+use core::time::Duration;
+
+use serde::Serialize;
+
+use ibc_relayer_types::core::ics02_client::trust_threshold::TrustThreshold;
+
 use crate::chain::cosmos;
 use crate::config::ChainConfig;
 use crate::foreign_client::CreateOptions;
@@ -39,3 +45,48 @@ impl ClientSettings {
         }
     }
 }
+
+/// Client parameters derived from the on-chain parameters of the chain targeted by a
+/// new client, which `create client --auto` prints for confirmation.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DerivedClientParams {
+    /// Unbonding period of the staking module of a Cosmos chain
+    #[serde(with = "humantime_serde")]
+    pub unbonding_period: Option<Duration>,
+    #[serde(with = "humantime_serde")]
+    pub trusting_period: Option<Duration>,
+    pub trust_threshold: Option<TrustThreshold>,
+    /// Forks scheduled by an Ethereum chain, which the client has to follow to verify
+    /// the signatures of the sync committees
+    pub forks: Vec<ScheduledFork>,
+}
+
+impl DerivedClientParams {
+    /// Derive the parameters of a Tendermint client from the unbonding period of the
+    /// chain, trusting the validator sets for two thirds of it, as long as a third of
+    /// their voting power signs the headers.
+    pub fn tendermint(unbonding_period: Duration) -> Self {
+        Self {
+            unbonding_period: Some(unbonding_period),
+            trusting_period: Some(2 * unbonding_period / 3),
+            trust_threshold: Some(TrustThreshold::ONE_THIRD),
+            forks: vec![],
+        }
+    }
+
+    pub fn create_options(&self) -> CreateOptions {
+        CreateOptions {
+            max_clock_drift: None,
+            trusting_period: self.trusting_period,
+            trust_threshold: self.trust_threshold,
+        }
+    }
+}
+
+/// A fork of an Ethereum chain, activated at `epoch`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ScheduledFork {
+    pub epoch: u64,
+    /// Hex-encoded fork version
+    pub version: String,
+}
@@ -8,7 +8,7 @@ use core::{
 };
 use futures::future::join_all;
 use num_bigint::BigInt;
-use std::{cmp::Ordering, thread};
+use std::cmp::Ordering;
 
 use tokio::runtime::Runtime as TokioRuntime;
 use tonic::{codegen::http::Uri, metadata::AsciiMetadataValue};
@@ -58,7 +58,7 @@ use tendermint_rpc::endpoint::broadcast::tx_sync::Response;
 use tendermint_rpc::endpoint::status;
 use tendermint_rpc::{Client, HttpClient, Order};
 
-use crate::chain::client::ClientSettings;
+use crate::chain::client::{ClientSettings, DerivedClientParams};
 use crate::chain::cosmos::batch::{
     send_batched_messages_and_wait_check_tx, send_batched_messages_and_wait_commit,
     sequential_send_batched_messages_and_wait_commit,
@@ -82,7 +82,7 @@ use crate::chain::cosmos::types::config::TxConfig;
 use crate::chain::cosmos::types::gas::{
     default_gas_from_config, gas_multiplier_from_config, max_gas_from_config,
 };
-use crate::chain::endpoint::{ChainEndpoint, ChainStatus, HealthCheck};
+use crate::chain::endpoint::{ChainCapabilities, ChainEndpoint, ChainStatus, HealthCheck};
 use crate::chain::handle::Subscription;
 use crate::chain::requests::*;
 use crate::chain::tracking::TrackedMsgs;
@@ -97,6 +97,7 @@ use crate::keyring::{KeyRing, Secp256k1KeyPair, SigningKeyPair};
 use crate::light_client::tendermint::LightClient as TmLightClient;
 use crate::light_client::{LightClient, Verified};
 use crate::misbehaviour::MisbehaviourEvidence;
+use crate::util::panic::spawn_chain_thread;
 use crate::util::pretty::{
     PrettyIdentifiedChannel, PrettyIdentifiedClientState, PrettyIdentifiedConnection,
 };
@@ -302,7 +303,9 @@ impl CosmosSdkChain {
             .init_subscriptions()
             .map_err(Error::event_monitor)?;
 
-        thread::spawn(move || event_monitor.run());
+        spawn_chain_thread(self.config.id.clone(), "event_monitor", move || {
+            event_monitor.run()
+        });
 
         Ok(monitor_tx)
     }
@@ -405,6 +408,11 @@ impl CosmosSdkChain {
             return Ok(unbonding_period);
         }
 
+        self.query_unbonding_period()
+    }
+
+    /// The unbonding period in the staking params of this chain
+    fn query_unbonding_period(&self) -> Result<Duration, Error> {
         let unbonding_time = self.query_staking_params()?.unbonding_time.ok_or_else(|| {
             Error::grpc_response_param("no unbonding time in staking params".to_string())
         })?;
@@ -828,6 +836,17 @@ impl ChainEndpoint for CosmosSdkChain {
         Ok(HealthCheck::Healthy)
     }
 
+    fn capabilities(&self) -> ChainCapabilities {
+        ChainCapabilities::ALL
+    }
+
+    /// Derive the client parameters from the unbonding period in the staking params,
+    /// whatever the unbonding period set in the configuration.
+    fn query_client_params(&self) -> Result<DerivedClientParams, Error> {
+        let unbonding_period = self.query_unbonding_period()?;
+        Ok(DerivedClientParams::tendermint(unbonding_period))
+    }
+
     /// Fetch a header from the chain at the given height and verify it.
     fn verify_header(
         &mut self,
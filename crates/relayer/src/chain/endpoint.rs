@@ -1,3 +1,4 @@
+use alloc::collections::BTreeMap;
 use alloc::sync::Arc;
 use core::convert::TryFrom;
 
@@ -32,7 +33,14 @@ use ibc_relayer_types::Height as ICSHeight;
 use tendermint_rpc::endpoint::broadcast::tx_sync::Response as TxResponse;
 
 use crate::account::Balance;
-use crate::chain::client::ClientSettings;
+use crate::audit::ConfigDrift;
+use crate::chain::ckb::client_cells::ClientCells;
+use crate::chain::ckb::prune::ClientsPruning;
+use crate::chain::ckb4ibc::cells::{IbcCell, IbcCellKind};
+use crate::chain::ckb4ibc::footprint::StorageFootprint;
+use crate::chain::ckb4ibc::history::IbcStateAt;
+use crate::chain::ckb4ibc::packet_size::OversizedPacket;
+use crate::chain::client::{ClientSettings, DerivedClientParams};
 use crate::chain::handle::Subscription;
 use crate::chain::requests::*;
 use crate::chain::tracking::TrackedMsgs;
@@ -46,6 +54,7 @@ use crate::event::IbcEventWithHeight;
 use crate::keyring::{AnySigningKeyPair, KeyRing, SigningKeyPairSized};
 use crate::light_client::AnyHeader;
 use crate::misbehaviour::MisbehaviourEvidence;
+use crate::progress::RelayStatus;
 
 use super::handle::CacheTxHashStatus;
 
@@ -63,6 +72,35 @@ pub struct ChainStatus {
     pub timestamp: Timestamp,
 }
 
+/// Features of IBC which a chain may not support, beyond the handshakes of the connections
+/// and the channels and the relaying of unordered packets, so that the supervisor and the
+/// workers check for them instead of relying on them
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChainCapabilities {
+    /// Channels whose packets are received in the order they are sent
+    pub ordered_channels: bool,
+    /// ICS-29 fee middleware, i.e. the incentivized packets and the counterparty payees
+    pub fee_middleware: bool,
+    /// ICS-31 cross-chain queries answered by the chain
+    pub cross_chain_queries: bool,
+    /// Upgrades of the chain which its clients on the counterparties are upgraded to
+    pub client_upgrades: bool,
+    /// Proofs of the IBC states of the chain, which the counterparties verify the
+    /// handshakes and the packets relayed from the chain against
+    pub proofs: bool,
+}
+
+impl ChainCapabilities {
+    /// Capabilities of a chain supporting every feature
+    pub const ALL: Self = Self {
+        ordered_channels: true,
+        fee_middleware: true,
+        cross_chain_queries: true,
+        client_upgrades: true,
+        proofs: true,
+    };
+}
+
 /// Defines a blockchain as understood by the relayer
 pub trait ChainEndpoint: Sized {
     /// Type of light blocks for this chain
@@ -99,9 +137,102 @@ pub trait ChainEndpoint: Sized {
     /// Perform a health check
     fn health_check(&self) -> Result<HealthCheck, Error>;
 
+    /// Features of IBC the chain supports
+    fn capabilities(&self) -> ChainCapabilities;
+
     // Events
     fn subscribe(&mut self) -> Result<Subscription, Error>;
 
+    /// Drop the chain-specific cached states, so that they are fetched again
+    /// from the chain the next time they are needed.
+    fn invalidate_cache(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Query the number and the capacity of the cells holding the IBC states,
+    /// which is only available on chains charging for the storage of states.
+    fn query_storage_footprint(&self) -> Result<StorageFootprint, Error> {
+        Err(Error::query(format!(
+            "storage footprint is not available on chain {}",
+            self.id()
+        )))
+    }
+
+    /// Query the pending packets skipped by the relayer because their data exceed the
+    /// size ceilings of the chain, which only apply to chains extracting the packets
+    /// from arbitrary on-chain data.
+    fn query_oversized_packets(&self) -> Result<Vec<OversizedPacket>, Error> {
+        Ok(vec![])
+    }
+
+    /// Query the connections, the channels and the outstanding packets as they were at
+    /// `block_number`, which is only available on chains able to look up their past
+    /// IBC states.
+    fn query_ibc_state_at(&self, block_number: u64) -> Result<IbcStateAt, Error> {
+        Err(Error::query(format!(
+            "IBC states at block {block_number} are not available on chain {}",
+            self.id()
+        )))
+    }
+
+    /// Query the live IBC cells of `kind`, or of all the kinds, along with the states
+    /// decoded from them, which only exist on chains keeping the IBC states in cells.
+    fn query_ibc_cells(&self, _kind: Option<IbcCellKind>) -> Result<Vec<IbcCell>, Error> {
+        Err(Error::query(format!(
+            "IBC cells are not available on chain {}",
+            self.id()
+        )))
+    }
+
+    /// Compare the chain-specific fields of the config which mirror on-chain states,
+    /// such as the addresses of the IBC contracts, against the live states of the chain.
+    fn audit_config(&self) -> Result<Vec<ConfigDrift>, Error> {
+        Ok(vec![])
+    }
+
+    /// Derive the parameters of a new client targeting this chain from its on-chain
+    /// parameters, such as the staking params of a Cosmos chain.
+    fn query_client_params(&self) -> Result<DerivedClientParams, Error> {
+        Err(Error::query(format!(
+            "client parameters cannot be derived from chain {}",
+            self.id()
+        )))
+    }
+
+    /// Consume the light client cells of the abandoned deployments of the Ethereum
+    /// light client, which only exist on CKB. Only lists them if `dry_run` is set.
+    fn prune_obsolete_clients(&mut self, _dry_run: bool) -> Result<ClientsPruning, Error> {
+        Err(Error::other_error(format!(
+            "there are no light client cells to prune on chain {}",
+            self.id()
+        )))
+    }
+
+    /// Query the decoded cells of the multi-client set of the Ethereum light client,
+    /// which only exist on CKB
+    fn query_light_client_cells(&self) -> Result<ClientCells, Error> {
+        Err(Error::query(format!(
+            "there are no light client cells on chain {}",
+            self.id()
+        )))
+    }
+
+    /// Query how far the event monitor has scanned the chain and the transactions sent
+    /// and not resolved yet, which are only tracked on chains persisting the progress of
+    /// the relaying.
+    fn query_relay_status(&self) -> Result<RelayStatus, Error> {
+        Err(Error::query(format!(
+            "relay status is not available on chain {}",
+            self.id()
+        )))
+    }
+
+    /// Query the outpoints of the contract cells resolved from the type args of the
+    /// configuration, by contract. Chains without contract cells have none.
+    fn query_contract_outpoints(&self) -> Result<BTreeMap<String, String>, Error> {
+        Ok(BTreeMap::new())
+    }
+
     // Keyring
 
     /// Returns the chain's keybase
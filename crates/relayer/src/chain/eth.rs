@@ -21,11 +21,11 @@ use ibc_relayer_types::{
         ics24_host::identifier::{ChainId, ChannelId, ConnectionId, PortId},
     },
     signer::Signer,
+    timestamp::Timestamp,
     Height as ICSHeight,
 };
 use semver::Version;
 use std::sync::Arc;
-use std::thread;
 use tendermint_rpc::endpoint::broadcast::tx_sync::Response;
 use tokio::runtime::Runtime as TokioRuntime;
 
@@ -35,7 +35,7 @@ use crate::keyring::{KeyRing, Secp256k1KeyPair};
 use crate::light_client::LightClient;
 use crate::{
     account::Balance,
-    chain::endpoint::{ChainEndpoint, ChainStatus, HealthCheck},
+    chain::endpoint::{ChainCapabilities, ChainEndpoint, ChainStatus, HealthCheck},
     client_state::{AnyClientState, IdentifiedAnyClientState},
     config::eth::EthChainConfig,
     config::ChainConfig,
@@ -45,12 +45,13 @@ use crate::{
     event::IbcEventWithHeight,
     light_client::eth::LightClient as EthLightClient,
     misbehaviour::MisbehaviourEvidence,
+    util::panic::spawn_chain_thread,
 };
 
 use super::requests::{CrossChainQueryRequest, QueryConsensusStateHeightsRequest};
 use super::tracking::TrackedMsgs;
 use super::{
-    client::ClientSettings,
+    client::{ClientSettings, DerivedClientParams},
     requests::{
         IncludeProof, QueryChannelClientStateRequest, QueryChannelRequest, QueryChannelsRequest,
         QueryClientConnectionsRequest, QueryClientStateRequest, QueryClientStatesRequest,
@@ -107,6 +108,19 @@ impl ChainEndpoint for EthChain {
         Ok(HealthCheck::Healthy)
     }
 
+    fn capabilities(&self) -> ChainCapabilities {
+        // the chain is only the source of the headers of the light client on CKB
+        ChainCapabilities::default()
+    }
+
+    fn query_client_params(&self) -> Result<DerivedClientParams, Error> {
+        let forks = self.light_client.fork_schedule()?;
+        Ok(DerivedClientParams {
+            forks,
+            ..Default::default()
+        })
+    }
+
     fn keybase(&self) -> &KeyRing<Self::SigningKeyPair> {
         &self.keybase
     }
@@ -180,7 +194,12 @@ impl ChainEndpoint for EthChain {
     }
 
     fn query_application_status(&self) -> Result<ChainStatus, Error> {
-        todo!()
+        let slot = self.light_client.finalized_slot();
+        let height = ICSHeight::new(0, slot).map_err(Error::ics02)?;
+        Ok(ChainStatus {
+            height,
+            timestamp: Timestamp::now(),
+        })
     }
 
     fn query_clients(
@@ -467,7 +486,9 @@ impl EthChain {
         )
         .map_err(Error::event_monitor)?;
 
-        thread::spawn(move || event_monitor.run());
+        spawn_chain_thread(self.config.id.clone(), "event_monitor", move || {
+            event_monitor.run()
+        });
 
         Ok(monitor_tx)
     }
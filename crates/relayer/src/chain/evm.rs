@@ -0,0 +1,718 @@
+use std::sync::Arc;
+
+use eth_light_client_in_ckb_prover::Receipts;
+use eth_light_client_in_ckb_verification::trie;
+use ethers::{
+    prelude::{k256::ecdsa::SigningKey, SignerMiddleware},
+    providers::{Middleware, Provider, Ws},
+    signers::{Signer as _, Wallet},
+    types::{TransactionReceipt, TransactionRequest, TxHash},
+    utils::{rlp, rlp::Encodable},
+};
+use ibc_proto::google::protobuf::Any;
+use ibc_relayer_types::{
+    applications::ics31_icq::response::CrossChainQueryResponse,
+    clients::ics07_axon::{
+        client_state::ClientState as AxonClientState,
+        consensus_state::ConsensusState as AxonConsensusState, header::Header as AxonHeader,
+    },
+    core::{
+        ics02_client::{error::Error as ClientError, events::UpdateClient, msgs::update_client},
+        ics03_connection::connection::{ConnectionEnd, IdentifiedConnectionEnd},
+        ics04_channel::{
+            channel::{ChannelEnd, IdentifiedChannelEnd},
+            packet::{PacketMsgType, Sequence},
+        },
+        ics23_commitment::{commitment::CommitmentPrefix, merkle::MerkleProof},
+        ics24_host::identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId},
+    },
+    proofs::Proofs,
+    signer::Signer,
+    timestamp::Timestamp,
+    tx_msg::Msg,
+    Height,
+};
+use tendermint_rpc::endpoint::broadcast::tx_sync::Response;
+use tokio::runtime::Runtime as TokioRuntime;
+use tracing::warn;
+
+use crate::{
+    account::Balance,
+    audit::ConfigDrift,
+    client_state::{AnyClientState, IdentifiedAnyClientState},
+    config::{evm::EvmChainConfig, ChainConfig},
+    connection::ConnectionMsgType,
+    consensus_state::AnyConsensusState,
+    denom::DenomTrace,
+    error::Error,
+    event::{monitor::TxMonitorCmd, IbcEventWithHeight},
+    keyring::{KeyRing, Secp256k1KeyPair},
+    misbehaviour::MisbehaviourEvidence,
+    util::panic::spawn_chain_thread,
+};
+
+use super::{
+    axon::{
+        contract::OwnableIBCHandler,
+        handler::{message_event, IbcHandler, ProofTxHashes},
+        monitor::AxonEventMonitor,
+    },
+    client::ClientSettings,
+    cosmos::encode::key_pair_to_signer,
+    endpoint::{ChainCapabilities, ChainEndpoint, ChainStatus, HealthCheck},
+    handle::{CacheTxHashStatus, Subscription},
+    requests::{
+        CrossChainQueryRequest, IncludeProof, QueryChannelClientStateRequest, QueryChannelRequest,
+        QueryChannelsRequest, QueryClientConnectionsRequest, QueryClientStateRequest,
+        QueryClientStatesRequest, QueryConnectionChannelsRequest, QueryConnectionRequest,
+        QueryConnectionsRequest, QueryConsensusStateHeightsRequest, QueryConsensusStateRequest,
+        QueryHostConsensusStateRequest, QueryNextSequenceReceiveRequest,
+        QueryPacketAcknowledgementRequest, QueryPacketAcknowledgementsRequest,
+        QueryPacketCommitmentRequest, QueryPacketCommitmentsRequest, QueryPacketEventDataRequest,
+        QueryPacketReceiptRequest, QueryTxRequest, QueryUnreceivedAcksRequest,
+        QueryUnreceivedPacketsRequest, QueryUpgradedClientStateRequest,
+        QueryUpgradedConsensusStateRequest,
+    },
+    tracking::TrackedMsgs,
+};
+
+type ContractProvider = SignerMiddleware<Provider<Ws>, Wallet<SigningKey>>;
+type Contract = OwnableIBCHandler<ContractProvider>;
+
+/// An EVM chain on which the IBC handler contract is deployed.
+///
+/// The handler has the same interface as the one deployed on Axon, so its bindings,
+/// queries, messages and event monitor are shared with
+/// [`AxonChain`](super::axon::AxonChain). Unlike on
+/// Axon, the proofs only cover the inclusion of the transaction receipts into their
+/// blocks, the blocks themselves are not verified.
+pub struct EvmChain {
+    rt: Arc<TokioRuntime>,
+    config: EvmChainConfig,
+    tx_monitor_cmd: Option<TxMonitorCmd>,
+    handler: IbcHandler<ContractProvider>,
+    client: Arc<ContractProvider>,
+    keybase: KeyRing<Secp256k1KeyPair>,
+    tx_hashes: ProofTxHashes,
+}
+
+impl ChainEndpoint for EvmChain {
+    type LightBlock = ChainId;
+    type Header = AxonHeader;
+    type ConsensusState = AxonConsensusState;
+    type ClientState = AxonClientState;
+    type SigningKeyPair = Secp256k1KeyPair;
+
+    fn config(&self) -> ChainConfig {
+        ChainConfig::Evm(self.config.clone())
+    }
+
+    fn bootstrap(config: ChainConfig, rt: Arc<TokioRuntime>) -> Result<Self, Error> {
+        let config: EvmChainConfig = config.try_into()?;
+        let keybase = KeyRing::new_secp256k1(Default::default(), "evm", &config.id)
+            .map_err(Error::key_base)?;
+
+        let url = config.websocket_addr.clone();
+        let provider = rt
+            .block_on(Provider::<Ws>::connect(url.to_string()))
+            .map_err(|_| Error::web_socket(url.into()))?;
+        // transactions are signed with the chain id of the node to be replay protected
+        let chain_id = rt
+            .block_on(provider.get_chainid())
+            .map_err(|e| Error::rpc_response(e.to_string()))?;
+        let key_entry = keybase.get_key(&config.key_name).map_err(Error::key_base)?;
+        let wallet = key_entry
+            .into_ether_wallet()
+            .with_chain_id(chain_id.as_u64());
+        let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+        let contract = Contract::new(config.contract_address, Arc::clone(&client));
+        let handler = IbcHandler::new(rt.clone(), contract);
+
+        Ok(Self {
+            rt,
+            config,
+            tx_monitor_cmd: None,
+            handler,
+            client,
+            keybase,
+            tx_hashes: ProofTxHashes::default(),
+        })
+    }
+
+    fn shutdown(self) -> Result<(), Error> {
+        if let Some(tx_monitor_cmd) = self.tx_monitor_cmd {
+            tx_monitor_cmd.shutdown().map_err(Error::event_monitor)?;
+        }
+        tracing::debug!("runtime of evm chain endpoint shutdown");
+        Ok(())
+    }
+
+    fn health_check(&self) -> Result<HealthCheck, Error> {
+        // the node must be reachable and on the chain the transactions are signed for
+        let chain_id = match self.rt.block_on(self.client.get_chainid()) {
+            Ok(chain_id) => chain_id.as_u64(),
+            Err(e) => {
+                let e = Error::rpc_response(e.to_string());
+                return Ok(HealthCheck::Unhealthy(Box::new(e)));
+            }
+        };
+        let signer_chain_id = self.client.signer().chain_id();
+        if chain_id != signer_chain_id {
+            let e = Error::other_error(format!(
+                "the node is on chain {chain_id} while the transactions are signed for chain {signer_chain_id}"
+            ));
+            return Ok(HealthCheck::Unhealthy(Box::new(e)));
+        }
+        Ok(HealthCheck::Healthy)
+    }
+
+    fn capabilities(&self) -> ChainCapabilities {
+        // the IBC contracts have no fee middleware, queries or upgrades
+        ChainCapabilities {
+            ordered_channels: true,
+            proofs: true,
+            ..Default::default()
+        }
+    }
+
+    fn subscribe(&mut self) -> Result<Subscription, Error> {
+        let tx_monitor_cmd = match &self.tx_monitor_cmd {
+            Some(tx_monitor_cmd) => tx_monitor_cmd,
+            None => {
+                let tx_monitor_cmd = self.init_event_monitor()?;
+                self.tx_monitor_cmd = Some(tx_monitor_cmd);
+                self.tx_monitor_cmd.as_ref().unwrap()
+            }
+        };
+
+        let subscription = tx_monitor_cmd.subscribe().map_err(Error::event_monitor)?;
+        Ok(subscription)
+    }
+
+    fn audit_config(&self) -> Result<Vec<ConfigDrift>, Error> {
+        let mut contracts = vec![("contract_address".to_owned(), self.config.contract_address)];
+        contracts.extend(self.config.client_contract_addresses.iter().map(
+            |(type_url, address)| {
+                (
+                    format!("client_contract_addresses.\"{type_url}\""),
+                    *address,
+                )
+            },
+        ));
+
+        let mut drifts = vec![];
+        for (field, address) in contracts {
+            let code = self
+                .rt
+                .block_on(self.client.get_code(address, None))
+                .map_err(|e| Error::rpc_response(e.to_string()))?;
+            if code.is_empty() {
+                drifts.push(ConfigDrift::new(
+                    field,
+                    format!("{address:?}"),
+                    None,
+                    "no contract is deployed at this address, set it to the address of the redeployed contract",
+                ));
+            }
+        }
+        Ok(drifts)
+    }
+
+    fn keybase(&self) -> &KeyRing<Self::SigningKeyPair> {
+        &self.keybase
+    }
+
+    fn keybase_mut(&mut self) -> &mut KeyRing<Self::SigningKeyPair> {
+        &mut self.keybase
+    }
+
+    fn get_signer(&self) -> Result<Signer, Error> {
+        let key_entry = self
+            .keybase()
+            .get_key(&self.config.key_name)
+            .map_err(Error::key_base)?;
+        let signer = key_pair_to_signer(&key_entry)?;
+        Ok(signer)
+    }
+
+    fn ibc_version(&self) -> Result<Option<semver::Version>, Error> {
+        Ok(None)
+    }
+
+    fn send_messages_and_wait_commit(
+        &mut self,
+        tracked_msgs: TrackedMsgs,
+    ) -> Result<Vec<IbcEventWithHeight>, Error> {
+        tracked_msgs
+            .msgs
+            .into_iter()
+            .map(|msg| self.send_message(msg))
+            .collect()
+    }
+
+    fn send_messages_and_wait_check_tx(
+        &mut self,
+        _tracked_msgs: TrackedMsgs,
+    ) -> Result<Vec<Response>, Error> {
+        Err(Error::other_error(
+            "evm chain does not support submitting transactions without waiting for them"
+                .to_owned(),
+        ))
+    }
+
+    fn verify_header(
+        &mut self,
+        _trusted: Height,
+        _target: Height,
+        _client_state: &AnyClientState,
+    ) -> Result<Self::LightBlock, Error> {
+        Err(Error::other_error(
+            "evm chain has no light client to verify headers".to_owned(),
+        ))
+    }
+
+    fn check_misbehaviour(
+        &mut self,
+        _update: &UpdateClient,
+        _client_state: &AnyClientState,
+    ) -> Result<Option<MisbehaviourEvidence>, Error> {
+        Ok(None)
+    }
+
+    fn query_balance(&self, key_name: Option<&str>, denom: Option<&str>) -> Result<Balance, Error> {
+        let key_name = key_name.unwrap_or(&self.config.key_name);
+        let address = self
+            .keybase()
+            .get_key(key_name)
+            .map_err(Error::key_base)?
+            .into_ether_wallet()
+            .address();
+        let amount = self
+            .rt
+            .block_on(self.client.get_balance(address, None))
+            .map_err(|e| Error::rpc_response(e.to_string()))?;
+        Ok(Balance {
+            amount: amount.to_string(),
+            denom: denom.unwrap_or("wei").to_owned(),
+        })
+    }
+
+    fn query_all_balances(&self, key_name: Option<&str>) -> Result<Vec<Balance>, Error> {
+        Ok(vec![self.query_balance(key_name, None)?])
+    }
+
+    fn query_denom_trace(&self, hash: String) -> Result<DenomTrace, Error> {
+        Err(Error::other_error(format!(
+            "evm chain does not support denomination traces, hash {hash}"
+        )))
+    }
+
+    fn query_commitment_prefix(&self) -> Result<CommitmentPrefix, Error> {
+        CommitmentPrefix::try_from(self.config.store_prefix.as_bytes().to_vec())
+            .map_err(|_| Error::ics02(ClientError::empty_prefix()))
+    }
+
+    fn query_application_status(&self) -> Result<ChainStatus, Error> {
+        // same as on Axon, the heights of the proofs are not checked by the counterparty
+        let max_height = Height::new(u64::MAX, u64::MAX).map_err(Error::ics02)?;
+        Ok(ChainStatus {
+            height: max_height,
+            timestamp: Timestamp::now(),
+        })
+    }
+
+    fn query_clients(
+        &self,
+        _request: QueryClientStatesRequest,
+    ) -> Result<Vec<IdentifiedAnyClientState>, Error> {
+        // the client states are returned by the contract without their identifiers
+        warn!("evm query_clients() not support");
+        Ok(vec![])
+    }
+
+    fn query_client_state(
+        &self,
+        request: QueryClientStateRequest,
+        _include_proof: IncludeProof,
+    ) -> Result<(AnyClientState, Option<MerkleProof>), Error> {
+        self.handler.query_client_state(request)
+    }
+
+    fn query_consensus_state(
+        &self,
+        request: QueryConsensusStateRequest,
+        _include_proof: IncludeProof,
+    ) -> Result<(AnyConsensusState, Option<MerkleProof>), Error> {
+        self.handler.query_consensus_state(request)
+    }
+
+    fn query_consensus_state_heights(
+        &self,
+        request: QueryConsensusStateHeightsRequest,
+    ) -> Result<Vec<Height>, Error> {
+        self.handler.query_consensus_state_heights(request)
+    }
+
+    fn query_upgraded_client_state(
+        &self,
+        _request: QueryUpgradedClientStateRequest,
+    ) -> Result<(AnyClientState, MerkleProof), Error> {
+        Err(Error::empty_upgraded_client_state())
+    }
+
+    fn query_upgraded_consensus_state(
+        &self,
+        _request: QueryUpgradedConsensusStateRequest,
+    ) -> Result<(AnyConsensusState, MerkleProof), Error> {
+        Err(Error::empty_upgraded_client_state())
+    }
+
+    fn query_connections(
+        &self,
+        _request: QueryConnectionsRequest,
+    ) -> Result<Vec<IdentifiedConnectionEnd>, Error> {
+        self.handler.query_connections()
+    }
+
+    fn query_client_connections(
+        &self,
+        request: QueryClientConnectionsRequest,
+    ) -> Result<Vec<ConnectionId>, Error> {
+        self.handler.query_client_connections(request)
+    }
+
+    fn query_connection(
+        &self,
+        request: QueryConnectionRequest,
+        _include_proof: IncludeProof,
+    ) -> Result<(ConnectionEnd, Option<MerkleProof>), Error> {
+        self.handler.query_connection(request)
+    }
+
+    fn query_connection_channels(
+        &self,
+        request: QueryConnectionChannelsRequest,
+    ) -> Result<Vec<IdentifiedChannelEnd>, Error> {
+        self.handler.query_connection_channels(request)
+    }
+
+    fn query_channels(
+        &self,
+        _request: QueryChannelsRequest,
+    ) -> Result<Vec<IdentifiedChannelEnd>, Error> {
+        self.handler.query_channels()
+    }
+
+    fn query_channel(
+        &self,
+        request: QueryChannelRequest,
+        _include_proof: IncludeProof,
+    ) -> Result<(ChannelEnd, Option<MerkleProof>), Error> {
+        self.handler.query_channel(request)
+    }
+
+    fn query_channel_client_state(
+        &self,
+        request: QueryChannelClientStateRequest,
+    ) -> Result<Option<IdentifiedAnyClientState>, Error> {
+        self.handler.query_channel_client_state(request)
+    }
+
+    fn query_packet_commitment(
+        &self,
+        request: QueryPacketCommitmentRequest,
+        _include_proof: IncludeProof,
+    ) -> Result<(Vec<u8>, Option<MerkleProof>), Error> {
+        self.handler.query_packet_commitment(request)
+    }
+
+    fn query_packet_commitments(
+        &self,
+        request: QueryPacketCommitmentsRequest,
+    ) -> Result<(Vec<Sequence>, Height), Error> {
+        self.handler.query_packet_commitments(request)
+    }
+
+    fn query_packet_receipt(
+        &self,
+        request: QueryPacketReceiptRequest,
+        _include_proof: IncludeProof,
+    ) -> Result<(Vec<u8>, Option<MerkleProof>), Error> {
+        self.handler.query_packet_receipt(request)
+    }
+
+    fn query_unreceived_packets(
+        &self,
+        request: QueryUnreceivedPacketsRequest,
+    ) -> Result<Vec<Sequence>, Error> {
+        self.handler.query_unreceived_packets(request)
+    }
+
+    fn query_packet_acknowledgement(
+        &self,
+        request: QueryPacketAcknowledgementRequest,
+        _include_proof: IncludeProof,
+    ) -> Result<(Vec<u8>, Option<MerkleProof>), Error> {
+        self.handler.query_packet_acknowledgement(request)
+    }
+
+    fn query_packet_acknowledgements(
+        &self,
+        request: QueryPacketAcknowledgementsRequest,
+    ) -> Result<(Vec<Sequence>, Height), Error> {
+        self.handler.query_packet_acknowledgements(request)
+    }
+
+    fn query_unreceived_acknowledgements(
+        &self,
+        request: QueryUnreceivedAcksRequest,
+    ) -> Result<Vec<Sequence>, Error> {
+        self.handler.query_unreceived_acknowledgements(request)
+    }
+
+    fn query_next_sequence_receive(
+        &self,
+        request: QueryNextSequenceReceiveRequest,
+        _include_proof: IncludeProof,
+    ) -> Result<(Sequence, Option<MerkleProof>), Error> {
+        self.handler.query_next_sequence_receive(request)
+    }
+
+    fn query_txs(&self, _request: QueryTxRequest) -> Result<Vec<IbcEventWithHeight>, Error> {
+        warn!("evm query_txs() not support");
+        Ok(vec![])
+    }
+
+    fn query_packet_events(
+        &self,
+        _request: QueryPacketEventDataRequest,
+    ) -> Result<Vec<IbcEventWithHeight>, Error> {
+        warn!("evm query_packet_events() not support");
+        Ok(vec![])
+    }
+
+    fn query_host_consensus_state(
+        &self,
+        _request: QueryHostConsensusStateRequest,
+    ) -> Result<Self::ConsensusState, Error> {
+        Err(Error::other_error(
+            "evm chain has no consensus state to be tracked by a client".to_owned(),
+        ))
+    }
+
+    fn build_client_state(
+        &self,
+        _height: Height,
+        _settings: ClientSettings,
+    ) -> Result<Self::ClientState, Error> {
+        Err(Error::other_error(
+            "evm chain has no client state to be tracked by a client".to_owned(),
+        ))
+    }
+
+    fn build_consensus_state(
+        &self,
+        _light_block: Self::LightBlock,
+    ) -> Result<Self::ConsensusState, Error> {
+        Err(Error::other_error(
+            "evm chain has no consensus state to be tracked by a client".to_owned(),
+        ))
+    }
+
+    fn build_header(
+        &mut self,
+        _trusted_height: Height,
+        _target_height: Height,
+        _client_state: &AnyClientState,
+    ) -> Result<(Self::Header, Vec<Self::Header>), Error> {
+        Err(Error::other_error(
+            "evm chain has no light client to build headers".to_owned(),
+        ))
+    }
+
+    fn maybe_register_counterparty_payee(
+        &mut self,
+        _channel_id: &ChannelId,
+        _port_id: &PortId,
+        _counterparty_payee: &Signer,
+    ) -> Result<(), Error> {
+        warn!("evm maybe_register_counterparty_payee() not support");
+        Ok(())
+    }
+
+    fn cross_chain_query(
+        &self,
+        _requests: Vec<CrossChainQueryRequest>,
+    ) -> Result<Vec<CrossChainQueryResponse>, Error> {
+        warn!("evm cross_chain_query() not support");
+        Ok(vec![])
+    }
+
+    fn query_incentivized_packet(
+        &self,
+        _request: ibc_proto::ibc::apps::fee::v1::QueryIncentivizedPacketRequest,
+    ) -> Result<ibc_proto::ibc::apps::fee::v1::QueryIncentivizedPacketResponse, Error> {
+        Err(Error::other_error(
+            "evm chain does not support incentivized packets".to_owned(),
+        ))
+    }
+
+    fn build_connection_proofs_and_client_state(
+        &self,
+        message_type: ConnectionMsgType,
+        connection_id: &ConnectionId,
+        _client_id: &ClientId,
+        _height: Height,
+    ) -> Result<(Option<AnyClientState>, Proofs), Error> {
+        let proofs = self
+            .tx_hashes
+            .connection_proofs(message_type, connection_id, |tx_hash| {
+                self.get_proofs(tx_hash)
+            })?;
+        Ok((None, proofs))
+    }
+
+    fn build_channel_proofs(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        _height: Height,
+    ) -> Result<Proofs, Error> {
+        self.tx_hashes
+            .channel_proofs(port_id, channel_id, |tx_hash| self.get_proofs(tx_hash))
+    }
+
+    fn build_packet_proofs(
+        &self,
+        packet_type: PacketMsgType,
+        port_id: PortId,
+        channel_id: ChannelId,
+        sequence: Sequence,
+        _height: Height,
+    ) -> Result<Proofs, Error> {
+        self.tx_hashes
+            .packet_proofs(packet_type, &port_id, &channel_id, sequence, |tx_hash| {
+                self.get_proofs(tx_hash)
+            })
+    }
+
+    fn cache_ics_tx_hash<T: Into<[u8; 32]>>(
+        &mut self,
+        cached_status: CacheTxHashStatus,
+        tx_hash: T,
+    ) -> Result<(), Error> {
+        let hash: [u8; 32] = tx_hash.into();
+        self.tx_hashes.cache(cached_status, TxHash::from(hash));
+        Ok(())
+    }
+}
+
+impl EvmChain {
+    fn init_event_monitor(&mut self) -> Result<TxMonitorCmd, Error> {
+        crate::time!("evm_init_event_monitor");
+        // there is no light client to emit the new blocks, the sender is dropped so
+        // that the monitor only relays the events of the contract
+        let (_, header_receiver) = tokio::sync::mpsc::channel(1);
+        let (event_monitor, monitor_tx) = AxonEventMonitor::new(
+            self.config.id.clone(),
+            self.config.websocket_addr.clone(),
+            self.config.contract_address,
+            header_receiver,
+            self.rt.clone(),
+        )
+        .map_err(Error::event_monitor)?;
+        spawn_chain_thread(self.config.id.clone(), "event_monitor", move || {
+            event_monitor.run()
+        });
+        Ok(monitor_tx)
+    }
+
+    /// Prove the inclusion of the receipt of the transaction into its block.
+    fn get_proofs(&self, tx_hash: &TxHash) -> Result<Proofs, Error> {
+        let receipt = self
+            .rt
+            .block_on(self.client.get_transaction_receipt(*tx_hash))
+            .map_err(|e| Error::rpc_response(e.to_string()))?
+            .ok_or_else(|| {
+                Error::other_error(format!(
+                    "can't find transaction receipt with hash {}",
+                    hex::encode(tx_hash)
+                ))
+            })?;
+        let block_number = receipt.block_number.ok_or_else(|| {
+            Error::other_error(format!(
+                "transaction {} is still pending",
+                hex::encode(tx_hash)
+            ))
+        })?;
+
+        let block = self
+            .rt
+            .block_on(self.client.get_block(block_number))
+            .map_err(|e| Error::rpc_response(e.to_string()))?
+            .ok_or_else(|| Error::other_error(format!("can't find block {block_number}")))?;
+        let receipts: Receipts = self
+            .rt
+            .block_on(self.client.get_block_receipts(block_number))
+            .map_err(|e| Error::rpc_response(e.to_string()))?
+            .into();
+        let receipt_proof = receipts.generate_proof(receipt.transaction_index.as_usize());
+
+        let key = rlp::encode(&receipt.transaction_index.as_u64());
+        if !trie::verify_proof(
+            &receipt_proof,
+            block.receipts_root.as_bytes(),
+            &key,
+            &receipt.rlp_bytes(),
+        ) {
+            return Err(Error::rpc_response("unverified receipts mpt".to_owned()));
+        }
+
+        let object_proof = rlp::RlpStream::new()
+            .append(&receipt)
+            .append_list::<Vec<_>, Vec<_>>(&receipt_proof)
+            .append(&block.receipts_root)
+            .as_raw()
+            .to_owned();
+        let height = Height::new(u64::MAX, u64::MAX).unwrap();
+        Proofs::new(
+            object_proof.try_into().map_err(Error::malformed_proof)?,
+            None,
+            None,
+            None,
+            height,
+        )
+        .map_err(Error::malformed_proof)
+    }
+
+    fn send_message(&mut self, message: Any) -> Result<IbcEventWithHeight, Error> {
+        let type_url = message.type_url.clone();
+        let tx_receipt = if type_url == update_client::TYPE_URL {
+            let msg = update_client::MsgUpdateClient::from_any(message)
+                .map_err(|e| Error::other_error(format!("fail to decode MsgUpdateClient {e}")))?;
+            let to = self
+                .config
+                .client_contract_addresses
+                .get(&msg.header.type_url)
+                .ok_or_else(|| {
+                    Error::other_error(format!(
+                        "no client contract configured for header {}",
+                        msg.header.type_url
+                    ))
+                })?;
+            let tx = TransactionRequest::new().to(*to).data(msg.header.value);
+            let tx_receipt: eyre::Result<Option<TransactionReceipt>> = self
+                .rt
+                .block_on(async { Ok(self.client.send_transaction(tx, None).await?.await?) });
+            tx_receipt.map_err(convert_err)?
+        } else {
+            self.handler.send_message(message)?
+        };
+        message_event(&type_url, tx_receipt)
+    }
+}
+
+fn convert_err<T: ToString>(err: T) -> Error {
+    Error::other_error(err.to_string())
+}
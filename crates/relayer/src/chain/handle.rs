@@ -1,3 +1,4 @@
+use alloc::collections::BTreeMap;
 use alloc::sync::Arc;
 use core::fmt::{self, Debug, Display};
 
@@ -29,6 +30,7 @@ use ibc_relayer_types::{
 
 use crate::{
     account::Balance,
+    audit::ConfigDrift,
     client_state::{AnyClientState, IdentifiedAnyClientState},
     config::ChainConfig,
     connection::ConnectionMsgType,
@@ -42,11 +44,19 @@ use crate::{
     keyring::AnySigningKeyPair,
     light_client::AnyHeader,
     misbehaviour::MisbehaviourEvidence,
+    progress::RelayStatus,
 };
 
 use super::{
-    client::ClientSettings,
-    endpoint::{ChainStatus, HealthCheck},
+    ckb::{client_cells::ClientCells, prune::ClientsPruning},
+    ckb4ibc::{
+        cells::{IbcCell, IbcCellKind},
+        footprint::StorageFootprint,
+        history::IbcStateAt,
+        packet_size::OversizedPacket,
+    },
+    client::{ClientSettings, DerivedClientParams},
+    endpoint::{ChainCapabilities, ChainStatus, HealthCheck},
     requests::*,
     tracking::TrackedMsgs,
 };
@@ -166,6 +176,10 @@ pub enum ChainRequest {
         reply_to: ReplyTo<Option<semver::Version>>,
     },
 
+    Capabilities {
+        reply_to: ReplyTo<ChainCapabilities>,
+    },
+
     QueryBalance {
         key_name: Option<String>,
         denom: Option<String>,
@@ -394,6 +408,53 @@ pub enum ChainRequest {
         tx_hash: [u8; 32],
         reply_to: ReplyTo<()>,
     },
+
+    InvalidateCache {
+        reply_to: ReplyTo<()>,
+    },
+
+    QueryStorageFootprint {
+        reply_to: ReplyTo<StorageFootprint>,
+    },
+
+    QueryOversizedPackets {
+        reply_to: ReplyTo<Vec<OversizedPacket>>,
+    },
+
+    QueryIbcStateAt {
+        block_number: u64,
+        reply_to: ReplyTo<IbcStateAt>,
+    },
+
+    QueryIbcCells {
+        kind: Option<IbcCellKind>,
+        reply_to: ReplyTo<Vec<IbcCell>>,
+    },
+
+    AuditConfig {
+        reply_to: ReplyTo<Vec<ConfigDrift>>,
+    },
+
+    QueryClientParams {
+        reply_to: ReplyTo<DerivedClientParams>,
+    },
+
+    PruneObsoleteClients {
+        dry_run: bool,
+        reply_to: ReplyTo<ClientsPruning>,
+    },
+
+    QueryLightClientCells {
+        reply_to: ReplyTo<ClientCells>,
+    },
+
+    QueryRelayStatus {
+        reply_to: ReplyTo<RelayStatus>,
+    },
+
+    QueryContractOutpoints {
+        reply_to: ReplyTo<BTreeMap<String, String>>,
+    },
 }
 
 pub trait ChainHandle: Clone + Display + Send + Sync + Debug + 'static {
@@ -411,6 +472,40 @@ pub trait ChainHandle: Clone + Display + Send + Sync + Debug + 'static {
     /// Subscribe to the events emitted by the chain.
     fn subscribe(&self) -> Result<Subscription, Error>;
 
+    /// Drop the chain-specific states cached by the chain runtime,
+    /// forcing them to be fetched again from the chain.
+    fn invalidate_cache(&self) -> Result<(), Error>;
+
+    /// Query the number and the capacity of the cells holding the IBC states.
+    fn query_storage_footprint(&self) -> Result<StorageFootprint, Error>;
+
+    /// Query the pending packets skipped because of their size.
+    fn query_oversized_packets(&self) -> Result<Vec<OversizedPacket>, Error>;
+
+    /// Query the connections, the channels and the outstanding packets at a past block.
+    fn query_ibc_state_at(&self, block_number: u64) -> Result<IbcStateAt, Error>;
+
+    /// Query the live IBC cells of a kind, or of all the kinds, decoded.
+    fn query_ibc_cells(&self, kind: Option<IbcCellKind>) -> Result<Vec<IbcCell>, Error>;
+
+    /// Compare the chain-specific config fields against the live states of the chain.
+    fn audit_config(&self) -> Result<Vec<ConfigDrift>, Error>;
+
+    /// Derive the parameters of a new client targeting the chain from its on-chain parameters.
+    fn query_client_params(&self) -> Result<DerivedClientParams, Error>;
+
+    /// Consume the light client cells of the abandoned deployments, or only list them.
+    fn prune_obsolete_clients(&self, dry_run: bool) -> Result<ClientsPruning, Error>;
+
+    /// Query the decoded cells of the multi-client set of the Ethereum light client.
+    fn query_light_client_cells(&self) -> Result<ClientCells, Error>;
+
+    /// Query the progress of the event monitor and the transactions not resolved yet.
+    fn query_relay_status(&self) -> Result<RelayStatus, Error>;
+
+    /// Query the outpoints of the contract cells resolved from the configuration.
+    fn query_contract_outpoints(&self) -> Result<BTreeMap<String, String>, Error>;
+
     /// Send the given `msgs` to the chain, packaged as one or more transactions,
     /// and return the list of events emitted by the chain after the transaction was committed.
     fn send_messages_and_wait_commit(
@@ -438,6 +533,9 @@ pub trait ChainHandle: Clone + Display + Send + Sync + Debug + 'static {
     /// Return the version of the IBC protocol that this chain is running, if known.
     fn ibc_version(&self) -> Result<Option<semver::Version>, Error>;
 
+    /// Features of IBC the chain supports, which are checked before relying on them
+    fn capabilities(&self) -> Result<ChainCapabilities, Error>;
+
     /// Query the balance of the given account for the given denom.
     /// If no account is given, behavior must be specified, e.g. retrieve it from configuration file.
     /// If no denom is given, behavior must be specified, e.g. using the denom used to pay tx fees
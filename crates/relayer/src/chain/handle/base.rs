@@ -1,3 +1,4 @@
+use alloc::collections::BTreeMap;
 use core::fmt::{Debug, Display, Error as FmtError, Formatter};
 
 use crossbeam_channel as channel;
@@ -26,7 +27,20 @@ use ibc_relayer_types::{
 
 use crate::{
     account::Balance,
-    chain::{client::ClientSettings, endpoint::ChainStatus, requests::*, tracking::TrackedMsgs},
+    audit::ConfigDrift,
+    chain::{
+        ckb::{client_cells::ClientCells, prune::ClientsPruning},
+        ckb4ibc::{
+            cells::{IbcCell, IbcCellKind},
+            footprint::StorageFootprint,
+            history::IbcStateAt,
+            packet_size::OversizedPacket,
+        },
+        client::{ClientSettings, DerivedClientParams},
+        endpoint::{ChainCapabilities, ChainStatus},
+        requests::*,
+        tracking::TrackedMsgs,
+    },
     client_state::{AnyClientState, IdentifiedAnyClientState},
     config::ChainConfig,
     connection::ConnectionMsgType,
@@ -37,6 +51,7 @@ use crate::{
     keyring::AnySigningKeyPair,
     light_client::AnyHeader,
     misbehaviour::MisbehaviourEvidence,
+    progress::RelayStatus,
 };
 
 use super::{
@@ -107,6 +122,53 @@ impl ChainHandle for BaseChainHandle {
         self.send(|reply_to| ChainRequest::Subscribe { reply_to })
     }
 
+    fn invalidate_cache(&self) -> Result<(), Error> {
+        self.send(|reply_to| ChainRequest::InvalidateCache { reply_to })
+    }
+
+    fn query_storage_footprint(&self) -> Result<StorageFootprint, Error> {
+        self.send(|reply_to| ChainRequest::QueryStorageFootprint { reply_to })
+    }
+
+    fn query_oversized_packets(&self) -> Result<Vec<OversizedPacket>, Error> {
+        self.send(|reply_to| ChainRequest::QueryOversizedPackets { reply_to })
+    }
+
+    fn query_ibc_state_at(&self, block_number: u64) -> Result<IbcStateAt, Error> {
+        self.send(|reply_to| ChainRequest::QueryIbcStateAt {
+            block_number,
+            reply_to,
+        })
+    }
+
+    fn query_ibc_cells(&self, kind: Option<IbcCellKind>) -> Result<Vec<IbcCell>, Error> {
+        self.send(|reply_to| ChainRequest::QueryIbcCells { kind, reply_to })
+    }
+
+    fn audit_config(&self) -> Result<Vec<ConfigDrift>, Error> {
+        self.send(|reply_to| ChainRequest::AuditConfig { reply_to })
+    }
+
+    fn query_client_params(&self) -> Result<DerivedClientParams, Error> {
+        self.send(|reply_to| ChainRequest::QueryClientParams { reply_to })
+    }
+
+    fn prune_obsolete_clients(&self, dry_run: bool) -> Result<ClientsPruning, Error> {
+        self.send(|reply_to| ChainRequest::PruneObsoleteClients { dry_run, reply_to })
+    }
+
+    fn query_light_client_cells(&self) -> Result<ClientCells, Error> {
+        self.send(|reply_to| ChainRequest::QueryLightClientCells { reply_to })
+    }
+
+    fn query_relay_status(&self) -> Result<RelayStatus, Error> {
+        self.send(|reply_to| ChainRequest::QueryRelayStatus { reply_to })
+    }
+
+    fn query_contract_outpoints(&self) -> Result<BTreeMap<String, String>, Error> {
+        self.send(|reply_to| ChainRequest::QueryContractOutpoints { reply_to })
+    }
+
     fn send_messages_and_wait_commit(
         &self,
         tracked_msgs: TrackedMsgs,
@@ -151,6 +213,10 @@ impl ChainHandle for BaseChainHandle {
         self.send(|reply_to| ChainRequest::IbcVersion { reply_to })
     }
 
+    fn capabilities(&self) -> Result<ChainCapabilities, Error> {
+        self.send(|reply_to| ChainRequest::Capabilities { reply_to })
+    }
+
     fn query_balance(
         &self,
         key_name: Option<String>,
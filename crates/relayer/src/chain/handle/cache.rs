@@ -1,3 +1,4 @@
+use alloc::collections::BTreeMap;
 use core::fmt::{Display, Error as FmtError, Formatter};
 use crossbeam_channel as channel;
 use tracing::Span;
@@ -22,9 +23,16 @@ use ibc_relayer_types::signer::Signer;
 use ibc_relayer_types::Height;
 
 use crate::account::Balance;
+use crate::audit::ConfigDrift;
 use crate::cache::{Cache, CacheStatus};
-use crate::chain::client::ClientSettings;
-use crate::chain::endpoint::{ChainStatus, HealthCheck};
+use crate::chain::ckb::client_cells::ClientCells;
+use crate::chain::ckb::prune::ClientsPruning;
+use crate::chain::ckb4ibc::cells::{IbcCell, IbcCellKind};
+use crate::chain::ckb4ibc::footprint::StorageFootprint;
+use crate::chain::ckb4ibc::history::IbcStateAt;
+use crate::chain::ckb4ibc::packet_size::OversizedPacket;
+use crate::chain::client::{ClientSettings, DerivedClientParams};
+use crate::chain::endpoint::{ChainCapabilities, ChainStatus, HealthCheck};
 use crate::chain::handle::{ChainHandle, ChainRequest, Subscription};
 use crate::chain::requests::*;
 use crate::chain::tracking::TrackedMsgs;
@@ -38,6 +46,7 @@ use crate::event::IbcEventWithHeight;
 use crate::keyring::AnySigningKeyPair;
 use crate::light_client::AnyHeader;
 use crate::misbehaviour::MisbehaviourEvidence;
+use crate::progress::RelayStatus;
 use crate::telemetry;
 
 /// A chain handle with support for caching.
@@ -92,6 +101,50 @@ impl<Handle: ChainHandle> ChainHandle for CachingChainHandle<Handle> {
         self.inner().subscribe()
     }
 
+    fn invalidate_cache(&self) -> Result<(), Error> {
+        self.inner().invalidate_cache()
+    }
+
+    fn query_storage_footprint(&self) -> Result<StorageFootprint, Error> {
+        self.inner().query_storage_footprint()
+    }
+
+    fn query_oversized_packets(&self) -> Result<Vec<OversizedPacket>, Error> {
+        self.inner().query_oversized_packets()
+    }
+
+    fn query_ibc_state_at(&self, block_number: u64) -> Result<IbcStateAt, Error> {
+        self.inner().query_ibc_state_at(block_number)
+    }
+
+    fn query_ibc_cells(&self, kind: Option<IbcCellKind>) -> Result<Vec<IbcCell>, Error> {
+        self.inner().query_ibc_cells(kind)
+    }
+
+    fn audit_config(&self) -> Result<Vec<ConfigDrift>, Error> {
+        self.inner().audit_config()
+    }
+
+    fn query_client_params(&self) -> Result<DerivedClientParams, Error> {
+        self.inner().query_client_params()
+    }
+
+    fn prune_obsolete_clients(&self, dry_run: bool) -> Result<ClientsPruning, Error> {
+        self.inner().prune_obsolete_clients(dry_run)
+    }
+
+    fn query_light_client_cells(&self) -> Result<ClientCells, Error> {
+        self.inner().query_light_client_cells()
+    }
+
+    fn query_relay_status(&self) -> Result<RelayStatus, Error> {
+        self.inner().query_relay_status()
+    }
+
+    fn query_contract_outpoints(&self) -> Result<BTreeMap<String, String>, Error> {
+        self.inner().query_contract_outpoints()
+    }
+
     fn send_messages_and_wait_commit(
         &self,
         tracked_msgs: TrackedMsgs,
@@ -126,6 +179,10 @@ impl<Handle: ChainHandle> ChainHandle for CachingChainHandle<Handle> {
         self.inner().ibc_version()
     }
 
+    fn capabilities(&self) -> Result<ChainCapabilities, Error> {
+        self.inner().capabilities()
+    }
+
     fn query_balance(
         &self,
         key_name: Option<String>,
@@ -1,5 +1,5 @@
 use core::fmt::{Display, Error as FmtError, Formatter};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, RwLock, RwLockReadGuard};
 
 use crossbeam_channel as channel;
@@ -26,8 +26,15 @@ use ibc_relayer_types::signer::Signer;
 use ibc_relayer_types::Height;
 
 use crate::account::Balance;
-use crate::chain::client::ClientSettings;
-use crate::chain::endpoint::{ChainStatus, HealthCheck};
+use crate::audit::ConfigDrift;
+use crate::chain::ckb::client_cells::ClientCells;
+use crate::chain::ckb::prune::ClientsPruning;
+use crate::chain::ckb4ibc::cells::{IbcCell, IbcCellKind};
+use crate::chain::ckb4ibc::footprint::StorageFootprint;
+use crate::chain::ckb4ibc::history::IbcStateAt;
+use crate::chain::ckb4ibc::packet_size::OversizedPacket;
+use crate::chain::client::{ClientSettings, DerivedClientParams};
+use crate::chain::endpoint::{ChainCapabilities, ChainStatus, HealthCheck};
 use crate::chain::handle::{ChainHandle, ChainRequest, Subscription};
 use crate::chain::requests::*;
 use crate::chain::tracking::TrackedMsgs;
@@ -41,6 +48,7 @@ use crate::event::IbcEventWithHeight;
 use crate::keyring::AnySigningKeyPair;
 use crate::light_client::AnyHeader;
 use crate::misbehaviour::MisbehaviourEvidence;
+use crate::progress::RelayStatus;
 use crate::util::lock::LockExt;
 
 #[derive(Debug, Clone)]
@@ -114,6 +122,61 @@ impl<Handle: ChainHandle> ChainHandle for CountingChainHandle<Handle> {
         self.inner().subscribe()
     }
 
+    fn invalidate_cache(&self) -> Result<(), Error> {
+        self.inc_metric("invalidate_cache");
+        self.inner().invalidate_cache()
+    }
+
+    fn query_storage_footprint(&self) -> Result<StorageFootprint, Error> {
+        self.inc_metric("query_storage_footprint");
+        self.inner().query_storage_footprint()
+    }
+
+    fn query_oversized_packets(&self) -> Result<Vec<OversizedPacket>, Error> {
+        self.inc_metric("query_oversized_packets");
+        self.inner().query_oversized_packets()
+    }
+
+    fn query_ibc_state_at(&self, block_number: u64) -> Result<IbcStateAt, Error> {
+        self.inc_metric("query_ibc_state_at");
+        self.inner().query_ibc_state_at(block_number)
+    }
+
+    fn query_ibc_cells(&self, kind: Option<IbcCellKind>) -> Result<Vec<IbcCell>, Error> {
+        self.inc_metric("query_ibc_cells");
+        self.inner().query_ibc_cells(kind)
+    }
+
+    fn audit_config(&self) -> Result<Vec<ConfigDrift>, Error> {
+        self.inc_metric("audit_config");
+        self.inner().audit_config()
+    }
+
+    fn query_client_params(&self) -> Result<DerivedClientParams, Error> {
+        self.inc_metric("query_client_params");
+        self.inner().query_client_params()
+    }
+
+    fn prune_obsolete_clients(&self, dry_run: bool) -> Result<ClientsPruning, Error> {
+        self.inc_metric("prune_obsolete_clients");
+        self.inner().prune_obsolete_clients(dry_run)
+    }
+
+    fn query_light_client_cells(&self) -> Result<ClientCells, Error> {
+        self.inc_metric("query_light_client_cells");
+        self.inner().query_light_client_cells()
+    }
+
+    fn query_relay_status(&self) -> Result<RelayStatus, Error> {
+        self.inc_metric("query_relay_status");
+        self.inner().query_relay_status()
+    }
+
+    fn query_contract_outpoints(&self) -> Result<BTreeMap<String, String>, Error> {
+        self.inc_metric("query_contract_outpoints");
+        self.inner().query_contract_outpoints()
+    }
+
     fn send_messages_and_wait_commit(
         &self,
         tracked_msgs: TrackedMsgs,
@@ -155,6 +218,11 @@ impl<Handle: ChainHandle> ChainHandle for CountingChainHandle<Handle> {
         self.inner().ibc_version()
     }
 
+    fn capabilities(&self) -> Result<ChainCapabilities, Error> {
+        self.inc_metric("capabilities");
+        self.inner().capabilities()
+    }
+
     fn query_balance(
         &self,
         key_name: Option<String>,
@@ -0,0 +1,1150 @@
+//! In-memory CKB chain for the unit tests of the CKB endpoints.
+//!
+//! Every transaction sent to the chain is committed at once in a block of its own,
+//! which consumes the cells of its inputs and creates the cells of its outputs. The
+//! indexer methods search the live cells the same way as the CKB indexer, so that the
+//! query and send paths of the endpoints can run without a CKB node. Transactions
+//! spending cells which are not live, or running a script set to fail, are rejected with
+//! the error of a CKB node.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use ckb_jsonrpc_types::{
+    BlockNumber, BlockView, CellData, CellInfo, CellWithStatus, ChainInfo, DryRunResult,
+    HeaderView, JsonBytes, OutPoint, OutputsValidator, RawTxPool, ResponseFormat, Transaction,
+    TransactionView, TransactionWithStatusResponse, TxPoolInfo, TxStatus,
+};
+use ckb_sdk::rpc::ckb_indexer::{Cell, Pagination, ScriptType, SearchKey, Tip};
+use ckb_types::bytes::Bytes;
+use ckb_types::core::{self, BlockBuilder};
+use ckb_types::{packed, prelude::*, H256};
+
+use super::ckb::prelude::{CellSearcher, CkbReader, CkbWriter, Response as Rpc, TxCompleter};
+use crate::error::Error;
+
+/// Timestamp of the genesis block, in milliseconds
+const GENESIS_TIMESTAMP: u64 = 1_700_000_000_000;
+
+/// Time between two blocks, in milliseconds
+const BLOCK_INTERVAL: u64 = 8_000;
+
+/// Cycles consumed by every script group of a transaction
+const SCRIPT_CYCLES: u64 = 1_000_000;
+
+/// Live cell along with the position of its creation, which orders the search results
+struct MockCell {
+    position: u64,
+    out_point: packed::OutPoint,
+    output: packed::CellOutput,
+    data: Bytes,
+    block_number: u64,
+}
+
+impl MockCell {
+    fn to_cell(&self, with_data: bool) -> Cell {
+        Cell {
+            output: self.output.clone().into(),
+            output_data: with_data.then(|| JsonBytes::from_bytes(self.data.clone())),
+            out_point: self.out_point.clone().into(),
+            block_number: self.block_number.into(),
+            tx_index: 0u32.into(),
+        }
+    }
+
+    fn matches(&self, search_key: &SearchKey) -> bool {
+        let search_script: packed::Script = search_key.script.clone().into();
+        let script = match search_key.script_type {
+            ScriptType::Lock => Some(self.output.lock()),
+            ScriptType::Type => self.output.type_().to_opt(),
+        };
+        script.map_or(false, |script| {
+            script.code_hash().as_slice() == search_script.code_hash().as_slice()
+                && script.hash_type().as_slice() == search_script.hash_type().as_slice()
+                && script
+                    .args()
+                    .raw_data()
+                    .starts_with(&search_script.args().raw_data())
+        })
+    }
+}
+
+struct Ledger {
+    blocks: Vec<core::BlockView>,
+    /// Committed transactions along with the number of their block
+    transactions: HashMap<H256, (core::TransactionView, u64)>,
+    live_cells: Vec<MockCell>,
+    next_position: u64,
+    /// Scripts failing with their exit code whenever they are run
+    failing_scripts: Vec<(packed::Script, i8)>,
+    /// Number of restarts of the indexer, which invalidate the cursors it returned
+    indexer_restarts: u64,
+    /// Number of cell queries served before the indexer restarts
+    indexer_restart_after: Option<u32>,
+}
+
+impl Ledger {
+    fn push_block(&mut self, transactions: Vec<core::TransactionView>) -> u64 {
+        let number = self.blocks.len() as u64;
+        let parent_hash = self
+            .blocks
+            .last()
+            .map(|block| block.hash())
+            .unwrap_or_default();
+        let block = BlockBuilder::default()
+            .number(number.pack())
+            .parent_hash(parent_hash)
+            .timestamp((GENESIS_TIMESTAMP + number * BLOCK_INTERVAL).pack())
+            .transactions(transactions)
+            .build();
+        self.blocks.push(block);
+        number
+    }
+
+    fn live_cell_position(&self, out_point: &packed::OutPoint) -> Option<usize> {
+        self.live_cells
+            .iter()
+            .position(|cell| cell.out_point.as_slice() == out_point.as_slice())
+    }
+
+    /// Positions of the live cells spent by `tx` and the cycles of its scripts, or the
+    /// error of a CKB node failing to resolve or to verify `tx`
+    fn verify(&self, tx: &core::TransactionView) -> Result<(Vec<usize>, u64), Error> {
+        let mut spent = vec![];
+        for input in tx.input_pts_iter() {
+            match self.live_cell_position(&input) {
+                Some(position) => spent.push(position),
+                None => {
+                    return Err(Error::rpc_response(format!(
+                        "TransactionFailedToResolve: Resolve failed Dead(OutPoint(0x{}))",
+                        hex::encode(input.as_slice())
+                    )))
+                }
+            }
+        }
+
+        // the scripts run by the transaction, along with the first cell of their group
+        let input_scripts = spent.iter().enumerate().flat_map(|(index, position)| {
+            let output = &self.live_cells[*position].output;
+            [
+                (Some(output.lock()), format!("Inputs[{index}].Lock")),
+                (output.type_().to_opt(), format!("Inputs[{index}].Type")),
+            ]
+        });
+        let output_scripts = tx
+            .outputs_with_data_iter()
+            .enumerate()
+            .map(|(index, (output, _))| {
+                (output.type_().to_opt(), format!("Outputs[{index}].Type"))
+            });
+        let mut groups: Vec<packed::Script> = vec![];
+        for (script, group) in input_scripts.chain(output_scripts) {
+            let Some(script) = script else {
+                continue;
+            };
+            if groups.iter().any(|s| s.as_slice() == script.as_slice()) {
+                continue;
+            }
+            let failure = self
+                .failing_scripts
+                .iter()
+                .find(|(failing, _)| failing.as_slice() == script.as_slice());
+            if let Some((_, exit_code)) = failure {
+                return Err(Error::rpc_response(format!(
+                    "TransactionFailedToVerify: Verification failed Script(TransactionScriptError {{ source: {group}, cause: ValidationFailure: see error code {exit_code} on page https://nervosnetwork.github.io/ckb-script-error-codes/ }})"
+                )));
+            }
+            groups.push(script);
+        }
+        Ok((spent, SCRIPT_CYCLES * groups.len() as u64))
+    }
+
+    fn tip(&self) -> &core::BlockView {
+        self.blocks
+            .last()
+            .expect("the genesis block is always there")
+    }
+
+    fn transaction(&self, hash: &H256) -> Option<TransactionWithStatusResponse> {
+        let (tx, number) = self.transactions.get(hash)?;
+        let block_hash = self.blocks[*number as usize].hash().unpack();
+        Some(TransactionWithStatusResponse {
+            transaction: Some(ResponseFormat::json(TransactionView::from(tx.clone()))),
+            tx_status: TxStatus::committed(block_hash),
+            cycles: None,
+        })
+    }
+}
+
+/// CKB chain and indexer kept in memory, cloned handles sharing the same chain
+#[derive(Clone)]
+pub struct MockCkb {
+    ledger: Arc<RwLock<Ledger>>,
+}
+
+impl Default for MockCkb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockCkb {
+    /// Chain made of an empty genesis block
+    pub fn new() -> Self {
+        let mut ledger = Ledger {
+            blocks: vec![],
+            transactions: HashMap::new(),
+            live_cells: vec![],
+            next_position: 0,
+            failing_scripts: vec![],
+            indexer_restarts: 0,
+            indexer_restart_after: None,
+        };
+        ledger.push_block(vec![]);
+        Self {
+            ledger: Arc::new(RwLock::new(ledger)),
+        }
+    }
+
+    pub fn tip_block_number(&self) -> u64 {
+        self.ledger.read().unwrap().tip().number()
+    }
+
+    /// Append empty blocks, e.g. to confirm the committed transactions
+    pub fn mine(&self, blocks: u64) {
+        let mut ledger = self.ledger.write().unwrap();
+        for _ in 0..blocks {
+            ledger.push_block(vec![]);
+        }
+    }
+
+    /// Create a live cell out of thin air, such as the cell of a contract or of a
+    /// state which is set up before the test
+    pub fn deploy_cell(&self, output: packed::CellOutput, data: Bytes) -> packed::OutPoint {
+        // the cellbase input of the next block tells apart the deployments of same cells
+        let tx = core::TransactionBuilder::default()
+            .input(packed::CellInput::new_cellbase_input(
+                self.tip_block_number() + 1,
+            ))
+            .output(output)
+            .output_data(data.pack())
+            .build();
+        self.deploy_transaction(tx.clone());
+        tx.output_pts()[0].clone()
+    }
+
+    /// Commit a transaction whose inputs are not checked, which is the only way to
+    /// create cells without spending others
+    pub fn deploy_transaction(&self, tx: core::TransactionView) -> H256 {
+        self.commit_transaction(tx, false)
+            .expect("unchecked transactions are always committed")
+    }
+
+    /// Commit a transaction as a CKB node would, i.e. only if all of its inputs are live
+    pub fn commit(&self, tx: core::TransactionView) -> Result<H256, Error> {
+        self.commit_transaction(tx, true)
+    }
+
+    /// Make the lock or type script fail with `exit_code` in every transaction running it
+    pub fn fail_script(&self, script: packed::Script, exit_code: i8) {
+        self.ledger
+            .write()
+            .unwrap()
+            .failing_scripts
+            .push((script, exit_code));
+    }
+
+    /// Restart the indexer once it served `queries` more cell queries
+    pub fn restart_indexer_after(&self, queries: u32) {
+        self.ledger.write().unwrap().indexer_restart_after = Some(queries);
+    }
+
+    pub fn is_live(&self, out_point: &packed::OutPoint) -> bool {
+        self.ledger
+            .read()
+            .unwrap()
+            .live_cells
+            .iter()
+            .any(|cell| cell.out_point.as_slice() == out_point.as_slice())
+    }
+
+    fn commit_transaction(
+        &self,
+        tx: core::TransactionView,
+        check_inputs: bool,
+    ) -> Result<H256, Error> {
+        let mut ledger = self.ledger.write().unwrap();
+        let tx_hash: H256 = tx.hash().unpack();
+        if ledger.transactions.contains_key(&tx_hash) {
+            return Err(Error::rpc_response(format!(
+                "PoolRejectedDuplicatedTransaction: transaction {tx_hash:#x} already exists"
+            )));
+        }
+
+        let mut spent = if check_inputs {
+            ledger.verify(&tx)?.0
+        } else {
+            tx.input_pts_iter()
+                .filter_map(|input| ledger.live_cell_position(&input))
+                .collect()
+        };
+        spent.sort_unstable();
+        spent.dedup();
+        for position in spent.into_iter().rev() {
+            ledger.live_cells.remove(position);
+        }
+
+        let block_number = ledger.push_block(vec![tx.clone()]);
+        for (out_point, (output, data)) in tx.output_pts_iter().zip(tx.outputs_with_data_iter()) {
+            let position = ledger.next_position;
+            ledger.next_position += 1;
+            ledger.live_cells.push(MockCell {
+                position,
+                out_point,
+                output,
+                data,
+                block_number,
+            });
+        }
+        ledger
+            .transactions
+            .insert(tx_hash.clone(), (tx, block_number));
+        Ok(tx_hash)
+    }
+
+    fn block_by_number(&self, number: u64) -> Result<BlockView, Error> {
+        self.ledger
+            .read()
+            .unwrap()
+            .blocks
+            .get(number as usize)
+            .map(|block| BlockView::from(block.clone()))
+            .ok_or_else(|| Error::rpc_response(format!("block {number} is not found")))
+    }
+}
+
+impl CkbReader for MockCkb {
+    fn get_blockchain_info(&self) -> Rpc<ChainInfo> {
+        let chain_info = serde_json::from_value(serde_json::json!({
+            "alerts": [],
+            "chain": "ckb_dev",
+            "difficulty": "0x10000",
+            "epoch": "0x0",
+            "is_initial_block_download": false,
+            "median_time": "0x0"
+        }))
+        .map_err(|e| Error::rpc_response(e.to_string()));
+        Box::pin(async { chain_info })
+    }
+
+    fn get_block_by_number(&self, number: BlockNumber) -> Rpc<BlockView> {
+        let resp = self.block_by_number(number.value());
+        Box::pin(async { resp })
+    }
+
+    fn get_block(&self, hash: &H256) -> Rpc<BlockView> {
+        let resp = self
+            .ledger
+            .read()
+            .unwrap()
+            .blocks
+            .iter()
+            .find(|block| {
+                let block_hash: H256 = block.hash().unpack();
+                &block_hash == hash
+            })
+            .map(|block| BlockView::from(block.clone()))
+            .ok_or_else(|| Error::rpc_response(format!("block {hash:#x} is not found")));
+        Box::pin(async { resp })
+    }
+
+    fn get_tip_header(&self) -> Rpc<HeaderView> {
+        let resp = HeaderView::from(self.ledger.read().unwrap().tip().header());
+        Box::pin(async { Ok(resp) })
+    }
+
+    fn get_transaction(&self, hash: &H256) -> Rpc<Option<TransactionWithStatusResponse>> {
+        let resp = self.ledger.read().unwrap().transaction(hash);
+        Box::pin(async { Ok(resp) })
+    }
+
+    fn get_live_cell(&self, out_point: &OutPoint, with_data: bool) -> Rpc<CellWithStatus> {
+        let out_point: packed::OutPoint = out_point.clone().into();
+        let resp = self
+            .ledger
+            .read()
+            .unwrap()
+            .live_cells
+            .iter()
+            .find(|cell| cell.out_point.as_slice() == out_point.as_slice())
+            .map(|cell| CellWithStatus {
+                cell: Some(CellInfo {
+                    output: cell.output.clone().into(),
+                    data: with_data.then(|| CellData {
+                        content: JsonBytes::from_bytes(cell.data.clone()),
+                        hash: packed::CellOutput::calc_data_hash(&cell.data).unpack(),
+                    }),
+                }),
+                status: "live".to_owned(),
+            })
+            .unwrap_or_else(|| CellWithStatus {
+                cell: None,
+                status: "unknown".to_owned(),
+            });
+        Box::pin(async { Ok(resp) })
+    }
+
+    fn get_txs_by_hashes(
+        &self,
+        hashes: Vec<H256>,
+    ) -> Rpc<Vec<Option<TransactionWithStatusResponse>>> {
+        let ledger = self.ledger.read().unwrap();
+        let resp = hashes.iter().map(|hash| ledger.transaction(hash)).collect();
+        Box::pin(async { Ok(resp) })
+    }
+
+    fn fetch_live_cells(
+        &self,
+        search_key: SearchKey,
+        limit: u32,
+        cursor: Option<JsonBytes>,
+    ) -> Rpc<Pagination<Cell>> {
+        if search_key.filter.is_some() {
+            let resp = Err(Error::rpc_response(
+                "filters of the search key are not supported".to_owned(),
+            ));
+            return Box::pin(async { resp });
+        }
+        let mut ledger = self.ledger.write().unwrap();
+        if ledger.indexer_restart_after == Some(0) {
+            ledger.indexer_restarts += 1;
+            ledger.indexer_restart_after = None;
+        }
+        // the cursor is the number of restarts of the indexer followed by the position of
+        // the last cell of the previous page
+        let restarts = ledger.indexer_restarts;
+        let after = match cursor.filter(|cursor| !cursor.is_empty()) {
+            Some(cursor) => {
+                let bytes = <[u8; 16]>::try_from(cursor.as_bytes())
+                    .ok()
+                    .filter(|bytes| bytes[..8] == restarts.to_be_bytes());
+                let Some(bytes) = bytes else {
+                    let resp = Err(Error::rpc_response(
+                        "Invalid params: unknown cursor".to_owned(),
+                    ));
+                    return Box::pin(async { resp });
+                };
+                Some(u64::from_be_bytes(bytes[8..].try_into().unwrap()))
+            }
+            None => None,
+        };
+        let with_data = search_key.with_data.unwrap_or(true);
+
+        let cells = ledger
+            .live_cells
+            .iter()
+            .filter(|cell| after.map_or(true, |after| cell.position > after))
+            .filter(|cell| cell.matches(&search_key))
+            .take(limit as usize)
+            .collect::<Vec<_>>();
+        let last_cursor = cells
+            .last()
+            .map(|cell| cell.position)
+            .or(after)
+            .map(|position| {
+                JsonBytes::from_vec([restarts.to_be_bytes(), position.to_be_bytes()].concat())
+            })
+            .unwrap_or_default();
+        let resp = Pagination {
+            objects: cells.iter().map(|cell| cell.to_cell(with_data)).collect(),
+            last_cursor,
+        };
+        if let Some(queries) = ledger.indexer_restart_after {
+            ledger.indexer_restart_after = Some(queries - 1);
+        }
+        Box::pin(async { Ok(resp) })
+    }
+
+    fn get_indexer_tip(&self) -> Rpc<Option<Tip>> {
+        let ledger = self.ledger.read().unwrap();
+        let tip = ledger.tip();
+        let resp = Tip {
+            block_hash: tip.hash().unpack(),
+            block_number: tip.number().into(),
+        };
+        Box::pin(async { Ok(Some(resp)) })
+    }
+
+    fn get_raw_tx_pool(&self, _verbose: bool) -> Rpc<RawTxPool> {
+        Box::pin(async {
+            Err(Error::rpc_response(
+                "the mock chain has no transaction pool".to_owned(),
+            ))
+        })
+    }
+
+    fn tx_pool_info(&self) -> Rpc<TxPoolInfo> {
+        Box::pin(async {
+            Err(Error::rpc_response(
+                "the mock chain has no transaction pool".to_owned(),
+            ))
+        })
+    }
+
+    fn estimate_cycles(&self, tx: &Transaction) -> Rpc<DryRunResult> {
+        let tx = packed::Transaction::from(tx.clone()).into_view();
+        let resp = self
+            .ledger
+            .read()
+            .unwrap()
+            .verify(&tx)
+            .map(|(_, cycles)| DryRunResult {
+                cycles: cycles.into(),
+            });
+        Box::pin(async { resp })
+    }
+}
+
+impl CkbWriter for MockCkb {
+    fn send_transaction(
+        &self,
+        tx: &Transaction,
+        _outputs_validator: Option<OutputsValidator>,
+    ) -> Rpc<H256> {
+        let tx = packed::Transaction::from(tx.clone()).into_view();
+        let resp = self.commit(tx);
+        Box::pin(async { resp })
+    }
+}
+
+impl CellSearcher for MockCkb {}
+impl TxCompleter for MockCkb {}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ckb_ics_axon::handler::{IbcChannel, IbcPacket, PacketStatus};
+    use ckb_ics_axon::message::{Envelope, MsgType};
+    use ckb_ics_axon::object::{ChannelCounterparty, Ordering, Packet, State};
+    use ckb_ics_axon::{ChannelArgs, PacketArgs};
+    use ckb_sdk::constants::TYPE_ID_CODE_HASH;
+    use ckb_sdk::rpc::ckb_indexer::SearchKey;
+    use ckb_sdk::traits::{CellQueryOptions, PrimaryScriptType};
+    use ckb_types::bytes::Bytes;
+    use ckb_types::core::{Capacity, ScriptHashType, TransactionBuilder, TransactionView};
+    use ckb_types::packed::{self, CellInput, CellOutput, Script, WitnessArgs};
+    use ckb_types::prelude::*;
+    use ckb_types::H256;
+    use ibc_relayer_types::clients::ics07_tendermint::client_state::test_util::get_dummy_tendermint_client_state;
+    use ibc_relayer_types::clients::ics07_tendermint::header::test_util::get_dummy_ics07_header;
+    use ibc_relayer_types::core::ics02_client::client_type::ClientType;
+    use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+    use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, ClientId, PortId};
+    use ibc_relayer_types::Height;
+
+    use super::{MockCkb, BLOCK_INTERVAL, GENESIS_TIMESTAMP, SCRIPT_CYCLES};
+    use crate::chain::ckb::pending_tx::{PendingTxConfig, PendingTxTracker};
+    use crate::chain::ckb::prelude::{CkbReader, CkbWriter};
+    use crate::chain::ckb4ibc::footprint::fetch_all_cells;
+    use crate::chain::ckb4ibc::history::collect_ibc_state_at;
+    use crate::chain::ckb4ibc::packet_size::collect_oversized_packets;
+    use crate::chain::ckb4ibc::tendermint::{get_client_lock_script, TendermintClient};
+    use crate::chain::ckb4ibc::utils::{
+        convert_port_id_to_array, get_encoded_object, get_script_hash,
+    };
+    use crate::chain::ckb4ibc::Ckb4IbcChainAsync;
+    use crate::config::ckb4ibc::ChainConfig;
+    use crate::consensus_state::AnyConsensusState;
+    use crate::error::{Error, ErrorDetail};
+
+    fn lock_script(args: &[u8]) -> Script {
+        Script::new_builder()
+            .code_hash([1u8; 32].pack())
+            .args(args.pack())
+            .build()
+    }
+
+    fn cell_output(lock: Script) -> CellOutput {
+        CellOutput::new_builder()
+            .lock(lock)
+            .build_exact_capacity(Capacity::zero())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fetch_live_cells_by_args_prefix_and_pages() {
+        let ckb = MockCkb::new();
+        for i in 0..5u8 {
+            ckb.deploy_cell(cell_output(lock_script(&[7, i])), Bytes::new());
+        }
+        ckb.deploy_cell(cell_output(lock_script(&[8])), Bytes::new());
+
+        let search_key: SearchKey =
+            CellQueryOptions::new(lock_script(&[7]), PrimaryScriptType::Lock).into();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let page = rt
+            .block_on(ckb.fetch_live_cells(search_key.clone(), 2, None))
+            .unwrap();
+        assert_eq!(page.objects.len(), 2);
+        let cells = rt.block_on(fetch_all_cells(&ckb, search_key)).unwrap();
+        assert_eq!(cells.len(), 5);
+        assert_eq!(ckb.tip_block_number(), 6);
+    }
+
+    #[test]
+    fn test_fetch_all_cells_starts_over_when_the_indexer_restarts() {
+        let ckb = MockCkb::new();
+        for i in 0..150u8 {
+            ckb.deploy_cell(cell_output(lock_script(&[7, i])), Bytes::new());
+        }
+
+        let search_key: SearchKey =
+            CellQueryOptions::new(lock_script(&[7]), PrimaryScriptType::Lock).into();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let page = rt
+            .block_on(ckb.fetch_live_cells(search_key.clone(), 2, None))
+            .unwrap();
+        ckb.restart_indexer_after(0);
+        let e = rt
+            .block_on(ckb.fetch_live_cells(search_key.clone(), 2, Some(page.last_cursor)))
+            .unwrap_err();
+        assert!(e.is_ckb_indexer_cursor_invalid());
+
+        // the cursor of the first page is invalidated before the second one is fetched
+        ckb.restart_indexer_after(1);
+        let cells = rt.block_on(fetch_all_cells(&ckb, search_key)).unwrap();
+        let mut out_points = cells
+            .iter()
+            .map(|cell| cell.out_point.clone())
+            .collect::<Vec<_>>();
+        out_points.dedup();
+        assert_eq!(out_points.len(), 150);
+    }
+
+    #[test]
+    fn test_send_transaction_spends_live_cells_only() {
+        let ckb = MockCkb::new();
+        let out_point = ckb.deploy_cell(cell_output(lock_script(&[1])), Bytes::new());
+        let spend = |args: &[u8]| {
+            TransactionBuilder::default()
+                .input(CellInput::new(out_point.clone(), 0))
+                .output(cell_output(lock_script(args)))
+                .output_data(Bytes::new().pack())
+                .build()
+        };
+
+        let tx = spend(&[2]);
+        ckb.commit(tx.clone()).unwrap();
+        assert!(!ckb.is_live(&out_point));
+        assert!(ckb.is_live(&tx.output_pts()[0]));
+
+        let tx = spend(&[3]);
+        let tx_hash: H256 = tx.hash().unpack();
+        let e = Error::ckb_send_tx_failure(format!("{tx_hash:#x}"), ckb.commit(tx).unwrap_err());
+        assert!(e.is_ckb_dead_cell_error());
+    }
+
+    #[test]
+    fn test_track_transactions_to_confirmation() {
+        let ckb = MockCkb::new();
+        let out_point = ckb.deploy_cell(cell_output(lock_script(&[1])), Bytes::new());
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(out_point, 0))
+            .output(cell_output(lock_script(&[2])))
+            .output_data(Bytes::new().pack())
+            .build();
+
+        let config = PendingTxConfig {
+            poll_interval: Duration::ZERO,
+            confirmations: 0,
+            timeout: Duration::from_secs(10),
+            accept_in_pool: false,
+        };
+        let tracker = PendingTxTracker::new(&ckb, config);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let mut results = rt
+            .block_on(tracker.submit_and_track(vec![(tx.data().into(), 1), (tx.data().into(), 2)]));
+
+        assert!(results.pop().unwrap().is_err());
+        let (tx_hash, payload) = results.pop().unwrap().unwrap();
+        assert_eq!(tx_hash, Unpack::<H256>::unpack(&tx.hash()));
+        assert_eq!(payload, 1);
+    }
+
+    #[test]
+    fn test_diagnose_script_failure() {
+        let ckb = MockCkb::new();
+        let lock = lock_script(&[1]);
+        let out_point = ckb.deploy_cell(cell_output(lock.clone()), Bytes::new());
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(out_point, 0))
+            .output(cell_output(lock_script(&[2])))
+            .output_data(Bytes::new().pack())
+            .build();
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let estimated = rt.block_on(ckb.estimate_cycles(&tx.data().into())).unwrap();
+        assert_eq!(estimated.cycles.value(), SCRIPT_CYCLES);
+
+        ckb.fail_script(lock.clone(), -31);
+        let tracker = PendingTxTracker::new(&ckb, PendingTxConfig::default());
+        let mut results = rt.block_on(tracker.submit_and_track(vec![(tx.data().into(), ())]));
+        let e = results.pop().unwrap().unwrap_err();
+        match e.detail() {
+            ErrorDetail::CkbTxScriptFailure(failure) => {
+                let code_hash: H256 = lock.code_hash().unpack();
+                assert_eq!(failure.exit_code, -31);
+                assert_eq!(failure.script, "Inputs[0].Lock");
+                assert_eq!(failure.contract, Some(format!("{code_hash:#x} (Data)")));
+                // a transaction failing to verify has no cycles to estimate
+                assert!(failure.cycles.is_none());
+            }
+            _ => panic!("expect a script failure, got {e}"),
+        }
+    }
+
+    #[test]
+    fn test_submit_chained_transactions() {
+        let ckb = MockCkb::new();
+        let out_point = ckb.deploy_cell(cell_output(lock_script(&[1])), Bytes::new());
+        let spend = |out_point: packed::OutPoint, args: &[u8]| {
+            TransactionBuilder::default()
+                .input(CellInput::new(out_point, 0))
+                .output(cell_output(lock_script(args)))
+                .output_data(Bytes::new().pack())
+                .build()
+        };
+        let parent = spend(out_point.clone(), &[2]);
+        let child = spend(parent.output_pts()[0].clone(), &[3]);
+        // spends the same cell as the first parent
+        let rejected = spend(out_point, &[4]);
+        let orphan = spend(rejected.output_pts()[0].clone(), &[5]);
+        let txs = [&parent, &child, &rejected, &orphan]
+            .into_iter()
+            .enumerate()
+            .map(|(index, tx)| (tx.data().into(), index))
+            .collect();
+        let parents = vec![vec![], vec![0], vec![], vec![2]];
+
+        let config = PendingTxConfig {
+            accept_in_pool: true,
+            ..Default::default()
+        };
+        let tracker = PendingTxTracker::new(&ckb, config);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let results = rt.block_on(tracker.submit_chained_and_track(txs, &parents));
+
+        assert_eq!(results[0].as_ref().unwrap().1, 0);
+        assert_eq!(results[1].as_ref().unwrap().1, 1);
+        assert!(ckb.is_live(&child.output_pts()[0]));
+        assert!(results[2].as_ref().unwrap_err().is_ckb_dead_cell_error());
+        // the child of the rejected transaction is not sent, to be assembled again
+        assert!(results[3].as_ref().unwrap_err().is_ckb_dead_cell_error());
+        assert!(!ckb.is_live(&orphan.output_pts()[0]));
+    }
+
+    #[test]
+    fn test_track_sent_transactions() {
+        let ckb = MockCkb::new();
+        let out_point = ckb.deploy_cell(cell_output(lock_script(&[1])), Bytes::new());
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(out_point, 0))
+            .output(cell_output(lock_script(&[2])))
+            .output_data(Bytes::new().pack())
+            .build();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let sent = rt
+            .block_on(ckb.send_transaction(&tx.data().into(), None))
+            .unwrap();
+
+        let config = PendingTxConfig {
+            poll_interval: Duration::ZERO,
+            confirmations: 0,
+            ..Default::default()
+        };
+        let tracker = PendingTxTracker::new(&ckb, config);
+        // the second transaction was dropped from the pool of the node
+        let results = rt.block_on(tracker.track_sent(vec![sent.clone(), H256([1; 32])]));
+
+        assert_eq!(results[0].as_ref().unwrap(), &sent);
+        assert!(results[1].is_err());
+    }
+
+    fn chain_config() -> ChainConfig {
+        toml::from_str(
+            r#"
+            id = "ckb4ibc-0"
+            counter_chain = "axon-0"
+            ckb_rpc = "http://127.0.0.1:8114"
+            ckb_indexer_rpc = "http://127.0.0.1:8116"
+            key_name = "relayer"
+            client_type_args = "0x0000000000000000000000000000000000000000000000000000000000000001"
+            connection_type_args = "0x0000000000000000000000000000000000000000000000000000000000000002"
+            channel_type_args = "0x0000000000000000000000000000000000000000000000000000000000000003"
+            packet_type_args = "0x0000000000000000000000000000000000000000000000000000000000000004"
+            max_packet_data_size = 16
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_collect_oversized_packets() {
+        let config = chain_config();
+        let ckb = MockCkb::new();
+        for (sequence, data) in [(1, vec![0; 8]), (2, vec![0; 32])] {
+            let packet = IbcPacket {
+                packet: Packet {
+                    sequence,
+                    source_port_id: "transfer".to_owned(),
+                    source_channel_id: "channel-0".to_owned(),
+                    destination_port_id: "transfer".to_owned(),
+                    destination_channel_id: "channel-1".to_owned(),
+                    data,
+                },
+                tx_hash: None,
+                status: PacketStatus::Send,
+            };
+            let envelope = Envelope {
+                msg_type: MsgType::MsgSendPacket,
+                content: vec![],
+            };
+            let packet_lock = Script::new_builder()
+                .code_hash(get_script_hash(&config.packet_type_args))
+                .args(vec![sequence as u8].pack())
+                .build();
+            let tx = TransactionBuilder::default()
+                .output(cell_output(packet_lock))
+                .output_data(Bytes::new().pack())
+                .witness(WitnessArgs::new_builder().build().as_bytes().pack())
+                .witness(
+                    WitnessArgs::new_builder()
+                        .output_type(get_encoded_object(packet).witness)
+                        .build()
+                        .as_bytes()
+                        .pack(),
+                )
+                .witness(
+                    WitnessArgs::new_builder()
+                        .output_type(get_encoded_object(envelope).witness)
+                        .build()
+                        .as_bytes()
+                        .pack(),
+                )
+                .build();
+            ckb.deploy_transaction(tx);
+        }
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let oversized = rt
+            .block_on(collect_oversized_packets(&ckb, &config))
+            .unwrap();
+        assert_eq!(oversized.len(), 1);
+        assert_eq!(u64::from(oversized[0].sequence), 2);
+        assert_eq!(oversized[0].data_size, 32);
+    }
+
+    /// Port of the channels, the hex encoding of the 32 bytes of the port in the args
+    /// of the channel and packet cells
+    const PORT_ID: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+
+    fn ibc_channel(num: u16) -> IbcChannel {
+        IbcChannel {
+            num,
+            port_id: PORT_ID.to_owned(),
+            state: State::Open,
+            order: Ordering::Ordered,
+            sequence: Default::default(),
+            counterparty: ChannelCounterparty {
+                port_id: "transfer".to_owned(),
+                channel_id: "channel-1".to_owned(),
+            },
+            connection_hops: vec![0],
+        }
+    }
+
+    /// Transaction of a packet sent on `channel`, which outputs the updated channel cell
+    fn channel_tx(
+        config: &ChainConfig,
+        input: Option<packed::OutPoint>,
+        channel: IbcChannel,
+    ) -> TransactionView {
+        let channel_lock = Script::new_builder()
+            .code_hash(get_script_hash(&config.channel_type_args))
+            .hash_type(ScriptHashType::Type.into())
+            .args(
+                ChannelArgs {
+                    client_id: config.client_type_args.clone().into(),
+                    open: true,
+                    channel_id: channel.num,
+                    port_id: convert_port_id_to_array(&PortId::from_str(PORT_ID).unwrap()).unwrap(),
+                }
+                .to_args()
+                .pack(),
+            )
+            .build();
+        let envelope = Envelope {
+            msg_type: MsgType::MsgSendPacket,
+            content: vec![],
+        };
+        TransactionBuilder::default()
+            .inputs(input.map(|out_point| CellInput::new(out_point, 0)))
+            .output(cell_output(channel_lock))
+            .output_data(Bytes::new().pack())
+            .witness(
+                WitnessArgs::new_builder()
+                    .output_type(get_encoded_object(channel).witness)
+                    .build()
+                    .as_bytes()
+                    .pack(),
+            )
+            .witness(
+                WitnessArgs::new_builder()
+                    .output_type(get_encoded_object(envelope).witness)
+                    .build()
+                    .as_bytes()
+                    .pack(),
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_collect_ibc_state_at() {
+        let config = chain_config();
+        let ckb = MockCkb::new();
+        let mut channel = ibc_channel(0);
+        channel.sequence.next_send_packet = 1;
+        channel.sequence.next_recv_ack = 1;
+        let opened = channel_tx(&config, None, channel.clone());
+        ckb.deploy_transaction(opened.clone());
+        ckb.mine(2);
+        // a packet is sent, which updates the channel cell
+        channel.sequence.next_send_packet = 2;
+        let sent = channel_tx(&config, Some(opened.output_pts()[0].clone()), channel);
+        ckb.commit(sent).unwrap();
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let state_at = |block_number| {
+            rt.block_on(collect_ibc_state_at(&ckb, &config, block_number))
+                .unwrap()
+        };
+        // the channel is opened in block 1
+        let state = state_at(0);
+        assert!(state.connections.is_empty());
+        assert!(state.channels.is_empty());
+
+        let state = state_at(3);
+        assert_eq!(state.channels.len(), 1);
+        assert!(state.outstanding_packets.is_empty());
+
+        let state = state_at(ckb.tip_block_number());
+        assert_eq!(state.channels.len(), 1);
+        assert_eq!(state.outstanding_packets.len(), 1);
+        assert_eq!(state.outstanding_packets[0].count, 1);
+        assert_eq!(
+            state.outstanding_packets[0].sequences,
+            Some(vec![Sequence::from(1)])
+        );
+    }
+
+    #[test]
+    fn test_query_channels_concurrently() {
+        let config = chain_config();
+        let ckb = MockCkb::new();
+        for num in [2, 10, 1] {
+            ckb.deploy_transaction(channel_tx(&config, None, ibc_channel(num)));
+        }
+
+        let chain = Ckb4IbcChainAsync::new(Arc::new(ckb), config);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let channels = rt.block_on(chain.query_channels()).unwrap();
+        // ordered by channel identifier whichever transaction completes first
+        let channel_ids = channels
+            .iter()
+            .map(|channel| channel.channel_id.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(channel_ids, ["channel-1", "channel-2", "channel-10"]);
+
+        let port_id = PortId::from_str(PORT_ID).unwrap();
+        let channel_id = ChannelId::new(1);
+        let (channel, _) = rt
+            .block_on(chain.fetch_channel(&channel_id, &port_id, true))
+            .unwrap();
+        assert_eq!(channel.0.channel_id, channel_id);
+    }
+
+    /// Transaction of a packet of `sequence` sent on channel `num`, which outputs the
+    /// packet cell
+    fn packet_tx(config: &ChainConfig, num: u16, sequence: u16) -> TransactionView {
+        let port_id = PortId::from_str(PORT_ID).unwrap();
+        let packet = IbcPacket {
+            packet: Packet {
+                sequence,
+                source_port_id: PORT_ID.to_owned(),
+                source_channel_id: format!("channel-{num}"),
+                destination_port_id: "transfer".to_owned(),
+                destination_channel_id: "channel-1".to_owned(),
+                data: vec![],
+            },
+            tx_hash: None,
+            status: PacketStatus::Send,
+        };
+        let envelope = Envelope {
+            msg_type: MsgType::MsgSendPacket,
+            content: vec![],
+        };
+        let packet_lock = Script::new_builder()
+            .code_hash(get_script_hash(&config.packet_type_args))
+            .hash_type(ScriptHashType::Type.into())
+            .args(
+                PacketArgs {
+                    channel_id: num,
+                    port_id: convert_port_id_to_array(&port_id).unwrap(),
+                    sequence,
+                    owner: config.packet_owner(&port_id),
+                }
+                .to_args()
+                .pack(),
+            )
+            .build();
+        TransactionBuilder::default()
+            .output(cell_output(packet_lock))
+            .output_data(Bytes::new().pack())
+            .witness(WitnessArgs::new_builder().build().as_bytes().pack())
+            .witness(
+                WitnessArgs::new_builder()
+                    .output_type(get_encoded_object(packet).witness)
+                    .build()
+                    .as_bytes()
+                    .pack(),
+            )
+            .witness(
+                WitnessArgs::new_builder()
+                    .output_type(get_encoded_object(envelope).witness)
+                    .build()
+                    .as_bytes()
+                    .pack(),
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_fetch_packets_in_one_search() {
+        let config = chain_config();
+        let ckb = MockCkb::new();
+        for sequence in 1..=3 {
+            ckb.deploy_transaction(packet_tx(&config, 0, sequence));
+        }
+        // the same sequence on another channel is not mistaken for the one of channel 0
+        ckb.deploy_transaction(packet_tx(&config, 1, 4));
+
+        let chain = Ckb4IbcChainAsync::new(Arc::new(ckb), config);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let port_id = PortId::from_str(PORT_ID).unwrap();
+        let sequences = [3, 1, 4, 2].map(Sequence::from).to_vec();
+        let packets = rt
+            .block_on(chain.fetch_packets(&ChannelId::new(0), &port_id, sequences))
+            .unwrap();
+        // in the order of the sequences, the ones without a packet cell being skipped
+        let fetched = packets
+            .iter()
+            .map(|(packet, _)| packet.packet.sequence)
+            .collect::<Vec<_>>();
+        assert_eq!(fetched, [3, 1, 2]);
+
+        let packets = rt
+            .block_on(chain.fetch_packets(&ChannelId::new(0), &port_id, vec![]))
+            .unwrap();
+        assert!(packets.is_empty());
+    }
+
+    #[test]
+    fn test_query_consensus_state() {
+        let config = chain_config();
+        let ckb = MockCkb::new();
+        let client_type_args = config.client_type_args.clone();
+        ckb.mine(2);
+        let contract = CellOutput::new_builder()
+            .lock(lock_script(&[]))
+            .type_(
+                Some(
+                    Script::new_builder()
+                        .code_hash(TYPE_ID_CODE_HASH.pack())
+                        .hash_type(ScriptHashType::Type.into())
+                        .args(client_type_args.as_bytes().pack())
+                        .build(),
+                )
+                .pack(),
+            )
+            .build();
+        ckb.deploy_cell(contract, Bytes::new());
+
+        let header = get_dummy_ics07_header();
+        let mut client_state =
+            get_dummy_tendermint_client_state(header.signed_header.header.clone());
+        client_state.latest_height = header.trusted_height;
+        let client = TendermintClient {
+            client_state,
+            consensus_states: vec![(header.trusted_height, header.clone().into())],
+        };
+        let tm_client_id = ClientId::new(ClientType::Tendermint, 0).unwrap();
+        let client = client.update(&tm_client_id, header).unwrap();
+        ckb.deploy_cell(
+            cell_output(get_client_lock_script(&client_type_args)),
+            Bytes::from(rlp::encode(&client).to_vec()),
+        );
+
+        let chain = Ckb4IbcChainAsync::new(Arc::new(ckb), config);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let heights = rt
+            .block_on(chain.query_consensus_state_heights(&tm_client_id))
+            .unwrap();
+        assert_eq!(heights, client.consensus_state_heights());
+        for height in heights {
+            let consensus_state = rt
+                .block_on(chain.query_consensus_state(&tm_client_id, height))
+                .unwrap();
+            assert_eq!(
+                consensus_state,
+                AnyConsensusState::Tendermint(
+                    client
+                        .consensus_state(&tm_client_id, height)
+                        .unwrap()
+                        .clone()
+                )
+            );
+        }
+        let unknown = Height::new(0, 21).unwrap();
+        assert!(rt
+            .block_on(chain.query_consensus_state(&tm_client_id, unknown))
+            .is_err());
+
+        // the only height of the other clients is dated by the block of their contract
+        let client_id = ClientId::new(ClientType::Ckb4Ibc, 0).unwrap();
+        let height = Height::new(1, 1).unwrap();
+        assert_eq!(
+            rt.block_on(chain.query_consensus_state_heights(&client_id))
+                .unwrap(),
+            vec![height]
+        );
+        let consensus_state = match rt
+            .block_on(chain.query_consensus_state(&client_id, height))
+            .unwrap()
+        {
+            AnyConsensusState::Ckb(consensus_state) => consensus_state,
+            _ => panic!("not the consensus state of a ckb client"),
+        };
+        let timestamp = (GENESIS_TIMESTAMP + 3 * BLOCK_INTERVAL) * 1_000_000;
+        assert_eq!(
+            consensus_state.timestamp.unix_timestamp_nanos(),
+            timestamp as i128
+        );
+        let err = rt
+            .block_on(chain.query_consensus_state(&client_id, Height::new(1, 2).unwrap()))
+            .unwrap_err();
+        assert!(err.to_string().contains("no consensus state"));
+    }
+}
@@ -1,3 +1,4 @@
+use alloc::collections::BTreeMap;
 use alloc::sync::Arc;
 use std::thread;
 
@@ -30,6 +31,7 @@ use ibc_relayer_types::{
 
 use crate::{
     account::Balance,
+    audit::ConfigDrift,
     chain::requests::QueryPacketEventDataRequest,
     client_state::{AnyClientState, IdentifiedAnyClientState},
     config::ChainConfig,
@@ -41,11 +43,20 @@ use crate::{
     keyring::AnySigningKeyPair,
     light_client::AnyHeader,
     misbehaviour::MisbehaviourEvidence,
+    progress::RelayStatus,
+    util::panic::spawn_chain_thread,
 };
 
 use super::{
-    client::ClientSettings,
-    endpoint::{ChainEndpoint, ChainStatus, HealthCheck},
+    ckb::{client_cells::ClientCells, prune::ClientsPruning},
+    ckb4ibc::{
+        cells::{IbcCell, IbcCellKind},
+        footprint::StorageFootprint,
+        history::IbcStateAt,
+        packet_size::OversizedPacket,
+    },
+    client::{ClientSettings, DerivedClientParams},
+    endpoint::{ChainCapabilities, ChainEndpoint, ChainStatus, HealthCheck},
     handle::{CacheTxHashStatus, ChainHandle, ChainRequest, ReplyTo, Subscription},
     requests::*,
     tracking::TrackedMsgs,
@@ -102,7 +113,7 @@ where
 
         // Spawn the runtime & return
         let id = handle.id();
-        let thread = thread::spawn(move || {
+        let thread = spawn_chain_thread(id.clone(), "runtime", move || {
             if let Err(e) = chain_runtime.run() {
                 error!("failed to start runtime for chain '{}': {}", id, e);
             }
@@ -162,6 +173,50 @@ where
                             self.subscribe(reply_to)?
                         },
 
+                        ChainRequest::InvalidateCache { reply_to } => {
+                            self.invalidate_cache(reply_to)?
+                        },
+
+                        ChainRequest::QueryStorageFootprint { reply_to } => {
+                            self.query_storage_footprint(reply_to)?
+                        },
+
+                        ChainRequest::QueryOversizedPackets { reply_to } => {
+                            self.query_oversized_packets(reply_to)?
+                        },
+
+                        ChainRequest::QueryIbcStateAt { block_number, reply_to } => {
+                            self.query_ibc_state_at(block_number, reply_to)?
+                        },
+
+                        ChainRequest::QueryIbcCells { kind, reply_to } => {
+                            self.query_ibc_cells(kind, reply_to)?
+                        },
+
+                        ChainRequest::AuditConfig { reply_to } => {
+                            self.audit_config(reply_to)?
+                        },
+
+                        ChainRequest::QueryClientParams { reply_to } => {
+                            self.query_client_params(reply_to)?
+                        },
+
+                        ChainRequest::PruneObsoleteClients { dry_run, reply_to } => {
+                            self.prune_obsolete_clients(dry_run, reply_to)?
+                        },
+
+                        ChainRequest::QueryLightClientCells { reply_to } => {
+                            self.query_light_client_cells(reply_to)?
+                        },
+
+                        ChainRequest::QueryRelayStatus { reply_to } => {
+                            self.query_relay_status(reply_to)?
+                        },
+
+                        ChainRequest::QueryContractOutpoints { reply_to } => {
+                            self.query_contract_outpoints(reply_to)?
+                        },
+
                         ChainRequest::SendMessagesAndWaitCommit { tracked_msgs, reply_to } => {
                             self.send_messages_and_wait_commit(tracked_msgs, reply_to)?
                         },
@@ -190,6 +245,10 @@ where
                             self.ibc_version(reply_to)?
                         },
 
+                        ChainRequest::Capabilities { reply_to } => {
+                            self.capabilities(reply_to)?
+                        },
+
                         ChainRequest::BuildHeader { trusted_height, target_height, client_state, reply_to } => {
                             self.build_header(trusted_height, target_height, client_state, reply_to)?
                         },
@@ -372,6 +431,82 @@ where
         reply_to.send(subscription).map_err(Error::send)
     }
 
+    fn invalidate_cache(&mut self, reply_to: ReplyTo<()>) -> Result<(), Error> {
+        let result = self.chain.invalidate_cache();
+        reply_to.send(result).map_err(Error::send)
+    }
+
+    fn query_storage_footprint(
+        &mut self,
+        reply_to: ReplyTo<StorageFootprint>,
+    ) -> Result<(), Error> {
+        let result = self.chain.query_storage_footprint();
+        reply_to.send(result).map_err(Error::send)
+    }
+
+    fn query_oversized_packets(
+        &mut self,
+        reply_to: ReplyTo<Vec<OversizedPacket>>,
+    ) -> Result<(), Error> {
+        let result = self.chain.query_oversized_packets();
+        reply_to.send(result).map_err(Error::send)
+    }
+
+    fn query_ibc_state_at(
+        &mut self,
+        block_number: u64,
+        reply_to: ReplyTo<IbcStateAt>,
+    ) -> Result<(), Error> {
+        let result = self.chain.query_ibc_state_at(block_number);
+        reply_to.send(result).map_err(Error::send)
+    }
+
+    fn query_ibc_cells(
+        &mut self,
+        kind: Option<IbcCellKind>,
+        reply_to: ReplyTo<Vec<IbcCell>>,
+    ) -> Result<(), Error> {
+        let result = self.chain.query_ibc_cells(kind);
+        reply_to.send(result).map_err(Error::send)
+    }
+
+    fn audit_config(&mut self, reply_to: ReplyTo<Vec<ConfigDrift>>) -> Result<(), Error> {
+        let result = self.chain.audit_config();
+        reply_to.send(result).map_err(Error::send)
+    }
+
+    fn query_client_params(&mut self, reply_to: ReplyTo<DerivedClientParams>) -> Result<(), Error> {
+        let result = self.chain.query_client_params();
+        reply_to.send(result).map_err(Error::send)
+    }
+
+    fn prune_obsolete_clients(
+        &mut self,
+        dry_run: bool,
+        reply_to: ReplyTo<ClientsPruning>,
+    ) -> Result<(), Error> {
+        let result = self.chain.prune_obsolete_clients(dry_run);
+        reply_to.send(result).map_err(Error::send)
+    }
+
+    fn query_light_client_cells(&self, reply_to: ReplyTo<ClientCells>) -> Result<(), Error> {
+        let result = self.chain.query_light_client_cells();
+        reply_to.send(result).map_err(Error::send)
+    }
+
+    fn query_relay_status(&self, reply_to: ReplyTo<RelayStatus>) -> Result<(), Error> {
+        let result = self.chain.query_relay_status();
+        reply_to.send(result).map_err(Error::send)
+    }
+
+    fn query_contract_outpoints(
+        &self,
+        reply_to: ReplyTo<BTreeMap<String, String>>,
+    ) -> Result<(), Error> {
+        let result = self.chain.query_contract_outpoints();
+        reply_to.send(result).map_err(Error::send)
+    }
+
     fn send_messages_and_wait_commit(
         &mut self,
         tracked_msgs: TrackedMsgs,
@@ -455,6 +590,11 @@ where
         reply_to.send(result).map_err(Error::send)
     }
 
+    fn capabilities(&mut self, reply_to: ReplyTo<ChainCapabilities>) -> Result<(), Error> {
+        let result = Ok(self.chain.capabilities());
+        reply_to.send(result).map_err(Error::send)
+    }
+
     fn build_header(
         &mut self,
         trusted_height: Height,
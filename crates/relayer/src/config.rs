@@ -3,9 +3,13 @@ pub mod axon;
 pub mod ckb;
 pub mod ckb4ibc;
 pub mod cosmos;
+pub mod diff;
 pub mod error;
 pub mod eth;
+pub mod evm;
 pub mod filter;
+pub mod path;
+pub mod rpc_url;
 
 use alloc::collections::BTreeMap;
 use core::{
@@ -39,6 +43,8 @@ use ckb4ibc::ChainConfig as Ckb4IbcChainConfig;
 use cosmos::ChainConfig as CosmosChainConfig;
 pub use error::Error;
 use eth::EthChainConfig;
+use evm::EvmChainConfig;
+use path::PathConfig;
 use tokio::sync::OnceCell;
 
 use self::filter::PacketFilter;
@@ -188,6 +194,14 @@ pub mod default {
     pub fn auto_register_counterparty_payee() -> bool {
         false
     }
+
+    pub fn remote_signer_timeout() -> Duration {
+        Duration::from_secs(10)
+    }
+
+    pub fn remote_signer_max_retries() -> u32 {
+        3
+    }
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -199,6 +213,7 @@ pub enum ChainConfig {
     Ckb(CkbChainConfig),
     Ckb4Ibc(Ckb4IbcChainConfig),
     Axon(AxonChainConfig),
+    Evm(EvmChainConfig),
 }
 
 impl ChainConfig {
@@ -209,6 +224,7 @@ impl ChainConfig {
             ChainConfig::Ckb(c) => &c.id,
             ChainConfig::Axon(c) => &c.id,
             ChainConfig::Ckb4Ibc(c) => &c.id,
+            ChainConfig::Evm(c) => &c.id,
         }
     }
 
@@ -219,6 +235,7 @@ impl ChainConfig {
             ChainConfig::Ckb(_) => todo!(),
             ChainConfig::Axon(_) => todo!(),
             ChainConfig::Ckb4Ibc(_) => todo!(),
+            ChainConfig::Evm(_) => todo!(),
         }
     }
 
@@ -229,9 +246,34 @@ impl ChainConfig {
             ChainConfig::Ckb(c) => &c.key_name,
             ChainConfig::Axon(c) => &c.key_name,
             ChainConfig::Ckb4Ibc(c) => &c.key_name,
+            ChainConfig::Evm(c) => &c.key_name,
+        }
+    }
+
+    /// Whether the relayer may submit transactions to the chain, otherwise the chain is
+    /// only monitored and its signing key is never loaded.
+    pub fn submit_txs(&self) -> bool {
+        match self {
+            ChainConfig::Ckb4Ibc(c) => c.submit_txs,
+            _ => true,
+        }
+    }
+
+    /// Balance of the relayer account below which it is reported to be running low,
+    /// in the smallest unit of the default denom of the chain
+    pub fn min_balance(&self) -> Option<u64> {
+        match self {
+            ChainConfig::Ckb4Ibc(c) => c.min_balance,
+            _ => None,
         }
     }
 
+    /// Whether all the transactions relaying the packets of a channel consume the cell
+    /// of the channel, so that they have to be submitted one after the other
+    pub fn serializes_channel_txs(&self) -> bool {
+        matches!(self, ChainConfig::Ckb4Ibc(_))
+    }
+
     pub fn downcast_cosmos(self) -> CosmosChainConfig {
         if let ChainConfig::Cosmos(c) = self {
             c
@@ -287,6 +329,7 @@ impl ChainConfig {
             ChainConfig::Ckb(_) => ChainType::Ckb,
             ChainConfig::Axon(_) => ChainType::Axon,
             ChainConfig::Ckb4Ibc(_) => ChainType::Ckb4Ibc,
+            ChainConfig::Evm(_) => ChainType::Evm,
         }
     }
 
@@ -297,6 +340,7 @@ impl ChainConfig {
             ChainConfig::Ckb(_) => todo!(),
             ChainConfig::Axon(_) => todo!(),
             ChainConfig::Ckb4Ibc(_) => Duration::from_secs(90),
+            ChainConfig::Evm(c) => c.max_block_time,
         }
     }
 }
@@ -371,6 +415,20 @@ impl TryFrom<ChainConfig> for AxonChainConfig {
     }
 }
 
+impl TryFrom<ChainConfig> for EvmChainConfig {
+    type Error = RelayerError;
+
+    fn try_from(value: ChainConfig) -> Result<Self, Self::Error> {
+        if let ChainConfig::Evm(value) = value {
+            Ok(value)
+        } else {
+            Err(RelayerError::config(ConfigError::encode(
+                toml::ser::Error::Custom("not EVM config".to_owned()),
+            )))
+        }
+    }
+}
+
 impl<'a> TryFrom<&'a ChainConfig> for &'a CkbChainConfig {
     type Error = RelayerError;
 
@@ -440,6 +498,8 @@ pub struct Config {
     pub telemetry: TelemetryConfig,
     #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
     pub chains: Vec<ChainConfig>,
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<PathConfig>,
 }
 
 impl Config {
@@ -604,6 +664,39 @@ impl Display for LogLevel {
 #[serde(default, deny_unknown_fields)]
 pub struct GlobalConfig {
     pub log_level: LogLevel,
+    /// File in which the chains and channels paused through the REST API are stored,
+    /// so that they stay paused across restarts
+    pub pause_file: Option<PathBuf>,
+    /// File in which the packet and channel events observed on the chains are archived,
+    /// to be exported through the REST API
+    pub archive_file: Option<PathBuf>,
+    /// Policy of the restarts of the chains whose threads panic
+    pub chain_restart: ChainRestartConfig,
+}
+
+/// Restarts of a chain after the panics of its threads, the first one `initial_backoff`
+/// after the panic, and each next one after twice the previous delay, up to `max_backoff`.
+/// The chain is marked failed and no longer restarted after `max_attempts` restarts in a
+/// row, the attempts being counted from zero again once the chain has run for
+/// `max_backoff` without panicking.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChainRestartConfig {
+    pub max_attempts: u32,
+    #[serde(with = "humantime_serde")]
+    pub initial_backoff: Duration,
+    #[serde(with = "humantime_serde")]
+    pub max_backoff: Duration,
+}
+
+impl Default for ChainRestartConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(300),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -681,6 +774,44 @@ impl Display for AddressType {
     }
 }
 
+/// Where the transactions of the relayer account of a chain are signed
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignerKind {
+    /// With the key of `key_name` in the keyring
+    #[default]
+    Keyring,
+    /// On a Ledger device connected over USB, with the first account of the app of the chain
+    Ledger,
+    /// By the remote signing service of `signing.remote`
+    Remote,
+}
+
+/// Settings of the signing backends of a chain
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SigningConfig {
+    #[serde(default)]
+    pub remote: Option<RemoteSignerConfig>,
+}
+
+/// External service holding the key of the relayer account and signing the digests
+/// of its transactions over HTTP
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteSignerConfig {
+    /// Base URL of the signing API, e.g. `http://127.0.0.1:9000`
+    pub url: Url,
+    /// Identifier of the key of the relayer account on the service
+    pub key_id: String,
+    /// Timeout of each request to the service
+    #[serde(default = "default::remote_signer_timeout", with = "humantime_serde")]
+    pub timeout: Duration,
+    /// Number of times a request is retried after a timeout or a server error
+    #[serde(default = "default::remote_signer_max_retries")]
+    pub max_retries: u32,
+}
+
 /// Attempt to load and parse the TOML config file as a `Config`.
 pub fn load(path: impl AsRef<Path>) -> Result<Config, Error> {
     let config_toml = std::fs::read_to_string(&path).map_err(Error::io)?;
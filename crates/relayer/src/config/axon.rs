@@ -1,8 +1,12 @@
+use std::path::PathBuf;
+
 use ethers::types::H160;
 use ibc_relayer_types::core::ics24_host::identifier::ChainId;
 use serde_derive::{Deserialize, Serialize};
 use tendermint_rpc::WebSocketClientUrl;
 
+use super::{SignerKind, SigningConfig};
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct AxonChainConfig {
     pub id: ChainId,
@@ -12,4 +16,14 @@ pub struct AxonChainConfig {
     pub store_prefix: String,
     pub ckb_light_client_contract_address: H160,
     pub image_cell_contract_address: H160,
+    /// Where the transactions are signed, either with the key of `key_name` in the keyring,
+    /// on a Ledger device running the Ethereum app or by the service of `signing.remote`
+    #[serde(default)]
+    pub signer: SignerKind,
+    #[serde(default)]
+    pub signing: SigningConfig,
+    /// File in which the event monitor records the height it has emitted the events
+    /// up to, so that a restarted relayer resumes from there instead of the latest block
+    #[serde(default)]
+    pub progress_file: Option<PathBuf>,
 }
@@ -1,21 +1,51 @@
+use core::time::Duration;
 use std::path::PathBuf;
 
 use ckb_types::H256;
 use ibc_relayer_types::core::ics24_host::identifier::ChainId;
 use serde_derive::{Deserialize, Serialize};
-use tendermint_rpc::Url;
+
+use super::rpc_url::RpcUrl;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ChainConfig {
     pub id: ChainId,
-    pub ckb_rpc: Url,
-    pub ckb_indexer_rpc: Url,
+    pub ckb_rpc: RpcUrl,
+    pub ckb_indexer_rpc: RpcUrl,
     pub lightclient_contract_typeargs: H256,
     pub lightclient_lock_typeargs: H256,
     pub client_type_args: ClientTypeArgs,
     pub minimal_updates_count: u8,
     pub key_name: String,
     pub data_dir: PathBuf,
+
+    /// Additional CKB nodes to which every signed transaction is also sent, without
+    /// waiting for their responses. Transactions are still tracked through `ckb_rpc`
+    #[serde(default)]
+    pub broadcast_rpcs: Vec<RpcUrl>,
+
+    /// Keep the light client updated on a schedule, without waiting for the
+    /// events of the Ethereum chain
+    #[serde(default)]
+    pub header_updater: Option<HeaderUpdaterConfig>,
+
+    /// Interval between two queries of the status of a sent transaction
+    #[serde(default = "default::tx_poll_interval", with = "humantime_serde")]
+    pub tx_poll_interval: Duration,
+
+    /// Number of blocks built on top of the block of a sent transaction before it is
+    /// considered committed
+    #[serde(default)]
+    pub tx_confirmations: u64,
+
+    /// How long a sent transaction is waited for before failing with a timeout
+    #[serde(default = "default::tx_commit_timeout", with = "humantime_serde")]
+    pub tx_commit_timeout: Duration,
+
+    /// Create a new set of multi-client cells in place of corrupted ones, instead of
+    /// failing to create the light client. Set by `forcerelay --force`
+    #[serde(skip)]
+    pub recreate_corrupted_clients: bool,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -25,3 +55,43 @@ pub struct ClientTypeArgs {
     // Number of client cells, plus one info cell
     pub cells_count: u8,
 }
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct HeaderUpdaterConfig {
+    /// Ethereum chain whose finalized headers are relayed
+    pub eth_chain_id: ChainId,
+    /// How often the lag of the light client is checked
+    #[serde(default = "default::update_interval", with = "humantime_serde")]
+    pub interval: Duration,
+    /// Number of slots the light client has to lag behind before being updated
+    #[serde(default = "default::min_lag")]
+    pub min_lag: u64,
+    /// Number of slots of lag above which the updater complains
+    #[serde(default = "default::max_lag")]
+    pub max_lag: u64,
+}
+
+/// Defaults for various fields
+pub mod default {
+    use super::*;
+
+    pub fn update_interval() -> Duration {
+        Duration::from_secs(60)
+    }
+
+    pub fn min_lag() -> u64 {
+        1
+    }
+
+    pub fn max_lag() -> u64 {
+        256
+    }
+
+    pub fn tx_poll_interval() -> Duration {
+        Duration::from_secs(3)
+    }
+
+    pub fn tx_commit_timeout() -> Duration {
+        Duration::from_secs(60)
+    }
+}
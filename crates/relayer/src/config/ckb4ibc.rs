@@ -1,24 +1,443 @@
+use core::time::Duration;
+use std::collections::BTreeMap;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::path::PathBuf;
+
 use ckb_types::H256;
-use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+use ibc_relayer_types::core::ics02_client::msgs::{
+    misbehaviour::TYPE_URL as MISBEHAVIOUR_TYPE_URL,
+    update_client::TYPE_URL as UPDATE_CLIENT_TYPE_URL,
+};
+use ibc_relayer_types::core::ics04_channel::msgs::{
+    timeout::TYPE_URL as TIMEOUT_TYPE_URL, timeout_on_close::TYPE_URL as TIMEOUT_ON_CLOSE_TYPE_URL,
+};
+use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ClientId, PortId};
 use serde_derive::{Deserialize, Serialize};
 use tendermint_rpc::Url;
 
+use super::rpc_url::RpcUrl;
+use super::{SignerKind, SigningConfig};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChainConfig {
     pub id: ChainId,
     pub counter_chain: ChainId,
-    pub ckb_rpc: Url,
-    pub ckb_indexer_rpc: Url,
+    pub ckb_rpc: RpcUrl,
+    pub ckb_indexer_rpc: RpcUrl,
+
+    /// Kind of node behind `ckb_rpc`, either a full node whose cells are searched through
+    /// `ckb_indexer_rpc`, or a ckb-light-client node serving both, `ckb_indexer_rpc` being
+    /// ignored then. The light client only keeps the cells and transactions of the scripts
+    /// it is registered for, which the relayer registers for the IBC contracts at startup
+    /// and for its accounts once it loads their keys
+    #[serde(default)]
+    pub rpc_mode: RpcMode,
+
+    /// Block from which the light client searches the cells of the scripts registered
+    /// by the relayer, e.g. the block deploying the IBC contracts, which shortens its
+    /// first sync. Only used with `rpc_mode = "light"`
+    #[serde(default)]
+    pub light_client_start_block: u64,
+
+    /// WebSocket endpoint of the CKB node, e.g. `ws://127.0.0.1:28114`, which pushes
+    /// its new tips to the event monitor so that it scans the new blocks as soon as they
+    /// come. The monitor polls `ckb_rpc` for the tip if it is not given, or while the
+    /// subscription is lost
+    #[serde(default)]
+    pub ws_url: Option<Url>,
     pub key_name: String,
 
     pub client_type_args: H256,
     pub connection_type_args: H256,
     pub channel_type_args: H256,
     pub packet_type_args: H256,
+
+    /// Type args of the client cells of additional counterparties, by the identifier
+    /// of the client in their connection ends. The channels of the connections on any
+    /// other client are bound to `client_type_args`
+    #[serde(default)]
+    pub clients: BTreeMap<ClientId, H256>,
+
+    /// Lock hash of the owner of the packet cells of each port, which can unlock them
+    /// instead of the relayer, e.g. the contract of the application bound to the port.
+    /// The owner of the packet cells of the other ports is zeroed
+    #[serde(default)]
+    pub packet_owners: BTreeMap<PortId, H256>,
+
+    /// File of the ports bound to the applications on the chain, edited with the
+    /// `ckb port` commands. If it is given, the channels are only opened on the bound
+    /// ports, otherwise on any port
+    #[serde(default)]
+    pub port_bindings_file: Option<PathBuf>,
+
+    /// How long the network type and the contract outpoints are cached before
+    /// being fetched again from the CKB node
+    #[serde(default = "default::cache_ttl", with = "humantime_serde")]
+    pub cache_ttl: Duration,
+
+    /// File in which the event monitor records how far it has scanned each script,
+    /// the scans restart from scratch on every launch if it is not given
+    #[serde(default)]
+    pub scan_offsets_path: Option<PathBuf>,
+
+    /// Number of blocks built on top of the block of a transaction before it is
+    /// considered final, both for emitting its IBC events and for the relayer's own txs
+    #[serde(default = "default::confirmations")]
+    pub confirmations: u64,
+
+    /// Interval between two queries of the status of the transactions sent
+    #[serde(default = "default::tx_poll_interval", with = "humantime_serde")]
+    pub tx_poll_interval: Duration,
+
+    /// How long the transactions sent are waited for before failing with a timeout,
+    /// including the time taken by their `confirmations`
+    #[serde(default = "default::tx_commit_timeout", with = "humantime_serde")]
+    pub tx_commit_timeout: Duration,
+
+    /// Additional CKB nodes to which every signed transaction is also sent, without
+    /// waiting for their responses. Transactions are still tracked through `ckb_rpc`
+    #[serde(default)]
+    pub broadcast_rpcs: Vec<RpcUrl>,
+
+    /// Multipliers applied to the fee rate of the transactions, per class of message
+    #[serde(default)]
+    pub fee_multipliers: FeeMultipliers,
+
+    /// Whether the relayer submits transactions to the chain. If disabled, the chain
+    /// is only monitored and the signing key of `key_name` is never loaded
+    #[serde(default = "default::submit_txs")]
+    pub submit_txs: bool,
+
+    /// Address of the relayer account for the queries which need it, such as the
+    /// balance, so that they don't load the signing key. Derived from the key if not given
+    #[serde(default)]
+    pub account_address: Option<String>,
+
+    /// Name of the key in the keyring of the account paying the fees of the transactions
+    /// and receiving their change, e.g. a treasury, while the IBC cells stay owned by the
+    /// relayer account of `key_name`. The balance and `min_balance` then refer to it.
+    /// The relayer account pays the fees itself if not given
+    #[serde(default)]
+    pub fee_payer_key_name: Option<String>,
+
+    /// Type script hashes of the SUDT/xUDT cells holding the ICS-20 tokens, by the
+    /// IBC denom trace of each token, e.g. `transfer/channel-0/uatom`
+    #[serde(default)]
+    pub udt_denoms: BTreeMap<String, H256>,
+
+    /// Capacity of the relayer account, in shannons, below which the wallet worker warns
+    /// that the change cells may run out and the chain is reported unhealthy
+    #[serde(default)]
+    pub min_balance: Option<u64>,
+
+    /// Where the transactions are signed, either with the key of `key_name` in the keyring,
+    /// on a Ledger device running the Nervos app or by the service of `signing.remote`
+    #[serde(default)]
+    pub signer: SignerKind,
+
+    #[serde(default)]
+    pub signing: SigningConfig,
+
+    /// Maximum size of the data of the packets extracted from the packet cells, in bytes.
+    /// Larger packets are skipped instead of being relayed to the counterparty
+    #[serde(default = "default::max_packet_data_size")]
+    pub max_packet_data_size: usize,
+
+    /// Maximum size of the memo of the ICS-20 packets, in bytes
+    #[serde(default = "default::max_memo_size")]
+    pub max_memo_size: usize,
+
+    /// Whether the event monitor recovers the signers of the IBC transactions it observes
+    /// from their witnesses, and warns about the ones signed by an unknown account
+    #[serde(default)]
+    pub verify_tx_authors: bool,
+
+    /// Addresses of the accounts of the other relayers of the chain, whose transactions
+    /// are expected when verifying the authors of the IBC transactions
+    #[serde(default)]
+    pub known_relayers: Vec<String>,
+
+    /// Whether the transactions are chained on the unconfirmed outputs of the previous
+    /// ones, instead of waiting for their `confirmations` first. The chained cells are
+    /// dropped and fetched again once one of the unconfirmed transactions is rejected
+    #[serde(default)]
+    pub chain_unconfirmed_txs: bool,
+
+    /// Version of the suite of IBC contracts deployed on the chain, e.g. `0.1.0`, which
+    /// is checked against the versions the relayer supports. The contracts are assumed
+    /// to be of the version the relayer is built for if not given
+    #[serde(default)]
+    pub contracts_version: Option<String>,
+
+    /// File in which the relayer records its progress on the chain, i.e. the height the
+    /// events are emitted up to, the transactions in flight and the packets relayed, so
+    /// that a restarted relayer neither emits the same events nor relays the same
+    /// packets again. Nothing is kept across restarts if it is not given
+    #[serde(default)]
+    pub progress_file: Option<PathBuf>,
+
+    /// Number of blocks the indexer may lag behind the node before the event monitor
+    /// warns about it and the chain is reported unhealthy, the cells of the blocks not
+    /// indexed yet being missing from the searches
+    #[serde(default = "default::max_indexer_lag")]
+    pub max_indexer_lag: u64,
+
+    /// How long the searches for cells wait for the indexer to catch up once it lags
+    /// more than `max_indexer_lag` blocks, before failing so that they are retried later
+    #[serde(default = "default::indexer_wait", with = "humantime_serde")]
+    pub indexer_wait: Duration,
+
+    /// Maximum number of requests sent to the node and the indexer per second, the
+    /// requests beyond it being queued. Unlimited if not given
+    #[serde(default)]
+    pub max_rpc_requests_per_second: Option<NonZeroU32>,
+
+    /// Maximum number of requests to the node and the indexer in flight at once, the
+    /// requests beyond it being queued. Unlimited if not given
+    #[serde(default)]
+    pub max_concurrent_rpc_requests: Option<NonZeroUsize>,
+
+    /// Block from which the event monitor scans the chain the first time it runs, i.e.
+    /// while `progress_file` holds no progress on it yet
+    #[serde(default)]
+    pub monitor_start: MonitorStart,
 }
 
 impl ChainConfig {
     pub fn client_id(&self) -> [u8; 32] {
         self.client_type_args.clone().into()
     }
+
+    /// Client cell of the connections on `client_id`
+    pub fn client_id_of(&self, client_id: &ClientId) -> [u8; 32] {
+        self.clients
+            .get(client_id)
+            .unwrap_or(&self.client_type_args)
+            .clone()
+            .into()
+    }
+
+    /// Owner of the packet cells of `port_id`
+    pub fn packet_owner(&self, port_id: &PortId) -> [u8; 32] {
+        self.packet_owners
+            .get(port_id)
+            .cloned()
+            .unwrap_or_default()
+            .into()
+    }
+
+    /// Type args of every client cell served by the chain, the default one first
+    pub fn all_client_type_args(&self) -> Vec<&H256> {
+        let mut all_type_args = vec![&self.client_type_args];
+        for type_args in self.clients.values() {
+            if !all_type_args.contains(&type_args) {
+                all_type_args.push(type_args);
+            }
+        }
+        all_type_args
+    }
+}
+
+/// Multipliers of the fee rate, so that time-sensitive messages confirm sooner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeeMultipliers {
+    /// Timeout of packets, which have to confirm before the counterparty acts on them
+    pub timeout: f64,
+    /// Evidence of misbehaviour, which has to freeze the client as soon as possible
+    pub misbehaviour: f64,
+    /// Routine client updates
+    pub update_client: f64,
+    /// All other messages
+    pub other: f64,
+}
+
+impl Default for FeeMultipliers {
+    fn default() -> Self {
+        Self {
+            timeout: 2.0,
+            misbehaviour: 2.0,
+            update_client: 1.0,
+            other: 1.0,
+        }
+    }
+}
+
+impl FeeMultipliers {
+    pub fn multiplier(&self, type_url: &str) -> f64 {
+        match type_url {
+            TIMEOUT_TYPE_URL | TIMEOUT_ON_CLOSE_TYPE_URL => self.timeout,
+            MISBEHAVIOUR_TYPE_URL => self.misbehaviour,
+            UPDATE_CLIENT_TYPE_URL => self.update_client,
+            _ => self.other,
+        }
+    }
+
+    /// Fee rate of the transaction of the message, in shannons per byte
+    pub fn fee_rate(&self, base_fee_rate: u64, type_url: &str) -> u64 {
+        (base_fee_rate as f64 * self.multiplier(type_url)).ceil() as u64
+    }
+}
+
+/// Kind of the CKB node the relayer talks to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcMode {
+    /// A full node, along with its indexer
+    #[default]
+    Full,
+    /// A ckb-light-client node
+    Light,
+}
+
+/// Block from which the event monitor starts scanning a chain, the events of the cells
+/// committed up to it being skipped
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitorStart {
+    /// The block deploying the earliest of the IBC contracts, found from the transactions
+    /// creating their cells, so that no IBC cell is missed
+    #[default]
+    Earliest,
+    /// The tip block at startup, the cells committed before being ignored
+    Tip,
+    /// The given block, e.g. `monitor_start = { height = 1000 }`
+    Height(u64),
+}
+
+/// Defaults for various fields
+pub mod default {
+    use super::*;
+
+    pub fn cache_ttl() -> Duration {
+        Duration::from_secs(600)
+    }
+
+    pub fn confirmations() -> u64 {
+        4
+    }
+
+    pub fn tx_poll_interval() -> Duration {
+        Duration::from_secs(3)
+    }
+
+    pub fn tx_commit_timeout() -> Duration {
+        Duration::from_secs(600)
+    }
+
+    pub fn submit_txs() -> bool {
+        true
+    }
+
+    pub fn max_packet_data_size() -> usize {
+        64 * 1024
+    }
+
+    /// Same as the limit of the ICS-20 module of ibc-go
+    pub fn max_memo_size() -> usize {
+        32 * 1024
+    }
+
+    pub fn max_indexer_lag() -> u64 {
+        100
+    }
+
+    pub fn indexer_wait() -> Duration {
+        Duration::from_secs(30)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ckb_types::h256;
+    use ibc_relayer_types::core::ics02_client::msgs::update_client::TYPE_URL as UPDATE_CLIENT_TYPE_URL;
+    use ibc_relayer_types::core::ics04_channel::msgs::{
+        recv_packet::TYPE_URL as RECV_PACKET_TYPE_URL, timeout::TYPE_URL as TIMEOUT_TYPE_URL,
+    };
+    use ibc_relayer_types::core::ics24_host::identifier::{ClientId, PortId};
+
+    use super::{ChainConfig, FeeMultipliers, RpcMode};
+
+    #[test]
+    fn test_fee_rate_per_message_class() {
+        let multipliers = FeeMultipliers {
+            update_client: 1.5,
+            ..Default::default()
+        };
+        assert_eq!(multipliers.fee_rate(1000, TIMEOUT_TYPE_URL), 2000);
+        assert_eq!(multipliers.fee_rate(1000, UPDATE_CLIENT_TYPE_URL), 1500);
+        assert_eq!(multipliers.fee_rate(1000, RECV_PACKET_TYPE_URL), 1000);
+    }
+
+    #[test]
+    fn test_client_of_connection() {
+        let config: ChainConfig = toml::from_str(
+            r#"
+            id = "ckb4ibc-0"
+            counter_chain = "axon-0"
+            ckb_rpc = "http://127.0.0.1:8114"
+            ckb_indexer_rpc = "http://127.0.0.1:8114"
+            key_name = "relayer_ckb_wallet"
+            client_type_args = "0x0000000000000000000000000000000000000000000000000000000000000001"
+            connection_type_args = "0x0000000000000000000000000000000000000000000000000000000000000002"
+            channel_type_args = "0x0000000000000000000000000000000000000000000000000000000000000003"
+            packet_type_args = "0x0000000000000000000000000000000000000000000000000000000000000004"
+
+            [clients]
+            07-axon-1 = "0x0000000000000000000000000000000000000000000000000000000000000005"
+            07-axon-2 = "0x0000000000000000000000000000000000000000000000000000000000000001"
+
+            [packet_owners]
+            transfer = "0x0000000000000000000000000000000000000000000000000000000000000006"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.client_id_of(&ClientId::from_str("07-axon-1").unwrap()),
+            <[u8; 32]>::from(h256!("0x5"))
+        );
+        assert_eq!(
+            config.client_id_of(&ClientId::from_str("07-axon-3").unwrap()),
+            config.client_id()
+        );
+        assert_eq!(
+            config.all_client_type_args(),
+            vec![&h256!("0x1"), &h256!("0x5")]
+        );
+        assert_eq!(
+            config.packet_owner(&PortId::transfer()),
+            <[u8; 32]>::from(h256!("0x6"))
+        );
+        assert_eq!(
+            config.packet_owner(&PortId::from_str("oracle").unwrap()),
+            [0u8; 32]
+        );
+        assert_eq!(config.rpc_mode, RpcMode::Full);
+    }
+
+    #[test]
+    fn test_light_client_rpc_mode() {
+        let config: ChainConfig = toml::from_str(
+            r#"
+            id = "ckb4ibc-0"
+            counter_chain = "axon-0"
+            ckb_rpc = "http://127.0.0.1:9000"
+            ckb_indexer_rpc = "http://127.0.0.1:9000"
+            rpc_mode = "light"
+            light_client_start_block = 1000
+            key_name = "relayer_ckb_wallet"
+            client_type_args = "0x0000000000000000000000000000000000000000000000000000000000000001"
+            connection_type_args = "0x0000000000000000000000000000000000000000000000000000000000000002"
+            channel_type_args = "0x0000000000000000000000000000000000000000000000000000000000000003"
+            packet_type_args = "0x0000000000000000000000000000000000000000000000000000000000000004"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.rpc_mode, RpcMode::Light);
+        assert_eq!(config.light_client_start_block, 1000);
+    }
 }
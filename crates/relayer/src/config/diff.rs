@@ -0,0 +1,133 @@
+//! Comparison of two configurations, to apply a reloaded configuration file to a
+//! running relayer.
+
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+use serde::Serialize;
+
+use super::{ChainConfig, Config};
+
+/// A change of the configuration of a chain
+#[derive(Clone, Debug)]
+pub enum ChainConfigUpdate {
+    Added(ChainConfig),
+    Removed(ChainId),
+    Updated(ChainConfig),
+}
+
+impl ChainConfigUpdate {
+    pub fn chain_id(&self) -> &ChainId {
+        match self {
+            Self::Added(config) | Self::Updated(config) => config.id(),
+            Self::Removed(chain_id) => chain_id,
+        }
+    }
+}
+
+/// The changes between two configurations.
+///
+/// Only the chains can be reconfigured while the relayer is running, the changes to
+/// the other sections are only reported.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigDiff {
+    pub chains: Vec<ChainConfigUpdate>,
+    /// The sections other than `chains` which were changed
+    pub unsupported: Vec<&'static str>,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.chains.is_empty() && self.unsupported.is_empty()
+    }
+}
+
+/// Compare the `old` configuration of the relayer with the `new` one.
+pub fn diff(old: &Config, new: &Config) -> ConfigDiff {
+    let mut diff = ConfigDiff::default();
+
+    for (name, changed) in [
+        ("global", !same(&old.global, &new.global)),
+        ("mode", !same(&old.mode, &new.mode)),
+        ("rest", !same(&old.rest, &new.rest)),
+        ("telemetry", !same(&old.telemetry, &new.telemetry)),
+        ("paths", !same(&old.paths, &new.paths)),
+    ] {
+        if changed {
+            diff.unsupported.push(name);
+        }
+    }
+
+    let old_chains = old.chains_map();
+    let new_chains = new.chains_map();
+
+    for (chain_id, old_chain) in &old_chains {
+        match new_chains.get(chain_id) {
+            Some(new_chain) if !same(old_chain, new_chain) => diff
+                .chains
+                .push(ChainConfigUpdate::Updated((*new_chain).clone())),
+            Some(_) => {}
+            None => diff
+                .chains
+                .push(ChainConfigUpdate::Removed((*chain_id).clone())),
+        }
+    }
+
+    for (chain_id, new_chain) in &new_chains {
+        if !old_chains.contains_key(chain_id) {
+            diff.chains
+                .push(ChainConfigUpdate::Added((*new_chain).clone()));
+        }
+    }
+
+    diff
+}
+
+/// The configuration types do not implement `PartialEq`, so they are compared
+/// through their serialized representation.
+fn same<T: Serialize>(old: &T, new: &T) -> bool {
+    match (serde_json::to_value(old), serde_json::to_value(new)) {
+        (Ok(old), Ok(new)) => old == new,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, ChainConfigUpdate};
+    use crate::config::{load, ChainConfig};
+
+    #[test]
+    fn test_config_diff() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/config/fixtures/relayer_conf_example.toml"
+        );
+        let old = load(path).expect("could not parse config");
+
+        assert!(diff(&old, &old).is_empty());
+
+        let mut new = old.clone();
+        let removed = new.chains.pop().unwrap();
+        if let ChainConfig::Cosmos(chain_config) = &mut new.chains[0] {
+            chain_config.key_name = "reloaded".to_string();
+        }
+        new.mode.packets.enabled = !old.mode.packets.enabled;
+
+        let changes = diff(&old, &new);
+        assert_eq!(changes.unsupported, vec!["mode"]);
+        assert_eq!(changes.chains.len(), 2);
+        assert!(matches!(
+            &changes.chains[0],
+            ChainConfigUpdate::Updated(config) if config.id() == old.chains[0].id()
+        ));
+        assert!(matches!(
+            &changes.chains[1],
+            ChainConfigUpdate::Removed(chain_id) if chain_id == removed.id()
+        ));
+
+        let changes = diff(&new, &old);
+        assert!(matches!(
+            &changes.chains[1],
+            ChainConfigUpdate::Added(config) if config.id() == removed.id()
+        ));
+    }
+}
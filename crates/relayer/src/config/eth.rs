@@ -15,6 +15,10 @@ pub struct EthChainConfig {
     pub rpc_addr_pool: Vec<String>,
     pub rpc_port: u16,
     pub forks: Forks,
+    /// Beacon node trusted to provide the latest finalized checkpoint, from which the
+    /// light client is bootstrapped instead of the `initial_checkpoint`
+    #[serde(default)]
+    pub checkpoint_sync_url: Option<String>,
 }
 
 pub fn array_hex_deserialize<'de, D, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error>
@@ -83,6 +87,7 @@ impl EthChainConfig {
             },
             initial_checkpoint: Default::default(),
             key_name: Default::default(),
+            checkpoint_sync_url: None,
         }
     }
 }
@@ -0,0 +1,62 @@
+use core::time::Duration;
+use std::collections::BTreeMap;
+
+use ethers::types::Address;
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+use serde_derive::{Deserialize, Serialize};
+use tendermint_rpc::WebSocketClientUrl;
+
+use crate::config::default;
+
+/// Configuration of an EVM chain on which the IBC handler contract is deployed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EvmChainConfig {
+    pub id: ChainId,
+    pub websocket_addr: WebSocketClientUrl,
+    /// Address of the IBC handler contract
+    pub contract_address: Address,
+    pub key_name: String,
+    pub store_prefix: String,
+    #[serde(default = "default::max_block_time", with = "humantime_serde")]
+    pub max_block_time: Duration,
+    /// Addresses of the light client contracts, by the type URL of the headers they update
+    #[serde(default)]
+    pub client_contract_addresses: BTreeMap<String, Address>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Address;
+    use crate::config::ChainConfig;
+
+    #[test]
+    fn test_evm_chain_config() {
+        let config: ChainConfig = toml::from_str(
+            r#"
+            id = "evm-0"
+            websocket_addr = "ws://127.0.0.1:8546"
+            contract_address = "0x0000000000000000000000000000000000000001"
+            key_name = "relayer_evm_wallet"
+            store_prefix = "forcerelay"
+
+            [client_contract_addresses]
+            "/ibc.lightclients.ckb.v1.Header" = "0x0000000000000000000000000000000000000002"
+            "#,
+        )
+        .unwrap();
+
+        let ChainConfig::Evm(config) = config else {
+            panic!("not parsed as an EVM chain config");
+        };
+        assert_eq!(
+            config.max_block_time,
+            crate::config::default::max_block_time()
+        );
+        assert_eq!(
+            config.client_contract_addresses["/ibc.lightclients.ckb.v1.Header"],
+            "0x0000000000000000000000000000000000000002"
+                .parse::<Address>()
+                .unwrap()
+        );
+    }
+}
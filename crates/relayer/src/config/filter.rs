@@ -19,6 +19,8 @@ pub struct PacketFilter {
     pub channel_policy: ChannelPolicy,
     #[serde(default)]
     pub min_fees: HashMap<ChannelFilterMatch, FeePolicy>,
+    #[serde(default)]
+    pub min_transfer_amounts: HashMap<ChannelFilterMatch, TransferPolicy>,
 }
 
 impl Default for PacketFilter {
@@ -27,6 +29,7 @@ impl Default for PacketFilter {
         Self {
             channel_policy: ChannelPolicy::default(),
             min_fees: HashMap::new(),
+            min_transfer_amounts: HashMap::new(),
         }
     }
 }
@@ -39,6 +42,7 @@ impl PacketFilter {
         Self {
             channel_policy,
             min_fees,
+            min_transfer_amounts: HashMap::new(),
         }
     }
 
@@ -89,6 +93,31 @@ impl FeePolicy {
     }
 }
 
+/// Represents the policy used to filter ICS-20 packets by the amount of tokens they transfer.
+/// Packets transferring less than the minimum amount of their denom are not relayed
+/// automatically, the denoms without a minimum amount are always relayed.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransferPolicy {
+    amounts: Vec<MinFee>,
+}
+
+impl TransferPolicy {
+    pub fn new(amounts: Vec<MinFee>) -> Self {
+        Self { amounts }
+    }
+
+    pub fn should_relay(&self, token: &RawCoin) -> bool {
+        self.amounts
+            .iter()
+            .filter(|min| {
+                min.denom
+                    .as_ref()
+                    .map_or(true, |denom| *denom == token.denom)
+            })
+            .all(|min| min.is_enough(token))
+    }
+}
+
 /// Represents the minimum fee authorized when filtering.
 /// If no denom is specified, any denom is allowed.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -600,6 +629,21 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn transfer_policy_min_amounts() {
+        let policy = TransferPolicy::new(vec![
+            MinFee::new(1000, Some("uatom".to_owned())),
+            MinFee::new(10, None),
+        ]);
+        let coin = |amount: u64, denom: &str| RawCoin::new(denom.to_owned(), amount);
+
+        assert!(policy.should_relay(&coin(1000, "uatom")));
+        assert!(!policy.should_relay(&coin(999, "uatom")));
+        assert!(policy.should_relay(&coin(10, "stake")));
+        assert!(!policy.should_relay(&coin(9, "stake")));
+        assert!(TransferPolicy::default().should_relay(&coin(0, "stake")));
+    }
+
     #[test]
     fn to_string_wildcards() {
         let wildcard = "ica*".parse::<Wildcard>().unwrap();
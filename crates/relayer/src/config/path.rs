@@ -0,0 +1,122 @@
+//! Paths declared in the `[[paths]]` sections of the configuration.
+//!
+//! The workers of a declared path are spawned at startup, whether or not there is
+//! anything to relay on it yet, instead of waiting for the scan of the chains to
+//! discover the path.
+
+use core::fmt::{Display, Error as FmtError, Formatter};
+
+use ibc_relayer_types::core::ics24_host::identifier::{
+    ChainId, ChannelId, ClientId, ConnectionId, PortId,
+};
+use serde_derive::{Deserialize, Serialize};
+
+/// A channel of a chain, along with the connection and the client it is built upon
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PathConfig {
+    pub chain: ChainId,
+    pub client: ClientId,
+    pub connection: ConnectionId,
+    #[serde(default = "default::port")]
+    pub port: PortId,
+    pub channel: ChannelId,
+    /// Maximum number of batches of packet messages relayed at once by each of the
+    /// packet workers of the channel
+    #[serde(default = "default::concurrency")]
+    pub concurrency: usize,
+}
+
+impl PathConfig {
+    /// Whether the path is the channel `channel` of the port `port` on `chain`
+    pub fn is_channel(&self, chain: &ChainId, port: &PortId, channel: &ChannelId) -> bool {
+        &self.chain == chain && &self.port == port && &self.channel == channel
+    }
+}
+
+impl Display for PathConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        write!(
+            f,
+            "{}:{}/{}/{}/{}",
+            self.chain, self.client, self.connection, self.port, self.channel
+        )
+    }
+}
+
+/// Defaults for various fields
+pub mod default {
+    use super::*;
+
+    pub fn port() -> PortId {
+        PortId::transfer()
+    }
+
+    pub fn concurrency() -> usize {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ibc_relayer_types::core::ics24_host::identifier::PortId;
+
+    use super::PathConfig;
+
+    #[test]
+    fn parse_path_config() {
+        let path: PathConfig = toml::from_str(
+            r#"
+            chain = "ibc-0"
+            client = "07-tendermint-0"
+            connection = "connection-0"
+            channel = "channel-0"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(path.port.as_str(), "transfer");
+        assert_eq!(path.concurrency, 1);
+        assert_eq!(
+            path.to_string(),
+            "ibc-0:07-tendermint-0/connection-0/transfer/channel-0"
+        );
+
+        let unknown_field = toml::from_str::<PathConfig>(
+            r#"
+            chain = "ibc-0"
+            client = "07-tendermint-0"
+            connection = "connection-0"
+            channel = "channel-0"
+            counterparty = "ibc-1"
+            "#,
+        );
+        assert!(unknown_field.is_err());
+    }
+
+    #[test]
+    fn parse_path_concurrency() {
+        let path: PathConfig = toml::from_str(
+            r#"
+            chain = "ibc-0"
+            client = "07-tendermint-0"
+            connection = "connection-0"
+            channel = "channel-0"
+            concurrency = 4
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(path.concurrency, 4);
+        assert!(path.is_channel(
+            &"ibc-0".parse().unwrap(),
+            &PortId::transfer(),
+            &"channel-0".parse().unwrap()
+        ));
+        assert!(!path.is_channel(
+            &"ibc-1".parse().unwrap(),
+            &PortId::transfer(),
+            &"channel-0".parse().unwrap()
+        ));
+    }
+}
@@ -0,0 +1,82 @@
+//! Address of a CKB JSON-RPC endpoint.
+
+use core::fmt;
+use core::str::FromStr;
+use std::path::PathBuf;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use tendermint_rpc::Url;
+
+const UNIX_SCHEME: &str = "unix://";
+
+/// Either an HTTP URL, or the path of a unix domain socket written as
+/// `unix:///path/to/ckb.sock` for relayers running on the same host as their node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RpcUrl {
+    Http(Url),
+    Unix(PathBuf),
+}
+
+impl FromStr for RpcUrl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix(UNIX_SCHEME) {
+            Some("") => Err(format!("missing socket path in `{s}`")),
+            Some(path) => Ok(RpcUrl::Unix(PathBuf::from(path))),
+            None => Url::from_str(s)
+                .map(RpcUrl::Http)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for RpcUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcUrl::Http(url) => write!(f, "{url}"),
+            RpcUrl::Unix(path) => write!(f, "{UNIX_SCHEME}{}", path.display()),
+        }
+    }
+}
+
+impl From<Url> for RpcUrl {
+    fn from(url: Url) -> Self {
+        RpcUrl::Http(url)
+    }
+}
+
+impl Serialize for RpcUrl {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for RpcUrl {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::str::FromStr;
+
+    use super::RpcUrl;
+
+    #[test]
+    fn test_parse_rpc_url() {
+        let unix = RpcUrl::from_str("unix:///var/run/ckb.sock").unwrap();
+        assert_eq!(unix, RpcUrl::Unix(PathBuf::from("/var/run/ckb.sock")));
+        assert_eq!(unix.to_string(), "unix:///var/run/ckb.sock");
+
+        let http = RpcUrl::from_str("http://127.0.0.1:8114").unwrap();
+        assert!(matches!(http, RpcUrl::Http(_)));
+        assert_eq!(RpcUrl::from_str(&http.to_string()).unwrap(), http);
+
+        assert!(RpcUrl::from_str("unix://").is_err());
+        assert!(RpcUrl::from_str("ftp://127.0.0.1").is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Feed of the decisions taken by the workers about the packets they handle.
+//!
+//! Beyond the IBC events themselves, the feed tells whether a packet was relayed,
+//! skipped by a filter, deferred until the connection delay elapses, or quarantined
+//! after failing repeatedly, along with the reason. The latest decisions are kept in
+//! a bounded buffer, so that late subscribers can catch up on them.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, PortId};
+
+/// Number of decisions kept for the late subscribers
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecisionKind {
+    /// The message was submitted to the target chain
+    Relayed,
+    /// The packet was filtered out, or was already relayed, and is not relayed automatically
+    Skipped,
+    /// The message waits for the connection delay to elapse
+    Deferred,
+    /// The message was given up on after failing repeatedly
+    Quarantined,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Decision {
+    pub kind: DecisionKind,
+    /// Source chain of the packet
+    pub chain_id: ChainId,
+    pub counterparty_chain_id: ChainId,
+    pub port_id: PortId,
+    pub channel_id: ChannelId,
+    /// Sequence of the packet, if the decision is about a packet
+    pub sequence: Option<Sequence>,
+    pub reason: String,
+    /// Seconds since the Unix epoch at which the decision was taken
+    pub timestamp: u64,
+}
+
+impl Decision {
+    pub fn new(
+        kind: DecisionKind,
+        chain_id: ChainId,
+        counterparty_chain_id: ChainId,
+        port_id: PortId,
+        channel_id: ChannelId,
+        sequence: Option<Sequence>,
+        reason: impl Into<String>,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+
+        Self {
+            kind,
+            chain_id,
+            counterparty_chain_id,
+            port_id,
+            channel_id,
+            sequence,
+            reason: reason.into(),
+            timestamp,
+        }
+    }
+}
+
+/// Subscription to a [`DecisionFeed`]
+pub struct DecisionSubscription {
+    /// Decisions taken before subscribing, the oldest first
+    pub backlog: Vec<Decision>,
+    /// Decisions taken from now on
+    pub receiver: Receiver<Decision>,
+}
+
+struct FeedState {
+    buffer: VecDeque<Decision>,
+    subscribers: Vec<Sender<Decision>>,
+}
+
+pub struct DecisionFeed {
+    capacity: usize,
+    state: Mutex<FeedState>,
+}
+
+impl DecisionFeed {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(FeedState {
+                buffer: VecDeque::with_capacity(capacity),
+                subscribers: vec![],
+            }),
+        }
+    }
+
+    /// Record the decision and forward it to the subscribers. The subscribers lagging
+    /// by more than the capacity of the feed miss it, the disconnected ones are dropped.
+    pub fn publish(&self, decision: Decision) {
+        let mut state = self.state.lock().unwrap();
+
+        state.subscribers.retain(|subscriber| {
+            !matches!(
+                subscriber.try_send(decision.clone()),
+                Err(TrySendError::Disconnected(_))
+            )
+        });
+
+        if state.buffer.len() == self.capacity {
+            state.buffer.pop_front();
+        }
+        state.buffer.push_back(decision);
+    }
+
+    pub fn subscribe(&self) -> DecisionSubscription {
+        let mut state = self.state.lock().unwrap();
+
+        let (sender, receiver) = crossbeam_channel::bounded(self.capacity);
+        state.subscribers.push(sender);
+
+        DecisionSubscription {
+            backlog: state.buffer.iter().cloned().collect(),
+            receiver,
+        }
+    }
+
+    /// The latest decisions, the oldest first
+    pub fn recent(&self) -> Vec<Decision> {
+        self.state.lock().unwrap().buffer.iter().cloned().collect()
+    }
+}
+
+static GLOBAL_FEED: Lazy<DecisionFeed> = Lazy::new(|| DecisionFeed::new(DEFAULT_CAPACITY));
+
+/// The feed the workers publish their decisions to
+pub fn global() -> &'static DecisionFeed {
+    &GLOBAL_FEED
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+    use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, PortId};
+
+    use super::{Decision, DecisionFeed, DecisionKind};
+
+    fn decision(sequence: u64) -> Decision {
+        Decision::new(
+            DecisionKind::Relayed,
+            ChainId::from_string("chain-a"),
+            ChainId::from_string("chain-b"),
+            PortId::transfer(),
+            ChannelId::from_str("channel-0").unwrap(),
+            Some(Sequence::from(sequence)),
+            "submitted",
+        )
+    }
+
+    #[test]
+    fn test_feed_keeps_latest_decisions() {
+        let decisions = (1..=3).map(decision).collect::<Vec<_>>();
+        let feed = DecisionFeed::new(2);
+        feed.publish(decisions[0].clone());
+
+        let subscription = feed.subscribe();
+        feed.publish(decisions[1].clone());
+        feed.publish(decisions[2].clone());
+
+        assert_eq!(subscription.backlog, decisions[..1]);
+        let received = subscription.receiver.try_iter().collect::<Vec<_>>();
+        assert_eq!(received, decisions[1..]);
+        assert_eq!(feed.recent(), decisions[1..]);
+    }
+
+    #[test]
+    fn test_feed_drops_disconnected_subscribers() {
+        let decisions = (1..=3).map(decision).collect::<Vec<_>>();
+        let feed = DecisionFeed::new(2);
+        drop(feed.subscribe());
+        let subscription = feed.subscribe();
+
+        for decision in decisions.iter() {
+            feed.publish(decision.clone());
+        }
+
+        assert_eq!(feed.state.lock().unwrap().subscribers.len(), 1);
+        // The subscriber misses the decisions beyond the capacity of its channel
+        let received = subscription.receiver.try_iter().collect::<Vec<_>>();
+        assert_eq!(received, decisions[..2]);
+    }
+}
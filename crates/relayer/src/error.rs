@@ -37,6 +37,7 @@ use ibc_relayer_types::{
     },
     proofs::ProofError,
     relayer::ics18_relayer::error as relayer_error,
+    Height,
 };
 
 use crate::chain::cosmos::version;
@@ -616,14 +617,59 @@ define_error! {
             {s: String}
             |e| {format_args!("Cannot convert {} as a ckb port id", e.s)},
 
+        CkbPortNotBound
+            {chain_id: ChainId, port_id: String}
+            |e| {format_args!("port {} is not bound on chain {}, bind it with `ckb port bind` before opening channels on it", e.port_id, e.chain_id)},
+
+        CkbPortBindings
+            {path: String, reason: String}
+            |e| {format_args!("failed to access the port bindings {}: {}", e.path, e.reason)},
+
         CkbConnIdInvalid
             {s: String}
             |e| {format_args!("Cannot convert {} as a ckb conn id", e.s)},
 
+        CkbConnIdStale
+            {connection_id: String, next_connection_number: u64}
+            |e| {format_args!("Connection id {} is not below the next connection number {} of the ckb connections cell", e.connection_id, e.next_connection_number)},
+
         CkbClientIdInvalid
             {s: String}
             |e| {format_args!("Cannot convert {} as a ckb client id", e.s)},
 
+        CkbTendermintClientInvalid
+            {client_id: String, reason: String}
+            |e| {format_args!("invalid cell of Tendermint client {} on ckb: {}", e.client_id, e.reason)},
+
+        CkbTendermintClientNotFound
+            {client_id: String}
+            |e| {format_args!("no live cell of Tendermint client {} on ckb", e.client_id)},
+
+        CkbTendermintHeaderUntrusted
+            {client_id: String, trusted_height: Height, latest_height: Height}
+            |e| {
+                format_args!("header for Tendermint client {} is trusted from height {} whose consensus state is not kept, its latest height is {}",
+                    e.client_id, e.trusted_height, e.latest_height)
+            },
+
+        CkbConsensusStateNotFound
+            {client_id: String, height: Height}
+            |e| { format_args!("no consensus state of client {} at height {} on ckb", e.client_id, e.height) },
+
+        CkbConsensusStatePruned
+            {client_id: String, height: Height, earliest_height: Height}
+            |e| {
+                format_args!("consensus state of client {} at height {} has been pruned on ckb, the earliest one kept is at height {}",
+                    e.client_id, e.height, e.earliest_height)
+            },
+
+        CkbProofHeightNotLatest
+            {height: Height, latest_height: Height}
+            |e| {
+                format_args!("cannot prove the ibc cells of ckb at height {}, only their state at the latest height {} is known",
+                    e.height, e.latest_height)
+            },
+
         CkbNoneWitness
             |_| { "Trying to get witness to decode an object but no witness in the tx" },
 
@@ -635,6 +681,112 @@ define_error! {
 
         EmptyConnectionHops
         |_| {"empty connection hops"},
+
+        CkbPacketSequenceMismatch
+            {expected: u64, actual: u64}
+            |e| {format_args!("Packet sequence {} mismatches the next sequence {} of the ordered channel", e.actual, e.expected)},
+
+        CkbUnorderedPacketTimeout
+            {channel_id: String}
+            |e| {format_args!("Cannot time out packets on the unordered channel {}", e.channel_id)},
+
+        CkbTxDeadCell
+            {tx_hash: String, reason: String}
+            |e| {format_args!("ckb transaction {} spends consumed cells: {}", e.tx_hash, e.reason)},
+
+        CkbTxInsufficientCapacity
+            {tx_hash: String, reason: String}
+            |e| {format_args!("ckb transaction {} has insufficient cell capacity: {}", e.tx_hash, e.reason)},
+
+        CkbTxScriptFailure
+            {
+                tx_hash: String,
+                exit_code: i8,
+                // script group of the failed script, e.g. "Inputs[0].Lock"
+                script: String,
+                // code hash and hash type of the failed script
+                contract: Option<String>,
+                // cycles estimated by the node for the whole transaction
+                cycles: Option<u64>,
+                reason: String,
+            }
+            |e| {
+                format_args!("script {}{} of ckb transaction {} failed with exit code {}{}: {}",
+                    e.script,
+                    e.contract.as_ref().map(|contract| format!(" of contract {contract}")).unwrap_or_default(),
+                    e.tx_hash,
+                    e.exit_code,
+                    e.cycles.map(|cycles| format!(" ({cycles} cycles estimated)")).unwrap_or_default(),
+                    e.reason)
+            },
+
+        CkbTxCommitTimeout
+            {tx_hash: String, timeout: Duration}
+            |e| {format_args!("timeout after {} waiting for ckb transaction {} to be committed", format_duration(e.timeout), e.tx_hash)},
+
+        CkbTxRejected
+            {tx_hash: String, reason: String}
+            |e| {format_args!("ckb transaction {} is rejected: {}", e.tx_hash, e.reason)},
+
+        CkbMultiClientCellsCountMismatch
+            {expected: u8, actual: usize}
+            |e| {format_args!("multi-client cells are corrupted: expected {} cells, found {}", e.expected, e.actual)},
+
+        CkbMultiClientDuplicateInfo
+            {first: String, second: String}
+            |e| {format_args!("multi-client cells are corrupted: more than one client info, first: {}, second: {}", e.first, e.second)},
+
+        CkbMultiClientInvalidData
+            {data: String}
+            |e| {format_args!("multi-client cells are corrupted: invalid cell data 0x{}", e.data)},
+
+        CkbMultiClientInfoMissing
+            |_| { "multi-client cells are corrupted: client info cell not found" },
+
+        CkbMultiClientUpdateCellsMissing
+            {oldest_id: u8, latest_id: u8}
+            |e| {format_args!("multi-client cells are corrupted: oldest client {} or latest client {} not found", e.oldest_id, e.latest_id)},
+
+        SubmissionDisabled
+            {chain_id: ChainId}
+            |e| {format_args!("transaction submission is disabled for chain {}, it is only monitored", e.chain_id)},
+
+        LowBalance
+            {
+                chain_id: ChainId,
+                balance: String,
+                min_balance: u64,
+            }
+            |e| {
+                format_args!("balance {} of the relayer account on chain {} is below the minimum of {}",
+                    e.balance, e.chain_id, e.min_balance)
+            },
+
+        CkbIndexerLagging
+            {
+                chain_id: ChainId,
+                indexer_tip: u64,
+                node_tip: u64,
+            }
+            |e| {
+                format_args!("the indexer of chain {} is at block {} while the node is at block {}",
+                    e.chain_id, e.indexer_tip, e.node_tip)
+            },
+
+        CkbLightClientUnsupported
+            { method: String }
+            |e| { format_args!("the ckb light client does not serve `{}`", e.method) },
+
+        CkbIncompatibleContracts
+            {
+                chain_id: ChainId,
+                version: String,
+                requirements: String,
+            }
+            |e| {
+                format_args!("the IBC contracts of chain {} at version {} do not meet the compatibility requirements {}",
+                    e.chain_id, e.version, e.requirements)
+            },
     }
 }
 
@@ -662,6 +814,69 @@ impl Error {
     pub fn other<T: ToString>(error: T) -> Error {
         Error::other_error(error.to_string())
     }
+
+    /// Classify the reason reported by a CKB node for rejecting a transaction, which is
+    /// either the message of a failed `send_transaction` call or the reason attached to
+    /// a rejected transaction status.
+    pub fn ckb_tx_rejection(tx_hash: String, reason: String) -> Error {
+        if reason.contains("Dead(OutPoint(") {
+            Error::ckb_tx_dead_cell(tx_hash, reason)
+        } else if reason.contains("InsufficientCellCapacity") {
+            Error::ckb_tx_insufficient_capacity(tx_hash, reason)
+        } else if let Some(exit_code) = parse_script_exit_code_in_error_message(&reason) {
+            let script = parse_script_group_in_error_message(&reason)
+                .unwrap_or_else(|| "unknown".to_owned());
+            Error::ckb_tx_script_failure(tx_hash, exit_code, script, None, None, reason)
+        } else {
+            Error::ckb_tx_rejected(tx_hash, reason)
+        }
+    }
+
+    /// Turn the error of a failed `send_transaction` call into a rejection of the given
+    /// transaction. Errors which don't come from the CKB node are returned as is.
+    pub fn ckb_send_tx_failure(tx_hash: String, error: Error) -> Error {
+        match error.detail() {
+            ErrorDetail::RpcResponse(e) => Error::ckb_tx_rejection(tx_hash, e.detail.clone()),
+            _ => error,
+        }
+    }
+
+    pub fn is_ckb_dead_cell_error(&self) -> bool {
+        matches!(self.detail(), ErrorDetail::CkbTxDeadCell(_))
+    }
+
+    /// Whether the indexer of the CKB chain lags too far behind the node for the cells
+    /// it returns to be trusted, which is resolved by retrying once it caught up
+    pub fn is_ckb_indexer_lagging(&self) -> bool {
+        matches!(self.detail(), ErrorDetail::CkbIndexerLagging(_))
+    }
+
+    /// Whether the indexer of the CKB chain rejected the cursor of a paginated query,
+    /// e.g. because it restarted since it returned the cursor, which is resolved by
+    /// scanning again from the first cell
+    pub fn is_ckb_indexer_cursor_invalid(&self) -> bool {
+        match self.detail() {
+            ErrorDetail::RpcResponse(e) => e.detail.to_lowercase().contains("cursor"),
+            _ => false,
+        }
+    }
+
+    pub fn is_ckb_script_failure(&self) -> bool {
+        matches!(self.detail(), ErrorDetail::CkbTxScriptFailure(_))
+    }
+
+    /// Whether the on-chain cells of the Ethereum multi-client are corrupted, which is
+    /// only recovered by re-creating the whole set of cells
+    pub fn is_ckb_multi_client_corrupted(&self) -> bool {
+        matches!(
+            self.detail(),
+            ErrorDetail::CkbMultiClientCellsCountMismatch(_)
+                | ErrorDetail::CkbMultiClientDuplicateInfo(_)
+                | ErrorDetail::CkbMultiClientInvalidData(_)
+                | ErrorDetail::CkbMultiClientInfoMissing(_)
+                | ErrorDetail::CkbMultiClientUpdateCellsMissing(_)
+        )
+    }
 }
 
 impl GrpcStatusSubdetail {
@@ -755,6 +970,22 @@ fn parse_sequences_in_mismatch_error_message(message: &str) -> Option<(u64, u64)
     }
 }
 
+/// Extracts the exit code of a failed script from a CKB verification error, of the form
+/// "ValidationFailure: see error code C on page ...".
+fn parse_script_exit_code_in_error_message(message: &str) -> Option<i8> {
+    let re = Regex::new(r#"ValidationFailure: see (the )?error code (?P<code>-?\d+)"#).unwrap();
+    re.captures(message)
+        .and_then(|captures| captures["code"].parse().ok())
+}
+
+/// Extracts the script group of a failed script from a CKB verification error, of the
+/// form "source: Inputs[I].Lock", "source: Inputs[I].Type" or "source: Outputs[I].Type".
+fn parse_script_group_in_error_message(message: &str) -> Option<String> {
+    let re = Regex::new(r#"source: (?P<group>(Inputs|Outputs)\[\d+\]\.(Lock|Type))"#).unwrap();
+    re.captures(message)
+        .map(|captures| captures["group"].to_owned())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -824,4 +1055,45 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    fn test_ckb_tx_rejection() {
+        let tx_hash = "0x01".to_string();
+
+        let dead = Error::ckb_tx_rejection(
+            tx_hash.clone(),
+            "TransactionFailedToResolve: Resolve failed Dead(OutPoint(0x02))".to_string(),
+        );
+        assert!(dead.is_ckb_dead_cell_error());
+
+        let capacity = Error::ckb_tx_rejection(
+            tx_hash.clone(),
+            "TransactionFailedToVerify: Verification failed Transaction(InsufficientCellCapacity(Outputs[0]))".to_string(),
+        );
+        assert!(matches!(
+            capacity.detail(),
+            ErrorDetail::CkbTxInsufficientCapacity(_)
+        ));
+
+        let script = Error::ckb_tx_rejection(
+            tx_hash.clone(),
+            "TransactionFailedToVerify: Verification failed Script(TransactionScriptError { source: Inputs[0].Lock, cause: ValidationFailure: see error code -31 on page https://nervosnetwork.github.io/ckb-script-error-codes/) })".to_string(),
+        );
+        match script.detail() {
+            ErrorDetail::CkbTxScriptFailure(e) => {
+                assert_eq!(e.exit_code, -31);
+                assert_eq!(e.script, "Inputs[0].Lock");
+            }
+            _ => panic!("expect a script failure, got {script}"),
+        }
+
+        let other = Error::ckb_tx_rejection(
+            tx_hash.clone(),
+            "PoolRejectedDuplicatedTransaction".to_string(),
+        );
+        assert!(matches!(other.detail(), ErrorDetail::CkbTxRejected(_)));
+
+        let transport = Error::ckb_send_tx_failure(tx_hash, Error::other("connection refused"));
+        assert!(matches!(transport.detail(), ErrorDetail::OtherError(_)));
+    }
 }
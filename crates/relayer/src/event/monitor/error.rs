@@ -49,6 +49,22 @@ define_error! {
             [ TraceError<RpcError> ]
             |_| { "RPC error" },
 
+        NodeUnreachable
+            { chain_id: ChainId, reason: String }
+            |e| { format!("failed to reach the node of chain {0}: {1}", e.chain_id, e.reason) },
+
+        Restarted
+            { chain_id: ChainId, height: u64 }
+            |e| { format!("event monitor of chain {0} restarted, events from height {1} may have been missed", e.chain_id, e.height) },
+
+        EventsRetracted
+            { chain_id: ChainId, tx_hashes: Vec<String> }
+            |e| { format!("events of transactions {0:?} of chain {1} are retracted, their blocks have been orphaned", e.tx_hashes, e.chain_id) },
+
+        ChainReorganized
+            { chain_id: ChainId, height: u64 }
+            |e| { format!("chain {0} reorganized after height {1}, the states scanned above it are stale", e.chain_id, e.height) },
+
         Others
             { reason: String }
             |e| { format!("uncategorized error: {0}", e.reason) },
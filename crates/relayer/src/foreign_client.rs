@@ -449,6 +449,22 @@ impl<DstChain: ChainHandle, SrcChain: ChainHandle> ForeignClient<DstChain, SrcCh
         fields(client = %self)
     )]
     pub fn upgrade(&self, src_upgrade_height: Height) -> Result<Vec<IbcEvent>, ForeignClientError> {
+        let capabilities = self.src_chain.capabilities().map_err(|e| {
+            ForeignClientError::client_upgrade(
+                self.id.clone(),
+                self.src_chain.id(),
+                "failed while querying the capabilities of the chain".to_string(),
+                e,
+            )
+        })?;
+        if !capabilities.client_upgrades {
+            return Err(ForeignClientError::client_upgrade_no_source(
+                self.id.clone(),
+                self.src_chain.id(),
+                "the chain does not support client upgrades".to_string(),
+            ));
+        }
+
         let msgs = self
             .build_update_client_with_trusted(src_upgrade_height, None)
             .map_err(|_| {
@@ -1391,7 +1407,7 @@ impl<DstChain: ChainHandle, SrcChain: ChainHandle> ForeignClient<DstChain, SrcCh
         skip_all,
         fields(client = %self)
     )]
-    fn fetch_consensus_state_heights(&self) -> Result<Vec<Height>, ForeignClientError> {
+    pub(crate) fn fetch_consensus_state_heights(&self) -> Result<Vec<Height>, ForeignClientError> {
         let mut heights = self
             .dst_chain
             .query_consensus_state_heights(QueryConsensusStateHeightsRequest {
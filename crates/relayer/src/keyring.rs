@@ -2,17 +2,26 @@ pub mod errors;
 pub use any_signing_key_pair::AnySigningKeyPair;
 pub use ed25519_key_pair::Ed25519KeyPair;
 pub use key_type::KeyType;
+pub use ledger::{connect_eth_ledger, LedgerSigner, CKB_HD_PATH};
+pub use remote::{RemoteEthSigner, RemoteSigner};
 pub use secp256k1_key_pair::Secp256k1KeyPair;
+pub use signer::{CkbTxSigner, DigestSigner, EthSigner, EthSignerError};
 pub use signing_key_pair::{SigningKeyPair, SigningKeyPairSized};
 
 mod any_signing_key_pair;
+mod ecdsa;
 mod ed25519_key_pair;
 mod key_type;
 mod key_utils;
+mod ledger;
 mod pub_key;
+mod remote;
 mod secp256k1_key_pair;
+mod signer;
 mod signing_key_pair;
 
+pub(crate) use key_utils::encode_bech32;
+
 use alloc::collections::btree_map::BTreeMap as HashMap;
 use std::ffi::OsStr;
 use std::fs::{self, File};
@@ -280,16 +289,21 @@ impl KeyRing<Ed25519KeyPair> {
     }
 }
 
-pub fn list_keys(config: &ChainConfig) -> Result<Vec<(String, AnySigningKeyPair)>, Error> {
-    let account_prefix = match config.r#type() {
+/// Prefix of the Bech32 encoded accounts of the keys of the chain
+pub fn account_prefix(config: &ChainConfig) -> &str {
+    match config.r#type() {
         ChainType::CosmosSdk => &config.cosmos().account_prefix,
         ChainType::Eth => "eth",
         ChainType::Axon => "axon",
         ChainType::Ckb => "ckb",
         ChainType::Ckb4Ibc => "ckb4ibc",
-    };
+        ChainType::Evm => "evm",
+    }
+}
+
+pub fn list_keys(config: &ChainConfig) -> Result<Vec<(String, AnySigningKeyPair)>, Error> {
     let keys = {
-        let keyring = KeyRing::new_secp256k1(Store::Test, account_prefix, config.id())?;
+        let keyring = KeyRing::new_secp256k1(Store::Test, account_prefix(config), config.id())?;
         keyring
             .keys()?
             .into_iter()
@@ -1,6 +1,6 @@
 use serde::Serialize;
 
-use super::{Ed25519KeyPair, KeyType, Secp256k1KeyPair, SigningKeyPair};
+use super::{errors::Error, Ed25519KeyPair, KeyType, Secp256k1KeyPair, SigningKeyPair};
 
 #[derive(Clone, Debug, Serialize)]
 #[serde(untagged)]
@@ -24,6 +24,21 @@ impl AnySigningKeyPair {
         }
     }
 
+    /// Serialized public key, compressed for secp256k1 keys.
+    pub fn public_key(&self) -> Vec<u8> {
+        match self {
+            Self::Secp256k1(key_pair) => key_pair.public_key.serialize().to_vec(),
+            Self::Ed25519(key_pair) => key_pair.public_key().to_vec(),
+        }
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            Self::Secp256k1(key_pair) => key_pair.sign(message),
+            Self::Ed25519(key_pair) => key_pair.sign(message),
+        }
+    }
+
     pub fn downcast<T: Clone + 'static>(&self) -> Option<T> {
         match self {
             Self::Secp256k1(key_pair) => key_pair.as_any(),
@@ -0,0 +1,83 @@
+//! Signing of the secp256k1 digests of the keyring keys.
+//!
+//! The digests are signed with libsecp256k1 by default, and with the pure-Rust
+//! `k256` crate when the `k256` feature is enabled. Both derive the nonce with
+//! RFC 6979 and normalize `s`, so they produce the same signatures.
+//!
+//! The feature only covers this signing: the keys of the keyring are still
+//! `secp256k1` keys, the signers of the observed transactions are still recovered
+//! with libsecp256k1, and the CKB SDK links it as well, so the binary is never
+//! free of libsecp256k1.
+
+use secp256k1::SecretKey;
+
+use super::errors::Error;
+
+/// Signature of the digest, made of `r` and `s`
+pub fn sign_compact(private_key: &SecretKey, digest: &[u8; 32]) -> Result<[u8; 64], Error> {
+    let signature = sign_recoverable(private_key, digest)?;
+    let mut compact = [0u8; 64];
+    compact.copy_from_slice(&signature[..64]);
+    Ok(compact)
+}
+
+/// Recoverable signature of the digest, made of `r`, `s` and the recovery id
+#[cfg(not(feature = "k256"))]
+pub fn sign_recoverable(private_key: &SecretKey, digest: &[u8; 32]) -> Result<[u8; 65], Error> {
+    use secp256k1::{Message, Secp256k1};
+
+    let message = Message::from_slice(digest)?;
+    let (recovery_id, data) = Secp256k1::signing_only()
+        .sign_ecdsa_recoverable(&message, private_key)
+        .serialize_compact();
+    let mut signature = [0u8; 65];
+    signature[..64].copy_from_slice(&data);
+    signature[64] = recovery_id.to_i32() as u8;
+    Ok(signature)
+}
+
+/// Recoverable signature of the digest, made of `r`, `s` and the recovery id
+#[cfg(feature = "k256")]
+pub fn sign_recoverable(private_key: &SecretKey, digest: &[u8; 32]) -> Result<[u8; 65], Error> {
+    use ethers::prelude::k256::ecdsa::SigningKey;
+
+    let signing_key = SigningKey::from_bytes(&private_key.secret_bytes().into())
+        .map_err(|e| Error::secp256k1(e.to_string()))?;
+    let (data, recovery_id) = signing_key
+        .sign_prehash_recoverable(digest)
+        .map_err(|e| Error::secp256k1(e.to_string()))?;
+    let mut signature = [0u8; 65];
+    signature[..64].copy_from_slice(&data.to_bytes());
+    signature[64] = recovery_id.to_byte();
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+    use secp256k1::{Message, Secp256k1, SecretKey};
+
+    use super::{sign_compact, sign_recoverable};
+
+    #[test]
+    fn test_signatures_match_libsecp256k1() {
+        let secp = Secp256k1::new();
+        for i in 1u8..20 {
+            let private_key = SecretKey::from_slice(&[i; 32]).unwrap();
+            let digest = [i.wrapping_mul(7); 32];
+            let message = Message::from_slice(&digest).unwrap();
+
+            let signature = sign_recoverable(&private_key, &digest).unwrap();
+            let recovery_id = RecoveryId::from_i32(signature[64] as i32).unwrap();
+            let expected = secp.sign_ecdsa_recoverable(&message, &private_key);
+            assert_eq!(
+                RecoverableSignature::from_compact(&signature[..64], recovery_id).unwrap(),
+                expected
+            );
+            assert_eq!(
+                sign_compact(&private_key, &digest).unwrap(),
+                secp.sign_ecdsa(&message, &private_key).serialize_compact()
+            );
+        }
+    }
+}
@@ -104,6 +104,9 @@ impl Ed25519KeyPair {
             address_type,
         })
     }
+    pub fn public_key(&self) -> [u8; 32] {
+        self.keypair.public.to_bytes()
+    }
 }
 
 impl SigningKeyPair for Ed25519KeyPair {
@@ -144,6 +144,14 @@ define_error! {
             { message: String }
             |e| { format!("secp256k1 error: {}", e.message) },
 
+        Ledger
+            { reason: String }
+            |e| { format!("Ledger device error: {}", e.reason) },
+
+        RemoteSigner
+            { reason: String }
+            |e| { format!("remote signer error: {}", e.reason) },
+
         UnsupportedAddressType
           {
               address_type: AddressType,
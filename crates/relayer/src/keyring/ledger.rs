@@ -0,0 +1,131 @@
+//! Ledger devices holding the keys of the relayer accounts.
+//!
+//! The CKB transactions are signed by the Nervos app, which signs the sighash digests
+//! once hash signing is allowed in its settings. The Axon transactions are signed by
+//! the Ethereum app through the Ledger signer of `ethers`.
+
+use std::sync::Mutex;
+
+use coins_ledger::common::{APDUCommand, APDUData};
+use coins_ledger::transports::{Ledger, LedgerAsync};
+use ethers::signers::{HDPath, Ledger as EthLedger};
+use futures::executor::block_on;
+use secp256k1::PublicKey;
+
+use super::errors::Error;
+use super::signer::{DigestSigner, EthSigner};
+
+/// Class of the instructions of the Nervos app
+const CLA: u8 = 0x80;
+const INS_GET_PUBLIC_KEY: u8 = 0x02;
+const INS_SIGN_MESSAGE_HASH: u8 = 0x07;
+
+/// The BIP-32 path is sent in the first packet, the data to sign in the last one
+const P1_FIRST: u8 = 0x00;
+const P1_LAST: u8 = 0x81;
+
+const RETCODE_OK: u16 = 0x9000;
+
+const HARDENED: u32 = 0x8000_0000;
+
+/// Path of the first account of the Nervos app, `m/44'/309'/0'/0/0`
+pub const CKB_HD_PATH: [u32; 5] = [44 | HARDENED, 309 | HARDENED, HARDENED, 0, 0];
+
+/// Key of an account of the Nervos app on a Ledger device connected over USB
+pub struct LedgerSigner {
+    transport: Mutex<Ledger>,
+    path: Vec<u32>,
+}
+
+impl LedgerSigner {
+    pub fn connect(path: &[u32]) -> Result<Self, Error> {
+        let transport = block_on(Ledger::init()).map_err(ledger_error)?;
+        Ok(Self {
+            transport: Mutex::new(transport),
+            path: path.to_vec(),
+        })
+    }
+
+    fn exchange(&self, ins: u8, p1: u8, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let command = APDUCommand {
+            cla: CLA,
+            ins,
+            p1,
+            p2: 0,
+            data: APDUData::new(&data),
+            response_len: None,
+        };
+        let transport = self
+            .transport
+            .lock()
+            .map_err(|e| Error::ledger(e.to_string()))?;
+        let answer = block_on(transport.exchange(&command)).map_err(ledger_error)?;
+        if answer.retcode() != RETCODE_OK {
+            return Err(Error::ledger(format!(
+                "the Nervos app rejected the request with code {:#06x}",
+                answer.retcode()
+            )));
+        }
+        Ok(answer.data().unwrap_or_default().to_vec())
+    }
+}
+
+impl DigestSigner for LedgerSigner {
+    fn public_key(&self) -> Result<PublicKey, Error> {
+        let response = self.exchange(INS_GET_PUBLIC_KEY, P1_FIRST, encode_path(&self.path))?;
+        // the public key is prefixed with its length
+        let (len, public_key) = response
+            .split_first()
+            .ok_or_else(|| Error::ledger("empty public key".to_owned()))?;
+        let public_key = public_key
+            .get(..*len as usize)
+            .ok_or_else(|| Error::ledger("truncated public key".to_owned()))?;
+        Ok(PublicKey::from_slice(public_key)?)
+    }
+
+    fn sign_digest(&self, digest: &[u8; 32]) -> Result<[u8; 65], Error> {
+        self.exchange(INS_SIGN_MESSAGE_HASH, P1_FIRST, encode_path(&self.path))?;
+        let response = self.exchange(INS_SIGN_MESSAGE_HASH, P1_LAST, digest.to_vec())?;
+        response.as_slice().try_into().map_err(|_| {
+            Error::ledger(format!(
+                "expected a signature of 65 bytes, got {} bytes",
+                response.len()
+            ))
+        })
+    }
+}
+
+/// Signer of the first account of the Ethereum app, for the transactions of `chain_id`
+pub async fn connect_eth_ledger(chain_id: u64) -> Result<EthSigner, Error> {
+    let ledger = EthLedger::new(HDPath::LedgerLive(0), chain_id)
+        .await
+        .map_err(ledger_error)?;
+    Ok(EthSigner::Ledger(ledger))
+}
+
+/// BIP-32 path as expected by the Ledger apps: the number of components followed by
+/// each component in big endian
+fn encode_path(path: &[u32]) -> Vec<u8> {
+    let mut encoded = vec![path.len() as u8];
+    for component in path {
+        encoded.extend(component.to_be_bytes());
+    }
+    encoded
+}
+
+fn ledger_error(e: impl ToString) -> Error {
+    Error::ledger(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_path, CKB_HD_PATH};
+
+    #[test]
+    fn test_encode_path() {
+        assert_eq!(
+            encode_path(&CKB_HD_PATH),
+            [5, 0x80, 0, 0, 44, 0x80, 0, 0x01, 0x35, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+}
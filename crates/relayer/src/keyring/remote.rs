@@ -0,0 +1,430 @@
+//! Remote signing service holding the key of the relayer account.
+//!
+//! The service exposes a small HTTP API, in the spirit of ckb-cli's signer plugins
+//! and web3signer, under the base URL of `signing.remote`:
+//!
+//! - `GET /api/v1/keys/{key_id}` returns `{"public_key": "0x…"}`, the compressed
+//!   secp256k1 public key of the account
+//! - `POST /api/v1/sign/{key_id}` with `{"digest": "0x…"}` returns `{"signature": "0x…"}`,
+//!   the 65-byte recoverable signature of the digest made of `r`, `s` and the recovery id
+//!
+//! The requests timing out, failing to connect or answered with a server error are retried.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers::types::{
+    transaction::eip2718::TypedTransaction, transaction::eip712::Eip712, Address, Signature, H256,
+    U256,
+};
+use ethers::utils::hash_message;
+use reqwest::{Client, Method};
+use secp256k1::PublicKey;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::runtime::Runtime as TokioRuntime;
+
+use super::errors::Error;
+use super::secp256k1_key_pair::{get_address, Secp256k1AddressType};
+use super::signer::DigestSigner;
+use crate::config::{RemoteSignerConfig, SigningConfig};
+
+/// Delay before the first retry of a failed request, growing linearly with the attempts
+const RETRY_DELAY: Duration = Duration::from_millis(200);
+
+#[derive(Serialize)]
+struct SignRequest {
+    digest: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct PublicKeyResponse {
+    public_key: String,
+}
+
+/// Client of the remote signing service of a chain
+pub struct RemoteSigner {
+    rt: Arc<TokioRuntime>,
+    client: Client,
+    url: String,
+    key_id: String,
+    max_retries: u32,
+}
+
+impl RemoteSigner {
+    pub fn new(config: &RemoteSignerConfig, rt: Arc<TokioRuntime>) -> Result<Self, Error> {
+        let client = Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .map_err(|e| Error::remote_signer(e.to_string()))?;
+        Ok(Self {
+            rt,
+            client,
+            url: config.url.to_string().trim_end_matches('/').to_owned(),
+            key_id: config.key_id.clone(),
+            max_retries: config.max_retries,
+        })
+    }
+
+    /// Client of the service of `signing.remote`, which must be configured
+    pub fn from_signing(signing: &SigningConfig, rt: Arc<TokioRuntime>) -> Result<Self, Error> {
+        let config = signing.remote.as_ref().ok_or_else(|| {
+            Error::remote_signer("`signer = \"remote\"` requires a `signing.remote` section".into())
+        })?;
+        Self::new(config, rt)
+    }
+
+    pub async fn fetch_public_key(&self) -> Result<PublicKey, Error> {
+        let response: PublicKeyResponse = self
+            .request(Method::GET, &format!("keys/{}", self.key_id), None)
+            .await?;
+        let public_key = decode_hex("public key", &response.public_key)?;
+        Ok(PublicKey::from_slice(&public_key)?)
+    }
+
+    pub async fn sign(&self, digest: &[u8; 32]) -> Result<[u8; 65], Error> {
+        let request = SignRequest {
+            digest: format!("0x{}", hex::encode(digest)),
+        };
+        let response: SignResponse = self
+            .request(
+                Method::POST,
+                &format!("sign/{}", self.key_id),
+                Some(&request),
+            )
+            .await?;
+        let signature = decode_hex("signature", &response.signature)?;
+        let mut signature: [u8; 65] = signature.as_slice().try_into().map_err(|_| {
+            Error::remote_signer(format!(
+                "expected a signature of 65 bytes, got {} bytes",
+                signature.len()
+            ))
+        })?;
+        // Ethereum style signers add 27 to the recovery id
+        if signature[64] >= 27 {
+            signature[64] -= 27;
+        }
+        Ok(signature)
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&SignRequest>,
+    ) -> Result<T, Error> {
+        let url = format!("{}/api/v1/{path}", self.url);
+        let mut attempt = 0;
+        loop {
+            let mut request = self.client.request(method.clone(), &url);
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+            let error = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    return response.json::<T>().await.map_err(|e| {
+                        Error::remote_signer(format!("invalid response from {url}: {e}"))
+                    });
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let error = Error::remote_signer(format!("{url} responded with {status}"));
+                    if !status.is_server_error() {
+                        return Err(error);
+                    }
+                    error
+                }
+                Err(e) => Error::remote_signer(format!("request to {url} failed: {e}")),
+            };
+            if attempt >= self.max_retries {
+                return Err(error);
+            }
+            attempt += 1;
+            tokio::time::sleep(RETRY_DELAY * attempt).await;
+        }
+    }
+}
+
+impl DigestSigner for RemoteSigner {
+    fn public_key(&self) -> Result<PublicKey, Error> {
+        self.rt.block_on(self.fetch_public_key())
+    }
+
+    fn sign_digest(&self, digest: &[u8; 32]) -> Result<[u8; 65], Error> {
+        self.rt.block_on(self.sign(digest))
+    }
+}
+
+/// Remote signer of the transactions of an Ethereum account
+pub struct RemoteEthSigner {
+    signer: RemoteSigner,
+    address: Address,
+    chain_id: u64,
+}
+
+impl RemoteEthSigner {
+    pub async fn connect(signer: RemoteSigner, chain_id: u64) -> Result<Self, Error> {
+        let public_key = signer.fetch_public_key().await?;
+        let address = get_address(&public_key, Secp256k1AddressType::Axon).into();
+        Ok(Self {
+            signer,
+            address,
+            chain_id,
+        })
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    pub fn with_chain_id(self, chain_id: u64) -> Self {
+        Self { chain_id, ..self }
+    }
+
+    /// Signature of the hash, with `v` being 27 plus the recovery id
+    pub async fn sign_hash(&self, hash: H256) -> Result<Signature, Error> {
+        let signature = self.signer.sign(hash.as_fixed_bytes()).await?;
+        Ok(Signature {
+            r: U256::from_big_endian(&signature[..32]),
+            s: U256::from_big_endian(&signature[32..64]),
+            v: signature[64] as u64 + 27,
+        })
+    }
+
+    pub async fn sign_message(&self, message: impl AsRef<[u8]>) -> Result<Signature, Error> {
+        self.sign_hash(hash_message(message)).await
+    }
+
+    pub async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Error> {
+        let mut tx = tx.clone();
+        let chain_id = match tx.chain_id() {
+            Some(chain_id) => chain_id.as_u64(),
+            None => {
+                tx.set_chain_id(self.chain_id);
+                self.chain_id
+            }
+        };
+        let mut signature = self.sign_hash(tx.sighash()).await?;
+        // EIP-155 replay protection
+        signature.v = signature.v - 27 + 35 + chain_id * 2;
+        Ok(signature)
+    }
+
+    pub async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Error> {
+        let hash = payload
+            .encode_eip712()
+            .map_err(|e| Error::remote_signer(e.to_string()))?;
+        self.sign_hash(H256::from(hash)).await
+    }
+}
+
+impl std::fmt::Debug for RemoteEthSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteEthSigner")
+            .field("url", &self.signer.url)
+            .field("key_id", &self.signer.key_id)
+            .field("address", &self.address)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+fn decode_hex(field: &str, value: &str) -> Result<Vec<u8>, Error> {
+    hex::decode(value.trim_start_matches("0x"))
+        .map_err(|e| Error::remote_signer(format!("invalid {field} {value}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ckb_hash::blake2b_256;
+    use ckb_sdk::traits::Signer;
+    use ckb_types::core::TransactionBuilder;
+    use ethers::types::transaction::eip2718::TypedTransaction;
+    use ethers::types::TransactionRequest;
+    use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+    use secp256k1::{Message, PublicKey, Secp256k1};
+    use serde_json::{json, Value};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::runtime::Runtime as TokioRuntime;
+
+    use super::{RemoteEthSigner, RemoteSigner};
+    use crate::config::{AddressType, RemoteSignerConfig};
+    use crate::keyring::{CkbTxSigner, DigestSigner, Secp256k1KeyPair};
+
+    const PRIVATE_KEY: &str = "0x63d86723e08f0f813a36ce6aa123bb2289d90680ae1e99d4de8cdb334553f24d";
+
+    fn key() -> Secp256k1KeyPair {
+        Secp256k1KeyPair::from_private_key(PRIVATE_KEY, &AddressType::Ckb { is_mainnet: false }, "")
+            .unwrap()
+    }
+
+    fn config(url: &str) -> RemoteSignerConfig {
+        RemoteSignerConfig {
+            url: url.parse().unwrap(),
+            key_id: "relayer".to_owned(),
+            timeout: Duration::from_millis(500),
+            max_retries: 2,
+        }
+    }
+
+    /// Read an HTTP request, returning its path and its body
+    async fn read_request(stream: &mut TcpStream) -> (String, Vec<u8>) {
+        let mut request = vec![];
+        let mut buffer = [0u8; 1024];
+        let head_len = loop {
+            let read = stream.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+            if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break end + 4;
+            }
+        };
+        let head = String::from_utf8_lossy(&request[..head_len]).to_string();
+        let path = head.split_whitespace().nth(1).unwrap().to_owned();
+        let content_length = head
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse::<usize>().unwrap())
+            })
+            .unwrap_or_default();
+        while request.len() < head_len + content_length {
+            let read = stream.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+        }
+        (path, request[head_len..].to_vec())
+    }
+
+    /// Serve the signing API with the test key on a local port, answering the first
+    /// `failures` requests with a server error
+    fn spawn_mock_signer(rt: &TokioRuntime, mut failures: usize) -> String {
+        let listener = rt.block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        rt.spawn(async move {
+            let key = key();
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let (path, body) = read_request(&mut stream).await;
+                let (status, body) = if failures > 0 {
+                    failures -= 1;
+                    ("503 Service Unavailable", json!({}))
+                } else if path == "/api/v1/keys/relayer" {
+                    let public_key = hex::encode(key.public_key.serialize());
+                    ("200 OK", json!({ "public_key": format!("0x{public_key}") }))
+                } else if path == "/api/v1/sign/relayer" {
+                    let request: Value = serde_json::from_slice(&body).unwrap();
+                    let digest = hex::decode(&request["digest"].as_str().unwrap()[2..]).unwrap();
+                    let signature = key.sign_digest(&digest.try_into().unwrap()).unwrap();
+                    let signature = hex::encode(signature);
+                    ("200 OK", json!({ "signature": format!("0x{signature}") }))
+                } else {
+                    ("404 Not Found", json!({}))
+                };
+                let body = body.to_string();
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        url
+    }
+
+    fn recover(digest: &[u8; 32], signature: &[u8]) -> PublicKey {
+        let recovery_id = RecoveryId::from_i32(signature[64] as i32).unwrap();
+        let signature = RecoverableSignature::from_compact(&signature[..64], recovery_id).unwrap();
+        Secp256k1::verification_only()
+            .recover_ecdsa(&Message::from_slice(digest).unwrap(), &signature)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_remote_signer_round_trip() {
+        let rt = Arc::new(TokioRuntime::new().unwrap());
+        let url = spawn_mock_signer(&rt, 0);
+        let signer = RemoteSigner::new(&config(&url), rt).unwrap();
+        let digest = [7u8; 32];
+
+        assert_eq!(signer.public_key().unwrap(), key().public_key);
+        let signature = signer.sign_digest(&digest).unwrap();
+        assert_eq!(recover(&digest, &signature), key().public_key);
+
+        // the CKB transactions are signed through the remote signer
+        let tx_signer = CkbTxSigner::new(Box::new(signer)).unwrap();
+        let lock_args = &blake2b_256(key().public_key.serialize())[..20];
+        let tx = TransactionBuilder::default().build();
+        let signature = tx_signer.sign(lock_args, &digest, true, &tx).unwrap();
+        assert_eq!(recover(&digest, &signature), key().public_key);
+    }
+
+    #[test]
+    fn test_remote_signer_retries_server_errors() {
+        let rt = Arc::new(TokioRuntime::new().unwrap());
+        let url = spawn_mock_signer(&rt, 2);
+        let signer = RemoteSigner::new(&config(&url), rt.clone()).unwrap();
+        assert_eq!(signer.public_key().unwrap(), key().public_key);
+
+        let url = spawn_mock_signer(&rt, 3);
+        let signer = RemoteSigner::new(&config(&url), rt).unwrap();
+        assert!(signer.public_key().is_err());
+    }
+
+    #[test]
+    fn test_remote_signer_times_out() {
+        let rt = Arc::new(TokioRuntime::new().unwrap());
+        let listener = rt.block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        // accept the connections without ever responding
+        rt.spawn(async move {
+            let mut streams = vec![];
+            loop {
+                streams.push(listener.accept().await.unwrap());
+            }
+        });
+        let mut config = config(&url);
+        config.timeout = Duration::from_millis(100);
+        config.max_retries = 1;
+        let signer = RemoteSigner::new(&config, rt).unwrap();
+        assert!(signer.sign_digest(&[7u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_remote_eth_signer_signs_transactions() {
+        let rt = Arc::new(TokioRuntime::new().unwrap());
+        let url = spawn_mock_signer(&rt, 0);
+        let signer = RemoteSigner::new(&config(&url), rt.clone()).unwrap();
+        let signer = rt.block_on(RemoteEthSigner::connect(signer, 5)).unwrap();
+        let wallet = key().into_ether_wallet();
+        assert_eq!(signer.address(), ethers::signers::Signer::address(&wallet));
+
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(signer.address())
+            .value(1)
+            .nonce(0)
+            .into();
+        let signature = rt.block_on(signer.sign_transaction(&tx)).unwrap();
+        // EIP-155 recovery ids of chain 5
+        assert!(matches!(signature.v, 45 | 46));
+        let mut tx = tx;
+        tx.set_chain_id(5);
+        assert_eq!(signature.recover(tx.sighash()).unwrap(), signer.address());
+    }
+}
@@ -6,18 +6,19 @@ use bitcoin::{
     util::bip32::{ChildNumber, DerivationPath, ExtendedPrivKey, ExtendedPubKey},
 };
 use ckb_hash::blake2b_256;
-use ckb_sdk::{AddressPayload, NetworkType};
+use ckb_sdk::{Address, AddressPayload, NetworkType};
 use digest::Digest;
 use ethers::{prelude::k256::ecdsa::SigningKey, signers::Wallet};
 use generic_array::{typenum::U32, GenericArray};
 use hdpath::StandardHDPath;
 use ripemd::Ripemd160;
-use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
 use strum::{EnumIter, IntoEnumIterator};
 
 use super::{
+    ecdsa,
     errors::Error,
     key_utils::{decode_bech32, encode_bech32, keccak256_hash},
     pub_key::EncodedPubKey,
@@ -138,6 +139,10 @@ fn encode_address(account_prefix: &str, address: &[u8]) -> Result<String, Error>
     encode_bech32(account_prefix, address)
 }
 
+fn ckb_address(public_key: &PublicKey, network: NetworkType) -> String {
+    Address::new(network, AddressPayload::from_pubkey(public_key), true).to_string()
+}
+
 // /!\ /!\ /!\ /!\ /!\ /!\ /!\ /!\ /!\ /!\ /!\ /!\ /!\ /!\ /!\
 // WARNING: Changing this struct in backward incompatible way
 //          will force users to re-import their keys.
@@ -226,23 +231,65 @@ impl Secp256k1KeyPair {
     fn from_mnemonic_internal(
         mnemonic: &str,
         hd_path: &StandardHDPath,
-        address_type: Secp256k1AddressType,
+        address_type: &AddressType,
         account_prefix: &str,
     ) -> Result<Self, Error> {
         let private_key = private_key_from_mnemonic(mnemonic, hd_path)?;
         let public_key = ExtendedPubKey::from_priv(&Secp256k1::signing_only(), &private_key);
-        let address = get_address(&public_key.public_key, address_type);
-        let account = encode_address(account_prefix, &address)?;
+        Self::from_keys(
+            private_key.private_key,
+            public_key.public_key,
+            address_type,
+            account_prefix,
+        )
+    }
+
+    /// Key pair of a raw private key, hex encoded with or without the `0x` prefix
+    pub fn from_private_key(
+        private_key: &str,
+        address_type: &AddressType,
+        account_prefix: &str,
+    ) -> Result<Self, Error> {
+        let private_key = private_key.trim();
+        let bytes = hex::decode(private_key.strip_prefix("0x").unwrap_or(private_key))
+            .map_err(|e| Error::secp256k1(format!("invalid private key: {e}")))?;
+        let private_key = SecretKey::from_slice(&bytes)?;
+        let public_key = PublicKey::from_secret_key(&Secp256k1::signing_only(), &private_key);
+        Self::from_keys(private_key, public_key, address_type, account_prefix)
+    }
+
+    /// The account of the CKB keys is the full address of their default lock on the
+    /// network of `address_type`, the one of the other keys is Bech32 encoded
+    fn from_keys(
+        private_key: SecretKey,
+        public_key: PublicKey,
+        address_type: &AddressType,
+        account_prefix: &str,
+    ) -> Result<Self, Error> {
+        let secp256k1_address_type = address_type.try_into()?;
+        let address = get_address(&public_key, secp256k1_address_type);
+        let account = match address_type {
+            AddressType::Ckb { is_mainnet: true } => ckb_address(&public_key, NetworkType::Mainnet),
+            AddressType::Ckb { is_mainnet: false } => {
+                ckb_address(&public_key, NetworkType::Testnet)
+            }
+            _ => encode_address(account_prefix, &address)?,
+        };
 
         Ok(Self {
-            private_key: private_key.private_key,
-            public_key: public_key.public_key,
+            private_key,
+            public_key,
             address,
-            address_type,
+            address_type: secp256k1_address_type,
             account,
         })
     }
 
+    /// Full address of the default secp256k1 lock of the key on the CKB `network`
+    pub fn ckb_address(&self, network: NetworkType) -> String {
+        ckb_address(&self.public_key, network)
+    }
+
     pub fn into_ckb_keypair(self, network: NetworkType) -> Self {
         if let Secp256k1AddressType::Ckb = self.address_type {
             return self;
@@ -318,7 +365,7 @@ impl SigningKeyPair for Secp256k1KeyPair {
         address_type: &AddressType,
         account_prefix: &str,
     ) -> Result<Self, Error> {
-        Self::from_mnemonic_internal(mnemonic, hd_path, address_type.try_into()?, account_prefix)
+        Self::from_mnemonic_internal(mnemonic, hd_path, address_type, account_prefix)
     }
 
     fn account(&self) -> String {
@@ -333,7 +380,7 @@ impl SigningKeyPair for Secp256k1KeyPair {
     // - https://github.com/evmos/ethermint/blob/main/crypto/ethsecp256k1/ethsecp256k1.go
     // - informalsystems/hermes#2863.
     fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Error> {
-        let message = match self.address_type {
+        match self.address_type {
             Secp256k1AddressType::Ethermint
             | Secp256k1AddressType::Cosmos
             | Secp256k1AddressType::Axon => {
@@ -344,32 +391,17 @@ impl SigningKeyPair for Secp256k1KeyPair {
                     Secp256k1AddressType::Cosmos => Sha256::digest(message),
                     _ => unreachable!("checked"),
                 };
-
-                // SAFETY: hashed_message is 32 bytes, as expected in `Message::from_slice`,
-                // so `unwrap` is safe.
-                Message::from_slice(&hashed_message).unwrap()
+                let signature = ecdsa::sign_compact(&self.private_key, &hashed_message.into())?;
+                Ok(signature.to_vec())
             }
-            Secp256k1AddressType::Ckb => Message::from_slice(message)?,
-        };
-
-        let signature = match self.address_type {
-            Secp256k1AddressType::Ethermint
-            | Secp256k1AddressType::Cosmos
-            | Secp256k1AddressType::Axon => Secp256k1::signing_only()
-                .sign_ecdsa(&message, &self.private_key)
-                .serialize_compact()
-                .to_vec(),
             Secp256k1AddressType::Ckb => {
-                let (recov_id, data) = Secp256k1::signing_only()
-                    .sign_ecdsa_recoverable(&message, &self.private_key)
-                    .serialize_compact();
-                let mut signature_bytes = [0u8; 65];
-                signature_bytes[0..64].copy_from_slice(&data[0..64]);
-                signature_bytes[64] = recov_id.to_i32() as u8;
-                signature_bytes.to_vec()
+                let digest: &[u8; 32] = message
+                    .try_into()
+                    .map_err(|_| secp256k1::Error::InvalidMessage)?;
+                let signature = ecdsa::sign_recoverable(&self.private_key, digest)?;
+                Ok(signature.to_vec())
             }
-        };
-        Ok(signature)
+        }
     }
 
     fn as_any(&self) -> &dyn Any {
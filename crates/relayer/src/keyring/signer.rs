@@ -0,0 +1,212 @@
+//! Backends signing the transactions of the relayer accounts.
+//!
+//! The transactions are signed with the key of the keyring by default. With the
+//! `signer = "ledger"` option of a CKB or Axon chain, they are signed on a Ledger
+//! device instead, and with `signer = "remote"` by an external signing service, so
+//! that the private key of the account never reaches the relayer.
+
+use async_trait::async_trait;
+use ckb_hash::blake2b_256;
+use ckb_types::bytes::Bytes;
+use ckb_types::core::TransactionView;
+use ethers::prelude::k256::ecdsa::SigningKey;
+use ethers::signers::{
+    Ledger as EthLedger, LedgerError, Signer as EthersSigner, Wallet, WalletError,
+};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::types::{Address, Signature};
+use secp256k1::PublicKey;
+
+use super::ecdsa;
+use super::errors::Error;
+use super::remote::RemoteEthSigner;
+use super::Secp256k1KeyPair;
+
+/// Signer of the 32-byte digests of the CKB transactions
+pub trait DigestSigner: Send + Sync {
+    fn public_key(&self) -> Result<PublicKey, Error>;
+
+    /// Recoverable signature of the digest, made of `r`, `s` and the recovery id
+    fn sign_digest(&self, digest: &[u8; 32]) -> Result<[u8; 65], Error>;
+}
+
+impl DigestSigner for Secp256k1KeyPair {
+    fn public_key(&self) -> Result<PublicKey, Error> {
+        Ok(self.public_key)
+    }
+
+    fn sign_digest(&self, digest: &[u8; 32]) -> Result<[u8; 65], Error> {
+        ecdsa::sign_recoverable(&self.private_key, digest)
+    }
+}
+
+/// Signer of the secp256k1 sighash lock of the relayer account, which hands the
+/// digests over to a [`DigestSigner`] in place of `SecpCkbRawKeySigner`
+pub struct CkbTxSigner {
+    signer: Box<dyn DigestSigner>,
+    /// Lock args of the account, the Blake160 hash of the compressed public key
+    lock_args: [u8; 20],
+}
+
+impl CkbTxSigner {
+    pub fn new(signer: Box<dyn DigestSigner>) -> Result<Self, Error> {
+        let public_key = signer.public_key()?;
+        let mut lock_args = [0u8; 20];
+        lock_args.copy_from_slice(&blake2b_256(public_key.serialize())[..20]);
+        Ok(Self { signer, lock_args })
+    }
+}
+
+impl ckb_sdk::traits::Signer for CkbTxSigner {
+    fn match_id(&self, id: &[u8]) -> bool {
+        id == self.lock_args
+    }
+
+    fn sign(
+        &self,
+        id: &[u8],
+        message: &[u8],
+        recoverable: bool,
+        _tx: &TransactionView,
+    ) -> Result<Bytes, ckb_sdk::traits::SignerError> {
+        if !self.match_id(id) {
+            return Err(ckb_sdk::traits::SignerError::IdNotFound);
+        }
+        let digest: &[u8; 32] = message.try_into().map_err(|_| {
+            ckb_sdk::traits::SignerError::InvalidMessage(format!(
+                "expected a digest of 32 bytes, got {} bytes",
+                message.len()
+            ))
+        })?;
+        let signature = self
+            .signer
+            .sign_digest(digest)
+            .map_err(|e| ckb_sdk::traits::SignerError::Other(anyhow::anyhow!(e.to_string())))?;
+        if recoverable {
+            Ok(Bytes::from(signature.to_vec()))
+        } else {
+            Ok(Bytes::from(signature[..64].to_vec()))
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EthSignerError {
+    #[error(transparent)]
+    Wallet(#[from] WalletError),
+    #[error(transparent)]
+    Ledger(#[from] LedgerError),
+    #[error("{0}")]
+    Remote(String),
+}
+
+impl From<Error> for EthSignerError {
+    fn from(e: Error) -> Self {
+        EthSignerError::Remote(e.to_string())
+    }
+}
+
+/// Signer of the transactions of the relayer account on Axon
+#[derive(Debug)]
+pub enum EthSigner {
+    Wallet(Wallet<SigningKey>),
+    Ledger(EthLedger),
+    Remote(RemoteEthSigner),
+}
+
+#[async_trait]
+impl EthersSigner for EthSigner {
+    type Error = EthSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            EthSigner::Wallet(wallet) => Ok(wallet.sign_message(message).await?),
+            EthSigner::Ledger(ledger) => Ok(ledger.sign_message(message).await?),
+            EthSigner::Remote(remote) => Ok(remote.sign_message(message).await?),
+        }
+    }
+
+    async fn sign_transaction(&self, message: &TypedTransaction) -> Result<Signature, Self::Error> {
+        match self {
+            EthSigner::Wallet(wallet) => Ok(wallet.sign_transaction(message).await?),
+            EthSigner::Ledger(ledger) => Ok(ledger.sign_transaction(message).await?),
+            EthSigner::Remote(remote) => Ok(remote.sign_transaction(message).await?),
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            EthSigner::Wallet(wallet) => Ok(wallet.sign_typed_data(payload).await?),
+            EthSigner::Ledger(ledger) => Ok(ledger.sign_typed_data(payload).await?),
+            EthSigner::Remote(remote) => Ok(remote.sign_typed_data(payload).await?),
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            EthSigner::Wallet(wallet) => wallet.address(),
+            EthSigner::Ledger(ledger) => ledger.address(),
+            EthSigner::Remote(remote) => remote.address(),
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        match self {
+            EthSigner::Wallet(wallet) => wallet.chain_id(),
+            EthSigner::Ledger(ledger) => ledger.chain_id(),
+            EthSigner::Remote(remote) => remote.chain_id(),
+        }
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            EthSigner::Wallet(wallet) => EthSigner::Wallet(wallet.with_chain_id(chain_id)),
+            EthSigner::Ledger(ledger) => EthSigner::Ledger(ledger.with_chain_id(chain_id)),
+            EthSigner::Remote(remote) => EthSigner::Remote(remote.with_chain_id(chain_id.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_sdk::traits::Signer;
+    use ckb_types::core::TransactionBuilder;
+    use secp256k1::{ecdsa::RecoverableSignature, ecdsa::RecoveryId, Message, Secp256k1};
+
+    use super::CkbTxSigner;
+    use crate::config::AddressType;
+    use crate::keyring::Secp256k1KeyPair;
+
+    #[test]
+    fn test_ckb_tx_signer_signs_with_the_key() {
+        let key = Secp256k1KeyPair::from_private_key(
+            "0x63d86723e08f0f813a36ce6aa123bb2289d90680ae1e99d4de8cdb334553f24d",
+            &AddressType::Ckb { is_mainnet: false },
+            "",
+        )
+        .unwrap();
+        let public_key = key.public_key;
+        let signer = CkbTxSigner::new(Box::new(key)).unwrap();
+        let lock_args = signer.lock_args;
+        let tx = TransactionBuilder::default().build();
+        let digest = [7u8; 32];
+
+        assert!(signer.sign(&[0; 20], &digest, true, &tx).is_err());
+        assert!(signer.sign(&lock_args, &digest[..31], true, &tx).is_err());
+
+        let signature = signer.sign(&lock_args, &digest, true, &tx).unwrap();
+        let recovery_id = RecoveryId::from_i32(signature[64] as i32).unwrap();
+        let signature = RecoverableSignature::from_compact(&signature[..64], recovery_id).unwrap();
+        let recovered = Secp256k1::verification_only()
+            .recover_ecdsa(&Message::from_slice(&digest).unwrap(), &signature)
+            .unwrap();
+        assert_eq!(recovered, public_key);
+    }
+}
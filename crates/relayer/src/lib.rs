@@ -20,6 +20,9 @@
 extern crate alloc;
 
 pub mod account;
+pub mod archive;
+pub mod attestation;
+pub mod audit;
 pub mod cache;
 pub mod chain;
 pub mod channel;
@@ -27,6 +30,7 @@ pub mod client_state;
 pub mod config;
 pub mod connection;
 pub mod consensus_state;
+pub mod decision;
 pub mod denom;
 pub mod error;
 pub mod event;
@@ -39,10 +43,12 @@ pub mod macros;
 pub mod misbehaviour;
 pub mod object;
 pub mod path;
+pub mod progress;
 pub mod registry;
 pub mod rest;
 pub mod sdk_error;
 pub mod spawn;
+pub mod summary;
 pub mod supervisor;
 pub mod telemetry;
 pub mod transfer;
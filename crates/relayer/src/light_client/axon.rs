@@ -2,7 +2,6 @@
 
 use std::sync::Arc;
 
-use ethers::prelude::k256::ecdsa::SigningKey;
 use ethers::prelude::*;
 use ethers::prelude::{Provider, Ws};
 use futures::TryFutureExt;
@@ -17,6 +16,7 @@ use crate::chain::endpoint::ChainEndpoint;
 use crate::client_state::AnyClientState;
 use crate::config::axon::AxonChainConfig;
 use crate::error::Error;
+use crate::keyring::EthSigner;
 use crate::misbehaviour::MisbehaviourEvidence;
 
 use super::Verified;
@@ -44,7 +44,7 @@ impl LightClient {
 
     pub fn bootstrap<T: AxonRpc + Sync + Send + 'static>(
         &self,
-        provider: Arc<SignerMiddleware<Provider<Ws>, Wallet<SigningKey>>>,
+        provider: Arc<SignerMiddleware<Provider<Ws>, EthSigner>>,
         rpc: T,
         epoch_len: u64,
     ) -> Result<(), Error> {
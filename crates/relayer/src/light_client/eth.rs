@@ -1,3 +1,6 @@
+pub mod bootstrap;
+pub mod failover;
+pub mod misbehaviour;
 mod utils;
 
 use std::cmp;
@@ -20,7 +23,8 @@ use ibc_relayer_types::clients::ics07_eth::types::{
 };
 use ibc_relayer_types::core::ics02_client::client_state::ClientState;
 use ibc_relayer_types::core::ics02_client::error::Error as ClientError;
-use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+use ibc_relayer_types::core::ics02_client::header::downcast_header;
+use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ClientId};
 use ibc_relayer_types::{
     clients::ics07_eth::header::Header, core::ics02_client::events::UpdateClient, Height,
 };
@@ -28,11 +32,12 @@ use reqwest_middleware::ClientBuilder;
 use reqwest_middleware::ClientWithMiddleware;
 use reqwest_retry::policies::ExponentialBackoff;
 use reqwest_retry::RetryTransientMiddleware;
+use serde::de::DeserializeOwned;
 use tracing::info;
 
 use crate::config::eth::EthChainConfig;
 use crate::{
-    chain::{endpoint::ChainEndpoint, eth::EthChain},
+    chain::{client::ScheduledFork, endpoint::ChainEndpoint, eth::EthChain},
     client_state::AnyClientState,
     error::Error,
     misbehaviour::MisbehaviourEvidence,
@@ -40,6 +45,8 @@ use crate::{
 
 use super::Verified;
 
+use self::failover::{BeaconEndpoints, EndpointHealth};
+use self::misbehaviour::detect_double_finality;
 use self::utils::calc_sync_period;
 use self::utils::compute_domain;
 use self::utils::compute_signing_root;
@@ -73,7 +80,7 @@ impl<R: ConsensusRpc> ConsensusClient<R> {
         config: Arc<EthChainConfig>,
     ) -> ConsensusClient<R> {
         ConsensusClient {
-            rpc: R::new(rpc_pool),
+            rpc: R::new(&config.id, rpc_pool),
             store: LightClientStore::default(),
             initial_checkpoint: *checkpoint_block_root,
             last_checkpoint: None,
@@ -83,6 +90,10 @@ impl<R: ConsensusRpc> ConsensusClient<R> {
         }
     }
 
+    pub fn rpc(&self) -> &R {
+        &self.rpc
+    }
+
     pub fn subscribe(&mut self) -> (UnboundedReceiver<Header>, UnboundedReceiver<Vec<Header>>) {
         let (sender_nc, receiver_nc) = unbounded_channel();
         let (sender_nb, receiver_nb) = unbounded_channel();
@@ -181,6 +192,27 @@ impl<R: ConsensusRpc> ConsensusClient<R> {
         Ok(update)
     }
 
+    /// Bootstrap from the latest finalized checkpoint of the `trusted` beacon node instead
+    /// of the `initial_checkpoint`. The bootstrap is fetched from the RPC pool and verified
+    /// against the root of the checkpoint, and the update of its header is returned.
+    pub async fn bootstrap_from_checkpoint(&mut self, trusted: &R) -> Result<Update> {
+        let checkpoint = trusted
+            .get_finalized_checkpoint()
+            .await
+            .map_err(|e| eyre!("could not fetch finalized checkpoint: {e}"))?;
+        self.initial_checkpoint = checkpoint;
+        self.bootstrap().await?;
+        info!(
+            "bootstrapped from checkpoint 0x{} at slot {}",
+            hex::encode(checkpoint),
+            self.store.finalized_header.slot
+        );
+
+        Ok(Update::from_finalized_header(
+            self.store.finalized_header.clone(),
+        ))
+    }
+
     pub fn cache_finality_update(&mut self, update: &Update) {
         self.store
             .finality_updates
@@ -224,6 +256,10 @@ impl<R: ConsensusRpc> ConsensusClient<R> {
         Ok(())
     }
 
+    pub fn finalized_slot(&self) -> u64 {
+        self.store.finalized_header.slot
+    }
+
     pub fn duration_until_next_update(&self) -> Duration {
         let current_slot = self.expected_current_slot();
         let next_slot = current_slot + 1;
@@ -527,11 +563,13 @@ impl<R: ConsensusRpc> ConsensusClient<R> {
 
 #[async_trait]
 pub trait ConsensusRpc {
-    fn new(rpcs: &[String]) -> Self;
+    fn new(chain_id: &ChainId, rpcs: &[String]) -> Self;
     async fn get_bootstrap(&self, block_root: &[u8]) -> Result<Bootstrap>;
     async fn get_updates(&self, period: u64, count: u8) -> Result<Vec<Update>>;
     async fn get_finality_update(&self) -> Result<FinalityUpdate>;
     async fn get_header(&self, slot: u64) -> Result<Option<Header>>;
+    /// Block root of the latest finalized checkpoint
+    async fn get_finalized_checkpoint(&self) -> Result<[u8; 32]>;
 }
 
 #[derive(Default)]
@@ -546,68 +584,97 @@ pub struct LightClientStore {
 }
 
 pub struct NimbusRpc {
-    rpc: Vec<String>,
+    endpoints: BeaconEndpoints,
     client: ClientWithMiddleware,
 }
 
 impl NimbusRpc {
+    pub fn endpoints(&self) -> &BeaconEndpoints {
+        &self.endpoints
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        Ok(self.client.get(url).send().await?.json::<T>().await?)
+    }
+
+    /// Send the request of `path` to the beacon endpoints in turn, from the active one,
+    /// until one of them answers
+    async fn request<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let mut last_error = None;
+        for index in self.endpoints.ordered() {
+            let url = format!("{}{path}", self.endpoints.url(index));
+            match self.get_json(&url).await {
+                Ok(res) => {
+                    self.endpoints.report_success(index);
+                    return Ok(res);
+                }
+                Err(err) => {
+                    self.endpoints.report_failure(index, &err);
+                    last_error = Some(err);
+                }
+            }
+        }
+        Err(last_error.expect("no beacon endpoint"))
+    }
+
     async fn get_header_inner(&self, rpc: &str, slot: u64) -> Result<Option<Header>> {
         let req = format!("{}/eth/v1/beacon/headers/{slot}", rpc);
         let res = self
-            .client
-            .get(req)
-            .send()
-            .await?
-            .json::<HeaderResponse::Response>()
+            .get_json::<HeaderResponse::Response>(&req)
             .await
             .map_err(|e| eyre::eyre!(format!("{e} (slot {slot})")))?;
 
         Ok(res.header())
     }
+
+    async fn get_fork_schedule(&self) -> Result<Vec<ScheduledFork>> {
+        let res = self
+            .request::<ForkScheduleResponse>("/eth/v1/config/fork_schedule")
+            .await?;
+
+        res.data
+            .into_iter()
+            .map(|fork| {
+                let epoch = fork
+                    .epoch
+                    .parse()
+                    .map_err(|e| eyre!("invalid fork epoch {}: {e}", fork.epoch))?;
+                Ok(ScheduledFork {
+                    epoch,
+                    version: fork.current_version,
+                })
+            })
+            .collect()
+    }
 }
 
 #[async_trait]
 impl ConsensusRpc for NimbusRpc {
-    fn new(rpcs: &[String]) -> Self {
+    fn new(chain_id: &ChainId, rpcs: &[String]) -> Self {
         let retry_policy = ExponentialBackoff::builder()
             .backoff_exponent(1)
             .build_with_max_retries(3);
         let client = ClientBuilder::new(reqwest::Client::new())
             .with(RetryTransientMiddleware::new_with_policy(retry_policy))
             .build();
-        assert!(!rpcs.is_empty());
         NimbusRpc {
-            rpc: rpcs.to_owned(),
+            endpoints: BeaconEndpoints::new(chain_id.clone(), rpcs),
             client,
         }
     }
 
     async fn get_updates(&self, period: u64, count: u8) -> Result<Vec<Update>> {
         let count = cmp::min(count, MAX_REQUEST_LIGHT_CLIENT_UPDATES);
-        let req = format!(
-            "{}/eth/v1/beacon/light_client/updates?start_period={period}&count={count}",
-            self.rpc[0]
-        );
-
-        let res = self
-            .client
-            .get(req)
-            .send()
-            .await?
-            .json::<UpdateResponse>()
-            .await?;
+        let req =
+            format!("/eth/v1/beacon/light_client/updates?start_period={period}&count={count}");
+        let res = self.request::<UpdateResponse>(&req).await?;
 
         Ok(res.iter().map(|d| d.data.clone()).collect())
     }
 
     async fn get_finality_update(&self) -> Result<FinalityUpdate> {
-        let req = format!("{}/eth/v1/beacon/light_client/finality_update", self.rpc[0]);
         let res = self
-            .client
-            .get(req)
-            .send()
-            .await?
-            .json::<FinalityUpdateResponse>()
+            .request::<FinalityUpdateResponse>("/eth/v1/beacon/light_client/finality_update")
             .await?;
 
         Ok(res.data)
@@ -615,50 +682,45 @@ impl ConsensusRpc for NimbusRpc {
 
     async fn get_bootstrap(&self, block_root: &[u8]) -> Result<Bootstrap> {
         let root_hex = hex::encode(block_root);
-        let req = format!(
-            "{}/eth/v1/beacon/light_client/bootstrap/0x{root_hex}",
-            self.rpc[0]
-        );
+        let req = format!("/eth/v1/beacon/light_client/bootstrap/0x{root_hex}");
+        let res = self.request::<BootstrapResponse>(&req).await?;
+
+        Ok(res.data)
+    }
 
+    async fn get_finalized_checkpoint(&self) -> Result<[u8; 32]> {
         let res = self
-            .client
-            .get(req)
-            .send()
-            .await?
-            .json::<BootstrapResponse>()
+            .request::<FinalityCheckpointsResponse>(
+                "/eth/v1/beacon/states/finalized/finality_checkpoints",
+            )
             .await?;
 
-        Ok(res.data)
+        let root = res.data.finalized.root;
+        hex::decode(root.trim_start_matches("0x"))?
+            .try_into()
+            .map_err(|_| eyre!("invalid checkpoint root {root}"))
     }
 
     async fn get_header(&self, slot: u64) -> Result<Option<Header>> {
-        let result = self.get_header_inner(&self.rpc[0], slot).await;
-        match result {
-            Ok(Some(header)) => Ok(Some(header)),
-            Ok(None) => {
-                for rpc in self.rpc.iter().skip(1) {
-                    if let Ok(Some(header)) = self.get_header_inner(rpc, slot).await {
-                        return Ok(Some(header));
-                    }
-                }
-                Ok(None)
-            }
-            Err(err) => {
-                let mut find_none = false;
-                for rpc in self.rpc.iter().skip(1) {
-                    match self.get_header_inner(rpc, slot).await {
-                        Ok(Some(header)) => return Ok(Some(header)),
-                        Ok(None) => find_none = true,
-                        _ => {}
-                    }
+        let mut find_none = false;
+        let mut first_error = None;
+        for index in self.endpoints.ordered() {
+            match self.get_header_inner(self.endpoints.url(index), slot).await {
+                Ok(Some(header)) => {
+                    self.endpoints.report_success(index);
+                    return Ok(Some(header));
                 }
-                if find_none {
-                    Ok(None)
-                } else {
-                    Err(err)
+                Ok(None) => find_none = true,
+                Err(err) => {
+                    self.endpoints.report_failure(index, &err);
+                    first_error.get_or_insert(err);
                 }
             }
         }
+        match first_error {
+            Some(err) if !find_none => Err(err),
+            _ => Ok(None),
+        }
     }
 }
 
@@ -689,6 +751,15 @@ impl LightClient {
 
     pub fn bootstrap(&mut self) -> Result<(), Error> {
         let client = self.consensus_client.clone();
+        let checkpoint_sync = self
+            .rt
+            .block_on(client.lock())
+            .config
+            .checkpoint_sync_url
+            .is_some();
+        if checkpoint_sync {
+            self.bootstrap_from_checkpoint()?;
+        }
         self.rt
             .block_on(self.rt.block_on(client.lock()).sync())
             .map_err(|e| Error::rpc_response(format!("chain {}: {e}", self.chain_id)))?;
@@ -706,6 +777,39 @@ impl LightClient {
         Ok(())
     }
 
+    /// Bootstrap the light client from the latest finalized checkpoint of the beacon
+    /// node at `checkpoint_sync_url`, returning the update of the checkpoint header.
+    pub fn bootstrap_from_checkpoint(&self) -> Result<Update, Error> {
+        let task = async {
+            let mut consensus_client = self.consensus_client.lock().await;
+            let url = consensus_client
+                .config
+                .checkpoint_sync_url
+                .clone()
+                .ok_or_else(|| eyre!("no checkpoint_sync_url is configured"))?;
+            let trusted = NimbusRpc::new(&self.chain_id, &[url]);
+            consensus_client.bootstrap_from_checkpoint(&trusted).await
+        };
+        self.rt
+            .block_on(task)
+            .map_err(|e| Error::rpc_response(format!("chain {}: {e}", self.chain_id)))
+    }
+
+    /// Health of the beacon endpoints of the `rpc_addr_pool`
+    pub fn beacon_endpoints_health(&self) -> Vec<EndpointHealth> {
+        self.rt
+            .block_on(self.consensus_client.lock())
+            .rpc()
+            .endpoints()
+            .health()
+    }
+
+    pub fn finalized_slot(&self) -> u64 {
+        self.rt
+            .block_on(self.consensus_client.lock())
+            .finalized_slot()
+    }
+
     pub fn get_finality_update(&self, finality_slot: u64) -> Result<Option<Update>, Error> {
         let mut consensus_client = self.rt.block_on(self.consensus_client.lock());
         let update = self
@@ -757,6 +861,49 @@ impl LightClient {
 
         self.rt.block_on(task)
     }
+
+    /// Evidence of the misbehaviour of the client `client_id` holding the header `root`
+    /// at `slot`, once the light client has seen that slot finalized
+    pub fn check_double_finality(
+        &self,
+        client_id: &ClientId,
+        slot: u64,
+        root: H256,
+    ) -> Result<Option<MisbehaviourEvidence>, Error> {
+        let task = async {
+            let consensus_client = self.consensus_client.lock().await;
+            if slot > consensus_client.finalized_slot() {
+                return Ok(None);
+            }
+            consensus_client.rpc().get_header(slot).await
+        };
+        let canonical = self
+            .rt
+            .block_on(task)
+            .map_err(|e| Error::rpc_response(format!("chain {}: {e}", self.chain_id)))?;
+        let Some(canonical) = canonical else {
+            return Ok(None);
+        };
+        let evidence =
+            detect_double_finality(client_id, slot, root, &canonical).map(|misbehaviour| {
+                MisbehaviourEvidence {
+                    misbehaviour: misbehaviour.into(),
+                    supporting_headers: vec![],
+                }
+            });
+        Ok(evidence)
+    }
+
+    /// Query the forks scheduled by the chain from the beacon node.
+    pub fn fork_schedule(&self) -> Result<Vec<ScheduledFork>, Error> {
+        let task = async {
+            let consensus_client = self.consensus_client.lock().await;
+            consensus_client.rpc.get_fork_schedule().await
+        };
+        self.rt
+            .block_on(task)
+            .map_err(|e| Error::rpc_response(format!("chain {}: {e}", self.chain_id)))
+    }
 }
 
 impl super::LightClient<EthChain> for LightClient {
@@ -793,12 +940,30 @@ impl super::LightClient<EthChain> for LightClient {
         })
     }
 
+    /// The header of the update event is the one accepted by the counterparty, checked
+    /// against the header finalized at its slot by the canonical chain
     fn check_misbehaviour(
         &mut self,
-        _update: &UpdateClient,
+        update: &UpdateClient,
         _client_state: &AnyClientState,
     ) -> Result<Option<MisbehaviourEvidence>, Error> {
-        todo!()
+        let update_header = update.header.as_ref().ok_or_else(|| {
+            Error::misbehaviour(format!(
+                "missing header in update client event {}",
+                self.chain_id
+            ))
+        })?;
+        let update_header: &Header = downcast_header(update_header.as_ref()).ok_or_else(|| {
+            Error::misbehaviour(format!(
+                "header type incompatible for chain {}",
+                self.chain_id
+            ))
+        })?;
+        self.check_double_finality(
+            update.client_id(),
+            update_header.slot,
+            update_header.tree_hash_root(),
+        )
     }
 
     fn fetch(&mut self, _height: Height) -> Result<<EthChain as ChainEndpoint>::LightBlock, Error> {
@@ -831,6 +996,32 @@ struct FinalityUpdateResponse {
     data: FinalityUpdate,
 }
 
+#[derive(serde::Deserialize, Debug)]
+struct FinalityCheckpointsResponse {
+    data: FinalityCheckpoints,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct FinalityCheckpoints {
+    finalized: Checkpoint,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct Checkpoint {
+    root: String,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct ForkScheduleResponse {
+    data: Vec<ForkData>,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct ForkData {
+    current_version: String,
+    epoch: String,
+}
+
 type UpdateResponse = Vec<UpdateData>;
 
 #[derive(serde::Deserialize, Debug)]
@@ -892,6 +1083,7 @@ mod tests {
     use ibc_relayer_types::clients::ics07_eth::header::Header;
     use ibc_relayer_types::clients::ics07_eth::types::ConsensusError;
     use ibc_relayer_types::clients::ics07_eth::types::FixedVector;
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
 
     pub struct MockRpc {
         testdata: PathBuf,
@@ -899,7 +1091,7 @@ mod tests {
 
     #[async_trait]
     impl ConsensusRpc for MockRpc {
-        fn new(path: &[String]) -> Self {
+        fn new(_chain_id: &ChainId, path: &[String]) -> Self {
             MockRpc {
                 testdata: PathBuf::from(path.get(0).unwrap()),
             }
@@ -925,11 +1117,22 @@ mod tests {
             let response: Vec<HeaderResponse::Response> = serde_json::from_str(&header)?;
             Ok(response[slot as usize].clone().header())
         }
+
+        async fn get_finalized_checkpoint(&self) -> Result<[u8; 32]> {
+            Ok(checkpoint())
+        }
     }
 
-    async fn get_client() -> ConsensusClient<MockRpc> {
+    fn checkpoint() -> [u8; 32] {
+        hex::decode("1e591af1e90f2db918b2a132991c7c2ee9a4ab26da496bd6e71e4f0bd65ea870")
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    fn config() -> EthChainConfig {
         let base_config = EthChainConfig::goerli();
-        let config = EthChainConfig {
+        EthChainConfig {
             id: base_config.id,
             genesis_time: base_config.genesis_time,
             genesis_root: base_config.genesis_root,
@@ -938,19 +1141,32 @@ mod tests {
             rpc_port: Default::default(),
             initial_checkpoint: Default::default(),
             key_name: Default::default(),
-        };
-        let checkpoint =
-            hex::decode("1e591af1e90f2db918b2a132991c7c2ee9a4ab26da496bd6e71e4f0bd65ea870")
-                .unwrap()
-                .try_into()
-                .unwrap();
-
-        let mut client =
-            ConsensusClient::new(&["src/testdata/".to_owned()], &checkpoint, Arc::new(config));
+            checkpoint_sync_url: Default::default(),
+        }
+    }
+
+    async fn get_client() -> ConsensusClient<MockRpc> {
+        let mut client = ConsensusClient::new(
+            &["src/testdata/".to_owned()],
+            &checkpoint(),
+            Arc::new(config()),
+        );
         client.bootstrap().await.unwrap();
         client
     }
 
+    #[tokio::test]
+    async fn test_bootstrap_from_checkpoint() {
+        let testdata = ["src/testdata/".to_owned()];
+        let mut client = ConsensusClient::<MockRpc>::new(&testdata, &[0; 32], Arc::new(config()));
+        let trusted = MockRpc::new(&ChainId::default(), &testdata);
+
+        let update = client.bootstrap_from_checkpoint(&trusted).await.unwrap();
+        assert_eq!(client.initial_checkpoint, checkpoint());
+        assert_eq!(update.finalized_header.slot, 3781056);
+        assert_eq!(client.finalized_slot(), 3781056);
+    }
+
     #[tokio::test]
     async fn test_verify_update() {
         let client = get_client().await;
@@ -1091,7 +1307,7 @@ mod tests {
         const END_SLOT: u64 = 5687712;
         const URL: &str = "https://www.lightclientdata.org";
 
-        let rpc = NimbusRpc::new(&[URL.to_owned()]);
+        let rpc = NimbusRpc::new(&ChainId::default(), &[URL.to_owned()]);
         let mut headers = vec![];
         for slot in START_SLOT..=END_SLOT {
             let header = rpc.get_header(slot).await.expect("get header");
@@ -0,0 +1,83 @@
+//! Initial multi-client cells of the Ethereum light client on CKB.
+//!
+//! A new multi-client set is made of `cells_count - 1` identical client cells, all
+//! starting from the same finalized header, and of the info cell pointing at the
+//! first of them.
+
+use eth2_types::EthSpec;
+use eth_light_client_in_ckb_verification::types::{
+    packed::{
+        Client as PackedClient, ClientInfo as PackedClientInfo, ProofUpdate as PackedProofUpdate,
+    },
+    prelude::*,
+};
+use ibc_relayer_storage::prelude::{StorageAsMMRStore, StorageReader, StorageWriter};
+use ibc_relayer_types::clients::ics07_eth::types::Update;
+
+use crate::chain::ckb::utils::get_verified_packed_client_and_proof_update;
+use crate::error::Error;
+
+/// Client cells and info cell of a multi-client set of `cells_count` cells, the info
+/// cell included, whose clients are all copies of `client`
+pub fn multi_client_set(
+    client: PackedClient,
+    cells_count: u8,
+    minimal_updates_count: u8,
+) -> (Vec<PackedClient>, PackedClientInfo) {
+    let client_count = cells_count.checked_sub(1).expect("invalid cells_count");
+    let clients = (0..client_count)
+        .map(|i| client.clone().as_builder().id(i.into()).build())
+        .collect::<Vec<_>>();
+    let client_info = PackedClientInfo::new_builder()
+        .last_id(0.into())
+        .minimal_updates_count(minimal_updates_count.into())
+        .build();
+    (clients, client_info)
+}
+
+/// Multi-client set starting from the header of a bootstrap checkpoint, along with the
+/// proof of the header, as expected by `assemble_create_multi_client_transaction`.
+///
+/// The header digests previously committed into `storage` are dropped, the MMR of the
+/// new clients starting from the checkpoint.
+pub fn bootstrap_multi_client<S, E>(
+    chain_id: &str,
+    checkpoint_update: Update,
+    storage: &S,
+    cells_count: u8,
+    minimal_updates_count: u8,
+) -> Result<(Vec<PackedClient>, PackedClientInfo, PackedProofUpdate), Error>
+where
+    S: StorageReader<E> + StorageWriter<E> + StorageAsMMRStore<E>,
+    E: EthSpec,
+{
+    let (_, client, proof_update) = get_verified_packed_client_and_proof_update(
+        chain_id,
+        &vec![checkpoint_update],
+        storage,
+        None,
+    )?;
+    let (clients, client_info) = multi_client_set(client, cells_count, minimal_updates_count);
+    Ok((clients, client_info, proof_update))
+}
+
+#[cfg(test)]
+mod tests {
+    use eth_light_client_in_ckb_verification::types::{packed::Client as PackedClient, prelude::*};
+
+    use super::multi_client_set;
+
+    #[test]
+    fn test_multi_client_set() {
+        let client = PackedClient::default();
+
+        let (clients, client_info) = multi_client_set(client, 3, 1);
+        let ids = clients
+            .iter()
+            .map(|client| u8::from(client.id().as_reader()))
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![0, 1]);
+        assert_eq!(u8::from(client_info.last_id().as_reader()), 0);
+        assert_eq!(u8::from(client_info.minimal_updates_count().as_reader()), 1);
+    }
+}
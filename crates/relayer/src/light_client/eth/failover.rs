@@ -0,0 +1,156 @@
+//! Failover among the beacon nodes of the `rpc_addr_pool` of an Ethereum chain.
+//!
+//! Requests are sent to the active endpoint first, and to the next endpoints of the
+//! pool in turn when it fails. The first endpoint to answer becomes the active one,
+//! so that a failing beacon node is skipped by the subsequent requests until another
+//! one fails in turn.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::telemetry;
+
+/// Health of a beacon endpoint, as observed by the requests sent to it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointHealth {
+    pub url: String,
+    /// Whether the requests are sent to this endpoint first
+    pub active: bool,
+    /// Number of requests failed since the last successful one
+    pub consecutive_failures: u64,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    url: String,
+    consecutive_failures: AtomicU64,
+}
+
+#[derive(Debug)]
+pub struct BeaconEndpoints {
+    chain_id: ChainId,
+    endpoints: Vec<Endpoint>,
+    active: AtomicUsize,
+    failovers: AtomicU64,
+}
+
+impl BeaconEndpoints {
+    pub fn new(chain_id: ChainId, urls: &[String]) -> Self {
+        assert!(!urls.is_empty(), "no beacon endpoint for chain {chain_id}");
+        let endpoints = urls
+            .iter()
+            .map(|url| Endpoint {
+                url: url.clone(),
+                consecutive_failures: AtomicU64::new(0),
+            })
+            .collect();
+        Self {
+            chain_id,
+            endpoints,
+            active: AtomicUsize::new(0),
+            failovers: AtomicU64::new(0),
+        }
+    }
+
+    /// Indexes of the endpoints in the order to try them, from the active one
+    pub fn ordered(&self) -> impl Iterator<Item = usize> {
+        let count = self.endpoints.len();
+        let active = self.active.load(Ordering::Relaxed);
+        (0..count).map(move |offset| (active + offset) % count)
+    }
+
+    pub fn url(&self, index: usize) -> &str {
+        &self.endpoints[index].url
+    }
+
+    /// Record a successful request to the endpoint, which becomes the active one
+    pub fn report_success(&self, index: usize) {
+        let endpoint = &self.endpoints[index];
+        endpoint.consecutive_failures.store(0, Ordering::Relaxed);
+        let previous = self.active.swap(index, Ordering::Relaxed);
+        if previous != index {
+            self.failovers.fetch_add(1, Ordering::Relaxed);
+            info!(
+                "chain {}: beacon requests fail over from {} to {}",
+                self.chain_id, self.endpoints[previous].url, endpoint.url
+            );
+            telemetry!(eth_beacon_failover, &self.chain_id, &endpoint.url);
+        }
+    }
+
+    /// Record a failed request to the endpoint
+    pub fn report_failure(&self, index: usize, error: &eyre::Report) {
+        let endpoint = &self.endpoints[index];
+        let failures = endpoint
+            .consecutive_failures
+            .fetch_add(1, Ordering::Relaxed)
+            + 1;
+        warn!(
+            "chain {}: beacon request to {} failed ({failures} consecutive failures): {error}",
+            self.chain_id, endpoint.url
+        );
+    }
+
+    /// Number of times the active endpoint changed
+    pub fn failovers(&self) -> u64 {
+        self.failovers.load(Ordering::Relaxed)
+    }
+
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        let active = self.active.load(Ordering::Relaxed);
+        self.endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, endpoint)| EndpointHealth {
+                url: endpoint.url.clone(),
+                active: index == active,
+                consecutive_failures: endpoint.consecutive_failures.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+    use super::BeaconEndpoints;
+
+    fn endpoints() -> BeaconEndpoints {
+        let urls = ["http://a", "http://b", "http://c"].map(String::from);
+        BeaconEndpoints::new(ChainId::from_string("eth-0"), &urls)
+    }
+
+    #[test]
+    fn test_failover_rotation() {
+        let endpoints = endpoints();
+        assert_eq!(endpoints.ordered().collect::<Vec<_>>(), vec![0, 1, 2]);
+
+        // the first endpoint fails, the second one answers and becomes the active one
+        endpoints.report_failure(0, &eyre::eyre!("connection refused"));
+        endpoints.report_success(1);
+        assert_eq!(endpoints.ordered().collect::<Vec<_>>(), vec![1, 2, 0]);
+        assert_eq!(endpoints.failovers(), 1);
+
+        let health = endpoints.health();
+        assert!(health[1].active);
+        assert!(!health[0].active);
+        assert_eq!(health[0].consecutive_failures, 1);
+        assert_eq!(health[1].consecutive_failures, 0);
+
+        // a success of the active endpoint is no failover
+        endpoints.report_success(1);
+        assert_eq!(endpoints.failovers(), 1);
+
+        endpoints.report_failure(1, &eyre::eyre!("timeout"));
+        endpoints.report_failure(2, &eyre::eyre!("timeout"));
+        endpoints.report_success(0);
+        assert_eq!(endpoints.ordered().collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(endpoints.failovers(), 2);
+        assert_eq!(endpoints.health()[0].consecutive_failures, 0);
+        assert_eq!(endpoints.health()[2].consecutive_failures, 1);
+    }
+}
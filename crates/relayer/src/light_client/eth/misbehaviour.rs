@@ -0,0 +1,65 @@
+//! Double finality of the Ethereum light client.
+//!
+//! A finalized header is never reverted by the beacon chain, so a client holding a
+//! header root at a slot other than the one of the header finalized there by the
+//! canonical chain has been fed with a conflicting finality, which is evidence enough
+//! to freeze it.
+
+use ibc_relayer_types::clients::ics07_eth::header::Header;
+use ibc_relayer_types::clients::ics07_eth::misbehaviour::Misbehaviour as EthMisbehaviour;
+use ibc_relayer_types::clients::ics07_eth::types::{TreeHash, H256};
+use ibc_relayer_types::core::ics24_host::identifier::ClientId;
+
+/// Misbehaviour of the client holding `root` at `slot`, if it conflicts with the header
+/// finalized at that slot by the canonical chain
+pub fn detect_double_finality(
+    client_id: &ClientId,
+    slot: u64,
+    root: H256,
+    canonical: &Header,
+) -> Option<EthMisbehaviour> {
+    // a skipped slot holds no header to conflict with
+    if canonical.slot != slot || canonical.is_empty() || canonical.tree_hash_root() == root {
+        return None;
+    }
+    Some(EthMisbehaviour {
+        client_id: client_id.clone(),
+        conflicting_root: root,
+        header: canonical.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ibc_relayer_types::clients::ics07_eth::header::Header;
+    use ibc_relayer_types::clients::ics07_eth::types::{TreeHash, H256};
+    use ibc_relayer_types::core::ics02_client::client_type::ClientType;
+    use ibc_relayer_types::core::ics24_host::identifier::ClientId;
+
+    use super::detect_double_finality;
+
+    #[test]
+    fn test_detect_double_finality() {
+        let client_id = ClientId::new(ClientType::Eth, 0).unwrap();
+        let canonical = Header {
+            slot: 6632736,
+            proposer_index: 1,
+            ..Default::default()
+        };
+        let root = canonical.tree_hash_root();
+
+        assert!(detect_double_finality(&client_id, canonical.slot, root, &canonical).is_none());
+        // the header of another slot tells nothing about the one held by the client
+        assert!(
+            detect_double_finality(&client_id, canonical.slot + 1, H256::zero(), &canonical)
+                .is_none()
+        );
+
+        let misbehaviour =
+            detect_double_finality(&client_id, canonical.slot, H256::zero(), &canonical)
+                .expect("conflicting finality");
+        assert_eq!(misbehaviour.client_id, client_id);
+        assert_eq!(misbehaviour.conflicting_root, H256::zero());
+        assert_eq!(misbehaviour.header, canonical);
+    }
+}
@@ -3,7 +3,7 @@ use ibc_relayer_types::core::{
     ics04_channel::channel::State as ChannelState,
     ics24_host::identifier::{ChannelId, PortChannelId, PortId},
 };
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::chain::requests::{QueryChannelRequest, QueryHeight};
 use crate::chain::{counterparty::check_channel_counterparty, requests::QueryConnectionRequest};
@@ -155,18 +155,26 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> Link<ChainA, ChainB> {
         };
 
         if auto_register_counterparty_payee && a_channel.version.supports_fee() {
-            let address_a = a_chain.get_signer().map_err(LinkError::relayer)?;
-
-            info!(
-                "auto registering counterparty payee on chain {} as {} on chain {}",
-                b_chain.id(),
-                address_a,
-                a_chain.id()
-            );
-
-            b_chain
-                .maybe_register_counterparty_payee(b_channel_id.clone(), b_port_id, address_a)
-                .map_err(LinkError::relayer)?;
+            let capabilities = b_chain.capabilities().map_err(LinkError::relayer)?;
+            if capabilities.fee_middleware {
+                let address_a = a_chain.get_signer().map_err(LinkError::relayer)?;
+
+                info!(
+                    "auto registering counterparty payee on chain {} as {} on chain {}",
+                    b_chain.id(),
+                    address_a,
+                    a_chain.id()
+                );
+
+                b_chain
+                    .maybe_register_counterparty_payee(b_channel_id.clone(), b_port_id, address_a)
+                    .map_err(LinkError::relayer)?;
+            } else {
+                warn!(
+                    "chain {} does not support the fee middleware, not registering the counterparty payee",
+                    b_chain.id()
+                );
+            }
         }
 
         Link::new(channel, with_tx_confirmation)
@@ -1,11 +1,14 @@
-use alloc::collections::BTreeMap as HashMap;
 use alloc::collections::VecDeque;
+use std::mem;
 use std::ops::Sub;
+use std::thread;
 use std::time::{Duration, Instant};
 
 use ibc_proto::google::protobuf::Any;
+use ibc_relayer_types::applications::transfer::packet::PacketData;
+use ibc_relayer_types::applications::transfer::RawCoin;
 use itertools::Itertools;
-use tracing::{debug, error, info, span, trace, warn, Level};
+use tracing::{debug, error, info, span, trace, warn, Level, Span};
 
 use ibc_relayer_types::core::ics02_client::events::ClientMisbehaviour as ClientMisbehaviourEvent;
 use ibc_relayer_types::core::ics04_channel::channel::{ChannelEnd, Order, State as ChannelState};
@@ -40,6 +43,8 @@ use crate::chain::tracking::TrackedMsgs;
 use crate::chain::tracking::TrackingId;
 use crate::channel::error::ChannelError;
 use crate::channel::Channel;
+use crate::config::filter::TransferPolicy;
+use crate::decision::{self, Decision, DecisionKind};
 use crate::event::monitor::EventBatch;
 use crate::event::IbcEventWithHeight;
 use crate::foreign_client::{ForeignClient, ForeignClientError};
@@ -106,6 +111,18 @@ pub struct RelayPath<ChainA: ChainHandle, ChainB: ChainHandle> {
     // transactions if [`confirm_txes`] is true.
     pending_txs_src: PendingTxs<ChainA>,
     pending_txs_dst: PendingTxs<ChainB>,
+
+    // Minimum amounts of the ICS-20 packets which are relayed from the event batches
+    // and while clearing packets.
+    transfer_policy: TransferPolicy,
+
+    // Whether the operational data targeting the source and the destination chains
+    // are relayed concurrently.
+    concurrent_targets: bool,
+    // Maximum number of pieces of operational data relayed at once to the source and
+    // destination chains.
+    src_lanes: usize,
+    dst_lanes: usize,
 }
 
 impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
@@ -150,9 +167,35 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
             confirm_txes: with_tx_confirmation,
             pending_txs_src: PendingTxs::new(src_chain, src_channel_id, src_port_id, dst_chain_id),
             pending_txs_dst: PendingTxs::new(dst_chain, dst_channel_id, dst_port_id, src_chain_id),
+
+            transfer_policy: TransferPolicy::default(),
+
+            concurrent_targets: false,
+            src_lanes: 1,
+            dst_lanes: 1,
         })
     }
 
+    /// Skip the ICS-20 packets transferring less than the minimum amounts of `policy`.
+    pub fn set_transfer_policy(&mut self, policy: TransferPolicy) {
+        self.transfer_policy = policy;
+    }
+
+    /// Relay up to `concurrency` pieces of operational data at once to each chain,
+    /// except to the chains serializing the transactions of a channel, to which the
+    /// operational data are still relayed one after the other.
+    pub fn set_concurrency(&mut self, concurrency: usize) -> Result<(), LinkError> {
+        let lanes = |serialized: bool| if serialized { 1 } else { concurrency.max(1) };
+
+        let src_config = self.src_chain().config().map_err(LinkError::relayer)?;
+        let dst_config = self.dst_chain().config().map_err(LinkError::relayer)?;
+
+        self.concurrent_targets = concurrency > 1;
+        self.src_lanes = lanes(src_config.serializes_channel_txs());
+        self.dst_lanes = lanes(dst_config.serializes_channel_txs());
+        Ok(())
+    }
+
     pub fn src_chain(&self) -> &ChainA {
         self.channel.src_chain()
     }
@@ -328,6 +371,7 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
     fn build_chan_close_confirm_from_event(
         &self,
         event: &IbcEventWithHeight,
+        height: Height,
     ) -> Result<Option<Any>, LinkError> {
         // Build the `MsgChannelCloseConfirm` only from `Timeout` or `CloseInitChannel` event types
         if event.event.event_type() != IbcEventType::Timeout
@@ -347,7 +391,7 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
         let src_channel_id = self.src_channel_id();
         let proofs = self
             .src_chain()
-            .build_channel_proofs(self.src_port_id(), src_channel_id, event.height)
+            .build_channel_proofs(self.src_port_id(), src_channel_id, height)
             .map_err(|e| LinkError::channel(ChannelError::channel_proof(e)))?;
 
         // Build the domain type message
@@ -478,6 +522,8 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
         &self,
         events: TrackedEvents,
     ) -> Result<(), LinkError> {
+        let events = self.filter_dust_transfers(events);
+
         // Obtain the operational data for the source chain (mostly timeout packets) and for the
         // destination chain (e.g., receive packet messages).
         let (src_opt, dst_opt) = self.generate_operational_data(events)?;
@@ -492,6 +538,72 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
         Ok(())
     }
 
+    /// Drops the `SendPacket` events of the ICS-20 packets which transfer less than the
+    /// minimum amount of their denom. Packets whose data is not an ICS-20 transfer are kept.
+    fn filter_dust_transfers(&self, events: TrackedEvents) -> TrackedEvents {
+        let tracking_id = events.tracking_id();
+        let retained = events
+            .events()
+            .iter()
+            .filter(|event_with_height| match &event_with_height.event {
+                IbcEvent::SendPacket(event) => {
+                    match serde_json::from_slice::<PacketData>(&event.packet.data) {
+                        Ok(data) => {
+                            let token =
+                                RawCoin::new(data.token.denom.to_string(), data.token.amount);
+                            let relay = self.transfer_policy.should_relay(&token);
+                            if !relay {
+                                debug!(
+                                    packet = %event.packet,
+                                    amount = %token.amount,
+                                    denom = %token.denom,
+                                    "skipping packet transferring less than the minimum amount"
+                                );
+                                self.publish_decision(
+                                    DecisionKind::Skipped,
+                                    &event_with_height.event,
+                                    format!(
+                                        "transfers {} {}, less than the minimum amount",
+                                        token.amount, token.denom
+                                    ),
+                                );
+                            }
+                            relay
+                        }
+                        Err(_) => true,
+                    }
+                }
+                _ => true,
+            })
+            .cloned()
+            .collect();
+
+        TrackedEvents::new(retained, tracking_id)
+    }
+
+    /// Publishes the decision taken about the event to the [`decision`] feed.
+    fn publish_decision(&self, kind: DecisionKind, event: &IbcEvent, reason: impl Into<String>) {
+        decision::global().publish(Decision::new(
+            kind,
+            self.src_chain().id(),
+            self.dst_chain().id(),
+            self.src_port_id().clone(),
+            self.src_channel_id().clone(),
+            event.packet().map(|packet| packet.sequence),
+            reason,
+        ));
+    }
+
+    fn publish_decisions(&self, kind: DecisionKind, odata: &OperationalData, reason: &str) {
+        for msg in odata.batch.iter() {
+            self.publish_decision(
+                kind,
+                &msg.event_with_height.event,
+                format!("{}: {reason}", msg.msg.type_url),
+            );
+        }
+    }
+
     /// Generates operational data out of a set of events.
     /// Handles building operational data targeting both the destination and source chains.
     ///
@@ -512,9 +624,9 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
         .entered();
 
         let input = events.events();
-        let src_height = match input.get(0) {
+        let src_height = match input.iter().map(|ev| ev.height).max() {
             None => return Ok((None, None)),
-            Some(ev) => ev.height,
+            Some(height) => self.dst_proofs_height(height)?,
         };
 
         let dst_latest_info = self
@@ -545,7 +657,7 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
 
             let (dst_msg, src_msg) = match &event_with_height.event {
                 IbcEvent::CloseInitChannel(_) => (
-                    self.build_chan_close_confirm_from_event(event_with_height)?,
+                    self.build_chan_close_confirm_from_event(event_with_height, src_height)?,
                     None,
                 ),
                 IbcEvent::TimeoutPacket(_) => {
@@ -560,7 +672,10 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
                             .state_matches(&ChannelState::Closed)
                     {
                         (
-                            self.build_chan_close_confirm_from_event(event_with_height)?,
+                            self.build_chan_close_confirm_from_event(
+                                event_with_height,
+                                src_height,
+                            )?,
                             None,
                         )
                     } else {
@@ -576,7 +691,7 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
                         self.build_recv_or_timeout_from_send_packet_event(
                             event,
                             &dst_latest_info,
-                            event_with_height.height,
+                            src_height,
                         )?
                     }
                 }
@@ -594,10 +709,7 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
 
                         (None, None)
                     } else {
-                        (
-                            self.build_ack_from_recv_event(event, event_with_height.height)?,
-                            None,
-                        )
+                        (self.build_ack_from_recv_event(event, src_height)?, None)
                     }
                 }
                 _ => (None, None),
@@ -645,6 +757,30 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
 
         let mut odata = initial_od;
 
+        // The destination may have progressed past the timeout of some packets since the
+        // operational data was generated, relaying their `RecvPacket` would only revert.
+        // Send the timeouts to the source chain instead.
+        if odata.target == OperationalDataTarget::Destination {
+            let dst_status = self
+                .dst_chain()
+                .query_application_status()
+                .map_err(|e| LinkError::query(self.dst_chain().id(), e))?;
+
+            if let Some(timeout_od) = self.split_timed_out_packets(&mut odata, &dst_status)? {
+                info!(
+                    "re-scheduling {} timed-out packet(s) to the source chain",
+                    timeout_od.batch.len()
+                );
+
+                self.schedule_operational_data(timeout_od)?;
+            }
+
+            if odata.batch.is_empty() {
+                info!("exhausted all events from this operational data");
+                return Ok(S::Reply::empty());
+            }
+        }
+
         for i in 0..MAX_RETRIES {
             debug!(retry.current = i + 1, retry.max = MAX_RETRIES, "retrying");
 
@@ -654,6 +790,12 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
                     // Done with this op. data
                     info!("submitted");
 
+                    self.publish_decisions(
+                        DecisionKind::Relayed,
+                        &odata,
+                        &format!("submitted to the {} chain", odata.target),
+                    );
+
                     telemetry!({
                         let (chain, counterparty, channel_id, port_id) =
                             self.target_info(odata.target);
@@ -674,7 +816,13 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
                     // This error means we could retry
                     error!("error {}", e.event);
                     if i + 1 == MAX_RETRIES {
-                        error!("{}/{} retries exhausted. giving up", i + 1, MAX_RETRIES)
+                        error!("{}/{} retries exhausted. giving up", i + 1, MAX_RETRIES);
+
+                        self.publish_decisions(
+                            DecisionKind::Quarantined,
+                            &odata,
+                            &format!("{MAX_RETRIES} submissions failed, last error: {}", e.event),
+                        );
                     } else {
                         // If we haven't exhausted all retries, regenerate the op. data & retry
                         match self.regenerate_operational_data(odata.clone()) {
@@ -866,8 +1014,42 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
         Ok(unreceived_ack.is_empty())
     }
 
+    /// Checks if the commitment of a packet has been cleared on the source chain of the
+    /// packet, which happens once its acknowledgment is received there.
+    fn recv_packet_commitment_cleared_on_src(&self, packet: &Packet) -> Result<bool, LinkError> {
+        let (bytes, _) = self
+            .dst_chain()
+            .query_packet_commitment(
+                QueryPacketCommitmentRequest {
+                    port_id: self.dst_port_id().clone(),
+                    channel_id: self.dst_channel_id().clone(),
+                    sequence: packet.sequence,
+                    height: QueryHeight::Latest,
+                },
+                IncludeProof::No,
+            )
+            .map_err(LinkError::relayer)?;
+
+        Ok(bytes.is_empty())
+    }
+
     /// Checks if a receive packet event has already been handled (e.g. by another relayer).
+    ///
+    /// The commitment of the packet is checked first, the same way whatever the type of
+    /// the source chain of the packet, so that no acknowledgment destined to fail is sent
+    /// once it is cleared.
     fn write_ack_event_handled(&self, rp: &WriteAcknowledgement) -> Result<bool, LinkError> {
+        if self.recv_packet_commitment_cleared_on_src(&rp.packet)? {
+            self.publish_decision(
+                DecisionKind::Skipped,
+                &IbcEvent::WriteAcknowledgement(rp.clone()),
+                format!(
+                    "packet commitment is already cleared on {}",
+                    self.dst_chain().id()
+                ),
+            );
+            return Ok(true);
+        }
         self.recv_packet_acknowledged_on_src(&rp.packet)
     }
 
@@ -1399,12 +1581,20 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
     ///
     /// Note that pieces of operational data that have not elapsed yet are
     /// also placed in the 'unprocessed' bucket.
+    ///
+    /// Up to as many elapsed pieces of operational data as there are lanes
+    /// to the target chain are relayed at once.
     fn execute_schedule_for_target_chain<I: Iterator<Item = OperationalData>>(
-        &mut self,
+        &self,
         mut operations: I,
         target_chain: OperationalDataTarget,
     ) -> Result<VecDeque<OperationalData>, (VecDeque<OperationalData>, LinkError)> {
         let mut unprocessed = VecDeque::new();
+        let lanes = match target_chain {
+            OperationalDataTarget::Source => self.src_lanes,
+            OperationalDataTarget::Destination => self.dst_lanes,
+        };
+        let mut elapsed_ods = Vec::with_capacity(lanes);
 
         while let Some(od) = operations.next() {
             let elapsed_result = match target_chain {
@@ -1424,19 +1614,19 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
                 Ok(elapsed) => {
                     if elapsed {
                         // The current piece of operational data has elapsed; we can go ahead and
-                        // attempt to relay it.
-                        match self
-                            .relay_from_operational_data::<relay_sender::AsyncSender>(od.clone())
+                        // attempt to relay it, once there is one for each lane.
+                        elapsed_ods.push(od);
+                        if elapsed_ods.len() < lanes {
+                            continue;
+                        }
+                        // If the relaying process failed, return all of the subsequent pieces of operational
+                        // data along with the underlying error that occurred.
+                        if let Err(e) =
+                            self.relay_elapsed_operational_data(mem::take(&mut elapsed_ods))
                         {
-                            // The operational data was successfully relayed; enqueue the associated tx.
-                            Ok(reply) => self.enqueue_pending_tx(reply, od),
-                            // The relaying process failed; return all of the subsequent pieces of operational
-                            // data along with the underlying error that occurred.
-                            Err(e) => {
-                                unprocessed.extend(operations);
-
-                                return Err((unprocessed, e));
-                            }
+                            unprocessed.extend(operations);
+
+                            return Err((unprocessed, e));
                         }
                     } else {
                         // The current piece of operational data has not elapsed; add it to the bucket
@@ -1450,6 +1640,7 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
                     // operational data has elapsed or not. Add the current piece of data, along with
                     // all of the subsequent pieces of data, to the unprocessed bucket and return it
                     // along with the error that resulted.
+                    unprocessed.extend(elapsed_ods);
                     unprocessed.push_back(od);
                     unprocessed.extend(operations);
 
@@ -1458,9 +1649,62 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
             }
         }
 
+        if let Err(e) = self.relay_elapsed_operational_data(elapsed_ods) {
+            return Err((unprocessed, e));
+        }
+
         Ok(unprocessed)
     }
 
+    /// Relays elapsed pieces of operational data targeting the same chain, each in
+    /// its own thread if there are several, and enqueues the associated txs.
+    ///
+    /// The pieces of operational data that failed to send are dropped, and the
+    /// first error that occurred is returned.
+    fn relay_elapsed_operational_data(&self, ods: Vec<OperationalData>) -> Result<(), LinkError> {
+        if ods.len() <= 1 {
+            for od in ods {
+                let reply =
+                    self.relay_from_operational_data::<relay_sender::AsyncSender>(od.clone())?;
+                self.enqueue_pending_tx(reply, od);
+            }
+            return Ok(());
+        }
+
+        let span = Span::current();
+        let results = thread::scope(|scope| {
+            let handles = ods
+                .into_iter()
+                .map(|od| {
+                    let span = span.clone();
+                    scope.spawn(move || {
+                        let _span = span.entered();
+                        let result = self
+                            .relay_from_operational_data::<relay_sender::AsyncSender>(od.clone());
+                        (result, od)
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("relaying thread panicked"))
+                .collect::<Vec<_>>()
+        });
+
+        let mut first_error = None;
+        for (result, od) in results {
+            match result {
+                Ok(reply) => self.enqueue_pending_tx(reply, od),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        first_error.map_or(Ok(()), Err)
+    }
+
     /// While there are pending operational data items, this function
     /// performs the relaying of packets corresponding to those
     /// operational data items to both the source and destination chains.
@@ -1469,6 +1713,10 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
     /// dropped. Subsequent pending operational data items that went unprocessed
     /// are queued up again for re-submission.
     pub fn execute_schedule(&mut self) -> Result<(), LinkError> {
+        if self.concurrent_targets {
+            return self.execute_schedule_concurrently();
+        }
+
         let src_od_iter = self.src_operational_data.take().into_iter();
 
         match self.execute_schedule_for_target_chain(src_od_iter, OperationalDataTarget::Source) {
@@ -1494,6 +1742,43 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
         Ok(())
     }
 
+    /// Same as [`RelayPath::execute_schedule`], except that the operational data items
+    /// targeting the source and the destination chains are relayed concurrently, the
+    /// failure of one side not preventing the relaying to the other.
+    fn execute_schedule_concurrently(&mut self) -> Result<(), LinkError> {
+        let src_od_iter = self.src_operational_data.take().into_iter();
+        let dst_od_iter = self.dst_operational_data.take().into_iter();
+
+        let this = &*self;
+        let span = Span::current();
+        let (src_result, dst_result) = thread::scope(|scope| {
+            let src_handle = scope.spawn(move || {
+                let _span = span.entered();
+                this.execute_schedule_for_target_chain(src_od_iter, OperationalDataTarget::Source)
+            });
+            let dst_result = this
+                .execute_schedule_for_target_chain(dst_od_iter, OperationalDataTarget::Destination);
+            let src_result = src_handle.join().expect("relaying thread panicked");
+            (src_result, dst_result)
+        });
+
+        let (unprocessed_src_data, src_error) = match src_result {
+            Ok(unprocessed) => (unprocessed, None),
+            Err((unprocessed, e)) => (unprocessed, Some(e)),
+        };
+        let (unprocessed_dst_data, dst_error) = match dst_result {
+            Ok(unprocessed) => (unprocessed, None),
+            Err((unprocessed, e)) => (unprocessed, Some(e)),
+        };
+        self.src_operational_data = unprocessed_src_data.into();
+        self.dst_operational_data = unprocessed_dst_data.into();
+
+        match src_error.or(dst_error) {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
     /// Kicks off the process of relaying pending txs to the source and destination chains.
     ///
     /// See [`Resubmit::from_clear_interval`] for more info about the `resubmit` parameter.
@@ -1568,69 +1853,18 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
             .query_application_status()
             .map_err(|e| LinkError::query(self.src_chain().id(), e))?;
 
-        let dst_current_height = dst_status.height;
-
         // Intermediary data struct to help better manage the transfer from dst. operational data
         // to source operational data.
         let mut all_dst_odata = self.dst_operational_data.clone_vec();
 
-        let mut timed_out: HashMap<usize, OperationalData> = HashMap::default();
-
-        // For each operational data targeting the destination chain...
-        for (odata_pos, odata) in all_dst_odata.iter_mut().enumerate() {
-            // ... check each `SendPacket` event, whether it should generate a timeout message
-            let mut retain_batch = vec![];
-
-            for gm in odata.batch.iter() {
-                let TransitMessage {
-                    event_with_height, ..
-                } = gm;
-
-                match &event_with_height.event {
-                    IbcEvent::SendPacket(event) => {
-                        // Catch any SendPacket event that timed-out
-                        if self.send_packet_event_handled(event)? {
-                            debug!(?event, "SendPacket event has already been handled");
-                        } else if let Some(new_msg) =
-                            self.build_timeout_from_send_packet_event(event, &dst_status)?
-                        {
-                            debug!(
-                                "found a timed-out message in the operational data: {}",
-                                odata.info(),
-                            );
-
-                            timed_out
-                                .entry(odata_pos)
-                                .or_insert_with(|| {
-                                    OperationalData::new(
-                                        dst_current_height,
-                                        OperationalDataTarget::Source,
-                                        odata.tracking_id,
-                                        self.channel.connection_delay,
-                                    )
-                                })
-                                .push(TransitMessage {
-                                    event_with_height: event_with_height.clone(),
-                                    msg: new_msg,
-                                });
-                        } else {
-                            // A SendPacket event, but did not time-out yet, retain
-                            retain_batch.push(gm.clone());
-                        }
-                    }
-                    IbcEvent::WriteAcknowledgement(event) => {
-                        if self.write_ack_event_handled(event)? {
-                            debug!(?event, "WriteAcknowledgement has already been handled");
-                        } else {
-                            retain_batch.push(gm.clone());
-                        }
-                    }
-                    _ => retain_batch.push(gm.clone()),
-                }
-            }
+        let mut timed_out: Vec<OperationalData> = vec![];
 
-            // Update the whole batch, keeping only the relevant ones
-            odata.batch = retain_batch;
+        // For each operational data targeting the destination chain, check whether any
+        // `SendPacket` event should generate a timeout message instead
+        for odata in all_dst_odata.iter_mut() {
+            if let Some(timeout_od) = self.split_timed_out_packets(odata, &dst_status)? {
+                timed_out.push(timeout_od);
+            }
         }
 
         // Possibly some op. data became empty (if no events were kept).
@@ -1647,7 +1881,7 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
         }
 
         // Schedule new operational data targeting the source chain
-        for (_, new_od) in timed_out.into_iter() {
+        for new_od in timed_out.into_iter() {
             info!(
                 "re-scheduling from new timed-out batch of size {}",
                 new_od.batch.len()
@@ -1659,6 +1893,73 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
         Ok(())
     }
 
+    /// Removes from the batch of the given operational data, which targets the destination
+    /// chain, the messages for events that were already handled and the `RecvPacket`
+    /// messages of packets that timed out according to `dst_status`.
+    ///
+    /// Returns the operational data targeting the source chain with the timeout messages
+    /// of these packets, if any packet timed out.
+    fn split_timed_out_packets(
+        &self,
+        odata: &mut OperationalData,
+        dst_status: &ChainStatus,
+    ) -> Result<Option<OperationalData>, LinkError> {
+        let mut timed_out: Option<OperationalData> = None;
+        let mut retain_batch = vec![];
+
+        for gm in odata.batch.iter() {
+            let TransitMessage {
+                event_with_height, ..
+            } = gm;
+
+            match &event_with_height.event {
+                IbcEvent::SendPacket(event) => {
+                    // Catch any SendPacket event that timed-out
+                    if self.send_packet_event_handled(event)? {
+                        debug!(?event, "SendPacket event has already been handled");
+                    } else if let Some(new_msg) =
+                        self.build_timeout_from_send_packet_event(event, dst_status)?
+                    {
+                        debug!(
+                            "found a timed-out message in the operational data: {}",
+                            odata.info(),
+                        );
+
+                        timed_out
+                            .get_or_insert_with(|| {
+                                OperationalData::new(
+                                    dst_status.height,
+                                    OperationalDataTarget::Source,
+                                    odata.tracking_id,
+                                    self.channel.connection_delay,
+                                )
+                            })
+                            .push(TransitMessage {
+                                event_with_height: event_with_height.clone(),
+                                msg: new_msg,
+                            });
+                    } else {
+                        // A SendPacket event, but did not time-out yet, retain
+                        retain_batch.push(gm.clone());
+                    }
+                }
+                IbcEvent::WriteAcknowledgement(event) => {
+                    if self.write_ack_event_handled(event)? {
+                        debug!(?event, "WriteAcknowledgement has already been handled");
+                    } else {
+                        retain_batch.push(gm.clone());
+                    }
+                }
+                _ => retain_batch.push(gm.clone()),
+            }
+        }
+
+        // Update the whole batch, keeping only the relevant ones
+        odata.batch = retain_batch;
+
+        Ok(timed_out)
+    }
+
     /// Adds a new operational data item for this relaying path to process later.
     /// If the relaying path has non-zero packet delays, this method also updates the client on the
     /// target chain with the appropriate headers.
@@ -1678,6 +1979,11 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
         // instant in the past, i.e. when this client update was first processed (`processed_time`)
         let scheduled_time = if od.conn_delay_needed() {
             debug!("connection delay must be taken into account: updating client");
+            self.publish_decisions(
+                DecisionKind::Deferred,
+                &od,
+                "waiting for the connection delay to elapse",
+            );
             let target_height = od.proofs_height.increment();
             match od.target {
                 OperationalDataTarget::Source => {
@@ -1754,6 +2060,41 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
         Ok((elapsed_src_ods, elapsed_dst_ods))
     }
 
+    /// Height of the source chain at which the proofs of the messages relaying events up
+    /// to `events_height` are built.
+    ///
+    /// The proofs at a height are verified against the consensus state at the next height of
+    /// the client on the destination chain. When the events are older than the latest
+    /// consensus state of the client, e.g. when a packet is relayed long after it was sent,
+    /// the proofs are pinned below that consensus state: the packet commitments and the
+    /// acknowledgements are still provable there, and the source chain may have pruned the
+    /// states at the height of the events by now. Otherwise, the proofs are built at the
+    /// height of the events and the client is updated to the next height ahead of them.
+    fn dst_proofs_height(&self, events_height: Height) -> Result<Height, LinkError> {
+        let consensus_heights = self
+            .restore_dst_client()
+            .fetch_consensus_state_heights()
+            .map_err(LinkError::client)?;
+
+        let proofs_height = pinned_proofs_height(&consensus_heights, events_height);
+        if proofs_height == events_height {
+            debug!(
+                "no consensus state of client {} verifies proofs at {}, the client will be updated to {}",
+                self.dst_client_id(),
+                events_height,
+                events_height.increment()
+            );
+        } else {
+            debug!(
+                "pinning proofs of events at {} to {}, below the latest consensus state of client {}",
+                events_height,
+                proofs_height,
+                self.dst_client_id()
+            );
+        }
+        Ok(proofs_height)
+    }
+
     fn restore_src_client(&self) -> ForeignClient<ChainA, ChainB> {
         ForeignClient::restore(
             self.src_client_id().clone(),
@@ -1872,3 +2213,48 @@ impl<ChainA: ChainHandle, ChainB: ChainHandle> RelayPath<ChainA, ChainB> {
         }
     }
 }
+
+/// Height right below the latest consensus state in `consensus_heights` if it is above
+/// `events_height`, so that the proofs at that height are verifiable without updating the
+/// client, or `events_height` otherwise
+fn pinned_proofs_height(consensus_heights: &[Height], events_height: Height) -> Height {
+    consensus_heights
+        .iter()
+        .filter(|height| **height > events_height)
+        .max()
+        .and_then(|height| height.decrement().ok())
+        .unwrap_or(events_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use ibc_relayer_types::Height;
+
+    use super::pinned_proofs_height;
+
+    #[test]
+    fn test_pinned_proofs_height() {
+        let height = |h| Height::new(1, h).unwrap();
+        let consensus_heights = [height(10), height(30), height(20)];
+
+        // old events are proven below the latest consensus state
+        assert_eq!(
+            pinned_proofs_height(&consensus_heights, height(5)),
+            height(29)
+        );
+        assert_eq!(
+            pinned_proofs_height(&consensus_heights, height(29)),
+            height(29)
+        );
+        // the client has to be updated for the recent events
+        assert_eq!(
+            pinned_proofs_height(&consensus_heights, height(30)),
+            height(30)
+        );
+        assert_eq!(
+            pinned_proofs_height(&consensus_heights, height(42)),
+            height(42)
+        );
+        assert_eq!(pinned_proofs_height(&[], height(5)), height(5));
+    }
+}
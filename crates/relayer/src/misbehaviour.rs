@@ -1,4 +1,7 @@
 use ibc_proto::{google::protobuf::Any, protobuf::Protobuf};
+use ibc_relayer_types::clients::ics07_eth::misbehaviour::{
+    Misbehaviour as EthMisbehaviour, ETH_MISBEHAVIOUR_TYPE_URL,
+};
 use ibc_relayer_types::clients::ics07_tendermint::misbehaviour::{
     Misbehaviour as TmMisbehaviour, TENDERMINT_MISBEHAVIOR_TYPE_URL,
 };
@@ -25,6 +28,7 @@ pub struct MisbehaviourEvidence {
 #[allow(clippy::large_enum_variant)]
 pub enum AnyMisbehaviour {
     Tendermint(TmMisbehaviour),
+    Eth(EthMisbehaviour),
 
     #[cfg(test)]
     Mock(MockMisbehaviour),
@@ -34,6 +38,7 @@ impl Misbehaviour for AnyMisbehaviour {
     fn client_id(&self) -> &ClientId {
         match self {
             Self::Tendermint(misbehaviour) => misbehaviour.client_id(),
+            Self::Eth(misbehaviour) => misbehaviour.client_id(),
 
             #[cfg(test)]
             Self::Mock(misbehaviour) => misbehaviour.client_id(),
@@ -43,6 +48,7 @@ impl Misbehaviour for AnyMisbehaviour {
     fn height(&self) -> Height {
         match self {
             Self::Tendermint(misbehaviour) => misbehaviour.height(),
+            Self::Eth(misbehaviour) => misbehaviour.height(),
 
             #[cfg(test)]
             Self::Mock(misbehaviour) => misbehaviour.height(),
@@ -60,6 +66,7 @@ impl TryFrom<Any> for AnyMisbehaviour {
             TENDERMINT_MISBEHAVIOR_TYPE_URL => Ok(AnyMisbehaviour::Tendermint(
                 TmMisbehaviour::decode_vec(&raw.value).map_err(Error::decode_raw_misbehaviour)?,
             )),
+            ETH_MISBEHAVIOUR_TYPE_URL => Ok(AnyMisbehaviour::Eth(EthMisbehaviour::try_from(raw)?)),
 
             #[cfg(test)]
             MOCK_MISBEHAVIOUR_TYPE_URL => Ok(AnyMisbehaviour::Mock(
@@ -80,6 +87,7 @@ impl From<AnyMisbehaviour> for Any {
                     .encode_vec()
                     .expect("encoding to `Any` from `AnyMisbehavior::Tendermint`"),
             },
+            AnyMisbehaviour::Eth(misbehaviour) => misbehaviour.into(),
 
             #[cfg(test)]
             AnyMisbehaviour::Mock(misbehaviour) => Any {
@@ -96,6 +104,7 @@ impl core::fmt::Display for AnyMisbehaviour {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         match self {
             AnyMisbehaviour::Tendermint(tm) => write!(f, "{tm}"),
+            AnyMisbehaviour::Eth(eth) => write!(f, "{eth}"),
 
             #[cfg(test)]
             AnyMisbehaviour::Mock(mock) => write!(f, "{mock:?}"),
@@ -109,6 +118,12 @@ impl From<TmMisbehaviour> for AnyMisbehaviour {
     }
 }
 
+impl From<EthMisbehaviour> for AnyMisbehaviour {
+    fn from(misbehaviour: EthMisbehaviour) -> Self {
+        Self::Eth(misbehaviour)
+    }
+}
+
 #[cfg(test)]
 impl From<MockMisbehaviour> for AnyMisbehaviour {
     fn from(misbehaviour: MockMisbehaviour) -> Self {
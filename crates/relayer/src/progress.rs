@@ -0,0 +1,303 @@
+//! Progress of the relaying on a chain, persisted across restarts.
+//!
+//! Without it, a restarted relayer scans the chain from scratch and has no idea of the
+//! transactions it sent right before stopping, so it may submit their packets again.
+//! The progress records how far the event monitor has scanned the chain, the
+//! transactions which are sent but not resolved yet along with the packets they relay,
+//! and the packets relayed lately, so that the monitor and the transaction tracker
+//! resume where they left off.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Error as FmtError, Formatter};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, PortId};
+use serde_derive::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Number of relayed packets remembered per channel and step, the older ones being
+/// forgotten first
+pub const MAX_RELAYED_SEQUENCES: usize = 1024;
+
+/// Message relaying a packet to the chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PacketStep {
+    Recv,
+    Ack,
+    Timeout,
+}
+
+impl Display for PacketStep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::Recv => write!(f, "recv"),
+            Self::Ack => write!(f, "ack"),
+            Self::Timeout => write!(f, "timeout"),
+        }
+    }
+}
+
+/// Packet relayed to the chain, identified by the channel end of the chain
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayedPacket {
+    pub step: PacketStep,
+    pub port_id: PortId,
+    pub channel_id: ChannelId,
+    pub sequence: Sequence,
+}
+
+impl RelayedPacket {
+    fn channel_key(&self) -> String {
+        format!("{}/{}/{}", self.step, self.port_id, self.channel_id)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Progress {
+    /// Height up to which the events of each scan have been emitted
+    #[serde(default)]
+    scanned_heights: BTreeMap<String, u64>,
+    /// Packets relayed by the transactions sent and not resolved yet, by transaction hash
+    #[serde(default)]
+    in_flight_txs: BTreeMap<String, Vec<RelayedPacket>>,
+    /// Sequences of the packets relayed lately, by step and channel end
+    #[serde(default)]
+    relayed_sequences: BTreeMap<String, BTreeSet<u64>>,
+}
+
+/// Snapshot of the progress of the relaying on a chain
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayStatus {
+    pub latest_height: u64,
+    /// Height up to which the events of each scan have been emitted
+    pub scanned_heights: BTreeMap<String, u64>,
+    /// Number of blocks the event monitor lags behind the chain, by its slowest scan
+    pub monitor_lag: Option<u64>,
+    /// Hashes of the transactions sent and not resolved yet
+    pub pending_txs: Vec<String>,
+}
+
+/// Progress of the relaying on a chain.
+///
+/// The progress is written to `path` on every update if it is given, and loaded from it
+/// on startup, otherwise it only lasts as long as the relayer runs.
+pub struct RelayProgress {
+    path: Option<PathBuf>,
+    progress: Progress,
+}
+
+/// Progress shared by the chain endpoint and its event monitor
+pub type SharedRelayProgress = Arc<Mutex<RelayProgress>>;
+
+impl RelayProgress {
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let progress = path
+            .as_ref()
+            .filter(|path| path.exists())
+            .and_then(|path| {
+                let content = std::fs::read_to_string(path)
+                    .map_err(|e| warn!("failed to read relay progress {}: {e}", path.display()))
+                    .ok()?;
+                serde_json::from_str(&content)
+                    .map_err(|e| warn!("failed to parse relay progress {}: {e}", path.display()))
+                    .ok()
+            })
+            .unwrap_or_default();
+        Self { path, progress }
+    }
+
+    pub fn shared(path: Option<PathBuf>) -> SharedRelayProgress {
+        Arc::new(Mutex::new(Self::load(path)))
+    }
+
+    /// Height up to which the events of `scan` have been emitted, if it ever ran
+    pub fn scanned_height(&self, scan: &str) -> Option<u64> {
+        self.progress.scanned_heights.get(scan).copied()
+    }
+
+    pub fn set_scanned_height(&mut self, scan: &str, height: u64) {
+        if self.scanned_height(scan) == Some(height) {
+            return;
+        }
+        self.progress
+            .scanned_heights
+            .insert(scan.to_owned(), height);
+        self.save();
+    }
+
+    /// Drop the scanned height of a scan which no longer runs, returning it
+    pub fn forget_scan(&mut self, scan: &str) -> Option<u64> {
+        let height = self.progress.scanned_heights.remove(scan)?;
+        self.save();
+        Some(height)
+    }
+
+    /// Status of the relaying, given the latest height of the chain
+    pub fn status(&self, latest_height: u64) -> RelayStatus {
+        let scanned_heights = self.progress.scanned_heights.clone();
+        let monitor_lag = scanned_heights
+            .values()
+            .min()
+            .map(|height| latest_height.saturating_sub(*height));
+        RelayStatus {
+            latest_height,
+            scanned_heights,
+            monitor_lag,
+            pending_txs: self.in_flight_txs(),
+        }
+    }
+
+    /// Hashes of the transactions sent and not resolved yet
+    pub fn in_flight_txs(&self) -> Vec<String> {
+        self.progress.in_flight_txs.keys().cloned().collect()
+    }
+
+    /// Record the transactions about to be sent, along with the packets they relay
+    pub fn add_in_flight_txs(&mut self, txs: Vec<(String, Vec<RelayedPacket>)>) {
+        if txs.is_empty() {
+            return;
+        }
+        self.progress.in_flight_txs.extend(txs);
+        self.save();
+    }
+
+    /// Forget the transactions sent, recording the packets of the ones which are
+    /// `committed` as relayed
+    pub fn resolve_in_flight_txs(&mut self, txs: Vec<(String, bool)>) {
+        let mut resolved = false;
+        for (tx_hash, committed) in txs {
+            let Some(packets) = self.progress.in_flight_txs.remove(&tx_hash) else {
+                continue;
+            };
+            resolved = true;
+            if committed {
+                packets
+                    .iter()
+                    .for_each(|packet| self.insert_relayed(packet));
+            }
+        }
+        if resolved {
+            self.save();
+        }
+    }
+
+    pub fn is_relayed(&self, packet: &RelayedPacket) -> bool {
+        self.progress
+            .relayed_sequences
+            .get(&packet.channel_key())
+            .map_or(false, |sequences| {
+                sequences.contains(&u64::from(packet.sequence))
+            })
+    }
+
+    /// Forget the whole progress, e.g. once the chain is reset
+    pub fn clear(&mut self) {
+        if self.progress == Progress::default() {
+            return;
+        }
+        self.progress = Progress::default();
+        self.save();
+    }
+
+    fn insert_relayed(&mut self, packet: &RelayedPacket) {
+        let sequences = self
+            .progress
+            .relayed_sequences
+            .entry(packet.channel_key())
+            .or_default();
+        sequences.insert(packet.sequence.into());
+        while sequences.len() > MAX_RELAYED_SEQUENCES {
+            let oldest = *sequences.iter().next().expect("sequences are not empty");
+            sequences.remove(&oldest);
+        }
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_string_pretty(&self.progress)
+            .map_err(|e| e.to_string())
+            .and_then(|content| std::fs::write(path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("failed to save relay progress {}: {e}", path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+    use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, PortId};
+
+    use super::{PacketStep, RelayProgress, RelayedPacket, MAX_RELAYED_SEQUENCES};
+
+    fn packet(step: PacketStep, sequence: u64) -> RelayedPacket {
+        RelayedPacket {
+            step,
+            port_id: PortId::transfer(),
+            channel_id: ChannelId::new(0),
+            sequence: Sequence::from(sequence),
+        }
+    }
+
+    #[test]
+    fn test_relay_progress_is_persisted() {
+        let path = std::env::temp_dir().join("forcerelay_relay_progress_test.json");
+        let _ = std::fs::remove_file(&path);
+
+        let mut progress = RelayProgress::load(Some(path.clone()));
+        assert_eq!(progress.scanned_height("packet"), None);
+        progress.set_scanned_height("packet", 42);
+        progress.add_in_flight_txs(vec![
+            ("0x01".to_owned(), vec![packet(PacketStep::Recv, 1)]),
+            ("0x02".to_owned(), vec![packet(PacketStep::Recv, 2)]),
+        ]);
+
+        let mut progress = RelayProgress::load(Some(path.clone()));
+        assert_eq!(progress.scanned_height("packet"), Some(42));
+        assert_eq!(progress.in_flight_txs(), vec!["0x01", "0x02"]);
+        progress.resolve_in_flight_txs(vec![("0x01".to_owned(), true), ("0x02".to_owned(), false)]);
+
+        let progress = RelayProgress::load(Some(path.clone()));
+        assert!(progress.in_flight_txs().is_empty());
+        assert!(progress.is_relayed(&packet(PacketStep::Recv, 1)));
+        assert!(!progress.is_relayed(&packet(PacketStep::Recv, 2)));
+        assert!(!progress.is_relayed(&packet(PacketStep::Ack, 1)));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_relayed_sequences_are_bounded() {
+        let mut progress = RelayProgress::load(None);
+        let count = MAX_RELAYED_SEQUENCES as u64 + 1;
+        for sequence in 1..=count {
+            progress.insert_relayed(&packet(PacketStep::Ack, sequence));
+        }
+        assert!(!progress.is_relayed(&packet(PacketStep::Ack, 1)));
+        assert!(progress.is_relayed(&packet(PacketStep::Ack, 2)));
+        assert!(progress.is_relayed(&packet(PacketStep::Ack, count)));
+    }
+
+    #[test]
+    fn test_relay_status() {
+        let mut progress = RelayProgress::load(None);
+        assert_eq!(progress.status(100).monitor_lag, None);
+
+        progress.set_scanned_height("packet", 90);
+        progress.set_scanned_height("channel", 95);
+        progress.add_in_flight_txs(vec![("0x01".to_owned(), vec![])]);
+        let status = progress.status(100);
+        assert_eq!(status.monitor_lag, Some(10));
+        assert_eq!(status.scanned_heights.len(), 2);
+        assert_eq!(status.pending_txs, vec!["0x01"]);
+
+        assert_eq!(progress.forget_scan("packet"), Some(90));
+        assert_eq!(progress.forget_scan("packet"), None);
+        assert_eq!(progress.status(100).monitor_lag, Some(5));
+    }
+}
@@ -41,6 +41,11 @@ impl<Chain: ChainHandle> Registry<Chain> {
         }
     }
 
+    /// Return the configuration used to spawn the chain runtimes.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
     /// Return the size of the registry, i.e., the number of distinct chain runtimes.
     pub fn size(&self) -> usize {
         self.handles.len()
@@ -81,6 +86,14 @@ impl<Chain: ChainHandle> Registry<Chain> {
         }
     }
 
+    /// Replace the configuration used to spawn the chain runtimes.
+    ///
+    /// The runtimes which are already running are left untouched, they have to be
+    /// shut down to be spawned again with the new configuration.
+    pub fn update_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
     /// Shutdown the runtime associated with the given chain identifier.
     pub fn shutdown(&mut self, chain_id: &ChainId) {
         if let Some(handle) = self.handles.remove(chain_id) {
@@ -1,13 +1,19 @@
 use crossbeam_channel::TryRecvError;
 use tracing::{error, trace};
 
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
 use crate::{
+    attestation::SignedHealthAttestation,
     config::Config,
+    rest::chain_state::{ChainState, ChainStateQuery},
     rest::request::ReplySender,
     rest::request::{Request, VersionInfo},
-    supervisor::dump_state::SupervisorState,
+    summary::StartupSummary,
+    supervisor::{dump_state::SupervisorState, pause::PauseTarget},
 };
 
+pub mod chain_state;
 pub mod request;
 
 mod error;
@@ -31,6 +37,13 @@ pub type Receiver = crossbeam_channel::Receiver<Request>;
 //  e.g., adjusting chain config, removing chains, etc.
 pub enum Command {
     DumpState(ReplySender<SupervisorState>),
+    InvalidateCache(ChainId, ReplySender<()>),
+    HealthAttestation(ChainId, ReplySender<SignedHealthAttestation>),
+    ChainState(ChainId, ChainStateQuery, ReplySender<ChainState>),
+    Summary(ReplySender<StartupSummary>),
+    Pause(PauseTarget, ReplySender<Vec<PauseTarget>>),
+    Resume(PauseTarget, ReplySender<Vec<PauseTarget>>),
+    Paused(ReplySender<Vec<PauseTarget>>),
 }
 
 /// Process incoming REST requests.
@@ -82,6 +95,52 @@ pub fn process_incoming_requests(config: &Config, channel: &Receiver) -> Option<
 
                 return Some(Command::DumpState(reply_to));
             }
+
+            Request::InvalidateCache { chain_id, reply_to } => {
+                trace!("InvalidateCache {}", chain_id);
+
+                return Some(Command::InvalidateCache(chain_id, reply_to));
+            }
+
+            Request::HealthAttestation { chain_id, reply_to } => {
+                trace!("HealthAttestation {}", chain_id);
+
+                return Some(Command::HealthAttestation(chain_id, reply_to));
+            }
+
+            Request::ChainState {
+                chain_id,
+                query,
+                reply_to,
+            } => {
+                trace!("ChainState {} {}", chain_id, query);
+
+                return Some(Command::ChainState(chain_id, query, reply_to));
+            }
+
+            Request::Summary { reply_to } => {
+                trace!("Summary");
+
+                return Some(Command::Summary(reply_to));
+            }
+
+            Request::Pause { target, reply_to } => {
+                trace!("Pause {}", target);
+
+                return Some(Command::Pause(target, reply_to));
+            }
+
+            Request::Resume { target, reply_to } => {
+                trace!("Resume {}", target);
+
+                return Some(Command::Resume(target, reply_to));
+            }
+
+            Request::Paused { reply_to } => {
+                trace!("Paused");
+
+                return Some(Command::Paused(reply_to));
+            }
         },
         Err(e) => {
             if !matches!(e, TryRecvError::Empty) {
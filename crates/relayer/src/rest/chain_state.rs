@@ -0,0 +1,117 @@
+//! IBC states of a chain served by the REST server.
+//!
+//! The states are built from the queries of the chain handle, i.e. from the same
+//! queries the `ChainEndpoint` of the chain implements. Some of them only exist on the
+//! CKB and Axon chains, e.g. the light client cells and the progress of the relaying,
+//! and fail on the other chains.
+
+use core::fmt::{Display, Error as FmtError, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use ibc_relayer_types::core::ics03_connection::connection::IdentifiedConnectionEnd;
+use ibc_relayer_types::core::ics04_channel::channel::IdentifiedChannelEnd;
+use ibc_relayer_types::core::ics04_channel::packet::Sequence;
+use ibc_relayer_types::core::ics24_host::identifier::{ChannelId, PortId};
+
+use crate::chain::ckb::client_cells::ClientCells;
+use crate::chain::handle::ChainHandle;
+use crate::chain::requests::{
+    PageRequest, QueryChannelsRequest, QueryConnectionsRequest, QueryPacketCommitmentsRequest,
+};
+use crate::error::Error;
+use crate::progress::RelayStatus;
+
+/// State of a chain to query
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainStateQuery {
+    Connections,
+    Channels,
+    Packets,
+    LightClient,
+    RelayStatus,
+}
+
+impl ChainStateQuery {
+    /// Segment of the route serving the state, after `/chain/{id}/`
+    pub fn route(&self) -> &'static str {
+        match self {
+            Self::Connections => "connections",
+            Self::Channels => "channels",
+            Self::Packets => "packets",
+            Self::LightClient => "light_client",
+            Self::RelayStatus => "relay_status",
+        }
+    }
+}
+
+impl Display for ChainStateQuery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        write!(f, "{}", self.route())
+    }
+}
+
+/// Packets committed on a channel end and not cleared yet
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PacketCommitments {
+    pub port_id: PortId,
+    pub channel_id: ChannelId,
+    pub sequences: Vec<Sequence>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "state", rename_all = "snake_case")]
+pub enum ChainState {
+    Connections(Vec<IdentifiedConnectionEnd>),
+    Channels(Vec<IdentifiedChannelEnd>),
+    /// Commitments of the channels having any
+    Packets(Vec<PacketCommitments>),
+    LightClient(ClientCells),
+    RelayStatus(RelayStatus),
+}
+
+/// Query the state of `chain` requested by `query`
+pub fn query_chain_state<Chain: ChainHandle>(
+    chain: &Chain,
+    query: ChainStateQuery,
+) -> Result<ChainState, Error> {
+    let state = match query {
+        ChainStateQuery::Connections => {
+            ChainState::Connections(chain.query_connections(QueryConnectionsRequest {
+                pagination: Some(PageRequest::all()),
+            })?)
+        }
+        ChainStateQuery::Channels => ChainState::Channels(query_all_channels(chain)?),
+        ChainStateQuery::Packets => {
+            let mut commitments = vec![];
+            for channel in query_all_channels(chain)? {
+                let (sequences, _) =
+                    chain.query_packet_commitments(QueryPacketCommitmentsRequest {
+                        port_id: channel.port_id.clone(),
+                        channel_id: channel.channel_id.clone(),
+                        pagination: Some(PageRequest::all()),
+                    })?;
+                if !sequences.is_empty() {
+                    commitments.push(PacketCommitments {
+                        port_id: channel.port_id,
+                        channel_id: channel.channel_id,
+                        sequences,
+                    });
+                }
+            }
+            ChainState::Packets(commitments)
+        }
+        ChainStateQuery::LightClient => ChainState::LightClient(chain.query_light_client_cells()?),
+        ChainStateQuery::RelayStatus => ChainState::RelayStatus(chain.query_relay_status()?),
+    };
+    Ok(state)
+}
+
+fn query_all_channels<Chain: ChainHandle>(
+    chain: &Chain,
+) -> Result<Vec<IdentifiedChannelEnd>, Error> {
+    chain.query_channels(QueryChannelsRequest {
+        pagination: Some(PageRequest::all()),
+    })
+}
@@ -20,9 +20,30 @@ pub enum RestApiError {
     #[error("failed to parse the string {0} into a valid chain identifier: {1}")]
     InvalidChainId(String, ValidationErrorDetail),
 
+    #[error("failed to parse the string {0} into a valid port identifier: {1}")]
+    InvalidPortId(String, ValidationErrorDetail),
+
+    #[error("failed to parse the string {0} into a valid channel identifier: {1}")]
+    InvalidChannelId(String, ValidationErrorDetail),
+
     #[error("failed while parsing the request body into a chain configuration: {0}")]
     InvalidChainConfig(String),
 
+    #[error("failed to invalidate the cache of chain {0}: {1}")]
+    InvalidateCache(ChainId, String),
+
+    #[error("failed to produce a health attestation of chain {0}: {1}")]
+    HealthAttestation(ChainId, String),
+
+    #[error("failed to query the {1} of chain {0}: {2}")]
+    ChainState(ChainId, String, String),
+
+    #[error("relaying is not paused for {0}")]
+    NotPaused(String),
+
+    #[error("failed to read the event archive: {0}")]
+    Archive(String),
+
     #[error("not implemented")]
     Unimplemented,
 }
@@ -35,7 +56,14 @@ impl RestApiError {
             RestApiError::Serialization(_) => "Serialization",
             RestApiError::ChainConfigNotFound(_) => "ChainConfigNotFound",
             RestApiError::InvalidChainId(_, _) => "InvalidChainId",
+            RestApiError::InvalidPortId(_, _) => "InvalidPortId",
+            RestApiError::InvalidChannelId(_, _) => "InvalidChannelId",
             RestApiError::InvalidChainConfig(_) => "InvalidChainConfig",
+            RestApiError::InvalidateCache(_, _) => "InvalidateCache",
+            RestApiError::HealthAttestation(_, _) => "HealthAttestation",
+            RestApiError::ChainState(_, _, _) => "ChainState",
+            RestApiError::NotPaused(_) => "NotPaused",
+            RestApiError::Archive(_) => "Archive",
             RestApiError::Unimplemented => "Unimplemented",
         }
     }
@@ -1,8 +1,17 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use ibc_relayer_types::core::ics24_host::identifier::ChainId;
 
-use crate::{config::ChainConfig, rest::RestApiError, supervisor::dump_state::SupervisorState};
+use crate::{
+    attestation::SignedHealthAttestation,
+    config::ChainConfig,
+    rest::{
+        chain_state::{ChainState, ChainStateQuery},
+        RestApiError,
+    },
+    summary::StartupSummary,
+    supervisor::{dump_state::SupervisorState, pause::PauseTarget},
+};
 
 pub type ReplySender<T> = crossbeam_channel::Sender<Result<T, RestApiError>>;
 pub type ReplyReceiver<T> = crossbeam_channel::Receiver<Result<T, RestApiError>>;
@@ -11,7 +20,7 @@ pub fn reply_channel<T>() -> (ReplySender<T>, ReplyReceiver<T>) {
     crossbeam_channel::bounded(1)
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VersionInfo {
     pub name: String,
     pub version: String,
@@ -36,4 +45,38 @@ pub enum Request {
         chain_id: ChainId,
         reply_to: ReplySender<ChainConfig>,
     },
+
+    InvalidateCache {
+        chain_id: ChainId,
+        reply_to: ReplySender<()>,
+    },
+
+    HealthAttestation {
+        chain_id: ChainId,
+        reply_to: ReplySender<SignedHealthAttestation>,
+    },
+
+    ChainState {
+        chain_id: ChainId,
+        query: ChainStateQuery,
+        reply_to: ReplySender<ChainState>,
+    },
+
+    Summary {
+        reply_to: ReplySender<StartupSummary>,
+    },
+
+    Pause {
+        target: PauseTarget,
+        reply_to: ReplySender<Vec<PauseTarget>>,
+    },
+
+    Resume {
+        target: PauseTarget,
+        reply_to: ReplySender<Vec<PauseTarget>>,
+    },
+
+    Paused {
+        reply_to: ReplySender<Vec<PauseTarget>>,
+    },
 }
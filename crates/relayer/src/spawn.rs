@@ -8,7 +8,7 @@ use ibc_relayer_types::core::ics24_host::identifier::ChainId;
 use crate::{
     chain::{
         axon::AxonChain, ckb::CkbChain, ckb4ibc::Ckb4IbcChain, cosmos::CosmosSdkChain,
-        eth::EthChain, handle::ChainHandle, runtime::ChainRuntime, ChainType,
+        eth::EthChain, evm::EvmChain, handle::ChainHandle, runtime::ChainRuntime, ChainType,
     },
     config::Config,
     error::Error as RelayerError,
@@ -61,6 +61,7 @@ pub fn spawn_chain_runtime<Handle: ChainHandle>(
         ChainType::Ckb => ChainRuntime::<CkbChain>::spawn::<Handle>(chain_config, rt),
         ChainType::Axon => ChainRuntime::<AxonChain>::spawn::<Handle>(chain_config, rt),
         ChainType::Ckb4Ibc => ChainRuntime::<Ckb4IbcChain>::spawn(chain_config, rt),
+        ChainType::Evm => ChainRuntime::<EvmChain>::spawn(chain_config, rt),
     }
     .map_err(SpawnError::relayer)?;
 
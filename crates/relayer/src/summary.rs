@@ -0,0 +1,193 @@
+//! Summary of the deployment the relayer runs, logged on startup.
+//!
+//! It gathers what an operator checks once the relayer is deployed: the chains it is
+//! configured with, the contract cells resolved from their type args, the accounts of
+//! their keys, their packet filters and the features the relayer is built with. The REST
+//! server serves it as well, so that the checks can be scripted.
+
+use alloc::collections::BTreeMap;
+use core::fmt::{Display, Error as FmtError, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+use crate::chain::handle::ChainHandle;
+use crate::chain::ChainType;
+use crate::config::filter::{ChannelPolicy, PacketFilter};
+use crate::config::ChainConfig;
+use crate::registry::Registry;
+
+/// Types of the chains whose endpoint is compiled in the relayer
+pub const ENDPOINTS: [ChainType; 6] = [
+    ChainType::CosmosSdk,
+    ChainType::Eth,
+    ChainType::Axon,
+    ChainType::Ckb,
+    ChainType::Ckb4Ibc,
+    ChainType::Evm,
+];
+
+/// Optional features the relayer library is built with
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Features {
+    pub endpoints: Vec<ChainType>,
+    pub telemetry: bool,
+    pub profiling: bool,
+    /// Whether the transaction digests are signed with the pure-Rust `k256` crate rather
+    /// than libsecp256k1, which is linked in any case
+    pub k256: bool,
+}
+
+impl Features {
+    pub fn compiled() -> Self {
+        Self {
+            endpoints: ENDPOINTS.to_vec(),
+            telemetry: cfg!(feature = "telemetry"),
+            profiling: cfg!(feature = "profiling"),
+            k256: cfg!(feature = "k256"),
+        }
+    }
+}
+
+/// Summary of a configured chain
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChainSummary {
+    pub id: ChainId,
+    pub chain_type: ChainType,
+    pub key_name: String,
+    /// Address of the relayer account, which is not queried if the chain is only monitored
+    pub account: Option<String>,
+    pub submit_txs: bool,
+    /// Outpoints of the contract cells, by contract
+    pub contract_outpoints: BTreeMap<String, String>,
+    /// Packet filter of the chain, if its config has any
+    pub packet_filter: Option<PacketFilter>,
+    /// Errors met while querying the chain
+    pub errors: Vec<String>,
+}
+
+impl ChainSummary {
+    /// Summary of the chain of `config`, queried from its handle, or the reason why
+    /// there is none
+    pub fn collect<Chain: ChainHandle>(
+        config: &ChainConfig,
+        chain: Result<&Chain, String>,
+    ) -> Self {
+        let mut summary = Self {
+            id: config.id().clone(),
+            chain_type: config.r#type(),
+            key_name: config.key_name().to_owned(),
+            account: None,
+            submit_txs: config.submit_txs(),
+            contract_outpoints: BTreeMap::new(),
+            packet_filter: match config {
+                ChainConfig::Cosmos(c) => Some(c.packet_filter.clone()),
+                _ => None,
+            },
+            errors: vec![],
+        };
+
+        let chain = match chain {
+            Ok(chain) => chain,
+            Err(e) => {
+                summary.errors.push(e);
+                return summary;
+            }
+        };
+        if summary.submit_txs {
+            match chain.get_signer() {
+                Ok(signer) => summary.account = Some(signer.to_string()),
+                Err(e) => summary
+                    .errors
+                    .push(format!("failed to query the account: {e}")),
+            }
+        }
+        match chain.query_contract_outpoints() {
+            Ok(outpoints) => summary.contract_outpoints = outpoints,
+            Err(e) => summary
+                .errors
+                .push(format!("failed to query the contract outpoints: {e}")),
+        }
+        summary
+    }
+}
+
+/// Summary of the deployment of the relayer
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StartupSummary {
+    /// Version of the relayer library
+    pub version: String,
+    pub features: Features,
+    /// Whether the REST server is running
+    pub rest: bool,
+    pub chains: Vec<ChainSummary>,
+}
+
+impl StartupSummary {
+    /// Summary of the chains configured in `registry`, the ones without a running
+    /// runtime being reported as such
+    pub fn collect<Chain: ChainHandle>(registry: &Registry<Chain>, rest: bool) -> Self {
+        let chains = registry
+            .config()
+            .chains
+            .iter()
+            .map(|config| {
+                let chain = registry
+                    .chains()
+                    .find(|chain| &chain.id() == config.id())
+                    .ok_or_else(|| "chain runtime is not running".to_owned());
+                ChainSummary::collect(config, chain)
+            })
+            .collect();
+
+        Self {
+            version: crate::rest::VER.to_owned(),
+            features: Features::compiled(),
+            rest,
+            chains,
+        }
+    }
+}
+
+impl Display for StartupSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+        writeln!(
+            f,
+            "# relayer v{}, telemetry: {}, rest: {}, profiling: {}",
+            self.version,
+            on_off(self.features.telemetry),
+            on_off(self.rest),
+            on_off(self.features.profiling)
+        )?;
+        for chain in &self.chains {
+            writeln!(f, "# chain {} ({:?})", chain.id, chain.chain_type)?;
+            writeln!(
+                f,
+                "  - key: {}, account: {}, submit txs: {}",
+                chain.key_name,
+                chain.account.as_deref().unwrap_or("-"),
+                chain.submit_txs
+            )?;
+            for (contract, outpoint) in &chain.contract_outpoints {
+                writeln!(f, "  - contract {contract}: {outpoint}")?;
+            }
+            if let Some(filter) = &chain.packet_filter {
+                match &filter.channel_policy {
+                    ChannelPolicy::Allow(filters) => {
+                        writeln!(f, "  - packet filter: allow {filters}")?
+                    }
+                    ChannelPolicy::Deny(filters) => {
+                        writeln!(f, "  - packet filter: deny {filters}")?
+                    }
+                    ChannelPolicy::AllowAll => writeln!(f, "  - packet filter: allow all")?,
+                }
+            }
+            for error in &chain.errors {
+                writeln!(f, "  - error: {error}")?;
+            }
+        }
+        Ok(())
+    }
+}
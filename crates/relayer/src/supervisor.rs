@@ -1,9 +1,11 @@
 use alloc::collections::btree_map::BTreeMap as HashMap;
+use alloc::collections::BTreeSet;
 use alloc::sync::Arc;
 use core::convert::Infallible;
 use core::ops::Deref;
 use core::time::Duration;
 use std::sync::RwLock;
+use std::time::Instant;
 
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use itertools::Itertools;
@@ -16,19 +18,29 @@ use ibc_relayer_types::{
 };
 
 use crate::{
-    chain::{endpoint::HealthCheck, handle::ChainHandle, tracking::TrackingId},
-    config::Config,
+    archive,
+    attestation::attest_health,
+    chain::{
+        ckb::header_updater::spawn_header_updater, endpoint::HealthCheck, handle::ChainHandle,
+        tracking::TrackingId,
+    },
+    config::{
+        diff::{self, ChainConfigUpdate},
+        ChainConfig, Config,
+    },
     event::{
         monitor::{self, Error as EventError, ErrorDetail as EventErrorDetail, EventBatch},
         IbcEventWithHeight,
     },
     object::Object,
     registry::{Registry, SharedRegistry},
-    rest,
+    rest::{self, chain_state::query_chain_state},
+    summary::StartupSummary,
     supervisor::scan::ScanMode,
     telemetry,
     util::{
         lock::LockExt,
+        panic::chain_panics,
         task::{spawn_background_task, Next, TaskError, TaskHandle},
     },
     worker::WorkerMap,
@@ -51,11 +63,27 @@ use cmd::SupervisorCmd;
 
 pub mod forcerelay;
 
+pub mod pause;
+use pause::PausedRelaying;
+
+pub mod restart;
+use restart::ChainRestarts;
+
 use self::{scan::ChainScanner, spawn::SpawnContext};
 
 type ArcBatch = Arc<monitor::Result<EventBatch>>;
 type Subscription = Receiver<ArcBatch>;
 
+/// The tasks of the supervisor which are bound to a chain, and which are respawned
+/// when the configuration of the chain is reloaded.
+#[derive(Default)]
+struct ChainTasks {
+    /// Event batch workers, by chain
+    batch_workers: HashMap<ChainId, TaskHandle>,
+    /// Header updaters, by CKB chain
+    header_updaters: HashMap<ChainId, TaskHandle>,
+}
+
 /**
     A wrapper around the SupervisorCmd sender so that we can
     send stop signal to the supervisor before stopping the
@@ -133,6 +161,14 @@ impl SupervisorHandle {
 
         Ok(state)
     }
+
+    /// Ask the supervisor to apply the chain configurations of a reloaded
+    /// configuration file
+    pub fn update_config(&self, config: Config) -> Result<(), Error> {
+        self.sender
+            .send(SupervisorCmd::UpdateConfig(Box::new(config)))
+            .map_err(|_| Error::handle_send())
+    }
 }
 
 pub fn spawn_supervisor_tasks<Chain: ChainHandle>(
@@ -158,6 +194,15 @@ pub fn spawn_supervisor_tasks<Chain: ChainHandle>(
 
     let workers = Arc::new(RwLock::new(WorkerMap::new()));
     let client_state_filter = Arc::new(RwLock::new(FilterPolicy::default()));
+    let monitor_only = config
+        .chains
+        .iter()
+        .filter(|chain_config| !chain_config.submit_txs())
+        .map(|chain_config| chain_config.id().clone());
+    let paused = Arc::new(RwLock::new(
+        PausedRelaying::load(config.global.pause_file.clone()).with_monitor_only(monitor_only),
+    ));
+    archive::init(config.global.archive_file.as_deref());
 
     let scan = chain_scanner(
         &config,
@@ -176,82 +221,193 @@ pub fn spawn_supervisor_tasks<Chain: ChainHandle>(
 
     spawn_context(&config, &mut registry.write(), &mut workers.acquire_write()).spawn_workers(scan);
 
+    for path in &config.paths {
+        spawn_context(&config, &mut registry.write(), &mut workers.acquire_write())
+            .spawn_workers_for_path(path)?;
+    }
+
+    for target in paused.acquire_read().targets() {
+        info!("relaying is paused for {}", target);
+    }
+    for chain_id in paused.acquire_read().monitor_only() {
+        info!(
+            "chain {} is only monitored, no transaction is submitted to it",
+            chain_id
+        );
+    }
+    shutdown_paused_workers(&mut workers.acquire_write(), &paused.acquire_read());
+
     let subscriptions = init_subscriptions(&config, &mut registry.write())?;
 
-    let batch_tasks = spawn_batch_workers(
-        &config,
+    let mut chain_tasks = ChainTasks::default();
+    spawn_header_updaters(&config, &registry, &mut chain_tasks.header_updaters);
+
+    let summary = StartupSummary::collect(&registry.read(), rest_rx.is_some());
+    info!("startup summary:");
+    info!("{}", summary);
+
+    let restarts = Arc::new(RwLock::new(ChainRestarts::new(
+        config.global.chain_restart.clone(),
+    )));
+    let config = Arc::new(RwLock::new(config));
+
+    for (chain, subscription) in subscriptions {
+        let handle = spawn_batch_worker(
+            config.clone(),
+            registry.clone(),
+            client_state_filter.clone(),
+            workers.clone(),
+            paused.clone(),
+            chain.clone(),
+            subscription,
+        );
+        chain_tasks.batch_workers.insert(chain.id(), handle);
+    }
+
+    let cmd_task = spawn_cmd_worker(
+        config.clone(),
         registry.clone(),
         client_state_filter,
         workers.clone(),
-        subscriptions,
+        paused.clone(),
+        restarts.clone(),
+        chain_tasks,
+        cmd_rx,
     );
 
-    let cmd_task = spawn_cmd_worker(registry.clone(), workers.clone(), cmd_rx);
-
     let mut tasks = vec![cmd_task];
-    tasks.extend(batch_tasks);
 
     if let Some(rest_rx) = rest_rx {
-        let rest_task = spawn_rest_worker(config, registry, workers, rest_rx);
+        let rest_task = spawn_rest_worker(config, registry, workers, paused, restarts, rest_rx);
         tasks.push(rest_task);
     }
 
     Ok(tasks)
 }
 
-fn spawn_batch_workers<Chain: ChainHandle>(
+/// Spawn a header updater for each CKB chain which has one configured, unless it is
+/// already running.
+fn spawn_header_updaters<Chain: ChainHandle>(
     config: &Config,
+    registry: &SharedRegistry<Chain>,
+    header_updaters: &mut HashMap<ChainId, TaskHandle>,
+) {
+    for chain_config in &config.chains {
+        let ChainConfig::Ckb(ckb_config) = chain_config else {
+            continue;
+        };
+        let Some(updater_config) = ckb_config.header_updater.clone() else {
+            continue;
+        };
+        if header_updaters.contains_key(&ckb_config.id) {
+            continue;
+        }
+
+        let chains = registry.get_or_spawn(&ckb_config.id).and_then(|ckb| {
+            let eth = registry.get_or_spawn(&updater_config.eth_chain_id)?;
+            Ok((eth, ckb))
+        });
+        match chains {
+            Ok((eth, ckb)) => {
+                let handle = spawn_header_updater(eth, ckb, updater_config);
+                header_updaters.insert(ckb_config.id.clone(), handle);
+            }
+            Err(e) => error!(
+                "failed to spawn the header updater of chain {}: {e}",
+                ckb_config.id
+            ),
+        }
+    }
+}
+
+fn spawn_batch_worker<Chain: ChainHandle>(
+    config: Arc<RwLock<Config>>,
     registry: SharedRegistry<Chain>,
     client_state_filter: Arc<RwLock<FilterPolicy>>,
     workers: Arc<RwLock<WorkerMap>>,
-    subscriptions: Vec<(Chain, Subscription)>,
-) -> Vec<TaskHandle> {
-    let mut handles = Vec::with_capacity(subscriptions.len());
-
-    for (chain, subscription) in subscriptions {
-        let config = config.clone();
-        let registry = registry.clone();
-        let client_state_filter = client_state_filter.clone();
-        let workers = workers.clone();
-
-        let handle = spawn_background_task(
-            error_span!("worker.batch", chain = %chain.id()),
-            Some(Duration::from_millis(5)),
-            move || -> Result<Next, TaskError<Infallible>> {
-                if let Ok(batch) = subscription.try_recv() {
-                    handle_batch(
-                        &config,
-                        &mut registry.write(),
-                        &mut client_state_filter.acquire_write(),
-                        &mut workers.acquire_write(),
-                        chain.clone(),
-                        batch,
-                    );
-                }
-
-                Ok(Next::Continue)
-            },
-        );
-
-        handles.push(handle);
-    }
+    paused: Arc<RwLock<PausedRelaying>>,
+    chain: Chain,
+    subscription: Subscription,
+) -> TaskHandle {
+    spawn_background_task(
+        error_span!("worker.batch", chain = %chain.id()),
+        Some(Duration::from_millis(5)),
+        move || -> Result<Next, TaskError<Infallible>> {
+            if let Ok(batch) = subscription.try_recv() {
+                handle_batch(
+                    &config.acquire_read(),
+                    &mut registry.write(),
+                    &mut client_state_filter.acquire_write(),
+                    &mut workers.acquire_write(),
+                    &paused.acquire_read(),
+                    chain.clone(),
+                    batch,
+                );
+            }
 
-    handles
+            Ok(Next::Continue)
+        },
+    )
 }
 
-pub fn spawn_cmd_worker<Chain: ChainHandle>(
+fn spawn_cmd_worker<Chain: ChainHandle>(
+    config: Arc<RwLock<Config>>,
     registry: SharedRegistry<Chain>,
+    client_state_filter: Arc<RwLock<FilterPolicy>>,
     workers: Arc<RwLock<WorkerMap>>,
+    paused: Arc<RwLock<PausedRelaying>>,
+    restarts: Arc<RwLock<ChainRestarts>>,
+    mut chain_tasks: ChainTasks,
     cmd_rx: Receiver<SupervisorCmd>,
 ) -> TaskHandle {
+    let panics = chain_panics();
     spawn_background_task(
         error_span!("worker.cmd"),
         Some(Duration::from_millis(500)),
         move || -> Result<Next, TaskError<Infallible>> {
+            let now = Instant::now();
+            let mut panicked = panicked_chains(&chain_tasks, &mut workers.acquire_write());
+            panicked.extend(panics.try_iter().map(|chain_panic| chain_panic.chain_id));
+            let due = {
+                let mut restarts = restarts.acquire_write();
+                for chain_id in &panicked {
+                    restarts.record_panic(chain_id, now);
+                }
+                restarts.due(now)
+            };
+            for chain_id in due {
+                restart_chain(
+                    &config,
+                    &registry,
+                    &client_state_filter,
+                    &workers,
+                    &paused,
+                    &mut chain_tasks,
+                    &chain_id,
+                );
+            }
+
             if let Ok(cmd) = cmd_rx.try_recv() {
                 match cmd {
                     SupervisorCmd::DumpState(reply_to) => {
-                        dump_state(&registry.read(), &workers.acquire_read(), reply_to);
+                        dump_state(
+                            &registry.read(),
+                            &workers.acquire_read(),
+                            &restarts.acquire_read(),
+                            reply_to,
+                        );
+                    }
+                    SupervisorCmd::UpdateConfig(new_config) => {
+                        reload_config(
+                            &config,
+                            &registry,
+                            &client_state_filter,
+                            &workers,
+                            &paused,
+                            &restarts,
+                            &mut chain_tasks,
+                            *new_config,
+                        );
                     }
                 }
             }
@@ -261,17 +417,246 @@ pub fn spawn_cmd_worker<Chain: ChainHandle>(
     )
 }
 
+/// The chains of which a background task was aborted after panicking, the panicked
+/// workers being shut down so that they are spawned again when their source chain is
+/// restarted.
+fn panicked_chains(chain_tasks: &ChainTasks, workers: &mut WorkerMap) -> BTreeSet<ChainId> {
+    let chain_tasks = chain_tasks
+        .batch_workers
+        .iter()
+        .chain(&chain_tasks.header_updaters)
+        .filter(|(_, handle)| handle.has_panicked())
+        .map(|(chain_id, _)| chain_id.clone());
+    let workers = workers
+        .shutdown_panicked()
+        .into_iter()
+        .map(|object| object.src_chain_id().clone());
+    chain_tasks.chain(workers).collect()
+}
+
+/// Apply the chain configurations of a reloaded configuration file.
+///
+/// The runtimes of the added, removed and updated chains are shut down along with
+/// their event subscriptions and the workers relaying from or to them, then spawned
+/// again from the new configuration. The workers relaying between unchanged chains
+/// keep running. The restarts of the reloaded chains are forgotten, so that a chain
+/// marked failed is given another chance.
+#[instrument(name = "supervisor.reload_config", level = "error", skip_all)]
+fn reload_config<Chain: ChainHandle>(
+    config: &Arc<RwLock<Config>>,
+    registry: &SharedRegistry<Chain>,
+    client_state_filter: &Arc<RwLock<FilterPolicy>>,
+    workers: &Arc<RwLock<WorkerMap>>,
+    paused: &Arc<RwLock<PausedRelaying>>,
+    restarts: &Arc<RwLock<ChainRestarts>>,
+    chain_tasks: &mut ChainTasks,
+    new_config: Config,
+) {
+    let old_config = config.acquire_read().clone();
+    let changes = diff::diff(&old_config, &new_config);
+
+    if changes.is_empty() {
+        info!("configuration is unchanged");
+        return;
+    }
+    for section in &changes.unsupported {
+        warn!("ignoring the changes to the `{section}` section, which require a restart");
+    }
+
+    let mut updated_config = old_config.clone();
+    updated_config.chains = new_config.chains;
+
+    {
+        let mut restarts = restarts.acquire_write();
+        for update in &changes.chains {
+            restarts.forget(update.chain_id());
+        }
+    }
+
+    respawn_chains(
+        config,
+        registry,
+        client_state_filter,
+        workers,
+        paused,
+        chain_tasks,
+        &old_config,
+        updated_config,
+        changes.chains,
+    );
+}
+
+/// Restart a chain after the panic of one of its threads, the same way as if its
+/// configuration was reloaded, so that it doesn't stay silently dead. The restarts
+/// are scheduled by [`ChainRestarts`], with a backoff up to a maximum of attempts.
+#[instrument(name = "supervisor.restart_chain", level = "error", skip_all, fields(chain = %chain_id))]
+fn restart_chain<Chain: ChainHandle>(
+    config: &Arc<RwLock<Config>>,
+    registry: &SharedRegistry<Chain>,
+    client_state_filter: &Arc<RwLock<FilterPolicy>>,
+    workers: &Arc<RwLock<WorkerMap>>,
+    paused: &Arc<RwLock<PausedRelaying>>,
+    chain_tasks: &mut ChainTasks,
+    chain_id: &ChainId,
+) {
+    let current_config = config.acquire_read().clone();
+    let Some(chain_config) = current_config.find_chain(chain_id).cloned() else {
+        return;
+    };
+    warn!("restarting the chain after the panic of one of its threads");
+
+    respawn_chains(
+        config,
+        registry,
+        client_state_filter,
+        workers,
+        paused,
+        chain_tasks,
+        &current_config,
+        current_config.clone(),
+        vec![ChainConfigUpdate::Updated(chain_config)],
+    );
+}
+
+/// Shut down the chains of `updates` along with their event subscriptions and the
+/// workers relaying from or to them, then spawn the added and updated chains again
+/// from `updated_config`, which replaces `old_config` as the current configuration.
+fn respawn_chains<Chain: ChainHandle>(
+    config: &Arc<RwLock<Config>>,
+    registry: &SharedRegistry<Chain>,
+    client_state_filter: &Arc<RwLock<FilterPolicy>>,
+    workers: &Arc<RwLock<WorkerMap>>,
+    paused: &Arc<RwLock<PausedRelaying>>,
+    chain_tasks: &mut ChainTasks,
+    old_config: &Config,
+    updated_config: Config,
+    updates: Vec<ChainConfigUpdate>,
+) {
+    let affected = updates
+        .iter()
+        .map(|update| update.chain_id().clone())
+        .collect::<BTreeSet<_>>();
+
+    // Dropping the handles stops the tasks
+    for update in &updates {
+        match update {
+            ChainConfigUpdate::Added(_) => info!("adding chain {}", update.chain_id()),
+            ChainConfigUpdate::Removed(_) => info!("removing chain {}", update.chain_id()),
+            ChainConfigUpdate::Updated(_) => info!("reloading chain {}", update.chain_id()),
+        }
+        chain_tasks.batch_workers.remove(update.chain_id());
+    }
+    chain_tasks.header_updaters.retain(|ckb_chain_id, _| {
+        let eth_chain_id = match old_config.find_chain(ckb_chain_id) {
+            Some(ChainConfig::Ckb(ckb_config)) => ckb_config
+                .header_updater
+                .as_ref()
+                .map(|updater| &updater.eth_chain_id),
+            _ => None,
+        };
+        !affected.contains(ckb_chain_id)
+            && !eth_chain_id.map_or(false, |eth_chain_id| affected.contains(eth_chain_id))
+    });
+
+    {
+        let mut workers = workers.acquire_write();
+        for chain_id in &affected {
+            for object in workers.objects_for_chain(chain_id) {
+                workers.shutdown_worker(&object);
+            }
+        }
+    }
+
+    {
+        let mut registry = registry.write();
+        registry.update_config(updated_config.clone());
+        for chain_id in &affected {
+            registry.shutdown(chain_id);
+        }
+    }
+
+    paused.acquire_write().set_monitor_only(
+        updated_config
+            .chains
+            .iter()
+            .filter(|chain_config| !chain_config.submit_txs())
+            .map(|chain_config| chain_config.id().clone()),
+    );
+    *config.acquire_write() = updated_config.clone();
+
+    for update in updates {
+        let (ChainConfigUpdate::Added(chain_config) | ChainConfigUpdate::Updated(chain_config)) =
+            update
+        else {
+            continue;
+        };
+        let chain_id = chain_config.id();
+
+        let scan = chain_scanner(
+            &updated_config,
+            &mut registry.write(),
+            &mut client_state_filter.acquire_write(),
+            ScanMode::Auto,
+        )
+        .scan_chain(&chain_config);
+        match scan {
+            Ok(scan) => spawn_context(
+                &updated_config,
+                &mut registry.write(),
+                &mut workers.acquire_write(),
+            )
+            .spawn_workers_for_chain(scan),
+            Err(e) => error!("failed to scan chain {}: {}", chain_id, e),
+        }
+
+        let chain = match registry.get_or_spawn(chain_id) {
+            Ok(chain) => chain,
+            Err(e) => {
+                error!("failed to spawn chain runtime for {}: {}", chain_id, e);
+                continue;
+            }
+        };
+        match chain.subscribe() {
+            Ok(subscription) => {
+                let handle = spawn_batch_worker(
+                    config.clone(),
+                    registry.clone(),
+                    client_state_filter.clone(),
+                    workers.clone(),
+                    paused.clone(),
+                    chain,
+                    subscription,
+                );
+                chain_tasks.batch_workers.insert(chain_id.clone(), handle);
+            }
+            Err(e) => error!("failed to subscribe to events of {}: {}", chain_id, e),
+        }
+    }
+
+    shutdown_paused_workers(&mut workers.acquire_write(), &paused.acquire_read());
+    spawn_header_updaters(&updated_config, registry, &mut chain_tasks.header_updaters);
+}
+
 pub fn spawn_rest_worker<Chain: ChainHandle>(
-    config: Config,
+    config: Arc<RwLock<Config>>,
     registry: SharedRegistry<Chain>,
     workers: Arc<RwLock<WorkerMap>>,
+    paused: Arc<RwLock<PausedRelaying>>,
+    restarts: Arc<RwLock<ChainRestarts>>,
     rest_rx: rest::Receiver,
 ) -> TaskHandle {
     spawn_background_task(
         error_span!("rest"),
         Some(Duration::from_millis(500)),
         move || -> Result<Next, TaskError<Infallible>> {
-            handle_rest_requests(&config, &registry.read(), &workers.acquire_read(), &rest_rx);
+            handle_rest_requests(
+                &config.acquire_read(),
+                &registry.read(),
+                &mut workers.acquire_write(),
+                &mut paused.acquire_write(),
+                &restarts.acquire_read(),
+                &rest_rx,
+            );
 
             Ok(Next::Continue)
         },
@@ -655,43 +1040,142 @@ fn init_subscriptions<Chain: ChainHandle>(
 fn dump_state<Chain: ChainHandle>(
     registry: &Registry<Chain>,
     workers: &WorkerMap,
+    restarts: &ChainRestarts,
     reply_to: Sender<SupervisorState>,
 ) {
-    let state = state(registry, workers);
+    let state = state(registry, workers, restarts);
     let _ = reply_to.try_send(state);
 }
 
 /// Returns a representation of the supervisor's internal state
 /// as a [`SupervisorState`].
-fn state<Chain: ChainHandle>(registry: &Registry<Chain>, workers: &WorkerMap) -> SupervisorState {
+fn state<Chain: ChainHandle>(
+    registry: &Registry<Chain>,
+    workers: &WorkerMap,
+    restarts: &ChainRestarts,
+) -> SupervisorState {
     let chains = registry.chains().map(|c| c.id()).collect_vec();
-    SupervisorState::new(chains, workers.handles())
+    SupervisorState::new(chains, workers.handles()).with_failed_chains(restarts.failed())
 }
 
 fn handle_rest_requests<Chain: ChainHandle>(
     config: &Config,
     registry: &Registry<Chain>,
-    workers: &WorkerMap,
+    workers: &mut WorkerMap,
+    paused: &mut PausedRelaying,
+    restarts: &ChainRestarts,
     rest_rx: &rest::Receiver,
 ) {
     if let Some(cmd) = rest::process_incoming_requests(config, rest_rx) {
-        handle_rest_cmd(registry, workers, cmd);
+        handle_rest_cmd(registry, workers, paused, restarts, cmd);
     }
 }
 
 #[instrument(name = "supervisor.handle_rest_cmd", level = "error", skip_all)]
 fn handle_rest_cmd<Chain: ChainHandle>(
     registry: &Registry<Chain>,
-    workers: &WorkerMap,
+    workers: &mut WorkerMap,
+    paused: &mut PausedRelaying,
+    restarts: &ChainRestarts,
     m: rest::Command,
 ) {
     match m {
         rest::Command::DumpState(reply) => {
-            let state = state(registry, workers);
+            let state = state(registry, workers, restarts);
             reply
                 .send(Ok(state))
                 .unwrap_or_else(|e| error!("error replying to a REST request {}", e));
         }
+        rest::Command::InvalidateCache(chain_id, reply) => {
+            let result = match registry.chains().find(|chain| chain.id() == chain_id) {
+                Some(chain) => chain
+                    .invalidate_cache()
+                    .map_err(|e| rest::RestApiError::InvalidateCache(chain_id, e.to_string())),
+                None => Err(rest::RestApiError::ChainConfigNotFound(chain_id)),
+            };
+            reply
+                .send(result)
+                .unwrap_or_else(|e| error!("error replying to a REST request {}", e));
+        }
+        rest::Command::HealthAttestation(chain_id, reply) => {
+            let result = match registry.chains().find(|chain| chain.id() == chain_id) {
+                Some(chain) => attest_health(chain)
+                    .map_err(|e| rest::RestApiError::HealthAttestation(chain_id, e.to_string())),
+                None => Err(rest::RestApiError::ChainConfigNotFound(chain_id)),
+            };
+            reply
+                .send(result)
+                .unwrap_or_else(|e| error!("error replying to a REST request {}", e));
+        }
+        rest::Command::ChainState(chain_id, query, reply) => {
+            let result = match registry.chains().find(|chain| chain.id() == chain_id) {
+                Some(chain) => query_chain_state(chain, query).map_err(|e| {
+                    rest::RestApiError::ChainState(chain_id, query.to_string(), e.to_string())
+                }),
+                None => Err(rest::RestApiError::ChainConfigNotFound(chain_id)),
+            };
+            reply
+                .send(result)
+                .unwrap_or_else(|e| error!("error replying to a REST request {}", e));
+        }
+        rest::Command::Summary(reply) => {
+            let summary = StartupSummary::collect(registry, true);
+            reply
+                .send(Ok(summary))
+                .unwrap_or_else(|e| error!("error replying to a REST request {}", e));
+        }
+        rest::Command::Pause(target, reply) => {
+            let result = if registry
+                .chains()
+                .any(|chain| &chain.id() == target.chain_id())
+            {
+                if paused.pause(target.clone()) {
+                    warn!("pausing relaying for {}", target);
+                    shutdown_paused_workers(workers, paused);
+                }
+                Ok(paused.targets())
+            } else {
+                Err(rest::RestApiError::ChainConfigNotFound(
+                    target.chain_id().clone(),
+                ))
+            };
+            reply
+                .send(result)
+                .unwrap_or_else(|e| error!("error replying to a REST request {}", e));
+        }
+        rest::Command::Resume(target, reply) => {
+            let result = if paused.resume(&target) {
+                info!("resuming relaying for {}", target);
+                Ok(paused.targets())
+            } else {
+                Err(rest::RestApiError::NotPaused(target.to_string()))
+            };
+            reply
+                .send(result)
+                .unwrap_or_else(|e| error!("error replying to a REST request {}", e));
+        }
+        rest::Command::Paused(reply) => {
+            reply
+                .send(Ok(paused.targets()))
+                .unwrap_or_else(|e| error!("error replying to a REST request {}", e));
+        }
+    }
+}
+
+/// Stop the workers of the paused chains and channels, so that they no longer submit
+/// any transaction. The workers are spawned again by the next events for their
+/// objects once relaying is resumed.
+fn shutdown_paused_workers(workers: &mut WorkerMap, paused: &PausedRelaying) {
+    let objects = workers
+        .handles()
+        .map(|handle| handle.object())
+        .filter(|object| paused.is_paused(object))
+        .cloned()
+        .collect_vec();
+
+    for object in objects {
+        debug!("stopping worker for paused object {}", object.short_name());
+        workers.shutdown_worker(&object);
     }
 }
 
@@ -721,6 +1205,7 @@ fn process_batch<Chain: ChainHandle>(
     registry: &mut Registry<Chain>,
     client_state_filter: &mut FilterPolicy,
     workers: &mut WorkerMap,
+    paused: &PausedRelaying,
     src_chain: Chain,
     batch: &EventBatch,
 ) -> Result<(), Error> {
@@ -728,6 +1213,8 @@ fn process_batch<Chain: ChainHandle>(
 
     telemetry!(received_event_batch, batch.tracking_id);
 
+    archive::archive_batch(batch);
+
     let collected = collect_events(config, workers, &src_chain, batch);
 
     // If there is a NewBlock event, forward this event first to any workers affected by it.
@@ -754,6 +1241,15 @@ fn process_batch<Chain: ChainHandle>(
             continue;
         }
 
+        if paused.is_paused(&object) {
+            trace!(
+                "skipping events for '{}'. reason: relaying is paused",
+                object.short_name()
+            );
+
+            continue;
+        }
+
         if events_with_heights.is_empty() {
             continue;
         }
@@ -855,6 +1351,7 @@ fn handle_batch<Chain: ChainHandle>(
     registry: &mut Registry<Chain>,
     client_state_filter: &mut FilterPolicy,
     workers: &mut WorkerMap,
+    paused: &PausedRelaying,
     chain: Chain,
     batch: ArcBatch,
 ) {
@@ -862,9 +1359,15 @@ fn handle_batch<Chain: ChainHandle>(
 
     match batch.deref() {
         Ok(batch) => {
-            if let Err(e) =
-                process_batch(config, registry, client_state_filter, workers, chain, batch)
-            {
+            if let Err(e) = process_batch(
+                config,
+                registry,
+                client_state_filter,
+                workers,
+                paused,
+                chain,
+                batch,
+            ) {
                 error!("error during batch processing: {}", e);
             }
         }
@@ -874,6 +1377,33 @@ fn handle_batch<Chain: ChainHandle>(
             let _ = clear_pending_packets(workers, &chain_id)
                 .map_err(|e| error!("error during clearing pending packets: {}", e));
         }
+        Err(EventError(EventErrorDetail::EventsRetracted(e), _)) => {
+            warn!(
+                "events of {} transactions were retracted by a reorg, clearing pending packets",
+                e.tx_hashes.len()
+            );
+
+            let _ = clear_pending_packets(workers, &chain_id)
+                .map_err(|e| error!("error during clearing pending packets: {}", e));
+        }
+        Err(EventError(EventErrorDetail::ChainReorganized(e), _)) => {
+            warn!(
+                "chain reorganized after height {}, clearing pending packets",
+                e.height
+            );
+
+            let _ = clear_pending_packets(workers, &chain_id)
+                .map_err(|e| error!("error during clearing pending packets: {}", e));
+        }
+        Err(EventError(EventErrorDetail::Restarted(e), _)) => {
+            warn!(
+                "event monitor restarted from height {}, clearing pending packets",
+                e.height
+            );
+
+            let _ = clear_pending_packets(workers, &chain_id)
+                .map_err(|e| error!("error during clearing pending packets: {}", e));
+        }
         Err(e) => {
             error!("error when receiving event batch: {}", e)
         }
@@ -1,8 +1,12 @@
 use crossbeam_channel::Sender;
 
+use crate::config::Config;
+
 use super::dump_state::SupervisorState;
 
 #[derive(Clone, Debug)]
 pub enum SupervisorCmd {
     DumpState(Sender<SupervisorState>),
+    /// Apply the chain configurations of a reloaded configuration file
+    UpdateConfig(Box<Config>),
 }
@@ -28,6 +28,9 @@ impl WorkerDesc {
 pub struct SupervisorState {
     pub chains: Vec<ChainId>,
     pub workers: BTreeMap<ObjectType, Vec<WorkerDesc>>,
+    /// Chains no longer restarted after panicking too many times in a row
+    #[serde(default)]
+    pub failed_chains: Vec<ChainId>,
 }
 
 impl SupervisorState {
@@ -44,7 +47,17 @@ impl SupervisorState {
             .update(|(_, os)| os.sort_by_key(|desc| desc.object.short_name()))
             .collect::<BTreeMap<_, _>>();
 
-        Self { chains, workers }
+        Self {
+            chains,
+            workers,
+            failed_chains: vec![],
+        }
+    }
+
+    pub fn with_failed_chains(mut self, mut failed_chains: Vec<ChainId>) -> Self {
+        failed_chains.sort();
+        self.failed_chains = failed_chains;
+        self
     }
 
     pub fn print_info(&self) {
@@ -58,6 +71,13 @@ impl Display for SupervisorState {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
         writeln!(f)?;
         writeln!(f, "* Chains: {}", self.chains.iter().join(", "))?;
+        if !self.failed_chains.is_empty() {
+            writeln!(
+                f,
+                "* Failed chains: {}",
+                self.failed_chains.iter().join(", ")
+            )?;
+        }
         for (tpe, objects) in &self.workers {
             writeln!(f, "* {tpe:?} workers:")?;
             for desc in objects {
@@ -3,6 +3,7 @@ use flex_error::define_error;
 use ibc_relayer_types::core::ics03_connection::connection::Counterparty;
 use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, ConnectionId, PortId};
 
+use crate::config::path::PathConfig;
 use crate::error::Error as RelayerError;
 use crate::spawn::SpawnError;
 use crate::supervisor::scan::Error as ScanError;
@@ -70,6 +71,11 @@ define_error! {
             [ ScanError ]
             |_| { "supervisor encountered an error when scanning chains" },
 
+        InvalidPath
+            { path: PathConfig }
+            [ ScanError ]
+            |e| { format_args!("path {} declared in the configuration does not exist", e.path) },
+
         HandleSend
             |_| { "failed to send a command to the supervisor through a channel" },
 
@@ -1,4 +1,10 @@
+use ibc_relayer_types::clients::ics07_eth::types::H256 as EthH256;
+use ibc_relayer_types::core::ics02_client::client_type::ClientType;
+use ibc_relayer_types::core::ics02_client::msgs::misbehaviour::MsgSubmitMisbehaviour;
+use ibc_relayer_types::core::ics24_host::identifier::ClientId;
 use ibc_relayer_types::events::IbcEvent;
+use ibc_relayer_types::tx_msg::Msg;
+use ibc_relayer_types::Height;
 use std::sync::Arc;
 use std::time::Duration;
 use tracing::{debug, error, info, warn};
@@ -7,13 +13,15 @@ use crate::chain::client::ClientSettings;
 use crate::chain::handle::ChainHandle;
 use crate::chain::requests::{PageRequest, QueryClientStatesRequest};
 use crate::chain::tracking::{NonCosmosTrackingId, TrackedMsgs, TrackingId};
-use crate::client_state::IdentifiedAnyClientState;
+use crate::client_state::{AnyClientState, IdentifiedAnyClientState};
 use crate::config::ChainConfig;
 use crate::error::{Error, ErrorDetail::LightClientVerification};
 use crate::event::monitor::EventBatch;
+use crate::light_client::eth::misbehaviour::detect_double_finality;
+use crate::telemetry;
 use tendermint_light_client::errors::ErrorDetail;
 
-const MAX_HEADERS_IN_BATCH: u64 = 256;
+pub(crate) const MAX_HEADERS_IN_BATCH: u64 = 256;
 const MAX_SLEEP_SECONDS: u64 = 5;
 const MAX_RETRY_NUMBER: u8 = 5;
 
@@ -47,7 +55,11 @@ pub fn handle_eth_ckb_event_batch<ChainA: ChainHandle, ChainB: ChainHandle>(
                 create_ethereum_light_client(src_chain, dst_chain, event_batch);
             }
         }
-        IbcEvent::NewBlock(_) => update_ethereum_headers(src_chain, dst_chain, event_batch),
+        IbcEvent::NewBlock(_) => {
+            if !check_ethereum_misbehaviour(src_chain, dst_chain) {
+                update_ethereum_headers(src_chain, dst_chain, event_batch)
+            }
+        }
         _ => warn!("receiving unrecognized event"),
     }
 }
@@ -87,6 +99,80 @@ fn create_ethereum_light_client<ChainA: ChainHandle, ChainB: ChainHandle>(
     }
 }
 
+/// Check the tip of the multi-client cells on CKB against the header finalized at its
+/// slot on Ethereum, and submit the evidence of their conflict to freeze the cells.
+///
+/// Returns whether the cells are frozen, so that no more headers are relayed to them.
+fn check_ethereum_misbehaviour<ChainA: ChainHandle, ChainB: ChainHandle>(
+    src_chain: &Arc<ChainA>,
+    dst_chain: &Arc<ChainB>,
+) -> bool {
+    let client_cells = match dst_chain.query_light_client_cells() {
+        Ok(value) => value,
+        Err(err) => {
+            debug!("dst_chain.query_light_client_cells: {err}, skip misbehaviour check");
+            return false;
+        }
+    };
+    if client_cells.frozen {
+        warn!(
+            "multi-client cells of type id {:#x} are frozen, stop relaying headers",
+            client_cells.type_id
+        );
+        return true;
+    }
+
+    let tip = &client_cells.latest;
+    let canonical = Height::new(0, tip.maximal_slot)
+        .map_err(|e| e.to_string())
+        .and_then(|slot| {
+            src_chain
+                .build_client_state(slot, ClientSettings::Other)
+                .map_err(|e| e.to_string())
+        });
+    let canonical = match canonical {
+        Ok(AnyClientState::Eth(client_state)) => client_state.lightclient_update.finalized_header,
+        Ok(_) => return false,
+        Err(err) => {
+            debug!("src_chain.build_client_state: {err}, skip misbehaviour check");
+            return false;
+        }
+    };
+    let client_id = ClientId::new(ClientType::Eth, 0).expect("eth client id");
+    let root = EthH256::from_slice(tip.tip_valid_header_root.as_bytes());
+    let Some(misbehaviour) = detect_double_finality(&client_id, tip.maximal_slot, root, &canonical)
+    else {
+        return false;
+    };
+
+    error!(%misbehaviour, "misbehaviour detected, sending evidence");
+    telemetry!(
+        client_misbehaviours_submitted,
+        &src_chain.id(),
+        &dst_chain.id(),
+        &client_id,
+        1
+    );
+    let msg = dst_chain.get_signer().map(|signer| {
+        MsgSubmitMisbehaviour {
+            client_id,
+            misbehaviour: misbehaviour.into(),
+            signer,
+        }
+        .to_any()
+    });
+    let result = msg.and_then(|msg| {
+        dst_chain.send_messages_and_wait_commit(TrackedMsgs::new_static(vec![msg], "evidence"))
+    });
+    match result {
+        Ok(_) => true,
+        Err(err) => {
+            error!("failed to submit the misbehaviour evidence: {err}");
+            false
+        }
+    }
+}
+
 fn update_ethereum_headers<ChainA: ChainHandle, ChainB: ChainHandle>(
     src_chain: &Arc<ChainA>,
     dst_chain: &Arc<ChainB>,
@@ -198,7 +284,7 @@ fn update_ethereum_headers<ChainA: ChainHandle, ChainB: ChainHandle>(
     }
 }
 
-fn send_messages<Chain: ChainHandle>(
+pub(crate) fn send_messages<Chain: ChainHandle>(
     chain: &Arc<Chain>,
     client_states: Vec<IdentifiedAnyClientState>,
 ) -> Result<Vec<crate::event::IbcEventWithHeight>, Error> {
@@ -212,7 +298,7 @@ fn send_messages<Chain: ChainHandle>(
     chain.send_messages_and_wait_commit(tracked_msgs)
 }
 
-fn extract_missing_slot_from_error(error: &Error) -> Option<u64> {
+pub(crate) fn extract_missing_slot_from_error(error: &Error) -> Option<u64> {
     if let LightClientVerification(verify_error) = error.detail() {
         match &verify_error.source {
             ErrorDetail::MissingLastBlockId(detail) => return Some(detail.height.into()),
@@ -0,0 +1,251 @@
+use alloc::collections::BTreeSet;
+use core::fmt::{Display, Error as FmtError, Formatter};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, PortId};
+
+use crate::object::Object;
+
+/// A chain or a channel end whose relaying is paused by an operator
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PauseTarget {
+    /// Every worker relaying from or to the chain
+    Chain { chain_id: ChainId },
+    /// The workers relaying from the channel end on the chain
+    Channel {
+        chain_id: ChainId,
+        port_id: PortId,
+        channel_id: ChannelId,
+    },
+}
+
+impl PauseTarget {
+    pub fn chain_id(&self) -> &ChainId {
+        match self {
+            Self::Chain { chain_id } => chain_id,
+            Self::Channel { chain_id, .. } => chain_id,
+        }
+    }
+
+    /// Whether the worker of `object` has to be stopped while the target is paused.
+    ///
+    /// Wallet workers are never paused since they only monitor the balance of the
+    /// relayer account.
+    pub fn covers(&self, object: &Object) -> bool {
+        match (self, object) {
+            (_, Object::Wallet(_)) => false,
+            (Self::Chain { chain_id }, object) => object.for_chain(chain_id),
+            (
+                Self::Channel {
+                    chain_id,
+                    port_id,
+                    channel_id,
+                },
+                Object::Channel(channel),
+            ) => {
+                &channel.src_chain_id == chain_id
+                    && &channel.src_port_id == port_id
+                    && &channel.src_channel_id == channel_id
+            }
+            (
+                Self::Channel {
+                    chain_id,
+                    port_id,
+                    channel_id,
+                },
+                Object::Packet(packet),
+            ) => {
+                &packet.src_chain_id == chain_id
+                    && &packet.src_port_id == port_id
+                    && &packet.src_channel_id == channel_id
+            }
+            (Self::Channel { .. }, _) => false,
+        }
+    }
+}
+
+impl Display for PauseTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::Chain { chain_id } => write!(f, "chain {chain_id}"),
+            Self::Channel {
+                chain_id,
+                port_id,
+                channel_id,
+            } => write!(f, "channel {channel_id}/{port_id} on chain {chain_id}"),
+        }
+    }
+}
+
+/// Chains and channels whose relaying is paused at runtime.
+///
+/// The events of a paused target are still received from the chains, but they are
+/// not dispatched to any worker. Targets are written to `path` on every update if it
+/// is given, so that they stay paused when the relayer restarts.
+///
+/// The chains configured without transaction submission are never relayed to either,
+/// whatever the paused targets.
+#[derive(Debug, Default)]
+pub struct PausedRelaying {
+    path: Option<PathBuf>,
+    targets: BTreeSet<PauseTarget>,
+    monitor_only: BTreeSet<ChainId>,
+}
+
+impl PausedRelaying {
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let targets = path
+            .as_ref()
+            .filter(|path| path.exists())
+            .and_then(|path| {
+                let content = std::fs::read_to_string(path)
+                    .map_err(|e| warn!("failed to read paused targets {}: {e}", path.display()))
+                    .ok()?;
+                serde_json::from_str(&content)
+                    .map_err(|e| warn!("failed to parse paused targets {}: {e}", path.display()))
+                    .ok()
+            })
+            .unwrap_or_default();
+        Self {
+            path,
+            targets,
+            monitor_only: BTreeSet::new(),
+        }
+    }
+
+    /// Set the chains to which no transaction is submitted.
+    pub fn with_monitor_only(mut self, chain_ids: impl IntoIterator<Item = ChainId>) -> Self {
+        self.set_monitor_only(chain_ids);
+        self
+    }
+
+    pub fn set_monitor_only(&mut self, chain_ids: impl IntoIterator<Item = ChainId>) {
+        self.monitor_only = chain_ids.into_iter().collect();
+    }
+
+    pub fn monitor_only(&self) -> impl Iterator<Item = &ChainId> {
+        self.monitor_only.iter()
+    }
+
+    /// Returns `false` if the target was already paused.
+    pub fn pause(&mut self, target: PauseTarget) -> bool {
+        let inserted = self.targets.insert(target);
+        if inserted {
+            self.save();
+        }
+        inserted
+    }
+
+    /// Returns `false` if the target was not paused.
+    pub fn resume(&mut self, target: &PauseTarget) -> bool {
+        let removed = self.targets.remove(target);
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    pub fn targets(&self) -> Vec<PauseTarget> {
+        self.targets.iter().cloned().collect()
+    }
+
+    pub fn is_paused(&self, object: &Object) -> bool {
+        if let Object::Wallet(_) = object {
+            return false;
+        }
+        self.monitor_only.contains(object.dst_chain_id())
+            || self.targets.iter().any(|target| target.covers(object))
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_string_pretty(&self.targets)
+            .map_err(|e| e.to_string())
+            .and_then(|content| std::fs::write(path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("failed to save paused targets {}: {e}", path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ibc_relayer_types::core::ics24_host::identifier::{ChainId, ChannelId, PortId};
+
+    use super::{PauseTarget, PausedRelaying};
+    use crate::object::{Object, Packet, Wallet};
+
+    fn packet(src_chain_id: &str, dst_chain_id: &str, channel_id: u64) -> Object {
+        Object::Packet(Packet {
+            dst_chain_id: ChainId::from_string(dst_chain_id),
+            src_chain_id: ChainId::from_string(src_chain_id),
+            src_channel_id: ChannelId::new(channel_id),
+            src_port_id: PortId::transfer(),
+        })
+    }
+
+    #[test]
+    fn test_paused_objects() {
+        let mut paused = PausedRelaying::default();
+        assert!(paused.pause(PauseTarget::Channel {
+            chain_id: ChainId::from_string("chain-a"),
+            port_id: PortId::transfer(),
+            channel_id: ChannelId::new(0),
+        }));
+
+        assert!(paused.is_paused(&packet("chain-a", "chain-b", 0)));
+        assert!(!paused.is_paused(&packet("chain-a", "chain-b", 1)));
+        assert!(!paused.is_paused(&packet("chain-b", "chain-a", 0)));
+
+        let chain = PauseTarget::Chain {
+            chain_id: ChainId::from_string("chain-b"),
+        };
+        assert!(paused.pause(chain.clone()));
+        assert!(!paused.pause(chain.clone()));
+        assert!(paused.is_paused(&packet("chain-c", "chain-b", 1)));
+        assert!(!paused.is_paused(&Object::Wallet(Wallet {
+            chain_id: ChainId::from_string("chain-b"),
+        })));
+
+        assert!(paused.resume(&chain));
+        assert!(!paused.resume(&chain));
+        assert!(!paused.is_paused(&packet("chain-c", "chain-b", 1)));
+    }
+
+    #[test]
+    fn test_monitor_only_chains() {
+        let paused = PausedRelaying::default().with_monitor_only([ChainId::from_string("chain-b")]);
+
+        assert!(paused.is_paused(&packet("chain-a", "chain-b", 0)));
+        assert!(!paused.is_paused(&packet("chain-b", "chain-a", 0)));
+        assert!(paused.targets().is_empty());
+    }
+
+    #[test]
+    fn test_paused_targets_are_persisted() {
+        let path = std::env::temp_dir().join("forcerelay_paused_targets_test.json");
+        let _ = std::fs::remove_file(&path);
+
+        let target = PauseTarget::Chain {
+            chain_id: ChainId::from_string("chain-a"),
+        };
+        let mut paused = PausedRelaying::load(Some(path.clone()));
+        assert!(paused.targets().is_empty());
+        paused.pause(target.clone());
+
+        let mut paused = PausedRelaying::load(Some(path.clone()));
+        assert_eq!(paused.targets(), vec![target.clone()]);
+        paused.resume(&target);
+
+        let paused = PausedRelaying::load(Some(path.clone()));
+        assert!(paused.targets().is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,187 @@
+//! Restarts of the chains whose threads panic.
+//!
+//! A chain is restarted after each panic of its runtime, event monitor, batch worker
+//! or of a worker relaying from it, with an exponential backoff between the restarts
+//! as configured in `global.chain_restart`. Once the chain keeps panicking after
+//! `max_attempts` restarts in a row, it is marked failed and left alone until its
+//! configuration is reloaded, instead of being restarted in a loop.
+
+use alloc::collections::btree_map::BTreeMap as HashMap;
+use core::time::Duration;
+use std::time::Instant;
+
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+use tracing::{error, warn};
+
+use crate::config::ChainRestartConfig;
+use crate::telemetry;
+
+#[derive(Clone, Debug, Default)]
+struct ChainRestart {
+    /// Restarts in a row, since the chain last ran for `max_backoff` without panicking
+    attempts: u32,
+    /// When the next restart is due, if one is scheduled
+    next: Option<Instant>,
+    last_restart: Option<Instant>,
+    failed: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ChainRestarts {
+    config: ChainRestartConfig,
+    chains: HashMap<ChainId, ChainRestart>,
+}
+
+impl ChainRestarts {
+    pub fn new(config: ChainRestartConfig) -> Self {
+        Self {
+            config,
+            chains: HashMap::new(),
+        }
+    }
+
+    /// Schedule a restart of the chain after the panic of one of its threads, unless
+    /// one is already scheduled, or mark the chain failed once it has been restarted
+    /// `max_attempts` times in a row
+    pub fn record_panic(&mut self, chain_id: &ChainId, now: Instant) {
+        let restart = self.chains.entry(chain_id.clone()).or_default();
+        if restart.failed || restart.next.is_some() {
+            return;
+        }
+
+        let recovered = restart.last_restart.map_or(false, |last_restart| {
+            now.saturating_duration_since(last_restart) >= self.config.max_backoff
+        });
+        if recovered {
+            restart.attempts = 0;
+        }
+
+        if restart.attempts >= self.config.max_attempts {
+            restart.failed = true;
+            error!(
+                "chain {chain_id} keeps panicking after {} restarts, marking it failed",
+                restart.attempts
+            );
+            telemetry!(chain_failures, chain_id);
+            return;
+        }
+
+        let delay = backoff(&self.config, restart.attempts);
+        restart.next = Some(now + delay);
+        warn!(
+            "restarting chain {chain_id} in {delay:?} after the panic of one of its threads (attempt {}/{})",
+            restart.attempts + 1,
+            self.config.max_attempts
+        );
+    }
+
+    /// The chains whose restart is due, counted as attempted
+    pub fn due(&mut self, now: Instant) -> Vec<ChainId> {
+        self.chains
+            .iter_mut()
+            .filter(|(_, restart)| restart.next.map_or(false, |next| next <= now))
+            .map(|(chain_id, restart)| {
+                restart.next = None;
+                restart.attempts += 1;
+                restart.last_restart = Some(now);
+                chain_id.clone()
+            })
+            .collect()
+    }
+
+    /// Forget the restarts of the chain, whose configuration was reloaded
+    pub fn forget(&mut self, chain_id: &ChainId) {
+        self.chains.remove(chain_id);
+    }
+
+    pub fn is_failed(&self, chain_id: &ChainId) -> bool {
+        self.chains
+            .get(chain_id)
+            .map_or(false, |restart| restart.failed)
+    }
+
+    /// The chains marked failed
+    pub fn failed(&self) -> Vec<ChainId> {
+        self.chains
+            .iter()
+            .filter(|(_, restart)| restart.failed)
+            .map(|(chain_id, _)| chain_id.clone())
+            .collect()
+    }
+}
+
+/// `initial_backoff` doubled at each attempt, up to `max_backoff`
+fn backoff(config: &ChainRestartConfig, attempts: u32) -> Duration {
+    1u32.checked_shl(attempts)
+        .and_then(|factor| config.initial_backoff.checked_mul(factor))
+        .map_or(config.max_backoff, |delay| delay.min(config.max_backoff))
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+    use std::time::Instant;
+
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+    use super::ChainRestarts;
+    use crate::config::ChainRestartConfig;
+
+    fn restarts() -> ChainRestarts {
+        ChainRestarts::new(ChainRestartConfig {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(3),
+        })
+    }
+
+    #[test]
+    fn test_restarts_back_off_until_chain_fails() {
+        let chain_id = ChainId::from_string("chain-a");
+        let mut restarts = restarts();
+        let mut now = Instant::now();
+
+        for delay in [1, 2, 3] {
+            restarts.record_panic(&chain_id, now);
+            // a panic of another thread of the chain doesn't schedule another restart
+            restarts.record_panic(&chain_id, now);
+            assert!(restarts
+                .due(now + Duration::from_secs(delay - 1))
+                .is_empty());
+            now += Duration::from_secs(delay);
+            assert_eq!(restarts.due(now), vec![chain_id.clone()]);
+            now += Duration::from_secs(1);
+        }
+
+        restarts.record_panic(&chain_id, now);
+        assert!(restarts.is_failed(&chain_id));
+        assert_eq!(restarts.failed(), vec![chain_id.clone()]);
+        assert!(restarts.due(now + Duration::from_secs(60)).is_empty());
+
+        restarts.forget(&chain_id);
+        assert!(restarts.failed().is_empty());
+    }
+
+    #[test]
+    fn test_restart_attempts_reset_once_chain_recovered() {
+        let chain_id = ChainId::from_string("chain-a");
+        let mut restarts = restarts();
+        let mut now = Instant::now();
+
+        for _ in 0..3 {
+            restarts.record_panic(&chain_id, now);
+            now += Duration::from_secs(3);
+            assert_eq!(restarts.due(now), vec![chain_id.clone()]);
+        }
+
+        // the chain ran for `max_backoff` since its last restart
+        now += Duration::from_secs(3);
+        restarts.record_panic(&chain_id, now);
+        assert!(!restarts.is_failed(&chain_id));
+        assert!(restarts.due(now).is_empty());
+        assert_eq!(
+            restarts.due(now + Duration::from_secs(1)),
+            vec![chain_id.clone()]
+        );
+    }
+}
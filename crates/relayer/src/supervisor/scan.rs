@@ -27,6 +27,7 @@ use crate::{
     client_state::IdentifiedAnyClientState,
     config::{
         filter::{ChannelFilters, ChannelPolicy},
+        path::PathConfig,
         ChainConfig, Config,
     },
     path::PathIdentifiers,
@@ -88,7 +89,21 @@ flex_error::define_error! {
                     "failed to query counterparty connection state of connection '{}' on counterparty chain '{}', reason: {}",
                     e.connection_id, e.counterparty_chain_id, e.reason
                 )
+            },
+
+        PathMismatch
+            {
+                chain_id: ChainId,
+                channel_id: ChannelId,
+                expected: String,
+                actual: String,
             }
+            |e| {
+                format_args!(
+                    "channel '{}' on chain '{}' is built upon '{}' instead of '{}'",
+                    e.channel_id, e.chain_id, e.actual, e.expected
+                )
+            },
     }
 }
 
@@ -743,6 +758,36 @@ fn scan_allowed_channel<Chain: ChainHandle>(
     })
 }
 
+/// Scan the channel of a path declared in the configuration, checking that it is
+/// built upon the declared connection and client.
+pub fn scan_declared_path<Chain: ChainHandle>(
+    registry: &'_ mut Registry<Chain>,
+    path: &PathConfig,
+) -> Result<(Chain, IdentifiedAnyClientState, ChannelScan), Error> {
+    let chain = registry.get_or_spawn(&path.chain).map_err(Error::spawn)?;
+    let scanned = scan_allowed_channel(registry, &chain, &path.port, &path.channel)?;
+
+    if scanned.connection.connection_id != path.connection {
+        return Err(Error::path_mismatch(
+            path.chain.clone(),
+            path.channel.clone(),
+            path.connection.to_string(),
+            scanned.connection.connection_id.to_string(),
+        ));
+    }
+    if scanned.client.client_id != path.client {
+        return Err(Error::path_mismatch(
+            path.chain.clone(),
+            path.channel.clone(),
+            path.client.to_string(),
+            scanned.client.client_id.to_string(),
+        ));
+    }
+
+    let channel_scan = ChannelScan::new(scanned.channel, scanned.counterparty_channel);
+    Ok((chain, scanned.client, channel_scan))
+}
+
 fn query_client<Chain: ChainHandle>(
     chain: &Chain,
     client_id: &ClientId,
@@ -8,16 +8,15 @@ use ibc_relayer_types::core::{
 use crate::{
     chain::{counterparty::connection_state_on_destination, handle::ChainHandle},
     client_state::IdentifiedAnyClientState,
-    config::Config,
+    config::{path::PathConfig, Config},
     object::{Channel, Client, Connection, Object, Packet, Wallet},
     registry::Registry,
     supervisor::error::Error as SupervisorError,
-    telemetry,
     worker::WorkerMap,
 };
 
 use super::{
-    scan::{ChainScan, ChainsScan, ChannelScan, ClientScan, ConnectionScan},
+    scan::{scan_declared_path, ChainScan, ChainsScan, ChannelScan, ClientScan, ConnectionScan},
     Error,
 };
 
@@ -71,10 +70,16 @@ impl<'a, Chain: ChainHandle> SpawnContext<'a, Chain> {
             self.spawn_workers_for_client(chain.clone(), client_scan);
         }
 
-        // Let's only spawn the wallet worker if telemetry is enabled,
-        // otherwise the worker just ends up issuing queries to the node
-        // without making anything of the result
-        telemetry!(self.spawn_wallet_worker(chain));
+        // Let's only spawn the wallet worker if telemetry is enabled or if the balance
+        // must be kept above a minimum, otherwise the worker just ends up issuing
+        // queries to the node without making anything of the result
+        let min_balance = self
+            .config
+            .find_chain(&scan.chain_id)
+            .and_then(|config| config.min_balance());
+        if cfg!(feature = "telemetry") || min_balance.is_some() {
+            self.spawn_wallet_worker(chain);
+        }
     }
 
     pub fn spawn_wallet_worker(&mut self, chain: Chain) {
@@ -212,6 +217,83 @@ impl<'a, Chain: ChainHandle> SpawnContext<'a, Chain> {
         }
     }
 
+    /// Spawns the workers of a path declared in the configuration, without waiting
+    /// for packets to be pending on its channel. Fails if the channel, connection
+    /// and client of the path do not exist on its chain.
+    pub fn spawn_workers_for_path(&mut self, path: &PathConfig) -> Result<(), Error> {
+        let _span = tracing::error_span!("path", path = %path).entered();
+
+        let (chain, client, channel_scan) = scan_declared_path(self.registry, path)
+            .map_err(|e| SupervisorError::invalid_path(path.clone(), e))?;
+
+        let chan_state_src = channel_scan.channel.channel_end.state;
+        let counterparty_channel = channel_scan.counterparty.clone().filter(|counterparty| {
+            chan_state_src.is_open() && counterparty.channel_end.state.is_open()
+        });
+        let Some(counterparty_channel) = counterparty_channel else {
+            // Let the handshake of the channel be completed first
+            self.spawn_workers_for_channel(chain, &client, channel_scan)?;
+            return Ok(());
+        };
+
+        let mode = &self.config.mode;
+
+        let counterparty_chain = self
+            .registry
+            .get_or_spawn(&client.client_state.chain_id())
+            .map_err(SupervisorError::spawn)?;
+
+        if mode.clients.enabled {
+            let client_object = Object::Client(Client {
+                dst_client_id: client.client_id.clone(),
+                dst_chain_id: chain.id(),
+                src_chain_id: counterparty_chain.id(),
+            });
+
+            self.workers
+                .spawn(
+                    counterparty_chain.clone(),
+                    chain.clone(),
+                    &client_object,
+                    self.config,
+                )
+                .then(|| info!("spawned client worker: {}", client_object.short_name()));
+        }
+
+        if mode.packets.enabled {
+            let path_objects = [
+                (
+                    chain.clone(),
+                    counterparty_chain.clone(),
+                    Object::Packet(Packet {
+                        dst_chain_id: counterparty_chain.id(),
+                        src_chain_id: chain.id(),
+                        src_channel_id: channel_scan.channel.channel_id,
+                        src_port_id: channel_scan.channel.port_id,
+                    }),
+                ),
+                (
+                    counterparty_chain.clone(),
+                    chain.clone(),
+                    Object::Packet(Packet {
+                        dst_chain_id: chain.id(),
+                        src_chain_id: counterparty_chain.id(),
+                        src_channel_id: counterparty_channel.channel_id,
+                        src_port_id: counterparty_channel.port_id,
+                    }),
+                ),
+            ];
+
+            for (src_chain, dst_chain, path_object) in path_objects {
+                self.workers
+                    .spawn(src_chain, dst_chain, &path_object, self.config)
+                    .then(|| info!("spawned packet worker: {}", path_object.short_name()));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Spawns all the [`WorkerHandle`](crate::worker::WorkerHandle)s that will
     /// handle a given channel for a given source chain.
     pub fn spawn_workers_for_channel(
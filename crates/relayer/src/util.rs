@@ -5,6 +5,7 @@ pub mod collate;
 pub mod diff;
 pub mod iter;
 pub mod lock;
+pub mod panic;
 pub mod pretty;
 pub mod queue;
 pub mod retry;
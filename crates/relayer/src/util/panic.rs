@@ -0,0 +1,177 @@
+//! Panics of the threads bound to a chain, reported to the supervisor.
+//!
+//! A panicking thread dies on its own: the runtime of the chain stops serving the
+//! queries of the workers, or its event monitor stops emitting events while the rest
+//! of the relayer keeps running, and nothing is relayed from or to the chain any more.
+//! The threads bound to a chain are spawned with [`spawn_chain_thread`], and the panic
+//! hook reports their panics, along with their payload and backtrace, to the telemetry
+//! and to the supervisor, which restarts the chain.
+//!
+//! The threads of the background tasks, such as the workers, are registered by
+//! [`spawn_background_task`](crate::util::task::spawn_background_task), which aborts
+//! the task whose step panics. Their panics are reported to the logs and the telemetry
+//! by the name of the span of the task, and the supervisor respawns the aborted tasks.
+
+use core::any::Any;
+use core::cell::RefCell;
+use core::fmt::{Display, Error as FmtError, Formatter};
+use core::panic::Location;
+use std::backtrace::Backtrace;
+use std::panic;
+use std::sync::Once;
+use std::thread::{self, JoinHandle};
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use once_cell::sync::Lazy;
+use tracing::error;
+
+use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+use crate::telemetry;
+
+/// Panic of a thread bound to a chain
+#[derive(Clone, Debug)]
+pub struct ChainPanic {
+    pub chain_id: ChainId,
+    /// Role of the thread in the chain, e.g. `event_monitor`
+    pub thread: &'static str,
+    /// Payload of the panic, along with where it was raised
+    pub message: String,
+    pub backtrace: String,
+}
+
+impl Display for ChainPanic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        write!(
+            f,
+            "{} thread of chain {} panicked: {}",
+            self.thread, self.chain_id, self.message
+        )
+    }
+}
+
+thread_local! {
+    static CHAIN_THREAD: RefCell<Option<(ChainId, &'static str)>> = RefCell::new(None);
+    static TASK_THREAD: RefCell<Option<&'static str>> = RefCell::new(None);
+}
+
+static CHAIN_PANICS: Lazy<(Sender<ChainPanic>, Receiver<ChainPanic>)> = Lazy::new(unbounded);
+
+/// Install the panic hook reporting the panics of the chain threads, once for the
+/// whole process. The hook which was installed before still runs after it
+pub fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            report_panic(info.payload(), info.location());
+            previous(info);
+        }));
+    });
+}
+
+/// Bind the current thread to `chain_id`, so that its panics are reported as the
+/// panics of the `thread` of the chain
+pub fn register_chain_thread(chain_id: ChainId, thread: &'static str) {
+    install_panic_hook();
+    CHAIN_THREAD.with(|chain_thread| *chain_thread.borrow_mut() = Some((chain_id, thread)));
+}
+
+/// Register the current thread as the one of the background `task`, so that its
+/// panics are reported by the name of the task
+pub fn register_task_thread(task: &'static str) {
+    install_panic_hook();
+    TASK_THREAD.with(|task_thread| *task_thread.borrow_mut() = Some(task));
+}
+
+/// Spawn a thread running `f` which is bound to `chain_id` as its `name` thread
+pub fn spawn_chain_thread<F, T>(chain_id: ChainId, name: &'static str, f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    thread::spawn(move || {
+        register_chain_thread(chain_id, name);
+        f()
+    })
+}
+
+/// Receiver of the panics of the chain threads
+pub fn chain_panics() -> Receiver<ChainPanic> {
+    CHAIN_PANICS.1.clone()
+}
+
+fn report_panic(payload: &(dyn Any + Send), location: Option<&Location<'_>>) {
+    // the thread locals are already gone if the thread panics while exiting
+    let chain_thread = CHAIN_THREAD
+        .try_with(|chain_thread| chain_thread.borrow().clone())
+        .ok()
+        .flatten();
+    let task_thread = TASK_THREAD
+        .try_with(|task_thread| *task_thread.borrow())
+        .ok()
+        .flatten();
+    if chain_thread.is_none() && task_thread.is_none() {
+        return;
+    }
+
+    let mut message = payload
+        .downcast_ref::<&str>()
+        .map(|message| (*message).to_owned())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string payload".to_owned());
+    if let Some(location) = location {
+        message = format!("{message}, at {location}");
+    }
+    let backtrace = Backtrace::force_capture().to_string();
+
+    let Some((chain_id, thread)) = chain_thread else {
+        let task = task_thread.expect("the thread is a chain or task thread");
+        error!(%backtrace, "task {task} panicked: {message}");
+        telemetry!(task_panics, task);
+        return;
+    };
+    let chain_panic = ChainPanic {
+        chain_id,
+        thread,
+        message,
+        backtrace,
+    };
+
+    error!(backtrace = %chain_panic.backtrace, "{chain_panic}");
+    telemetry!(chain_thread_panics, &chain_panic.chain_id, thread);
+    let _ = CHAIN_PANICS.0.send(chain_panic);
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use ibc_relayer_types::core::ics24_host::identifier::ChainId;
+
+    use super::{chain_panics, register_task_thread, spawn_chain_thread, TASK_THREAD};
+
+    #[test]
+    fn test_chain_thread_panics_are_reported() {
+        let chain_id = ChainId::from_string("panicking-chain");
+        let thread = spawn_chain_thread(chain_id.clone(), "test", || {
+            panic!("expected panic");
+        });
+        assert!(thread.join().is_err());
+
+        let chain_panic = chain_panics().recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(chain_panic.chain_id, chain_id);
+        assert_eq!(chain_panic.thread, "test");
+        assert!(chain_panic.message.starts_with("expected panic, at "));
+        assert!(!chain_panic.backtrace.is_empty());
+    }
+
+    #[test]
+    fn test_task_threads_are_registered() {
+        let task = std::thread::spawn(|| {
+            register_task_thread("worker.test");
+            TASK_THREAD.with(|task_thread| *task_thread.borrow())
+        });
+        assert_eq!(task.join().unwrap(), Some("worker.test"));
+    }
+}
@@ -2,11 +2,13 @@ use core::fmt::Display;
 use core::mem;
 use core::time::Duration;
 use crossbeam_channel::{bounded, Sender};
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::{Arc, RwLock};
 use std::thread;
 use tracing::{debug, error, warn};
 
 use crate::util::lock::LockExt;
+use crate::util::panic::register_task_thread;
 
 /**
    A task handle holds the endpoints for stopping or waiting for a
@@ -22,6 +24,7 @@ use crate::util::lock::LockExt;
 pub struct TaskHandle {
     shutdown_sender: Sender<()>,
     stopped: Arc<RwLock<bool>>,
+    panicked: Arc<RwLock<bool>>,
     join_handle: DropJoinHandle,
 }
 
@@ -84,6 +87,11 @@ pub enum Next {
    so that the step runner do not get stuck indefinitely even
    when shutdown instruction has been sent through the
    [`TaskHandle`].
+
+   If the step runner panics, the panic is reported along with the name of
+   the span, and the background task is aborted as if it had returned a
+   fatal error, which the holder of the [`TaskHandle`] can tell with
+   [`has_panicked`](TaskHandle::has_panicked).
 */
 pub fn spawn_background_task<E: Display>(
     span: tracing::Span,
@@ -94,29 +102,37 @@ pub fn spawn_background_task<E: Display>(
 
     let stopped = Arc::new(RwLock::new(false));
     let write_stopped = stopped.clone();
+    let panicked = Arc::new(RwLock::new(false));
+    let write_panicked = panicked.clone();
 
     let (shutdown_sender, receiver) = bounded(1);
 
     let join_handle = thread::spawn(move || {
+        register_task_thread(span.metadata().map_or("task", |metadata| metadata.name()));
         let _entered = span.enter();
         loop {
             match receiver.try_recv() {
                 Ok(()) => {
                     break;
                 }
-                _ => match step_runner() {
-                    Ok(Next::Continue) => {}
-                    Ok(Next::Abort) => {
+                _ => match panic::catch_unwind(AssertUnwindSafe(&mut step_runner)) {
+                    Ok(Ok(Next::Continue)) => {}
+                    Ok(Ok(Next::Abort)) => {
                         debug!("aborting task");
                         break;
                     }
-                    Err(TaskError::Ignore(e)) => {
+                    Ok(Err(TaskError::Ignore(e))) => {
                         warn!("task encountered ignorable error: {}", e);
                     }
-                    Err(TaskError::Fatal(e)) => {
+                    Ok(Err(TaskError::Fatal(e))) => {
                         error!("task aborting after encountering fatal error: {}", e);
                         break;
                     }
+                    Err(_) => {
+                        error!("task aborting after panicking");
+                        *write_panicked.acquire_write() = true;
+                        break;
+                    }
                 },
             }
             if let Some(interval) = interval_pause {
@@ -132,6 +148,7 @@ pub fn spawn_background_task<E: Display>(
     TaskHandle {
         shutdown_sender,
         stopped,
+        panicked,
         join_handle: DropJoinHandle(Some(join_handle)),
     }
 }
@@ -179,6 +196,14 @@ impl TaskHandle {
     pub fn is_stopped(&self) -> bool {
         *self.stopped.acquire_read()
     }
+
+    /**
+       Check whether a background task has been aborted because its step
+       runner panicked.
+    */
+    pub fn has_panicked(&self) -> bool {
+        *self.panicked.acquire_read()
+    }
 }
 
 impl Drop for DropJoinHandle {
@@ -194,3 +219,37 @@ impl Drop for TaskHandle {
         let _ = self.shutdown_sender.send(());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+    use core::time::Duration;
+    use std::time::Instant;
+
+    use tracing::error_span;
+
+    use super::{spawn_background_task, Next, TaskError};
+
+    #[test]
+    fn test_panicking_task_is_aborted() {
+        let mut steps = 0;
+        let handle = spawn_background_task(
+            error_span!("worker.test"),
+            None,
+            move || -> Result<Next, TaskError<Infallible>> {
+                steps += 1;
+                if steps == 3 {
+                    panic!("expected panic");
+                }
+                Ok(Next::Continue)
+            },
+        );
+
+        let start = Instant::now();
+        while !handle.is_stopped() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(handle.has_panicked());
+    }
+}
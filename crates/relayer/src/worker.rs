@@ -3,12 +3,15 @@ use core::fmt::{Display, Error as FmtError, Formatter};
 use ibc_relayer_types::core::ics04_channel::channel::Order;
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
-use tracing::error;
+use tracing::{error, warn};
 
 use crate::foreign_client::ForeignClient;
 use crate::link::{Link, LinkParameters, Resubmit};
 use crate::{
-    chain::handle::{ChainHandle, ChainHandlePair},
+    chain::{
+        endpoint::ChainCapabilities,
+        handle::{ChainHandle, ChainHandlePair},
+    },
     config::Config,
     object::Object,
 };
@@ -48,6 +51,19 @@ impl WorkerId {
     }
 }
 
+/// Features of IBC supported by `chain`, or all of them if its runtime fails to report
+/// them, as they were assumed before chains reported them
+fn capabilities_of<Chain: ChainHandle>(chain: &Chain) -> ChainCapabilities {
+    chain.capabilities().unwrap_or_else(|e| {
+        warn!(
+            "failed to query the capabilities of chain {}, assuming it supports every feature: {}",
+            chain.id(),
+            e
+        );
+        ChainCapabilities::ALL
+    })
+}
+
 impl Display for WorkerId {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
         write!(f, "{}", self.0)
@@ -62,6 +78,9 @@ pub fn spawn_worker_tasks<ChainA: ChainHandle, ChainB: ChainHandle>(
 ) -> WorkerHandle {
     let mut task_handles = Vec::new();
 
+    let (src_capabilities, dst_capabilities) =
+        (capabilities_of(&chains.a), capabilities_of(&chains.b));
+
     let (cmd_tx, data) = match &object {
         Object::Client(client) => {
             let client = ForeignClient::restore(client.dst_client_id.clone(), chains.b, chains.a);
@@ -94,6 +113,16 @@ pub fn spawn_worker_tasks<ChainA: ChainHandle, ChainB: ChainHandle>(
 
             (cmd_tx, Some(data))
         }
+        Object::Connection(_) | Object::Channel(_) | Object::Packet(_)
+            if !src_capabilities.proofs =>
+        {
+            warn!(
+                "chain {} provides no proofs of its IBC states, not spawning {} worker",
+                chains.a.id(),
+                object.short_name()
+            );
+            (None, None)
+        }
         Object::Connection(connection) => {
             let (cmd_tx, cmd_rx) = crossbeam_channel::unbounded();
             let connection_task =
@@ -123,13 +152,25 @@ pub fn spawn_worker_tasks<ChainA: ChainHandle, ChainB: ChainHandle>(
             );
 
             match link_res {
-                Ok(link) => {
+                Ok(link)
+                    if link.a_to_b.channel().ordering == Order::Ordered
+                        && !(src_capabilities.ordered_channels
+                            && dst_capabilities.ordered_channels) =>
+                {
+                    warn!(
+                        "chains {} and {} do not both support ordered channels, not spawning {} worker",
+                        chains.a.id(),
+                        path.dst_chain_id,
+                        path.short_name()
+                    );
+                    (None, None)
+                }
+                Ok(mut link) => {
                     let channel_ordering = link.a_to_b.channel().ordering;
                     let should_clear_on_start =
                         packets_config.clear_on_start || channel_ordering == Order::Ordered;
 
                     let (cmd_tx, cmd_rx) = crossbeam_channel::unbounded();
-                    let link = Arc::new(Mutex::new(link));
                     let resubmit = Resubmit::from_clear_interval(packets_config.clear_interval);
 
                     let src_chain_config = config
@@ -151,8 +192,55 @@ pub fn spawn_worker_tasks<ChainA: ChainHandle, ChainB: ChainHandle>(
                         }
                     };
 
+                    if let Some(transfer_policy) = src_chain_config.and_then(|chain_config| {
+                        chain_config
+                            .packet_filter()
+                            .min_transfer_amounts
+                            .iter()
+                            .find(|(channel, _)| channel.matches(&path.src_channel_id))
+                            .map(|(_, policy)| policy.clone())
+                    }) {
+                        link.a_to_b.set_transfer_policy(transfer_policy);
+                    }
+
+                    let concurrency = config
+                        .paths
+                        .iter()
+                        .find(|declared| {
+                            let relay_path = &link.a_to_b;
+                            declared.is_channel(
+                                &path.src_chain_id,
+                                relay_path.src_port_id(),
+                                relay_path.src_channel_id(),
+                            ) || declared.is_channel(
+                                &path.dst_chain_id,
+                                relay_path.dst_port_id(),
+                                relay_path.dst_channel_id(),
+                            )
+                        })
+                        .map(|declared| declared.concurrency);
+                    if let Some(concurrency) = concurrency.filter(|concurrency| *concurrency > 1) {
+                        if let Err(e) = link.a_to_b.set_concurrency(concurrency) {
+                            error!(
+                                "failed to relay the packets of {} concurrently: {}",
+                                path.short_name(),
+                                e
+                            );
+                        }
+                    }
+
+                    let link = Arc::new(Mutex::new(link));
+
+                    if fee_filter.is_some() && !src_capabilities.fee_middleware {
+                        warn!(
+                            "chain {} does not support the fee middleware, ignoring the fee filter of {}",
+                            chains.a.id(),
+                            path.short_name()
+                        );
+                    }
+
                     // Only spawn the incentivized worker if a fee filter is specified in the configuration
-                    let packet_task = match fee_filter {
+                    let packet_task = match fee_filter.filter(|_| src_capabilities.fee_middleware) {
                         Some(filter) => packet::spawn_incentivized_packet_cmd_worker(
                             cmd_rx,
                             link.clone(),
@@ -190,6 +278,17 @@ pub fn spawn_worker_tasks<ChainA: ChainHandle, ChainB: ChainHandle>(
             (None, None)
         }
 
+        Object::CrossChainQuery(cross_chain_query)
+            if !(src_capabilities.cross_chain_queries && dst_capabilities.cross_chain_queries) =>
+        {
+            warn!(
+                "chains {} and {} do not both support cross-chain queries, not spawning {} worker",
+                chains.a.id(),
+                chains.b.id(),
+                cross_chain_query.short_name()
+            );
+            (None, None)
+        }
         Object::CrossChainQuery(cross_chain_query) => {
             let (cmd_tx, cmd_rx) = crossbeam_channel::unbounded();
             let cross_chain_query_task = cross_chain_query::spawn_cross_chain_query_worker(
@@ -116,6 +116,11 @@ impl WorkerHandle {
         true
     }
 
+    /// Whether one of the worker tasks was aborted after panicking
+    pub fn has_panicked(&self) -> bool {
+        self.task_handles.iter().any(TaskHandle::has_panicked)
+    }
+
     /// Wait for the worker thread to finish.
     pub fn join(mut self) {
         let task_handles = mem::take(&mut self.task_handles);
@@ -209,6 +209,20 @@ impl WorkerMap {
         // Drop handle automatically handles the waiting for tasks to terminate.
     }
 
+    /// Shut down the workers aborted after the panic of one of their tasks, returning
+    /// their [`Object`]s.
+    pub fn shutdown_panicked(&mut self) -> Vec<Object> {
+        let panicked = self
+            .workers
+            .iter()
+            .filter_map(|(object, handle)| handle.has_panicked().then(|| object.clone()))
+            .collect::<Vec<_>>();
+        for object in &panicked {
+            self.shutdown_worker(object);
+        }
+        panicked
+    }
+
     /// Shut down all the workers, asynchronously.
     pub fn shutdown(&mut self) {
         let workers = mem::take(&mut self.workers);
@@ -19,6 +19,7 @@ use ibc_relayer_types::Height;
 
 use crate::chain::handle::{CacheTxHashStatus, ChainHandle};
 use crate::config::filter::FeePolicy;
+use crate::decision::{self, Decision, DecisionKind};
 use crate::event::monitor::EventBatch;
 use crate::event::IbcEventWithHeight;
 use crate::foreign_client::HasExpiredOrFrozenError;
@@ -292,7 +293,12 @@ fn handle_incentivized_packet_cmd<ChainA: ChainHandle, ChainB: ChainHandle>(
             // In addition if the WriteAcknowledgment are not relayed, no fees will be paid.
             //IbcEvent::WriteAcknowledgement(ack) => get_incentivized_for_write_acknowledgement(link, ack, event.height.revision_height(), incentivized_ack_cache.clone()),
         }
-        filter_batch(batch.borrow_mut(), incentivized_recv_cache, fee_filter);
+        filter_batch(
+            batch.borrow_mut(),
+            path,
+            incentivized_recv_cache,
+            fee_filter,
+        );
         handle_update_schedule(link, 0, path, batch)
     } else {
         Ok(())
@@ -346,19 +352,36 @@ fn _get_incentivized_for_write_acknowledgement<ChainA: ChainHandle, ChainB: Chai
 /// should be relayed or not.
 fn filter_batch(
     batch: &mut EventBatch,
+    path: &Packet,
     incentivized_recv_cache: &RwArc<Cache<Sequence, IncentivizedPacket>>,
     fee_filter: &FeePolicy,
 ) {
     batch.events.retain(|e| match &e.event {
-        IbcEvent::SendPacket(packet) => incentivized_recv_cache
-            .acquire_read()
-            .get(&packet.packet.sequence)
-            .map_or(false, |incentivized_event| {
-                let grouped_amounts =
-                    retrieve_all_fees_from_incentivized_packet(incentivized_event);
-
-                fee_filter.should_relay(IbcEventType::SendPacket, &grouped_amounts)
-            }),
+        IbcEvent::SendPacket(packet) => {
+            let relay = incentivized_recv_cache
+                .acquire_read()
+                .get(&packet.packet.sequence)
+                .map_or(false, |incentivized_event| {
+                    let grouped_amounts =
+                        retrieve_all_fees_from_incentivized_packet(incentivized_event);
+
+                    fee_filter.should_relay(IbcEventType::SendPacket, &grouped_amounts)
+                });
+
+            if !relay {
+                decision::global().publish(Decision::new(
+                    DecisionKind::Skipped,
+                    path.src_chain_id.clone(),
+                    path.dst_chain_id.clone(),
+                    path.src_port_id.clone(),
+                    path.src_channel_id.clone(),
+                    Some(packet.packet.sequence),
+                    "not incentivized enough by the fees",
+                ));
+            }
+
+            relay
+        }
         _ => true,
     });
 }
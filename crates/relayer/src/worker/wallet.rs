@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use tracing::{error_span, trace, warn};
+use tracing::{error_span, info, trace, warn};
 
 use crate::{
     chain::handle::ChainHandle,
@@ -10,6 +10,8 @@ use crate::{
 
 pub fn spawn_wallet_worker<Chain: ChainHandle>(chain: Chain) -> TaskHandle {
     let span = error_span!("wallet", chain = %chain.id());
+    let min_balance = chain.config().ok().and_then(|config| config.min_balance());
+    let mut low_balance = false;
 
     spawn_background_task(span, Some(Duration::from_secs(5)), move || {
         let key = chain.get_key().map_err(|e| {
@@ -36,6 +38,26 @@ pub fn spawn_wallet_worker<Chain: ChainHandle>(chain: Chain) -> TaskHandle {
                     &key.account(),
                     &balance.denom
                 );
+
+                if let Some(min_balance) = min_balance {
+                    let below = amount < min_balance as f64;
+                    if below && !low_balance {
+                        warn!(
+                            %amount, denom = %balance.denom, account = %key.account(), min_balance,
+                            "wallet balance is below the minimum, the relayer may soon be unable to pay for its transactions"
+                        );
+                    } else if !below && low_balance {
+                        info!(%amount, denom = %balance.denom, account = %key.account(), min_balance, "wallet balance is back above the minimum");
+                    }
+                    low_balance = below;
+                    telemetry!(
+                        wallet_low_balance,
+                        &chain.id(),
+                        &key.account(),
+                        &balance.denom,
+                        below
+                    );
+                }
             }
             Err(e) => {
                 warn!(
@@ -3,6 +3,7 @@ extern crate alloc;
 pub mod encoder;
 mod path_identifier;
 pub mod server;
+pub mod snapshot;
 pub mod state;
 
 use alloc::sync::Arc;
@@ -13,6 +14,7 @@ use std::{
     thread::JoinHandle,
 };
 
+pub use crate::snapshot::TelemetrySnapshot;
 pub use crate::state::TelemetryState;
 
 pub fn new_state() -> Arc<TelemetryState> {
@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use prometheus::proto::{self, MetricFamily, MetricType};
+use serde::Serialize;
+
+/// The current value of the relayer metrics, for host applications which embed the
+/// relayer and forward its metrics into their own monitoring systems instead of
+/// scraping the Prometheus endpoint.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct TelemetrySnapshot {
+    pub counters: Vec<Sample>,
+    pub gauges: Vec<Sample>,
+    pub histograms: Vec<HistogramSample>,
+}
+
+/// The value of a counter or a gauge for a set of labels
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Sample {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub value: f64,
+}
+
+/// The observations of a histogram for a set of labels
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct HistogramSample {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub count: u64,
+    pub sum: f64,
+    /// Upper bound and cumulative count of every bucket
+    pub buckets: Vec<(f64, u64)>,
+}
+
+impl TelemetrySnapshot {
+    /// Build a snapshot from the metric families gathered from a Prometheus registry.
+    ///
+    /// Summaries and untyped metrics are not recorded by the relayer and are skipped.
+    pub fn from_families(families: &[MetricFamily]) -> Self {
+        let mut snapshot = Self::default();
+
+        for mf in families {
+            let name = mf.get_name();
+
+            for m in mf.get_metric() {
+                match mf.get_field_type() {
+                    MetricType::COUNTER => snapshot.counters.push(Sample {
+                        name: name.to_string(),
+                        labels: get_labels(m),
+                        value: m.get_counter().get_value(),
+                    }),
+                    MetricType::GAUGE => snapshot.gauges.push(Sample {
+                        name: name.to_string(),
+                        labels: get_labels(m),
+                        value: m.get_gauge().get_value(),
+                    }),
+                    MetricType::HISTOGRAM => {
+                        let h = m.get_histogram();
+                        snapshot.histograms.push(HistogramSample {
+                            name: name.to_string(),
+                            labels: get_labels(m),
+                            count: h.get_sample_count(),
+                            sum: h.get_sample_sum(),
+                            buckets: h
+                                .get_bucket()
+                                .iter()
+                                .map(|b| (b.get_upper_bound(), b.get_cumulative_count()))
+                                .collect(),
+                        });
+                    }
+                    MetricType::SUMMARY | MetricType::UNTYPED => {}
+                }
+            }
+        }
+
+        snapshot
+    }
+
+    /// The counter with the given name and labels, if any
+    pub fn counter(&self, name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+        find(&self.counters, name, labels)
+    }
+
+    /// The gauge with the given name and labels, if any
+    pub fn gauge(&self, name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+        find(&self.gauges, name, labels)
+    }
+}
+
+fn find(samples: &[Sample], name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+    samples
+        .iter()
+        .find(|sample| {
+            sample.name == name
+                && labels
+                    .iter()
+                    .all(|(k, v)| sample.labels.get(*k).map(String::as_str) == Some(*v))
+        })
+        .map(|sample| sample.value)
+}
+
+fn get_labels(mc: &proto::Metric) -> BTreeMap<String, String> {
+    mc.get_label()
+        .iter()
+        .map(|lp| (lp.get_name().to_string(), lp.get_value().to_string()))
+        .collect()
+}
@@ -22,6 +22,7 @@ use ibc_relayer_types::{
 use tendermint::Time;
 
 use crate::path_identifier::PathIdentifier;
+use crate::snapshot::TelemetrySnapshot;
 
 const EMPTY_BACKLOG_SYMBOL: u64 = 0;
 const BACKLOG_CAPACITY: usize = 1000;
@@ -121,6 +122,17 @@ pub struct TelemetryState {
     /// Number of times Forcerelay reconnected to the websocket endpoint, per chain
     ws_reconnect: Counter<u64>,
 
+    /// Number of panics of the threads bound to a chain, which are then restarted along
+    /// with the chain, per chain and thread
+    chain_thread_panics: Counter<u64>,
+
+    /// Number of panics of the background tasks, such as the workers, which are then
+    /// respawned, per task
+    task_panics: Counter<u64>,
+
+    /// Number of times a chain was marked failed after exhausting its restart attempts
+    chain_failures: Counter<u64>,
+
     /// How many IBC events did Forcerelay receive via the WebSocket subscription, per chain
     ws_events: Counter<u64>,
 
@@ -130,6 +142,9 @@ pub struct TelemetryState {
     /// The balance of each wallet Forcerelay uses per chain
     wallet_balance: ObservableGauge<f64>,
 
+    /// Whether the balance of each wallet is below the configured minimum, per account, denom and chain
+    wallet_low_balance: ObservableGauge<u64>,
+
     /// Indicates the latency for all transactions submitted to a specific chain,
     /// i.e. the difference between the moment when Forcerelay received a batch of events
     /// until the corresponding transaction(s) were submitted. Milliseconds.
@@ -192,6 +207,52 @@ pub struct TelemetryState {
 
     /// Sum of rewarded fees over the past FEE_LIFETIME seconds
     period_fees: ObservableGauge<u64>,
+
+    /// Number of blocks the CKB4IBC event monitor lags behind the tip, per chain and scanned script
+    ckb4ibc_scan_lag: ObservableGauge<u64>,
+
+    /// Number of blocks the CKB indexer lags behind the tip of the node, per chain
+    ckb4ibc_indexer_lag: ObservableGauge<u64>,
+
+    /// Number of live cells queries sent to a CKB node or indexer, per chain and RPC method
+    ckb_cell_queries: Counter<u64>,
+
+    /// Number of requests to a CKB node or indexer held back by the request limits, per chain and RPC method
+    ckb_rpc_throttled: Counter<u64>,
+
+    /// Time spent assembling and signing a CKB4IBC transaction, in milliseconds
+    ckb4ibc_tx_assembly_time: ObservableGauge<u64>,
+
+    /// Time between the submission of a batch of CKB4IBC transactions and the
+    /// confirmation of its transactions, in milliseconds
+    ckb4ibc_tx_confirmation_latency: ObservableGauge<u64>,
+
+    /// Fee paid by each confirmed CKB4IBC transaction, in shannons
+    ckb4ibc_tx_fee: ObservableGauge<u64>,
+
+    /// Number of lookups into the cache of IBC cells of a CKB4IBC chain, per cached
+    /// object and outcome
+    ckb4ibc_cache_lookups: Counter<u64>,
+
+    /// Number of messages found to be already relayed to a CKB4IBC chain by another
+    /// relayer, per chain and message type
+    ckb4ibc_externally_relayed: Counter<u64>,
+
+    /// Number of client updates skipped because the consensus state they carry was
+    /// already on a CKB4IBC chain, i.e. submitted by another relayer, per client
+    ckb4ibc_free_ride_client_updates: Counter<u64>,
+
+    /// Number of IBC transactions observed on a CKB4IBC chain, per author among
+    /// `self`, `known_peer` and `unknown`
+    ckb4ibc_tx_authors: Counter<u64>,
+
+    /// Number of queries of a CKB4IBC connection whose id exceeds the connections
+    /// recorded on chain, i.e. whose states need to be resynced, per connection
+    ckb4ibc_stale_connection_ids: Counter<u64>,
+
+    /// Number of times the requests of an Ethereum light client failed over to
+    /// another beacon endpoint, per endpoint failed over to
+    eth_beacon_failovers: Counter<u64>,
 }
 
 impl TelemetryState {
@@ -200,6 +261,11 @@ impl TelemetryState {
         self.exporter.registry().gather()
     }
 
+    /// Take a snapshot of the current value of the metrics
+    pub fn snapshot(&self) -> TelemetrySnapshot {
+        TelemetrySnapshot::from_families(&self.gather())
+    }
+
     pub fn init_worker_by_type(&self, worker_type: WorkerType) {
         self.worker(worker_type, 0);
     }
@@ -452,6 +518,36 @@ impl TelemetryState {
         self.ws_reconnect.add(&cx, 1, labels);
     }
 
+    /// Number of panics of the threads bound to a chain, per thread
+    pub fn chain_thread_panics(&self, chain_id: &ChainId, thread: &'static str) {
+        let cx = Context::current();
+
+        let labels = &[
+            KeyValue::new("chain", chain_id.to_string()),
+            KeyValue::new("thread", thread),
+        ];
+
+        self.chain_thread_panics.add(&cx, 1, labels);
+    }
+
+    /// Number of panics of the background tasks, per task
+    pub fn task_panics(&self, task: &'static str) {
+        let cx = Context::current();
+
+        let labels = &[KeyValue::new("task", task)];
+
+        self.task_panics.add(&cx, 1, labels);
+    }
+
+    /// Number of times a chain was marked failed after exhausting its restart attempts
+    pub fn chain_failures(&self, chain_id: &ChainId) {
+        let cx = Context::current();
+
+        let labels = &[KeyValue::new("chain", chain_id.to_string())];
+
+        self.chain_failures.add(&cx, 1, labels);
+    }
+
     /// How many IBC events did Forcerelay receive via the WebSocket subscription, per chain
     pub fn ws_events(&self, chain_id: &ChainId, count: u64) {
         let cx = Context::current();
@@ -484,6 +580,163 @@ impl TelemetryState {
         self.wallet_balance.observe(&cx, amount, labels);
     }
 
+    /// Whether the balance of a wallet is below the minimum configured for its chain,
+    /// `1` if it is, `0` otherwise
+    pub fn wallet_low_balance(&self, chain_id: &ChainId, account: &str, denom: &str, low: bool) {
+        let cx = Context::current();
+
+        let labels = &[
+            KeyValue::new("chain", chain_id.to_string()),
+            KeyValue::new("account", account.to_string()),
+            KeyValue::new("denom", denom.to_string()),
+        ];
+
+        self.wallet_low_balance.observe(&cx, u64::from(low), labels);
+    }
+
+    /// Number of blocks between the tip of a CKB4IBC chain and the last cell
+    /// scanned by its event monitor, per scanned script
+    pub fn ckb4ibc_scan_lag(&self, chain_id: &ChainId, script: &str, lag: u64) {
+        let cx = Context::current();
+
+        let labels = &[
+            KeyValue::new("chain", chain_id.to_string()),
+            KeyValue::new("script", script.to_string()),
+        ];
+
+        self.ckb4ibc_scan_lag.observe(&cx, lag, labels);
+    }
+
+    /// Number of blocks the CKB indexer of a CKB4IBC chain lags behind the node
+    pub fn ckb4ibc_indexer_lag(&self, chain_id: &ChainId, lag: u64) {
+        let cx = Context::current();
+
+        let labels = &[KeyValue::new("chain", chain_id.to_string())];
+
+        self.ckb4ibc_indexer_lag.observe(&cx, lag, labels);
+    }
+
+    /// Number of live cells queries sent to a CKB node or indexer, per RPC method
+    pub fn ckb_cell_queries(&self, chain_id: &ChainId, method: &'static str) {
+        let cx = Context::current();
+
+        let labels = &[
+            KeyValue::new("chain", chain_id.to_string()),
+            KeyValue::new("method", method),
+        ];
+
+        self.ckb_cell_queries.add(&cx, 1, labels);
+    }
+
+    /// Number of requests to a CKB node or indexer held back by the request limits, per RPC method
+    pub fn ckb_rpc_throttled(&self, chain_id: &ChainId, method: &'static str) {
+        let cx = Context::current();
+
+        let labels = &[
+            KeyValue::new("chain", chain_id.to_string()),
+            KeyValue::new("method", method),
+        ];
+
+        self.ckb_rpc_throttled.add(&cx, 1, labels);
+    }
+
+    /// Time spent assembling and signing a CKB4IBC transaction, in milliseconds
+    pub fn ckb4ibc_tx_assembly_time(&self, chain_id: &ChainId, elapsed_ms: u64) {
+        let cx = Context::current();
+
+        let labels = &[KeyValue::new("chain", chain_id.to_string())];
+
+        self.ckb4ibc_tx_assembly_time
+            .observe(&cx, elapsed_ms, labels);
+    }
+
+    /// Latency and fee of a confirmed CKB4IBC transaction, the fee being in shannons
+    pub fn ckb4ibc_tx_confirmed(&self, chain_id: &ChainId, latency_ms: u64, fee: u64) {
+        let cx = Context::current();
+
+        let labels = &[KeyValue::new("chain", chain_id.to_string())];
+
+        self.ckb4ibc_tx_confirmation_latency
+            .observe(&cx, latency_ms, labels);
+        self.ckb4ibc_tx_fee.observe(&cx, fee, labels);
+    }
+
+    /// Lookup into the cache of IBC cells of a CKB4IBC chain, `object` being the kind
+    /// of the cached cell. The hit ratio is the share of lookups with `hit="true"`.
+    pub fn ckb4ibc_cache_lookup(&self, chain_id: &ChainId, object: &'static str, hit: bool) {
+        let cx = Context::current();
+
+        let labels = &[
+            KeyValue::new("chain", chain_id.to_string()),
+            KeyValue::new("object", object),
+            KeyValue::new("hit", hit.to_string()),
+        ];
+
+        self.ckb4ibc_cache_lookups.add(&cx, 1, labels);
+    }
+
+    /// Message which another relayer submitted to a CKB4IBC chain before this one
+    pub fn ckb4ibc_externally_relayed(&self, chain_id: &ChainId, msg_type: &str) {
+        let cx = Context::current();
+
+        let labels = &[
+            KeyValue::new("chain", chain_id.to_string()),
+            KeyValue::new("msg_type", msg_type.to_string()),
+        ];
+
+        self.ckb4ibc_externally_relayed.add(&cx, 1, labels);
+    }
+
+    /// Client update skipped because another relayer submitted its consensus state to a
+    /// CKB4IBC chain already
+    pub fn ckb4ibc_free_ride_client_update(&self, chain_id: &ChainId, client_id: &str) {
+        let cx = Context::current();
+
+        let labels = &[
+            KeyValue::new("chain", chain_id.to_string()),
+            KeyValue::new("client", client_id.to_string()),
+        ];
+
+        self.ckb4ibc_free_ride_client_updates.add(&cx, 1, labels);
+    }
+
+    /// IBC transaction observed on a CKB4IBC chain, signed by `author`
+    pub fn ckb4ibc_tx_author(&self, chain_id: &ChainId, author: &str) {
+        let cx = Context::current();
+
+        let labels = &[
+            KeyValue::new("chain", chain_id.to_string()),
+            KeyValue::new("author", author.to_string()),
+        ];
+
+        self.ckb4ibc_tx_authors.add(&cx, 1, labels);
+    }
+
+    /// Connection queried on a CKB4IBC chain whose id exceeds the connections recorded
+    /// in its connections cell
+    pub fn ckb4ibc_stale_connection_id(&self, chain_id: &ChainId, connection_id: &str) {
+        let cx = Context::current();
+
+        let labels = &[
+            KeyValue::new("chain", chain_id.to_string()),
+            KeyValue::new("connection", connection_id.to_string()),
+        ];
+
+        self.ckb4ibc_stale_connection_ids.add(&cx, 1, labels);
+    }
+
+    /// Requests of the Ethereum light client failed over to the beacon `endpoint`
+    pub fn eth_beacon_failover(&self, chain_id: &ChainId, endpoint: &str) {
+        let cx = Context::current();
+
+        let labels = &[
+            KeyValue::new("chain", chain_id.to_string()),
+            KeyValue::new("endpoint", endpoint.to_string()),
+        ];
+
+        self.eth_beacon_failovers.add(&cx, 1, labels);
+    }
+
     pub fn received_event_batch(&self, tracking_id: impl ToString) {
         self.in_flight_events
             .insert(tracking_id.to_string(), Instant::now());
@@ -860,9 +1113,21 @@ impl AggregatorSelector for CustomAggregatorSelector {
     fn aggregator_for(&self, descriptor: &Descriptor) -> Option<Arc<dyn Aggregator + Send + Sync>> {
         match descriptor.name() {
             "wallet_balance" => Some(Arc::new(last_value())),
+            "wallet_low_balance" => Some(Arc::new(last_value())),
             "backlog_oldest_sequence" => Some(Arc::new(last_value())),
             "backlog_oldest_timestamp" => Some(Arc::new(last_value())),
             "backlog_size" => Some(Arc::new(last_value())),
+            "ckb4ibc_scan_lag" => Some(Arc::new(last_value())),
+            "ckb4ibc_indexer_lag" => Some(Arc::new(last_value())),
+            "ckb4ibc_tx_assembly_time" => Some(Arc::new(histogram(&[
+                10.0, 50.0, 100.0, 500.0, 1000.0, 5000.0,
+            ]))),
+            "ckb4ibc_tx_confirmation_latency" => Some(Arc::new(histogram(&[
+                1000.0, 5000.0, 10000.0, 20000.0, 40000.0, 60000.0,
+            ]))),
+            "ckb4ibc_tx_fee" => Some(Arc::new(histogram(&[
+                1000.0, 5000.0, 10000.0, 50000.0, 100000.0, 1000000.0,
+            ]))),
             // Prometheus' supports only collector for histogram, sum, and last value aggregators.
             // https://docs.rs/opentelemetry-prometheus/0.11.0/src/opentelemetry_prometheus/lib.rs.html#411-418
             // TODO: Once quantile sketches are supported, replace histograms with that.
@@ -946,6 +1211,21 @@ impl Default for TelemetryState {
                 .with_description("Number of times Forcerelay reconnected to the websocket endpoint")
                 .init(),
 
+            chain_thread_panics: meter
+                .u64_counter("chain_thread_panics")
+                .with_description("Number of panics of the threads bound to a chain, per thread")
+                .init(),
+
+            task_panics: meter
+                .u64_counter("task_panics")
+                .with_description("Number of panics of the background tasks, such as the workers, per task")
+                .init(),
+
+            chain_failures: meter
+                .u64_counter("chain_failures")
+                .with_description("Number of times a chain was marked failed after exhausting its restart attempts")
+                .init(),
+
             ws_events: meter
                 .u64_counter("ws_events")
                 .with_description("How many IBC events did Forcerelay receive via the websocket subscription")
@@ -961,6 +1241,11 @@ impl Default for TelemetryState {
                 .with_description("The balance of each wallet Forcerelay uses per chain. Please note that when converting the balance to f64 a loss in precision might be introduced in the displayed value")
                 .init(),
 
+            wallet_low_balance: meter
+                .u64_observable_gauge("wallet_low_balance")
+                .with_description("Whether the balance of each wallet is below the minimum configured for its chain, 1 if it is and 0 otherwise")
+                .init(),
+
             send_packet_events: meter
                 .u64_counter("send_packet_events")
                 .with_description("Number of SendPacket events received")
@@ -1038,6 +1323,76 @@ impl Default for TelemetryState {
                 .u64_observable_gauge("ics29_period_fees")
                 .with_description("Amount of ICS29 fees rewarded over the past 7 days")
                 .init(),
+
+            ckb4ibc_scan_lag: meter
+                .u64_observable_gauge("ckb4ibc_scan_lag")
+                .with_description("Number of blocks the CKB4IBC event monitor lags behind the tip, per scanned script")
+                .init(),
+
+            ckb4ibc_indexer_lag: meter
+                .u64_observable_gauge("ckb4ibc_indexer_lag")
+                .with_description("Number of blocks the CKB indexer lags behind the tip of the node")
+                .init(),
+
+            ckb_cell_queries: meter
+                .u64_counter("ckb_cell_queries")
+                .with_description("Number of live cells queries sent to a CKB node or indexer, per RPC method")
+                .init(),
+
+            ckb_rpc_throttled: meter
+                .u64_counter("ckb_rpc_throttled")
+                .with_description("Number of requests to a CKB node or indexer held back by the request limits, per RPC method")
+                .init(),
+
+            ckb4ibc_tx_assembly_time: meter
+                .u64_observable_gauge("ckb4ibc_tx_assembly_time")
+                .with_unit(Unit::new("milliseconds"))
+                .with_description("Time spent assembling and signing a CKB4IBC transaction. Milliseconds.")
+                .init(),
+
+            ckb4ibc_tx_confirmation_latency: meter
+                .u64_observable_gauge("ckb4ibc_tx_confirmation_latency")
+                .with_unit(Unit::new("milliseconds"))
+                .with_description("The latency of the confirmed CKB4IBC transactions, i.e. the difference between \
+                    the moment when Forcerelay submitted a batch of transactions and when all of them were \
+                    resolved. Milliseconds.")
+                .init(),
+
+            ckb4ibc_tx_fee: meter
+                .u64_observable_gauge("ckb4ibc_tx_fee")
+                .with_unit(Unit::new("shannons"))
+                .with_description("Fee paid by each confirmed CKB4IBC transaction. Shannons.")
+                .init(),
+
+            ckb4ibc_cache_lookups: meter
+                .u64_counter("ckb4ibc_cache_lookups")
+                .with_description("Number of lookups into the cache of IBC cells of a CKB4IBC chain, per cached object and whether it was a hit")
+                .init(),
+
+            ckb4ibc_externally_relayed: meter
+                .u64_counter("ckb4ibc_externally_relayed")
+                .with_description("Number of messages already relayed to a CKB4IBC chain by another relayer, per message type")
+                .init(),
+
+            ckb4ibc_free_ride_client_updates: meter
+                .u64_counter("ckb4ibc_free_ride_client_updates")
+                .with_description("Number of client updates skipped as their consensus states were already submitted to a CKB4IBC chain by another relayer, per client")
+                .init(),
+
+            ckb4ibc_tx_authors: meter
+                .u64_counter("ckb4ibc_tx_authors")
+                .with_description("Number of IBC transactions observed on a CKB4IBC chain, per author: self, known_peer or unknown")
+                .init(),
+
+            ckb4ibc_stale_connection_ids: meter
+                .u64_counter("ckb4ibc_stale_connection_ids")
+                .with_description("Number of queries of a CKB4IBC connection whose id exceeds the connections recorded on chain, per connection")
+                .init(),
+
+            eth_beacon_failovers: meter
+                .u64_counter("eth_beacon_failovers")
+                .with_description("Number of times the requests of an Ethereum light client failed over to another beacon endpoint")
+                .init(),
         }
     }
 }
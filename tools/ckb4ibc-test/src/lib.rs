@@ -6,9 +6,9 @@ mod rpc_client;
 mod tests {
     use super::rpc_client::RpcClient;
     use ckb_hash::blake2b_256;
-    use ckb_ics_axon::handler::{IbcChannel, IbcConnections};
-    use ckb_ics_axon::object::State;
-    use ckb_ics_axon::ChannelArgs;
+    use ckb_ics_axon::handler::{IbcChannel, IbcConnections, IbcPacket, PacketStatus};
+    use ckb_ics_axon::object::{Ordering, State};
+    use ckb_ics_axon::{ChannelArgs, PacketArgs};
     use ckb_jsonrpc_types::TransactionView;
     // use ckb_sdk::constants::TYPE_ID_CODE_HASH;
     use ckb_sdk::rpc::ckb_light_client::{ScriptType, SearchKey};
@@ -19,15 +19,16 @@ mod tests {
     use futures::TryFutureExt;
     use relayer::chain::ckb::prelude::CkbReader;
     use relayer::chain::ckb4ibc::extractor::{
-        extract_channel_end_from_tx, extract_connections_from_tx,
+        extract_channel_end_from_tx, extract_connections_from_tx, extract_ibc_packet_from_tx,
     };
+    use relayer::chain::ckb4ibc::utils::get_script_hash;
+    use relayer::config::rpc_url::RpcUrl;
     use secp256k1::{Secp256k1, SecretKey};
     use std::process::{Child, Command, Stdio};
     use std::str::FromStr;
     use std::thread;
     use std::time;
     use std::time::Duration;
-    use tendermint_rpc::Url;
 
     const CONNECTION_CODE_HASH: H256 =
         h256!("0xcf6e0c0148123081af1deda0ef162d39cfdfe1ea6565d3689009c1f3562a5e82");
@@ -35,6 +36,8 @@ mod tests {
         h256!("0x9ea73e5003f580eb4f380944b1de0711c6b5a4bb96c6f9bf8186203b7c684606");
     const CLIENT_TYPE_ARGS: H256 =
         h256!("0x29866e133f707f070459b905065294ab1a7b70bea200952a080f849319ae6202");
+    const PACKET_TYPE_ARGS: H256 =
+        h256!("0xad8bca6ff76ad676bb7eb35882faf259cb6ff50be8ce9c0b9d6f51728ec54fab");
     #[test]
     fn test_config() {
         use relayer::config::load;
@@ -46,6 +49,100 @@ mod tests {
     #[ignore]
     #[test]
     fn integration_test() {
+        let (port_id_a, port_id_b) = prepare_connection();
+
+        let a_channel = create_channel(port_id_a, port_id_b, "unordered");
+        if !matches!(a_channel.order, Ordering::Unordered) {
+            panic!("create unordered channel failed")
+        }
+    }
+
+    #[ignore]
+    #[test]
+    fn ordered_channel_integration_test() {
+        let (port_id_a, port_id_b) = prepare_connection();
+
+        let a_channel = create_channel(port_id_a, port_id_b, "ordered");
+        let b_channel = fetch_ibc_channel_cell(8214, port_id_b.into(), true);
+        if !matches!(a_channel.order, Ordering::Ordered)
+            || !matches!(b_channel.order, Ordering::Ordered)
+        {
+            panic!("create ordered channel failed")
+        }
+        if a_channel.sequence.next_recv_packet != b_channel.sequence.next_recv_packet {
+            panic!("sequences of the ordered channel ends mismatch")
+        }
+
+        // two packets are received in the order of their sequences
+        let first = a_channel.sequence.next_send_packet;
+        let mut relayer = start_relayer();
+        send_transfer(port_id_a, 600);
+        send_transfer(port_id_a, 600);
+        for sequence in [first, first + 1] {
+            let status = wait_for_packet_status(port_id_a, sequence, &PacketStatus::Ack);
+            if status != Some(PacketStatus::Ack) {
+                let _ = relayer.kill();
+                panic!("packet {sequence} was not acknowledged, its status is {status:?}")
+            }
+        }
+        let _ = relayer.kill();
+        let a_channel = fetch_ibc_channel_cell(8114, port_id_a.into(), true);
+        let b_channel = fetch_ibc_channel_cell(8214, port_id_b.into(), true);
+        if a_channel.sequence.next_send_packet != first + 2
+            || b_channel.sequence.next_recv_packet != first + 2
+        {
+            panic!(
+                "expected the next sequence to be {}, found {:?} on chain a and {:?} on chain b",
+                first + 2,
+                a_channel.sequence,
+                b_channel.sequence
+            )
+        }
+
+        // the timeout of a packet submitted by the relayer closes the ordered channel
+        send_transfer(port_id_a, 10);
+        thread::sleep(Duration::from_secs(30));
+        let mut relayer = start_relayer();
+        let mut closed = false;
+        for _ in 0..60 {
+            thread::sleep(Duration::from_secs(5));
+            closed = try_fetch_ibc_channel_cell(8114, port_id_a.into(), false)
+                .map_or(false, |channel| channel.state == State::Closed);
+            if closed {
+                break;
+            }
+        }
+        let _ = relayer.kill();
+        if !closed {
+            panic!(
+                "ordered channel was not closed by the timeout of packet {}",
+                first + 2
+            )
+        }
+    }
+
+    #[ignore]
+    #[test]
+    fn packet_relay_integration_test() {
+        let (port_id_a, port_id_b) = prepare_connection();
+
+        let a_channel = create_channel(port_id_a, port_id_b, "unordered");
+        let sequence = a_channel.sequence.next_send_packet;
+
+        let mut relayer = start_relayer();
+        send_transfer(port_id_a, 600);
+
+        // wait for the packet to be received on chain b and acknowledged back on chain a
+        let status = wait_for_packet_status(port_id_a, sequence, &PacketStatus::Ack);
+        let _ = relayer.kill();
+        if status != Some(PacketStatus::Ack) {
+            panic!("packet {sequence} was not acknowledged, its status is {status:?}")
+        }
+    }
+
+    // Start both chains and open a connection between them, returning the ports of
+    // the users on each side.
+    fn prepare_connection() -> (H256, H256) {
         prepare_ckb_chain("ckb-dev-a", 8114);
         prepare_ckb_chain("ckb-dev-b", 8214);
 
@@ -86,6 +183,11 @@ mod tests {
         let user_b_public_key = user_b_private_key.public_key(&Secp256k1::new()).serialize();
         let port_id_b = H256::from(blake2b_256(&user_b_public_key[..]));
 
+        (port_id_a, port_id_b)
+    }
+
+    // Open a channel with the given ordering and return its end on chain a.
+    fn create_channel(port_id_a: H256, port_id_b: H256, order: &str) -> IbcChannel {
         let mut create_channel = Command::new("cargo")
             .arg("run")
             .arg("--")
@@ -101,6 +203,8 @@ mod tests {
             .arg("ckb4ibc-0")
             .arg("--a-connection")
             .arg("ckb4ibc-connection-0")
+            .arg("--order")
+            .arg(order)
             .current_dir("../../")
             .spawn()
             .unwrap();
@@ -108,17 +212,82 @@ mod tests {
         create_channel.wait().unwrap();
         let three_secs = time::Duration::from_secs(3);
         thread::sleep(three_secs);
-        let a_channel = fetch_ibc_channel_cell(8114, port_id_a.into());
+        let a_channel = fetch_ibc_channel_cell(8114, port_id_a.into(), true);
         println!("a_channel: {:?}", a_channel);
         let three_secs = time::Duration::from_secs(3);
         thread::sleep(three_secs);
-        let b_channel = fetch_ibc_channel_cell(8214, port_id_b.into());
+        let b_channel = fetch_ibc_channel_cell(8214, port_id_b.into(), true);
         println!("b_channel: {:?}", b_channel);
         if !check_channel(&a_channel) || !check_channel(&b_channel) {
             panic!("create channel failed")
         }
 
         let _ = create_channel.kill();
+        a_channel
+    }
+
+    // Run the relayer in the background, relaying the packets of the open channels.
+    fn start_relayer() -> Child {
+        Command::new("cargo")
+            .arg("run")
+            .arg("--")
+            .arg("--config")
+            .arg("./tools/ckb4ibc-test/config.toml")
+            .arg("start")
+            .current_dir("../../")
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap()
+    }
+
+    // Status of the packet of the given sequence sent from the port of user a, once it
+    // reaches `expected` or after waiting for five minutes.
+    fn wait_for_packet_status(
+        port_id_a: H256,
+        sequence: u16,
+        expected: &PacketStatus,
+    ) -> Option<PacketStatus> {
+        let mut status = None;
+        for _ in 0..60 {
+            thread::sleep(Duration::from_secs(5));
+            status = fetch_ibc_packet(8114, port_id_a.into(), sequence).map(|p| p.status);
+            if status.as_ref() == Some(expected) {
+                break;
+            }
+        }
+        status
+    }
+
+    // Send an ICS-20 packet from the port of user a over the first channel of chain a,
+    // timing out after the given number of seconds.
+    fn send_transfer(port_id_a: H256, timeout_seconds: u64) {
+        let mut transfer = Command::new("cargo")
+            .arg("run")
+            .arg("--")
+            .arg("--config")
+            .arg("./tools/ckb4ibc-test/config.toml")
+            .arg("tx")
+            .arg("ft-transfer")
+            .arg("--src-chain")
+            .arg("ckb4ibc-0")
+            .arg("--dst-chain")
+            .arg("ckb4ibc-1")
+            .arg("--src-port")
+            .arg(format!("{:x}", port_id_a))
+            .arg("--src-channel")
+            .arg("channel-0")
+            .arg("--amount")
+            .arg("1")
+            .arg("--timeout-seconds")
+            .arg(timeout_seconds.to_string())
+            .current_dir("../../")
+            .spawn()
+            .unwrap();
+
+        if !transfer.wait().unwrap().success() {
+            panic!("send packet failed");
+        }
+        println!("send packet success");
     }
 
     fn check_channel(channel: &IbcChannel) -> bool {
@@ -253,7 +422,7 @@ mod tests {
 
     fn fetch_ibc_connections(port: u32) -> IbcConnections {
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let url = Url::from_str(&format!("http://127.0.0.1:{}", port)).unwrap();
+        let url = RpcUrl::from_str(&format!("http://127.0.0.1:{}", port)).unwrap();
         let client = RpcClient::new(&url, &url);
         let resp = client
             .fetch_live_cells(
@@ -290,9 +459,21 @@ mod tests {
         ibc_connection
     }
 
-    fn fetch_ibc_channel_cell(port: u32, port_id: [u8; 32]) -> IbcChannel {
+    fn fetch_ibc_channel_cell(port: u32, port_id: [u8; 32], open: bool) -> IbcChannel {
+        match try_fetch_ibc_channel_cell(port, port_id, open) {
+            Ok(r) => r,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    // The first channel cell of the port, among the open or the unopened ones.
+    fn try_fetch_ibc_channel_cell(
+        port: u32,
+        port_id: [u8; 32],
+        open: bool,
+    ) -> Result<IbcChannel, relayer::error::Error> {
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let url = Url::from_str(&format!("http://127.0.0.1:{}", port)).unwrap();
+        let url = RpcUrl::from_str(&format!("http://127.0.0.1:{}", port)).unwrap();
         let rpc_client = RpcClient::new(&url, &url);
         let resp = rpc_client
             .fetch_live_cells(
@@ -302,7 +483,7 @@ mod tests {
                         .args(
                             ChannelArgs {
                                 client_id: CLIENT_TYPE_ARGS.into(),
-                                open: true,
+                                open,
                                 channel_id: 0,
                                 port_id,
                             }
@@ -321,7 +502,9 @@ mod tests {
                 None,
             )
             .and_then(|resp| async move {
-                let cell = resp.objects.first().unwrap();
+                let Some(cell) = resp.objects.first() else {
+                    return Err(relayer::error::Error::query("no channel cell".to_owned()));
+                };
                 let tx_hash = &cell.out_point.tx_hash;
                 let tx_resp = rpc_client
                     .get_transaction(tx_hash)
@@ -341,6 +524,61 @@ mod tests {
                 let (_, channel_end) = extract_channel_end_from_tx(tx)?;
                 Ok(channel_end)
             });
+        rt.block_on(resp)
+    }
+
+    // The packet cell of the given sequence sent from the port on the first channel,
+    // or `None` if there is none yet.
+    fn fetch_ibc_packet(port: u32, port_id: [u8; 32], sequence: u16) -> Option<IbcPacket> {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let url = RpcUrl::from_str(&format!("http://127.0.0.1:{}", port)).unwrap();
+        let rpc_client = RpcClient::new(&url, &url);
+        let resp = rpc_client
+            .fetch_live_cells(
+                SearchKey {
+                    script: Script::new_builder()
+                        .code_hash(get_script_hash(&PACKET_TYPE_ARGS))
+                        .args(
+                            PacketArgs {
+                                channel_id: 0,
+                                port_id,
+                                sequence,
+                                owner: [0; 32],
+                            }
+                            .get_search_args()
+                            .pack(),
+                        )
+                        .hash_type(ScriptHashType::Type.into())
+                        .build()
+                        .into(),
+                    script_type: ScriptType::Lock,
+                    filter: None,
+                    with_data: None,
+                    group_by_transaction: None,
+                },
+                1,
+                None,
+            )
+            .and_then(|resp| async move {
+                let Some(cell) = resp.objects.first() else {
+                    return Ok(None);
+                };
+                let tx_resp = rpc_client
+                    .get_transaction(&cell.out_point.tx_hash)
+                    .await?
+                    .unwrap()
+                    .transaction
+                    .unwrap();
+                let tx = match tx_resp.inner {
+                    ckb_jsonrpc_types::Either::Left(r) => r,
+                    ckb_jsonrpc_types::Either::Right(json_bytes) => {
+                        let bytes = json_bytes.as_bytes();
+                        let tx: TransactionView = serde_json::from_slice(bytes).unwrap();
+                        tx
+                    }
+                };
+                Ok(Some(extract_ibc_packet_from_tx(tx)?))
+            });
         match rt.block_on(resp) {
             Ok(r) => r,
             Err(e) => panic!("{e}"),
@@ -1,10 +1,11 @@
 #![allow(dead_code)]
 
 use ckb_jsonrpc_types::{
-    BlockNumber, BlockView, CellWithStatus, ChainInfo, HeaderView, JsonBytes, OutPoint,
-    OutputsValidator, RawTxPool, Transaction, TransactionWithStatusResponse, TxPoolInfo, Uint32,
+    BlockNumber, BlockView, CellWithStatus, ChainInfo, DryRunResult, HeaderView, JsonBytes,
+    OutPoint, OutputsValidator, RawTxPool, Transaction, TransactionWithStatusResponse, TxPoolInfo,
+    Uint32,
 };
-use ckb_sdk::rpc::ckb_indexer::{Cell, Order, Pagination, SearchKey};
+use ckb_sdk::rpc::ckb_indexer::{Cell, Order, Pagination, SearchKey, Tip};
 use ckb_types::H256;
 use futures::FutureExt;
 use reqwest::Client;
@@ -163,6 +164,10 @@ impl CkbReader for RpcClient {
         .boxed()
     }
 
+    fn get_indexer_tip(&self) -> Rpc<Option<Tip>> {
+        jsonrpc!("get_indexer_tip", Target::Indexer, self, Option<Tip>).boxed()
+    }
+
     fn get_raw_tx_pool(&self, verbose: bool) -> Rpc<RawTxPool> {
         jsonrpc!("get_raw_tx_pool", Target::CKB, self, RawTxPool, verbose).boxed()
     }
@@ -170,6 +175,10 @@ impl CkbReader for RpcClient {
     fn tx_pool_info(&self) -> Rpc<TxPoolInfo> {
         jsonrpc!("tx_pool_info", Target::CKB, self, TxPoolInfo).boxed()
     }
+
+    fn estimate_cycles(&self, tx: &Transaction) -> Rpc<DryRunResult> {
+        jsonrpc!("estimate_cycles", Target::CKB, self, DryRunResult, tx).boxed()
+    }
 }
 
 impl CkbWriter for RpcClient {
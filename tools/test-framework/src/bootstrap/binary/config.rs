@@ -1,6 +1,6 @@
 use ibc_relayer::config::{
-    cosmos::ChainConfig as CosmosChainConfig, Config, GlobalConfig, ModeConfig, RestConfig,
-    TelemetryConfig,
+    cosmos::ChainConfig as CosmosChainConfig, path::PathConfig, Config, GlobalConfig, ModeConfig,
+    RestConfig, TelemetryConfig,
 };
 use serde_derive::{Deserialize, Serialize};
 
@@ -20,6 +20,8 @@ pub struct CosmosConfig {
     pub telemetry: TelemetryConfig,
     #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
     pub chains: Vec<CosmosChainConfig>,
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<PathConfig>,
 }
 
 impl From<Config> for CosmosConfig {
@@ -34,6 +36,7 @@ impl From<Config> for CosmosConfig {
                 .into_iter()
                 .map(|c| c.downcast_cosmos())
                 .collect(),
+            paths: value.paths,
         }
     }
 }
@@ -20,6 +20,8 @@
    is still a [`ChainHandle`].
 */
 
+use std::collections::BTreeMap;
+
 use crossbeam_channel as channel;
 use tracing::Span;
 
@@ -27,8 +29,15 @@ use ibc_proto::ibc::apps::fee::v1::{
     QueryIncentivizedPacketRequest, QueryIncentivizedPacketResponse,
 };
 use ibc_relayer::account::Balance;
-use ibc_relayer::chain::client::ClientSettings;
-use ibc_relayer::chain::endpoint::{ChainStatus, HealthCheck};
+use ibc_relayer::audit::ConfigDrift;
+use ibc_relayer::chain::ckb::client_cells::ClientCells;
+use ibc_relayer::chain::ckb::prune::ClientsPruning;
+use ibc_relayer::chain::ckb4ibc::cells::{IbcCell, IbcCellKind};
+use ibc_relayer::chain::ckb4ibc::footprint::StorageFootprint;
+use ibc_relayer::chain::ckb4ibc::history::IbcStateAt;
+use ibc_relayer::chain::ckb4ibc::packet_size::OversizedPacket;
+use ibc_relayer::chain::client::{ClientSettings, DerivedClientParams};
+use ibc_relayer::chain::endpoint::{ChainCapabilities, ChainStatus, HealthCheck};
 use ibc_relayer::chain::handle::{ChainHandle, ChainRequest, Subscription};
 use ibc_relayer::chain::requests::*;
 use ibc_relayer::chain::tracking::TrackedMsgs;
@@ -42,6 +51,7 @@ use ibc_relayer::event::IbcEventWithHeight;
 use ibc_relayer::keyring::AnySigningKeyPair;
 use ibc_relayer::light_client::AnyHeader;
 use ibc_relayer::misbehaviour::MisbehaviourEvidence;
+use ibc_relayer::progress::RelayStatus;
 use ibc_relayer_types::applications::ics31_icq::response::CrossChainQueryResponse;
 use ibc_relayer_types::core::ics02_client::events::UpdateClient;
 use ibc_relayer_types::core::ics03_connection::connection::ConnectionEnd;
@@ -91,6 +101,50 @@ where
         self.value().subscribe()
     }
 
+    fn invalidate_cache(&self) -> Result<(), Error> {
+        self.value().invalidate_cache()
+    }
+
+    fn query_storage_footprint(&self) -> Result<StorageFootprint, Error> {
+        self.value().query_storage_footprint()
+    }
+
+    fn query_oversized_packets(&self) -> Result<Vec<OversizedPacket>, Error> {
+        self.value().query_oversized_packets()
+    }
+
+    fn query_ibc_state_at(&self, block_number: u64) -> Result<IbcStateAt, Error> {
+        self.value().query_ibc_state_at(block_number)
+    }
+
+    fn query_ibc_cells(&self, kind: Option<IbcCellKind>) -> Result<Vec<IbcCell>, Error> {
+        self.value().query_ibc_cells(kind)
+    }
+
+    fn audit_config(&self) -> Result<Vec<ConfigDrift>, Error> {
+        self.value().audit_config()
+    }
+
+    fn query_client_params(&self) -> Result<DerivedClientParams, Error> {
+        self.value().query_client_params()
+    }
+
+    fn prune_obsolete_clients(&self, dry_run: bool) -> Result<ClientsPruning, Error> {
+        self.value().prune_obsolete_clients(dry_run)
+    }
+
+    fn query_light_client_cells(&self) -> Result<ClientCells, Error> {
+        self.value().query_light_client_cells()
+    }
+
+    fn query_relay_status(&self) -> Result<RelayStatus, Error> {
+        self.value().query_relay_status()
+    }
+
+    fn query_contract_outpoints(&self) -> Result<BTreeMap<String, String>, Error> {
+        self.value().query_contract_outpoints()
+    }
+
     fn send_messages_and_wait_commit(
         &self,
         tracked_msgs: TrackedMsgs,
@@ -125,6 +179,10 @@ where
         self.value().ibc_version()
     }
 
+    fn capabilities(&self) -> Result<ChainCapabilities, Error> {
+        self.value().capabilities()
+    }
+
     fn query_application_status(&self) -> Result<ChainStatus, Error> {
         self.value().query_application_status()
     }